@@ -0,0 +1,352 @@
+//! First-party product analytics. The frontend reports events through
+//! `track_event`; each one must match a schema below, a fixed share of users
+//! is sampled, and only the newest events within the retention window are
+//! kept. Events store no principal, just the hour they happened in.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::quarantine;
+
+/// Hard cap on stored events, whatever the retention window
+const MAX_EVENTS: u64 = 200_000;
+const MAX_PROPS: usize = 10;
+const MAX_TEXT_LEN: usize = 200;
+/// Expired events dropped per tracked event, so trimming stays cheap
+const TRIM_BATCH: usize = 20;
+const HOUR_NANOS: u64 = 60 * 60 * 1_000_000_000;
+const DAY_NANOS: u64 = 24 * HOUR_NANOS;
+/// Recorded by the backend itself, never accepted from track_event
+const SERVER_EVENTS: [&str; 1] = ["repeat_booking"];
+
+// ============================================================================
+// Schemas
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PropType {
+    Text,
+    Number,
+    Bool,
+}
+
+/// (property, type, required)
+type PropSpec = (&'static str, PropType, bool);
+
+/// Every event that may be recorded; anything else is rejected
+const SCHEMAS: &[(&str, &[PropSpec])] = &[
+    ("page_view", &[("path", PropType::Text, true)]),
+    ("booking_form_opened", &[("availability_id", PropType::Text, true)]),
+    ("slot_selected", &[
+        ("availability_id", PropType::Text, true),
+        ("duration_minutes", PropType::Number, true),
+    ]),
+    ("booking_completed", &[
+        ("availability_id", PropType::Text, true),
+        ("paid", PropType::Bool, true),
+    ]),
+    ("calendar_connected", &[("provider", PropType::Text, true)]),
+    ("link_copied", &[("kind", PropType::Text, true)]),
+    ("repeat_booking", &[("availability_id", PropType::Text, true)]),
+    ("onboarding_step", &[
+        ("step", PropType::Number, true),
+        ("skipped", PropType::Bool, false),
+    ]),
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PropValue {
+    Text(String),
+    Number(i64),
+    Bool(bool),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct EventProp {
+    pub key: String,
+    pub value: PropValue,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct TrackedEvent {
+    name: String,
+    props: Vec<EventProp>,
+    hour: u64,          // Nanoseconds, rounded down to the hour
+    sample_percent: u8, // Share of users sampled when it was recorded
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AnalyticsConfig {
+    pub sample_percent: u8, // 0-100; users outside the sample are dropped
+    pub retention_days: u32,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self { sample_percent: 100, retention_days: 90 }
+    }
+}
+
+/// Events of one name on one day. `estimated_total` scales each event by
+/// its sampling rate.
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct EventCount {
+    pub name: String,
+    pub day: u64,           // Nanoseconds at the start of the UTC day
+    pub count: u64,
+    pub estimated_total: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for TrackedEvent {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TrackedEvent").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AnalyticsConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "AnalyticsConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static ANALYTICS_CONFIG: RefCell<StableCell<AnalyticsConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(70))),
+            AnalyticsConfig::default(),
+        ).expect("Failed to init analytics config")
+    );
+
+    // Sequence number -> event, oldest first
+    static ANALYTICS_EVENTS: RefCell<StableBTreeMap<u64, TrackedEvent, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(71)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear_events() {
+    ANALYTICS_EVENTS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn validate(name: &str, props: &[EventProp]) -> Result<(), String> {
+    let (_, specs) = SCHEMAS.iter()
+        .find(|(schema, _)| *schema == name)
+        .ok_or_else(|| format!("Unknown event: {}", name))?;
+    if props.len() > MAX_PROPS {
+        return Err(format!("At most {} properties per event", MAX_PROPS));
+    }
+
+    for (i, prop) in props.iter().enumerate() {
+        let (_, expected, _) = specs.iter()
+            .find(|(key, _, _)| *key == prop.key)
+            .ok_or_else(|| format!("{} has no property {}", name, prop.key))?;
+        if props[..i].iter().any(|earlier| earlier.key == prop.key) {
+            return Err(format!("Duplicate property {}", prop.key));
+        }
+        let actual = match &prop.value {
+            PropValue::Text(text) => {
+                if text.len() > MAX_TEXT_LEN {
+                    return Err(format!("{} must be at most {} characters", prop.key, MAX_TEXT_LEN));
+                }
+                PropType::Text
+            }
+            PropValue::Number(_) => PropType::Number,
+            PropValue::Bool(_) => PropType::Bool,
+        };
+        if actual != *expected {
+            return Err(format!("{} must be {:?}", prop.key, expected));
+        }
+    }
+
+    if let Some((key, _, _)) = specs.iter().find(|(key, _, required)| *required && !props.iter().any(|p| p.key == *key)) {
+        return Err(format!("{} requires property {}", name, key));
+    }
+    Ok(())
+}
+
+/// Stable 0-99 bucket per user, so a sampled user stays sampled
+fn bucket(user: Principal) -> u8 {
+    use sha2::{Sha256, Digest};
+    let hash = Sha256::digest(user.as_slice());
+    (u16::from_be_bytes([hash[0], hash[1]]) % 100) as u8
+}
+
+/// Drop events past retention and the oldest ones over the hard cap
+fn trim(now: u64, retention_days: u32) {
+    let cutoff = now.saturating_sub(retention_days as u64 * DAY_NANOS);
+    ANALYTICS_EVENTS.with(|e| {
+        let mut map = e.borrow_mut();
+        let expired: Vec<u64> = map.iter()
+            .take(TRIM_BATCH)
+            .take_while(|(_, event)| event.hour < cutoff)
+            .map(|(seq, _)| seq)
+            .collect();
+        for seq in expired {
+            map.remove(&seq);
+        }
+        while map.len() > MAX_EVENTS {
+            let Some((oldest, _)) = map.first_key_value() else { break };
+            map.remove(&oldest);
+        }
+    });
+}
+
+// ============================================================================
+// Ingestion
+// ============================================================================
+
+/// Validate and record one event. Returns whether it was kept (false when
+/// the caller is outside the sample).
+pub fn track_event(caller: Principal, name: String, props: Vec<EventProp>) -> Result<bool, String> {
+    if SERVER_EVENTS.contains(&name.as_str()) {
+        return Err(format!("{} is recorded by the backend", name));
+    }
+    record(caller, name, props)
+}
+
+/// Record an event on the user's behalf, sampled like tracked ones
+pub fn record(caller: Principal, name: String, props: Vec<EventProp>) -> Result<bool, String> {
+    validate(&name, &props)?;
+    let config = get_config();
+    if bucket(caller) >= config.sample_percent {
+        return Ok(false);
+    }
+
+    let now = time();
+    let event = TrackedEvent {
+        name,
+        props,
+        hour: now - now % HOUR_NANOS,
+        sample_percent: config.sample_percent,
+    };
+    ANALYTICS_EVENTS.with(|e| {
+        let mut map = e.borrow_mut();
+        let seq = map.last_key_value().map(|(seq, _)| seq + 1).unwrap_or(0);
+        map.insert(seq, event);
+    });
+    trim(now, config.retention_days);
+    Ok(true)
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+/// Daily counts per event between `from` and `to` (nanoseconds), optionally
+/// for a single event name
+pub fn event_counts(name: Option<String>, from: u64, to: u64) -> Vec<EventCount> {
+    let mut counts: BTreeMap<(String, u64), (u64, u64)> = BTreeMap::new();
+    ANALYTICS_EVENTS.with(|e| {
+        for (_, event) in e.borrow().iter() {
+            if event.hour < from || event.hour >= to || name.as_ref().is_some_and(|n| *n != event.name) {
+                continue;
+            }
+            let entry = counts.entry((event.name, event.hour - event.hour % DAY_NANOS)).or_default();
+            entry.0 += 1;
+            entry.1 += 100 / event.sample_percent.max(1) as u64;
+        }
+    });
+    counts.into_iter()
+        .map(|((name, day), (count, estimated_total))| EventCount { name, day, count, estimated_total })
+        .collect()
+}
+
+pub fn get_config() -> AnalyticsConfig {
+    ANALYTICS_CONFIG.with(|c| c.borrow().get().clone())
+}
+
+pub fn set_config(config: AnalyticsConfig) -> Result<(), String> {
+    if config.sample_percent > 100 {
+        return Err("sample_percent must be 0-100".to_string());
+    }
+    if config.retention_days == 0 || config.retention_days > 365 {
+        return Err("retention_days must be 1-365".to_string());
+    }
+    ANALYTICS_CONFIG.with(|c| c.borrow_mut().set(config))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to store analytics config: {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prop(key: &str, value: PropValue) -> EventProp {
+        EventProp { key: key.to_string(), value }
+    }
+
+    #[test]
+    fn accepts_events_matching_their_schema() {
+        assert!(validate("page_view", &[prop("path", PropValue::Text("/book".into()))]).is_ok());
+        assert!(validate("onboarding_step", &[prop("step", PropValue::Number(2))]).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_names_keys_and_types() {
+        assert!(validate("clicked", &[]).is_err());
+        assert!(validate("page_view", &[]).is_err());
+        assert!(validate("page_view", &[prop("path", PropValue::Number(1))]).is_err());
+        assert!(validate("page_view", &[
+            prop("path", PropValue::Text("/".into())),
+            prop("email", PropValue::Text("a@b.c".into())),
+        ]).is_err());
+        assert!(validate("page_view", &[
+            prop("path", PropValue::Text("/".into())),
+            prop("path", PropValue::Text("/".into())),
+        ]).is_err());
+        assert!(validate("page_view", &[prop("path", PropValue::Text("x".repeat(MAX_TEXT_LEN + 1)))]).is_err());
+    }
+
+    #[test]
+    fn server_events_cannot_be_sent_by_clients() {
+        let props = vec![prop("availability_id", PropValue::Text("abc123".into()))];
+        assert!(validate("repeat_booking", &props).is_ok());
+        assert!(track_event(Principal::from_slice(&[1]), "repeat_booking".into(), props).is_err());
+    }
+
+    #[test]
+    fn buckets_are_stable_and_in_range() {
+        let user = Principal::from_slice(&[7, 7]);
+        assert_eq!(bucket(user), bucket(user));
+        assert!((0..250u8).all(|i| bucket(Principal::from_slice(&[i])) < 100));
+    }
+}
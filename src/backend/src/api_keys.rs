@@ -0,0 +1,231 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use sha2::{Sha256, Digest};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{entropy, principal_moves, quarantine};
+use crate::error_codes::{self, ErrorCode};
+
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+const KEY_PREFIX: &str = "wk_";
+const MAX_KEYS_PER_OWNER: usize = 10;
+const DEFAULT_REQUESTS_PER_HOUR: u32 = 600;
+const MAX_REQUESTS_PER_HOUR: u32 = 10_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ApiScope {
+    ReadAvailabilities,
+    WriteBookings,
+    WriteBusyTimes,
+}
+
+/// A key third-party servers use to call the HTTP API as its owner.
+/// Only the SHA-256 of the secret is stored.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ApiKey {
+    pub id: String,                 // First characters of the hash, safe to display
+    pub owner: Principal,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub requests_per_hour: u32,
+    pub created_at: u64,
+    pub last_used_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub requests_per_hour: Option<u32>,
+}
+
+/// Returned once at creation; the secret can't be retrieved again
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct CreatedApiKey {
+    pub key: ApiKey,
+    pub secret: String,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl ApiKey {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and without scopes, so it authorizes nothing.
+    fn unreadable() -> Self {
+        ApiKey {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            name: String::new(),
+            scopes: vec![],
+            requests_per_hour: 0,
+            created_at: 0,
+            last_used_at: None,
+        }
+    }
+}
+
+impl Storable for ApiKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ApiKey").unwrap_or_else(ApiKey::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // SHA-256 of the secret (hex) -> key
+    static API_KEYS: RefCell<StableBTreeMap<String, ApiKey, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(29)))
+        )
+    );
+
+    // Key ID -> (window start in ns, requests in window); resets on upgrade
+    static KEY_WINDOWS: RefCell<HashMap<String, (u64, u32)>> = RefCell::new(HashMap::new());
+}
+
+pub fn clear() {
+    API_KEYS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn keys_of(owner: Principal) -> Vec<(String, ApiKey)> {
+    API_KEYS.with(|k| {
+        k.borrow()
+            .iter()
+            .filter(|(_, key)| key.owner == owner)
+            .collect()
+    })
+}
+
+fn consume_key_rate(key: &ApiKey) -> Result<(), String> {
+    let now = time();
+    KEY_WINDOWS.with(|w| {
+        let mut windows = w.borrow_mut();
+        let entry = windows.entry(key.id.clone()).or_insert((now, 0));
+        if now - entry.0 >= HOUR_NS {
+            *entry = (now, 0);
+        }
+        if entry.1 >= key.requests_per_hour {
+            return Err(error_codes::coded(ErrorCode::QuotaExceeded, format!("at most {} requests per hour for this key", key.requests_per_hour)));
+        }
+        entry.1 += 1;
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Authentication
+// ============================================================================
+
+/// Resolve a presented secret to its key, checking scope and rate limit.
+/// Errors carry the HTTP status to answer with.
+pub fn authenticate(secret: &str, scope: ApiScope) -> Result<ApiKey, (u16, String)> {
+    let hash = hash_secret(secret);
+    let mut key = API_KEYS.with(|k| k.borrow().get(&hash))
+        .ok_or((401, "Invalid API key".to_string()))?;
+    if !key.scopes.contains(&scope) {
+        return Err((403, format!("API key lacks the {:?} scope", scope)));
+    }
+    consume_key_rate(&key).map_err(|e| (429, e))?;
+
+    key.last_used_at = Some(time());
+    API_KEYS.with(|k| k.borrow_mut().insert(hash, key.clone()));
+    Ok(key)
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn create_api_key(caller: Principal, req: CreateApiKeyRequest) -> Result<CreatedApiKey, String> {
+    if caller == Principal::anonymous() {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Sign in to create API keys"));
+    }
+    if req.name.trim().is_empty() || req.name.len() > 100 {
+        return Err("name must be 1-100 characters".to_string());
+    }
+    if req.scopes.is_empty() {
+        return Err("at least one scope is required".to_string());
+    }
+    let requests_per_hour = req.requests_per_hour.unwrap_or(DEFAULT_REQUESTS_PER_HOUR);
+    if requests_per_hour == 0 || requests_per_hour > MAX_REQUESTS_PER_HOUR {
+        return Err(format!("requests_per_hour must be 1-{}", MAX_REQUESTS_PER_HOUR));
+    }
+    if keys_of(caller).len() >= MAX_KEYS_PER_OWNER {
+        return Err(format!("At most {} API keys", MAX_KEYS_PER_OWNER));
+    }
+
+    let secret = format!("{}{}", KEY_PREFIX, entropy::next_token(24)?);
+    let hash = hash_secret(&secret);
+
+    let key = ApiKey {
+        id: hash[..12].to_string(),
+        owner: caller,
+        name: req.name.trim().to_string(),
+        scopes: req.scopes,
+        requests_per_hour,
+        created_at: time(),
+        last_used_at: None,
+    };
+    API_KEYS.with(|k| k.borrow_mut().insert(hash, key.clone()));
+
+    ic_cdk::println!("🔐 Created API key {} for {}", key.id, caller.to_text());
+    Ok(CreatedApiKey { key, secret })
+}
+
+pub fn list_api_keys(caller: Principal) -> Vec<ApiKey> {
+    keys_of(caller).into_iter().map(|(_, key)| key).collect()
+}
+
+pub fn revoke_api_key(caller: Principal, id: String) -> Result<(), String> {
+    let (hash, _) = keys_of(caller)
+        .into_iter()
+        .find(|(_, key)| key.id == id)
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "API key not found"))?;
+    API_KEYS.with(|k| k.borrow_mut().remove(&hash));
+    KEY_WINDOWS.with(|w| w.borrow_mut().remove(&id));
+    Ok(())
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    API_KEYS.with(|k| principal_moves::count_owned_in(&k.borrow(), |key| &mut key.owner, owner))
+}
+
+/// Keys keep working and now act for `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    API_KEYS.with(|k| principal_moves::move_owned_in(&mut k.borrow_mut(), |key| &mut key.owner, from, to))
+}
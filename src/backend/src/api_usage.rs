@@ -0,0 +1,198 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use ic_cdk::api::call::CallResult;
+use ic_cdk::api::management_canister::http_request::{CanisterHttpRequestArgument, HttpResponse};
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::circuit_breaker::{self, Provider};
+use crate::quotas::{self, QuotaKind};
+use crate::tokens;
+use crate::quarantine;
+
+const DAY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+/// Days of history kept per account
+const RETENTION_DAYS: u64 = 30;
+const REPORT_SIZE: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Google API calls made on behalf of one connected account in one UTC day
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ApiUsageDay {
+    pub day: u64,            // Days since the Unix epoch
+    pub google_calls: u32,   // Outcalls that reached Google (cache hits excluded)
+    pub google_errors: u32,  // Of those, rejected calls and non-2xx responses
+}
+
+/// One account's usage for the admin cost report
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct AccountUsage {
+    pub account: Principal,
+    pub google_calls: u32,
+    pub google_errors: u32,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for ApiUsageDay {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ApiUsageDay").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // (canonical account, day) -> usage; older than RETENTION_DAYS is pruned on write
+    static API_USAGE: RefCell<StableBTreeMap<(Principal, u64), ApiUsageDay, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(55)))
+        )
+    );
+}
+
+pub fn clear() {
+    API_USAGE.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn today() -> u64 {
+    time() / DAY_NS
+}
+
+fn is_success(result: &CallResult<(HttpResponse,)>) -> bool {
+    result.as_ref().is_ok_and(|(response,)| response.status >= 200u16 && response.status < 300u16)
+}
+
+// ============================================================================
+// Accounting
+// ============================================================================
+
+/// Count one Google outcall against the principal's connected account
+pub fn record_google(principal: Principal, result: &CallResult<(HttpResponse,)>) {
+    let account = tokens::canonical(principal);
+    let day = today();
+    API_USAGE.with(|u| {
+        let mut map = u.borrow_mut();
+        let mut usage = map.get(&(account, day)).unwrap_or(ApiUsageDay { day, ..Default::default() });
+        usage.google_calls += 1;
+        if !is_success(result) {
+            usage.google_errors += 1;
+        }
+        map.insert((account, day), usage);
+
+        let expired: Vec<(Principal, u64)> = map
+            .range((account, 0)..(account, day.saturating_sub(RETENTION_DAYS)))
+            .map(|(key, _)| key)
+            .collect();
+        for key in expired {
+            map.remove(&key);
+        }
+    });
+}
+
+/// Google outcall on behalf of `principal`, through the circuit breaker, counted
+pub async fn google_request(
+    principal: Principal,
+    request: CanisterHttpRequestArgument,
+    cycles: u128,
+) -> CallResult<(HttpResponse,)> {
+    let result = circuit_breaker::http_request(Provider::Google, request, cycles).await;
+    record_google(principal, &result);
+    result
+}
+
+pub fn google_calls_today(principal: Principal) -> u32 {
+    let account = tokens::canonical(principal);
+    API_USAGE.with(|u| u.borrow().get(&(account, today()))).map_or(0, |usage| usage.google_calls)
+}
+
+/// Fail with QuotaExceeded once the account has used its daily Google calls
+pub fn check_google(principal: Principal) -> Result<(), String> {
+    let account = tokens::canonical(principal);
+    quotas::check_count(account, QuotaKind::GoogleCallsPerDay, google_calls_today(account))
+}
+
+// ============================================================================
+// Queries & Admin
+// ============================================================================
+
+/// The account's usage over the retention window, oldest day first
+pub fn get_usage(principal: Principal) -> Vec<ApiUsageDay> {
+    let account = tokens::canonical(principal);
+    API_USAGE.with(|u| {
+        u.borrow()
+            .range((account, today().saturating_sub(RETENTION_DAYS))..=(account, u64::MAX))
+            .map(|(_, usage)| usage)
+            .collect()
+    })
+}
+
+/// Accounts with the most Google calls on `day` (today if None)
+pub fn usage_report(day: Option<u64>) -> Vec<AccountUsage> {
+    let day = day.unwrap_or_else(today);
+    let mut report: Vec<AccountUsage> = API_USAGE.with(|u| {
+        u.borrow()
+            .iter()
+            .filter(|((_, d), _)| *d == day)
+            .map(|((account, _), usage)| AccountUsage {
+                account,
+                google_calls: usage.google_calls,
+                google_errors: usage.google_errors,
+            })
+            .collect()
+    });
+    report.sort_by_key(|usage| std::cmp::Reverse(usage.google_calls));
+    report.truncate(REPORT_SIZE);
+    report
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+fn days_of(account: Principal) -> Vec<ApiUsageDay> {
+    API_USAGE.with(|u| u.borrow().range((account, 0)..=(account, u64::MAX)).map(|(_, usage)| usage).collect())
+}
+
+pub fn count_owned(account: Principal) -> u32 {
+    days_of(account).len() as u32
+}
+
+/// Fold `from`'s daily usage into `to`'s, so a move doesn't reset the quota
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let days = days_of(from);
+    API_USAGE.with(|u| {
+        let mut map = u.borrow_mut();
+        for usage in &days {
+            map.remove(&(from, usage.day));
+            let mut merged = map.get(&(to, usage.day)).unwrap_or(ApiUsageDay { day: usage.day, ..Default::default() });
+            merged.google_calls += usage.google_calls;
+            merged.google_errors += usage.google_errors;
+            map.insert((to, usage.day), merged);
+        }
+    });
+    days.len() as u32
+}
@@ -0,0 +1,126 @@
+//! Version 2 of the Candid API. v1 methods keep their names and signatures so
+//! deployed frontends keep working; breaking changes ship here as `v2_*`
+//! methods, implemented as thin shims over the same logic.
+//!
+//! What changes in v2: errors are a variant instead of a free-form string,
+//! so clients can branch on the kind without parsing message prefixes.
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use crate::availabilities::{self, Availability, FreeSlot};
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::quotas::{self, QuotaKind};
+use crate::trace;
+use crate::error_codes::{self, ErrorCode};
+
+/// Newest API version this canister serves
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Oldest version still served; raise it only after every frontend has moved on
+pub const MIN_SUPPORTED_VERSION: u32 = 1;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ApiError {
+    NotFound(String),
+    Unauthorized(String),
+    QuotaExceeded(String),
+    FeatureNotAvailable(String),
+    UpstreamUnavailable(String),
+    InvalidInput(String),
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ApiVersionInfo {
+    pub current: u32,
+    pub min_supported: u32,
+}
+
+// ============================================================================
+// Compatibility Shims
+// ============================================================================
+
+/// Classify a v1 error message. The variant follows from the error code it
+/// was raised with, so localized messages land where their English wording
+/// would; the text loses its kind and gets the code appended.
+pub fn from_legacy(message: String) -> ApiError {
+    let (code, _) = error_codes::split(&message);
+    from_code(code, error_codes::with_code(message))
+}
+
+/// The variant for an error code, carrying the client-facing text
+fn from_code(code: ErrorCode, text: String) -> ApiError {
+    match code {
+        ErrorCode::QuotaExceeded => ApiError::QuotaExceeded(text),
+        ErrorCode::FeatureNotAvailable => ApiError::FeatureNotAvailable(text),
+        ErrorCode::UpstreamUnavailable => ApiError::UpstreamUnavailable(text),
+        ErrorCode::Unauthorized => ApiError::Unauthorized(text),
+        ErrorCode::AvailNotFound | ErrorCode::BookingNotFound | ErrorCode::NotFound => ApiError::NotFound(text),
+        _ => ApiError::InvalidInput(text),
+    }
+}
+
+pub fn api_version() -> ApiVersionInfo {
+    ApiVersionInfo { current: CURRENT_VERSION, min_supported: MIN_SUPPORTED_VERSION }
+}
+
+pub fn get_availability(caller: Principal, id: String, origin: Option<String>) -> Result<Availability, ApiError> {
+    let availability = availabilities::get_visible_availability(caller, id, origin.as_deref()).map_err(from_legacy)?;
+    Ok(availabilities::public_view(availability, caller))
+}
+
+pub fn get_free_slots(
+    caller: Principal,
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+    origin: Option<String>,
+) -> Result<Vec<FreeSlot>, ApiError> {
+    availabilities::get_visible_availability(caller, id.clone(), origin.as_deref()).map_err(from_legacy)?;
+    availabilities::get_free_slots_as(caller, id, range_start, range_end, duration_minutes, access_code)
+        .map_err(from_legacy)
+}
+
+/// Traced like v1 create_booking, so its error and the jobs it queues carry
+/// the trace ID
+pub fn create_booking(caller: Principal, req: CreateBookingRequest) -> Result<Booking, ApiError> {
+    trace::run_into(move || {
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        bookings::create_booking(caller, req)
+    }, from_code)
+}
+
+pub fn get_booking(caller: Principal, id: String) -> Result<Booking, ApiError> {
+    bookings::get_booking(caller, id).map_err(from_legacy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localized_messages_map_to_the_variant_of_their_code() {
+        assert!(matches!(
+            from_legacy("Die gewünschte Zeit ist bereits gebucht".to_string()),
+            ApiError::InvalidInput(text) if text.ends_with("(E_SLOT_TAKEN)")
+        ));
+        assert!(matches!(
+            from_legacy("QuotaExceeded: 10 availabilities on the Free plan".to_string()),
+            ApiError::QuotaExceeded(text) if text == "10 availabilities on the Free plan (E_QUOTA_EXCEEDED)"
+        ));
+        assert!(matches!(
+            from_legacy(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change busy privacy")),
+            ApiError::Unauthorized(text) if text.starts_with("Only the owner")
+        ));
+        assert!(matches!(
+            from_legacy(error_codes::coded(ErrorCode::AvailNotFound, "Availability not found")),
+            ApiError::NotFound(text) if text == "Availability not found (E_AVAIL_NOT_FOUND)"
+        ));
+        assert!(matches!(from_legacy("Only confirmed bookings can be moved".to_string()), ApiError::InvalidInput(_)));
+    }
+}
@@ -1,8 +1,9 @@
-use candid::{CandidType, Principal, Decode, Encode};
+use candid::{CandidType, Principal, Encode};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use ic_cdk::api::time;
+use std::ops::Bound as RangeBound;
+use crate::clock::time;
 use ic_stable_structures::{
     memory_manager::MemoryId,
     storable::Bound,
@@ -10,6 +11,58 @@ use ic_stable_structures::{
 };
 use std::borrow::Cow;
 use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::upgrade_check::{self, RunningDigest};
+use crate::tenants;
+use crate::{bookings, calendar_access, counters, cursor, entropy, experiments, ical, migration, org_busy, quarantine, read_cache, trash, verified_emails};
+use crate::followups::{self, FollowUp};
+use crate::org_defaults::{self, OrgSetting};
+use crate::scheduling::{self, CommonFreeSlot, MeetingSuggestion, ParticipantWindows, SuggestionConstraints};
+use crate::delegations::{self, DelegationScope};
+use crate::i18n::Locale;
+use crate::index_rebuild::RebuildChunk;
+use crate::watched_map::WatchedMap;
+use crate::error_codes::{self, ErrorCode};
+
+/// Busy blocks are widened to this granularity in Quantized mode (seconds)
+const BUSY_QUANTUM_SECS: u64 = 30 * 60;
+
+/// Longest range get_free_slots will expand (busy times are only synced 90 days ahead)
+const MAX_FREE_SLOT_RANGE_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// Limits for finding common time across several availabilities
+const MAX_PARTICIPANTS: usize = 10;
+const MAX_GROUP_RANGE_SECS: u64 = 31 * 24 * 60 * 60;
+
+/// Meeting duration options
+pub const MIN_DURATION_MINUTES: u32 = 5;
+pub const MAX_DURATION_MINUTES: u32 = 480;
+const MAX_BUFFER_MINUTES: u32 = 240;
+const MAX_DURATION_OPTIONS: usize = 10;
+
+const MAX_KIOSK_DEVICES: usize = 20;
+const MAX_COLLABORATORS: usize = 20;
+
+/// VIP reserve windows
+const MAX_RESERVE_HOURS: u32 = 7 * 24;
+const MAX_VIP_ENTRIES: usize = 100;
+
+/// Start times offered when a guest picks a duration (seconds), unless the
+/// availability sets its own alignment
+const DURATION_STEP_SECS: u64 = 15 * 60;
+/// Alignment steps must divide an hour so boundaries repeat every hour
+const ALIGNMENT_STEPS_MINUTES: [u32; 6] = [5, 10, 15, 20, 30, 60];
+const MAX_LEAD_MINUTES: u32 = 7 * 24 * 60;
+const MAX_MEETING_URL_LEN: usize = 500;
+/// Private event properties; Google caps keys at 44 and values at 1024 characters
+const MAX_EVENT_FIELDS: usize = 20;
+const MAX_EVENT_FIELD_KEY_LEN: usize = 44;
+pub const MAX_EVENT_FIELD_VALUE_LEN: usize = 1024;
+const MAX_EVENT_FIELD_LABEL_LEN: usize = 100;
+/// Guests one group session can take
+const MAX_SEATS: u32 = 500;
+/// Most availabilities per page of a paged listing
+const PAGE_SIZE: usize = 100;
 
 // ============================================================================
 // Types
@@ -37,6 +90,18 @@ pub struct Availability {
     pub busy_times: Option<Vec<BusyTimeBlock>>,
     pub is_favorite: bool,
     pub display_order: u32,
+    pub busy_privacy: Option<BusyPrivacy>, // None = Exact (records created before this setting existed)
+    pub cancellation_policy: Option<CancellationPolicy>, // None = guests can always cancel for a full refund
+    pub locale: Option<Locale>, // Language of guest-facing generated text (None = owner's locale)
+    pub durations: Option<Vec<DurationOption>>, // None = guests book any length inside a free window
+    pub kiosk: Option<KioskConfig>, // None = walk-up booking disabled
+    pub collaborators: Option<Vec<Collaborator>>, // None = only the owner and their delegates
+    pub vip: Option<VipConfig>, // None = no reserve window, every slot is public
+    pub alignment: Option<SlotAlignment>, // None = 15-minute steps and no minimum notice
+    pub meeting_url: Option<String>, // Meet/Zoom link sent to booked guests; hidden from public views
+    pub event_fields: Option<Vec<EventField>>, // None = booking events only carry the booking link
+    pub seats: Option<u32>, // Group event: guests who can book the same slot (None = one)
+    pub follow_ups: Option<Vec<FollowUp>>, // Post-meeting emails/webhooks; hidden from public views
 }
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -45,6 +110,125 @@ pub struct BusyTimeBlock {
     pub end_time: u64,
 }
 
+/// How much of the owner's busy data is revealed to anyone who isn't the owner
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BusyPrivacy {
+    Exact,         // Raw busy blocks as synced from the calendar
+    Quantized,     // Busy blocks widened to 30-minute boundaries
+    FreeSlotsOnly, // No busy data at all, guests only see get_free_slots results
+}
+
+/// Refund rules for guest cancellations, measured in hours before the booking starts:
+/// full refund until free_until_hours, partial refund until partial_until_hours,
+/// no refund after that
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CancellationPolicy {
+    pub free_until_hours: u32,
+    pub partial_until_hours: u32,
+    pub partial_refund_percent: u8,
+}
+
+/// A meeting length guests can pick, with time kept clear around it
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DurationOption {
+    pub minutes: u32,
+    pub buffer_before_minutes: u32,
+    pub buffer_after_minutes: u32,
+}
+
+/// Walk-up booking from shared devices (meeting-room panels, office-hours kiosks)
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KioskConfig {
+    pub devices: Vec<Principal>,          // Identities of the shared devices allowed to book
+    pub duration_minutes: u32,
+    pub lead_minutes: u32,                // Earliest start is now + lead_minutes
+    pub max_bookings_per_device_per_hour: u32,
+}
+
+/// What a collaborator may do on one availability. Every role can view it.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CollaboratorRole {
+    Viewer,   // Full record (including busy data) and link analytics
+    Editor,   // Also changes the schedule and settings
+    Approver, // Also sees and cancels bookings made on it
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Collaborator {
+    pub principal: Principal,
+    pub role: CollaboratorRole,
+}
+
+/// Slots starting within the next `reserve_hours` are held back for VIP guests:
+/// matching verified emails or domains, or anyone with one of the access codes
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VipConfig {
+    pub reserve_hours: u32,
+    pub emails: Vec<String>,
+    pub domains: Vec<String>,       // e.g. "bigclient.com"
+    pub access_codes: Vec<String>,
+}
+
+/// Layout of offered slots, so they look the same whenever the page is loaded
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SlotAlignment {
+    pub step_minutes: u32, // Slots start and end on these local boundaries (30 = :00/:30, 60 = top of the hour)
+    pub lead_minutes: u32, // Minimum notice before the first offered slot
+}
+
+/// Extra private extended property written on the Google event of each
+/// booking, for automations in the owner's Workspace to pick up
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EventField {
+    pub key: String,           // Property name in Google, e.g. "crm_deal_id"
+    pub label: String,         // Question shown on the booking form
+    pub value: Option<String>, // Fixed value on every event; None = the guest answers it
+}
+
+/// A guest's answer to one of the availability's asked event fields
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldAnswer {
+    pub key: String,
+    pub value: String,
+}
+
+/// A bookable window (Unix seconds) computed from slots minus busy times
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FreeSlot {
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// Unsaved schedule settings for `preview_free_slots`; None keeps the saved value
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AvailabilitySettingsPreview {
+    pub slots: Option<Vec<TimeSlot>>,
+    pub timezone: Option<String>,
+    pub durations: Option<Vec<DurationOption>>, // Some(empty) = any length inside a free window
+    pub alignment: Option<SlotAlignment>,
+}
+
+/// Portable description of an availability's setup, independent of the
+/// canister's storage format
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct AvailabilityDefinition {
+    pub format_version: u32,
+    pub title: String,
+    pub description: String,
+    pub timezone: String,
+    pub slots: Vec<TimeSlot>,
+    pub busy_privacy: Option<BusyPrivacy>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub durations: Option<Vec<DurationOption>>,
+    pub locale: Option<Locale>,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct AvailabilityExport {
+    pub json: String,           // AvailabilityDefinition as JSON
+    pub vavailability: String,  // RFC 7953 VAVAILABILITY calendar
+}
+
 #[derive(CandidType, Deserialize)]
 pub struct CreateAvailabilityRequest {
     pub title: String,
@@ -65,6 +249,15 @@ pub struct UpdateAvailabilityRequest {
     pub timezone: Option<String>,
 }
 
+/// One reply's worth of availabilities; `truncated` means pass `next_cursor`
+/// back for the rest
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct AvailabilityPage {
+    pub availabilities: Vec<Availability>,
+    pub truncated: bool,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct AvailabilityStatus {
     pub start: u64,
@@ -72,33 +265,71 @@ pub struct AvailabilityStatus {
     pub free: bool,
 }
 
+impl Availability {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and empty, so it is never bookable.
+    fn unreadable() -> Self {
+        Availability {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            owner_email: None,
+            owner_name: None,
+            title: String::new(),
+            description: String::new(),
+            slots: vec![],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            busy_times: None,
+            is_favorite: false,
+            display_order: 0,
+            busy_privacy: None,
+            cancellation_policy: None,
+            locale: None,
+            durations: None,
+            kiosk: None,
+            collaborators: None,
+            vip: None,
+            alignment: None,
+            meeting_url: None,
+            event_fields: None,
+            seats: None,
+            follow_ups: None,
+        }
+    }
+
+    pub fn is_unreadable(&self) -> bool {
+        self.id.is_empty()
+    }
+}
+
 // ============================================================================
 // Storable Implementations
 // ============================================================================
 
 impl Storable for Availability {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Availability").unwrap_or_else(Availability::unreadable)
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
 
 // Wrapper for Vec<String> to make it Storable
-#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
-struct StringVec(Vec<String>);
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StringVec(pub Vec<String>);
 
 impl Storable for StringVec {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "StringVec").unwrap_or_default()
     }
 
     const BOUND: Bound = Bound::Unbounded;
@@ -109,47 +340,68 @@ impl Storable for StringVec {
 // ============================================================================
 
 thread_local! {
-    pub static AVAILABILITIES: RefCell<StableBTreeMap<String, Availability, Memory>> = RefCell::new(
-        StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0)))
+    // Every write queues the availability for the read cache
+    pub static AVAILABILITIES: RefCell<WatchedMap<String, Availability>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))),
+            |id, old, new| {
+                read_cache::touch(id);
+                upgrade_check::track("AVAILABILITIES", id, old, new);
+            },
+        )
+    );
+
+    pub static USER_AVAILABILITIES: RefCell<WatchedMap<Principal, StringVec>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1))),
+            |owner, old, new| upgrade_check::track("USER_AVAILABILITIES", owner, old, new),
         )
     );
 
-    pub static USER_AVAILABILITIES: RefCell<StableBTreeMap<Principal, StringVec, Memory>> = RefCell::new(
+    // Owner -> favorite availability ID. The display order is the order of the
+    // owner's USER_AVAILABILITIES list, so choosing a favorite rewrites one list
+    // instead of every availability record.
+    pub static USER_FAVORITES: RefCell<StableBTreeMap<Principal, String, Memory>> = RefCell::new(
         StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1)))
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(51)))
         )
     );
 
-    // These can remain as regular HashMaps since they're just indices (rebuilt on upgrade)
+    // Heap copies of the search indices; being migrated to stable memory (see migration.rs)
     pub static EMAIL_TO_PRINCIPAL: RefCell<HashMap<String, Principal>> = RefCell::new(HashMap::new());
     pub static USERNAME_TO_PRINCIPAL: RefCell<HashMap<String, Principal>> = RefCell::new(HashMap::new());
 }
 
+pub fn clear() {
+    AVAILABILITIES.with(|m| m.borrow_mut().clear_new());
+    USER_AVAILABILITIES.with(|m| m.borrow_mut().clear_new());
+    USER_FAVORITES.with(|m| m.borrow_mut().clear_new());
+    EMAIL_TO_PRINCIPAL.with(|e| e.borrow_mut().clear());
+    USERNAME_TO_PRINCIPAL.with(|u| u.borrow_mut().clear());
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
 /// Generate a random 6-character alphanumeric ID
-pub fn generate_availability_id() -> String {
+pub fn generate_availability_id() -> Result<String, String> {
     const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
-    let mut id = String::with_capacity(6);
-    
-    for _ in 0..6 {
-        let random_byte = (time() % CHARSET.len() as u64) as usize;
-        id.push(CHARSET[random_byte] as char);
-    }
-    
-    // Ensure uniqueness
-    AVAILABILITIES.with(|a| {
-        let avails = a.borrow();
-        if avails.contains_key(&id) {
-            // If collision, add timestamp suffix
-            format!("{}{}", id, (time() % 100))
-        } else {
-            id
+    loop {
+        let id: String = entropy::next_bytes(6)?
+            .into_iter()
+            .map(|byte| CHARSET[byte as usize % CHARSET.len()] as char)
+            .collect();
+        // Draw again on a collision
+        if !AVAILABILITIES.with(|a| a.borrow().contains_key(&id)) {
+            return Ok(id);
         }
-    })
+    }
+}
+
+/// Search index key: the same email or username can exist once per tenant
+fn index_key(tenant_id: &str, value: &str) -> String {
+    format!("{}|{}", tenant_id, value)
 }
 
 /// Validate time slot
@@ -199,6 +451,21 @@ fn check_slot_overlaps(slots: &[TimeSlot]) -> Result<(), String> {
     Ok(())
 }
 
+/// Validate a non-empty weekly schedule of non-overlapping slots
+pub fn validate_weekly_slots(slots: &[TimeSlot]) -> Result<(), String> {
+    if slots.is_empty() {
+        return Err("at least 1 slot is required".to_string());
+    }
+    
+    // Validate each slot
+    for slot in slots {
+        validate_time_slot(slot)?;
+    }
+    
+    // Check for overlaps
+    check_slot_overlaps(slots)
+}
+
 /// Validate availability request
 fn validate_availability(title: &str, description: &str, slots: &[TimeSlot]) -> Result<(), String> {
     // Validate title
@@ -211,18 +478,7 @@ fn validate_availability(title: &str, description: &str, slots: &[TimeSlot]) ->
         return Err("description must be 0-500 characters".to_string());
     }
     
-    // Validate slots
-    if slots.is_empty() {
-        return Err("at least 1 slot is required".to_string());
-    }
-    
-    // Validate each slot
-    for slot in slots {
-        validate_time_slot(slot)?;
-    }
-    
-    // Check for overlaps
-    check_slot_overlaps(slots)?;
+    validate_weekly_slots(slots)?;
     
     Ok(())
 }
@@ -248,8 +504,8 @@ pub fn create_availability(caller: Principal, req: CreateAvailabilityRequest) ->
             .unwrap_or(0)
     });
     
-    let availability = Availability {
-        id: generate_availability_id(),
+    let mut availability = Availability {
+        id: generate_availability_id()?,
         owner: caller,
         owner_email: req.owner_email.clone(),
         owner_name: req.owner_name.clone(),
@@ -262,12 +518,26 @@ pub fn create_availability(caller: Principal, req: CreateAvailabilityRequest) ->
         busy_times: req.busy_times,
         is_favorite: display_order == 0, // First availability is favorite by default
         display_order,
+        busy_privacy: None,
+        cancellation_policy: None,
+        locale: None,
+        durations: None,
+        kiosk: None,
+        collaborators: None,
+        vip: None,
+        alignment: None,
+        meeting_url: None,
+        event_fields: None,
+        seats: None,
+        follow_ups: None,
     };
+    org_defaults::inherit(&mut availability);
     
     // Store availability
     AVAILABILITIES.with(|a| {
         a.borrow_mut().insert(availability.id.clone(), availability.clone())
     });
+    counters::availability_added(availability.owner, &availability.id);
     
     // Update user index
     USER_AVAILABILITIES.with(|ua| {
@@ -277,24 +547,56 @@ pub fn create_availability(caller: Principal, req: CreateAvailabilityRequest) ->
         map.insert(caller, StringVec(ids));
     });
     
-    // Update search indices
+    index_owner(&availability);
+    
+    Ok(availability)
+}
+
+/// Update search indices (scoped to the owner's tenant)
+pub fn index_owner(availability: &Availability) {
+    let owner = availability.owner;
     if let Some(ref email) = availability.owner_email {
-        EMAIL_TO_PRINCIPAL.with(|e| e.borrow_mut().insert(email.clone(), caller));
+        migration::index_email(index_key(&tenants::tenant_of(owner), email), owner);
     }
     if let Some(ref name) = availability.owner_name {
-        USERNAME_TO_PRINCIPAL.with(|u| u.borrow_mut().insert(name.clone(), caller));
+        migration::index_username(index_key(&tenants::tenant_of(owner), name), owner);
     }
-    
-    Ok(availability)
 }
 
 /// Get availability by ID
 pub fn get_availability(id: String) -> Result<Availability, String> {
-    AVAILABILITIES.with(|a| {
+    let mut availability = AVAILABILITIES.with(|a| {
         a.borrow()
             .get(&id)
-            .ok_or_else(|| "Availability not found".to_string())
-    })
+            .filter(|availability| !availability.is_unreadable())
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))
+    })?;
+    if let Some(favorite) = USER_FAVORITES.with(|f| f.borrow().get(&availability.owner)) {
+        let ids = USER_AVAILABILITIES.with(|ua| ua.borrow().get(&availability.owner)).unwrap_or_default();
+        let position = ids.0.iter().position(|other| *other == id).unwrap_or(ids.0.len());
+        apply_order(std::slice::from_mut(&mut availability), position as u32, &favorite);
+    }
+    Ok(availability)
+}
+
+/// Get availability by ID for a viewer. Availabilities of other tenants are
+/// reported as missing, except to their collaborators.
+pub fn get_visible_availability(viewer: Principal, id: String, origin: Option<&str>) -> Result<Availability, String> {
+    let availability = get_availability(id)?;
+    if !tenants::visible_to(viewer, origin, availability.owner) && !has_role(&availability, viewer, CollaboratorRole::Viewer) {
+        return Err(error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"));
+    }
+    Ok(availability)
+}
+
+/// Fill in `is_favorite` and `display_order` for a run of the owner's list
+/// starting at `first_position`. Only for owners with a USER_FAVORITES entry;
+/// others keep the values stored on their records from before it existed.
+fn apply_order(availabilities: &mut [Availability], first_position: u32, favorite: &str) {
+    for (offset, availability) in availabilities.iter_mut().enumerate() {
+        availability.display_order = first_position + offset as u32;
+        availability.is_favorite = availability.id == favorite;
+    }
 }
 
 /// Update an existing availability
@@ -303,11 +605,11 @@ pub fn update_availability(caller: Principal, req: UpdateAvailabilityRequest) ->
         let mut map = a.borrow_mut();
         let mut availability = map
             .get(&req.id)
-            .ok_or_else(|| "Availability not found".to_string())?;
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
         
         // Verify ownership
-        if availability.owner != caller {
-            return Err("Only the owner can update this availability".to_string());
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can update this availability"));
         }
         
         // Update fields
@@ -326,14 +628,7 @@ pub fn update_availability(caller: Principal, req: UpdateAvailabilityRequest) ->
         }
         
         if let Some(slots) = req.slots {
-            // Validate new slots
-            if slots.is_empty() {
-                return Err("at least 1 slot is required".to_string());
-            }
-            for slot in &slots {
-                validate_time_slot(slot)?;
-            }
-            check_slot_overlaps(&slots)?;
+            validate_weekly_slots(&slots)?;
             
             availability.slots = slots;
         }
@@ -344,6 +639,8 @@ pub fn update_availability(caller: Principal, req: UpdateAvailabilityRequest) ->
         
         availability.updated_at = time();
         
+        delegations::record(caller, availability.owner, format!("update availability {}", req.id));
+
         // Re-insert the updated availability
         map.insert(req.id.clone(), availability.clone());
         
@@ -355,40 +652,96 @@ pub fn update_availability(caller: Principal, req: UpdateAvailabilityRequest) ->
 /// Delete an availability
 pub fn delete_availability(caller: Principal, id: String) -> Result<(), String> {
     // Verify ownership
-    let owner = AVAILABILITIES.with(|a| {
+    let availability = AVAILABILITIES.with(|a| {
         a.borrow()
             .get(&id)
-            .map(|avail| avail.owner)
-            .ok_or_else(|| "Availability not found".to_string())
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))
     })?;
+    let owner = availability.owner;
     
-    if owner != caller {
-        return Err("Only the owner can delete this availability".to_string());
+    if !delegations::allowed(caller, owner, DelegationScope::ManageAvailabilities) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can delete this availability"));
     }
+    delegations::record(caller, owner, format!("delete availability {}", id));
     
     // Remove from storage
     AVAILABILITIES.with(|a| {
         a.borrow_mut().remove(&id);
     });
+    counters::availability_removed(owner, &id);
     
-    // Remove from user index
+    // Remove from the owner's index (a delegate may be the caller)
     USER_AVAILABILITIES.with(|ua| {
         let mut map = ua.borrow_mut();
-        if let Some(string_vec) = map.get(&caller) {
+        if let Some(string_vec) = map.get(&owner) {
             let mut ids = string_vec.0.clone();
             ids.retain(|avail_id| avail_id != &id);
-            map.insert(caller, StringVec(ids));
+            map.insert(owner, StringVec(ids));
         }
     });
     
+    trash::put_availability(caller, availability);
     ic_cdk::println!("🗑️ Deleted availability: {}", id);
     Ok(())
 }
 
+/// Put a trashed availability back under its owner, last in their list
+pub fn restore(availability: Availability) -> Result<(), String> {
+    if AVAILABILITIES.with(|a| a.borrow().contains_key(&availability.id)) {
+        return Err("An availability with this ID already exists".to_string());
+    }
+    let owner = availability.owner;
+    USER_AVAILABILITIES.with(|ua| {
+        let mut map = ua.borrow_mut();
+        let mut ids = map.get(&owner).map(|v| v.0).unwrap_or_default();
+        ids.push(availability.id.clone());
+        map.insert(owner, StringVec(ids));
+    });
+    index_owner(&availability);
+    counters::availability_added(owner, &availability.id);
+    AVAILABILITIES.with(|a| a.borrow_mut().insert(availability.id.clone(), availability));
+    Ok(())
+}
+
+/// Hand every availability of `from` to `to` (the same user under a new
+/// principal), appended after `to`'s own. Returns how many moved.
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let ids = USER_AVAILABILITIES.with(|ua| ua.borrow_mut().remove(&from)).map(|v| v.0).unwrap_or_default();
+    let mut moved = Vec::new();
+    for id in ids {
+        let Some(mut availability) = AVAILABILITIES.with(|a| a.borrow().get(&id)) else {
+            continue;
+        };
+        counters::availability_removed(from, &id);
+        availability.owner = to;
+        availability.updated_at = time();
+        index_owner(&availability);
+        counters::availability_added(to, &id);
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(id.clone(), availability));
+        moved.push(id);
+    }
+    let count = moved.len() as u32;
+    USER_AVAILABILITIES.with(|ua| {
+        let mut map = ua.borrow_mut();
+        let mut ids = map.get(&to).map(|v| v.0).unwrap_or_default();
+        ids.extend(moved);
+        map.insert(to, StringVec(ids));
+    });
+    USER_FAVORITES.with(|f| {
+        let mut map = f.borrow_mut();
+        if let Some(favorite) = map.remove(&from) {
+            if !map.contains_key(&to) {
+                map.insert(to, favorite);
+            }
+        }
+    });
+    count
+}
+
 /// List all availabilities for the caller
 /// Automatically populates owner_email and owner_name if they're missing
 pub fn list_user_availabilities(caller: Principal) -> Vec<Availability> {
-    USER_AVAILABILITIES.with(|ua| {
+    let mut list: Vec<Availability> = USER_AVAILABILITIES.with(|ua| {
         let user_avails = ua.borrow();
         match user_avails.get(&caller) {
             Some(string_vec) => {
@@ -396,11 +749,48 @@ pub fn list_user_availabilities(caller: Principal) -> Vec<Availability> {
                     let avails = a.borrow();
                     string_vec.0.iter()
                         .filter_map(|id| avails.get(id))
+                        .filter(|availability| !availability.is_unreadable())
                         .collect()
                 })
             }
             None => vec![],
         }
+    });
+    if let Some(favorite) = USER_FAVORITES.with(|f| f.borrow().get(&caller)) {
+        apply_order(&mut list, 0, &favorite);
+    }
+    list
+}
+
+/// `list_user_availabilities` one page at a time, in the same order
+pub fn list_user_availabilities_page(owner: Principal, cursor: Option<String>) -> Result<AvailabilityPage, String> {
+    let (availabilities, next_cursor) = cursor::page(
+        list_user_availabilities(owner),
+        cursor,
+        "availabilities",
+        &owner.to_text(),
+        PAGE_SIZE,
+        |a| a.id.clone(),
+    )?;
+    Ok(AvailabilityPage {
+        availabilities,
+        truncated: next_cursor.is_some(),
+        next_cursor,
+    })
+}
+
+/// Number of availabilities owned by a user (for quota checks)
+/// Availabilities and favorite of a principal, for principal moves
+pub fn count_owned(owner: Principal) -> u32 {
+    count_user_availabilities(owner) + USER_FAVORITES.with(|f| f.borrow().contains_key(&owner)) as u32
+}
+
+pub fn count_user_availabilities(owner: Principal) -> u32 {
+    USER_AVAILABILITIES.with(|ua| {
+        ua.borrow()
+            .get(&owner)
+            .map(|v| v.0.len() as u32)
+            .unwrap_or(0)
     })
 }
 
@@ -414,15 +804,15 @@ pub fn regenerate_availability_id(caller: Principal, old_id: String) -> Result<S
     let availability = AVAILABILITIES.with(|a| {
         a.borrow()
             .get(&old_id)
-            .ok_or_else(|| "Availability not found".to_string())
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))
     })?;
     
     if availability.owner != caller {
-        return Err("Only the owner can regenerate this availability ID".to_string());
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can regenerate this availability ID"));
     }
     
     // Generate new ID
-    let new_id = generate_availability_id();
+    let new_id = generate_availability_id()?;
     
     // Create new availability with new ID
     let mut new_availability = availability.clone();
@@ -447,169 +837,1219 @@ pub fn regenerate_availability_id(caller: Principal, old_id: String) -> Result<S
             map.insert(caller, StringVec(ids));
         }
     });
+    USER_FAVORITES.with(|f| {
+        let mut map = f.borrow_mut();
+        if map.get(&caller).is_some_and(|favorite| favorite == old_id) {
+            map.insert(caller, new_id.clone());
+        }
+    });
     
     ic_cdk::println!("🔄 Regenerated availability ID: {} -> {}", old_id, new_id);
     Ok(new_id)
 }
 
 // ============================================================================
-// Search Functions
+// Privacy & Free Slots
 // ============================================================================
 
-/// Search availabilities by email
-pub fn search_availabilities_by_email(email: String) -> Vec<Availability> {
-    let principal = EMAIL_TO_PRINCIPAL.with(|e| {
-        e.borrow().get(&email).cloned()
-    });
+/// Whether the caller holds the role on the availability. Owners hold every
+/// role and any collaborator counts as a Viewer.
+pub fn has_role(availability: &Availability, caller: Principal, role: CollaboratorRole) -> bool {
+    availability.owner == caller
+        || availability.collaborators.as_deref().unwrap_or(&[]).iter().any(|c| {
+            c.principal == caller && (c.role == role || role == CollaboratorRole::Viewer)
+        })
+}
+
+/// Owners, their availability delegates and Editors may change settings
+pub fn can_edit(caller: Principal, availability: &Availability) -> bool {
+    delegations::allowed(caller, availability.owner, DelegationScope::ManageAvailabilities)
+        || has_role(availability, caller, CollaboratorRole::Editor)
+}
+
+/// Shape an availability for a viewer: the owner and collaborators see
+/// everything, everyone else only gets the busy data allowed by busy_privacy
+pub fn public_view(mut availability: Availability, viewer: Principal) -> Availability {
+    if has_role(&availability, viewer, CollaboratorRole::Viewer) {
+        return availability;
+    }
+    availability.collaborators = None;
+    availability.vip = None; // Access codes are secrets
+    availability.meeting_url = None;
+    availability.follow_ups = None;
+    // Guests see what they are asked, not the owner's fixed values
+    availability.event_fields = availability.event_fields
+        .map(|fields| fields.into_iter().filter(|f| f.value.is_none()).collect());
     
-    match principal {
-        Some(p) => list_user_availabilities(p),
-        None => vec![],
+    match availability.busy_privacy.unwrap_or(BusyPrivacy::Exact) {
+        BusyPrivacy::Exact => {}
+        BusyPrivacy::Quantized => {
+            availability.busy_times = availability.busy_times
+                .as_deref()
+                .map(|blocks| scheduling::quantize_busy_times(blocks, BUSY_QUANTUM_SECS));
+        }
+        BusyPrivacy::FreeSlotsOnly => {
+            availability.busy_times = None;
+        }
     }
+    
+    availability
 }
 
-/// Search availabilities by username
-pub fn search_availabilities_by_username(username: String) -> Vec<Availability> {
-    let principal = USERNAME_TO_PRINCIPAL.with(|u| {
-        u.borrow().get(&username).cloned()
-    });
-    
-    match principal {
-        Some(p) => list_user_availabilities(p),
-        None => vec![],
+/// List another user's availabilities as seen by the viewer.
+/// Users of other tenants are invisible to search.
+fn list_public_availabilities(viewer: Principal, origin: Option<&str>, owner: Principal) -> Vec<Availability> {
+    if !tenants::visible_to(viewer, origin, owner) {
+        return vec![];
+    }
+    list_user_availabilities(owner)
+        .into_iter()
+        .map(|availability| public_view(availability, viewer))
+        .collect()
+}
+
+/// Whether the viewer may see the VIP reserve window: the owner and
+/// collaborators, guests with a listed verified email or domain, and anyone
+/// presenting one of the access codes
+fn is_vip(availability: &Availability, viewer: Principal, access_code: Option<&str>) -> bool {
+    let Some(ref vip) = availability.vip else {
+        return true;
+    };
+    if has_role(availability, viewer, CollaboratorRole::Viewer) {
+        return true;
     }
+    if access_code.is_some_and(|code| vip.access_codes.iter().any(|c| c == code)) {
+        return true;
+    }
+    verified_emails::get(viewer).is_some_and(|email| {
+        let domain = email.rsplit_once('@').map(|(_, d)| d).unwrap_or("");
+        vip.emails.contains(&email) || vip.domains.iter().any(|d| d == domain)
+    })
 }
 
-/// Search availabilities by principal
-pub fn search_availabilities_by_principal(principal: Principal) -> Vec<Availability> {
-    list_user_availabilities(principal)
+/// Compute free windows (Unix seconds) for an availability within a range,
+/// as seen by a guest without VIP access
+pub fn get_free_slots(id: String, range_start: u64, range_end: u64, duration_minutes: Option<u32>) -> Result<Vec<FreeSlot>, String> {
+    get_free_slots_as(Principal::anonymous(), id, range_start, range_end, duration_minutes, None)
 }
 
-/// Update busy times for an availability
-pub fn update_availability_busy_times(caller: Principal, id: String, busy_times: Vec<BusyTimeBlock>) -> Result<(), String> {
-    AVAILABILITIES.with(|a| {
-        let mut map = a.borrow_mut();
-        let mut availability = map
-            .get(&id)
-            .ok_or_else(|| "Availability not found".to_string())?;
-        
-        // Verify ownership
-        if availability.owner != caller {
-            return Err("Only the owner can update busy times".to_string());
+/// Compute free windows (Unix seconds) for an availability within a range.
+/// Never exposes busy data, so it is safe for every privacy mode. Slots in
+/// the VIP reserve window are left out unless the viewer is a VIP.
+pub fn get_free_slots_as(
+    viewer: Principal,
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+) -> Result<Vec<FreeSlot>, String> {
+    if range_start >= range_end {
+        return Err("range_start must be before range_end".to_string());
+    }
+    if range_end - range_start > MAX_FREE_SLOT_RANGE_SECS {
+        return Err("range must be at most 90 days".to_string());
+    }
+    
+    let availability = experiments::as_seen_by(get_availability(id)?, viewer);
+    slots_for(&availability, viewer, range_start, range_end, duration_minutes, access_code)
+}
+
+/// Free windows, or slots of one duration, computed from an availability value
+fn slots_for(
+    availability: &Availability,
+    viewer: Principal,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+) -> Result<Vec<FreeSlot>, String> {
+    // A calendar whose Google access was revoked is paused until reconnected
+    if calendar_access::is_revoked(availability.owner) {
+        return Ok(Vec::new());
+    }
+    // Never offer slots in the past or inside the notice period, nor reserved ones to non-VIPs
+    let now = time() / 1_000_000_000;
+    let lead = availability.alignment.as_ref().map(|a| a.lead_minutes as u64 * 60).unwrap_or(0);
+    let earliest = match availability.vip {
+        Some(ref vip) if !is_vip(availability, viewer, access_code.as_deref()) => now + lead.max(vip.reserve_hours as u64 * 3600),
+        _ => now + lead,
+    };
+    let range_start = range_start.max(earliest);
+    if range_start >= range_end {
+        return Ok(vec![]);
+    }
+    
+    match duration_minutes {
+        Some(minutes) => {
+            let option = duration_option(availability, minutes)?;
+            Ok(duration_slots(availability, &option, range_start, range_end))
         }
-        
-        availability.busy_times = Some(busy_times.clone());
-        availability.updated_at = time();
-        
-        // Re-insert the updated availability
-        map.insert(id.clone(), availability);
-        
-        ic_cdk::println!("✅ Updated busy times for availability: {} ({} blocks)", id, busy_times.len());
-        Ok(())
-    })
+        None => Ok(aligned_windows(availability, free_windows(availability, range_start, range_end))),
+    }
 }
 
-// ============================================================================
-// Batch Search Functions (Optimized for Multiple Users)
-// ============================================================================
+/// Free slots under unsaved settings; unset fields keep the stored value
+pub fn preview_free_slots(
+    caller: Principal,
+    id: String,
+    settings: AvailabilitySettingsPreview,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+) -> Result<Vec<FreeSlot>, String> {
+    if range_start >= range_end {
+        return Err("range_start must be before range_end".to_string());
+    }
+    if range_end - range_start > MAX_FREE_SLOT_RANGE_SECS {
+        return Err("range must be at most 90 days".to_string());
+    }
+    let mut availability = get_availability(id)?;
+    if !can_edit(caller, &availability) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner, their delegates and editors can preview settings"));
+    }
 
-/// Batch search availabilities by multiple emails
-/// Uses O(1) HashMap lookups - scalable to millions of users
-pub fn search_by_emails(emails: Vec<String>) -> Vec<Vec<Availability>> {
-    emails.iter()
-        .map(|email| {
-            // O(1) lookup in EMAIL_TO_PRINCIPAL HashMap
-            let principal_opt = EMAIL_TO_PRINCIPAL.with(|e| {
-                e.borrow().get(email).cloned()
-            });
-            
-            match principal_opt {
-                Some(principal) => list_user_availabilities(principal),
-                None => vec![],
-            }
-        })
-        .collect()
+    if let Some(slots) = settings.slots {
+        validate_weekly_slots(&slots)?;
+        availability.slots = slots;
+    }
+    if let Some(timezone) = settings.timezone {
+        bookings::validate_timezone(&timezone)?;
+        availability.timezone = timezone;
+    }
+    if let Some(durations) = settings.durations {
+        validate_durations(&durations)?;
+        availability.durations = if durations.is_empty() { None } else { Some(durations) };
+    }
+    if let Some(alignment) = settings.alignment {
+        validate_alignment(&alignment)?;
+        availability.alignment = Some(alignment);
+    }
+    slots_for(&availability, Principal::anonymous(), range_start, range_end, duration_minutes, None)
 }
 
-/// Batch search availabilities by multiple usernames
-/// Uses O(1) HashMap lookups - scalable to millions of users
-pub fn search_by_usernames(usernames: Vec<String>) -> Vec<Vec<Availability>> {
-    usernames.iter()
-        .map(|username| {
-            // O(1) lookup in USERNAME_TO_PRINCIPAL HashMap
-            let principal_opt = USERNAME_TO_PRINCIPAL.with(|u| {
-                u.borrow().get(username).cloned()
-            });
-            
-            match principal_opt {
-                Some(principal) => list_user_availabilities(principal),
-                None => vec![],
-            }
+/// Step between offered start times, in seconds
+pub fn slot_step(availability: &Availability) -> u64 {
+    availability.alignment.as_ref().map(|a| a.step_minutes as u64 * 60).unwrap_or(DURATION_STEP_SECS)
+}
+
+/// Round a timestamp up (or down) to the next step boundary in the
+/// availability's local time, so :30 offsets like India's still get clean slots
+fn align(availability: &Availability, at: u64, step: u64, up: bool) -> u64 {
+    scheduling::align_local(&scheduling::parse_timezone(&availability.timezone), at, step, up)
+}
+
+/// Trim free windows to step boundaries when the owner asked for aligned slots
+fn aligned_windows(availability: &Availability, windows: Vec<FreeSlot>) -> Vec<FreeSlot> {
+    if availability.alignment.is_none() {
+        return windows;
+    }
+    let step = slot_step(availability);
+    windows
+        .into_iter()
+        .map(|w| FreeSlot {
+            start_time: align(availability, w.start_time, step, true),
+            end_time: align(availability, w.end_time, step, false),
         })
+        .filter(|w| w.start_time < w.end_time)
         .collect()
 }
 
-/// Set an availability as favorite and reorder all availabilities
-/// Only one availability can be favorite at a time
-pub fn set_favorite_availability(caller: Principal, id: String) -> Result<(), String> {
-    // Verify the availability exists and is owned by caller
-    let target_availability = AVAILABILITIES.with(|a| {
-        a.borrow()
-            .get(&id)
-            .ok_or_else(|| "Availability not found".to_string())
-    })?;
-    
-    if target_availability.owner != caller {
-        return Err("Only the owner can set favorite".to_string());
+/// The configured option for a meeting length. Availabilities without
+/// duration options accept any length with no buffers.
+pub fn duration_option(availability: &Availability, minutes: u32) -> Result<DurationOption, String> {
+    match availability.durations {
+        Some(ref options) => options
+            .iter()
+            .find(|o| o.minutes == minutes)
+            .cloned()
+            .ok_or_else(|| format!("{}-minute meetings are not offered", minutes)),
+        None if (MIN_DURATION_MINUTES..=MAX_DURATION_MINUTES).contains(&minutes) => Ok(DurationOption {
+            minutes,
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+        }),
+        None => Err(format!("duration must be {}-{} minutes", MIN_DURATION_MINUTES, MAX_DURATION_MINUTES)),
     }
-    
-    // Get all user's availabilities
-    let user_availability_ids = USER_AVAILABILITIES.with(|ua| {
-        ua.borrow()
-            .get(&caller)
-            .map(|v| v.0.clone())
-            .unwrap_or_default()
-    });
-    
-    if user_availability_ids.is_empty() {
-        return Err("No availabilities found".to_string());
+}
+
+/// Whether the buffers around [start, end) are clear of calendar events and bookings
+pub fn buffers_clear(availability: &Availability, option: &DurationOption, start: u64, end: u64) -> bool {
+    let (from, to) = scheduling::with_buffers(option, start, end);
+    let overlaps = |b: &BusyTimeBlock| b.start_time < to && from < b.end_time;
+
+    !calendar_busy(availability, from, to).iter().any(overlaps)
+        && !bookings::taken_blocks(availability, from, to).iter().any(overlaps)
+}
+
+/// Bookable meetings of one length, starting on step boundaries
+fn duration_slots(availability: &Availability, option: &DurationOption, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    let length = option.minutes as u64 * 60;
+    let step = slot_step(availability);
+    let mut slots = Vec::new();
+    for window in free_windows(availability, range_start, range_end) {
+        let mut start = align(availability, window.start_time, step, true);
+        while start + length <= window.end_time {
+            if buffers_clear(availability, option, start, start + length) {
+                slots.push(FreeSlot { start_time: start, end_time: start + length });
+            }
+            start += step;
+        }
+    }
+    slots
+}
+
+/// The owner's calendar busy times plus their org's shared busy blocks,
+/// widened to BUSY_QUANTUM_SECS under Quantized privacy so the edges of
+/// free slots don't give the exact busy boundaries away
+fn calendar_busy(availability: &Availability, range_start: u64, range_end: u64) -> Vec<BusyTimeBlock> {
+    let mut busy = availability.busy_times.clone().unwrap_or_default();
+    busy.extend(org_busy::blocks_for(availability.owner, range_start, range_end));
+    match availability.busy_privacy {
+        Some(BusyPrivacy::Quantized) => scheduling::quantize_busy_times(&busy, BUSY_QUANTUM_SECS),
+        _ => busy,
+    }
+}
+
+/// Free windows in a range: weekly slots minus calendar busy times, org-wide
+/// busy blocks and existing bookings on the owner's calendar (group sessions
+/// with a free seat stay open)
+pub fn free_windows(availability: &Availability, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    let windows = expand_slots(availability, range_start, range_end);
+    let busy = calendar_busy(availability, range_start, range_end);
+    let booked = bookings::taken_blocks(availability, range_start, range_end);
+    scheduling::subtract_busy_times(scheduling::subtract_busy_times(windows, &busy), &booked)
+}
+
+/// Time the availability offers in a range, booked or not: weekly slots minus
+/// calendar and org-wide busy blocks
+pub fn offered_windows(availability: &Availability, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    let windows = expand_slots(availability, range_start, range_end);
+    scheduling::subtract_busy_times(windows, &calendar_busy(availability, range_start, range_end))
+}
+
+/// Whether [start, end) fits entirely inside one free window of the availability's
+/// calendar (bookings are checked separately so conflicts get their own error)
+pub fn is_free(availability: &Availability, start: u64, end: u64) -> bool {
+    let windows = expand_slots(availability, start, end);
+    let busy = calendar_busy(availability, start, end);
+    scheduling::subtract_busy_times(windows, &busy)
+        .iter()
+        .any(|w| w.start_time <= start && w.end_time >= end)
+}
+
+/// Expand weekly slots (local time in the availability's timezone) into
+/// concrete UTC windows clipped to the range
+fn expand_slots(availability: &Availability, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    scheduling::expand_weekly(&availability.slots, &availability.timezone, range_start, range_end)
+}
+
+// ============================================================================
+// Group Scheduling
+// ============================================================================
+
+fn validate_group_range(range_start: u64, range_end: u64) -> Result<(), String> {
+    if range_start >= range_end {
+        return Err("range_start must be before range_end".to_string());
+    }
+    if range_end - range_start > MAX_GROUP_RANGE_SECS {
+        return Err("range must be at most 31 days".to_string());
+    }
+    Ok(())
+}
+
+fn load_participants(participants: Vec<String>, range_start: u64, range_end: u64) -> Result<Vec<ParticipantWindows>, String> {
+    if participants.is_empty() || participants.len() > MAX_PARTICIPANTS {
+        return Err(format!("participants must contain 1-{} availability IDs", MAX_PARTICIPANTS));
+    }
+    participants
+        .into_iter()
+        .map(|id| {
+            let availability = get_availability(id.clone())?;
+            Ok(ParticipantWindows {
+                id,
+                free: free_windows(&availability, range_start, range_end),
+                timezone: scheduling::parse_timezone(&availability.timezone),
+            })
+        })
+        .collect()
+}
+
+/// Windows where every participant (identified by availability ID) is free.
+/// With `fairness_mode`, windows are ranked by how reasonable they are in each
+/// participant's local time and flagged when they fall outside 8:00-20:00 for anyone.
+pub fn get_common_free_slots(
+    participants: Vec<String>,
+    range_start: u64,
+    range_end: u64,
+    fairness_mode: bool,
+) -> Result<Vec<CommonFreeSlot>, String> {
+    validate_group_range(range_start, range_end)?;
+
+    let now = time() / 1_000_000_000;
+    let range_start = range_start.max(now);
+    if range_start >= range_end {
+        return Ok(vec![]);
+    }
+
+    let windows = load_participants(participants, range_start, range_end)?;
+    Ok(scheduling::common_slots(&windows, fairness_mode))
+}
+
+/// Rank meeting times that work for every participant (identified by availability ID)
+pub fn suggest_meeting_times(
+    participants: Vec<String>,
+    duration_minutes: u32,
+    constraints: SuggestionConstraints,
+) -> Result<Vec<MeetingSuggestion>, String> {
+    if !(5..=480).contains(&duration_minutes) {
+        return Err("duration_minutes must be 5-480".to_string());
+    }
+    validate_group_range(constraints.range_start, constraints.range_end)?;
+
+    let now = time() / 1_000_000_000;
+    let range_start = constraints.range_start.max(now);
+    let range_end = constraints.range_end;
+    if range_start >= range_end {
+        return Ok(vec![]);
+    }
+
+    let windows = load_participants(participants, range_start, range_end)?;
+
+    Ok(scheduling::rank(&windows, duration_minutes as u64 * 60, &SuggestionConstraints {
+        range_start,
+        ..constraints
+    }))
+}
+
+// ============================================================================
+// Search Functions
+// ============================================================================
+
+/// Search availabilities by email
+pub fn search_availabilities_by_email(viewer: Principal, email: String, origin: Option<&str>) -> Vec<Availability> {
+    let principal = migration::lookup_email(&index_key(&tenants::viewer_tenant(viewer, origin), &email));
+    
+    match principal {
+        Some(p) => list_public_availabilities(viewer, origin, p),
+        None => vec![],
     }
+}
+
+/// Search availabilities by username
+pub fn search_availabilities_by_username(viewer: Principal, username: String, origin: Option<&str>) -> Vec<Availability> {
+    let principal = migration::lookup_username(&index_key(&tenants::viewer_tenant(viewer, origin), &username));
     
-    // Update all availabilities
+    match principal {
+        Some(p) => list_public_availabilities(viewer, origin, p),
+        None => vec![],
+    }
+}
+
+/// Search availabilities by principal
+pub fn search_availabilities_by_principal(viewer: Principal, principal: Principal, origin: Option<&str>) -> Vec<Availability> {
+    list_public_availabilities(viewer, origin, principal)
+}
+
+/// Export the schedule and settings as JSON and VAVAILABILITY (owner only)
+pub fn export_availability_definition(caller: Principal, id: String) -> Result<AvailabilityExport, String> {
+    let availability = get_availability(id)?;
+    if availability.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can export an availability"));
+    }
+
+    let definition = AvailabilityDefinition {
+        format_version: 1,
+        title: availability.title.clone(),
+        description: availability.description.clone(),
+        timezone: availability.timezone.clone(),
+        slots: availability.slots.clone(),
+        busy_privacy: availability.busy_privacy,
+        cancellation_policy: availability.cancellation_policy.clone(),
+        durations: availability.durations.clone(),
+        locale: availability.locale,
+    };
+    let json = serde_json::to_string_pretty(&definition).map_err(|e| e.to_string())?;
+
+    Ok(AvailabilityExport {
+        json,
+        vavailability: ical::vavailability(&availability),
+    })
+}
+
+/// Update busy times for an availability
+pub fn update_availability_busy_times(caller: Principal, id: String, busy_times: Vec<BusyTimeBlock>) -> Result<(), String> {
     AVAILABILITIES.with(|a| {
         let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
         
-        // First pass: set all is_favorite to false
-        for avail_id in &user_availability_ids {
-            if let Some(mut avail) = map.get(avail_id) {
-                avail.is_favorite = false;
-                avail.updated_at = time();
-                map.insert(avail_id.clone(), avail);
-            }
+        // Verify ownership
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can update busy times"));
         }
         
-        // Second pass: set target as favorite and reorder
-        if let Some(mut target) = map.get(&id) {
-            target.is_favorite = true;
-            target.display_order = 0;
-            target.updated_at = time();
-            map.insert(id.clone(), target);
+        availability.busy_times = Some(busy_times.clone());
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("update busy times of {}", id));
+
+        // Re-insert the updated availability
+        map.insert(id.clone(), availability);
+        
+        ic_cdk::println!("✅ Updated busy times for availability: {} ({} blocks)", id, busy_times.len());
+        Ok(())
+    })
+}
+
+/// Set how much busy data non-owners can see for an availability
+pub fn set_availability_busy_privacy(caller: Principal, id: String, privacy: BusyPrivacy) -> Result<(), String> {
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        // Verify ownership
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change busy privacy"));
         }
+        org_defaults::require_overridable(availability.owner, OrgSetting::BusyPrivacy)?;
         
-        // Third pass: update display_order for all others
-        let mut order = 1u32;
-        for avail_id in &user_availability_ids {
-            if avail_id != &id {
-                if let Some(mut avail) = map.get(avail_id) {
-                    avail.display_order = order;
-                    avail.updated_at = time();
-                    map.insert(avail_id.clone(), avail);
-                    order += 1;
-                }
+        availability.busy_privacy = Some(privacy);
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set busy privacy of {}", id));
+        map.insert(id.clone(), availability);
+        
+        ic_cdk::println!("🔒 Set busy privacy for availability: {} ({:?})", id, privacy);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) the cancellation policy shown to guests
+pub fn validate_cancellation_policy(policy: &CancellationPolicy) -> Result<(), String> {
+    if policy.partial_until_hours > policy.free_until_hours {
+        return Err("partial_until_hours must not exceed free_until_hours".to_string());
+    }
+    if policy.partial_refund_percent > 100 {
+        return Err("partial_refund_percent must be 0-100".to_string());
+    }
+    Ok(())
+}
+
+pub fn set_availability_cancellation_policy(caller: Principal, id: String, policy: Option<CancellationPolicy>) -> Result<(), String> {
+    if let Some(ref p) = policy {
+        validate_cancellation_policy(p)?;
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change the cancellation policy"));
+        }
+        org_defaults::require_overridable(availability.owner, OrgSetting::CancellationPolicy)?;
+        
+        availability.cancellation_policy = policy;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set cancellation policy of {}", id));
+        map.insert(id.clone(), availability);
+        
+        ic_cdk::println!("📜 Set cancellation policy for availability: {}", id);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) the language used for guest-facing text
+pub fn validate_durations(durations: &[DurationOption]) -> Result<(), String> {
+    if durations.len() > MAX_DURATION_OPTIONS {
+        return Err(format!("At most {} duration options", MAX_DURATION_OPTIONS));
+    }
+    for (i, option) in durations.iter().enumerate() {
+        if !(MIN_DURATION_MINUTES..=MAX_DURATION_MINUTES).contains(&option.minutes) {
+            return Err(format!("duration must be {}-{} minutes", MIN_DURATION_MINUTES, MAX_DURATION_MINUTES));
+        }
+        if option.buffer_before_minutes > MAX_BUFFER_MINUTES || option.buffer_after_minutes > MAX_BUFFER_MINUTES {
+            return Err(format!("buffers must be at most {} minutes", MAX_BUFFER_MINUTES));
+        }
+        if durations[..i].iter().any(|o| o.minutes == option.minutes) {
+            return Err(format!("Duplicate {}-minute option", option.minutes));
+        }
+    }
+    Ok(())
+}
+
+pub fn set_availability_durations(caller: Principal, id: String, durations: Vec<DurationOption>) -> Result<(), String> {
+    validate_durations(&durations)?;
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change meeting durations"));
+        }
+        org_defaults::require_overridable(availability.owner, OrgSetting::Durations)?;
+        
+        availability.durations = if durations.is_empty() { None } else { Some(durations) };
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set meeting durations of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+pub fn set_availability_kiosk(caller: Principal, id: String, kiosk: Option<KioskConfig>) -> Result<(), String> {
+    if let Some(ref config) = kiosk {
+        if config.devices.is_empty() || config.devices.len() > MAX_KIOSK_DEVICES {
+            return Err(format!("devices must list 1-{} principals", MAX_KIOSK_DEVICES));
+        }
+        if config.devices.contains(&Principal::anonymous()) {
+            return Err("devices must be signed in".to_string());
+        }
+        if !(MIN_DURATION_MINUTES..=MAX_DURATION_MINUTES).contains(&config.duration_minutes) {
+            return Err(format!("duration must be {}-{} minutes", MIN_DURATION_MINUTES, MAX_DURATION_MINUTES));
+        }
+        if config.lead_minutes > 24 * 60 {
+            return Err("lead_minutes must be at most one day".to_string());
+        }
+        if config.max_bookings_per_device_per_hour == 0 {
+            return Err("max_bookings_per_device_per_hour must be greater than 0".to_string());
+        }
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can configure kiosk booking"));
+        }
+        
+        availability.kiosk = kiosk;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("configure kiosk booking of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) how offered slots are aligned
+pub fn validate_alignment(alignment: &SlotAlignment) -> Result<(), String> {
+    if !ALIGNMENT_STEPS_MINUTES.contains(&alignment.step_minutes) {
+        return Err(format!("step_minutes must be one of {:?}", ALIGNMENT_STEPS_MINUTES));
+    }
+    if alignment.lead_minutes > MAX_LEAD_MINUTES {
+        return Err("lead_minutes must be at most 7 days".to_string());
+    }
+    Ok(())
+}
+
+pub fn set_availability_alignment(caller: Principal, id: String, alignment: Option<SlotAlignment>) -> Result<(), String> {
+    if let Some(ref a) = alignment {
+        validate_alignment(a)?;
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change slot alignment"));
+        }
+        org_defaults::require_overridable(availability.owner, OrgSetting::Alignment)?;
+        
+        availability.alignment = alignment;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set slot alignment of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) the VIP reserve window
+pub fn set_availability_meeting_url(caller: Principal, id: String, meeting_url: Option<String>) -> Result<(), String> {
+    if let Some(ref url) = meeting_url {
+        if !url.starts_with("https://") || url.len() > MAX_MEETING_URL_LEN {
+            return Err(format!("meeting_url must be an https URL up to {} characters", MAX_MEETING_URL_LEN));
+        }
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change the meeting link"));
+        }
+        
+        availability.meeting_url = meeting_url;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set meeting link of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Guests one slot can take
+pub fn seats(availability: &Availability) -> u32 {
+    availability.seats.unwrap_or(1)
+}
+
+/// Make the availability a group event (Some) or one guest per slot (None)
+pub fn set_availability_seats(caller: Principal, id: String, seats: Option<u32>) -> Result<(), String> {
+    if seats.is_some_and(|s| !(2..=MAX_SEATS).contains(&s)) {
+        return Err(format!("seats must be 2-{}", MAX_SEATS));
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change seats"));
+        }
+        
+        availability.seats = seats;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set seats of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) the actions run after each booking ends.
+/// Bookings confirmed from now on get them.
+pub fn set_availability_follow_ups(caller: Principal, id: String, follow_ups: Option<Vec<FollowUp>>) -> Result<(), String> {
+    if let Some(ref follow_ups) = follow_ups {
+        followups::validate(follow_ups)?;
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change follow-ups"));
+        }
+        org_defaults::require_overridable(availability.owner, OrgSetting::FollowUps)?;
+        
+        availability.follow_ups = follow_ups;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set follow-ups of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Set (Some) or clear (None) the extra properties written on booking events
+pub fn set_availability_event_fields(caller: Principal, id: String, fields: Option<Vec<EventField>>) -> Result<(), String> {
+    if let Some(ref fields) = fields {
+        validate_event_fields(fields)?;
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change event fields"));
+        }
+        
+        availability.event_fields = fields;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set event fields of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+fn validate_event_fields(fields: &[EventField]) -> Result<(), String> {
+    if fields.len() > MAX_EVENT_FIELDS {
+        return Err(format!("At most {} event fields", MAX_EVENT_FIELDS));
+    }
+    let mut keys = std::collections::HashSet::new();
+    for field in fields {
+        let valid_key = !field.key.is_empty()
+            && field.key.len() <= MAX_EVENT_FIELD_KEY_LEN
+            && field.key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid_key {
+            return Err(format!("Event field keys must be 1-{} letters, digits, '_', '-' or '.'", MAX_EVENT_FIELD_KEY_LEN));
+        }
+        if field.key.starts_with("weeekaly_") {
+            return Err("Event field keys starting with weeekaly_ are reserved".to_string());
+        }
+        if !keys.insert(field.key.as_str()) {
+            return Err(format!("Duplicate event field key: {}", field.key));
+        }
+        if field.label.len() > MAX_EVENT_FIELD_LABEL_LEN {
+            return Err(format!("Event field labels must be at most {} characters", MAX_EVENT_FIELD_LABEL_LEN));
+        }
+        if field.value.as_ref().is_some_and(|v| v.len() > MAX_EVENT_FIELD_VALUE_LEN) {
+            return Err(format!("Event field values must be at most {} characters", MAX_EVENT_FIELD_VALUE_LEN));
+        }
+    }
+    Ok(())
+}
+
+/// Answers must be to fields the availability asks guests about; unanswered
+/// fields are simply left off the event
+pub fn validate_answers(availability: &Availability, answers: &[FieldAnswer]) -> Result<(), String> {
+    let asked = |key: &str| availability.event_fields.iter().flatten().any(|f| f.key == key && f.value.is_none());
+    for answer in answers {
+        if !asked(&answer.key) {
+            return Err(format!("Unknown field: {}", answer.key));
+        }
+        if answer.value.len() > MAX_EVENT_FIELD_VALUE_LEN {
+            return Err(format!("{} must be at most {} characters", answer.key, MAX_EVENT_FIELD_VALUE_LEN));
+        }
+    }
+    Ok(())
+}
+
+/// Private properties for a booking's event: the fixed values, then the
+/// guest's non-empty answers
+pub fn event_properties(availability: &Availability, answers: &[FieldAnswer]) -> Vec<(String, String)> {
+    let mut properties = Vec::new();
+    for field in availability.event_fields.iter().flatten() {
+        let value = match field.value {
+            Some(ref value) => Some(value.clone()),
+            None => answers.iter().find(|a| a.key == field.key).map(|a| a.value.trim().to_string()),
+        };
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            properties.push((field.key.clone(), value));
+        }
+    }
+    properties
+}
+
+pub fn set_availability_vip(caller: Principal, id: String, vip: Option<VipConfig>) -> Result<(), String> {
+    let vip = match vip {
+        Some(config) => {
+            if config.reserve_hours == 0 || config.reserve_hours > MAX_RESERVE_HOURS {
+                return Err(format!("reserve_hours must be 1-{}", MAX_RESERVE_HOURS));
+            }
+            if config.emails.len() + config.domains.len() + config.access_codes.len() > MAX_VIP_ENTRIES {
+                return Err(format!("At most {} VIP emails, domains and codes in total", MAX_VIP_ENTRIES));
             }
+            if config.access_codes.iter().any(|c| c.len() < 6 || c.len() > 64) {
+                return Err("access codes must be 6-64 characters".to_string());
+            }
+            let normalize = |list: Vec<String>| -> Vec<String> {
+                list.iter().map(|v| v.trim().trim_start_matches('@').to_lowercase()).filter(|v| !v.is_empty()).collect()
+            };
+            Some(VipConfig {
+                reserve_hours: config.reserve_hours,
+                emails: normalize(config.emails),
+                domains: normalize(config.domains),
+                access_codes: config.access_codes,
+            })
         }
-    });
+        None => None,
+    };
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change VIP access"));
+        }
+        
+        availability.vip = vip;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set VIP access of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+/// Replace the collaborator list (owner only). An empty list removes everyone.
+pub fn set_availability_collaborators(caller: Principal, id: String, collaborators: Vec<Collaborator>) -> Result<(), String> {
+    if collaborators.len() > MAX_COLLABORATORS {
+        return Err(format!("At most {} collaborators", MAX_COLLABORATORS));
+    }
+    for (i, collaborator) in collaborators.iter().enumerate() {
+        if collaborator.principal == Principal::anonymous() {
+            return Err("Collaborators must be signed in".to_string());
+        }
+        if collaborators[..i].iter().any(|c| c.principal == collaborator.principal) {
+            return Err("Each collaborator can hold only one role".to_string());
+        }
+    }
+
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if availability.owner != caller {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change collaborators"));
+        }
+        if collaborators.iter().any(|c| c.principal == caller) {
+            return Err("The owner cannot be a collaborator".to_string());
+        }
+        
+        availability.collaborators = if collaborators.is_empty() { None } else { Some(collaborators) };
+        availability.updated_at = time();
+        
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+pub fn set_availability_locale(caller: Principal, id: String, locale: Option<Locale>) -> Result<(), String> {
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut availability = map
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))?;
+        
+        if !can_edit(caller, &availability) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change the locale"));
+        }
+        org_defaults::require_overridable(availability.owner, OrgSetting::Locale)?;
+        
+        availability.locale = locale;
+        availability.updated_at = time();
+        
+        delegations::record(caller, availability.owner, format!("set locale of {}", id));
+        map.insert(id.clone(), availability);
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Tenant Administration
+// ============================================================================
+
+/// Every availability owned by a member of the tenant
+pub fn list_tenant_availabilities(tenant_id: &str) -> Vec<Availability> {
+    AVAILABILITIES.with(|a| {
+        a.borrow()
+            .iter()
+            .filter(|(_, availability)| tenants::tenant_of(availability.owner) == tenant_id)
+            .map(|(_, availability)| availability)
+            .collect()
+    })
+}
+
+/// `list_tenant_availabilities` one page at a time, for browsing large orgs
+pub fn list_tenant_availabilities_page(tenant_id: &str, cursor: Option<String>) -> Result<AvailabilityPage, String> {
+    let (availabilities, next_cursor) = cursor::page(
+        list_tenant_availabilities(tenant_id),
+        cursor,
+        "tenant_availabilities",
+        tenant_id,
+        PAGE_SIZE,
+        |a| a.id.clone(),
+    )?;
+    Ok(AvailabilityPage {
+        availabilities,
+        truncated: next_cursor.is_some(),
+        next_cursor,
+    })
+}
+
+/// Moderation: remove an availability belonging to the tenant
+pub fn delete_tenant_availability(tenant_id: &str, id: String) -> Result<(), String> {
+    let owner = get_availability(id.clone())?.owner;
+    if tenants::tenant_of(owner) != tenant_id {
+        return Err(error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"));
+    }
+    delete_availability(owner, id)
+}
+
+// ============================================================================
+// Batch Search Functions (Optimized for Multiple Users)
+// ============================================================================
+
+/// Batch search availabilities by multiple emails
+/// One index lookup per entry - scalable to millions of users
+pub fn search_by_emails(viewer: Principal, emails: Vec<String>, origin: Option<&str>) -> Vec<Vec<Availability>> {
+    let tenant_id = tenants::viewer_tenant(viewer, origin);
+    emails.iter()
+        .map(|email| {
+            let principal_opt = migration::lookup_email(&index_key(&tenant_id, email));
+            
+            match principal_opt {
+                Some(principal) => list_public_availabilities(viewer, origin, principal),
+                None => vec![],
+            }
+        })
+        .collect()
+}
+
+/// Batch search availabilities by multiple usernames
+/// One index lookup per entry - scalable to millions of users
+pub fn search_by_usernames(viewer: Principal, usernames: Vec<String>, origin: Option<&str>) -> Vec<Vec<Availability>> {
+    let tenant_id = tenants::viewer_tenant(viewer, origin);
+    usernames.iter()
+        .map(|username| {
+            let principal_opt = migration::lookup_username(&index_key(&tenant_id, username));
+            
+            match principal_opt {
+                Some(principal) => list_public_availabilities(viewer, origin, principal),
+                None => vec![],
+            }
+        })
+        .collect()
+}
+
+/// Set an availability as favorite and move it to the front of the owner's
+/// list. Only one availability can be favorite at a time. Touches the owner's
+/// list and favorite entry, never the availability records.
+pub fn set_favorite_availability(caller: Principal, id: String) -> Result<(), String> {
+    // Verify the availability exists and is owned by caller
+    let target_availability = AVAILABILITIES.with(|a| {
+        a.borrow()
+            .get(&id)
+            .ok_or_else(|| error_codes::coded(ErrorCode::AvailNotFound, "Availability not found"))
+    })?;
+    
+    if target_availability.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can set favorite"));
+    }
+    
+    let mut ids = USER_AVAILABILITIES.with(|ua| ua.borrow().get(&caller))
+        .map(|v| v.0)
+        .unwrap_or_default();
+    
+    if ids.is_empty() {
+        return Err("No availabilities found".to_string());
+    }
+    
+    // First favorite since USER_FAVORITES existed: adopt the order stored on the records
+    if !USER_FAVORITES.with(|f| f.borrow().contains_key(&caller)) {
+        AVAILABILITIES.with(|a| {
+            let map = a.borrow();
+            ids.sort_by_key(|avail_id| map.get(avail_id).map_or(u32::MAX, |avail| avail.display_order));
+        });
+    }
+    
+    ids.retain(|avail_id| avail_id != &id);
+    ids.insert(0, id.clone());
+    USER_AVAILABILITIES.with(|ua| ua.borrow_mut().insert(caller, StringVec(ids)));
+    USER_FAVORITES.with(|f| f.borrow_mut().insert(caller, id.clone()));
     
     ic_cdk::println!("⭐ Set favorite availability: {}", id);
     Ok(())
 }
+
+/// Remove placeholders of undecodable availabilities, returning their IDs
+pub fn remove_unreadable() -> Vec<String> {
+    AVAILABILITIES.with(|a| {
+        let mut map = a.borrow_mut();
+        let ids: Vec<String> = map.iter().filter(|(_, av)| av.is_unreadable()).map(|(id, _)| id).collect();
+        for id in &ids {
+            map.remove(id);
+        }
+        ids
+    })
+}
+
+/// Fingerprints of the availability map and owner index, for upgrade checks
+pub fn upgrade_digests() -> Vec<(&'static str, RunningDigest)> {
+    vec![
+        ("AVAILABILITIES", AVAILABILITIES.with(|m| upgrade_check::digest(m.borrow().inner()))),
+        ("USER_AVAILABILITIES", USER_AVAILABILITIES.with(|m| upgrade_check::digest(m.borrow().inner()))),
+    ]
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    AVAILABILITIES.with(|m| storage::map_stats(m.borrow().inner()))
+}
+
+// ============================================================================
+// Index Rebuild
+// ============================================================================
+
+/// Put the next `limit` availabilities after `after` back into their owner's
+/// list, the search indices and the counters
+pub fn rebuild_chunk(after: Option<String>, limit: usize) -> RebuildChunk {
+    let start = after.map_or(RangeBound::Unbounded, RangeBound::Excluded);
+    let chunk: Vec<(String, Availability)> = AVAILABILITIES.with(|a| {
+        a.borrow().range((start, RangeBound::Unbounded)).take(limit).collect()
+    });
+    let mut fixed = 0;
+    for (id, availability) in chunk.iter().filter(|(_, availability)| !availability.is_unreadable()) {
+        USER_AVAILABILITIES.with(|ua| {
+            let mut map = ua.borrow_mut();
+            let mut ids = map.get(&availability.owner).map(|v| v.0).unwrap_or_default();
+            if !ids.contains(id) {
+                ids.push(id.clone());
+                map.insert(availability.owner, StringVec(ids));
+                fixed += 1;
+            }
+        });
+        index_owner(availability);
+        counters::count_availability(availability.owner);
+    }
+    RebuildChunk {
+        next: if chunk.len() == limit { chunk.last().map(|(id, _)| id.clone()) } else { None },
+        scanned: chunk.len() as u64,
+        fixed,
+    }
+}
+
+/// Drop IDs of missing, re-owned or duplicated availabilities from the next
+/// `limit` owner lists after `after`, keeping the rest in order
+pub fn prune_owner_lists_chunk(after: Option<Principal>, limit: usize) -> RebuildChunk {
+    let start = after.map_or(RangeBound::Unbounded, RangeBound::Excluded);
+    let lists: Vec<(Principal, StringVec)> = USER_AVAILABILITIES.with(|ua| {
+        ua.borrow().range((start, RangeBound::Unbounded)).take(limit).collect()
+    });
+    let mut fixed = 0;
+    for (owner, ids) in &lists {
+        let mut kept: Vec<String> = Vec::with_capacity(ids.0.len());
+        for id in &ids.0 {
+            let owned = AVAILABILITIES.with(|a| a.borrow().get(id)).is_some_and(|a| a.owner == *owner);
+            if owned && !kept.contains(id) {
+                kept.push(id.clone());
+            }
+        }
+        if kept.len() != ids.0.len() {
+            fixed += (ids.0.len() - kept.len()) as u64;
+            USER_AVAILABILITIES.with(|ua| ua.borrow_mut().insert(*owner, StringVec(kept)));
+        }
+    }
+    RebuildChunk {
+        next: if lists.len() == limit { lists.last().map(|(owner, _)| owner.to_text()) } else { None },
+        scanned: lists.len() as u64,
+        fixed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    // Wednesday 2025-01-01 00:00 UTC
+    const DAY_START: u64 = 1_735_689_600;
+
+    fn availability(busy_privacy: BusyPrivacy) -> Availability {
+        Availability {
+            id: "quantized".to_string(),
+            owner: Principal::from_slice(&[1; 29]),
+            owner_email: None,
+            owner_name: None,
+            title: "Intro call".to_string(),
+            description: String::new(),
+            slots: vec![TimeSlot { day_of_week: 3, start_time: 9 * 60, end_time: 17 * 60 }],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            busy_times: Some(vec![
+                BusyTimeBlock { start_time: DAY_START + 10 * 3600 + 7 * 60, end_time: DAY_START + 10 * 3600 + 52 * 60 },
+                BusyTimeBlock { start_time: DAY_START + 14 * 3600 + 20 * 60, end_time: DAY_START + 14 * 3600 + 40 * 60 },
+            ]),
+            is_favorite: false,
+            display_order: 0,
+            busy_privacy: Some(busy_privacy),
+            cancellation_policy: None,
+            locale: None,
+            durations: None,
+            kiosk: None,
+            collaborators: None,
+            vip: None,
+            alignment: None,
+            meeting_url: None,
+            event_fields: None,
+            seats: None,
+            follow_ups: None,
+        }
+    }
+
+    fn slot_edges(availability: &Availability) -> Vec<u64> {
+        slots_for(availability, Principal::anonymous(), DAY_START, DAY_START + 86_400, None, None)
+            .unwrap()
+            .into_iter()
+            .flat_map(|slot| [slot.start_time, slot.end_time])
+            .collect()
+    }
+
+    #[test]
+    fn quantized_busy_times_keep_every_slot_edge_on_the_grid() {
+        clock::install(MockClock::at(DAY_START * 1_000_000_000));
+
+        let exact = slot_edges(&availability(BusyPrivacy::Exact));
+        assert!(exact.iter().any(|edge| edge % BUSY_QUANTUM_SECS != 0));
+
+        let quantized = slot_edges(&availability(BusyPrivacy::Quantized));
+        assert!(quantized.iter().all(|edge| edge % BUSY_QUANTUM_SECS == 0), "{:?}", quantized);
+        assert_eq!(quantized, vec![
+            DAY_START + 9 * 3600, DAY_START + 10 * 3600,
+            DAY_START + 11 * 3600, DAY_START + 14 * 3600,
+            DAY_START + 15 * 3600, DAY_START + 17 * 3600,
+        ]);
+    }
+
+    fn busy_spans(availability: &Availability) -> Option<Vec<(u64, u64)>> {
+        availability.busy_times.as_ref()
+            .map(|blocks| blocks.iter().map(|b| (b.start_time, b.end_time)).collect())
+    }
+
+    #[test]
+    fn public_view_applies_busy_privacy_to_guests_only() {
+        let guest = Principal::from_slice(&[2; 29]);
+        let exact = availability(BusyPrivacy::Exact);
+        let owner = exact.owner;
+
+        assert_eq!(busy_spans(&public_view(exact.clone(), guest)), busy_spans(&exact));
+
+        let quantized = busy_spans(&public_view(availability(BusyPrivacy::Quantized), guest)).unwrap();
+        assert!(quantized.iter().all(|(start, end)| start % BUSY_QUANTUM_SECS == 0 && end % BUSY_QUANTUM_SECS == 0));
+
+        assert_eq!(busy_spans(&public_view(availability(BusyPrivacy::FreeSlotsOnly), guest)), None);
+        // The owner keeps seeing the exact blocks whatever guests get
+        assert_eq!(busy_spans(&public_view(availability(BusyPrivacy::FreeSlotsOnly), owner)), busy_spans(&exact));
+    }
+
+    #[test]
+    fn cancellation_policies_keep_the_partial_window_inside_the_free_one() {
+        let policy = |free_until_hours, partial_until_hours, partial_refund_percent| CancellationPolicy {
+            free_until_hours,
+            partial_until_hours,
+            partial_refund_percent,
+        };
+
+        assert!(validate_cancellation_policy(&policy(48, 24, 50)).is_ok());
+        assert!(validate_cancellation_policy(&policy(24, 24, 100)).is_ok());
+        assert!(validate_cancellation_policy(&policy(0, 0, 0)).is_ok());
+        assert!(validate_cancellation_policy(&policy(24, 48, 50)).is_err());
+        assert!(validate_cancellation_policy(&policy(48, 24, 101)).is_err());
+    }
+}
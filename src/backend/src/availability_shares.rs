@@ -0,0 +1,356 @@
+//! Availability templates shared with a teammate. The sender's schedule and
+//! settings (slots, durations, policies, questions, follow-ups) are copied
+//! into a pending share addressed to a principal or an email; the recipient
+//! accepts it into a new availability of their own. Bookings, collaborators,
+//! VIP codes, kiosk devices, the meeting link and calendar busy times stay
+//! with the sender.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{
+    self, Availability, BusyPrivacy, CancellationPolicy, CreateAvailabilityRequest, DurationOption,
+    EventField, SlotAlignment, TimeSlot, AVAILABILITIES,
+};
+use crate::followups::FollowUp;
+use crate::i18n::Locale;
+use crate::entropy;
+use crate::org_defaults;
+use crate::quotas::{self, QuotaKind};
+use crate::verified_emails;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Pending shares one user may have outstanding
+const MAX_PENDING_PER_SENDER: usize = 50;
+const MAX_EMAIL_LEN: usize = 254;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ShareRecipient {
+    Principal(Principal),
+    Email(String), // Lowercased; matched against the recipient's verified sign-in email
+}
+
+/// Everything copied into the recipient's availability
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AvailabilityTemplate {
+    pub title: String,
+    pub description: String,
+    pub slots: Vec<TimeSlot>,
+    pub timezone: String,
+    pub busy_privacy: Option<BusyPrivacy>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub locale: Option<Locale>,
+    pub durations: Option<Vec<DurationOption>>,
+    pub alignment: Option<SlotAlignment>,
+    pub event_fields: Option<Vec<EventField>>,
+    pub seats: Option<u32>,
+    pub follow_ups: Option<Vec<FollowUp>>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct AvailabilityShare {
+    pub id: String,
+    pub from: Principal,
+    pub to: ShareRecipient,
+    pub source_availability_id: String,
+    pub template: AvailabilityTemplate,
+    pub created_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl AvailabilityShare {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Sent by and to nobody, so no one can accept it.
+    fn unreadable() -> Self {
+        AvailabilityShare {
+            id: String::new(),
+            from: Principal::anonymous(),
+            to: ShareRecipient::Principal(Principal::anonymous()),
+            source_availability_id: String::new(),
+            template: AvailabilityTemplate {
+                title: String::new(),
+                description: String::new(),
+                slots: vec![],
+                timezone: "UTC".to_string(),
+                busy_privacy: None,
+                cancellation_policy: None,
+                locale: None,
+                durations: None,
+                alignment: None,
+                event_fields: None,
+                seats: None,
+                follow_ups: None,
+            },
+            created_at: 0,
+        }
+    }
+}
+
+impl Storable for AvailabilityShare {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "AvailabilityShare").unwrap_or_else(AvailabilityShare::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "<recipient key>|<share id>" -> pending share; removed once accepted,
+    // declined or revoked
+    static AVAILABILITY_SHARES: RefCell<StableBTreeMap<String, AvailabilityShare, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(78)))
+        )
+    );
+}
+
+pub fn clear() {
+    AVAILABILITY_SHARES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn recipient_key(to: &ShareRecipient) -> String {
+    match to {
+        ShareRecipient::Principal(principal) => format!("p:{}", principal),
+        ShareRecipient::Email(email) => format!("e:{}", email),
+    }
+}
+
+fn key(to: &ShareRecipient, id: &str) -> String {
+    format!("{}|{}", recipient_key(to), id)
+}
+
+/// A principal in text form, else an email address
+fn parse_recipient(to: &str) -> Result<ShareRecipient, String> {
+    let to = to.trim();
+    if let Ok(principal) = Principal::from_text(to) {
+        return Ok(ShareRecipient::Principal(principal));
+    }
+    if to.len() > MAX_EMAIL_LEN || !to.contains('@') {
+        return Err("Recipient must be a principal or an email address".to_string());
+    }
+    Ok(ShareRecipient::Email(to.to_lowercase()))
+}
+
+fn generate_share_id() -> Result<String, String> {
+    entropy::next_token(6)
+}
+
+fn template_of(availability: &Availability) -> AvailabilityTemplate {
+    AvailabilityTemplate {
+        title: availability.title.clone(),
+        description: availability.description.clone(),
+        slots: availability.slots.clone(),
+        timezone: availability.timezone.clone(),
+        busy_privacy: availability.busy_privacy,
+        cancellation_policy: availability.cancellation_policy.clone(),
+        locale: availability.locale,
+        durations: availability.durations.clone(),
+        alignment: availability.alignment.clone(),
+        event_fields: availability.event_fields.clone(),
+        seats: availability.seats,
+        follow_ups: availability.follow_ups.clone(),
+    }
+}
+
+fn shares_with_prefix(prefix: &str) -> Vec<AvailabilityShare> {
+    AVAILABILITY_SHARES.with(|s| {
+        s.borrow()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(_, share)| share)
+            .collect()
+    })
+}
+
+/// Recipient keys the caller answers to: their principal and verified email
+fn recipients_of(caller: Principal) -> Vec<ShareRecipient> {
+    let mut recipients = vec![ShareRecipient::Principal(caller)];
+    if let Some(email) = verified_emails::get(caller) {
+        recipients.push(ShareRecipient::Email(email));
+    }
+    recipients
+}
+
+fn find_incoming(caller: Principal, id: &str) -> Result<AvailabilityShare, String> {
+    recipients_of(caller)
+        .iter()
+        .find_map(|to| AVAILABILITY_SHARES.with(|s| s.borrow().get(&key(to, id))))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Share not found"))
+}
+
+fn remove(share: &AvailabilityShare) {
+    AVAILABILITY_SHARES.with(|s| s.borrow_mut().remove(&key(&share.to, &share.id)));
+}
+
+// ============================================================================
+// Sharing
+// ============================================================================
+
+/// Send a copy of the availability's template to a principal or email
+pub fn share_template(caller: Principal, id: String, to: String) -> Result<AvailabilityShare, String> {
+    let availability = availabilities::get_availability(id)?;
+    if !availabilities::can_edit(caller, &availability) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can share this availability"));
+    }
+    let to = parse_recipient(&to)?;
+    if recipients_of(caller).contains(&to) {
+        return Err("Cannot share a template with yourself".to_string());
+    }
+    if list_outgoing(caller).len() >= MAX_PENDING_PER_SENDER {
+        return Err(format!("At most {} pending shares; revoke some first", MAX_PENDING_PER_SENDER));
+    }
+
+    let share = AvailabilityShare {
+        id: generate_share_id()?,
+        from: caller,
+        to,
+        source_availability_id: availability.id.clone(),
+        template: template_of(&availability),
+        created_at: time(),
+    };
+    AVAILABILITY_SHARES.with(|s| s.borrow_mut().insert(key(&share.to, &share.id), share.clone()));
+    ic_cdk::println!("📤 Shared template of {} as {}", availability.id, share.id);
+    Ok(share)
+}
+
+/// Shares waiting for the caller, newest first
+pub fn list_incoming(caller: Principal) -> Vec<AvailabilityShare> {
+    let mut shares: Vec<AvailabilityShare> = recipients_of(caller)
+        .iter()
+        .flat_map(|to| shares_with_prefix(&format!("{}|", recipient_key(to))))
+        .collect();
+    shares.sort_by_key(|share| std::cmp::Reverse(share.created_at));
+    shares
+}
+
+/// Shares the caller sent that are still pending
+pub fn list_outgoing(caller: Principal) -> Vec<AvailabilityShare> {
+    AVAILABILITY_SHARES.with(|s| {
+        s.borrow()
+            .iter()
+            .map(|(_, share)| share)
+            .filter(|share| share.from == caller)
+            .collect()
+    })
+}
+
+/// Create an availability of the caller's own from a share sent to them
+pub fn accept(caller: Principal, id: String) -> Result<Availability, String> {
+    let share = find_incoming(caller, &id)?;
+    quotas::check_count(caller, QuotaKind::Availabilities, availabilities::count_user_availabilities(caller))?;
+
+    let template = share.template.clone();
+    let mut availability = availabilities::create_availability(caller, CreateAvailabilityRequest {
+        title: template.title,
+        description: template.description,
+        slots: template.slots,
+        timezone: template.timezone,
+        owner_email: verified_emails::get(caller),
+        owner_name: None,
+        busy_times: None,
+    })?;
+    availability.busy_privacy = template.busy_privacy;
+    availability.cancellation_policy = template.cancellation_policy;
+    availability.locale = template.locale;
+    availability.durations = template.durations;
+    availability.alignment = template.alignment;
+    availability.event_fields = template.event_fields;
+    availability.seats = template.seats;
+    availability.follow_ups = template.follow_ups;
+    org_defaults::enforce(&mut availability);
+    AVAILABILITIES.with(|a| a.borrow_mut().insert(availability.id.clone(), availability.clone()));
+
+    remove(&share);
+    ic_cdk::println!("📥 Share {} accepted as availability {}", share.id, availability.id);
+    Ok(availability)
+}
+
+pub fn decline(caller: Principal, id: String) -> Result<(), String> {
+    remove(&find_incoming(caller, &id)?);
+    Ok(())
+}
+
+/// Withdraw a share the caller sent before it is accepted
+pub fn revoke(caller: Principal, id: String) -> Result<(), String> {
+    let share = list_outgoing(caller)
+        .into_iter()
+        .find(|share| share.id == id)
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Share not found"))?;
+    remove(&share);
+    Ok(())
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Shares the principal sent or that are addressed to it
+pub fn count_owned(principal: Principal) -> u32 {
+    AVAILABILITY_SHARES.with(|s| {
+        s.borrow().iter().filter(|(_, share)| share.from == principal || share.to == ShareRecipient::Principal(principal)).count() as u32
+    })
+}
+
+/// Shares `from` sent now come from `to`, and shares waiting for `from`
+/// wait for `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let shares: Vec<AvailabilityShare> = AVAILABILITY_SHARES.with(|s| {
+        s.borrow()
+            .iter()
+            .map(|(_, share)| share)
+            .filter(|share| share.from == from || share.to == ShareRecipient::Principal(from))
+            .collect()
+    });
+    for mut share in shares.iter().cloned() {
+        remove(&share);
+        if share.from == from {
+            share.from = to;
+        }
+        if share.to == ShareRecipient::Principal(from) {
+            share.to = ShareRecipient::Principal(to);
+        }
+        AVAILABILITY_SHARES.with(|s| s.borrow_mut().insert(key(&share.to, &share.id), share));
+    }
+    shares.len() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipients_are_principals_or_lowercased_emails() {
+        let principal = Principal::from_slice(&[1, 2, 3]);
+        assert_eq!(parse_recipient(&principal.to_text()), Ok(ShareRecipient::Principal(principal)));
+        assert_eq!(parse_recipient(" Ana@Example.com "), Ok(ShareRecipient::Email("ana@example.com".into())));
+        assert!(parse_recipient("not a recipient").is_err());
+    }
+}
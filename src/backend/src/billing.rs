@@ -0,0 +1,474 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::plans::{self, Feature, PlanSource, PlanTier};
+use crate::{ledger, principal_moves, quarantine, regions, service_status};
+
+const DAY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Length of one paid period
+const BILLING_PERIOD_NS: u64 = 30 * DAY_NS;
+
+/// How long to wait before retrying a failed renewal
+const RETRY_INTERVAL_NS: u64 = DAY_NS;
+
+/// How often the timer looks for due subscriptions
+const BILLING_TIMER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BillingConfig {
+    pub ledger_canister: Option<Principal>, // None = on-chain billing disabled
+    pub monthly_price: u64,                 // In the ledger's base units (e.g. e8s)
+    pub grace_period_days: u32,
+}
+
+impl Default for BillingConfig {
+    fn default() -> Self {
+        BillingConfig {
+            ledger_canister: None,
+            monthly_price: 0,
+            grace_period_days: 7,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SubscriptionStatus {
+    Active,
+    PastDue,   // Renewal failed, still Pro until grace_until
+    Cancelled,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Subscription {
+    pub owner: Principal,
+    pub status: SubscriptionStatus,
+    pub ledger_canister: Principal,
+    pub amount: u64, // Price locked in at subscription time
+    pub created_at: u64,
+    pub current_period_end: u64,
+    pub next_charge_at: u64,
+    pub failed_attempts: u32,
+    pub grace_until: Option<u64>,
+    pub last_block_index: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for BillingConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "BillingConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Subscription {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Cancelled and owned by nobody, so it is never charged.
+    fn unreadable() -> Self {
+        Subscription {
+            owner: Principal::anonymous(),
+            status: SubscriptionStatus::Cancelled,
+            ledger_canister: Principal::anonymous(),
+            amount: 0,
+            created_at: 0,
+            current_period_end: 0,
+            next_charge_at: 0,
+            failed_attempts: 0,
+            grace_until: None,
+            last_block_index: None,
+            last_error: None,
+        }
+    }
+}
+
+impl Storable for Subscription {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Subscription").unwrap_or_else(Subscription::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static BILLING_CONFIG: RefCell<StableCell<BillingConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6))),
+            BillingConfig::default(),
+        ).expect("Failed to init billing config")
+    );
+
+    static SUBSCRIPTIONS: RefCell<StableBTreeMap<Principal, Subscription, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7)))
+        )
+    );
+
+    // Principals with a ledger call in flight, so a user call and the timer
+    // can never charge the same subscription twice
+    static CHARGES_IN_FLIGHT: RefCell<HashSet<Principal>> = RefCell::new(HashSet::new());
+}
+
+pub fn clear() {
+    SUBSCRIPTIONS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn begin_charge(principal: Principal) -> Result<(), String> {
+    CHARGES_IN_FLIGHT.with(|c| {
+        if !c.borrow_mut().insert(principal) {
+            return Err("A charge is already in progress".to_string());
+        }
+        Ok(())
+    })
+}
+
+fn end_charge(principal: Principal) {
+    CHARGES_IN_FLIGHT.with(|c| {
+        c.borrow_mut().remove(&principal);
+    });
+}
+
+fn save(subscription: &Subscription) {
+    SUBSCRIPTIONS.with(|s| {
+        s.borrow_mut().insert(subscription.owner, subscription.clone());
+    });
+}
+
+// ============================================================================
+// Config
+// ============================================================================
+
+pub fn get_config() -> BillingConfig {
+    BILLING_CONFIG.with(|c| c.borrow().get().clone())
+}
+
+pub fn set_config(config: BillingConfig) -> Result<(), String> {
+    BILLING_CONFIG.with(|c| {
+        c.borrow_mut()
+            .set(config)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store billing config: {:?}", e))
+    })
+}
+
+// ============================================================================
+// Subscriptions
+// ============================================================================
+
+pub fn get_subscription(principal: Principal) -> Option<Subscription> {
+    SUBSCRIPTIONS.with(|s| s.borrow().get(&principal))
+}
+
+/// Start a Pro subscription. The caller must have called icrc2_approve on the
+/// configured ledger for at least the monthly price (plus fee) beforehand.
+/// Subscribing again before a cancelled subscription's paid period ends
+/// reactivates it instead, and the next charge is the renewal.
+pub async fn subscribe(caller: Principal) -> Result<Subscription, String> {
+    regions::require_available(caller, Feature::Payments)?;
+    let config = get_config();
+    let ledger_canister = config.ledger_canister
+        .ok_or("On-chain billing is not configured")?;
+
+    if let Some(mut existing) = get_subscription(caller) {
+        if existing.status != SubscriptionStatus::Cancelled {
+            return Err("Already subscribed".to_string());
+        }
+        if reactivate(&mut existing, time()) {
+            save(&existing);
+            plans::set_plan(caller, PlanTier::Pro, PlanSource::Icrc, Some(existing.current_period_end));
+            ic_cdk::println!("🔁 [billing] {} resubscribed before their paid period ended", caller.to_text());
+            return Ok(existing);
+        }
+    }
+
+    begin_charge(caller)?;
+    let result = ledger::transfer_from(ledger_canister, caller, config.monthly_price, time()).await;
+    end_charge(caller);
+    let block_index = result?;
+
+    let now = time();
+    let subscription = Subscription {
+        owner: caller,
+        status: SubscriptionStatus::Active,
+        ledger_canister,
+        amount: config.monthly_price,
+        created_at: now,
+        current_period_end: now + BILLING_PERIOD_NS,
+        next_charge_at: now + BILLING_PERIOD_NS,
+        failed_attempts: 0,
+        grace_until: None,
+        last_block_index: Some(block_index),
+        last_error: None,
+    };
+    save(&subscription);
+    plans::set_plan(caller, PlanTier::Pro, PlanSource::Icrc, Some(subscription.current_period_end));
+
+    ic_cdk::println!("✅ [billing] {} subscribed (block {})", caller.to_text(), block_index);
+    Ok(subscription)
+}
+
+/// Undo a cancellation whose paid period is still running: renewals resume
+/// at the period end, at the price locked in then, with nothing charged now
+fn reactivate(subscription: &mut Subscription, now: u64) -> bool {
+    if subscription.status != SubscriptionStatus::Cancelled || subscription.current_period_end <= now {
+        return false;
+    }
+    subscription.status = SubscriptionStatus::Active;
+    subscription.next_charge_at = subscription.current_period_end;
+    subscription.failed_attempts = 0;
+    subscription.grace_until = None;
+    subscription.last_error = None;
+    true
+}
+
+/// Stop future renewals; Pro stays active until the paid period ends
+pub fn cancel(caller: Principal) -> Result<(), String> {
+    let mut subscription = get_subscription(caller)
+        .ok_or("No subscription found")?;
+
+    if subscription.status == SubscriptionStatus::Cancelled {
+        return Err("Subscription is already cancelled".to_string());
+    }
+
+    subscription.status = SubscriptionStatus::Cancelled;
+    save(&subscription);
+
+    ic_cdk::println!("🛑 [billing] {} cancelled their subscription", caller.to_text());
+    Ok(())
+}
+
+/// Whether a charge for the principal is waiting on the ledger
+pub fn charge_in_flight(principal: Principal) -> bool {
+    CHARGES_IN_FLIGHT.with(|c| c.borrow().contains(&principal))
+}
+
+pub fn count_owned(principal: Principal) -> u32 {
+    SUBSCRIPTIONS.with(|s| principal_moves::count_keyed(&s.borrow(), principal))
+}
+
+/// Carry `from`'s subscription over to `to` (the same user under a new
+/// principal) unless `to` has one of its own. Renewals then charge `to`,
+/// which needs its own ledger approval.
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let moved = SUBSCRIPTIONS.with(|s| principal_moves::move_keyed(&mut s.borrow_mut(), from, to));
+    if let Some(mut subscription) = get_subscription(to).filter(|_| moved > 0) {
+        subscription.owner = to;
+        save(&subscription);
+    }
+    moved
+}
+
+/// Attempt one renewal charge and move the subscription through
+/// Active -> PastDue -> Cancelled as charges fail past the grace period
+async fn charge_renewal(principal: Principal) {
+    let Some(subscription) = get_subscription(principal) else {
+        return;
+    };
+    if subscription.status == SubscriptionStatus::Cancelled || begin_charge(principal).is_err() {
+        return;
+    }
+
+    let result = ledger::transfer_from(
+        subscription.ledger_canister,
+        principal,
+        subscription.amount,
+        subscription.next_charge_at,
+    ).await;
+    end_charge(principal);
+
+    // Re-read: the user may have cancelled while the ledger call was in flight
+    let Some(mut subscription) = get_subscription(principal) else {
+        return;
+    };
+    let was_cancelled = subscription.status == SubscriptionStatus::Cancelled;
+    let block_index = result.as_ref().ok().copied();
+    let grace_days = get_config().grace_period_days as u64;
+    if let Some((tier, expires_at)) = settle_renewal(&mut subscription, result, time(), grace_days) {
+        plans::set_plan(principal, tier, PlanSource::Icrc, expires_at);
+    }
+    save(&subscription);
+
+    match (block_index, subscription.status) {
+        (Some(block_index), _) => ic_cdk::println!("✅ [billing] Renewed {} (block {})", principal.to_text(), block_index),
+        (None, _) if was_cancelled => ic_cdk::println!("⚠️ [billing] Renewal failed for {} after it was cancelled", principal.to_text()),
+        (None, SubscriptionStatus::Cancelled) => ic_cdk::println!("⛔ [billing] Grace period over for {}, downgraded", principal.to_text()),
+        (None, _) => ic_cdk::println!("⚠️ [billing] Renewal failed for {} (attempt {})", principal.to_text(), subscription.failed_attempts),
+    }
+}
+
+/// Fold a finished renewal charge into the subscription, as re-read after
+/// the ledger call. A cancel that landed while the charge was in flight
+/// stands: the paid period is extended but nothing renews after it. Returns
+/// the plan the owner should now have, if it changes.
+fn settle_renewal(
+    subscription: &mut Subscription,
+    result: Result<u64, String>,
+    now: u64,
+    grace_days: u64,
+) -> Option<(PlanTier, Option<u64>)> {
+    let cancelled = subscription.status == SubscriptionStatus::Cancelled;
+    match result {
+        Ok(block_index) => {
+            if !cancelled {
+                subscription.status = SubscriptionStatus::Active;
+            }
+            subscription.current_period_end = subscription.current_period_end.max(now) + BILLING_PERIOD_NS;
+            subscription.next_charge_at = subscription.current_period_end;
+            subscription.failed_attempts = 0;
+            subscription.grace_until = None;
+            subscription.last_block_index = Some(block_index);
+            subscription.last_error = None;
+            Some((PlanTier::Pro, Some(subscription.current_period_end)))
+        }
+        Err(e) if cancelled => {
+            subscription.last_error = Some(e);
+            None
+        }
+        Err(e) => {
+            let grace_until = *subscription.grace_until.get_or_insert(now + grace_days * DAY_NS);
+            subscription.failed_attempts += 1;
+            subscription.last_error = Some(e);
+
+            if now >= grace_until {
+                subscription.status = SubscriptionStatus::Cancelled;
+                Some((PlanTier::Free, None))
+            } else {
+                subscription.status = SubscriptionStatus::PastDue;
+                subscription.next_charge_at = now + RETRY_INTERVAL_NS;
+                Some((PlanTier::Pro, Some(grace_until)))
+            }
+        }
+    }
+}
+
+/// Charge every subscription whose renewal is due
+async fn process_due_subscriptions() {
+    let now = time();
+    let due: Vec<Principal> = SUBSCRIPTIONS.with(|s| {
+        s.borrow()
+            .iter()
+            .filter(|(_, sub)| sub.status != SubscriptionStatus::Cancelled && sub.next_charge_at <= now)
+            .map(|(principal, _)| principal)
+            .collect()
+    });
+
+    if !due.is_empty() {
+        ic_cdk::println!("💳 [billing] Processing {} due subscriptions", due.len());
+    }
+
+    for principal in due {
+        charge_renewal(principal).await;
+    }
+}
+
+/// Register the renewal timer (timers don't survive upgrades, so this runs
+/// from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("billing", BILLING_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(BILLING_TIMER_INTERVAL, || {
+        service_status::heartbeat("billing", BILLING_TIMER_INTERVAL);
+        ic_cdk::spawn(process_due_subscriptions());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cancelled(current_period_end: u64) -> Subscription {
+        Subscription {
+            owner: Principal::from_slice(&[1; 29]),
+            status: SubscriptionStatus::Cancelled,
+            ledger_canister: Principal::from_slice(&[2; 10]),
+            amount: 500_000,
+            created_at: 0,
+            current_period_end,
+            next_charge_at: current_period_end,
+            failed_attempts: 0,
+            grace_until: None,
+            last_block_index: Some(3),
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn resubscribing_inside_the_paid_period_charges_only_at_renewal() {
+        let now = 10 * DAY_NS;
+        let mut subscription = cancelled(now + 5 * DAY_NS);
+        assert!(reactivate(&mut subscription, now));
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+        assert_eq!(subscription.next_charge_at, now + 5 * DAY_NS);
+        assert_eq!(subscription.current_period_end, now + 5 * DAY_NS);
+        assert_eq!(subscription.amount, 500_000);
+
+        // Once the period is over a new subscription is charged up front
+        let mut lapsed = cancelled(now);
+        assert!(!reactivate(&mut lapsed, now));
+        assert_eq!(lapsed.status, SubscriptionStatus::Cancelled);
+    }
+
+    #[test]
+    fn a_cancel_during_the_renewal_charge_stands() {
+        let now = 40 * DAY_NS;
+        // The timer started the charge, then the user cancelled before the ledger answered
+        let mut subscription = cancelled(now);
+        let plan = settle_renewal(&mut subscription, Ok(4), now, 7);
+
+        assert_eq!(subscription.status, SubscriptionStatus::Cancelled);
+        assert_eq!(subscription.current_period_end, now + BILLING_PERIOD_NS);
+        assert_eq!(subscription.last_block_index, Some(4));
+        assert_eq!(plan, Some((PlanTier::Pro, Some(now + BILLING_PERIOD_NS))));
+
+        // A failed charge after the cancel leaves it cancelled and the plan alone
+        let mut subscription = cancelled(now);
+        assert_eq!(settle_renewal(&mut subscription, Err("InsufficientFunds".to_string()), now, 7), None);
+        assert_eq!(subscription.status, SubscriptionStatus::Cancelled);
+        assert_eq!(subscription.failed_attempts, 0);
+    }
+
+    #[test]
+    fn a_renewal_without_a_cancel_stays_active() {
+        let now = 40 * DAY_NS;
+        let mut subscription = cancelled(now);
+        subscription.status = SubscriptionStatus::PastDue;
+        subscription.failed_attempts = 2;
+        settle_renewal(&mut subscription, Ok(5), now, 7);
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+        assert_eq!(subscription.failed_attempts, 0);
+        assert_eq!(subscription.next_charge_at, now + BILLING_PERIOD_NS);
+    }
+}
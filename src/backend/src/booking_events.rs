@@ -0,0 +1,180 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::bookings::{Booking, BookingPayment, BookingRefund, BookingStatus};
+use crate::quarantine;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Everything that ever happens to a booking. The booking map in bookings.rs
+/// is a projection of this log; `project` is the only way it changes.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum BookingEvent {
+    Created(Box<Booking>),              // Held (Pending) or Confirmed right away
+    PaymentCaptured(BookingPayment),    // Pending -> Confirmed
+    HoldReleased,                       // Payment failed; the booking disappears
+    Cancelled { by: Principal },
+    Refunded(BookingRefund),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BookingEventRecord {
+    pub seq: u64,
+    pub booking_id: String,
+    pub at: u64,
+    pub event: BookingEvent,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl BookingEventRecord {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Belongs to no booking, so replays skip it.
+    fn unreadable() -> Self {
+        BookingEventRecord {
+            seq: 0,
+            booking_id: String::new(),
+            at: 0,
+            event: BookingEvent::HoldReleased,
+        }
+    }
+}
+
+impl Storable for BookingEventRecord {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "BookingEventRecord").unwrap_or_else(BookingEventRecord::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Sequence number -> event, append-only
+    static BOOKING_EVENTS: RefCell<StableBTreeMap<u64, BookingEventRecord, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(40)))
+        )
+    );
+}
+
+// ============================================================================
+// Log
+// ============================================================================
+
+/// Append an event and return its record
+pub fn append(booking_id: &str, event: BookingEvent) -> BookingEventRecord {
+    BOOKING_EVENTS.with(|e| {
+        let mut log = e.borrow_mut();
+        let seq = log.last_key_value().map(|(seq, _)| seq + 1).unwrap_or(0);
+        let record = BookingEventRecord { seq, booking_id: booking_id.to_string(), at: time(), event };
+        log.insert(seq, record.clone());
+        record
+    })
+}
+
+/// The state a booking is in after `event`, given its state before
+pub fn project(current: Option<Booking>, record: &BookingEventRecord) -> Option<Booking> {
+    let mut booking = match (&record.event, current) {
+        (BookingEvent::Created(booking), _) => return Some(booking.as_ref().clone()),
+        (BookingEvent::HoldReleased, _) => return None,
+        (_, None) => return None,
+        (_, Some(booking)) => booking,
+    };
+    match &record.event {
+        BookingEvent::PaymentCaptured(payment) => {
+            booking.status = BookingStatus::Confirmed;
+            booking.payment = Some(payment.clone());
+        }
+        BookingEvent::Cancelled { by } => {
+            booking.status = BookingStatus::Cancelled;
+            booking.cancelled_by = Some(*by);
+            booking.cancelled_at = Some(record.at);
+        }
+        BookingEvent::Refunded(refund) => booking.refund = Some(refund.clone()),
+        BookingEvent::Created(_) | BookingEvent::HoldReleased => {}
+    }
+    booking.updated_at = record.at;
+    Some(booking)
+}
+
+/// Events of one booking, oldest first
+pub fn history(booking_id: &str) -> Vec<BookingEventRecord> {
+    BOOKING_EVENTS.with(|e| {
+        e.borrow()
+            .iter()
+            .filter(|(_, record)| record.booking_id == booking_id)
+            .map(|(_, record)| record)
+            .collect()
+    })
+}
+
+/// Fold the whole log into the current state of every booking it mentions
+/// (`None` = the booking no longer exists). Bookings created before the log
+/// existed have no events and are not included.
+pub fn replay() -> BTreeMap<String, Option<Booking>> {
+    let mut states: BTreeMap<String, Option<Booking>> = BTreeMap::new();
+    BOOKING_EVENTS.with(|e| {
+        for (_, record) in e.borrow().iter() {
+            if record.booking_id.is_empty() {
+                continue;
+            }
+            let state = states.entry(record.booking_id.clone()).or_default();
+            *state = project(state.take(), &record);
+        }
+    });
+    states
+}
+
+/// Drop every event of bookings deleted for good. The only exception to the
+/// log being append-only: permanent deletion has to reach the history too.
+pub fn forget(booking_ids: &HashSet<String>) {
+    if booking_ids.is_empty() {
+        return;
+    }
+    BOOKING_EVENTS.with(|e| {
+        let mut log = e.borrow_mut();
+        let seqs: Vec<u64> = log.iter()
+            .filter(|(_, record)| booking_ids.contains(&record.booking_id))
+            .map(|(seq, _)| seq)
+            .collect();
+        for seq in seqs {
+            log.remove(&seq);
+        }
+    });
+}
+
+pub fn len() -> u64 {
+    BOOKING_EVENTS.with(|e| e.borrow().len())
+}
+
+/// Drop every record (dev fixtures only)
+pub fn clear() {
+    BOOKING_EVENTS.with(|m| m.borrow_mut().clear_new());
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    BOOKING_EVENTS.with(|m| storage::map_stats(&m.borrow()))
+}
@@ -0,0 +1,138 @@
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use crate::availabilities::{self, CancellationPolicy, DurationOption, MAX_DURATION_MINUTES, MAX_EVENT_FIELD_VALUE_LEN, MIN_DURATION_MINUTES};
+use crate::bookings::{MAX_GUEST_EMAIL_LEN, MAX_GUEST_NAME_LEN};
+use crate::i18n::{self, Locale};
+use crate::session_links;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum FieldType {
+    Text,
+    Email,
+    UnixSeconds,
+    Minutes,
+}
+
+/// One input of the booking form and the rules the backend applies to it
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SchemaField {
+    pub name: String, // Field name in CreateBookingRequest / RedeemSessionLinkRequest; "field_answers.<key>" for event fields
+    pub field_type: FieldType,
+    pub required: bool,
+    pub max_length: Option<u32>,
+    pub description: String,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SchemaPrice {
+    pub duration_minutes: u32,
+    pub amount: u64, // In the ledger's base units
+    pub ledger_canister: Principal,
+}
+
+/// Everything a client needs to render and pre-validate a booking form the
+/// same way the backend will check it
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct BookingSchema {
+    pub availability_id: String,
+    pub title: String,
+    pub timezone: String,
+    pub locale: Locale,
+    pub fields: Vec<SchemaField>,
+    pub durations: Vec<DurationOption>, // Empty = any whole-minute length in [min, max]
+    pub min_duration_minutes: u32,
+    pub max_duration_minutes: u32,
+    pub start_step_minutes: u32,        // Offered starts are on these boundaries
+    pub lead_minutes: u32,              // Minimum notice
+    pub prices: Vec<SchemaPrice>,       // Empty = free
+    pub cancellation_policy: Option<CancellationPolicy>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn field(name: &str, field_type: FieldType, required: bool, max_length: Option<usize>, description: &str) -> SchemaField {
+    SchemaField {
+        name: name.to_string(),
+        field_type,
+        required,
+        max_length: max_length.map(|l| l as u32),
+        description: description.to_string(),
+    }
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// The booking form for an availability, or for a paid session link on it
+pub fn get_booking_schema(id: String, session_link: Option<String>) -> Result<BookingSchema, String> {
+    let availability = availabilities::get_availability(id)?;
+    let link = match session_link {
+        Some(token) => {
+            let link = session_links::get_session_link(token)?;
+            if link.availability_id != availability.id {
+                return Err("Session link belongs to a different availability".to_string());
+            }
+            Some(link)
+        }
+        None => None,
+    };
+
+    let guest_fields = [
+        field("guest_name", FieldType::Text, true, Some(MAX_GUEST_NAME_LEN), "Name shown to the host"),
+        field("guest_email", FieldType::Email, false, Some(MAX_GUEST_EMAIL_LEN), "Where confirmations are sent"),
+    ];
+    let mut fields = vec![field("start_time", FieldType::UnixSeconds, true, None, "Start of a slot from get_free_slots")];
+    let (durations, prices) = match link {
+        // Session links fix the length (or offer priced alternatives) and carry the price
+        Some(ref link) => {
+            fields.push(field("duration_minutes", FieldType::Minutes, false, None, "One of the priced lengths; defaults to the link's"));
+            let mut prices = vec![SchemaPrice {
+                duration_minutes: link.duration_minutes,
+                amount: link.price,
+                ledger_canister: link.ledger_canister,
+            }];
+            prices.extend(link.duration_prices.iter().flatten().map(|p| SchemaPrice {
+                duration_minutes: p.duration_minutes,
+                amount: p.price,
+                ledger_canister: link.ledger_canister,
+            }));
+            let durations = prices.iter().map(|p| DurationOption {
+                minutes: p.duration_minutes,
+                buffer_before_minutes: 0,
+                buffer_after_minutes: 0,
+            }).collect();
+            (durations, prices)
+        }
+        None => {
+            fields.push(field("end_time", FieldType::UnixSeconds, true, None, "End of the meeting; start + one of the durations"));
+            (availability.durations.clone().unwrap_or_default(), vec![])
+        }
+    };
+    fields.extend(guest_fields);
+    // Owner-defined questions, answered through field_answers
+    fields.extend(availability.event_fields.iter().flatten().filter(|f| f.value.is_none()).map(|f| {
+        field(&format!("field_answers.{}", f.key), FieldType::Text, false, Some(MAX_EVENT_FIELD_VALUE_LEN), &f.label)
+    }));
+
+    Ok(BookingSchema {
+        title: link.as_ref().map(|l| l.title.clone()).unwrap_or_else(|| availability.title.clone()),
+        timezone: availability.timezone.clone(),
+        locale: i18n::resolve(availability.locale, availability.owner),
+        fields,
+        durations,
+        min_duration_minutes: MIN_DURATION_MINUTES,
+        max_duration_minutes: MAX_DURATION_MINUTES,
+        start_step_minutes: (availabilities::slot_step(&availability) / 60) as u32,
+        lead_minutes: availability.alignment.as_ref().map(|a| a.lead_minutes).unwrap_or(0),
+        prices,
+        cancellation_policy: availability.cancellation_policy.clone(),
+        availability_id: availability.id,
+    })
+}
@@ -0,0 +1,1092 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::ops::Bound as RangeBound;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    Storable,
+};
+use std::borrow::Cow;
+use crate::memory::MEMORY_MANAGER;
+use crate::storage::{self, EntryStats};
+use crate::upgrade_check::{self, RunningDigest};
+use crate::watched_map::WatchedMap;
+use crate::availabilities::{self, Availability, BusyTimeBlock, CancellationPolicy, CollaboratorRole, FieldAnswer, FreeSlot, StringVec};
+use crate::{calendar_access, counters, cursor, earnings, entropy, experiments, followups, ical, ledger, legal_hold, quarantine, tenants, trash};
+use crate::integrations::{self, HookEvent};
+use crate::booking_events::{self, BookingEvent, BookingEventRecord};
+use crate::invoicing::TaxBreakdown;
+use crate::delegations::{self, DelegationScope};
+use crate::quotas::{self, QuotaKind};
+use crate::i18n::{self, Message};
+use crate::index_rebuild::RebuildChunk;
+use icrc_ledger_types::icrc1::account::Account;
+use crate::error_codes::{self, ErrorCode};
+
+/// Guest details limits, also published by get_booking_schema
+pub const MAX_GUEST_NAME_LEN: usize = 100;
+pub const MAX_GUEST_EMAIL_LEN: usize = 254;
+/// Most bookings per page of a paged listing
+const PAGE_SIZE: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BookingStatus {
+    Pending,   // Slot is held while a payment is in flight
+    Confirmed,
+    Cancelled,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BookingPayment {
+    pub ledger_canister: Principal,
+    pub amount: u64,                   // What the guest actually paid, after discounts
+    pub discount_percent: Option<u8>,
+    pub block_index: Option<u64>,      // None when a discount covered the full price
+    pub paid_at: u64,
+    pub tax: Option<TaxBreakdown>,     // None when the owner has no tax profile
+    pub stripe_payment_id: Option<String>, // Set when paid through Stripe instead of a ledger
+}
+
+/// Who settles a held booking
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PaymentMethod {
+    Ledger, // ICRC-2 transfer_from, made by the canister
+    Stripe, // Stripe Checkout, reported by webhook
+}
+
+/// What a Pending booking is waiting for; a payment only confirms the hold
+/// if it arrives through the same method for the same amount
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PaymentHold {
+    pub method: PaymentMethod,
+    pub amount: u64,      // In the currency's smallest unit
+    pub currency: String, // Ledger canister ID for Ledger, lowercase ISO 4217 code for Stripe
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct BookingRefund {
+    pub amount: u64,
+    pub block_index: Option<u64>,
+    pub error: Option<String>,         // Set when the ledger transfer failed
+    pub refunded_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Booking {
+    pub id: String,
+    pub availability_id: String,
+    pub owner: Principal,
+    pub guest: Principal,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>, // IANA zone for guest-facing times; None = the availability's
+    pub start_time: u64,               // Unix seconds
+    pub end_time: u64,                 // Unix seconds
+    pub status: BookingStatus,
+    pub payment: Option<BookingPayment>,
+    pub session_link: Option<String>,  // Token of the paid session link it was booked through
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub cancelled_by: Option<Principal>,
+    pub cancelled_at: Option<u64>,
+    pub refund: Option<BookingRefund>,
+    pub field_answers: Option<Vec<FieldAnswer>>, // Guest's answers to the availability's event fields
+    pub payment_hold: Option<PaymentHold>,        // Set on holds; kept once the payment settles
+}
+
+/// What the caller would get back if they cancelled now
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct CancellationQuote {
+    pub refund_percent: u8,
+    pub refund_amount: u64,
+    pub policy: Option<CancellationPolicy>,
+}
+
+/// One guest in a booking's session, as the caller may see them
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct Attendee {
+    pub booking_id: Option<String>, // Hidden from other guests
+    pub guest_name: String,
+    pub guest_email: Option<String>, // Only for the owner's side and the guest themselves
+    pub is_caller: bool,
+}
+
+/// One reply's worth of bookings; `truncated` means pass `next_cursor` back
+/// for the rest
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct BookingPage {
+    pub bookings: Vec<Booking>,
+    pub truncated: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateBookingRequest {
+    pub availability_id: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,  // IANA zone detected by the guest's browser
+    pub field_answers: Option<Vec<FieldAnswer>>,
+}
+
+impl Booking {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Cancelled and owned by nobody, so it never blocks a
+    /// slot. `replay_booking_events` can rebuild the real one if it was logged.
+    pub(crate) fn unreadable() -> Self {
+        Booking {
+            id: String::new(),
+            availability_id: String::new(),
+            owner: Principal::anonymous(),
+            guest: Principal::anonymous(),
+            guest_name: String::new(),
+            guest_email: None,
+            guest_timezone: None,
+            start_time: 0,
+            end_time: 0,
+            status: BookingStatus::Cancelled,
+            payment: None,
+            session_link: None,
+            created_at: 0,
+            updated_at: 0,
+            cancelled_by: None,
+            cancelled_at: None,
+            refund: None,
+            field_answers: None,
+            payment_hold: None,
+        }
+    }
+
+    pub fn is_unreadable(&self) -> bool {
+        self.id.is_empty()
+    }
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for Booking {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Booking").unwrap_or_else(Booking::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static BOOKINGS: RefCell<WatchedMap<String, Booking>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+            |id, old, new| upgrade_check::track("BOOKINGS", id, old, new),
+        )
+    );
+
+    // Owner -> booking IDs, used for conflict checks and listing
+    static OWNER_BOOKINGS: RefCell<WatchedMap<Principal, StringVec>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14))),
+            |owner, old, new| upgrade_check::track("OWNER_BOOKINGS", owner, old, new),
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// 12 random hex chars
+fn generate_booking_id() -> Result<String, String> {
+    entropy::next_token(6)
+}
+
+fn validate_guest(req: &CreateBookingRequest) -> Result<(), String> {
+    if req.guest_name.trim().is_empty() || req.guest_name.len() > MAX_GUEST_NAME_LEN {
+        return Err(format!("guest_name must be 1-{} characters", MAX_GUEST_NAME_LEN));
+    }
+    if let Some(email) = &req.guest_email {
+        if email.len() > MAX_GUEST_EMAIL_LEN || !email.contains('@') {
+            return Err("guest_email is not a valid email address".to_string());
+        }
+    }
+    if let Some(timezone) = &req.guest_timezone {
+        validate_timezone(timezone)?;
+    }
+    Ok(())
+}
+
+pub fn validate_timezone(timezone: &str) -> Result<(), String> {
+    timezone.parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| format!("Unknown timezone: {}", timezone))
+}
+
+/// Zone for guest-facing times: the guest's own, else the availability's
+pub fn guest_timezone(booking: &Booking) -> String {
+    booking.guest_timezone.clone()
+        .or_else(|| availabilities::get_availability(booking.availability_id.clone()).ok().map(|a| a.timezone))
+        .unwrap_or_else(|| "UTC".to_string())
+}
+
+fn owner_booking_ids(owner: Principal) -> Vec<String> {
+    OWNER_BOOKINGS.with(|ob| ob.borrow().get(&owner).map(|v| v.0).unwrap_or_default())
+}
+
+/// Check the requested window is bookable: in the future, inside a free
+/// window of the availability and not overlapping any other live booking
+fn validate_slot(availability: &Availability, start: u64, end: u64) -> Result<(), String> {
+    validate_slot_against(availability, start, end, None)
+}
+
+/// With a `snapshot`, the calendar check uses the free windows the guest was
+/// shown instead of the live calendar, so busy-time syncs since then don't
+/// reject the slot. Existing bookings are always hard conflicts.
+fn validate_slot_against(availability: &Availability, start: u64, end: u64, snapshot: Option<&[FreeSlot]>) -> Result<(), String> {
+    if start >= end {
+        return Err("start_time must be less than end_time".to_string());
+    }
+    calendar_access::require_connected(availability.owner)?;
+    let locale = i18n::resolve(availability.locale, availability.owner);
+    if start < time() / 1_000_000_000 {
+        return Err(i18n::t(locale, Message::SlotInPast).to_string());
+    }
+    let lead = availability.alignment.as_ref().map(|a| a.lead_minutes as u64 * 60).unwrap_or(0);
+    if start < time() / 1_000_000_000 + lead {
+        return Err(i18n::t(locale, Message::SlotNotAvailable).to_string());
+    }
+    let free = match snapshot {
+        Some(windows) => windows.iter().any(|w| w.start_time <= start && w.end_time >= end),
+        None => availabilities::is_free(availability, start, end),
+    };
+    if !free {
+        return Err(i18n::t(locale, Message::SlotNotAvailable).to_string());
+    }
+
+    // Overlapping is only allowed to join a group session that has a free seat
+    let overlapping = live_owner_bookings(availability.owner, start, end);
+    let joins_session = overlapping.len() < availabilities::seats(availability) as usize
+        && overlapping.iter().all(|bk| in_session(bk, availability, start, end));
+    if !overlapping.is_empty() && !joins_session {
+        return Err(i18n::t(locale, Message::SlotAlreadyBooked).to_string());
+    }
+
+    // Availabilities with duration options only take those lengths, with their buffers kept clear
+    if availability.durations.is_some() {
+        let length = end - start;
+        if !length.is_multiple_of(60) {
+            return Err("Meeting length must be whole minutes".to_string());
+        }
+        let option = availabilities::duration_option(availability, (length / 60) as u32)?;
+        let clear = match snapshot {
+            Some(_) => taken_blocks(
+                availability,
+                start.saturating_sub(option.buffer_before_minutes as u64 * 60),
+                end + option.buffer_after_minutes as u64 * 60,
+            ).is_empty(),
+            None => availabilities::buffers_clear(availability, &option, start, end),
+        };
+        if !clear {
+            return Err(i18n::t(locale, Message::SlotNotAvailable).to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Write a booking's projected state (`None` = gone) to the booking map and
+/// owner index, keeping the booking counters in step
+fn write_projection(id: &str, state: Option<Booking>) {
+    match state {
+        Some(booking) => {
+            OWNER_BOOKINGS.with(|ob| {
+                let mut map = ob.borrow_mut();
+                let mut ids = map.get(&booking.owner).map(|v| v.0).unwrap_or_default();
+                if !ids.iter().any(|i| i == id) {
+                    ids.push(id.to_string());
+                    map.insert(booking.owner, StringVec(ids));
+                }
+            });
+            let before = BOOKINGS.with(|b| b.borrow_mut().insert(id.to_string(), booking.clone()));
+            counters::booking_changed(before.as_ref(), Some(&booking));
+        }
+        None => {
+            let Some(booking) = BOOKINGS.with(|b| b.borrow_mut().remove(&id.to_string())) else {
+                return;
+            };
+            counters::booking_changed(Some(&booking), None);
+            OWNER_BOOKINGS.with(|ob| {
+                let mut map = ob.borrow_mut();
+                if let Some(mut ids) = map.get(&booking.owner) {
+                    ids.0.retain(|i| i != id);
+                    map.insert(booking.owner, ids);
+                }
+            });
+        }
+    }
+}
+
+/// Append an event to the booking log and apply it to the current state
+fn commit(id: &str, event: BookingEvent) -> Option<Booking> {
+    let record = booking_events::append(id, event);
+    let state = booking_events::project(find(id), &record);
+    write_projection(id, state.clone());
+    state
+}
+
+/// Refund share for a guest cancelling `hours_left` hours before the start.
+/// Without a policy guests can always cancel for free.
+fn refund_percent(policy: Option<&CancellationPolicy>, hours_left: u64) -> u8 {
+    let Some(policy) = policy else {
+        return 100;
+    };
+    if hours_left >= policy.free_until_hours as u64 {
+        100
+    } else if hours_left >= policy.partial_until_hours as u64 {
+        policy.partial_refund_percent
+    } else {
+        0
+    }
+}
+
+/// Owners cancelling always refund in full; guests follow the availability's policy
+fn quote(caller: Principal, booking: &Booking) -> CancellationQuote {
+    let policy = availabilities::get_availability(booking.availability_id.clone())
+        .ok()
+        .and_then(|a| a.cancellation_policy);
+
+    let refund_percent = if caller == booking.owner {
+        100
+    } else {
+        let hours_left = booking.start_time.saturating_sub(time() / 1_000_000_000) / 3600;
+        refund_percent(policy.as_ref(), hours_left)
+    };
+    let paid = booking.payment.as_ref().map(|p| p.amount).unwrap_or(0);
+
+    CancellationQuote {
+        refund_percent,
+        refund_amount: paid * refund_percent as u64 / 100,
+        policy,
+    }
+}
+
+fn insert_booking(
+    availability: &Availability,
+    guest: Principal,
+    req: CreateBookingRequest,
+    payment_hold: Option<PaymentHold>,
+    session_link: Option<String>,
+) -> Result<Booking, String> {
+    validate_guest(&req)?;
+    availabilities::validate_answers(availability, req.field_answers.as_deref().unwrap_or_default())?;
+    validate_slot(availability, req.start_time, req.end_time)?;
+    store_booking(availability, guest, req, payment_hold, session_link)
+}
+
+fn store_booking(
+    availability: &Availability,
+    guest: Principal,
+    req: CreateBookingRequest,
+    payment_hold: Option<PaymentHold>,
+    session_link: Option<String>,
+) -> Result<Booking, String> {
+    let now = time();
+    // A booking waiting for a payment holds its slot as Pending
+    let status = if payment_hold.is_some() { BookingStatus::Pending } else { BookingStatus::Confirmed };
+    let booking = Booking {
+        id: generate_booking_id()?,
+        availability_id: availability.id.clone(),
+        owner: availability.owner,
+        guest,
+        guest_name: req.guest_name.trim().to_string(),
+        guest_email: req.guest_email,
+        guest_timezone: req.guest_timezone,
+        start_time: req.start_time,
+        end_time: req.end_time,
+        status,
+        payment: None,
+        session_link,
+        created_at: now,
+        updated_at: now,
+        cancelled_by: None,
+        cancelled_at: None,
+        refund: None,
+        field_answers: req.field_answers.filter(|answers| !answers.is_empty()),
+        payment_hold,
+    };
+
+    commit(&booking.id, BookingEvent::Created(Box::new(booking.clone())));
+
+    ic_cdk::println!("📅 Booking {} ({:?}) on {}", booking.id, booking.status, booking.availability_id);
+    if booking.status == BookingStatus::Confirmed {
+        counters::booking_confirmed(booking.owner);
+        followups::schedule(&booking);
+        integrations::notify(HookEvent::BookingCreated, &booking.id);
+    }
+    Ok(booking)
+}
+
+// ============================================================================
+// Bookings
+// ============================================================================
+
+/// Book a free slot on an availability
+pub fn create_booking(caller: Principal, req: CreateBookingRequest) -> Result<Booking, String> {
+    let availability = validate_booking(caller, &req)?;
+    book_validated(caller, &availability, req)
+}
+
+/// Run every check `create_booking` makes, returning the availability as the
+/// caller sees it. Lets several bookings be checked before any is stored.
+pub fn validate_booking(caller: Principal, req: &CreateBookingRequest) -> Result<Availability, String> {
+    let availability = experiments::as_seen_by(availabilities::get_availability(req.availability_id.clone())?, caller);
+    validate_guest(req)?;
+    availabilities::validate_answers(&availability, req.field_answers.as_deref().unwrap_or_default())?;
+    validate_slot(&availability, req.start_time, req.end_time)?;
+    Ok(availability)
+}
+
+/// Store a booking that passed `validate_booking` in this same call
+pub fn book_validated(caller: Principal, availability: &Availability, req: CreateBookingRequest) -> Result<Booking, String> {
+    let booking = store_booking(availability, caller, req, None, None)?;
+    experiments::record_booking(availability, caller);
+    Ok(booking)
+}
+
+/// Run every check `create_booking` would (free window, calendar, buffers,
+/// existing bookings and holds, the caller's daily cap) without booking
+pub fn check_booking_conflict(caller: Principal, availability_id: String, start: u64, end: u64) -> Result<(), String> {
+    let availability = experiments::as_seen_by(availabilities::get_availability(availability_id)?, caller);
+    quotas::check_rate(caller, QuotaKind::BookingsPerDay)?;
+    validate_slot(&availability, start, end)
+}
+
+/// Book a slot the guest picked from a free-slot snapshot
+pub fn create_booking_from_snapshot(caller: Principal, req: CreateBookingRequest, snapshot: &[FreeSlot]) -> Result<Booking, String> {
+    let availability = availabilities::get_availability(req.availability_id.clone())?;
+    validate_guest(&req)?;
+    availabilities::validate_answers(&availability, req.field_answers.as_deref().unwrap_or_default())?;
+    validate_slot_against(&availability, req.start_time, req.end_time, Some(snapshot))?;
+    let booking = store_booking(&availability, caller, req, None, None)?;
+    experiments::record_booking(&availability, caller);
+    Ok(booking)
+}
+
+/// Record a booking that was already agreed elsewhere (e.g. imported from
+/// another scheduler). It need not fit the weekly schedule, but must not
+/// overlap an existing booking.
+pub fn import_booking(availability: &Availability, req: CreateBookingRequest) -> Result<Booking, String> {
+    validate_guest(&req)?;
+    availabilities::validate_answers(availability, req.field_answers.as_deref().unwrap_or_default())?;
+    if req.start_time >= req.end_time {
+        return Err("start_time must be less than end_time".to_string());
+    }
+    if !booked_blocks(availability.owner, req.start_time, req.end_time).is_empty() {
+        return Err(error_codes::coded(ErrorCode::SlotTaken, "Overlaps an existing booking"));
+    }
+    store_booking(availability, availability.owner, req, None, None)
+}
+
+/// Reserve a slot as Pending while the guest's payment goes through
+pub fn hold_slot(
+    availability: &Availability,
+    guest: Principal,
+    req: CreateBookingRequest,
+    session_link: String,
+    payment_hold: PaymentHold,
+) -> Result<Booking, String> {
+    insert_booking(availability, guest, req, Some(payment_hold), Some(session_link))
+}
+
+/// Confirm a held booking once its payment has settled
+pub fn confirm_payment(id: &str, payment: BookingPayment) -> Result<Booking, String> {
+    let held = find(id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if held.status != BookingStatus::Pending {
+        return Err(format!("Booking {} is {:?}, not awaiting payment", id, held.status));
+    }
+    let booking = commit(id, BookingEvent::PaymentCaptured(payment)).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    counters::booking_confirmed(booking.owner);
+    followups::schedule(&booking);
+    integrations::notify(HookEvent::BookingCreated, &booking.id);
+    Ok(booking)
+}
+
+/// Drop a held booking whose payment failed, freeing the slot again. A
+/// booking whose payment already settled is left alone.
+pub fn release_hold(id: &str) {
+    if !find(id).is_some_and(|booking| booking.status == BookingStatus::Pending) {
+        return;
+    }
+    commit(id, BookingEvent::HoldReleased);
+    ic_cdk::println!("↩️ Released hold on booking {}", id);
+}
+
+pub fn find(id: &str) -> Option<Booking> {
+    BOOKINGS.with(|b| b.borrow().get(&id.to_string()))
+}
+
+/// Whether a booking still occupies its slot (held or confirmed)
+pub fn is_live(id: &str) -> bool {
+    find(id).is_some_and(|bk| bk.status != BookingStatus::Cancelled)
+}
+
+/// Whether the caller manages bookings for the owner (delegates) or for this
+/// availability (Approvers)
+pub fn manages(caller: Principal, booking: &Booking) -> bool {
+    delegations::allowed(caller, booking.owner, DelegationScope::ManageBookings)
+        || availabilities::get_availability(booking.availability_id.clone())
+            .is_ok_and(|a| availabilities::has_role(&a, caller, CollaboratorRole::Approver))
+}
+
+/// Visible to the availability owner (and whoever manages its bookings) and
+/// the guest who booked it
+pub fn get_booking(caller: Principal, id: String) -> Result<Booking, String> {
+    let booking = BOOKINGS.with(|b| b.borrow().get(&id)).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    // A guest must still share the owner's tenant; the owner's side always may read
+    let guest = booking.guest == caller && tenants::same_tenant(caller, booking.owner);
+    if !guest && !manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not a participant of this booking"));
+    }
+    Ok(booking)
+}
+
+/// Everyone booked into the same session as a booking. The owner's side sees
+/// the full list; a guest sees the other guests' names only.
+pub fn get_attendees(caller: Principal, id: String) -> Result<Vec<Attendee>, String> {
+    let booking = get_booking(caller, id)?;
+    let full = manages(caller, &booking);
+    let attendees = live_owner_bookings(booking.owner, booking.start_time, booking.end_time)
+        .into_iter()
+        .filter(|bk| bk.availability_id == booking.availability_id
+            && bk.start_time == booking.start_time
+            && bk.end_time == booking.end_time)
+        .map(|bk| {
+            let visible = full || bk.guest == caller;
+            Attendee {
+                booking_id: visible.then(|| bk.id.clone()),
+                guest_email: if visible { bk.guest_email } else { None },
+                guest_name: bk.guest_name,
+                is_caller: bk.guest == caller,
+            }
+        })
+        .collect();
+    Ok(attendees)
+}
+
+/// The side of the booking the caller acts for; delegates act for the owner
+fn party(caller: Principal, booking: &Booking) -> Principal {
+    if caller == booking.guest { caller } else { booking.owner }
+}
+
+/// Debit the owner's earnings and send the refund (less the ledger fee) to the
+/// guest, restoring the earnings if the transfer fails
+async fn send_refund(booking: &Booking, payment: &BookingPayment, amount: u64) -> Result<Option<u64>, String> {
+    earnings::debit_refund(booking.owner, payment.ledger_canister, amount, &booking.id)?;
+
+    let result = match ledger::fee(payment.ledger_canister).await {
+        Ok(fee) if fee >= amount => return Ok(None), // Nothing left to send after the fee
+        Ok(fee) => ledger::transfer(
+            payment.ledger_canister,
+            Account { owner: booking.guest, subaccount: None },
+            amount - fee,
+            time(),
+        ).await.map(Some),
+        Err(e) => Err(e),
+    };
+
+    if result.is_err() {
+        earnings::reverse(booking.owner, payment.ledger_canister, amount, &booking.id);
+    }
+    result
+}
+
+/// Preview the refund before cancelling
+pub fn get_cancellation_quote(caller: Principal, id: String) -> Result<CancellationQuote, String> {
+    let booking = get_booking(caller, id)?;
+    Ok(quote(party(caller, &booking), &booking))
+}
+
+/// Cancel an upcoming booking as its owner or guest, refunding any payment
+/// according to the cancellation policy
+pub async fn cancel_booking(caller: Principal, id: String) -> Result<Booking, String> {
+    let booking = get_booking(caller, id)?;
+    match booking.status {
+        BookingStatus::Confirmed => {}
+        BookingStatus::Pending => return Err(error_codes::coded(ErrorCode::PaymentPending, "Booking payment is still in progress")),
+        BookingStatus::Cancelled => return Err("Booking is already cancelled".to_string()),
+    }
+    if booking.start_time <= time() / 1_000_000_000 {
+        return Err("Cannot cancel a booking that has already started".to_string());
+    }
+
+    let quote = quote(party(caller, &booking), &booking);
+
+    // Commit the cancellation before the ledger call so it can't be repeated
+    let booking = commit(&booking.id, BookingEvent::Cancelled { by: caller }).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if caller != booking.guest {
+        delegations::record(caller, booking.owner, format!("cancel booking {}", booking.id));
+    }
+    ic_cdk::println!("❌ Booking {} cancelled by {}", booking.id, caller.to_text());
+    followups::cancel(&booking.id);
+    integrations::notify(HookEvent::BookingCancelled, &booking.id);
+
+    let Some(payment) = booking.payment.clone() else {
+        return Ok(booking);
+    };
+    // Stripe payments are refunded from the Stripe dashboard, not the ledger
+    if quote.refund_amount == 0 || payment.stripe_payment_id.is_some() {
+        return Ok(booking);
+    }
+
+    let result = send_refund(&booking, &payment, quote.refund_amount).await;
+    let (block_index, error) = match result {
+        Ok(block_index) => (block_index, None),
+        Err(e) => (None, Some(e)),
+    };
+    let refund = BookingRefund {
+        amount: quote.refund_amount,
+        block_index,
+        error,
+        refunded_at: time(),
+    };
+    Ok(commit(&booking.id, BookingEvent::Refunded(refund)).unwrap_or(booking))
+}
+
+/// Move a confirmed booking to new times (its owner moved the event in their
+/// calendar). The log has no reschedule event, since adding one would change
+/// the released history type, so the move is recorded as a fresh `Created`
+/// snapshot.
+pub fn reschedule(id: &str, start: u64, end: u64) -> Result<Booking, String> {
+    let mut booking = find(id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if booking.status != BookingStatus::Confirmed {
+        return Err("Only confirmed bookings can be moved".to_string());
+    }
+    if start >= end {
+        return Err("start_time must be less than end_time".to_string());
+    }
+    let overlaps = owner_booking_ids(booking.owner).iter()
+        .filter(|other| other.as_str() != id)
+        .filter_map(|other| find(other))
+        .any(|other| other.status != BookingStatus::Cancelled && other.start_time < end && start < other.end_time);
+    if overlaps {
+        return Err(error_codes::coded(ErrorCode::SlotTaken, "The new time overlaps another booking"));
+    }
+    booking.start_time = start;
+    booking.end_time = end;
+    booking.updated_at = time();
+    let moved = commit(id, BookingEvent::Created(Box::new(booking))).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    ic_cdk::println!("🕒 Booking {} moved to {}-{}", id, start, end);
+    Ok(moved)
+}
+
+/// Bookings on a principal's availabilities, for principal moves
+pub fn count_owned(owner: Principal) -> u32 {
+    owner_booking_ids(owner).len() as u32
+}
+
+/// Hand every booking owned by `from` to `to` (the same user under a new
+/// principal), recorded as fresh `Created` snapshots like `reschedule`.
+/// Returns how many moved.
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let ids = owner_booking_ids(from);
+    let mut count = 0;
+    for id in &ids {
+        let Some(mut booking) = find(id) else {
+            continue;
+        };
+        booking.owner = to;
+        booking.updated_at = time();
+        if commit(id, BookingEvent::Created(Box::new(booking))).is_some() {
+            count += 1;
+        }
+    }
+    // The projection only appends to the new owner's list
+    OWNER_BOOKINGS.with(|ob| ob.borrow_mut().remove(&from));
+    count
+}
+
+/// Move a cancelled or past booking to the owner's trash. Upcoming bookings
+/// must be cancelled first so the guest is told and refunded, and bookings
+/// under a legal hold can't be deleted at all.
+pub fn delete_booking(caller: Principal, id: String) -> Result<(), String> {
+    let booking = find(&id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if !manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "only the owner can delete bookings"));
+    }
+    legal_hold::require_no_hold(booking.owner)?;
+    let over = booking.end_time <= time() / 1_000_000_000;
+    match booking.status {
+        BookingStatus::Cancelled => {}
+        BookingStatus::Confirmed if over => {}
+        BookingStatus::Pending => return Err(error_codes::coded(ErrorCode::PaymentPending, "Booking payment is still in progress")),
+        BookingStatus::Confirmed => return Err("Cancel the booking before deleting it".to_string()),
+    }
+    if caller != booking.owner {
+        delegations::record(caller, booking.owner, format!("delete booking {}", booking.id));
+    }
+    write_projection(&id, None);
+    trash::put_booking(caller, booking);
+    Ok(())
+}
+
+/// Put a trashed booking back; it must not overlap a live booking made since
+pub fn restore(booking: Booking) -> Result<(), String> {
+    if find(&booking.id).is_some() {
+        return Err("A booking with this ID already exists".to_string());
+    }
+    if booking.status != BookingStatus::Cancelled
+        && !booked_blocks(booking.owner, booking.start_time, booking.end_time).is_empty()
+    {
+        return Err(error_codes::coded(ErrorCode::SlotTaken, "The slot has been booked again since"));
+    }
+    let id = booking.id.clone();
+    write_projection(&id, Some(booking));
+    Ok(())
+}
+
+/// Live (pending or confirmed) bookings of an owner overlapping a range
+fn live_owner_bookings(owner: Principal, range_start: u64, range_end: u64) -> Vec<Booking> {
+    BOOKINGS.with(|b| {
+        let map = b.borrow();
+        owner_booking_ids(owner)
+            .iter()
+            .filter_map(|id| map.get(id))
+            .filter(|bk| bk.status != BookingStatus::Cancelled)
+            .filter(|bk| bk.start_time < range_end && range_start < bk.end_time)
+            .collect()
+    })
+}
+
+fn block(booking: &Booking) -> BusyTimeBlock {
+    BusyTimeBlock { start_time: booking.start_time, end_time: booking.end_time }
+}
+
+/// Whether a booking is a seat in the availability's session [start, end)
+fn in_session(booking: &Booking, availability: &Availability, start: u64, end: u64) -> bool {
+    booking.availability_id == availability.id && booking.start_time == start && booking.end_time == end
+}
+
+/// Live (pending or confirmed) bookings of an owner overlapping a range, as busy blocks
+pub fn booked_blocks(owner: Principal, range_start: u64, range_end: u64) -> Vec<BusyTimeBlock> {
+    live_owner_bookings(owner, range_start, range_end).iter().map(block).collect()
+}
+
+/// Booked time that keeps new guests off an availability: the owner's live
+/// bookings, except this availability's group sessions that have a free seat
+pub fn taken_blocks(availability: &Availability, range_start: u64, range_end: u64) -> Vec<BusyTimeBlock> {
+    let live = live_owner_bookings(availability.owner, range_start, range_end);
+    let seats = availabilities::seats(availability) as usize;
+    if seats <= 1 {
+        return live.iter().map(block).collect();
+    }
+    let taken = |bk: &Booking| live.iter().filter(|other| in_session(other, availability, bk.start_time, bk.end_time)).count();
+    live.iter()
+        .filter(|bk| bk.availability_id != availability.id || taken(bk) >= seats)
+        .map(block)
+        .collect()
+}
+
+/// Bookings on one availability, for its owner and Approvers
+pub fn list_availability_bookings(caller: Principal, availability_id: String) -> Result<Vec<Booking>, String> {
+    let availability = availabilities::get_availability(availability_id)?;
+    if !availabilities::has_role(&availability, caller, CollaboratorRole::Approver) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not an approver of this availability"));
+    }
+    let mut bookings = list_owner_bookings(availability.owner);
+    bookings.retain(|bk| bk.availability_id == availability.id);
+    Ok(bookings)
+}
+
+/// One page of a booking listing, resuming at `cursor`. `filters` names the
+/// listing the cursor was issued for (e.g. the availability ID).
+pub fn page(bookings: Vec<Booking>, cursor: Option<String>, filters: &str) -> Result<BookingPage, String> {
+    let (bookings, next_cursor) = cursor::page(bookings, cursor, "bookings", filters, PAGE_SIZE, |bk| bk.id.clone())?;
+    Ok(BookingPage {
+        bookings,
+        truncated: next_cursor.is_some(),
+        next_cursor,
+    })
+}
+
+/// All bookings on the owner's availabilities, soonest first
+pub fn list_owner_bookings(owner: Principal) -> Vec<Booking> {
+    let mut bookings: Vec<Booking> = BOOKINGS.with(|b| {
+        let map = b.borrow();
+        owner_booking_ids(owner)
+            .iter()
+            .filter_map(|id| map.get(id))
+            .filter(|bk| !bk.is_unreadable())
+            .collect()
+    });
+    bookings.sort_by_key(|bk| bk.start_time);
+    bookings
+}
+
+/// Calendar file for a booking (an invite, or a cancellation once cancelled),
+/// for its guest and whoever manages it
+pub fn get_booking_ics(caller: Principal, id: String) -> Result<String, String> {
+    let booking = get_booking(caller, id)?;
+    let availability = availabilities::get_availability(booking.availability_id.clone()).ok();
+    Ok(ical::booking_event(&booking, availability.as_ref()))
+}
+
+/// Event history of a booking, for anyone who can see the booking
+pub fn get_booking_history(caller: Principal, id: String) -> Result<Vec<BookingEventRecord>, String> {
+    get_booking(caller, id.clone())?;
+    Ok(booking_events::history(&id))
+}
+
+/// Rebuild every logged booking from its events, repairing the booking map
+/// after a bug. Returns how many bookings were rewritten.
+pub fn replay_booking_events() -> u32 {
+    let states = booking_events::replay();
+    let count = states.len() as u32;
+    for (id, state) in states {
+        // Deleted bookings stay deleted; their events go when the trash is purged
+        if state.as_ref().is_some_and(|booking| trash::holds(booking.owner, &id)) {
+            continue;
+        }
+        write_projection(&id, state);
+    }
+    ic_cdk::println!("🔁 Replayed {} booking events into {} bookings", booking_events::len(), count);
+    count
+}
+
+/// Remove placeholders of undecodable bookings, returning their IDs
+pub fn remove_unreadable() -> Vec<String> {
+    BOOKINGS.with(|b| {
+        let mut map = b.borrow_mut();
+        let ids: Vec<String> = map.iter().filter(|(_, bk)| bk.is_unreadable()).map(|(id, _)| id).collect();
+        for id in &ids {
+            map.remove(id);
+        }
+        ids
+    })
+}
+
+/// Fingerprints of the booking map and owner index, for upgrade checks
+pub fn upgrade_digests() -> Vec<(&'static str, RunningDigest)> {
+    vec![
+        ("BOOKINGS", BOOKINGS.with(|m| upgrade_check::digest(m.borrow().inner()))),
+        ("OWNER_BOOKINGS", OWNER_BOOKINGS.with(|m| upgrade_check::digest(m.borrow().inner()))),
+    ]
+}
+
+/// Drop every record (dev fixtures only)
+pub fn clear() {
+    BOOKINGS.with(|m| m.borrow_mut().clear_new());
+    OWNER_BOOKINGS.with(|m| m.borrow_mut().clear_new());
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    BOOKINGS.with(|m| storage::map_stats(m.borrow().inner()))
+}
+
+// ============================================================================
+// Index Rebuild
+// ============================================================================
+
+pub fn count() -> u64 {
+    BOOKINGS.with(|b| b.borrow().len())
+}
+
+/// Put the next `limit` bookings after `after` back into their owner's list
+/// and the counters
+pub fn rebuild_chunk(after: Option<String>, limit: usize) -> RebuildChunk {
+    let start = after.map_or(RangeBound::Unbounded, RangeBound::Excluded);
+    let chunk: Vec<(String, Booking)> = BOOKINGS.with(|b| {
+        b.borrow().range((start, RangeBound::Unbounded)).take(limit).collect()
+    });
+    let mut fixed = 0;
+    for (id, booking) in chunk.iter().filter(|(_, booking)| !booking.is_unreadable()) {
+        OWNER_BOOKINGS.with(|ob| {
+            let mut map = ob.borrow_mut();
+            let mut ids = map.get(&booking.owner).map(|v| v.0).unwrap_or_default();
+            if !ids.contains(id) {
+                ids.push(id.clone());
+                map.insert(booking.owner, StringVec(ids));
+                fixed += 1;
+            }
+        });
+        counters::count_booking(booking);
+    }
+    RebuildChunk {
+        next: if chunk.len() == limit { chunk.last().map(|(id, _)| id.clone()) } else { None },
+        scanned: chunk.len() as u64,
+        fixed,
+    }
+}
+
+/// Drop IDs of missing, re-owned or duplicated bookings from the next
+/// `limit` owner lists after `after`
+pub fn prune_owner_lists_chunk(after: Option<Principal>, limit: usize) -> RebuildChunk {
+    let start = after.map_or(RangeBound::Unbounded, RangeBound::Excluded);
+    let lists: Vec<(Principal, StringVec)> = OWNER_BOOKINGS.with(|ob| {
+        ob.borrow().range((start, RangeBound::Unbounded)).take(limit).collect()
+    });
+    let mut fixed = 0;
+    for (owner, ids) in &lists {
+        let mut kept: Vec<String> = Vec::with_capacity(ids.0.len());
+        for id in &ids.0 {
+            if find(id).is_some_and(|b| b.owner == *owner) && !kept.contains(id) {
+                kept.push(id.clone());
+            }
+        }
+        if kept.len() != ids.0.len() {
+            fixed += (ids.0.len() - kept.len()) as u64;
+            OWNER_BOOKINGS.with(|ob| ob.borrow_mut().insert(*owner, StringVec(kept)));
+        }
+    }
+    RebuildChunk {
+        next: if lists.len() == limit { lists.last().map(|(owner, _)| owner.to_text()) } else { None },
+        scanned: lists.len() as u64,
+        fixed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availabilities::{TimeSlot, AVAILABILITIES};
+    use crate::clock::{self, MockClock};
+
+    const NOW: u64 = 1_735_689_600;
+    const HOUR: u64 = 3600;
+    const SECOND_NS: u64 = 1_000_000_000;
+
+    /// A paid booking starting 72 hours from NOW on an availability with a
+    /// free-until-48h, half-refund-until-24h policy
+    fn paid_booking(owner: Principal, guest: Principal) -> Booking {
+        let availability = Availability {
+            id: "policy".to_string(),
+            owner,
+            owner_email: None,
+            owner_name: None,
+            title: "Consultation".to_string(),
+            description: String::new(),
+            slots: vec![TimeSlot { day_of_week: 6, start_time: 0, end_time: 24 * 60 }],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            busy_times: None,
+            is_favorite: false,
+            display_order: 0,
+            busy_privacy: None,
+            cancellation_policy: Some(CancellationPolicy {
+                free_until_hours: 48,
+                partial_until_hours: 24,
+                partial_refund_percent: 50,
+            }),
+            locale: None,
+            durations: None,
+            kiosk: None,
+            collaborators: None,
+            vip: None,
+            alignment: None,
+            meeting_url: None,
+            event_fields: None,
+            seats: None,
+            follow_ups: None,
+        };
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(availability.id.clone(), availability));
+
+        let mut booking = Booking::unreadable();
+        booking.id = "paid".to_string();
+        booking.availability_id = "policy".to_string();
+        booking.owner = owner;
+        booking.guest = guest;
+        booking.start_time = NOW + 72 * HOUR;
+        booking.end_time = NOW + 73 * HOUR;
+        booking.status = BookingStatus::Confirmed;
+        booking.payment = Some(BookingPayment {
+            ledger_canister: Principal::management_canister(),
+            amount: 1_000,
+            discount_percent: None,
+            block_index: Some(7),
+            paid_at: NOW,
+            tax: None,
+            stripe_payment_id: None,
+        });
+        booking
+    }
+
+    #[test]
+    fn guest_refunds_step_down_exactly_at_the_policy_windows() {
+        let clock = MockClock::at(NOW * SECOND_NS);
+        clock::install(clock.clone());
+        let owner = Principal::from_slice(&[1; 29]);
+        let guest = Principal::from_slice(&[2; 29]);
+        let booking = paid_booking(owner, guest);
+        let refund = |booking: &Booking| {
+            let quote = quote(guest, booking);
+            (quote.refund_percent, quote.refund_amount)
+        };
+
+        // 48 hours before the start is still free
+        clock.set((booking.start_time - 48 * HOUR) * SECOND_NS);
+        assert_eq!(refund(&booking), (100, 1_000));
+        clock.advance(SECOND_NS);
+        assert_eq!(refund(&booking), (50, 500));
+
+        // 24 hours before the start is still the partial refund
+        clock.set((booking.start_time - 24 * HOUR) * SECOND_NS);
+        assert_eq!(refund(&booking), (50, 500));
+        clock.advance(SECOND_NS);
+        assert_eq!(refund(&booking), (0, 0));
+
+        clock.set((booking.start_time + HOUR) * SECOND_NS);
+        assert_eq!(refund(&booking), (0, 0));
+    }
+
+    #[test]
+    fn owners_refund_in_full_inside_every_window() {
+        let clock = MockClock::at(NOW * SECOND_NS);
+        clock::install(clock.clone());
+        let owner = Principal::from_slice(&[3; 29]);
+        let booking = paid_booking(owner, Principal::from_slice(&[4; 29]));
+
+        for hours_before in [72, 48, 24, 1] {
+            clock.set((booking.start_time - hours_before * HOUR) * SECOND_NS);
+            let quote = quote(owner, &booking);
+            assert_eq!((quote.refund_percent, quote.refund_amount), (100, 1_000));
+        }
+    }
+
+    #[test]
+    fn without_a_policy_guests_cancel_for_free_until_the_start() {
+        let clock = MockClock::at(NOW * SECOND_NS);
+        clock::install(clock.clone());
+        let guest = Principal::from_slice(&[6; 29]);
+        let mut booking = paid_booking(Principal::from_slice(&[5; 29]), guest);
+        AVAILABILITIES.with(|a| {
+            let mut map = a.borrow_mut();
+            let mut availability = map.get(&booking.availability_id).unwrap();
+            availability.cancellation_policy = None;
+            map.insert(availability.id.clone(), availability);
+        });
+
+        clock.set((booking.start_time - 1) * SECOND_NS);
+        let full = quote(guest, &booking);
+        assert_eq!((full.refund_percent, full.refund_amount), (100, 1_000));
+        assert!(full.policy.is_none());
+
+        // Nothing was paid, so nothing comes back
+        booking.payment = None;
+        assert_eq!(quote(guest, &booking).refund_amount, 0);
+    }
+}
@@ -0,0 +1,156 @@
+//! Google access the user has revoked from their Google account. A refresh
+//! answered with `invalid_grant` can't succeed again, so the connection is
+//! marked revoked, its stored tokens are dropped, and the owner's
+//! availabilities stop taking bookings (their free slots can no longer be
+//! checked against the calendar) until the user connects Google again.
+//! The frontend shows the revocation from `get_calendar_connection`.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, principal_moves, quarantine, tokens};
+use crate::error_codes::{self, ErrorCode};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Revocation {
+    pub revoked_at: u64,
+    pub paused_availabilities: Vec<String>, // Not bookable until Google is reconnected
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct CalendarConnection {
+    pub connected: bool,
+    pub revocation: Option<Revocation>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for Revocation {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Revocation").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Canonical principal -> revocation still waiting for a reconnect
+    static REVOCATIONS: RefCell<StableBTreeMap<Principal, Revocation, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(74)))
+        )
+    );
+}
+
+pub fn clear() {
+    REVOCATIONS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Whether a failed token response says the grant is gone for good
+pub fn is_invalid_grant(body: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| json.get("error").and_then(|e| e.as_str()).map(|e| e == "invalid_grant"))
+        .unwrap_or(false)
+}
+
+/// Record that the principal's Google access was revoked and drop its tokens
+pub fn mark_revoked(principal: Principal) -> Revocation {
+    let owner = tokens::canonical(principal);
+    let revocation = Revocation {
+        revoked_at: time(),
+        paused_availabilities: availabilities::list_user_availabilities(principal)
+            .into_iter()
+            .map(|availability| availability.id)
+            .collect(),
+    };
+    tokens::remove(owner);
+    REVOCATIONS.with(|r| r.borrow_mut().insert(owner, revocation.clone()));
+    ic_cdk::println!(
+        "🔌 [calendar] Google access revoked for {}; paused {} availabilities",
+        owner, revocation.paused_availabilities.len()
+    );
+    revocation
+}
+
+/// Google was connected again; the paused availabilities take bookings again
+pub fn clear_revocation(principal: Principal) {
+    let owner = tokens::canonical(principal);
+    if REVOCATIONS.with(|r| r.borrow_mut().remove(&owner)).is_some() {
+        ic_cdk::println!("🔌 [calendar] Google reconnected for {}", owner);
+    }
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+pub fn is_revoked(owner: Principal) -> bool {
+    REVOCATIONS.with(|r| r.borrow().contains_key(&tokens::canonical(owner)))
+}
+
+/// Call before booking one of the owner's availabilities
+pub fn require_connected(owner: Principal) -> Result<(), String> {
+    if is_revoked(owner) {
+        return Err(error_codes::coded(ErrorCode::CalendarDisconnected, "this calendar is paused until its owner reconnects Google"));
+    }
+    Ok(())
+}
+
+pub fn get_connection(caller: Principal) -> CalendarConnection {
+    CalendarConnection {
+        connected: tokens::get(caller).is_some(),
+        revocation: REVOCATIONS.with(|r| r.borrow().get(&tokens::canonical(caller))),
+    }
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(principal: Principal) -> u32 {
+    REVOCATIONS.with(|r| principal_moves::count_keyed(&r.borrow(), principal))
+}
+
+/// A revocation follows the token it belongs to
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    REVOCATIONS.with(|r| principal_moves::move_keyed(&mut r.borrow_mut(), from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_invalid_grant_only() {
+        assert!(is_invalid_grant(br#"{"error": "invalid_grant", "error_description": "Token has been expired or revoked."}"#));
+        assert!(!is_invalid_grant(br#"{"error": "invalid_client"}"#));
+        assert!(!is_invalid_grant(b"Service Unavailable"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Journal of bookings whose Google event was moved in Google but not here.
+//! Events created for a booking carry its ID in their private extended
+//! properties; every busy-time fetch compares those events with their
+//! bookings and records any divergence. The owner then either accepts
+//! Google's time (the booking moves) or restores weeekaly's (the event is
+//! patched back). Resolved entries stay in the journal.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::bookings::{self, BookingStatus};
+use crate::datetime;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Private extended property linking a Google event to its booking
+pub const BOOKING_ID_PROPERTY: &str = "weeekaly_booking_id";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ConflictResolution {
+    AcceptGoogle,    // The booking moved to the event's time
+    RestoreWeeekaly, // The event was moved back to the booking's time
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CalendarConflict {
+    pub booking_id: String,
+    pub event_id: String,
+    pub booking_start: u64,  // Unix seconds, as booked
+    pub booking_end: u64,
+    pub google_start: u64,   // Unix seconds, as found in Google
+    pub google_end: u64,
+    pub detected_at: u64,
+    pub resolution: Option<ConflictResolution>, // None = still open
+    pub resolved_at: Option<u64>,
+}
+
+/// Every conflict found on one booking, oldest first
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct ConflictHistory(Vec<CalendarConflict>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for ConflictHistory {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ConflictHistory").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "<owner>|<booking id>" -> conflicts found on the booking
+    static CALENDAR_CONFLICTS: RefCell<StableBTreeMap<String, ConflictHistory, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(75)))
+        )
+    );
+}
+
+pub fn clear() {
+    CALENDAR_CONFLICTS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn key(owner: Principal, booking_id: &str) -> String {
+    format!("{}|{}", owner, booking_id)
+}
+
+/// Booking ID and (start, end) of an event weeekaly created, if it is one
+fn linked_times(event: &serde_json::Value) -> Option<(String, u64, u64)> {
+    let booking_id = event.get("extendedProperties")?.get("private")?.get(BOOKING_ID_PROPERTY)?.as_str()?;
+    let start = datetime::parse_iso8601(event.get("start")?.get("dateTime")?.as_str()?).ok()?;
+    let end = datetime::parse_iso8601(event.get("end")?.get("dateTime")?.as_str()?).ok()?;
+    Some((booking_id.to_string(), start, end))
+}
+
+fn open_conflict(owner: Principal, booking_id: &str) -> Option<CalendarConflict> {
+    CALENDAR_CONFLICTS.with(|c| c.borrow().get(&key(owner, booking_id)))
+        .and_then(|history| history.0.last().cloned())
+        .filter(|conflict| conflict.resolution.is_none())
+}
+
+fn close(owner: Principal, booking_id: &str, resolution: ConflictResolution) -> CalendarConflict {
+    CALENDAR_CONFLICTS.with(|c| {
+        let mut map = c.borrow_mut();
+        let mut history = map.get(&key(owner, booking_id)).unwrap_or_default();
+        let conflict = history.0.last_mut().expect("closing an open conflict");
+        conflict.resolution = Some(resolution);
+        conflict.resolved_at = Some(time());
+        let closed = conflict.clone();
+        map.insert(key(owner, booking_id), history);
+        closed
+    })
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Compare the owner's Google events with the bookings they were created
+/// for, journaling every confirmed booking whose event has moved
+pub fn detect(owner: Principal, events: &[serde_json::Value]) {
+    for (event, (booking_id, google_start, google_end)) in events.iter().filter_map(|e| linked_times(e).map(|t| (e, t))) {
+        let Some(booking) = bookings::find(&booking_id) else { continue };
+        if booking.owner != owner || booking.status != BookingStatus::Confirmed {
+            continue;
+        }
+        if (booking.start_time, booking.end_time) == (google_start, google_end) {
+            continue;
+        }
+        if open_conflict(owner, &booking_id).is_some_and(|c| (c.google_start, c.google_end) == (google_start, google_end)) {
+            continue;
+        }
+
+        let conflict = CalendarConflict {
+            booking_id: booking_id.clone(),
+            event_id: event.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string(),
+            booking_start: booking.start_time,
+            booking_end: booking.end_time,
+            google_start,
+            google_end,
+            detected_at: time(),
+            resolution: None,
+            resolved_at: None,
+        };
+        CALENDAR_CONFLICTS.with(|c| {
+            let mut map = c.borrow_mut();
+            let mut history = map.get(&key(owner, &booking_id)).unwrap_or_default();
+            // A newer move in Google replaces the open entry instead of piling up
+            if history.0.last().is_some_and(|last| last.resolution.is_none()) {
+                history.0.pop();
+            }
+            history.0.push(conflict);
+            map.insert(key(owner, &booking_id), history);
+        });
+        ic_cdk::println!("⚠️ [calendar] Booking {} was moved in Google", booking_id);
+    }
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// The caller's journal, most recently detected first
+pub fn list_conflicts(caller: Principal) -> Vec<CalendarConflict> {
+    let prefix = format!("{}|", caller);
+    let mut conflicts: Vec<CalendarConflict> = CALENDAR_CONFLICTS.with(|c| {
+        c.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .flat_map(|(_, history)| history.0)
+            .collect()
+    });
+    conflicts.sort_by_key(|conflict| std::cmp::Reverse(conflict.detected_at));
+    conflicts
+}
+
+pub async fn resolve(caller: Principal, booking_id: String, resolution: ConflictResolution) -> Result<CalendarConflict, String> {
+    let booking = bookings::find(&booking_id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if !bookings::manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "only the owner can resolve calendar conflicts"));
+    }
+    let conflict = open_conflict(booking.owner, &booking_id).ok_or("No open conflict on this booking")?;
+
+    match resolution {
+        ConflictResolution::AcceptGoogle => {
+            bookings::reschedule(&booking_id, conflict.google_start, conflict.google_end)?;
+        }
+        ConflictResolution::RestoreWeeekaly => {
+            crate::patch_calendar_event_times(booking.owner, &conflict.event_id, booking.start_time, booking.end_time).await?;
+            // The booking may have been resolved from another tab while the patch was in flight
+            if open_conflict(booking.owner, &booking_id).is_none() {
+                return Err("Conflict was already resolved".to_string());
+            }
+        }
+    }
+    ic_cdk::println!("✅ [calendar] Conflict on {} resolved: {:?}", booking_id, resolution);
+    Ok(close(booking.owner, &booking_id, resolution))
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+fn histories_of(owner: Principal) -> Vec<(String, ConflictHistory)> {
+    let prefix = format!("{}|", owner);
+    CALENDAR_CONFLICTS.with(|c| {
+        c.borrow().range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)).collect()
+    })
+}
+
+/// Bookings of the owner with a conflict journal
+pub fn count_owned(owner: Principal) -> u32 {
+    histories_of(owner).len() as u32
+}
+
+/// Rekey `from`'s journal under `to`, where its bookings moved
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let histories = histories_of(from);
+    let prefix_len = format!("{}|", from).len();
+    CALENDAR_CONFLICTS.with(|c| {
+        let mut map = c.borrow_mut();
+        for (old_key, history) in &histories {
+            map.remove(old_key);
+            map.insert(key(to, &old_key[prefix_len..]), history.clone());
+        }
+    });
+    histories.len() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_events_with_a_booking_id_are_linked() {
+        let linked = serde_json::json!({
+            "id": "evt1",
+            "start": {"dateTime": "2025-03-01T09:00:00Z"},
+            "end": {"dateTime": "2025-03-01T10:00:00+01:00"},
+            "extendedProperties": {"private": {"weeekaly_booking_id": "bk1"}}
+        });
+        assert_eq!(linked_times(&linked), Some(("bk1".to_string(), 1_740_819_600, 1_740_819_600)));
+
+        let foreign = serde_json::json!({
+            "id": "evt2",
+            "start": {"dateTime": "2025-03-01T09:00:00Z"},
+            "end": {"dateTime": "2025-03-01T10:00:00Z"}
+        });
+        assert_eq!(linked_times(&foreign), None);
+    }
+}
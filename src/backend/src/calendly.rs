@@ -0,0 +1,274 @@
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use std::collections::HashMap;
+use ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse,
+    TransformArgs, TransformContext,
+};
+use crate::datetime;
+use crate::availabilities::{self, CreateAvailabilityRequest, DurationOption, TimeSlot};
+use crate::bookings::{self, CreateBookingRequest};
+use crate::quotas::{self, QuotaKind};
+use crate::circuit_breaker::{self, Provider};
+use crate::outcall_cache::{self, CachedEndpoint};
+
+const API_BASE: &str = "https://api.calendly.com";
+const MAX_RESPONSE_BYTES: u64 = 512 * 1024;
+
+/// Only the first page of each listing is imported
+const PAGE_SIZE: u32 = 100;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// An event type converted into an availability
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ImportedAvailability {
+    pub title: String,
+    pub duration_minutes: u32,
+    pub slots: Vec<TimeSlot>,
+    pub timezone: String,
+    pub availability_id: Option<String>, // Set once created (None in dry runs)
+}
+
+/// A future scheduled event converted into a booking
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ImportedBooking {
+    pub title: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub availability_title: Option<String>,
+    pub booking_id: Option<String>,     // Set once created (None in dry runs or when skipped)
+    pub skipped_reason: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct CalendlyImportReport {
+    pub dry_run: bool,
+    pub availabilities: Vec<ImportedAvailability>,
+    pub bookings: Vec<ImportedBooking>,
+}
+
+// ============================================================================
+// HTTP Outcalls
+// ============================================================================
+
+async fn get_json(caller: Principal, api_token: &str, url: String) -> Result<serde_json::Value, String> {
+    quotas::consume_rate(caller, QuotaKind::OutcallsPerHour)?;
+
+    let request = CanisterHttpRequestArgument {
+        url,
+        method: HttpMethod::GET,
+        body: None,
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        transform: Some(TransformContext::from_name("transform_import_response".to_string(), vec![])),
+        headers: vec![HttpHeader {
+            name: "Authorization".to_string(),
+            value: format!("Bearer {}", api_token),
+        }],
+    };
+
+    circuit_breaker::check(Provider::Calendly)?;
+    match outcall_cache::http_request(CachedEndpoint::CalendlyApi, caller, request, 30_000_000_000).await {
+        Ok((response,)) => {
+            if response.status != 200u16 {
+                return Err(format!(
+                    "Calendly API error {}: {}",
+                    response.status,
+                    String::from_utf8_lossy(&response.body)
+                ));
+            }
+            serde_json::from_slice(&response.body)
+                .map_err(|e| format!("Failed to parse Calendly response: {}", e))
+        }
+        Err((code, msg)) => Err(format!("Calendly request failed: {:?} - {}", code, msg)),
+    }
+}
+
+/// Keep only status and body so replicas agree on the response
+pub fn transform_response(args: TransformArgs) -> HttpResponse {
+    HttpResponse {
+        status: args.response.status,
+        headers: vec![],
+        body: args.response.body,
+    }
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+fn weekday_index(name: &str) -> Option<u8> {
+    ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"]
+        .iter()
+        .position(|d| *d == name)
+        .map(|i| i as u8)
+}
+
+/// "09:30" -> 570
+fn minutes_of(hhmm: &str) -> Option<u16> {
+    let (h, m) = hhmm.split_once(':')?;
+    Some(h.parse::<u16>().ok()? * 60 + m.parse::<u16>().ok()?)
+}
+
+/// Weekly slots and timezone of the user's default availability schedule
+fn weekly_schedule(schedules: &serde_json::Value) -> (Vec<TimeSlot>, String) {
+    let collection = schedules["collection"].as_array().cloned().unwrap_or_default();
+    let Some(schedule) = collection.iter().find(|s| s["default"] == true).or(collection.first()) else {
+        return (vec![], "UTC".to_string());
+    };
+
+    let slots = schedule["rules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|rule| rule["type"] == "wday")
+        .flat_map(|rule| {
+            let day = rule["wday"].as_str().and_then(weekday_index);
+            rule["intervals"].as_array().cloned().unwrap_or_default().into_iter().filter_map(move |interval| {
+                let start = minutes_of(interval["from"].as_str()?)?;
+                // Calendly ends a full day at 24:00; slots end at 23:59 at most
+                let end = minutes_of(interval["to"].as_str()?)?.min(1439);
+                (start < end).then_some(TimeSlot { day_of_week: day?, start_time: start, end_time: end })
+            })
+        })
+        .collect();
+
+    let timezone = schedule["timezone"].as_str().unwrap_or("UTC").to_string();
+    (slots, timezone)
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+fn unix_seconds(iso: &str) -> Option<u64> {
+    datetime::parse_iso8601(iso).ok()
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Convert the caller's Calendly event types into availabilities and their
+/// upcoming events into bookings. With `dry_run`, nothing is created and the
+/// report previews what would be. The token is used for this call only.
+pub async fn import_from_calendly(caller: Principal, api_token: String, dry_run: bool) -> Result<CalendlyImportReport, String> {
+    if api_token.trim().is_empty() {
+        return Err("api_token is required".to_string());
+    }
+    let token = api_token.trim();
+
+    let me = get_json(caller, token, format!("{}/users/me", API_BASE)).await?;
+    let user_uri = me["resource"]["uri"].as_str().ok_or("Unexpected Calendly user response")?.to_string();
+    let user = urlencoding::encode(&user_uri).into_owned();
+
+    let schedules = get_json(caller, token, format!("{}/user_availability_schedules?user={}", API_BASE, user)).await?;
+    let event_types = get_json(caller, token, format!("{}/event_types?user={}&active=true&count={}", API_BASE, user, PAGE_SIZE)).await?;
+    let now = crate::clock::time() / 1_000_000_000;
+    let min_start = datetime::format_iso8601(now);
+    let events = get_json(caller, token, format!(
+        "{}/scheduled_events?user={}&status=active&min_start_time={}&count={}",
+        API_BASE, user, urlencoding::encode(&min_start), PAGE_SIZE
+    )).await?;
+
+    let (slots, timezone) = weekly_schedule(&schedules);
+    if slots.is_empty() {
+        return Err("No weekly hours found in the Calendly availability schedule".to_string());
+    }
+
+    // Event type URI -> (title, availability ID once created)
+    let mut imported_types: HashMap<String, (String, Option<String>)> = HashMap::new();
+    let mut availabilities_report = Vec::new();
+    for event_type in event_types["collection"].as_array().into_iter().flatten() {
+        let (Some(uri), Some(name)) = (event_type["uri"].as_str(), event_type["name"].as_str()) else {
+            continue;
+        };
+        let duration = event_type["duration"].as_u64().unwrap_or(30) as u32;
+        let title = truncate(name, 100);
+
+        let availability_id = if dry_run {
+            None
+        } else {
+            quotas::check_count(caller, QuotaKind::Availabilities, availabilities::count_user_availabilities(caller))?;
+            let created = availabilities::create_availability(caller, CreateAvailabilityRequest {
+                title: title.clone(),
+                description: truncate(event_type["description_plain"].as_str().unwrap_or(""), 500),
+                slots: slots.clone(),
+                timezone: timezone.clone(),
+                owner_email: None,
+                owner_name: None,
+                busy_times: None,
+            })?;
+            availabilities::set_availability_durations(caller, created.id.clone(), vec![DurationOption {
+                minutes: duration,
+                buffer_before_minutes: 0,
+                buffer_after_minutes: 0,
+            }])?;
+            Some(created.id)
+        };
+
+        imported_types.insert(uri.to_string(), (title.clone(), availability_id.clone()));
+        availabilities_report.push(ImportedAvailability {
+            title,
+            duration_minutes: duration,
+            slots: slots.clone(),
+            timezone: timezone.clone(),
+            availability_id,
+        });
+    }
+
+    let mut bookings_report = Vec::new();
+    for event in events["collection"].as_array().into_iter().flatten() {
+        let title = truncate(event["name"].as_str().unwrap_or("Calendly event"), 100);
+        let (Some(start), Some(end)) = (
+            event["start_time"].as_str().and_then(unix_seconds),
+            event["end_time"].as_str().and_then(unix_seconds),
+        ) else {
+            continue;
+        };
+        let event_type = event["event_type"].as_str().and_then(|uri| imported_types.get(uri));
+
+        let mut entry = ImportedBooking {
+            title: title.clone(),
+            start_time: start,
+            end_time: end,
+            availability_title: event_type.map(|(t, _)| t.clone()),
+            booking_id: None,
+            skipped_reason: None,
+        };
+        match event_type {
+            None => entry.skipped_reason = Some("Event type was not imported".to_string()),
+            Some((_, Some(availability_id))) => {
+                let result = availabilities::get_availability(availability_id.clone()).and_then(|availability| {
+                    bookings::import_booking(&availability, CreateBookingRequest {
+                        availability_id: availability_id.clone(),
+                        start_time: start,
+                        end_time: end,
+                        guest_name: title,
+                        guest_email: None,
+                        guest_timezone: None,
+                        field_answers: None,
+                    })
+                });
+                match result {
+                    Ok(booking) => entry.booking_id = Some(booking.id),
+                    Err(e) => entry.skipped_reason = Some(e),
+                }
+            }
+            Some((_, None)) => {} // Dry run
+        }
+        bookings_report.push(entry);
+    }
+
+    ic_cdk::println!(
+        "📥 Calendly import for {} (dry_run={}): {} event types, {} events",
+        caller.to_text(), dry_run, availabilities_report.len(), bookings_report.len()
+    );
+    Ok(CalendlyImportReport {
+        dry_run,
+        availabilities: availabilities_report,
+        bookings: bookings_report,
+    })
+}
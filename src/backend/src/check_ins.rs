@@ -0,0 +1,219 @@
+//! Check-in for booked sessions (workshops, office hours). Each booking gets
+//! a token the guest shows at the door, e.g. as a QR code; whoever runs the
+//! session for the owner checks them in with it. Check-ins make up the
+//! owner's attendance list per session and the no-show numbers per
+//! availability.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::bookings::{self, Booking, BookingStatus};
+use crate::entropy;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Guests may be checked in from this long before the start (seconds)
+const EARLY_CHECK_IN_SECS: u64 = 30 * 60;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct CheckIn {
+    token: String,
+    checked_in_at: Option<u64>,
+    checked_in_by: Option<Principal>,
+}
+
+/// One guest of a session, for the owner
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct AttendanceEntry {
+    pub booking_id: String,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub checked_in_at: Option<u64>,
+}
+
+/// Attendance over an availability's past confirmed bookings
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct NoShowStats {
+    pub availability_id: String,
+    pub past_bookings: u64,
+    pub attended: u64,
+    pub no_shows: u64,
+    pub no_show_rate_percent: u8,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl CheckIn {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Its empty token matches no guest.
+    fn unreadable() -> Self {
+        CheckIn {
+            token: String::new(),
+            checked_in_at: None,
+            checked_in_by: None,
+        }
+    }
+}
+
+impl Storable for CheckIn {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "CheckIn").unwrap_or_else(CheckIn::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Booking ID -> check-in token and state
+    static CHECK_INS: RefCell<StableBTreeMap<String, CheckIn, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(76)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    CHECK_INS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn checked_in_at(booking_id: &str) -> Option<u64> {
+    CHECK_INS.with(|c| c.borrow().get(&booking_id.to_string())).and_then(|c| c.checked_in_at)
+}
+
+fn entry(booking: Booking) -> AttendanceEntry {
+    AttendanceEntry {
+        checked_in_at: checked_in_at(&booking.id),
+        booking_id: booking.id,
+        guest_name: booking.guest_name,
+        guest_email: booking.guest_email,
+    }
+}
+
+fn rate_percent(part: u64, total: u64) -> u8 {
+    if total == 0 {
+        return 0;
+    }
+    (part * 100 / total) as u8
+}
+
+// ============================================================================
+// Check-in
+// ============================================================================
+
+/// The guest's check-in token for a booking, created on first request
+pub fn get_token(caller: Principal, booking_id: String) -> Result<String, String> {
+    let booking = bookings::get_booking(caller, booking_id.clone())?;
+    if booking.status != BookingStatus::Confirmed {
+        return Err("Only confirmed bookings can be checked in".to_string());
+    }
+    if let Some(existing) = CHECK_INS.with(|c| c.borrow().get(&booking_id)) {
+        return Ok(existing.token);
+    }
+
+    let token = entropy::next_token(16)?;
+    CHECK_INS.with(|c| c.borrow_mut().insert(booking_id, CheckIn { token: token.clone(), checked_in_at: None, checked_in_by: None }));
+    Ok(token)
+}
+
+/// Check a guest in with the token they show; for whoever manages the booking
+pub fn check_in(caller: Principal, booking_id: String, guest_token: String) -> Result<AttendanceEntry, String> {
+    let booking = bookings::find(&booking_id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if !bookings::manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "only the owner can check guests in"));
+    }
+    if booking.status != BookingStatus::Confirmed {
+        return Err("Only confirmed bookings can be checked in".to_string());
+    }
+    let now = time() / 1_000_000_000;
+    if now + EARLY_CHECK_IN_SECS < booking.start_time || now > booking.end_time {
+        return Err("Check-in opens 30 minutes before the start and closes at the end".to_string());
+    }
+
+    CHECK_INS.with(|c| {
+        let mut map = c.borrow_mut();
+        let mut record = map.get(&booking_id)
+            .filter(|record| !record.token.is_empty() && record.token == guest_token)
+            .ok_or("Invalid check-in token")?;
+        if record.checked_in_at.is_some() {
+            return Err("Guest is already checked in".to_string());
+        }
+        record.checked_in_at = Some(time());
+        record.checked_in_by = Some(caller);
+        map.insert(booking_id.clone(), record);
+        Ok(())
+    })?;
+
+    ic_cdk::println!("🎟️ Checked in booking {}", booking_id);
+    Ok(entry(booking))
+}
+
+// ============================================================================
+// Attendance
+// ============================================================================
+
+/// Everyone booked into the session starting at `start_time`, with their
+/// check-in time (None = not checked in)
+pub fn attendance(caller: Principal, availability_id: String, start_time: u64) -> Result<Vec<AttendanceEntry>, String> {
+    Ok(bookings::list_availability_bookings(caller, availability_id)?
+        .into_iter()
+        .filter(|bk| bk.start_time == start_time && bk.status == BookingStatus::Confirmed)
+        .map(entry)
+        .collect())
+}
+
+/// Attended and no-show counts over the availability's finished bookings
+pub fn no_show_stats(caller: Principal, availability_id: String) -> Result<NoShowStats, String> {
+    let now = time() / 1_000_000_000;
+    let past: Vec<Booking> = bookings::list_availability_bookings(caller, availability_id.clone())?
+        .into_iter()
+        .filter(|bk| bk.status == BookingStatus::Confirmed && bk.end_time <= now)
+        .collect();
+    let attended = past.iter().filter(|bk| checked_in_at(&bk.id).is_some()).count() as u64;
+    let past_bookings = past.len() as u64;
+    Ok(NoShowStats {
+        availability_id,
+        past_bookings,
+        attended,
+        no_shows: past_bookings - attended,
+        no_show_rate_percent: rate_percent(past_bookings - attended, past_bookings),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_show_rate_rounds_down_and_handles_no_bookings() {
+        assert_eq!(rate_percent(0, 0), 0);
+        assert_eq!(rate_percent(1, 3), 33);
+        assert_eq!(rate_percent(3, 3), 100);
+    }
+}
@@ -0,0 +1,166 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+use ic_cdk::api::call::{CallResult, RejectionCode};
+use ic_cdk::api::management_canister::http_request::{self, CanisterHttpRequestArgument, HttpResponse};
+use crate::clock::time;
+use crate::service_status;
+use crate::error_codes::{self, ErrorCode};
+
+/// Consecutive failures that open the breaker
+const FAILURE_THRESHOLD: u32 = 5;
+/// How long an open breaker rejects calls before letting one probe through
+const OPEN_DURATION: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Provider {
+    Google,
+    Calendly,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BreakerState {
+    Closed,
+    Open,      // Calls fail fast with UpstreamUnavailable
+    HalfOpen,  // The next call is a probe; its outcome closes or re-opens the breaker
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct BreakerStatus {
+    pub provider: Provider,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<u64>,
+}
+
+struct Breaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<u64>,
+    probe_started_at: Option<u64>, // A probe older than OPEN_DURATION is assumed lost
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Breaker { state: BreakerState::Closed, consecutive_failures: 0, opened_at: None, probe_started_at: None }
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Heap-only: every breaker starts closed again after an upgrade
+    static BREAKERS: RefCell<HashMap<Provider, Breaker>> = RefCell::new(HashMap::new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Server errors and rate limiting mean the provider is unhealthy; other
+/// statuses are answers to the request itself
+fn is_upstream_failure(response: &HttpResponse) -> bool {
+    response.status == 429u16 || response.status >= 500u16
+}
+
+fn open(provider: Provider, breaker: &mut Breaker) {
+    breaker.state = BreakerState::Open;
+    breaker.opened_at = Some(time());
+    breaker.probe_started_at = None;
+    ic_cdk::println!("🔌 [breaker] {:?} opened after {} failures", provider, breaker.consecutive_failures);
+    ic_cdk_timers::set_timer(OPEN_DURATION, move || {
+        BREAKERS.with(|b| {
+            if let Some(breaker) = b.borrow_mut().get_mut(&provider) {
+                if breaker.state == BreakerState::Open {
+                    breaker.state = BreakerState::HalfOpen;
+                }
+            }
+        });
+    });
+}
+
+fn record(provider: Provider, success: bool) {
+    service_status::record_outcall(provider, success);
+    BREAKERS.with(|b| {
+        let mut map = b.borrow_mut();
+        let breaker = map.entry(provider).or_default();
+        breaker.probe_started_at = None;
+        if success {
+            if breaker.state != BreakerState::Closed {
+                ic_cdk::println!("🔌 [breaker] {:?} closed", provider);
+            }
+            *breaker = Breaker::default();
+            return;
+        }
+        breaker.consecutive_failures += 1;
+        if breaker.state == BreakerState::HalfOpen || breaker.consecutive_failures >= FAILURE_THRESHOLD {
+            open(provider, breaker);
+        }
+    });
+}
+
+// ============================================================================
+// Breaker
+// ============================================================================
+
+/// Fails fast while the provider's breaker is open. In half-open state one
+/// caller is let through as the probe and the rest keep failing fast.
+pub fn check(provider: Provider) -> Result<(), String> {
+    let now = time();
+    BREAKERS.with(|b| {
+        let mut map = b.borrow_mut();
+        let breaker = map.entry(provider).or_default();
+        match breaker.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::HalfOpen if breaker.probe_started_at.is_none_or(|at| now > at + OPEN_DURATION.as_nanos() as u64) => {
+                breaker.probe_started_at = Some(now);
+                Ok(())
+            }
+            _ => Err(error_codes::coded(ErrorCode::UpstreamUnavailable, format!(
+                "{:?} is failing; try again in about {} seconds",
+                provider,
+                OPEN_DURATION.as_secs()
+            ))),
+        }
+    })
+}
+
+/// Drop-in replacement for the management canister's `http_request` that
+/// feeds the provider's breaker. Callers run `check` right before it, so an
+/// open breaker surfaces as a readable UpstreamUnavailable error.
+pub async fn http_request(
+    provider: Provider,
+    request: CanisterHttpRequestArgument,
+    cycles: u128,
+) -> CallResult<(HttpResponse,)> {
+    let open = BREAKERS.with(|b| b.borrow().get(&provider).is_some_and(|br| br.state == BreakerState::Open));
+    if open {
+        return Err((RejectionCode::SysTransient, error_codes::coded(ErrorCode::UpstreamUnavailable, format!("{:?}", provider))));
+    }
+    let result = http_request::http_request(request, cycles).await;
+    record(provider, result.as_ref().is_ok_and(|(response,)| !is_upstream_failure(response)));
+    result
+}
+
+/// Breaker state of every provider that has been called since the last upgrade
+pub fn get_breaker_status() -> Vec<BreakerStatus> {
+    BREAKERS.with(|b| {
+        b.borrow()
+            .iter()
+            .map(|(provider, breaker)| BreakerStatus {
+                provider: *provider,
+                state: breaker.state,
+                consecutive_failures: breaker.consecutive_failures,
+                opened_at: breaker.opened_at,
+            })
+            .collect()
+    })
+}
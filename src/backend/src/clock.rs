@@ -0,0 +1,103 @@
+//! The canister's source of time. Every module reads the time through
+//! `clock::time()` instead of `ic_cdk::api::time()`, so unit tests can run
+//! time-dependent logic (expiry, holds, reminders, free slots) against a
+//! mock clock they set and advance, since the system time is only
+//! available inside a canister.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// ============================================================================
+// Clocks
+// ============================================================================
+
+pub trait Clock {
+    /// Nanoseconds since the Unix epoch
+    fn now(&self) -> u64;
+}
+
+/// System time of the subnet, the same for the whole round
+#[cfg_attr(test, allow(dead_code))]
+pub struct IcClock;
+
+impl Clock for IcClock {
+    fn now(&self) -> u64 {
+        ic_cdk::api::time()
+    }
+}
+
+/// Clock that only moves when told to
+#[cfg(test)]
+pub struct MockClock(std::cell::Cell<u64>);
+
+#[cfg(test)]
+impl MockClock {
+    pub fn at(now: u64) -> Rc<Self> {
+        Rc::new(MockClock(std::cell::Cell::new(now)))
+    }
+
+    pub fn set(&self, now: u64) {
+        self.0.set(now);
+    }
+
+    pub fn advance(&self, by_ns: u64) {
+        self.0.set(self.0.get() + by_ns);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static CLOCK: RefCell<Rc<dyn Clock>> = RefCell::new(default_clock());
+}
+
+#[cfg(not(test))]
+fn default_clock() -> Rc<dyn Clock> {
+    Rc::new(IcClock)
+}
+
+/// 2025-01-01T00:00:00Z, so tests that never set the clock get a plausible time
+#[cfg(test)]
+fn default_clock() -> Rc<dyn Clock> {
+    MockClock::at(1_735_689_600 * 1_000_000_000)
+}
+
+// ============================================================================
+// Time
+// ============================================================================
+
+/// Current time in nanoseconds since the Unix epoch
+pub fn time() -> u64 {
+    CLOCK.with(|c| c.borrow().now())
+}
+
+/// Make `clock` the source of time for this thread (each test runs on its own)
+#[cfg(test)]
+pub fn install(clock: Rc<dyn Clock>) {
+    CLOCK.with(|c| *c.borrow_mut() = clock);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_follow_the_installed_clock() {
+        let clock = MockClock::at(1_000);
+        install(clock.clone());
+        assert_eq!(time(), 1_000);
+        clock.advance(500);
+        assert_eq!(time(), 1_500);
+        clock.set(42);
+        assert_eq!(time(), 42);
+    }
+}
@@ -0,0 +1,288 @@
+//! Counters kept up to date as records are written, so stats endpoints read
+//! a handful of entries instead of scanning whole maps (which would not fit
+//! a query's instruction limit). Each count is kept per user, per org and
+//! globally, and is updated in the same message as the write it counts.
+//!
+//! Availabilities are counted where they are inserted and removed; bookings
+//! in `bookings::write_projection`, the one place their stored state changes.
+//! The global availability total is seeded from the map's length on upgrade;
+//! `index_rebuild` recounts everything else from the maps.
+
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ops::Bound as RangeBound;
+use crate::clock::time;
+use ic_stable_structures::{memory_manager::MemoryId, StableBTreeMap};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::AVAILABILITIES;
+use crate::bookings::{Booking, BookingStatus};
+use crate::tenants::{self, DEFAULT_TENANT};
+use crate::index_rebuild::{self, RebuildChunk, Walk};
+
+const DAY_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CounterScope {
+    Global,
+    User(Principal),
+    Org(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Metric {
+    Availabilities,
+    Bookings,          // Confirmed bookings still stored
+    CancelledBookings, // Cancelled bookings still stored
+}
+
+#[derive(CandidType, Serialize, Clone, Debug, Default)]
+pub struct CounterSet {
+    pub availabilities: u64,
+    pub bookings: u64,
+    pub cancelled_bookings: u64,
+}
+
+/// Public, aggregate-only numbers for the stats page
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct PlatformStats {
+    pub total_availabilities: u64,
+    pub total_bookings: u64,
+    pub bookings_this_week: u64,
+    pub active_orgs: u64,           // Orgs with at least one booking this week
+    pub week_start: u64,            // Nanoseconds, Monday 00:00 UTC
+    pub generated_at: u64,
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Counter name -> value
+    static COUNTERS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(72)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn key(scope: &CounterScope, metric: Metric) -> String {
+    let metric = match metric {
+        Metric::Availabilities => "availabilities",
+        Metric::Bookings => "bookings",
+        Metric::CancelledBookings => "cancelled_bookings",
+    };
+    match scope {
+        CounterScope::Global => metric.to_string(),
+        CounterScope::User(principal) => format!("user|{}|{}", principal, metric),
+        CounterScope::Org(tenant_id) => format!("org|{}|{}", tenant_id, metric),
+    }
+}
+
+/// The user, their org and the global scope
+fn scopes_of(owner: Principal) -> [CounterScope; 3] {
+    [CounterScope::User(owner), CounterScope::Org(tenants::tenant_of(owner)), CounterScope::Global]
+}
+
+fn bump(owner: Principal, metric: Metric, delta: i64) {
+    for scope in scopes_of(owner) {
+        add(key(&scope, metric), delta);
+    }
+}
+
+fn booking_metric(booking: &Booking) -> Option<Metric> {
+    match booking.status {
+        BookingStatus::Confirmed => Some(Metric::Bookings),
+        BookingStatus::Cancelled => Some(Metric::CancelledBookings),
+        BookingStatus::Pending => None,
+    }
+}
+
+/// Monday 00:00 UTC of the week containing `nanos` (1970-01-01 was a Thursday)
+fn week_start(nanos: u64) -> u64 {
+    let days = nanos / DAY_NANOS;
+    (days - (days + 3) % 7) * DAY_NANOS
+}
+
+fn bookings_key(week: u64) -> String {
+    format!("bookings|week|{}", week)
+}
+
+fn org_bookings_key(tenant_id: &str, week: u64) -> String {
+    format!("org|{}|bookings|week|{}", tenant_id, week)
+}
+
+fn active_orgs_key(week: u64) -> String {
+    format!("active_orgs|week|{}", week)
+}
+
+fn get(key: &str) -> u64 {
+    COUNTERS.with(|c| c.borrow().get(&key.to_string())).unwrap_or(0)
+}
+
+/// Apply `delta` and return the new value (never below zero)
+fn add(key: String, delta: i64) -> u64 {
+    COUNTERS.with(|c| {
+        let mut map = c.borrow_mut();
+        let value = map.get(&key).unwrap_or(0).saturating_add_signed(delta);
+        map.insert(key, value);
+        value
+    })
+}
+
+// ============================================================================
+// Write Hooks
+// ============================================================================
+
+/// Seed the totals that can be read off a map's length; runs on upgrade
+pub fn seed() {
+    let global = key(&CounterScope::Global, Metric::Availabilities);
+    if COUNTERS.with(|c| c.borrow().contains_key(&global)) {
+        return;
+    }
+    let total = AVAILABILITIES.with(|a| a.borrow().len());
+    COUNTERS.with(|c| c.borrow_mut().insert(global, total));
+    ic_cdk::println!("🔢 Seeded availability counter at {}", total);
+}
+
+pub fn availability_added(owner: Principal, id: &str) {
+    if !index_rebuild::counts_later(Walk::Availabilities, id) {
+        bump(owner, Metric::Availabilities, 1);
+    }
+}
+
+pub fn availability_removed(owner: Principal, id: &str) {
+    if !index_rebuild::counts_later(Walk::Availabilities, id) {
+        bump(owner, Metric::Availabilities, -1);
+    }
+}
+
+/// A stored booking went from `before` to `after` (`None` = not stored)
+pub fn booking_changed(before: Option<&Booking>, after: Option<&Booking>) {
+    let Some(id) = before.or(after).map(|b| b.id.as_str()) else {
+        return;
+    };
+    if index_rebuild::counts_later(Walk::Bookings, id) {
+        return;
+    }
+    let old = before.and_then(|b| booking_metric(b).map(|metric| (b.owner, metric)));
+    let new = after.and_then(|b| booking_metric(b).map(|metric| (b.owner, metric)));
+    if old == new {
+        return;
+    }
+    if let Some((owner, metric)) = old {
+        bump(owner, metric, -1);
+    }
+    if let Some((owner, metric)) = new {
+        bump(owner, metric, 1);
+    }
+}
+
+/// A booking was confirmed (directly or once its payment settled)
+pub fn booking_confirmed(owner: Principal) {
+    let week = week_start(time());
+    add(bookings_key(week), 1);
+    let tenant_id = tenants::tenant_of(owner);
+    if tenant_id != DEFAULT_TENANT && add(org_bookings_key(&tenant_id, week), 1) == 1 {
+        add(active_orgs_key(week), 1);
+    }
+}
+
+// ============================================================================
+// Index Rebuild
+// ============================================================================
+
+/// Zero the next `limit` counters after `after`. Weekly counters record
+/// events rather than stored state, so they are kept.
+pub fn reset_chunk(after: Option<String>, limit: usize) -> RebuildChunk {
+    let start = after.map_or(RangeBound::Unbounded, RangeBound::Excluded);
+    let keys: Vec<String> = COUNTERS.with(|c| {
+        c.borrow().range((start, RangeBound::Unbounded)).take(limit).map(|(key, _)| key).collect()
+    });
+    let stored: Vec<&String> = keys.iter().filter(|key| !key.contains("|week|")).collect();
+    COUNTERS.with(|c| {
+        let mut map = c.borrow_mut();
+        for key in &stored {
+            map.remove(key);
+        }
+    });
+    RebuildChunk {
+        next: if keys.len() == limit { keys.last().cloned() } else { None },
+        scanned: keys.len() as u64,
+        fixed: 0,
+    }
+}
+
+/// Count a stored availability found by the rebuild walk
+pub fn count_availability(owner: Principal) {
+    bump(owner, Metric::Availabilities, 1);
+}
+
+/// Count a stored booking found by the rebuild walk
+pub fn count_booking(booking: &Booking) {
+    if let Some(metric) = booking_metric(booking) {
+        bump(booking.owner, metric, 1);
+    }
+}
+
+/// Drop every counter (dev fixtures only)
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    COUNTERS.with(|c| c.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+pub fn counts(scope: &CounterScope) -> CounterSet {
+    CounterSet {
+        availabilities: get(&key(scope, Metric::Availabilities)),
+        bookings: get(&key(scope, Metric::Bookings)),
+        cancelled_bookings: get(&key(scope, Metric::CancelledBookings)),
+    }
+}
+
+pub fn platform_stats() -> PlatformStats {
+    let now = time();
+    let week = week_start(now);
+    PlatformStats {
+        total_availabilities: get(&key(&CounterScope::Global, Metric::Availabilities)),
+        total_bookings: get(&key(&CounterScope::Global, Metric::Bookings)),
+        bookings_this_week: get(&bookings_key(week)),
+        active_orgs: get(&active_orgs_key(week)),
+        week_start: week,
+        generated_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weeks_start_on_monday_utc() {
+        // 2024-01-01 was a Monday
+        let monday = 19_723 * DAY_NANOS;
+        assert_eq!(week_start(monday), monday);
+        assert_eq!(week_start(monday + 6 * DAY_NANOS + DAY_NANOS - 1), monday);
+        assert_eq!(week_start(monday - 1), monday - 7 * DAY_NANOS);
+    }
+
+    #[test]
+    fn global_keys_keep_their_original_names() {
+        let user = Principal::from_slice(&[1]);
+        assert_eq!(key(&CounterScope::Global, Metric::Availabilities), "availabilities");
+        assert_eq!(key(&CounterScope::User(user), Metric::Bookings), format!("user|{}|bookings", user));
+        assert_eq!(key(&CounterScope::Org("acme".into()), Metric::CancelledBookings), "org|acme|cancelled_bookings");
+    }
+}
@@ -0,0 +1,208 @@
+//! Opaque pagination cursors shared by the paged listings. A cursor carries
+//! the position to resume at plus the listing and filters it was issued
+//! for, signed with a key only the canister holds, so clients can neither
+//! forge positions nor replay a cursor against another listing.
+
+use candid::{CandidType, Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{quarantine, response_size, webhook_auth};
+
+/// Bytes of the HMAC kept in a cursor
+const TAG_LEN: usize = 16;
+const MAX_CURSOR_LEN: usize = 1_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct CursorKey {
+    key: Option<Vec<u8>>, // Generated from raw_rand on first start
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq)]
+struct CursorPayload {
+    listing: String,
+    filters: String,
+    position: String,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for CursorKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "CursorKey").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static CURSOR_KEY: RefCell<StableCell<CursorKey, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(63))),
+            CursorKey::default(),
+        ).expect("Failed to init cursor key")
+    );
+}
+
+// ============================================================================
+// Key Setup
+// ============================================================================
+
+/// Create the signing key right after install; kept across upgrades so
+/// outstanding cursors stay valid
+pub fn start_timer() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || ic_cdk::spawn(ensure_key()));
+}
+
+async fn ensure_key() {
+    if CURSOR_KEY.with(|k| k.borrow().get().key.is_some()) {
+        return;
+    }
+    match ic_cdk::api::management_canister::main::raw_rand().await {
+        Ok((bytes,)) => {
+            CURSOR_KEY.with(|k| k.borrow_mut().set(CursorKey { key: Some(bytes) }))
+                .expect("Failed to store cursor key");
+        }
+        Err((code, msg)) => {
+            ic_cdk::println!("❌ [cursor] Failed to get randomness: {:?} - {}", code, msg);
+            start_timer();
+        }
+    }
+}
+
+fn key() -> Result<Vec<u8>, String> {
+    CURSOR_KEY.with(|k| k.borrow().get().key.clone())
+        .ok_or_else(|| "Paging is still starting up, try again shortly".to_string())
+}
+
+// ============================================================================
+// Sealing
+// ============================================================================
+
+fn seal(key: &[u8], payload: &CursorPayload) -> String {
+    let mut bytes = Encode!(payload).unwrap();
+    let tag = webhook_auth::hmac_sha256(key, &bytes);
+    bytes.extend_from_slice(&tag[..TAG_LEN]);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn open(key: &[u8], cursor: &str) -> Result<CursorPayload, String> {
+    const INVALID: &str = "Invalid cursor";
+    if cursor.len() > MAX_CURSOR_LEN {
+        return Err(INVALID.to_string());
+    }
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| INVALID)?;
+    if bytes.len() <= TAG_LEN {
+        return Err(INVALID.to_string());
+    }
+    let (body, tag) = bytes.split_at(bytes.len() - TAG_LEN);
+    if !webhook_auth::constant_time_eq(&webhook_auth::hmac_sha256(key, body)[..TAG_LEN], tag) {
+        return Err(INVALID.to_string());
+    }
+    Decode!(body, CursorPayload).map_err(|_| INVALID.to_string())
+}
+
+fn position(key: &[u8], cursor: &str, listing: &str, filters: &str) -> Result<String, String> {
+    let payload = open(key, cursor)?;
+    if payload.listing != listing || payload.filters != filters {
+        return Err("Cursor belongs to a different listing".to_string());
+    }
+    Ok(payload.position)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// HMAC of `message` under the cursor key, for other opaque tokens. Callers
+/// prefix their own context so tags of one kind can't pass as another.
+pub fn sign(message: &[u8]) -> Result<[u8; 32], String> {
+    Ok(webhook_auth::hmac_sha256(&key()?, message))
+}
+
+/// Cursor that resumes `listing` (with these `filters`) at `position`
+pub fn encode(listing: &str, filters: &str, position: &str) -> Result<String, String> {
+    let payload = CursorPayload {
+        listing: listing.to_string(),
+        filters: filters.to_string(),
+        position: position.to_string(),
+    };
+    Ok(seal(&key()?, &payload))
+}
+
+/// The position a cursor resumes at, if it was issued for this listing and filters
+pub fn decode(cursor: &str, listing: &str, filters: &str) -> Result<String, String> {
+    position(&key()?, cursor, listing, filters)
+}
+
+/// One page of `items` (at most `max_items`, and never over the reply
+/// limit), resuming at `cursor`. Returns the page and the cursor for the
+/// next one.
+pub fn page<T: CandidType>(
+    items: Vec<T>,
+    cursor: Option<String>,
+    listing: &str,
+    filters: &str,
+    max_items: usize,
+    key: impl Fn(&T) -> String,
+) -> Result<(Vec<T>, Option<String>), String> {
+    let start = cursor.map(|c| decode(&c, listing, filters)).transpose()?;
+    let (items, next) = response_size::page_from(items, start, max_items, key)?;
+    let next_cursor = next.map(|position| encode(listing, filters, &position)).transpose()?;
+    Ok((items, next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(position: &str) -> CursorPayload {
+        CursorPayload {
+            listing: "bookings".to_string(),
+            filters: "owner".to_string(),
+            position: position.to_string(),
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let cursor = seal(b"key", &payload("b42"));
+        assert_eq!(open(b"key", &cursor), Ok(payload("b42")));
+        assert_eq!(position(b"key", &cursor, "bookings", "owner"), Ok("b42".to_string()));
+    }
+
+    #[test]
+    fn tampered_or_foreign_cursors_are_rejected() {
+        let cursor = seal(b"key", &payload("b42"));
+        assert!(open(b"other key", &cursor).is_err());
+        let mut bytes = URL_SAFE_NO_PAD.decode(&cursor).unwrap();
+        bytes[10] ^= 1;
+        assert!(open(b"key", &URL_SAFE_NO_PAD.encode(bytes)).is_err());
+        assert!(open(b"key", "not a cursor").is_err());
+        assert!(position(b"key", &cursor, "bookings", "someone else").is_err());
+        assert!(position(b"key", &cursor, "availabilities", "owner").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Everything the canister keeps about the caller, in one download.
+
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use crate::clock::time;
+use crate::availabilities::{self, Availability};
+use crate::bookings::{self, Booking};
+use crate::regions::{self, RegionDeclaration};
+use crate::response_size;
+use crate::terms::{self, TermsAcceptance};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct MyDataExport {
+    pub principal: Principal,
+    pub exported_at: u64,
+    pub availabilities: Vec<Availability>,
+    pub bookings: Vec<Booking>,                  // Made on the caller's availabilities
+    pub terms_acceptances: Vec<TermsAcceptance>,
+    pub region: Option<RegionDeclaration>,
+    pub truncated: bool,                         // Lists were cut to fit one reply; use the paged listings
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+pub fn export_my_data(caller: Principal) -> MyDataExport {
+    let all_availabilities = availabilities::list_user_availabilities(caller);
+    let all_bookings = bookings::list_owner_bookings(caller);
+    let total = all_availabilities.len() + all_bookings.len();
+
+    // Split the reply budget evenly so neither list crowds out the other
+    let budget = response_size::MAX_RESPONSE_BYTES / 2;
+    let mut availabilities = all_availabilities;
+    availabilities.truncate(response_size::fitting_len(&availabilities, budget));
+    let mut bookings = all_bookings;
+    bookings.truncate(response_size::fitting_len(&bookings, budget));
+
+    MyDataExport {
+        principal: caller,
+        exported_at: time(),
+        truncated: availabilities.len() + bookings.len() < total,
+        availabilities,
+        bookings,
+        terms_acceptances: terms::acceptances(caller),
+        region: regions::get_declaration(caller),
+    }
+}
@@ -0,0 +1,133 @@
+//! ISO 8601 parsing and formatting for Unix-second timestamps. Pure, so the
+//! round trip is property-tested off-chain.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse an ISO 8601 timestamp to Unix seconds. Accepted forms:
+/// - `2024-01-15T10:00:00Z`, `2024-01-15T10:00:00.250-08:00` (fractions are truncated)
+/// - `2024-01-15T10:00Z` (seconds omitted)
+/// - `2024-01-15` (date only, taken as midnight UTC)
+///
+/// A date-time without `Z` or an offset is rejected: it names a different
+/// instant in every timezone, and guessing one silently shifts events.
+pub fn parse_iso8601(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let instant = if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        dt.timestamp()
+    } else if let Ok(dt) = DateTime::parse_from_str(input, "%Y-%m-%dT%H:%M%#z") {
+        dt.timestamp()
+    } else if let Some(dt) = input.strip_suffix(['Z', 'z'])
+        .and_then(|naive| NaiveDateTime::parse_from_str(naive, "%Y-%m-%dT%H:%M").ok())
+    {
+        dt.and_utc().timestamp()
+    } else if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp()).unwrap_or_default()
+    } else if NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        || NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M").is_ok()
+    {
+        return Err(format!("'{}' has no UTC offset; add Z or e.g. +02:00", input));
+    } else {
+        return Err(format!("'{}' is not an ISO 8601 date or date-time (e.g. 2025-03-01T09:00:00Z)", input));
+    };
+    u64::try_from(instant).map_err(|_| format!("'{}' is before 1970", input))
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format Unix seconds as `YYYY-MM-DDTHH:MM:SSZ`, the form `parse_iso8601` reads back
+pub fn format_iso8601(unix_secs: u64) -> String {
+    i64::try_from(unix_secs)
+        .ok()
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // 2024-01-15T10:00:00Z
+    const T: u64 = 1_705_312_800;
+
+    /// Year 9999, the last year with a four-digit ISO 8601 representation
+    const MAX_SECS: u64 = 253_402_300_799;
+
+    #[test]
+    fn parses_utc_and_offsets() {
+        assert_eq!(parse_iso8601("2024-01-15T10:00:00Z"), Ok(T));
+        assert_eq!(parse_iso8601("2024-01-15T02:00:00-08:00"), Ok(T));
+        assert_eq!(parse_iso8601("2024-01-15T15:30:00+05:30"), Ok(T));
+        assert_eq!(parse_iso8601(" 2024-01-15T10:00:00Z "), Ok(T));
+    }
+
+    #[test]
+    fn truncates_fractional_seconds() {
+        assert_eq!(parse_iso8601("2024-01-15T10:00:00.999Z"), Ok(T));
+        assert_eq!(parse_iso8601("2024-01-15T10:00:00.123456789+00:00"), Ok(T));
+    }
+
+    #[test]
+    fn accepts_minutes_without_seconds() {
+        assert_eq!(parse_iso8601("2024-01-15T10:00Z"), Ok(T));
+        assert_eq!(parse_iso8601("2024-01-15T11:00+01:00"), Ok(T));
+    }
+
+    #[test]
+    fn date_only_is_midnight_utc() {
+        assert_eq!(parse_iso8601("2024-01-15"), Ok(T - 10 * 3600));
+    }
+
+    #[test]
+    fn rejects_date_times_without_offset() {
+        let err = parse_iso8601("2024-01-15T10:00:00").unwrap_err();
+        assert!(err.contains("no UTC offset"), "{}", err);
+        assert!(parse_iso8601("2024-01-15T10:00").unwrap_err().contains("no UTC offset"));
+    }
+
+    #[test]
+    fn rejects_malformed_and_pre_epoch_input() {
+        for input in ["", "2024-13-01", "2024-02-30T10:00:00Z", "15/01/2024", "2024-01-15T25:00:00Z", "10:00:00Z"] {
+            assert!(parse_iso8601(input).is_err(), "accepted {:?}", input);
+        }
+        assert!(parse_iso8601("1969-12-31T23:59:59Z").unwrap_err().contains("before 1970"));
+    }
+
+    #[test]
+    fn formats_in_utc_with_seconds() {
+        assert_eq!(format_iso8601(T), "2024-01-15T10:00:00Z");
+        assert_eq!(format_iso8601(0), "1970-01-01T00:00:00Z");
+    }
+
+    proptest! {
+        #[test]
+        fn format_then_parse_round_trips(secs in 0u64..=MAX_SECS) {
+            prop_assert_eq!(parse_iso8601(&format_iso8601(secs)), Ok(secs));
+        }
+
+        #[test]
+        fn offsets_name_the_same_instant(secs in 86_400u64..MAX_SECS - 86_400, offset_minutes in -14 * 60i64..=14 * 60) {
+            let offset = chrono::FixedOffset::east_opt((offset_minutes * 60) as i32).unwrap();
+            let local = DateTime::<Utc>::from_timestamp(secs as i64, 0).unwrap().with_timezone(&offset);
+            prop_assert_eq!(parse_iso8601(&local.to_rfc3339()), Ok(secs));
+        }
+
+        #[test]
+        fn fractions_never_round_up(secs in 0u64..MAX_SECS, nanos in 0u32..1_000_000_000) {
+            let dt = DateTime::<Utc>::from_timestamp(secs as i64, nanos).unwrap();
+            prop_assert_eq!(parse_iso8601(&dt.to_rfc3339_opts(SecondsFormat::AutoSi, true)), Ok(secs));
+        }
+
+        #[test]
+        fn arbitrary_text_never_panics(input in "\\PC{0,40}") {
+            let _ = parse_iso8601(&input);
+        }
+    }
+}
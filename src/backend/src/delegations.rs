@@ -0,0 +1,310 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::{cursor, quarantine, trace};
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_DELEGATES_PER_OWNER: usize = 20;
+/// Most recent delegate actions kept per owner
+const MAX_AUDIT_ENTRIES: usize = 500;
+const AUDIT_PAGE_SIZE: usize = 100;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DelegationScope {
+    ManageAvailabilities, // Edit, delete and configure the owner's availabilities
+    ManageBookings,       // See and cancel bookings on the owner's availabilities
+}
+
+/// Rights an owner has handed to another principal (e.g. an executive assistant)
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ManagementDelegation {
+    pub owner: Principal,
+    pub delegate: Principal,
+    pub scopes: Vec<DelegationScope>,
+    pub granted_at: u64,
+}
+
+/// Something a delegate did on the owner's behalf
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct DelegateAction {
+    pub actor: Principal,
+    pub action: String,
+    pub at: u64,
+    pub trace_id: Option<String>, // Trace of the call that did it (None before traces existed)
+}
+
+/// One page of the audit log; pass `next_cursor` back for older entries
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct DelegateAuditPage {
+    pub actions: Vec<DelegateAction>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DelegationList(pub Vec<ManagementDelegation>);
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AuditLog(pub Vec<DelegateAction>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for DelegationList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "DelegationList").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AuditLog {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "AuditLog").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Owner -> principals acting for them
+    static DELEGATIONS: RefCell<StableBTreeMap<Principal, DelegationList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(31)))
+        )
+    );
+
+    // Owner -> actions delegates took for them, oldest first
+    static DELEGATE_AUDIT: RefCell<StableBTreeMap<Principal, AuditLog, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(32)))
+        )
+    );
+}
+
+pub fn clear() {
+    DELEGATIONS.with(|m| m.borrow_mut().clear_new());
+    DELEGATE_AUDIT.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn delegations_of(owner: Principal) -> Vec<ManagementDelegation> {
+    DELEGATIONS.with(|d| d.borrow().get(&owner)).unwrap_or_default().0
+}
+
+/// Whether the caller may act for the owner within the scope (owners always can)
+pub fn allowed(caller: Principal, owner: Principal, scope: DelegationScope) -> bool {
+    caller == owner
+        || delegations_of(owner)
+            .iter()
+            .any(|d| d.delegate == caller && d.scopes.contains(&scope))
+}
+
+/// Record an action taken for the owner. Owners acting for themselves aren't logged.
+pub fn record(actor: Principal, owner: Principal, action: String) {
+    if actor == owner {
+        return;
+    }
+    ic_cdk::println!("🧑‍💼 {} acted for {}: {}", actor.to_text(), owner.to_text(), action);
+    DELEGATE_AUDIT.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut log = map.get(&owner).unwrap_or_default();
+        log.0.push(DelegateAction { actor, action, at: time(), trace_id: trace::current() });
+        if log.0.len() > MAX_AUDIT_ENTRIES {
+            let excess = log.0.len() - MAX_AUDIT_ENTRIES;
+            log.0.drain(..excess);
+        }
+        map.insert(owner, log);
+    });
+}
+
+/// Fails unless the caller may act for the owner within the scope
+pub fn require(caller: Principal, owner: Principal, scope: DelegationScope) -> Result<(), String> {
+    if !allowed(caller, owner, scope) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, format!("no {:?} delegation from this owner", scope)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// Grant (or replace) a delegate's scopes. An empty scope list revokes.
+pub fn grant_delegation(owner: Principal, delegate: Principal, scopes: Vec<DelegationScope>) -> Result<Option<ManagementDelegation>, String> {
+    if delegate == owner {
+        return Err("Cannot delegate to yourself".to_string());
+    }
+    if delegate == Principal::anonymous() {
+        return Err("Delegate must be signed in".to_string());
+    }
+
+    let mut list = delegations_of(owner);
+    list.retain(|d| d.delegate != delegate);
+    let granted = if scopes.is_empty() {
+        None
+    } else {
+        if list.len() >= MAX_DELEGATES_PER_OWNER {
+            return Err(error_codes::coded(ErrorCode::QuotaExceeded, format!("at most {} delegates", MAX_DELEGATES_PER_OWNER)));
+        }
+        let mut unique = Vec::new();
+        for scope in scopes {
+            if !unique.contains(&scope) {
+                unique.push(scope);
+            }
+        }
+        let delegation = ManagementDelegation { owner, delegate, scopes: unique, granted_at: time() };
+        list.push(delegation.clone());
+        Some(delegation)
+    };
+
+    DELEGATIONS.with(|d| {
+        let mut map = d.borrow_mut();
+        if list.is_empty() {
+            map.remove(&owner);
+        } else {
+            map.insert(owner, DelegationList(list));
+        }
+    });
+    Ok(granted)
+}
+
+/// Principals the owner has delegated to
+pub fn list_delegates(owner: Principal) -> Vec<ManagementDelegation> {
+    delegations_of(owner)
+}
+
+/// Owners the caller may act for
+pub fn list_delegated_to_me(caller: Principal) -> Vec<ManagementDelegation> {
+    DELEGATIONS.with(|d| {
+        d.borrow()
+            .iter()
+            .flat_map(|(_, list)| list.0)
+            .filter(|d| d.delegate == caller)
+            .collect()
+    })
+}
+
+/// What delegates did on the owner's behalf, newest first
+pub fn get_delegate_audit_log(owner: Principal) -> Vec<DelegateAction> {
+    let mut log = DELEGATE_AUDIT.with(|a| a.borrow().get(&owner)).unwrap_or_default().0;
+    log.reverse();
+    log
+}
+
+/// `get_delegate_audit_log` one page at a time
+pub fn get_delegate_audit_log_page(owner: Principal, cursor: Option<String>) -> Result<DelegateAuditPage, String> {
+    let (actions, next_cursor) = cursor::page(
+        get_delegate_audit_log(owner),
+        cursor,
+        "delegate_audit",
+        &owner.to_text(),
+        AUDIT_PAGE_SIZE,
+        |a| format!("{}|{}|{}", a.at, a.actor, a.action),
+    )?;
+    Ok(DelegateAuditPage { actions, next_cursor })
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Delegations the principal granted or holds, plus its audit entries
+pub fn count_owned(principal: Principal) -> u32 {
+    let granted = delegations_of(principal).len() + list_delegated_to_me(principal).len();
+    let audited = DELEGATE_AUDIT.with(|a| a.borrow().get(&principal)).map_or(0, |log| log.0.len());
+    (granted + audited) as u32
+}
+
+/// Carry `from`'s delegations, both ways, and its audit log over to `to`
+/// (the same user under a new principal). Where `to` already has a
+/// delegation with the same counterpart, `to`'s own is kept.
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let mut moved = 0;
+    DELEGATIONS.with(|d| {
+        let mut map = d.borrow_mut();
+        if let Some(list) = map.remove(&from) {
+            let mut merged = map.get(&to).unwrap_or_default();
+            for mut delegation in list.0 {
+                if delegation.delegate != to && !merged.0.iter().any(|d| d.delegate == delegation.delegate) {
+                    delegation.owner = to;
+                    merged.0.push(delegation);
+                    moved += 1;
+                }
+            }
+            if !merged.0.is_empty() {
+                map.insert(to, merged);
+            }
+        }
+        let granted_to_from: Vec<(Principal, DelegationList)> = map.iter()
+            .filter(|(_, list)| list.0.iter().any(|d| d.delegate == from))
+            .collect();
+        for (owner, mut list) in granted_to_from {
+            let keep_own = owner == to || list.0.iter().any(|d| d.delegate == to);
+            list.0.retain_mut(|d| {
+                if d.delegate != from {
+                    return true;
+                }
+                if keep_own {
+                    return false;
+                }
+                d.delegate = to;
+                moved += 1;
+                true
+            });
+            if list.0.is_empty() {
+                map.remove(&owner);
+            } else {
+                map.insert(owner, list);
+            }
+        }
+    });
+    DELEGATE_AUDIT.with(|a| {
+        let mut map = a.borrow_mut();
+        if let Some(log) = map.remove(&from) {
+            moved += log.0.len() as u32;
+            let mut merged = map.get(&to).unwrap_or_default();
+            merged.0.extend(log.0);
+            merged.0.sort_by_key(|action| action.at);
+            if merged.0.len() > MAX_AUDIT_ENTRIES {
+                let excess = merged.0.len() - MAX_AUDIT_ENTRIES;
+                merged.0.drain(..excess);
+            }
+            map.insert(to, merged);
+        }
+    });
+    moved
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    DELEGATE_AUDIT.with(|m| storage::map_stats(&m.borrow()))
+}
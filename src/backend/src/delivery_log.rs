@@ -0,0 +1,170 @@
+//! Per-booking record of every notification delivery attempt, so an owner can
+//! see whether the guest's confirmation went out and send it again.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::bookings::{self, BookingStatus};
+use crate::integrations::{self, HookEvent};
+use crate::matrix;
+use crate::outbox::{self, SideEffect};
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Oldest attempts are dropped past this many per booking
+const MAX_RECORDS_PER_BOOKING: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum NotificationChannel {
+    IntegrationHook,
+    Matrix,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,   // The outbox retries, so a later attempt may still succeed
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryRecord {
+    pub channel: NotificationChannel,
+    pub target: Option<String>,               // Hook ID for IntegrationHook
+    pub event: HookEvent,
+    pub status: DeliveryStatus,
+    pub detail: String,                       // HTTP status or error
+    pub provider_message_id: Option<String>,  // None while responses are stripped for consensus
+    pub attempted_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct DeliveryLog {
+    records: Vec<DeliveryRecord>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for DeliveryLog {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "DeliveryLog").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Booking ID -> delivery attempts, oldest first
+    static DELIVERY_LOG: RefCell<StableBTreeMap<String, DeliveryLog, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(61)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    DELIVERY_LOG.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Note one delivery attempt; `result` is the HTTP status or the error
+pub fn record(
+    booking_id: &str,
+    channel: NotificationChannel,
+    target: Option<String>,
+    event: HookEvent,
+    result: &Result<String, String>,
+) {
+    let (status, detail) = match result {
+        Ok(status) => (DeliveryStatus::Delivered, status.clone()),
+        Err(e) => (DeliveryStatus::Failed, e.clone()),
+    };
+    DELIVERY_LOG.with(|l| {
+        let mut map = l.borrow_mut();
+        let mut log = map.get(&booking_id.to_string()).unwrap_or_default();
+        log.records.push(DeliveryRecord {
+            channel,
+            target,
+            event,
+            status,
+            detail,
+            provider_message_id: None,
+            attempted_at: time(),
+        });
+        let excess = log.records.len().saturating_sub(MAX_RECORDS_PER_BOOKING);
+        log.records.drain(..excess);
+        map.insert(booking_id.to_string(), log);
+    });
+}
+
+// ============================================================================
+// Queries & Resend
+// ============================================================================
+
+/// Delivery attempts for a booking, for whoever manages its bookings
+pub fn get_log(caller: Principal, booking_id: String) -> Result<Vec<DeliveryRecord>, String> {
+    let booking = bookings::find(&booking_id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if !bookings::manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "only the owner can see notification deliveries"));
+    }
+    Ok(DELIVERY_LOG.with(|l| l.borrow().get(&booking_id)).unwrap_or_default().records)
+}
+
+/// Queue the booking's current notification on a channel again; returns how
+/// many deliveries were queued
+pub fn resend(caller: Principal, booking_id: String, channel: NotificationChannel) -> Result<u32, String> {
+    let booking = bookings::find(&booking_id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+    if !bookings::manages(caller, &booking) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "only the owner can resend notifications"));
+    }
+    let event = match booking.status {
+        BookingStatus::Pending => return Err(error_codes::coded(ErrorCode::PaymentPending, "Booking payment is still in progress")),
+        BookingStatus::Confirmed => HookEvent::BookingCreated,
+        BookingStatus::Cancelled => HookEvent::BookingCancelled,
+    };
+
+    let effects: Vec<SideEffect> = match channel {
+        NotificationChannel::IntegrationHook => integrations::hook_ids_for(booking.owner, event)
+            .into_iter()
+            .map(|hook_id| SideEffect::IntegrationHook { hook_id, event, booking_id: booking.id.clone() })
+            .collect(),
+        NotificationChannel::Matrix if matrix::is_subscribed(booking.owner, event) => {
+            vec![SideEffect::Matrix { owner: booking.owner, event, booking_id: booking.id.clone() }]
+        }
+        NotificationChannel::Matrix => vec![],
+    };
+    if effects.is_empty() {
+        return Err(format!("No {:?} channel is set up for this event", channel));
+    }
+    let queued = effects.len() as u32;
+    for effect in effects {
+        outbox::enqueue(effect);
+    }
+    ic_cdk::println!("🔁 Resending {:?} for booking {} ({} queued)", channel, booking.id, queued);
+    Ok(queued)
+}
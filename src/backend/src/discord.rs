@@ -0,0 +1,334 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use serde_json::{json, Value};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, Availability};
+use crate::bookings::{self, CreateBookingRequest};
+use crate::http::{self, HttpRequest, HttpResponse};
+use crate::{entropy, principal_moves, quarantine, quotas, webhook_auth};
+use crate::quotas::QuotaKind;
+use crate::error_codes::{self, ErrorCode};
+
+/// Interactions endpoint URL path to register in the Discord developer portal
+pub const INTERACTIONS_PATH: &str = "/discord/interactions";
+
+const LINK_CODE_TTL_NS: u64 = 10 * 60 * 1_000_000_000;
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// How far ahead /book looks for a free slot
+const BOOK_HORIZON_DAYS: u64 = 14;
+const MAX_LISTED_SLOTS: usize = 10;
+
+// Interaction and response types from the Discord API
+const PING: u64 = 1;
+const APPLICATION_COMMAND: u64 = 2;
+const PONG: u64 = 1;
+const CHANNEL_MESSAGE: u64 = 4;
+const EPHEMERAL: u64 = 1 << 6;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiscordConfig {
+    pub public_key: Option<String>, // Application public key (hex); None = integration off
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for DiscordConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "DiscordConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static DISCORD_CONFIG: RefCell<StableCell<DiscordConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(37))),
+            DiscordConfig::default(),
+        ).expect("Failed to init Discord config")
+    );
+
+    // Discord user ID -> linked weeekaly principal
+    static DISCORD_LINKS: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(38)))
+        )
+    );
+
+    // One-time link code -> (principal, expiry in ns). Heap-only: codes live
+    // for minutes and can simply be requested again after an upgrade.
+    static LINK_CODES: RefCell<HashMap<String, (Principal, u64)>> = RefCell::new(HashMap::new());
+}
+
+pub fn clear() {
+    DISCORD_LINKS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Check the Ed25519 signature Discord puts on every interaction
+fn verify(req: &HttpRequest) -> Result<(), String> {
+    let public_key = DISCORD_CONFIG.with(|c| c.borrow().get().public_key.clone())
+        .ok_or("Discord integration is not configured")?;
+    let signature = http::header(req, "X-Signature-Ed25519").ok_or("Missing signature")?;
+    let timestamp = http::header(req, "X-Signature-Timestamp").ok_or("Missing timestamp")?;
+    let signed_at: u64 = timestamp.parse().map_err(|_| "Invalid timestamp")?;
+    webhook_auth::check_timestamp(signed_at, time() / 1_000_000_000, webhook_auth::DEFAULT_REPLAY_WINDOW_SECS)?;
+
+    let mut message = timestamp.as_bytes().to_vec();
+    message.extend_from_slice(&req.body);
+    webhook_auth::verify_ed25519(&public_key, &message, signature)
+}
+
+fn linked_principal(discord_id: &str) -> Option<Principal> {
+    DISCORD_LINKS.with(|l| l.borrow().get(&discord_id.to_string()))
+}
+
+/// The availability a linked user is booked through: their favorite, else their first
+fn primary_availability(principal: Principal) -> Option<Availability> {
+    let owned = availabilities::list_user_availabilities(principal);
+    owned.iter().find(|a| a.is_favorite).or(owned.first()).cloned()
+}
+
+fn option<'a>(data: &'a Value, name: &str) -> Option<&'a Value> {
+    data["options"].as_array()?
+        .iter()
+        .find(|o| o["name"] == name)
+        .map(|o| &o["value"])
+}
+
+/// "30m", "1h", "90" (minutes)
+fn parse_minutes(text: &str) -> Option<u32> {
+    let text = text.trim().to_lowercase();
+    if let Some(h) = text.strip_suffix('h') {
+        return h.trim().parse::<u32>().ok().map(|h| h * 60);
+    }
+    text.trim_end_matches(['m', 'i', 'n']).trim().parse().ok()
+}
+
+/// "today", "tomorrow" or a number of days from now (0 = today), as a UTC day start
+fn parse_day(text: &str, now: u64) -> Option<u64> {
+    let offset = match text.trim().to_lowercase().as_str() {
+        "" | "today" => 0,
+        "tomorrow" => 1,
+        other => other.parse::<u64>().ok().filter(|d| *d <= 30)?,
+    };
+    Some((now / DAY_SECS + offset) * DAY_SECS)
+}
+
+fn reply(content: String) -> Value {
+    json!({ "type": CHANNEL_MESSAGE, "data": { "content": content, "flags": EPHEMERAL } })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// `/link code:<code>` connects the Discord account to the principal that made the code
+fn link(discord_id: &str, data: &Value) -> String {
+    let code = option(data, "code").and_then(|v| v.as_str()).unwrap_or("").trim().to_uppercase();
+    let entry = LINK_CODES.with(|c| c.borrow_mut().remove(&code));
+    match entry {
+        Some((principal, expires_at)) if expires_at > time() => {
+            DISCORD_LINKS.with(|l| l.borrow_mut().insert(discord_id.to_string(), principal));
+            "Linked! People in this server can now use /free and /book with you.".to_string()
+        }
+        _ => "That code is invalid or expired. Create a new one in weeekaly settings.".to_string(),
+    }
+}
+
+/// `/free user:@user when:tomorrow` lists the user's free windows that day
+fn free(data: &Value) -> String {
+    let Some(target) = option(data, "user").and_then(|v| v.as_str()) else {
+        return "Pick a user.".to_string();
+    };
+    let Some(availability) = linked_principal(target).and_then(primary_availability) else {
+        return format!("<@{}> hasn't linked a weeekaly calendar yet.", target);
+    };
+    let now = time() / 1_000_000_000;
+    let when = option(data, "when").and_then(|v| v.as_str()).unwrap_or("today");
+    let Some(day) = parse_day(when, now) else {
+        return "`when` must be today, tomorrow or a number of days.".to_string();
+    };
+
+    match availabilities::get_free_slots(availability.id, day, day + DAY_SECS, None) {
+        Ok(slots) if slots.is_empty() => format!("<@{}> has no free time then.", target),
+        Ok(slots) => {
+            let lines: Vec<String> = slots.iter()
+                .take(MAX_LISTED_SLOTS)
+                .map(|s| format!("• <t:{}:f> – <t:{}:t>", s.start_time, s.end_time))
+                .collect();
+            format!("<@{}> is free:\n{}", target, lines.join("\n"))
+        }
+        Err(e) => error_codes::split(&e).1.to_string(),
+    }
+}
+
+/// `/book user:@user duration:30m` books the earliest free slot of that length
+/// with the invoking (linked) user as guest
+fn book(discord_id: &str, display_name: &str, data: &Value) -> String {
+    let Some(guest) = linked_principal(discord_id) else {
+        return "Link your weeekaly account first with /link.".to_string();
+    };
+    let Some(target) = option(data, "user").and_then(|v| v.as_str()) else {
+        return "Pick a user.".to_string();
+    };
+    let Some(availability) = linked_principal(target).and_then(primary_availability) else {
+        return format!("<@{}> hasn't linked a weeekaly calendar yet.", target);
+    };
+    let duration = option(data, "duration").and_then(|v| v.as_str()).unwrap_or("30m");
+    let Some(minutes) = parse_minutes(duration) else {
+        return "`duration` looks like 30m or 1h.".to_string();
+    };
+    if let Err(e) = quotas::consume_rate(guest, QuotaKind::BookingsPerDay) {
+        return error_codes::split(&e).1.to_string();
+    }
+
+    let now = time() / 1_000_000_000;
+    let slots = match availabilities::get_free_slots_as(
+        guest, availability.id.clone(), now, now + BOOK_HORIZON_DAYS * DAY_SECS, Some(minutes), None,
+    ) {
+        Ok(slots) => slots,
+        Err(e) => return error_codes::split(&e).1.to_string(),
+    };
+    let Some(slot) = slots.first() else {
+        return format!("<@{}> has no free {}-minute slot in the next two weeks.", target, minutes);
+    };
+
+    match bookings::create_booking(guest, CreateBookingRequest {
+        availability_id: availability.id,
+        start_time: slot.start_time,
+        end_time: slot.end_time,
+        guest_name: display_name.chars().take(100).collect(),
+        guest_email: None,
+        guest_timezone: None,
+        field_answers: None,
+    }) {
+        Ok(_) => format!("Booked with <@{}> at <t:{}:F>.", target, slot.start_time),
+        Err(e) => error_codes::split(&e).1.to_string(),
+    }
+}
+
+// ============================================================================
+// Entry Points
+// ============================================================================
+
+/// Handle a signed interaction POSTed by Discord
+pub fn handle(req: &HttpRequest) -> HttpResponse {
+    if req.method != "POST" {
+        return http::text(405, "Method not allowed");
+    }
+    if let Err(e) = verify(req) {
+        return http::text(401, &e);
+    }
+    let Ok(interaction) = serde_json::from_slice::<Value>(&req.body) else {
+        return http::text(400, "Invalid JSON");
+    };
+
+    let body = match interaction["type"].as_u64() {
+        Some(PING) => json!({ "type": PONG }),
+        // A captured command stays validly signed for the whole replay window
+        Some(APPLICATION_COMMAND) if !webhook_auth::claim_event("discord", interaction["id"].as_str().unwrap_or_default()) => {
+            reply("Already handled".to_string())
+        }
+        Some(APPLICATION_COMMAND) => {
+            // Guild interactions carry the user under `member`, DMs at the top level
+            let user = if interaction["member"]["user"].is_object() { &interaction["member"]["user"] } else { &interaction["user"] };
+            let discord_id = user["id"].as_str().unwrap_or_default();
+            let name = user["global_name"].as_str().or(user["username"].as_str()).unwrap_or("Discord user");
+            let data = &interaction["data"];
+            reply(match data["name"].as_str() {
+                Some("link") => link(discord_id, data),
+                Some("free") => free(data),
+                Some("book") => book(discord_id, name, data),
+                _ => "Unknown command".to_string(),
+            })
+        }
+        _ => return http::text(400, "Unsupported interaction type"),
+    };
+    http::json(200, &body)
+}
+
+/// Code to paste into `/link` within ten minutes
+pub fn create_discord_link_code(caller: Principal) -> Result<String, String> {
+    if caller == Principal::anonymous() {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Sign in to link Discord"));
+    }
+    let code = entropy::next_token(16)?[..8].to_uppercase();
+    LINK_CODES.with(|c| {
+        let mut codes = c.borrow_mut();
+        let now = time();
+        codes.retain(|_, (_, expires_at)| *expires_at > now);
+        codes.insert(code.clone(), (caller, now + LINK_CODE_TTL_NS));
+    });
+    Ok(code)
+}
+
+/// Remove every Discord account linked to the caller
+pub fn unlink_discord(caller: Principal) -> u32 {
+    DISCORD_LINKS.with(|l| {
+        let mut links = l.borrow_mut();
+        let ids: Vec<String> = links.iter().filter(|(_, p)| *p == caller).map(|(id, _)| id).collect();
+        for id in &ids {
+            links.remove(id);
+        }
+        ids.len() as u32
+    })
+}
+
+pub fn get_config() -> DiscordConfig {
+    DISCORD_CONFIG.with(|c| c.borrow().get().clone())
+}
+
+pub fn set_config(config: DiscordConfig) -> Result<(), String> {
+    if let Some(ref key) = config.public_key {
+        if hex::decode(key).map(|b| b.len()) != Ok(32) {
+            return Err("public_key must be 32 bytes of hex".to_string());
+        }
+    }
+    DISCORD_CONFIG.with(|c| {
+        c.borrow_mut()
+            .set(config)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store Discord config: {:?}", e))
+    })
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Discord accounts linked to the principal
+pub fn count_owned(principal: Principal) -> u32 {
+    DISCORD_LINKS.with(|l| principal_moves::count_owned_in(&l.borrow(), |linked| linked, principal))
+}
+
+/// Point `from`'s Discord links at `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    DISCORD_LINKS.with(|l| principal_moves::move_owned_in(&mut l.borrow_mut(), |linked| linked, from, to))
+}
@@ -0,0 +1,476 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use icrc_ledger_types::icrc1::account::Account;
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::ledger::{self, TransferFailure};
+use crate::outbox;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Side of a double-entry posting. Custody is the canister's own ledger
+/// balance (an asset); Owner is what the canister owes that owner (a liability).
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BookAccount {
+    Custody,
+    Owner(Principal),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum EntryKind {
+    BookingPayment,     // Custody <- guest, owed to owner
+    BookingRefund,      // Owner's share returned to the guest
+    Withdrawal,         // Paid out to the owner's account
+    Reversal,           // Undo of a refund/withdrawal whose transfer failed
+}
+
+/// One balanced journal entry: `amount` is debited from one account and
+/// credited to the other, so Custody always equals the sum of owner balances
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub ledger_canister: Principal,
+    pub kind: EntryKind,
+    pub debit: BookAccount,
+    pub credit: BookAccount,
+    pub amount: u64,
+    pub reference: String,     // Booking ID or withdrawal block index
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct EarningsBalance {
+    pub ledger_canister: Principal,
+    pub balance: u64,
+}
+
+/// A withdrawal's transfer, kept as sent so a retry is the same transfer
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Payout {
+    pub owner: Principal,
+    pub ledger_canister: Principal,
+    pub to: Account,
+    pub debited: u64,         // Taken from the balance, fee included
+    pub amount: u64,          // Sent
+    pub created_at_time: u64, // Also the memo
+    pub reference: String,    // Of the Withdrawal journal entry
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl JournalEntry {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Moves nothing, so every balance stays as it was.
+    fn unreadable() -> Self {
+        JournalEntry {
+            id: 0,
+            ledger_canister: Principal::anonymous(),
+            kind: EntryKind::Reversal,
+            debit: BookAccount::Custody,
+            credit: BookAccount::Custody,
+            amount: 0,
+            reference: String::new(),
+            created_at: 0,
+        }
+    }
+}
+
+impl Storable for JournalEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "JournalEntry").unwrap_or_else(JournalEntry::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Append-only journal, keyed by sequential entry ID
+    static JOURNAL: RefCell<StableBTreeMap<u64, JournalEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16)))
+        )
+    );
+
+    // "owner|ledger" -> running balance, kept in step with the journal
+    static BALANCES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17)))
+        )
+    );
+
+    // Owners with a withdrawal in flight, so a second call can't spend the same balance
+    static WITHDRAWALS_IN_FLIGHT: RefCell<HashSet<Principal>> = RefCell::new(HashSet::new());
+}
+
+pub fn clear() {
+    JOURNAL.with(|m| m.borrow_mut().clear_new());
+    BALANCES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn balance_key(owner: Principal, ledger_canister: Principal) -> String {
+    format!("{}|{}", owner.to_text(), ledger_canister.to_text())
+}
+
+fn balance_of(owner: Principal, ledger_canister: Principal) -> u64 {
+    BALANCES.with(|b| b.borrow().get(&balance_key(owner, ledger_canister)).unwrap_or(0))
+}
+
+/// Append an entry and apply it to the owner's running balance
+fn post(owner: Principal, ledger_canister: Principal, kind: EntryKind, amount: u64, reference: String) {
+    let (debit, credit) = match kind {
+        EntryKind::BookingPayment | EntryKind::Reversal => (BookAccount::Custody, BookAccount::Owner(owner)),
+        EntryKind::BookingRefund | EntryKind::Withdrawal => (BookAccount::Owner(owner), BookAccount::Custody),
+    };
+
+    let key = balance_key(owner, ledger_canister);
+    BALANCES.with(|b| {
+        let mut balances = b.borrow_mut();
+        let current = balances.get(&key).unwrap_or(0);
+        let updated = match credit {
+            BookAccount::Owner(_) => current + amount,
+            BookAccount::Custody => current - amount,
+        };
+        balances.insert(key, updated);
+    });
+
+    JOURNAL.with(|j| {
+        let mut journal = j.borrow_mut();
+        let id = journal.len();
+        journal.insert(id, JournalEntry {
+            id,
+            ledger_canister,
+            kind,
+            debit,
+            credit,
+            amount,
+            reference,
+            created_at: time(),
+        });
+    });
+}
+
+fn begin_withdrawal(owner: Principal) -> Result<(), String> {
+    WITHDRAWALS_IN_FLIGHT.with(|w| {
+        if !w.borrow_mut().insert(owner) {
+            return Err("A withdrawal is already in progress".to_string());
+        }
+        Ok(())
+    })
+}
+
+fn end_withdrawal(owner: Principal) {
+    WITHDRAWALS_IN_FLIGHT.with(|w| {
+        w.borrow_mut().remove(&owner);
+    });
+}
+
+// ============================================================================
+// Postings (called by the booking flows)
+// ============================================================================
+
+/// A guest's payment landed in custody on the owner's behalf
+pub fn credit_payment(owner: Principal, ledger_canister: Principal, amount: u64, booking_id: &str) {
+    post(owner, ledger_canister, EntryKind::BookingPayment, amount, booking_id.to_string());
+}
+
+/// Reserve a refund against the owner's balance before sending it
+pub fn debit_refund(owner: Principal, ledger_canister: Principal, amount: u64, booking_id: &str) -> Result<(), String> {
+    if balance_of(owner, ledger_canister) < amount {
+        return Err("Owner balance is too low to cover the refund".to_string());
+    }
+    post(owner, ledger_canister, EntryKind::BookingRefund, amount, booking_id.to_string());
+    Ok(())
+}
+
+/// Put back a debit whose ledger transfer failed
+pub fn reverse(owner: Principal, ledger_canister: Principal, amount: u64, reference: &str) {
+    post(owner, ledger_canister, EntryKind::Reversal, amount, reference.to_string());
+}
+
+// ============================================================================
+// Owner Queries & Withdrawals
+// ============================================================================
+
+pub fn get_balances(owner: Principal) -> Vec<EarningsBalance> {
+    let prefix = format!("{}|", owner.to_text());
+    BALANCES.with(|b| {
+        b.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, balance)| {
+                let ledger_canister = Principal::from_text(&key[prefix.len()..]).ok()?;
+                Some(EarningsBalance { ledger_canister, balance })
+            })
+            .collect()
+    })
+}
+
+/// Journal entries touching the owner's balance, newest first
+pub fn list_entries(owner: Principal) -> Vec<JournalEntry> {
+    let mine = BookAccount::Owner(owner);
+    JOURNAL.with(|j| {
+        j.borrow()
+            .iter()
+            .rev()
+            .filter(|(_, entry)| entry.debit == mine || entry.credit == mine)
+            .map(|(_, entry)| entry)
+            .collect()
+    })
+}
+
+/// Pay out earnings to any ICRC-1 account. The balance is debited before the
+/// transfer and restored only if the ledger refused it. A transfer with no
+/// known outcome is retried through the outbox with the same arguments and
+/// the balance stays debited meanwhile. The ledger fee comes out of `amount`.
+pub async fn withdraw(caller: Principal, ledger_canister: Principal, amount: u64, to: Account) -> Result<u64, String> {
+    if amount == 0 {
+        return Err("amount must be greater than 0".to_string());
+    }
+
+    begin_withdrawal(caller)?;
+    if balance_of(caller, ledger_canister) < amount {
+        end_withdrawal(caller);
+        return Err("Insufficient earnings balance".to_string());
+    }
+
+    let fee = match ledger::fee(ledger_canister).await {
+        Ok(fee) if fee < amount => fee,
+        Ok(_) => {
+            end_withdrawal(caller);
+            return Err("amount must be greater than the ledger fee".to_string());
+        }
+        Err(e) => {
+            end_withdrawal(caller);
+            return Err(e);
+        }
+    };
+
+    // Re-check after the await, then debit before the transfer
+    if balance_of(caller, ledger_canister) < amount {
+        end_withdrawal(caller);
+        return Err("Insufficient earnings balance".to_string());
+    }
+    let created_at_time = time();
+    let payout = Payout {
+        owner: caller,
+        ledger_canister,
+        to,
+        debited: amount,
+        amount: amount - fee,
+        created_at_time,
+        reference: format!("withdrawal@{}", created_at_time),
+    };
+    post(caller, ledger_canister, EntryKind::Withdrawal, amount, payout.reference.clone());
+
+    let result = transfer_payout(&payout).await;
+    end_withdrawal(caller);
+
+    match book_transfer(&payout, result) {
+        Ok(block_index) => Ok(block_index),
+        Err(TransferFailure::Rejected(e)) => Err(e),
+        Err(TransferFailure::Unknown(e)) => {
+            outbox::enqueue_payout(payout, e.clone());
+            Err(error_codes::coded(ErrorCode::UpstreamUnavailable, format!("{}. The payout will be retried; the amount stays reserved until it settles", e)))
+        }
+    }
+}
+
+/// Send the payout. Memo and created_at_time are fixed per payout so every
+/// attempt is the same transfer to the ledger.
+async fn transfer_payout(payout: &Payout) -> Result<u64, TransferFailure> {
+    let result = ledger::transfer_at(
+        payout.ledger_canister,
+        payout.to,
+        payout.amount,
+        payout.created_at_time,
+        payout.created_at_time,
+    ).await;
+    if let Ok(block_index) = result {
+        ic_cdk::println!("🏦 {} withdrew {} (block {})", payout.owner.to_text(), payout.debited, block_index);
+    }
+    result
+}
+
+/// Book a payout's transfer result: a refusal proves nothing moved, so the
+/// debit is reversed; with no known outcome the amount stays debited
+fn book_transfer(payout: &Payout, result: Result<u64, TransferFailure>) -> Result<u64, TransferFailure> {
+    if let Err(TransferFailure::Rejected(_)) = result {
+        reverse(payout.owner, payout.ledger_canister, payout.debited, &payout.reference);
+    }
+    result
+}
+
+/// Outbox retry of a payout with no known outcome. Settled once the ledger
+/// reports a block (the earlier attempt's, if it went through) or refuses it,
+/// in which case the balance is restored.
+pub async fn settle_payout(payout: Payout) -> Result<(), String> {
+    let result = transfer_payout(&payout).await;
+    match book_transfer(&payout, result) {
+        Ok(_) => Ok(()),
+        Err(TransferFailure::Rejected(e)) => {
+            ic_cdk::println!("↩️ Payout {} of {} refused, balance restored: {}", payout.reference, payout.owner.to_text(), e);
+            Ok(())
+        }
+        Err(TransferFailure::Unknown(e)) => Err(e),
+    }
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Whether a withdrawal's transfer is still out or queued for a retry, so
+/// the balance can't move yet
+pub fn withdrawal_in_flight(owner: Principal) -> bool {
+    WITHDRAWALS_IN_FLIGHT.with(|w| w.borrow().contains(&owner)) || outbox::has_payout(owner)
+}
+
+/// Balances and journal entries of the owner
+pub fn count_owned(owner: Principal) -> u32 {
+    (get_balances(owner).len() + list_entries(owner).len()) as u32
+}
+
+/// Hand `from`'s balances to `to` (the same user under a new principal),
+/// adding them to any `to` already has, and re-address its journal entries
+/// so the books stay balanced per owner
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let balances = get_balances(from);
+    for balance in &balances {
+        BALANCES.with(|b| {
+            let mut map = b.borrow_mut();
+            map.remove(&balance_key(from, balance.ledger_canister));
+            let key = balance_key(to, balance.ledger_canister);
+            let current = map.get(&key).unwrap_or(0);
+            map.insert(key, current + balance.balance);
+        });
+    }
+    let readdress = |account: BookAccount| if account == BookAccount::Owner(from) { BookAccount::Owner(to) } else { account };
+    let entries = list_entries(from);
+    JOURNAL.with(|j| {
+        let mut journal = j.borrow_mut();
+        for mut entry in entries.iter().cloned() {
+            entry.debit = readdress(entry.debit);
+            entry.credit = readdress(entry.credit);
+            journal.insert(entry.id, entry);
+        }
+    });
+    (balances.len() + entries.len()) as u32
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    JOURNAL.with(|m| storage::map_stats(&m.borrow()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    const LEDGER: Principal = Principal::from_slice(&[9; 29]);
+
+    /// What custody holds according to the journal
+    fn custody() -> i128 {
+        JOURNAL.with(|j| {
+            j.borrow().iter().map(|(_, entry)| match (entry.debit, entry.credit) {
+                (BookAccount::Custody, _) => entry.amount as i128,
+                (_, BookAccount::Custody) => -(entry.amount as i128),
+                _ => 0,
+            }).sum()
+        })
+    }
+
+    /// A withdrawal of `amount` from `owner`, debited as withdraw does
+    /// before sending the transfer
+    fn debited_payout(owner: Principal, amount: u64) -> Payout {
+        let payout = Payout {
+            owner,
+            ledger_canister: LEDGER,
+            to: Account { owner, subaccount: None },
+            debited: amount,
+            amount: amount - 10,
+            created_at_time: time(),
+            reference: format!("withdrawal@{}", time()),
+        };
+        post(owner, LEDGER, EntryKind::Withdrawal, amount, payout.reference.clone());
+        payout
+    }
+
+    #[test]
+    fn every_posting_keeps_custody_equal_to_the_owner_balances() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        let ada = Principal::from_slice(&[1; 29]);
+        let bob = Principal::from_slice(&[2; 29]);
+
+        credit_payment(ada, LEDGER, 1_000, "bk-1");
+        credit_payment(bob, LEDGER, 500, "bk-2");
+        debit_refund(ada, LEDGER, 200, "bk-1").unwrap();
+        assert!(debit_refund(bob, LEDGER, 501, "bk-2").is_err());
+        reverse(ada, LEDGER, 200, "bk-1");
+        debited_payout(bob, 300);
+
+        for entry in list_entries(ada).into_iter().chain(list_entries(bob)) {
+            assert_ne!(entry.debit, entry.credit);
+        }
+        assert_eq!(balance_of(ada, LEDGER), 1_000);
+        assert_eq!(balance_of(bob, LEDGER), 200);
+        assert_eq!(custody(), 1_200);
+    }
+
+    #[test]
+    fn a_refused_payout_is_reversed() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        let owner = Principal::from_slice(&[3; 29]);
+        credit_payment(owner, LEDGER, 1_000, "bk-3");
+        let payout = debited_payout(owner, 300);
+
+        let result = book_transfer(&payout, Err(TransferFailure::Rejected("InsufficientFunds".to_string())));
+
+        assert!(matches!(result, Err(TransferFailure::Rejected(_))));
+        assert_eq!(balance_of(owner, LEDGER), 1_000);
+        assert_eq!(list_entries(owner)[0].kind, EntryKind::Reversal);
+        assert_eq!(custody(), 1_000);
+    }
+
+    #[test]
+    fn a_payout_with_no_known_outcome_stays_debited() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        let owner = Principal::from_slice(&[4; 29]);
+        credit_payment(owner, LEDGER, 1_000, "bk-4");
+        let payout = debited_payout(owner, 300);
+
+        let result = book_transfer(&payout, Err(TransferFailure::Unknown("call timed out".to_string())));
+
+        assert!(matches!(result, Err(TransferFailure::Unknown(_))));
+        assert_eq!(balance_of(owner, LEDGER), 700);
+        assert_eq!(list_entries(owner)[0].kind, EntryKind::Withdrawal);
+        assert_eq!(custody(), 700);
+    }
+}
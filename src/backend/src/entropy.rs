@@ -0,0 +1,173 @@
+//! Unpredictable tokens and IDs without a `raw_rand` call per ID. A secret
+//! pool key is seeded from the management canister's randomness, mixed with
+//! fresh randomness every hour by a timer, and expanded with HMAC-SHA256
+//! over a counter. Key and counter are stable so tokens never repeat across
+//! upgrades. Record IDs, API keys, link and check-in tokens and invite codes
+//! all come from here.
+
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{quarantine, service_status, webhook_auth};
+
+const RESEED_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Longest token one call may ask for, in bytes
+const MAX_TOKEN_BYTES: usize = 64;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct EntropyPool {
+    key: Option<Vec<u8>>, // None until the first raw_rand arrives
+    counter: u64,         // Blocks drawn under the current key
+    seeded_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for EntropyPool {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "EntropyPool").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static ENTROPY_POOL: RefCell<StableCell<EntropyPool, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(85))),
+            EntropyPool::default(),
+        ).expect("Failed to init entropy pool")
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// `len` bytes of output for the key, starting at block `counter`
+fn expand(key: &[u8], counter: u64, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + 32);
+    let mut block = counter;
+    while out.len() < len {
+        out.extend_from_slice(&webhook_auth::hmac_sha256(key, &block.to_be_bytes()));
+        block += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+/// New key from the current one and fresh randomness, so a single bad
+/// `raw_rand` reply can't weaken the pool
+fn mix(key: Option<&[u8]>, fresh: &[u8]) -> Vec<u8> {
+    webhook_auth::hmac_sha256(fresh, key.unwrap_or_default()).to_vec()
+}
+
+fn blocks(len: usize) -> u64 {
+    len.div_ceil(32) as u64
+}
+
+async fn reseed() {
+    match ic_cdk::api::management_canister::main::raw_rand().await {
+        Ok((fresh,)) => {
+            ENTROPY_POOL.with(|p| {
+                let mut cell = p.borrow_mut();
+                let key = mix(cell.get().key.as_deref(), &fresh);
+                cell.set(EntropyPool { key: Some(key), counter: 0, seeded_at: time() })
+            }).expect("Failed to store entropy pool");
+        }
+        Err((code, msg)) => {
+            ic_cdk::println!("❌ [entropy] Failed to get randomness: {:?} - {}", code, msg);
+            if !seeded() {
+                ic_cdk_timers::set_timer(Duration::from_secs(5), || ic_cdk::spawn(reseed()));
+            }
+        }
+    }
+}
+
+fn seeded() -> bool {
+    ENTROPY_POOL.with(|p| p.borrow().get().key.is_some())
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+/// `len` unpredictable bytes from the pool
+pub fn next_bytes(len: usize) -> Result<Vec<u8>, String> {
+    if len == 0 || len > MAX_TOKEN_BYTES {
+        return Err(format!("Tokens must be 1-{} bytes", MAX_TOKEN_BYTES));
+    }
+    ENTROPY_POOL.with(|p| {
+        let mut cell = p.borrow_mut();
+        let mut pool = cell.get().clone();
+        let key = pool.key.clone()
+            .ok_or_else(|| "Still starting up, try again shortly".to_string())?;
+        let bytes = expand(&key, pool.counter, len);
+        pool.counter += blocks(len);
+        cell.set(pool).map_err(|e| format!("Failed to store entropy pool: {:?}", e))?;
+        Ok(bytes)
+    })
+}
+
+/// Hex token made of `len` unpredictable bytes (`2 * len` characters)
+pub fn next_token(len: usize) -> Result<String, String> {
+    next_bytes(len).map(hex::encode)
+}
+
+/// Seed the pool right after install and mix in fresh randomness every hour
+/// (timers don't survive upgrades, so this runs from both init and post_upgrade)
+pub fn start_timer() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || ic_cdk::spawn(reseed()));
+    service_status::heartbeat("entropy", RESEED_INTERVAL);
+    ic_cdk_timers::set_timer_interval(RESEED_INTERVAL, || {
+        service_status::heartbeat("entropy", RESEED_INTERVAL);
+        ic_cdk::spawn(reseed());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expansion_continues_where_the_counter_left_off() {
+        let key = [7u8; 32];
+        let long = expand(&key, 0, 64);
+        assert_eq!(expand(&key, 0, 24), long[..24]);
+        assert_eq!(expand(&key, blocks(24), 32), long[32..]);
+        assert_ne!(long[..32], long[32..]);
+        assert_ne!(expand(&[8u8; 32], 0, 32), long[..32]);
+    }
+
+    #[test]
+    fn reseeding_changes_the_key_and_depends_on_both_inputs() {
+        let first = mix(None, &[1u8; 32]);
+        let second = mix(Some(&first), &[2u8; 32]);
+        assert_ne!(first, second);
+        assert_ne!(second, mix(Some(&first), &[3u8; 32]));
+        assert_ne!(second, mix(Some(&[9u8; 32]), &[2u8; 32]));
+    }
+}
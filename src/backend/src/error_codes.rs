@@ -0,0 +1,208 @@
+//! Stable error codes. Every error a client receives carries one, e.g.
+//! "Availability not found (E_AVAIL_NOT_FOUND, trace 3f9a01c2)", so
+//! frontends can localize and integrators can branch without parsing the
+//! wording. Codes and numbers never change meaning once released; add new
+//! ones at the end of their range. Errors stay plain strings, and the code
+//! is picked where each one is raised: localized errors through their i18n
+//! catalog key, the rest with `coded`, which puts the kind in front
+//! ("Unauthorized: not the owner"). Anything raised without one is
+//! E_INVALID_INPUT. The kind is stripped again before clients see the text.
+
+use candid::CandidType;
+use serde::Serialize;
+use crate::i18n::{self, Message};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCode {
+    AvailNotFound,
+    BookingNotFound,
+    NotFound,
+    SlotTaken,
+    SlotUnavailable,
+    TokenExpired,
+    PaymentPending,
+    Unauthorized,
+    CalendarDisconnected,
+    GoogleAccessRevoked,
+    QuotaExceeded,
+    FeatureNotAvailable,
+    RegionRequired,
+    RegionNotSupported,
+    UpstreamUnavailable,
+    InvalidInput,
+}
+
+/// One row of the published catalog
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ErrorCatalogEntry {
+    pub code: String,
+    pub number: u16,
+    pub description: String,
+}
+
+/// Code, number and meaning of every error. Numbers by range: 1xxx missing
+/// records, 2xxx scheduling conflicts, 3xxx access, 4xxx limits, 5xxx
+/// upstream services, 9xxx invalid input.
+const CATALOG: [(ErrorCode, &str, u16, &str); 16] = [
+    (ErrorCode::AvailNotFound, "E_AVAIL_NOT_FOUND", 1001, "The availability does not exist"),
+    (ErrorCode::BookingNotFound, "E_BOOKING_NOT_FOUND", 1002, "The booking does not exist"),
+    (ErrorCode::NotFound, "E_NOT_FOUND", 1000, "Another record does not exist"),
+    (ErrorCode::SlotTaken, "E_SLOT_TAKEN", 2001, "The time is already booked"),
+    (ErrorCode::SlotUnavailable, "E_SLOT_UNAVAILABLE", 2002, "The time is outside the offered slots or busy"),
+    (ErrorCode::TokenExpired, "E_TOKEN_EXPIRED", 2003, "A link, token, code or slot snapshot has expired"),
+    (ErrorCode::PaymentPending, "E_PAYMENT_PENDING", 2004, "The booking's payment is still in progress"),
+    (ErrorCode::Unauthorized, "E_UNAUTHORIZED", 3001, "The caller may not do this"),
+    (ErrorCode::CalendarDisconnected, "E_CALENDAR_DISCONNECTED", 3002, "The owner has no calendar connected"),
+    (ErrorCode::GoogleAccessRevoked, "E_GOOGLE_ACCESS_REVOKED", 3003, "Google access was revoked; reconnect the calendar"),
+    (ErrorCode::QuotaExceeded, "E_QUOTA_EXCEEDED", 4001, "A plan quota or rate limit was reached"),
+    (ErrorCode::FeatureNotAvailable, "E_FEATURE_NOT_AVAILABLE", 4002, "The feature is not in the caller's plan"),
+    (ErrorCode::RegionRequired, "E_REGION_REQUIRED", 4003, "The org requires a data region to be chosen first"),
+    (ErrorCode::RegionNotSupported, "E_REGION_NOT_SUPPORTED", 4004, "The feature is not available in the data region"),
+    (ErrorCode::UpstreamUnavailable, "E_UPSTREAM_UNAVAILABLE", 5001, "An upstream service is failing; retry later"),
+    (ErrorCode::InvalidInput, "E_INVALID_INPUT", 9001, "The request was rejected; the message says why"),
+];
+
+/// The kind each code is raised with, as the prefix `coded` writes
+const KINDS: [(&str, ErrorCode); 16] = [
+    ("AvailNotFound", ErrorCode::AvailNotFound),
+    ("BookingNotFound", ErrorCode::BookingNotFound),
+    ("NotFound", ErrorCode::NotFound),
+    ("SlotTaken", ErrorCode::SlotTaken),
+    ("SlotUnavailable", ErrorCode::SlotUnavailable),
+    ("TokenExpired", ErrorCode::TokenExpired),
+    ("PaymentPending", ErrorCode::PaymentPending),
+    ("Unauthorized", ErrorCode::Unauthorized),
+    ("CalendarDisconnected", ErrorCode::CalendarDisconnected),
+    ("GoogleAccessRevoked", ErrorCode::GoogleAccessRevoked),
+    ("QuotaExceeded", ErrorCode::QuotaExceeded),
+    ("FeatureNotAvailable", ErrorCode::FeatureNotAvailable),
+    ("RegionRequired", ErrorCode::RegionRequired),
+    ("RegionNotSupported", ErrorCode::RegionNotSupported),
+    ("UpstreamUnavailable", ErrorCode::UpstreamUnavailable),
+    ("InvalidInput", ErrorCode::InvalidInput),
+];
+
+// ============================================================================
+// Codes
+// ============================================================================
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        CATALOG.iter().find(|(code, ..)| *code == self).map(|(_, name, ..)| *name).unwrap_or("E_INVALID_INPUT")
+    }
+}
+
+/// Code of a localized error, the same in every language. Exhaustive, so a
+/// new catalog entry has to pick one.
+fn code_of(message: Message) -> Option<ErrorCode> {
+    match message {
+        Message::SlotNotAvailable => Some(ErrorCode::SlotUnavailable),
+        Message::SlotAlreadyBooked => Some(ErrorCode::SlotTaken),
+        Message::SlotInPast => Some(ErrorCode::InvalidInput),
+        Message::LinkAlreadyUsed => Some(ErrorCode::InvalidInput),
+        Message::LinkExpired => Some(ErrorCode::TokenExpired),
+        Message::SignInToPay => Some(ErrorCode::InvalidInput),
+        Message::BookingOn => None, // Receipt text, never an error
+    }
+}
+
+/// An error raised with its code, e.g.
+/// `Err(error_codes::coded(ErrorCode::NotFound, "Team not found"))`
+pub fn coded(code: ErrorCode, message: impl std::fmt::Display) -> String {
+    let kind = KINDS.iter().find(|(_, c)| *c == code).map(|(kind, _)| *kind).unwrap_or("InvalidInput");
+    format!("{}: {}", kind, message)
+}
+
+/// Code of an error and the text to show for it (without its kind)
+pub fn split(message: &str) -> (ErrorCode, &str) {
+    if let Some(code) = i18n::message_key(message).and_then(code_of) {
+        return (code, message);
+    }
+    message
+        .split_once(':')
+        .and_then(|(kind, text)| KINDS.iter().find(|(k, _)| *k == kind).map(|(_, code)| (*code, text.trim_start())))
+        .unwrap_or((ErrorCode::InvalidInput, message))
+}
+
+pub fn classify(message: &str) -> ErrorCode {
+    split(message).0
+}
+
+/// The message with its code appended
+pub fn with_code(message: String) -> String {
+    let (code, text) = split(&message);
+    format!("{} ({})", text, code.as_str())
+}
+
+/// Run a query body, giving its error a code (update calls get theirs
+/// together with a trace ID from `trace::run`)
+pub fn run<T>(body: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    body().map_err(with_code)
+}
+
+pub fn catalog() -> Vec<ErrorCatalogEntry> {
+    CATALOG
+        .iter()
+        .map(|(_, code, number, description)| ErrorCatalogEntry {
+            code: code.to_string(),
+            number: *number,
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_map_to_their_codes() {
+        assert_eq!(classify(&coded(ErrorCode::AvailNotFound, "Availability not found")), ErrorCode::AvailNotFound);
+        assert_eq!(classify("QuotaExceeded: 10 availabilities on the Free plan"), ErrorCode::QuotaExceeded);
+        assert_eq!(classify(&coded(ErrorCode::SlotTaken, "Requested time is already booked")), ErrorCode::SlotTaken);
+        assert_eq!(classify("title must be 1-100 characters"), ErrorCode::InvalidInput);
+        // The wording alone never picks a code
+        assert_eq!(classify("Availability not found"), ErrorCode::InvalidInput);
+        assert_eq!(classify("Only confirmed bookings can be moved"), ErrorCode::InvalidInput);
+        assert_eq!(classify("Invalid JSON body: expected value at line 1: column 2"), ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn the_kind_is_stripped_before_clients_see_the_text() {
+        let error = coded(ErrorCode::Unauthorized, "Only the owner can change seats");
+        assert_eq!(error, "Unauthorized: Only the owner can change seats");
+        assert_eq!(with_code(error), "Only the owner can change seats (E_UNAUTHORIZED)");
+        assert_eq!(with_code("Slot is in the past".to_string()), "Slot is in the past (E_INVALID_INPUT)");
+    }
+
+    #[test]
+    fn every_code_has_a_kind() {
+        for (code, ..) in CATALOG {
+            assert_eq!(classify(&coded(code, "text")), code, "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn localized_errors_get_the_same_code_in_every_language() {
+        use crate::i18n::{t, Locale, MESSAGES};
+        assert_eq!(classify("Dieser Link ist abgelaufen"), ErrorCode::TokenExpired);
+        for message in MESSAGES.into_iter().filter(|m| code_of(*m).is_some()) {
+            let english = classify(t(Locale::En, message));
+            for locale in [Locale::De, Locale::Es, Locale::Ar, Locale::He] {
+                assert_eq!(classify(t(locale, message)), english, "{:?} in {:?}", message, locale);
+            }
+            assert_eq!(Some(english), code_of(message));
+        }
+    }
+
+    #[test]
+    fn codes_and_numbers_are_unique() {
+        for (i, (code, name, number, _)) in CATALOG.iter().enumerate() {
+            assert!(CATALOG[..i].iter().all(|(c, n, num, _)| c != code && n != name && num != number));
+        }
+    }
+}
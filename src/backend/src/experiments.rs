@@ -0,0 +1,288 @@
+//! Time-boxed A/B tests of an availability's weekly hours. While an
+//! experiment runs, each guest is assigned a variant by a hash of their
+//! principal, so they always see the same hours, and the owner gets views
+//! (slot snapshots) and bookings per variant.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, Availability, TimeSlot};
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+const MAX_EXPERIMENT_SECS: u64 = 90 * 24 * 3600;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SlotVariant {
+    A, // The availability's saved weekly slots
+    B, // The experiment's alternative slots
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct VariantStats {
+    pub views: u64,    // Slot snapshots taken by guests on this variant
+    pub bookings: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SlotExperiment {
+    pub availability_id: String,
+    pub variant_b_slots: Vec<TimeSlot>,
+    pub b_percent: u8,     // Share of guests shown variant B
+    pub starts_at: u64,    // Unix seconds
+    pub ends_at: u64,      // Unix seconds; stats stay readable afterwards
+    pub a: VariantStats,
+    pub b: VariantStats,
+    pub created_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct StartExperimentRequest {
+    pub availability_id: String,
+    pub variant_b_slots: Vec<TimeSlot>,
+    pub b_percent: u8,
+    pub starts_at: Option<u64>, // None = now
+    pub ends_at: u64,
+}
+
+impl SlotExperiment {
+    fn is_running(&self, now_secs: u64) -> bool {
+        self.starts_at <= now_secs && now_secs < self.ends_at
+    }
+
+    fn stats_mut(&mut self, variant: SlotVariant) -> &mut VariantStats {
+        match variant {
+            SlotVariant::A => &mut self.a,
+            SlotVariant::B => &mut self.b,
+        }
+    }
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl SlotExperiment {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Already over and showing nobody variant B.
+    fn unreadable() -> Self {
+        SlotExperiment {
+            availability_id: String::new(),
+            variant_b_slots: vec![],
+            b_percent: 0,
+            starts_at: 0,
+            ends_at: 0,
+            a: VariantStats::default(),
+            b: VariantStats::default(),
+            created_by: Principal::anonymous(),
+            created_at: 0,
+        }
+    }
+}
+
+impl Storable for SlotExperiment {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "SlotExperiment").unwrap_or_else(SlotExperiment::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Availability ID -> its latest experiment, running or finished
+    static SLOT_EXPERIMENTS: RefCell<StableBTreeMap<String, SlotExperiment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(62)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    SLOT_EXPERIMENTS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Stable bucket 0-99 for a guest within one experiment
+fn bucket(availability_id: &str, guest: Principal) -> u8 {
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    hasher.update(availability_id.as_bytes());
+    hasher.update(guest.as_slice());
+    let hash = hasher.finalize();
+    (u16::from_be_bytes([hash[0], hash[1]]) % 100) as u8
+}
+
+fn assign(b_percent: u8, bucket: u8) -> SlotVariant {
+    if bucket < b_percent { SlotVariant::B } else { SlotVariant::A }
+}
+
+fn running(availability_id: &str) -> Option<SlotExperiment> {
+    SLOT_EXPERIMENTS.with(|e| e.borrow().get(&availability_id.to_string()))
+        .filter(|experiment| experiment.is_running(time() / 1_000_000_000))
+}
+
+/// The guest's variant of a running experiment. Owners and collaborators
+/// always see the saved slots.
+fn variant_of(availability: &Availability, guest: Principal) -> Option<SlotVariant> {
+    if availabilities::has_role(availability, guest, availabilities::CollaboratorRole::Viewer) {
+        return None;
+    }
+    let experiment = running(&availability.id)?;
+    Some(assign(experiment.b_percent, bucket(&availability.id, guest)))
+}
+
+fn record(availability_id: &str, variant: SlotVariant, count: impl Fn(&mut VariantStats)) {
+    SLOT_EXPERIMENTS.with(|e| {
+        let mut map = e.borrow_mut();
+        if let Some(mut experiment) = map.get(&availability_id.to_string()) {
+            count(experiment.stats_mut(variant));
+            map.insert(availability_id.to_string(), experiment);
+        }
+    });
+}
+
+// ============================================================================
+// Guest Flow
+// ============================================================================
+
+/// The availability as this guest should see it: variant B swaps in the
+/// experiment's weekly slots
+pub fn as_seen_by(mut availability: Availability, guest: Principal) -> Availability {
+    if variant_of(&availability, guest) == Some(SlotVariant::B) {
+        if let Some(experiment) = running(&availability.id) {
+            availability.slots = experiment.variant_b_slots;
+        }
+    }
+    availability
+}
+
+/// Count a guest loading free slots
+pub fn record_view(availability_id: &str, guest: Principal) {
+    let Ok(availability) = availabilities::get_availability(availability_id.to_string()) else {
+        return;
+    };
+    if let Some(variant) = variant_of(&availability, guest) {
+        record(&availability.id, variant, |stats| stats.views += 1);
+    }
+}
+
+/// Count a guest's booking
+pub fn record_booking(availability: &Availability, guest: Principal) {
+    if let Some(variant) = variant_of(availability, guest) {
+        record(&availability.id, variant, |stats| stats.bookings += 1);
+    }
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// Start an experiment, replacing a finished one and its stats
+pub fn start_experiment(caller: Principal, req: StartExperimentRequest) -> Result<SlotExperiment, String> {
+    let availability = availabilities::get_availability(req.availability_id.clone())?;
+    if !availabilities::can_edit(caller, &availability) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can run experiments"));
+    }
+    if running(&availability.id).is_some() {
+        return Err("An experiment is already running; stop it first".to_string());
+    }
+    if !(1..=99).contains(&req.b_percent) {
+        return Err("b_percent must be 1-99".to_string());
+    }
+    if req.variant_b_slots.is_empty() {
+        return Err("variant_b_slots must not be empty".to_string());
+    }
+    availabilities::validate_weekly_slots(&req.variant_b_slots)?;
+
+    let now = time() / 1_000_000_000;
+    let starts_at = req.starts_at.unwrap_or(now).max(now);
+    if req.ends_at <= starts_at {
+        return Err("ends_at must be after the start".to_string());
+    }
+    if req.ends_at - starts_at > MAX_EXPERIMENT_SECS {
+        return Err("An experiment can run for at most 90 days".to_string());
+    }
+
+    let experiment = SlotExperiment {
+        availability_id: availability.id.clone(),
+        variant_b_slots: req.variant_b_slots,
+        b_percent: req.b_percent,
+        starts_at,
+        ends_at: req.ends_at,
+        a: VariantStats::default(),
+        b: VariantStats::default(),
+        created_by: caller,
+        created_at: time(),
+    };
+    SLOT_EXPERIMENTS.with(|e| e.borrow_mut().insert(availability.id.clone(), experiment.clone()));
+    ic_cdk::println!("🧪 Experiment on {} until {} ({}% on B)", availability.id, experiment.ends_at, experiment.b_percent);
+    Ok(experiment)
+}
+
+/// End the experiment now, keeping its stats
+pub fn stop_experiment(caller: Principal, availability_id: String) -> Result<SlotExperiment, String> {
+    let mut experiment = get_experiment(caller, availability_id)?.ok_or("No experiment on this availability")?;
+    let now = time() / 1_000_000_000;
+    if !experiment.is_running(now) && experiment.starts_at <= now {
+        return Err("Experiment has already ended".to_string());
+    }
+    experiment.ends_at = now.max(experiment.starts_at);
+    SLOT_EXPERIMENTS.with(|e| e.borrow_mut().insert(experiment.availability_id.clone(), experiment.clone()));
+    Ok(experiment)
+}
+
+pub fn get_experiment(caller: Principal, availability_id: String) -> Result<Option<SlotExperiment>, String> {
+    let availability = availabilities::get_availability(availability_id)?;
+    if !availabilities::has_role(&availability, caller, availabilities::CollaboratorRole::Viewer)
+        && !availabilities::can_edit(caller, &availability)
+    {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can see experiments"));
+    }
+    Ok(SLOT_EXPERIMENTS.with(|e| e.borrow().get(&availability.id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_stable_and_in_range() {
+        let guest = Principal::from_slice(&[1, 2, 3]);
+        assert_eq!(bucket("abc", guest), bucket("abc", guest));
+        assert!((0..250u8).all(|i| bucket("abc", Principal::from_slice(&[i])) < 100));
+    }
+
+    #[test]
+    fn split_follows_b_percent() {
+        assert_eq!(assign(30, 0), SlotVariant::B);
+        assert_eq!(assign(30, 29), SlotVariant::B);
+        assert_eq!(assign(30, 30), SlotVariant::A);
+        let on_b = (0..=255u8).filter(|i| assign(50, bucket("abc", Principal::from_slice(&[*i]))) == SlotVariant::B).count();
+        assert!((80..=176).contains(&on_b));
+    }
+}
@@ -0,0 +1,214 @@
+//! Demo data for local and frontend development. Only compiled with the
+//! `dev-fixtures` feature, so these endpoints don't exist on mainnet builds.
+
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use crate::clock::time;
+use crate::availabilities::{self, Availability, StringVec, TimeSlot};
+use crate::availabilities::{AVAILABILITIES, USER_AVAILABILITIES};
+use crate::bookings::{self, CreateBookingRequest};
+use crate::{
+    analytics, check_ins, counters, delivery_log, experiments, followups, link_stats, org_busy, outbox, outcall_cache,
+    principal_moves, read_cache, resources, sync_scheduler, templates,
+};
+
+const MAX_USERS: u32 = 50;
+const MAX_AVAILABILITIES_PER_USER: u32 = 10;
+const MAX_BOOKINGS_PER_AVAILABILITY: u32 = 20;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Stable structures (as listed in storage.rs) and how to empty them
+struct Bookkeeping {
+    #[cfg_attr(not(test), allow(dead_code))]
+    structures: &'static [&'static str],
+    clear: fn(),
+}
+
+/// Records about bookings, availabilities and tenants rather than a user,
+/// cleared along with the per-principal stores (principal_moves::STORES)
+const BOOKKEEPING: [Bookkeeping; 15] = [
+    Bookkeeping { structures: &["LINK_STATS"], clear: link_stats::clear },
+    Bookkeeping { structures: &["RESERVATIONS"], clear: resources::clear_reservations },
+    Bookkeeping { structures: &["ORG_BUSY"], clear: org_busy::clear },
+    Bookkeeping { structures: &["OUTBOX"], clear: outbox::clear },
+    Bookkeeping { structures: &["OUTCALL_CACHE"], clear: outcall_cache::clear },
+    Bookkeeping { structures: &["NOTIFICATION_TEMPLATES"], clear: templates::clear },
+    Bookkeeping { structures: &["DELIVERY_LOG"], clear: delivery_log::clear },
+    Bookkeeping { structures: &["SLOT_EXPERIMENTS"], clear: experiments::clear },
+    Bookkeeping { structures: &["ANALYTICS_EVENTS"], clear: analytics::clear_events },
+    Bookkeeping { structures: &["COUNTERS"], clear: counters::clear },
+    Bookkeeping { structures: &["CHECK_INS"], clear: check_ins::clear },
+    Bookkeeping { structures: &["FOLLOW_UP_QUEUE"], clear: followups::clear },
+    Bookkeeping { structures: &["READ_CACHE_PENDING"], clear: read_cache::clear_pending },
+    Bookkeeping { structures: &["SYNC_STATE"], clear: sync_scheduler::clear },
+    Bookkeeping { structures: &["PRINCIPAL_MOVES"], clear: principal_moves::clear_history },
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SeedReport {
+    pub users: Vec<Principal>,
+    pub availabilities: u32,
+    pub bookings: u32,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// The same principal for the same index on every run
+pub fn demo_user(index: u32) -> Principal {
+    Principal::self_authenticating(format!("weeekaly-demo-user-{}", index))
+}
+
+/// Weekdays 09:00-17:00 in the availability's timezone
+fn working_hours() -> Vec<TimeSlot> {
+    (1..=5).map(|day| TimeSlot { day_of_week: day, start_time: 9 * 60, end_time: 17 * 60 }).collect()
+}
+
+fn demo_availability(owner: Principal, user: u32, index: u32) -> Availability {
+    let now = time();
+    Availability {
+        id: format!("demo-{}-{}", user, index),
+        owner,
+        owner_email: Some(format!("demo{}@example.com", user)),
+        owner_name: Some(format!("Demo User {}", user)),
+        title: format!("Demo meeting {}", index + 1),
+        description: "Seeded for local development".to_string(),
+        slots: working_hours(),
+        timezone: "UTC".to_string(),
+        created_at: now,
+        updated_at: now,
+        busy_times: None,
+        is_favorite: index == 0,
+        display_order: index,
+        busy_privacy: None,
+        cancellation_policy: None,
+        locale: None,
+        durations: None,
+        kiosk: None,
+        collaborators: None,
+        vip: None,
+        alignment: None,
+        meeting_url: None,
+        event_fields: None,
+        seats: None,
+        follow_ups: None,
+    }
+}
+
+/// Start of the next Monday (UTC), so the seeded week is always in the future
+fn next_monday() -> u64 {
+    let today = time() / 1_000_000_000 / DAY_SECS;
+    // 1970-01-01 was a Thursday: day 4 counting from Monday = 0
+    let weekday = (today + 3) % 7;
+    (today + 7 - weekday) * DAY_SECS
+}
+
+// ============================================================================
+// Fixtures
+// ============================================================================
+
+/// Create `users` demo users, each with `availabilities` availabilities that
+/// get `bookings` one-hour bookings from the next user, spread over the
+/// working hours of next week. Existing demo records with the same IDs are
+/// replaced.
+pub fn seed_demo_data(users: u32, availabilities: u32, bookings: u32) -> Result<SeedReport, String> {
+    if users > MAX_USERS || availabilities > MAX_AVAILABILITIES_PER_USER || bookings > MAX_BOOKINGS_PER_AVAILABILITY {
+        return Err(format!(
+            "At most {} users, {} availabilities per user and {} bookings per availability",
+            MAX_USERS, MAX_AVAILABILITIES_PER_USER, MAX_BOOKINGS_PER_AVAILABILITY
+        ));
+    }
+
+    let monday = next_monday();
+    let principals: Vec<Principal> = (0..users).map(demo_user).collect();
+    let mut report = SeedReport { users: principals.clone(), availabilities: 0, bookings: 0 };
+
+    for (u, owner) in principals.iter().enumerate() {
+        let u = u as u32;
+        let guest = principals[((u + 1) % users) as usize];
+        let mut ids = Vec::new();
+        for a in 0..availabilities {
+            let availability = demo_availability(*owner, u, a);
+            ids.push(availability.id.clone());
+            availabilities::index_owner(&availability);
+            if AVAILABILITIES.with(|m| m.borrow_mut().insert(availability.id.clone(), availability.clone())).is_none() {
+                counters::availability_added(*owner, &availability.id);
+            }
+            report.availabilities += 1;
+
+            for b in 0..bookings {
+                // Hourly slots, eight a day Monday to Friday, numbered across the owner's
+                // availabilities since they all share the owner's calendar
+                let slot = a * bookings + b;
+                let day = (slot / 8) % 5;
+                let start = monday + day as u64 * DAY_SECS + (9 + (slot % 8) as u64) * 3600;
+                let req = CreateBookingRequest {
+                    availability_id: availability.id.clone(),
+                    start_time: start,
+                    end_time: start + 3600,
+                    guest_name: format!("Demo User {}", (u + 1) % users),
+                    guest_email: Some(format!("demo{}@example.com", (u + 1) % users)),
+                    guest_timezone: None,
+                    field_answers: None,
+                };
+                // Slots can run out when many bookings are requested; those are skipped
+                if bookings::create_booking(guest, req).is_ok() {
+                    report.bookings += 1;
+                }
+            }
+        }
+        USER_AVAILABILITIES.with(|m| m.borrow_mut().insert(*owner, StringVec(ids)));
+    }
+
+    ic_cdk::println!(
+        "🌱 Seeded {} users, {} availabilities, {} bookings",
+        users, report.availabilities, report.bookings
+    );
+    Ok(report)
+}
+
+/// Wipe user data: every per-principal store and the bookkeeping kept about
+/// bookings and availabilities. Canister configuration (default limits,
+/// billing and Stripe settings, promo codes, policies) and sign-in sessions
+/// are kept.
+pub fn reset_all_data() {
+    principal_moves::clear_all();
+    for bookkeeping in BOOKKEEPING.iter() {
+        (bookkeeping.clear)();
+    }
+    ic_cdk::println!("🧨 Reset all user data");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage;
+
+    /// Configuration, sign-in sessions and the canister's own records
+    const KEPT: &[&str] = &[
+        "DEFAULT_LIMITS", "BILLING_CONFIG", "PROMO_CODES", "ORIGIN_CONFIGS", "RESOURCES", "DISCORD_CONFIG",
+        "STORAGE_SNAPSHOT", "CORRUPT_RECORDS", "UPGRADE_SNAPSHOT", "STABLE_SESSIONS", "MIGRATION_STATUS",
+        "MAINTENANCE_JOBS", "SEEN_WEBHOOK_EVENTS", "STRIPE_CONFIG", "STRIPE_DEAD_LETTERS (retired)", "CURSOR_KEY",
+        "TERMS_CONFIG", "REGION_POLICIES", "ANALYTICS_CONFIG", "REBUILD_STATUS", "ORG_DEFAULTS", "SELF_TEST_SCRATCH",
+        "ORIGIN_ALIASES", "READ_CACHE_CONFIG", "ENTROPY_POOL", "UPGRADE_DIGESTS",
+    ];
+
+    #[test]
+    fn reset_clears_every_stable_structure_but_configuration() {
+        let cleared: Vec<&str> = principal_moves::cleared_structures()
+            .chain(BOOKKEEPING.iter().flat_map(|bookkeeping| bookkeeping.structures.iter().copied()))
+            .collect();
+        for (id, name) in storage::STRUCTURES {
+            assert!(
+                cleared.contains(&name) != KEPT.contains(&name),
+                "memory {} ({}) must be in exactly one of the cleared stores or KEPT",
+                id, name
+            );
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Post-meeting actions. An availability can ask for a follow-up email to the
+//! guest, or a webhook call, some hours after each booking ends. Confirming a
+//! booking queues its follow-ups; a timer sends the due ones and retries
+//! failures with backoff. Cancelling the booking drops its queue entries, and
+//! a booking moved later waits for its new end time.
+//!
+//! Emails are sent from the owner's Gmail with their Google token, so the
+//! owner must have granted the `gmail.send` permission when connecting.
+
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+use crate::clock::time;
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, TransformContext,
+};
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{api_usage, availabilities, integrations, quarantine, service_status, templates, tokens};
+use crate::bookings::{Booking, BookingStatus};
+use crate::circuit_breaker::{self, Provider};
+use crate::integrations::HookEvent;
+
+const FOLLOW_UP_TIMER_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_FOLLOW_UPS: usize = 5;
+const MAX_DELAY_HOURS: u32 = 30 * 24;
+const MAX_URL_LEN: usize = 500;
+/// Attempts before a follow-up is given up on
+const MAX_ATTEMPTS: u32 = 5;
+const BASE_BACKOFF_NS: u64 = 5 * 60 * 1_000_000_000;
+/// A follow-up being sent is not picked up again for this long
+const LEASE_NS: u64 = 5 * 60 * 1_000_000_000;
+const MAX_PER_TICK: usize = 20;
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FollowUpAction {
+    Email { subject: String, body: String }, // Template text with {{variables}}, sent to the guest
+    Webhook { url: String },                 // POSTed the booking's hook fields
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FollowUp {
+    pub delay_hours: u32, // After the booking's end time
+    pub action: FollowUpAction,
+}
+
+/// A follow-up queued for one booking
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct ScheduledFollowUp {
+    booking_id: String,
+    index: u32,            // Position in the availability's follow_ups
+    due_at: u64,           // Nanoseconds
+    attempts: u32,
+    last_error: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for ScheduledFollowUp {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ScheduledFollowUp").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "<booking id>|<index>" -> queued follow-up. Entries are removed once sent.
+    static FOLLOW_UP_QUEUE: RefCell<StableBTreeMap<String, ScheduledFollowUp, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(77)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn key(booking_id: &str, index: u32) -> String {
+    format!("{}|{}", booking_id, index)
+}
+
+fn due_at(booking: &Booking, follow_up: &FollowUp) -> u64 {
+    booking.end_time * 1_000_000_000 + follow_up.delay_hours as u64 * HOUR_NS
+}
+
+fn follow_ups_of(booking: &Booking) -> Vec<FollowUp> {
+    availabilities::get_availability(booking.availability_id.clone())
+        .ok()
+        .and_then(|a| a.follow_ups)
+        .unwrap_or_default()
+}
+
+fn update_entry(key: &str, f: impl FnOnce(&mut ScheduledFollowUp)) {
+    FOLLOW_UP_QUEUE.with(|q| {
+        let mut map = q.borrow_mut();
+        if let Some(mut entry) = map.get(&key.to_string()) {
+            f(&mut entry);
+            map.insert(key.to_string(), entry);
+        }
+    });
+}
+
+/// RFC 2822 message for the Gmail API, base64url-encoded
+fn raw_email(to: &str, subject: &str, body: &str) -> String {
+    let message = format!(
+        "To: {}\r\nSubject: =?UTF-8?B?{}?=\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=UTF-8\r\n\r\n{}",
+        to,
+        STANDARD.encode(subject),
+        body,
+    );
+    URL_SAFE_NO_PAD.encode(message)
+}
+
+pub fn validate(follow_ups: &[FollowUp]) -> Result<(), String> {
+    if follow_ups.len() > MAX_FOLLOW_UPS {
+        return Err(format!("At most {} follow-ups", MAX_FOLLOW_UPS));
+    }
+    for follow_up in follow_ups {
+        if follow_up.delay_hours > MAX_DELAY_HOURS {
+            return Err(format!("delay_hours must be at most {}", MAX_DELAY_HOURS));
+        }
+        match follow_up.action {
+            FollowUpAction::Email { ref subject, ref body } => templates::validate(subject, body)?,
+            FollowUpAction::Webhook { ref url } => {
+                if !url.starts_with("https://") || url.len() > MAX_URL_LEN {
+                    return Err(format!("Webhook url must be an https URL up to {} characters", MAX_URL_LEN));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Scheduling
+// ============================================================================
+
+/// Queue the availability's follow-ups for a booking that was just confirmed
+pub fn schedule(booking: &Booking) {
+    let follow_ups = follow_ups_of(booking);
+    FOLLOW_UP_QUEUE.with(|q| {
+        let mut map = q.borrow_mut();
+        for (index, follow_up) in follow_ups.iter().enumerate() {
+            map.insert(key(&booking.id, index as u32), ScheduledFollowUp {
+                booking_id: booking.id.clone(),
+                index: index as u32,
+                due_at: due_at(booking, follow_up),
+                attempts: 0,
+                last_error: None,
+            });
+        }
+    });
+}
+
+/// Drop every follow-up still queued for a booking
+pub fn cancel(booking_id: &str) {
+    let prefix = format!("{}|", booking_id);
+    let keys: Vec<String> = FOLLOW_UP_QUEUE.with(|q| {
+        q.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| key)
+            .collect()
+    });
+    FOLLOW_UP_QUEUE.with(|q| {
+        let mut map = q.borrow_mut();
+        for key in &keys {
+            map.remove(key);
+        }
+    });
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+async fn send_email(booking: &Booking, subject: &str, body: &str) -> Result<(), String> {
+    let Some(to) = booking.guest_email.clone() else {
+        return Ok(()); // Nobody to write to
+    };
+    let token = tokens::access_token(booking.owner).ok_or("Owner has no Google token")?;
+    api_usage::check_google(booking.owner)?;
+    let rendered = templates::render_text(booking, HookEvent::BookingCreated, subject, body);
+
+    let request = CanisterHttpRequestArgument {
+        url: "https://gmail.googleapis.com/gmail/v1/users/me/messages/send".to_string(),
+        method: HttpMethod::POST,
+        body: Some(serde_json::json!({ "raw": raw_email(&to, &rendered.subject, &rendered.body) }).to_string().into_bytes()),
+        max_response_bytes: Some(1024),
+        transform: Some(TransformContext::from_name("transform_hook_response".to_string(), vec![])),
+        headers: vec![
+            HttpHeader { name: "Content-Type".to_string(), value: "application/json".to_string() },
+            HttpHeader { name: "Authorization".to_string(), value: format!("Bearer {}", token) },
+        ],
+    };
+
+    circuit_breaker::check(Provider::Google)?;
+    match api_usage::google_request(booking.owner, request, 25_000_000_000).await {
+        Ok((response,)) if integrations::is_success(&response.status) => Ok(()),
+        Ok((response,)) if u16::try_from(&response.status.0).is_ok_and(|code| code == 403) => Err("Gmail refused: reconnect Google with permission to send email".to_string()),
+        Ok((response,)) => Err(format!("HTTP {}", response.status)),
+        Err((code, msg)) => Err(format!("{:?}: {}", code, msg)),
+    }
+}
+
+async fn call_webhook(booking: &Booking, url: &str) -> Result<(), String> {
+    let title = availabilities::get_availability(booking.availability_id.clone())
+        .map(|a| a.title)
+        .unwrap_or_default();
+    let mut object = serde_json::Map::new();
+    for field in integrations::FIELDS {
+        object.insert(field.to_string(), integrations::field_value(field, HookEvent::BookingCreated, booking, &title).into());
+    }
+    object.insert("event".to_string(), "booking.follow_up".into());
+
+    let request = CanisterHttpRequestArgument {
+        url: url.to_string(),
+        method: HttpMethod::POST,
+        body: Some(serde_json::Value::Object(object).to_string().into_bytes()),
+        max_response_bytes: Some(1024),
+        transform: Some(TransformContext::from_name("transform_hook_response".to_string(), vec![])),
+        headers: vec![HttpHeader {
+            name: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+        }],
+    };
+    match http_request(request, 25_000_000_000).await {
+        Ok((response,)) if integrations::is_success(&response.status) => Ok(()),
+        Ok((response,)) => Err(format!("HTTP {}", response.status)),
+        Err((code, msg)) => Err(format!("{:?}: {}", code, msg)),
+    }
+}
+
+async fn run(key: String, entry: ScheduledFollowUp) {
+    let booking = crate::bookings::find(&entry.booking_id);
+    // A booking cancelled or dropped since, or a follow-up the owner removed, has nothing left to send
+    let follow_up = booking.as_ref()
+        .filter(|b| b.status == BookingStatus::Confirmed)
+        .and_then(|b| follow_ups_of(b).into_iter().nth(entry.index as usize));
+    let (Some(booking), Some(follow_up)) = (booking, follow_up) else {
+        FOLLOW_UP_QUEUE.with(|q| q.borrow_mut().remove(&key));
+        return;
+    };
+    let due = due_at(&booking, &follow_up);
+    if due > time() {
+        update_entry(&key, |e| e.due_at = due); // Moved later since it was queued
+        return;
+    }
+
+    let result = match follow_up.action {
+        FollowUpAction::Email { ref subject, ref body } => send_email(&booking, subject, body).await,
+        FollowUpAction::Webhook { ref url } => call_webhook(&booking, url).await,
+    };
+    match result {
+        Ok(()) => {
+            FOLLOW_UP_QUEUE.with(|q| q.borrow_mut().remove(&key));
+        }
+        Err(e) if entry.attempts + 1 >= MAX_ATTEMPTS => {
+            ic_cdk::println!("☠️ [follow-up] Gave up on {} for booking {}: {}", entry.index, booking.id, e);
+            FOLLOW_UP_QUEUE.with(|q| q.borrow_mut().remove(&key));
+        }
+        Err(e) => update_entry(&key, |entry| {
+            entry.attempts += 1;
+            entry.due_at = time() + BASE_BACKOFF_NS * (1 << entry.attempts);
+            entry.last_error = Some(e);
+        }),
+    }
+}
+
+/// Take every due follow-up, leasing each so overlapping ticks don't send it twice
+fn lease_due() -> Vec<(String, ScheduledFollowUp)> {
+    let now = time();
+    let due: Vec<(String, ScheduledFollowUp)> = FOLLOW_UP_QUEUE.with(|q| {
+        q.borrow()
+            .iter()
+            .filter(|(_, entry)| entry.due_at <= now)
+            .take(MAX_PER_TICK)
+            .collect()
+    });
+    for (key, _) in &due {
+        update_entry(key, |e| e.due_at = now + LEASE_NS);
+    }
+    due
+}
+
+/// Start every due follow-up
+fn process_due() {
+    for (key, entry) in lease_due() {
+        ic_cdk::spawn(run(key, entry));
+    }
+}
+
+/// Register the delivery timer (runs from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("follow_ups", FOLLOW_UP_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(FOLLOW_UP_TIMER_INTERVAL, || {
+        service_status::heartbeat("follow_ups", FOLLOW_UP_TIMER_INTERVAL);
+        process_due();
+    });
+}
+
+/// Drop every record (dev fixtures only)
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    FOLLOW_UP_QUEUE.with(|m| m.borrow_mut().clear_new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    #[test]
+    fn email_subject_is_encoded_and_message_is_url_safe() {
+        let raw = raw_email("guest@example.com", "Thanks — see you", "Hi");
+        let message = String::from_utf8(URL_SAFE_NO_PAD.decode(raw).unwrap()).unwrap();
+        assert!(message.starts_with("To: guest@example.com\r\nSubject: =?UTF-8?B?"));
+        assert!(message.ends_with("\r\n\r\nHi"));
+    }
+
+    #[test]
+    fn a_follow_up_is_leased_once_due_and_retried_after_the_lease() {
+        let booking_end = 1_735_689_600 * 1_000_000_000;
+        let clock = MockClock::at(booking_end);
+        clock::install(clock.clone());
+        let entry_key = key("booking-1", 0);
+        FOLLOW_UP_QUEUE.with(|q| q.borrow_mut().insert(entry_key.clone(), ScheduledFollowUp {
+            booking_id: "booking-1".to_string(),
+            index: 0,
+            due_at: booking_end + 24 * HOUR_NS,
+            attempts: 0,
+            last_error: None,
+        }));
+        let queued_due_at = || FOLLOW_UP_QUEUE.with(|q| q.borrow().get(&entry_key).map(|e| e.due_at));
+
+        clock.advance(24 * HOUR_NS - 1);
+        assert!(lease_due().is_empty());
+
+        clock.advance(1);
+        let leased = lease_due();
+        assert_eq!(leased.len(), 1);
+        assert_eq!(leased[0].0, entry_key);
+        assert_eq!(queued_due_at(), Some(time() + LEASE_NS));
+
+        // A second tick inside the lease doesn't start it again
+        clock.advance(LEASE_NS - 1);
+        assert!(lease_due().is_empty());
+
+        // A run that never reported back is picked up once the lease runs out
+        clock.advance(1);
+        assert_eq!(lease_due().len(), 1);
+        FOLLOW_UP_QUEUE.with(|q| q.borrow_mut().remove(&entry_key));
+    }
+}
@@ -0,0 +1,445 @@
+use candid::{CandidType, Deserialize};
+use serde_bytes::ByteBuf;
+use crate::api_keys::{self, ApiKey, ApiScope};
+use crate::availabilities::{self, BusyTimeBlock};
+use crate::bookings::{self, CreateBookingRequest};
+use crate::error_codes::{self, ErrorCode};
+use crate::{discord, ical, qr, stripe, terms};
+use crate::quotas::{self, QuotaKind};
+
+/// Public OpenAPI document describing the JSON API
+const OPENAPI_PATH: &str = "/api/openapi.json";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Request/response shapes of the HTTP gateway interface
+#[derive(CandidType, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+}
+
+#[derive(CandidType)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+    pub upgrade: Option<bool>, // Some(true) asks the gateway to retry as http_request_update
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+pub fn response(status_code: u16, content_type: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse {
+        status_code,
+        headers: vec![("Content-Type".to_string(), content_type.to_string())],
+        body: ByteBuf::from(body),
+        upgrade: None,
+    }
+}
+
+pub fn text(status_code: u16, message: &str) -> HttpResponse {
+    response(status_code, "text/plain; charset=utf-8", message.as_bytes().to_vec())
+}
+
+pub fn json(status_code: u16, value: &serde_json::Value) -> HttpResponse {
+    response(status_code, "application/json", value.to_string().into_bytes())
+}
+
+pub fn json_error(status_code: u16, message: &str) -> HttpResponse {
+    json(status_code, &serde_json::json!({ "error": message }))
+}
+
+fn upgrade() -> HttpResponse {
+    HttpResponse {
+        status_code: 200,
+        headers: vec![],
+        body: ByteBuf::new(),
+        upgrade: Some(true),
+    }
+}
+
+/// Split a request URL into its path and raw query string
+pub fn split_url(url: &str) -> (&str, &str) {
+    url.split_once('?').unwrap_or((url, ""))
+}
+
+/// URL-decoded value of a query string parameter
+pub fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+        .and_then(|v| urlencoding::decode(v).ok())
+        .map(|v| v.into_owned())
+}
+
+/// Case-insensitive header lookup
+pub fn header<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+// ============================================================================
+// Entry Points
+// ============================================================================
+
+/// Public, uncertified routes. API routes need authentication and rate
+/// limiting (state changes), so apart from the OpenAPI document they are
+/// upgraded to an update call.
+pub fn http_request(req: HttpRequest) -> HttpResponse {
+    let (path, query) = split_url(&req.url);
+    if path == OPENAPI_PATH {
+        return json(200, &openapi());
+    }
+    if path.starts_with("/api/") || path == discord::INTERACTIONS_PATH || path == stripe::WEBHOOK_PATH {
+        return upgrade();
+    }
+    if req.method != "GET" {
+        return text(405, "Method not allowed");
+    }
+    if let Some(file) = path.strip_prefix("/qr/") {
+        return qr::serve(file, query);
+    }
+    match path.strip_prefix("/a/").and_then(|rest| rest.strip_suffix("/availability.ics")) {
+        Some(id) if !id.contains('/') => ical::serve(id),
+        _ => text(404, "Not found"),
+    }
+}
+
+pub fn http_request_update(req: HttpRequest) -> HttpResponse {
+    let (path, _) = split_url(&req.url);
+    if path.starts_with("/api/") {
+        return handle_api(&req);
+    }
+    if path == discord::INTERACTIONS_PATH {
+        return discord::handle(&req);
+    }
+    if path == stripe::WEBHOOK_PATH {
+        return stripe::handle(&req);
+    }
+    text(404, "Not found")
+}
+
+// ============================================================================
+// JSON API
+// ============================================================================
+
+type Handler = fn(&ApiRequest) -> Result<HttpResponse, HttpResponse>;
+
+/// One authenticated JSON route. The registry drives both dispatch and the
+/// OpenAPI document, so the two can't drift apart.
+struct Route {
+    method: &'static str,
+    path: &'static str,                         // `{name}` segments are path parameters
+    scope: ApiScope,
+    summary: &'static str,
+    query: &'static [(&'static str, bool)],     // (name, required), all integers
+    body: Option<&'static str>,                 // Request schema name
+    response: (u16, &'static str),              // Status and response schema name
+    handler: Handler,
+}
+
+/// What a handler gets: the authenticated key, path parameters and the raw request
+struct ApiRequest<'a> {
+    key: ApiKey,
+    params: Vec<&'a str>,
+    query: &'a str,
+    req: &'a HttpRequest,
+}
+
+const ROUTES: &[Route] = &[
+    Route {
+        method: "GET",
+        path: "/api/availabilities",
+        scope: ApiScope::ReadAvailabilities,
+        summary: "List the key owner's availabilities",
+        query: &[],
+        body: None,
+        response: (200, "AvailabilityList"),
+        handler: list_availabilities,
+    },
+    Route {
+        method: "GET",
+        path: "/api/availabilities/{id}",
+        scope: ApiScope::ReadAvailabilities,
+        summary: "Get one of the key owner's availabilities",
+        query: &[],
+        body: None,
+        response: (200, "Availability"),
+        handler: get_availability,
+    },
+    Route {
+        method: "GET",
+        path: "/api/availabilities/{id}/free_slots",
+        scope: ApiScope::ReadAvailabilities,
+        summary: "Free windows (Unix seconds), or bookable slots of one duration",
+        query: &[("start", true), ("end", true), ("duration", false)],
+        body: None,
+        response: (200, "FreeSlotList"),
+        handler: get_free_slots,
+    },
+    Route {
+        method: "POST",
+        path: "/api/bookings",
+        scope: ApiScope::WriteBookings,
+        summary: "Book a slot on an availability as the key owner",
+        query: &[],
+        body: Some("CreateBookingRequest"),
+        response: (201, "Booking"),
+        handler: create_booking,
+    },
+    Route {
+        method: "POST",
+        path: "/api/availabilities/{id}/busy",
+        scope: ApiScope::WriteBusyTimes,
+        summary: "Replace an availability's busy times",
+        query: &[],
+        body: Some("BusyTimes"),
+        response: (200, "Ok"),
+        handler: set_busy_times,
+    },
+];
+
+/// Path parameters if `path` matches the route pattern
+fn match_path<'a>(pattern: &str, path: &'a str) -> Option<Vec<&'a str>> {
+    let pattern: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    if pattern.len() != segments.len() {
+        return None;
+    }
+    let mut params = Vec::new();
+    for (p, s) in pattern.iter().zip(&segments) {
+        if p.starts_with('{') {
+            params.push(*s);
+        } else if p != s {
+            return None;
+        }
+    }
+    Some(params)
+}
+
+/// `Authorization: Bearer <key>` or `X-API-Key: <key>`
+fn authenticate(req: &HttpRequest, scope: ApiScope) -> Result<ApiKey, HttpResponse> {
+    let secret = header(req, "Authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| header(req, "X-API-Key"))
+        .ok_or_else(|| json_error(401, "Missing API key"))?;
+    api_keys::authenticate(secret.trim(), scope).map_err(|(status, e)| json_error(status, error_codes::split(&e).1))
+}
+
+/// A backend error without its kind, with the status its code calls for
+fn backend_error(error: String) -> HttpResponse {
+    let (code, text) = error_codes::split(&error);
+    let status_code = match code {
+        ErrorCode::QuotaExceeded => 429,
+        ErrorCode::Unauthorized => 403,
+        _ => 400,
+    };
+    json_error(status_code, text)
+}
+
+fn to_json<T: serde::Serialize>(status_code: u16, value: &T) -> HttpResponse {
+    match serde_json::to_value(value) {
+        Ok(value) => json(status_code, &value),
+        Err(e) => json_error(500, &e.to_string()),
+    }
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(req: &HttpRequest) -> Result<T, HttpResponse> {
+    serde_json::from_slice(&req.body).map_err(|e| json_error(400, &format!("Invalid JSON body: {}", e)))
+}
+
+#[derive(Deserialize)]
+struct BusyTimesBody {
+    busy_times: Vec<BusyTimeBlock>,
+}
+
+/// Availability owned by the key's owner
+fn owned_availability(key: &ApiKey, id: &str) -> Result<availabilities::Availability, HttpResponse> {
+    match availabilities::get_availability(id.to_string()) {
+        Ok(availability) if availability.owner == key.owner => Ok(availability),
+        _ => Err(json_error(404, "Availability not found")),
+    }
+}
+
+fn handle_api(req: &HttpRequest) -> HttpResponse {
+    let (path, query) = split_url(&req.url);
+    let Some((route, params)) = ROUTES.iter()
+        .filter(|route| route.method == req.method)
+        .find_map(|route| match_path(route.path, path).map(|params| (route, params)))
+    else {
+        return json_error(404, "Unknown API route");
+    };
+
+    authenticate(req, route.scope)
+        .and_then(|key| (route.handler)(&ApiRequest { key, params, query, req }))
+        .unwrap_or_else(|error| error)
+}
+
+// ============================================================================
+// Route Handlers
+// ============================================================================
+
+fn list_availabilities(api: &ApiRequest) -> Result<HttpResponse, HttpResponse> {
+    Ok(to_json(200, &availabilities::list_user_availabilities(api.key.owner)))
+}
+
+fn get_availability(api: &ApiRequest) -> Result<HttpResponse, HttpResponse> {
+    owned_availability(&api.key, api.params[0]).map(|availability| to_json(200, &availability))
+}
+
+fn get_free_slots(api: &ApiRequest) -> Result<HttpResponse, HttpResponse> {
+    let availability = owned_availability(&api.key, api.params[0])?;
+    let param = |name| query_param(api.query, name).and_then(|v| v.parse::<u64>().ok());
+    let (Some(start), Some(end)) = (param("start"), param("end")) else {
+        return Err(json_error(400, "start and end (Unix seconds) are required"));
+    };
+    availabilities::get_free_slots_as(api.key.owner, availability.id, start, end, param("duration").map(|d| d as u32), None)
+        .map(|slots| to_json(200, &slots))
+        .map_err(backend_error)
+}
+
+fn create_booking(api: &ApiRequest) -> Result<HttpResponse, HttpResponse> {
+    let body: CreateBookingRequest = parse_body(api.req)?;
+    terms::require_accepted(api.key.owner)
+        .and_then(|_| quotas::consume_rate(api.key.owner, QuotaKind::BookingsPerDay))
+        .and_then(|_| bookings::create_booking(api.key.owner, body))
+        .map(|booking| to_json(201, &booking))
+        .map_err(backend_error)
+}
+
+fn set_busy_times(api: &ApiRequest) -> Result<HttpResponse, HttpResponse> {
+    let body: BusyTimesBody = parse_body(api.req)?;
+    terms::require_accepted(api.key.owner)
+        .and_then(|_| availabilities::update_availability_busy_times(api.key.owner, api.params[0].to_string(), body.busy_times))
+        .map(|_| json(200, &serde_json::json!({ "ok": true })))
+        .map_err(backend_error)
+}
+
+// ============================================================================
+// OpenAPI
+// ============================================================================
+
+/// JSON schemas referenced by the routes (the fields integrators rely on)
+fn schemas() -> serde_json::Value {
+    use serde_json::json;
+    let slot = json!({
+        "type": "object",
+        "required": ["start_time", "end_time"],
+        "properties": {
+            "start_time": { "type": "integer", "description": "Unix seconds" },
+            "end_time": { "type": "integer", "description": "Unix seconds" }
+        }
+    });
+    json!({
+        "Availability": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "timezone": { "type": "string" },
+                "slots": { "type": "array", "items": { "type": "object", "properties": {
+                    "day_of_week": { "type": "integer", "minimum": 0, "maximum": 6 },
+                    "start_time": { "type": "integer", "description": "Minutes from midnight" },
+                    "end_time": { "type": "integer", "description": "Minutes from midnight" }
+                } } }
+            }
+        },
+        "AvailabilityList": { "type": "array", "items": { "$ref": "#/components/schemas/Availability" } },
+        "FreeSlot": slot,
+        "FreeSlotList": { "type": "array", "items": { "$ref": "#/components/schemas/FreeSlot" } },
+        "BusyTimes": {
+            "type": "object",
+            "required": ["busy_times"],
+            "properties": { "busy_times": { "type": "array", "items": { "$ref": "#/components/schemas/FreeSlot" } } }
+        },
+        "CreateBookingRequest": {
+            "type": "object",
+            "required": ["availability_id", "start_time", "end_time", "guest_name"],
+            "properties": {
+                "availability_id": { "type": "string" },
+                "start_time": { "type": "integer", "description": "Unix seconds" },
+                "end_time": { "type": "integer", "description": "Unix seconds" },
+                "guest_name": { "type": "string" },
+                "guest_email": { "type": "string", "nullable": true },
+                "guest_timezone": { "type": "string", "nullable": true, "description": "IANA zone, e.g. Europe/Berlin" }
+            }
+        },
+        "Booking": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "availability_id": { "type": "string" },
+                "status": { "type": "string", "enum": ["Pending", "Confirmed", "Cancelled"] },
+                "start_time": { "type": "integer" },
+                "end_time": { "type": "integer" },
+                "guest_name": { "type": "string" },
+                "guest_email": { "type": "string", "nullable": true },
+                "guest_timezone": { "type": "string", "nullable": true, "description": "IANA zone, e.g. Europe/Berlin" }
+            }
+        },
+        "Ok": { "type": "object", "properties": { "ok": { "type": "boolean" } } },
+        "Error": { "type": "object", "properties": { "error": { "type": "string" } } }
+    })
+}
+
+/// OpenAPI 3.0 description of every registered route
+pub fn openapi() -> serde_json::Value {
+    use serde_json::json;
+    let schema_ref = |name: &str| json!({ "$ref": format!("#/components/schemas/{}", name) });
+    let error = json!({ "description": "Error", "content": { "application/json": { "schema": schema_ref("Error") } } });
+
+    let mut paths = serde_json::Map::new();
+    for route in ROUTES {
+        let mut parameters: Vec<serde_json::Value> = route.path
+            .split('/')
+            .filter_map(|s| s.strip_prefix('{')?.strip_suffix('}'))
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .collect();
+        parameters.extend(route.query.iter().map(|(name, required)| {
+            json!({ "name": name, "in": "query", "required": required, "schema": { "type": "integer" } })
+        }));
+
+        let mut operation = json!({
+            "summary": route.summary,
+            "description": format!("Requires the {:?} scope.", route.scope),
+            "parameters": parameters,
+            "responses": {
+                route.response.0.to_string(): {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": schema_ref(route.response.1) } }
+                },
+                "400": error, "401": error, "403": error, "404": error, "429": error
+            }
+        });
+        if let Some(body) = route.body {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema_ref(body) } }
+            });
+        }
+
+        let entry = paths.entry(route.path.to_string()).or_insert_with(|| json!({}));
+        entry[route.method.to_lowercase()] = operation;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "weeekaly API", "version": "1" },
+        "security": [{ "bearerAuth": [] }],
+        "paths": paths,
+        "components": {
+            "securitySchemes": { "bearerAuth": { "type": "http", "scheme": "bearer" } },
+            "schemas": schemas()
+        }
+    })
+}
@@ -0,0 +1,342 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    StableBTreeMap,
+};
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike};
+use chrono_tz::Tz;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::principal_moves;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Languages the canister can generate text in
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Locale {
+    En,
+    De,
+    Es,
+    Ar,
+    He,
+}
+
+impl Locale {
+    /// Parse a BCP 47 tag such as "de", "de-AT" or "es_MX"; unknown tags fall back to English
+    pub fn from_tag(tag: &str) -> Locale {
+        let language = tag.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        match language.as_str() {
+            "de" => Locale::De,
+            "es" => Locale::Es,
+            "ar" => Locale::Ar,
+            "he" | "iw" => Locale::He,
+            _ => Locale::En,
+        }
+    }
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Es => "es",
+            Locale::Ar => "ar",
+            Locale::He => "he",
+        }
+    }
+
+    pub fn is_rtl(&self) -> bool {
+        matches!(self, Locale::Ar | Locale::He)
+    }
+}
+
+/// Server-generated strings that reach guests
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    SlotNotAvailable,
+    SlotAlreadyBooked,
+    SlotInPast,
+    LinkAlreadyUsed,
+    LinkExpired,
+    SignInToPay,
+    BookingOn,
+}
+
+const LOCALES: [Locale; 5] = [Locale::En, Locale::De, Locale::Es, Locale::Ar, Locale::He];
+
+pub const MESSAGES: [Message; 7] = [
+    Message::SlotNotAvailable,
+    Message::SlotAlreadyBooked,
+    Message::SlotInPast,
+    Message::LinkAlreadyUsed,
+    Message::LinkExpired,
+    Message::SignInToPay,
+    Message::BookingOn,
+];
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Principal -> locale tag chosen by the user
+    static USER_LOCALES: RefCell<StableBTreeMap<Principal, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22)))
+        )
+    );
+
+    // Principals who want generated dates annotated with the Hijri date
+    static HIJRI_ANNOTATION: RefCell<StableBTreeMap<Principal, bool, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(23)))
+        )
+    );
+}
+
+pub fn clear() {
+    USER_LOCALES.with(|m| m.borrow_mut().clear_new());
+    HIJRI_ANNOTATION.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// String Catalog
+// ============================================================================
+
+pub fn t(locale: Locale, message: Message) -> &'static str {
+    use Message::*;
+    match (locale, message) {
+        (Locale::En, SlotNotAvailable) => "Requested time is not available",
+        (Locale::De, SlotNotAvailable) => "Die gewünschte Zeit ist nicht verfügbar",
+        (Locale::Es, SlotNotAvailable) => "La hora solicitada no está disponible",
+        (Locale::Ar, SlotNotAvailable) => "الوقت المطلوب غير متاح",
+        (Locale::He, SlotNotAvailable) => "המועד המבוקש אינו זמין",
+
+        (Locale::En, SlotAlreadyBooked) => "Requested time is already booked",
+        (Locale::De, SlotAlreadyBooked) => "Die gewünschte Zeit ist bereits gebucht",
+        (Locale::Es, SlotAlreadyBooked) => "La hora solicitada ya está reservada",
+        (Locale::Ar, SlotAlreadyBooked) => "الوقت المطلوب محجوز بالفعل",
+        (Locale::He, SlotAlreadyBooked) => "המועד המבוקש כבר תפוס",
+
+        (Locale::En, SlotInPast) => "Cannot book a slot in the past",
+        (Locale::De, SlotInPast) => "Vergangene Zeiten können nicht gebucht werden",
+        (Locale::Es, SlotInPast) => "No se puede reservar una hora pasada",
+        (Locale::Ar, SlotInPast) => "لا يمكن حجز وقت في الماضي",
+        (Locale::He, SlotInPast) => "לא ניתן להזמין מועד שעבר",
+
+        (Locale::En, LinkAlreadyUsed) => "Session link has already been used",
+        (Locale::De, LinkAlreadyUsed) => "Dieser Link wurde bereits verwendet",
+        (Locale::Es, LinkAlreadyUsed) => "Este enlace ya se ha utilizado",
+        (Locale::Ar, LinkAlreadyUsed) => "تم استخدام هذا الرابط بالفعل",
+        (Locale::He, LinkAlreadyUsed) => "הקישור הזה כבר נוצל",
+
+        (Locale::En, LinkExpired) => "Session link has expired",
+        (Locale::De, LinkExpired) => "Dieser Link ist abgelaufen",
+        (Locale::Es, LinkExpired) => "Este enlace ha caducado",
+        (Locale::Ar, LinkExpired) => "انتهت صلاحية هذا الرابط",
+        (Locale::He, LinkExpired) => "תוקף הקישור הזה פג",
+
+        (Locale::En, SignInToPay) => "Sign in to pay for this session",
+        (Locale::De, SignInToPay) => "Bitte melde dich an, um diese Sitzung zu bezahlen",
+        (Locale::Es, SignInToPay) => "Inicia sesión para pagar esta sesión",
+        (Locale::Ar, SignInToPay) => "سجّل الدخول لدفع ثمن هذه الجلسة",
+        (Locale::He, SignInToPay) => "יש להתחבר כדי לשלם על הפגישה",
+
+        (Locale::En, BookingOn) => "Booking on",
+        (Locale::De, BookingOn) => "Buchung am",
+        (Locale::Es, BookingOn) => "Reserva el",
+        (Locale::Ar, BookingOn) => "حجز في",
+        (Locale::He, BookingOn) => "הזמנה ב־",
+    }
+}
+
+/// The catalog key of a string generated by `t` in any locale, so an error
+/// can be classified by what it says rather than by its English wording
+pub fn message_key(text: &str) -> Option<Message> {
+    MESSAGES.into_iter().find(|message| LOCALES.iter().any(|locale| t(*locale, *message) == text))
+}
+
+// ============================================================================
+// Date Formatting
+// ============================================================================
+
+fn weekday_name(locale: Locale, weekday: u32) -> &'static str {
+    // 0 = Monday
+    const EN: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+    const DE: [&str; 7] = ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"];
+    const ES: [&str; 7] = ["lunes", "martes", "miércoles", "jueves", "viernes", "sábado", "domingo"];
+    const AR: [&str; 7] = ["الاثنين", "الثلاثاء", "الأربعاء", "الخميس", "الجمعة", "السبت", "الأحد"];
+    const HE: [&str; 7] = ["יום שני", "יום שלישי", "יום רביעי", "יום חמישי", "יום שישי", "שבת", "יום ראשון"];
+    let names = match locale {
+        Locale::En => EN,
+        Locale::De => DE,
+        Locale::Es => ES,
+        Locale::Ar => AR,
+        Locale::He => HE,
+    };
+    names[weekday as usize % 7]
+}
+
+fn month_name(locale: Locale, month: u32) -> &'static str {
+    // 1 = January
+    const EN: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+    const DE: [&str; 12] = ["Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember"];
+    const ES: [&str; 12] = ["enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre"];
+    const AR: [&str; 12] = ["يناير", "فبراير", "مارس", "أبريل", "مايو", "يونيو", "يوليو", "أغسطس", "سبتمبر", "أكتوبر", "نوفمبر", "ديسمبر"];
+    const HE: [&str; 12] = ["ינואר", "פברואר", "מרץ", "אפריל", "מאי", "יוני", "יולי", "אוגוסט", "ספטמבר", "אוקטובר", "נובמבר", "דצמבר"];
+    let names = match locale {
+        Locale::En => EN,
+        Locale::De => DE,
+        Locale::Es => ES,
+        Locale::Ar => AR,
+        Locale::He => HE,
+    };
+    names[(month as usize + 11) % 12]
+}
+
+/// Long date and 24h time in the given IANA timezone (unknown zones use UTC),
+/// e.g. "Monday, January 15, 2024, 10:00" / "Montag, 15. Januar 2024, 10:00".
+/// Right-to-left locales are wrapped in a directional isolate so the text
+/// renders correctly when embedded in left-to-right content.
+pub fn format_datetime(locale: Locale, unix_secs: u64, timezone: &str, hijri: bool) -> String {
+    let tz: Tz = timezone.parse().unwrap_or(chrono_tz::UTC);
+    let Some(dt) = tz.timestamp_opt(unix_secs as i64, 0).single() else {
+        return unix_secs.to_string();
+    };
+
+    let weekday = weekday_name(locale, dt.weekday().num_days_from_monday());
+    let month = month_name(locale, dt.month());
+    let (day, year) = (dt.day(), dt.year());
+    let time = format!("{:02}:{:02}", dt.hour(), dt.minute());
+
+    let mut text = match locale {
+        Locale::En => format!("{}, {} {}, {}, {}", weekday, month, day, year, time),
+        Locale::De => format!("{}, {}. {} {}, {}", weekday, day, month, year, time),
+        Locale::Es => format!("{}, {} de {} de {}, {}", weekday, day, month, year, time),
+        Locale::Ar => format!("{}، {} {} {}، {}", weekday, day, month, year, time),
+        Locale::He => format!("{}, {} ב{} {}, {}", weekday, day, month, year, time),
+    };
+
+    if hijri {
+        let (h_year, h_month, h_day) = hijri_from_date(dt.date_naive());
+        text = format!("{} ({} {} {} {})", text, h_day, hijri_month_name(locale, h_month), h_year, hijri_era(locale));
+    }
+
+    if locale == Locale::Ar {
+        text = arabic_indic_digits(&text);
+    }
+    if locale.is_rtl() {
+        text = rtl_isolate(&text);
+    }
+    text
+}
+
+// ============================================================================
+// Right-to-left & Hijri Helpers
+// ============================================================================
+
+/// Wrap text in RIGHT-TO-LEFT ISOLATE ... POP DIRECTIONAL ISOLATE
+pub fn rtl_isolate(text: &str) -> String {
+    format!("\u{2067}{}\u{2069}", text)
+}
+
+/// Replace ASCII digits with Arabic-Indic digits (٠١٢٣٤٥٦٧٨٩)
+pub fn arabic_indic_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => char::from_u32(0x0660 + d).unwrap_or(c),
+            None => c,
+        })
+        .collect()
+}
+
+/// Civil date to the tabular (arithmetical) Islamic calendar. It can differ
+/// by a day from sighting-based calendars, so it is only used as an annotation.
+pub fn hijri_from_date(date: NaiveDate) -> (i64, u32, u32) {
+    let jdn = date.num_days_from_ce() as i64 + 1_721_425;
+    let l = jdn - 1_948_440 + 10_632;
+    let n = (l - 1) / 10_631;
+    let l = l - 10_631 * n + 354;
+    let j = ((10_985 - l) / 5_316) * ((50 * l) / 17_719) + (l / 5_670) * ((43 * l) / 15_238);
+    let l = l - ((30 - j) / 15) * ((17_719 * j) / 50) - (j / 16) * ((15_238 * j) / 43) + 29;
+    let month = (24 * l) / 709;
+    let day = l - (709 * month) / 24;
+    let year = 30 * n + j - 30;
+    (year, month as u32, day as u32)
+}
+
+fn hijri_month_name(locale: Locale, month: u32) -> &'static str {
+    // 1 = Muharram
+    const AR: [&str; 12] = ["محرم", "صفر", "ربيع الأول", "ربيع الآخر", "جمادى الأولى", "جمادى الآخرة", "رجب", "شعبان", "رمضان", "شوال", "ذو القعدة", "ذو الحجة"];
+    const HE: [&str; 12] = ["מוחרם", "צפר", "רביע אל-אוול", "רביע א-תאני", "ג'ומאדא אל-אולא", "ג'ומאדא א-תאניה", "רג'ב", "שעבאן", "רמדאן", "שוואל", "ד'ו אל-קעדה", "ד'ו אל-חיג'ה"];
+    const LATIN: [&str; 12] = ["Muharram", "Safar", "Rabi al-Awwal", "Rabi al-Thani", "Jumada al-Ula", "Jumada al-Akhirah", "Rajab", "Shaban", "Ramadan", "Shawwal", "Dhu al-Qadah", "Dhu al-Hijjah"];
+    let names = match locale {
+        Locale::Ar => AR,
+        Locale::He => HE,
+        _ => LATIN,
+    };
+    names[(month as usize + 11) % 12]
+}
+
+fn hijri_era(locale: Locale) -> &'static str {
+    match locale {
+        Locale::Ar => "هـ",
+        Locale::He => "להג'רה",
+        _ => "AH",
+    }
+}
+
+// ============================================================================
+// User Preferences
+// ============================================================================
+
+pub fn get_user_locale(principal: Principal) -> Option<Locale> {
+    USER_LOCALES
+        .with(|l| l.borrow().get(&principal))
+        .map(|tag| Locale::from_tag(&tag))
+}
+
+pub fn set_user_locale(principal: Principal, locale: Locale) {
+    USER_LOCALES.with(|l| {
+        l.borrow_mut().insert(principal, locale.tag().to_string());
+    });
+}
+
+/// Locale for content about an availability: its own setting, then the owner's, then English
+pub fn resolve(availability_locale: Option<Locale>, owner: Principal) -> Locale {
+    availability_locale
+        .or_else(|| get_user_locale(owner))
+        .unwrap_or(Locale::En)
+}
+
+pub fn wants_hijri(principal: Principal) -> bool {
+    HIJRI_ANNOTATION.with(|h| h.borrow().get(&principal).unwrap_or(false))
+}
+
+pub fn set_hijri_annotation(principal: Principal, enabled: bool) {
+    HIJRI_ANNOTATION.with(|h| {
+        let mut prefs = h.borrow_mut();
+        if enabled {
+            prefs.insert(principal, true);
+        } else {
+            prefs.remove(&principal);
+        }
+    });
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(principal: Principal) -> u32 {
+    USER_LOCALES.with(|l| principal_moves::count_keyed(&l.borrow(), principal))
+        + HIJRI_ANNOTATION.with(|h| principal_moves::count_keyed(&h.borrow(), principal))
+}
+
+/// Carry `from`'s language settings over to `to` unless `to` set its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    USER_LOCALES.with(|l| principal_moves::move_keyed(&mut l.borrow_mut(), from, to))
+        + HIJRI_ANNOTATION.with(|h| principal_moves::move_keyed(&mut h.borrow_mut(), from, to))
+}
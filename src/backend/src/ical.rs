@@ -0,0 +1,167 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use crate::availabilities::{self, Availability};
+use crate::bookings::{Booking, BookingStatus};
+use crate::http::{self, HttpResponse};
+use crate::error_codes;
+
+const PRODID: &str = "-//weeekaly//availability//EN";
+const UID_DOMAIN: &str = "weeekaly.com";
+
+/// Weekly rules are anchored on this Sunday; RRULEs repeat them from there
+const ANCHOR_SUNDAY: (i32, u32, u32) = (2024, 1, 7);
+
+const BYDAY: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Escape TEXT values (RFC 5545 section 3.3.11)
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold content lines longer than 75 octets (RFC 5545 section 3.1)
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += len;
+    }
+    folded
+}
+
+/// UTC DATE-TIME form, e.g. 20240107T090000Z (`unix_secs` in seconds)
+pub fn utc_stamp(unix_secs: u64) -> String {
+    Utc.timestamp_opt(unix_secs as i64, 0)
+        .single()
+        .map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Join content lines with CRLF, folding each one
+pub fn render(lines: &[String]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+// ============================================================================
+// VAVAILABILITY
+// ============================================================================
+
+/// The weekly schedule as a VCALENDAR holding one VAVAILABILITY component
+/// (RFC 7953) with a weekly-recurring AVAILABLE block per slot
+pub fn vavailability(availability: &Availability) -> String {
+    let (year, month, day) = ANCHOR_SUNDAY;
+    let anchor = NaiveDate::from_ymd_opt(year, month, day).expect("valid anchor date");
+    let dtstamp = utc_stamp(availability.updated_at / 1_000_000_000);
+    let tzid = &availability.timezone;
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "BEGIN:VAVAILABILITY".to_string(),
+        format!("UID:{}@{}", availability.id, UID_DOMAIN),
+        format!("DTSTAMP:{}", dtstamp),
+        format!("SUMMARY:{}", escape_text(&availability.title)),
+    ];
+    if !availability.description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&availability.description)));
+    }
+
+    for (i, slot) in availability.slots.iter().enumerate() {
+        let date = anchor + chrono::Duration::days(slot.day_of_week as i64);
+        let local = |minutes: u16| format!("{}T{:02}{:02}00", date.format("%Y%m%d"), minutes / 60, minutes % 60);
+        lines.extend([
+            "BEGIN:AVAILABLE".to_string(),
+            format!("UID:{}-{}@{}", availability.id, i, UID_DOMAIN),
+            format!("DTSTAMP:{}", dtstamp),
+            format!("DTSTART;TZID={}:{}", tzid, local(slot.start_time)),
+            format!("DTEND;TZID={}:{}", tzid, local(slot.end_time)),
+            format!("RRULE:FREQ=WEEKLY;BYDAY={}", BYDAY[slot.day_of_week as usize % 7]),
+            "END:AVAILABLE".to_string(),
+        ]);
+    }
+
+    lines.extend(["END:VAVAILABILITY".to_string(), "END:VCALENDAR".to_string()]);
+    render(&lines)
+}
+
+// ============================================================================
+// VEVENT
+// ============================================================================
+
+/// One booking as an iTIP VCALENDAR (RFC 5546): a REQUEST while it stands,
+/// a CANCEL once cancelled, so calendar apps add and later remove it.
+/// `availability` supplies the title, organizer and meeting link.
+pub fn booking_event(booking: &Booking, availability: Option<&Availability>) -> String {
+    let cancelled = booking.status == BookingStatus::Cancelled;
+    let title = availability.map(|a| a.title.as_str()).unwrap_or("Meeting");
+    let meeting_url = availability.and_then(|a| a.meeting_url.as_deref());
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        format!("METHOD:{}", if cancelled { "CANCEL" } else { "REQUEST" }),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@{}", booking.id, UID_DOMAIN),
+        format!("DTSTAMP:{}", utc_stamp(booking.updated_at / 1_000_000_000)),
+        // Clients keep the highest sequence, so the cancellation replaces the invite
+        format!("SEQUENCE:{}", u8::from(cancelled)),
+        format!("DTSTART:{}", utc_stamp(booking.start_time)),
+        format!("DTEND:{}", utc_stamp(booking.end_time)),
+        format!("SUMMARY:{}", escape_text(title)),
+        format!("STATUS:{}", if cancelled { "CANCELLED" } else { "CONFIRMED" }),
+    ];
+    if let Some(email) = availability.and_then(|a| a.owner_email.as_deref()) {
+        let name = availability.and_then(|a| a.owner_name.as_deref()).unwrap_or(email);
+        lines.push(format!("ORGANIZER;CN=\"{}\":mailto:{}", name.replace('"', "'"), email));
+    }
+    if let Some(email) = booking.guest_email.as_deref() {
+        lines.push(format!(
+            "ATTENDEE;CN=\"{}\";ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED:mailto:{}",
+            booking.guest_name.replace('"', "'"),
+            email,
+        ));
+    }
+    if let Some(url) = meeting_url {
+        lines.push(format!("URL:{}", url));
+        lines.push(format!("LOCATION:{}", escape_text(url)));
+        lines.push(format!("DESCRIPTION:{}", escape_text(&format!("Join: {}", url))));
+    }
+    lines.extend(["END:VEVENT".to_string(), "END:VCALENDAR".to_string()]);
+    render(&lines)
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// Serves `GET /a/<availability_id>/availability.ics` so calendar clients
+/// can subscribe to the published windows
+pub fn serve(id: &str) -> HttpResponse {
+    match availabilities::get_availability(id.to_string()) {
+        Ok(availability) => {
+            let mut response = http::response(200, "text/calendar; charset=utf-8", vavailability(&availability).into_bytes());
+            response.headers.push(("Cache-Control".to_string(), "public, max-age=300".to_string()));
+            response
+        }
+        Err(e) => http::text(404, error_codes::split(&e).1),
+    }
+}
@@ -0,0 +1,199 @@
+//! Rebuild of every index derived from the availability and booking maps:
+//! the owner lists, the email/username search indices and the counters.
+//! It runs as a maintenance job, a chunk at a time, so it fits the
+//! instruction limit however large the maps are.
+//!
+//! Owner lists are repaired in place (missing IDs appended, dangling and
+//! duplicate ones dropped), so the order users gave their availabilities
+//! survives. Counters are zeroed first and recounted by the walk; while the
+//! walk is under way, writes to records it hasn't reached yet leave the
+//! counters alone (see `counts_later`), so nothing is counted twice. A
+//! cancelled or failed rebuild leaves the counters partial until it is run
+//! again.
+
+use candid::{CandidType, Encode, Principal};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, bookings, counters, quarantine};
+use crate::maintenance::{self, MaintenanceJob, MaintenanceTask};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum RebuildPhase {
+    #[default]
+    NotStarted,
+    ResetCounters,
+    Availabilities,    // Owner lists, search indices and counters from AVAILABILITIES
+    AvailabilityLists, // Drop dangling IDs from USER_AVAILABILITIES
+    Bookings,          // Owner lists and counters from BOOKINGS
+    BookingLists,      // Drop dangling IDs from OWNER_BOOKINGS
+    Done,
+}
+
+/// Which walk a record belongs to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Walk {
+    Availabilities,
+    Bookings,
+}
+
+/// Progress of the current (or last) rebuild, persisted so it resumes
+/// across messages and upgrades
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RebuildStatus {
+    pub phase: RebuildPhase,
+    pub cursor: Option<String>,          // Last key handled in the current phase
+    pub availabilities_total: u64,       // Map sizes when the rebuild started
+    pub bookings_total: u64,
+    pub availabilities_scanned: u64,
+    pub bookings_scanned: u64,
+    pub entries_fixed: u64,              // List entries added or dropped
+    pub started_at: Option<u64>,
+    pub completed_at: Option<u64>,
+}
+
+/// What one chunk of a phase did; `next` is None once the phase is finished
+pub struct RebuildChunk {
+    pub next: Option<String>,
+    pub scanned: u64,
+    pub fixed: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for RebuildStatus {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "RebuildStatus").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static REBUILD_STATUS: RefCell<StableCell<RebuildStatus, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(73))),
+            RebuildStatus::default(),
+        ).expect("Failed to init rebuild status")
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+pub fn status() -> RebuildStatus {
+    REBUILD_STATUS.with(|s| s.borrow().get().clone())
+}
+
+fn save(status: &RebuildStatus) {
+    REBUILD_STATUS.with(|s| s.borrow_mut().set(status.clone()))
+        .expect("Failed to save rebuild status");
+}
+
+fn principal_cursor(cursor: &Option<String>) -> Option<Principal> {
+    cursor.as_ref().and_then(|text| Principal::from_text(text).ok())
+}
+
+/// Whether the running rebuild will still count this record, in which case
+/// a write to it must not touch the counters
+pub fn counts_later(walk: Walk, id: &str) -> bool {
+    let status = status();
+    let ahead = || status.cursor.as_ref().is_none_or(|cursor| id > cursor.as_str());
+    match (status.phase, walk) {
+        (RebuildPhase::NotStarted | RebuildPhase::Done, _) => false,
+        (RebuildPhase::ResetCounters, _) => true,
+        (RebuildPhase::Availabilities, Walk::Availabilities) => ahead(),
+        (RebuildPhase::Availabilities | RebuildPhase::AvailabilityLists, Walk::Bookings) => true,
+        (RebuildPhase::Bookings, Walk::Bookings) => ahead(),
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Rebuild
+// ============================================================================
+
+/// Start a rebuild as a maintenance job; a rebuild that is already running
+/// is returned instead of restarted
+pub fn start() -> MaintenanceJob {
+    if let Some(job) = maintenance::find_active(MaintenanceTask::IndexRebuild) {
+        return job;
+    }
+    save(&RebuildStatus {
+        phase: RebuildPhase::ResetCounters,
+        availabilities_total: availabilities::AVAILABILITIES.with(|a| a.borrow().len()),
+        bookings_total: bookings::count(),
+        started_at: Some(time()),
+        ..Default::default()
+    });
+    ic_cdk::println!("🔧 [rebuild] Rebuilding indices");
+    maintenance::start(MaintenanceTask::IndexRebuild)
+}
+
+/// Run one chunk of the current phase; returns the items handled and whether
+/// the rebuild is finished
+pub fn run_step(batch: usize) -> (u64, bool) {
+    let mut status = status();
+    let chunk = match status.phase {
+        RebuildPhase::NotStarted | RebuildPhase::Done => return (0, true),
+        RebuildPhase::ResetCounters => counters::reset_chunk(status.cursor.clone(), batch),
+        RebuildPhase::Availabilities => {
+            let chunk = availabilities::rebuild_chunk(status.cursor.clone(), batch);
+            status.availabilities_scanned += chunk.scanned;
+            chunk
+        }
+        RebuildPhase::AvailabilityLists => availabilities::prune_owner_lists_chunk(principal_cursor(&status.cursor), batch),
+        RebuildPhase::Bookings => {
+            let chunk = bookings::rebuild_chunk(status.cursor.clone(), batch);
+            status.bookings_scanned += chunk.scanned;
+            chunk
+        }
+        RebuildPhase::BookingLists => bookings::prune_owner_lists_chunk(principal_cursor(&status.cursor), batch),
+    };
+    status.entries_fixed += chunk.fixed;
+
+    match chunk.next {
+        Some(next) => status.cursor = Some(next),
+        None => {
+            status.cursor = None;
+            status.phase = match status.phase {
+                RebuildPhase::ResetCounters => RebuildPhase::Availabilities,
+                RebuildPhase::Availabilities => RebuildPhase::AvailabilityLists,
+                RebuildPhase::AvailabilityLists => RebuildPhase::Bookings,
+                RebuildPhase::Bookings => RebuildPhase::BookingLists,
+                _ => RebuildPhase::Done,
+            };
+            if status.phase == RebuildPhase::Done {
+                status.completed_at = Some(time());
+                ic_cdk::println!(
+                    "✅ [rebuild] Rebuilt indices of {} availabilities and {} bookings ({} list entries fixed)",
+                    status.availabilities_scanned, status.bookings_scanned, status.entries_fixed
+                );
+            }
+        }
+    }
+    save(&status);
+    (chunk.scanned, status.phase == RebuildPhase::Done)
+}
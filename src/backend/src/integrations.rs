@@ -0,0 +1,369 @@
+use candid::{CandidType, Nat, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse,
+    TransformArgs, TransformContext,
+};
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, datetime, delivery_log, entropy, i18n, matrix, principal_moves, quarantine, rebook, trace};
+use crate::delivery_log::NotificationChannel;
+use crate::outbox::{self, SideEffect};
+use crate::bookings::{self, Booking, BookingStatus};
+use crate::plans::{self, Feature};
+use crate::quotas::{self, QuotaKind};
+use crate::error_codes::{self, ErrorCode};
+
+/// Flat payload fields hooks can receive, in the default order
+pub const FIELDS: [&str; 18] = [
+    "event",
+    "booking_id",
+    "availability_id",
+    "availability_title",
+    "status",
+    "guest_name",
+    "guest_email",
+    "start_time",
+    "end_time",
+    "start_unix",
+    "end_unix",
+    "duration_minutes",
+    "amount_paid",
+    "created_at",
+    "guest_timezone",
+    "guest_local_start",
+    "rebook_url",
+    "trace_id",
+];
+
+const MAX_FIELD_MAPPINGS: usize = 20;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum HookEvent {
+    BookingCreated,
+    BookingCancelled,
+}
+
+impl HookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            HookEvent::BookingCreated => "booking.created",
+            HookEvent::BookingCancelled => "booking.cancelled",
+        }
+    }
+}
+
+/// Rename a payload field (e.g. `guest_email` -> `Email`) to match sheet columns
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct FieldMapping {
+    pub field: String,
+    pub key: String,
+}
+
+/// An outbound catch hook (Zapier, Make, Sheets webhooks) receiving one flat
+/// JSON object per booking event
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct IntegrationHook {
+    pub id: String,
+    pub owner: Principal,
+    pub url: String,
+    pub events: Vec<HookEvent>,
+    pub field_mappings: Vec<FieldMapping>, // Empty = every field under its own name
+    pub created_at: u64,
+    pub last_delivery_at: Option<u64>,
+    pub last_status: Option<String>,       // HTTP status or error of the last delivery
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateIntegrationHookRequest {
+    pub url: String,
+    pub events: Vec<HookEvent>,
+    pub field_mappings: Vec<FieldMapping>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl IntegrationHook {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and subscribed to no events, so it never fires.
+    fn unreadable() -> Self {
+        IntegrationHook {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            url: String::new(),
+            events: vec![],
+            field_mappings: vec![],
+            created_at: 0,
+            last_delivery_at: None,
+            last_status: None,
+        }
+    }
+}
+
+impl Storable for IntegrationHook {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "IntegrationHook").unwrap_or_else(IntegrationHook::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static HOOKS: RefCell<StableBTreeMap<String, IntegrationHook, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(28)))
+        )
+    );
+}
+
+pub fn clear() {
+    HOOKS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn generate_hook_id() -> Result<String, String> {
+    Ok(format!("hook-{}", entropy::next_token(6)?))
+}
+
+fn validate_request(req: &CreateIntegrationHookRequest) -> Result<(), String> {
+    if !req.url.starts_with("https://") || req.url.len() > 500 {
+        return Err("url must be an https URL up to 500 characters".to_string());
+    }
+    if req.events.is_empty() {
+        return Err("at least one event is required".to_string());
+    }
+    if req.field_mappings.len() > MAX_FIELD_MAPPINGS {
+        return Err(format!("At most {} field mappings", MAX_FIELD_MAPPINGS));
+    }
+    for mapping in &req.field_mappings {
+        if !FIELDS.contains(&mapping.field.as_str()) {
+            return Err(format!("Unknown field: {} (expected one of {})", mapping.field, FIELDS.join(", ")));
+        }
+        if mapping.key.trim().is_empty() || mapping.key.len() > 64 {
+            return Err("mapping keys must be 1-64 characters".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn hooks_of(owner: Principal) -> Vec<IntegrationHook> {
+    HOOKS.with(|h| {
+        h.borrow()
+            .iter()
+            .filter(|(_, hook)| hook.owner == owner)
+            .map(|(_, hook)| hook)
+            .collect()
+    })
+}
+
+/// Every field as a string so spreadsheet columns stay stable
+pub fn field_value(field: &str, event: HookEvent, booking: &Booking, title: &str) -> String {
+    match field {
+        "event" => event.name().to_string(),
+        "booking_id" => booking.id.clone(),
+        "availability_id" => booking.availability_id.clone(),
+        "availability_title" => title.to_string(),
+        "status" => format!("{:?}", booking.status),
+        "guest_name" => booking.guest_name.clone(),
+        "guest_email" => booking.guest_email.clone().unwrap_or_default(),
+        "start_time" => datetime::format_iso8601(booking.start_time),
+        "end_time" => datetime::format_iso8601(booking.end_time),
+        "start_unix" => booking.start_time.to_string(),
+        "end_unix" => booking.end_time.to_string(),
+        "duration_minutes" => ((booking.end_time - booking.start_time) / 60).to_string(),
+        "amount_paid" => booking.payment.as_ref().map(|p| p.amount.to_string()).unwrap_or_default(),
+        "created_at" => datetime::format_iso8601(booking.created_at / 1_000_000_000),
+        "guest_timezone" => bookings::guest_timezone(booking),
+        "guest_local_start" => {
+            let availability_locale = availabilities::get_availability(booking.availability_id.clone()).ok().and_then(|a| a.locale);
+            let locale = i18n::resolve(availability_locale, booking.owner);
+            i18n::format_datetime(locale, booking.start_time, &bookings::guest_timezone(booking), false)
+        }
+        "rebook_url" => rebook::url_for(booking),
+        "trace_id" => trace::current().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Flat JSON object for one hook, honouring its field mappings
+pub fn payload(hook: &IntegrationHook, event: HookEvent, booking: &Booking) -> String {
+    let title = availabilities::get_availability(booking.availability_id.clone())
+        .map(|a| a.title)
+        .unwrap_or_default();
+
+    let mut object = serde_json::Map::new();
+    if hook.field_mappings.is_empty() {
+        for field in FIELDS {
+            object.insert(field.to_string(), field_value(field, event, booking, &title).into());
+        }
+    } else {
+        for mapping in &hook.field_mappings {
+            object.insert(mapping.key.clone(), field_value(&mapping.field, event, booking, &title).into());
+        }
+    }
+    serde_json::Value::Object(object).to_string()
+}
+
+/// POST the booking event to one hook. Errors (non-2xx included) are retried by the outbox.
+pub async fn deliver(hook_id: String, event: HookEvent, booking_id: String) -> Result<(), String> {
+    // A hook deleted or a booking dropped since the event was queued has nothing left to deliver
+    let Some(hook) = HOOKS.with(|h| h.borrow().get(&hook_id)) else {
+        return Ok(());
+    };
+    let Some(booking) = bookings::find(&booking_id) else {
+        return Ok(());
+    };
+    if event == HookEvent::BookingCreated && booking.status != BookingStatus::Confirmed {
+        return Ok(());
+    }
+
+    let request = CanisterHttpRequestArgument {
+        url: hook.url.clone(),
+        method: HttpMethod::POST,
+        body: Some(payload(&hook, event, &booking).into_bytes()),
+        max_response_bytes: Some(1024),
+        transform: Some(TransformContext::from_name("transform_hook_response".to_string(), vec![])),
+        headers: vec![HttpHeader {
+            name: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+        }],
+    };
+
+    let result = match http_request(request, 25_000_000_000).await {
+        Ok((response,)) if is_success(&response.status) => Ok(response.status.to_string()),
+        Ok((response,)) => Err(format!("HTTP {}", response.status)),
+        Err((code, msg)) => Err(format!("{:?}: {}", code, msg)),
+    };
+
+    delivery_log::record(&booking.id, NotificationChannel::IntegrationHook, Some(hook.id.clone()), event, &result);
+    HOOKS.with(|h| {
+        let mut map = h.borrow_mut();
+        if let Some(mut stored) = map.get(&hook.id) {
+            stored.last_delivery_at = Some(time());
+            stored.last_status = Some(result.clone().unwrap_or_else(|e| e));
+            map.insert(hook.id.clone(), stored);
+        }
+    });
+    result.map(|_| ())
+}
+
+pub fn is_success(status: &Nat) -> bool {
+    u16::try_from(&status.0).is_ok_and(|code| (200..300).contains(&code))
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// Queue delivery of a booking event to the owner's hooks and Matrix room.
+/// Jobs go through the outbox, so they are only sent if this call commits
+/// and are retried until the receiver accepts them.
+pub fn notify(event: HookEvent, booking_id: &str) {
+    let Some(booking) = bookings::find(booking_id) else {
+        return;
+    };
+    for hook_id in hook_ids_for(booking.owner, event) {
+        outbox::enqueue(SideEffect::IntegrationHook { hook_id, event, booking_id: booking.id.clone() });
+    }
+    matrix::notify(event, &booking);
+}
+
+/// The owner's hooks subscribed to the event
+pub fn hook_ids_for(owner: Principal, event: HookEvent) -> Vec<String> {
+    hooks_of(owner).into_iter().filter(|h| h.events.contains(&event)).map(|h| h.id).collect()
+}
+
+/// Strip everything that differs between replicas so the outcall reaches consensus
+pub fn transform_response(args: TransformArgs) -> HttpResponse {
+    HttpResponse {
+        status: args.response.status,
+        headers: vec![],
+        body: vec![],
+    }
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn create_hook(caller: Principal, req: CreateIntegrationHookRequest) -> Result<IntegrationHook, String> {
+    plans::require_feature(caller, Feature::Webhooks)?;
+    quotas::check_count(caller, QuotaKind::WebhookSubscriptions, hooks_of(caller).len() as u32)?;
+    validate_request(&req)?;
+
+    let hook = IntegrationHook {
+        id: generate_hook_id()?,
+        owner: caller,
+        url: req.url,
+        events: req.events,
+        field_mappings: req.field_mappings,
+        created_at: time(),
+        last_delivery_at: None,
+        last_status: None,
+    };
+    HOOKS.with(|h| h.borrow_mut().insert(hook.id.clone(), hook.clone()));
+
+    ic_cdk::println!("🪝 Created integration hook {} for {}", hook.id, caller.to_text());
+    Ok(hook)
+}
+
+pub fn list_hooks(caller: Principal) -> Vec<IntegrationHook> {
+    hooks_of(caller)
+}
+
+pub fn delete_hook(caller: Principal, id: String) -> Result<(), String> {
+    HOOKS.with(|h| {
+        let mut map = h.borrow_mut();
+        match map.get(&id) {
+            Some(hook) if hook.owner == caller => {
+                map.remove(&id);
+                Ok(())
+            }
+            _ => Err(error_codes::coded(ErrorCode::NotFound, "Hook not found")),
+        }
+    })
+}
+
+/// The JSON a hook would receive for one of the caller's bookings
+pub fn preview_payload(caller: Principal, id: String, booking_id: String) -> Result<String, String> {
+    let hook = hooks_of(caller).into_iter().find(|h| h.id == id).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Hook not found"))?;
+    let booking = bookings::get_booking(caller, booking_id)?;
+    Ok(payload(&hook, HookEvent::BookingCreated, &booking))
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    HOOKS.with(|h| principal_moves::count_owned_in(&h.borrow(), |hook| &mut hook.owner, owner))
+}
+
+/// Hand `from`'s hooks to `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    HOOKS.with(|h| principal_moves::move_owned_in(&mut h.borrow_mut(), |hook| &mut hook.owner, from, to))
+}
@@ -0,0 +1,431 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, CollaboratorRole};
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::{entropy, principal_moves, quarantine};
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_ROUNDS: usize = 5;
+const MAX_POOL_SIZE: usize = 10;
+const MAX_OPEN_CANDIDATES_PER_PIPELINE: usize = 500;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One stage of an interview loop, booked with any interviewer from the pool
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct InterviewRound {
+    pub name: String,
+    pub pool: Vec<String>, // Interviewers' availability IDs
+    pub duration_minutes: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct InterviewPipeline {
+    pub id: String,
+    pub owner: Principal,
+    pub title: String,
+    pub rounds: Vec<InterviewRound>,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreatePipelineRequest {
+    pub title: String,
+    pub rounds: Vec<InterviewRound>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CandidateStage {
+    AwaitingBooking, // The current round's slots are on offer
+    Booked,          // The current round is booked and hasn't ended yet
+    Completed,       // Every round took place
+    Withdrawn,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RoundBooking {
+    pub round: u32,
+    pub availability_id: String,
+    pub booking_id: String,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// A candidate's progress through a pipeline. The token is their private
+/// scheduling link.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Candidate {
+    pub token: String,
+    pub pipeline_id: String,
+    pub owner: Principal,
+    pub name: String,
+    pub email: Option<String>,
+    pub round: u32, // Index into the pipeline's rounds
+    pub stage: CandidateStage,
+    pub history: Vec<RoundBooking>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// A bookable interview slot for the candidate's current round
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct RoundSlot {
+    pub availability_id: String,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct BookRoundRequest {
+    pub token: String,
+    pub availability_id: String,
+    pub start_time: u64, // Unix seconds; the round's duration sets the end
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl InterviewPipeline {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and without rounds.
+    fn unreadable() -> Self {
+        InterviewPipeline {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            title: String::new(),
+            rounds: vec![],
+            created_at: 0,
+        }
+    }
+}
+
+impl Storable for InterviewPipeline {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "InterviewPipeline").unwrap_or_else(InterviewPipeline::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Candidate {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Withdrawn and owned by nobody, so no round is offered.
+    fn unreadable() -> Self {
+        Candidate {
+            token: String::new(),
+            pipeline_id: String::new(),
+            owner: Principal::anonymous(),
+            name: String::new(),
+            email: None,
+            round: 0,
+            stage: CandidateStage::Withdrawn,
+            history: vec![],
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for Candidate {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Candidate").unwrap_or_else(Candidate::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static PIPELINES: RefCell<StableBTreeMap<String, InterviewPipeline, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(33)))
+        )
+    );
+
+    // Candidate token -> pipeline state
+    static CANDIDATES: RefCell<StableBTreeMap<String, Candidate, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(34)))
+        )
+    );
+}
+
+pub fn clear() {
+    PIPELINES.with(|m| m.borrow_mut().clear_new());
+    CANDIDATES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn generate_pipeline_id() -> Result<String, String> {
+    Ok(format!("ivp-{}", entropy::next_token(6)?))
+}
+
+fn get_pipeline(id: &str) -> Result<InterviewPipeline, String> {
+    PIPELINES.with(|p| p.borrow().get(&id.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Pipeline not found"))
+}
+
+fn owned_pipeline(caller: Principal, id: &str) -> Result<InterviewPipeline, String> {
+    let pipeline = get_pipeline(id)?;
+    if pipeline.owner != caller {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Pipeline not found"));
+    }
+    Ok(pipeline)
+}
+
+fn save(candidate: &Candidate) {
+    CANDIDATES.with(|c| c.borrow_mut().insert(candidate.token.clone(), candidate.clone()));
+}
+
+/// Pools may only use availabilities the pipeline owner owns or approves bookings for
+fn validate_rounds(caller: Principal, rounds: &[InterviewRound]) -> Result<(), String> {
+    if rounds.is_empty() || rounds.len() > MAX_ROUNDS {
+        return Err(format!("a pipeline needs 1-{} rounds", MAX_ROUNDS));
+    }
+    for round in rounds {
+        if round.name.trim().is_empty() || round.name.len() > 100 {
+            return Err("round names must be 1-100 characters".to_string());
+        }
+        if round.pool.is_empty() || round.pool.len() > MAX_POOL_SIZE {
+            return Err(format!("each round needs 1-{} interviewer availabilities", MAX_POOL_SIZE));
+        }
+        for id in &round.pool {
+            let availability = availabilities::get_availability(id.clone())?;
+            if !availabilities::has_role(&availability, caller, CollaboratorRole::Approver) {
+                return Err(error_codes::coded(ErrorCode::Unauthorized, format!("Not an owner or approver of availability {}", id)));
+            }
+            availabilities::duration_option(&availability, round.duration_minutes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring a candidate's stage up to date: a booked round that has ended moves
+/// them to the next round (or completes the pipeline), a cancelled one puts
+/// the round back on offer
+fn advance(candidate: &mut Candidate, pipeline: &InterviewPipeline) {
+    if candidate.stage != CandidateStage::Booked {
+        return;
+    }
+    let Some(last) = candidate.history.last() else {
+        return;
+    };
+    if !bookings::is_live(&last.booking_id) {
+        candidate.stage = CandidateStage::AwaitingBooking;
+    } else if last.end_time <= time() / 1_000_000_000 {
+        candidate.round += 1;
+        candidate.stage = if candidate.round as usize >= pipeline.rounds.len() {
+            CandidateStage::Completed
+        } else {
+            CandidateStage::AwaitingBooking
+        };
+    }
+}
+
+fn load_candidate(token: &str) -> Result<(Candidate, InterviewPipeline), String> {
+    let mut candidate = CANDIDATES.with(|c| c.borrow().get(&token.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Candidate not found"))?;
+    let pipeline = get_pipeline(&candidate.pipeline_id)?;
+    advance(&mut candidate, &pipeline);
+    Ok((candidate, pipeline))
+}
+
+// ============================================================================
+// Owner Operations
+// ============================================================================
+
+pub fn create_interview_pipeline(caller: Principal, req: CreatePipelineRequest) -> Result<InterviewPipeline, String> {
+    if req.title.trim().is_empty() || req.title.len() > 100 {
+        return Err("title must be 1-100 characters".to_string());
+    }
+    validate_rounds(caller, &req.rounds)?;
+
+    let pipeline = InterviewPipeline {
+        id: generate_pipeline_id()?,
+        owner: caller,
+        title: req.title.trim().to_string(),
+        rounds: req.rounds,
+        created_at: time(),
+    };
+    PIPELINES.with(|p| p.borrow_mut().insert(pipeline.id.clone(), pipeline.clone()));
+
+    ic_cdk::println!("🧑‍💻 Created interview pipeline {} ({} rounds)", pipeline.id, pipeline.rounds.len());
+    Ok(pipeline)
+}
+
+pub fn list_interview_pipelines(caller: Principal) -> Vec<InterviewPipeline> {
+    PIPELINES.with(|p| {
+        p.borrow()
+            .iter()
+            .filter(|(_, pipeline)| pipeline.owner == caller)
+            .map(|(_, pipeline)| pipeline)
+            .collect()
+    })
+}
+
+/// Enter a candidate into Round 1; share the returned token with them
+pub fn add_candidate(caller: Principal, pipeline_id: String, name: String, email: Option<String>) -> Result<Candidate, String> {
+    let pipeline = owned_pipeline(caller, &pipeline_id)?;
+    if name.trim().is_empty() || name.len() > 100 {
+        return Err("name must be 1-100 characters".to_string());
+    }
+    let open = list_candidates(caller, pipeline_id.clone())?
+        .iter()
+        .filter(|c| matches!(c.stage, CandidateStage::AwaitingBooking | CandidateStage::Booked))
+        .count();
+    if open >= MAX_OPEN_CANDIDATES_PER_PIPELINE {
+        return Err(error_codes::coded(ErrorCode::QuotaExceeded, format!("at most {} candidates in progress per pipeline", MAX_OPEN_CANDIDATES_PER_PIPELINE)));
+    }
+
+    let now = time();
+    let candidate = Candidate {
+        token: entropy::next_token(16)?,
+        pipeline_id: pipeline.id,
+        owner: caller,
+        name: name.trim().to_string(),
+        email,
+        round: 0,
+        stage: CandidateStage::AwaitingBooking,
+        history: vec![],
+        created_at: now,
+        updated_at: now,
+    };
+    save(&candidate);
+    Ok(candidate)
+}
+
+/// Candidates of one pipeline with their current stage
+pub fn list_candidates(caller: Principal, pipeline_id: String) -> Result<Vec<Candidate>, String> {
+    let pipeline = owned_pipeline(caller, &pipeline_id)?;
+    Ok(CANDIDATES.with(|c| {
+        c.borrow()
+            .iter()
+            .filter(|(_, candidate)| candidate.pipeline_id == pipeline_id)
+            .map(|(_, mut candidate)| {
+                advance(&mut candidate, &pipeline);
+                candidate
+            })
+            .collect()
+    }))
+}
+
+/// Take a candidate out of the pipeline; booked interviews are left for the
+/// interviewers to cancel
+pub fn withdraw_candidate(caller: Principal, token: String) -> Result<Candidate, String> {
+    let (mut candidate, _) = load_candidate(&token)?;
+    if candidate.owner != caller {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Candidate not found"));
+    }
+    candidate.stage = CandidateStage::Withdrawn;
+    candidate.updated_at = time();
+    save(&candidate);
+    Ok(candidate)
+}
+
+// ============================================================================
+// Candidate Operations
+// ============================================================================
+
+pub fn get_candidate_status(token: String) -> Result<Candidate, String> {
+    load_candidate(&token).map(|(candidate, _)| candidate)
+}
+
+/// Slots for the candidate's current round across the round's interviewer pool
+pub fn get_round_slots(token: String, range_start: u64, range_end: u64) -> Result<Vec<RoundSlot>, String> {
+    let (candidate, pipeline) = load_candidate(&token)?;
+    if candidate.stage != CandidateStage::AwaitingBooking {
+        return Err(format!("Nothing to book: {:?}", candidate.stage));
+    }
+    let round = &pipeline.rounds[candidate.round as usize];
+
+    let mut slots = Vec::new();
+    for id in &round.pool {
+        let free = availabilities::get_free_slots(id.clone(), range_start, range_end, Some(round.duration_minutes))?;
+        slots.extend(free.into_iter().map(|s| RoundSlot {
+            availability_id: id.clone(),
+            start_time: s.start_time,
+            end_time: s.end_time,
+        }));
+    }
+    slots.sort_by_key(|s| s.start_time);
+    Ok(slots)
+}
+
+/// Book the current round with one interviewer from its pool
+pub fn book_interview_round(caller: Principal, req: BookRoundRequest) -> Result<Booking, String> {
+    let (mut candidate, pipeline) = load_candidate(&req.token)?;
+    if candidate.stage != CandidateStage::AwaitingBooking {
+        return Err(format!("Nothing to book: {:?}", candidate.stage));
+    }
+    let round = &pipeline.rounds[candidate.round as usize];
+    if !round.pool.contains(&req.availability_id) {
+        return Err(format!("That interviewer isn't part of {}", round.name));
+    }
+
+    let end_time = req.start_time + round.duration_minutes as u64 * 60;
+    let booking = bookings::create_booking(caller, CreateBookingRequest {
+        availability_id: req.availability_id.clone(),
+        start_time: req.start_time,
+        end_time,
+        guest_name: candidate.name.clone(),
+        guest_email: candidate.email.clone(),
+        guest_timezone: None,
+        field_answers: None,
+    })?;
+
+    candidate.history.push(RoundBooking {
+        round: candidate.round,
+        availability_id: req.availability_id,
+        booking_id: booking.id.clone(),
+        start_time: req.start_time,
+        end_time,
+    });
+    candidate.stage = CandidateStage::Booked;
+    candidate.updated_at = time();
+    save(&candidate);
+
+    ic_cdk::println!("🧑‍💻 Candidate {} booked {} of {}", candidate.name, round.name, pipeline.id);
+    Ok(booking)
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Pipelines and candidates of the owner
+pub fn count_owned(owner: Principal) -> u32 {
+    PIPELINES.with(|p| principal_moves::count_owned_in(&p.borrow(), |pipeline| &mut pipeline.owner, owner))
+        + CANDIDATES.with(|c| principal_moves::count_owned_in(&c.borrow(), |candidate| &mut candidate.owner, owner))
+}
+
+/// Hand `from`'s pipelines and candidates to `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    PIPELINES.with(|p| principal_moves::move_owned_in(&mut p.borrow_mut(), |pipeline| &mut pipeline.owner, from, to))
+        + CANDIDATES.with(|c| principal_moves::move_owned_in(&mut c.borrow_mut(), |candidate| &mut candidate.owner, from, to))
+}
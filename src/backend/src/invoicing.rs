@@ -0,0 +1,283 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::bookings::{self, Booking};
+use crate::{availabilities, principal_moves, quarantine};
+use crate::i18n::{self, Message};
+use crate::templates::{self, RenderedTemplate, TemplateKind};
+
+/// Tax rates are stored in basis points (2000 = 20%)
+const MAX_TAX_RATE_BPS: u32 = 5_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Seller details and tax settings an owner applies to their paid bookings
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TaxProfile {
+    pub business_name: String,
+    pub business_address: String,
+    pub country_code: String,          // ISO 3166-1 alpha-2, e.g. "DE"
+    pub vat_id: Option<String>,
+    pub tax_rate_bps: u32,
+    pub prices_include_tax: bool,      // false = tax is added on top of the price
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct SetTaxProfileRequest {
+    pub business_name: String,
+    pub business_address: String,
+    pub country_code: String,
+    pub vat_id: Option<String>,
+    pub tax_rate_bps: u32,
+    pub prices_include_tax: bool,
+}
+
+/// Tax split recorded on a booking payment at the time it was charged
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TaxBreakdown {
+    pub rate_bps: u32,
+    pub net_amount: u64,
+    pub tax_amount: u64,
+    pub vat_id: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct Receipt {
+    pub booking_id: String,
+    pub issued_at: u64,
+    pub seller: Option<TaxProfile>,
+    pub buyer_name: String,
+    pub buyer_email: Option<String>,
+    pub description: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub ledger_canister: Principal,
+    pub block_index: Option<u64>,
+    pub net_amount: u64,
+    pub tax_amount: u64,
+    pub tax_rate_bps: u32,
+    pub total_amount: u64,
+    pub message: Option<RenderedTemplate>, // The owner's Receipt template, if they set one
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for TaxProfile {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TaxProfile").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static TAX_PROFILES: RefCell<StableBTreeMap<Principal, TaxProfile, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18)))
+        )
+    );
+}
+
+pub fn clear() {
+    TAX_PROFILES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn validate_profile(req: &SetTaxProfileRequest) -> Result<(), String> {
+    if req.business_name.trim().is_empty() || req.business_name.len() > 200 {
+        return Err("business_name must be 1-200 characters".to_string());
+    }
+    if req.business_address.len() > 500 {
+        return Err("business_address must be 0-500 characters".to_string());
+    }
+    if req.country_code.len() != 2 || !req.country_code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err("country_code must be a 2-letter ISO code".to_string());
+    }
+    if let Some(ref vat_id) = req.vat_id {
+        if vat_id.len() < 4 || vat_id.len() > 20 || !vat_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err("vat_id must be 4-20 letters and digits".to_string());
+        }
+    }
+    if req.tax_rate_bps > MAX_TAX_RATE_BPS {
+        return Err(format!("tax_rate_bps must be 0-{}", MAX_TAX_RATE_BPS));
+    }
+    Ok(())
+}
+
+/// Quote a field for CSV if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// ============================================================================
+// Tax Profiles
+// ============================================================================
+
+pub fn get_tax_profile(owner: Principal) -> Option<TaxProfile> {
+    TAX_PROFILES.with(|t| t.borrow().get(&owner))
+}
+
+pub fn set_tax_profile(caller: Principal, req: SetTaxProfileRequest) -> Result<TaxProfile, String> {
+    validate_profile(&req)?;
+
+    let profile = TaxProfile {
+        business_name: req.business_name.trim().to_string(),
+        business_address: req.business_address.trim().to_string(),
+        country_code: req.country_code.to_uppercase(),
+        vat_id: req.vat_id,
+        tax_rate_bps: req.tax_rate_bps,
+        prices_include_tax: req.prices_include_tax,
+        updated_at: time(),
+    };
+    TAX_PROFILES.with(|t| t.borrow_mut().insert(caller, profile.clone()));
+
+    ic_cdk::println!("🧾 Updated tax profile for {}", caller.to_text());
+    Ok(profile)
+}
+
+pub fn clear_tax_profile(caller: Principal) {
+    TAX_PROFILES.with(|t| t.borrow_mut().remove(&caller));
+}
+
+pub fn count_owned(owner: Principal) -> u32 {
+    TAX_PROFILES.with(|t| principal_moves::count_keyed(&t.borrow(), owner))
+}
+
+/// Carry `from`'s profile over to `to` unless `to` has one of its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    TAX_PROFILES.with(|t| principal_moves::move_keyed(&mut t.borrow_mut(), from, to))
+}
+
+/// Apply the owner's tax settings to a price, returning what the guest is
+/// charged and the split to record. Owners without a profile charge no tax.
+pub fn apply_tax(owner: Principal, price: u64) -> (u64, Option<TaxBreakdown>) {
+    let Some(profile) = get_tax_profile(owner) else {
+        return (price, None);
+    };
+    let rate = profile.tax_rate_bps as u64;
+
+    let (total, net) = if profile.prices_include_tax {
+        (price, price * 10_000 / (10_000 + rate))
+    } else {
+        (price + price * rate / 10_000, price)
+    };
+
+    (total, Some(TaxBreakdown {
+        rate_bps: profile.tax_rate_bps,
+        net_amount: net,
+        tax_amount: total - net,
+        vat_id: profile.vat_id,
+    }))
+}
+
+// ============================================================================
+// Receipts & Exports
+// ============================================================================
+
+/// Receipt for a paid booking, available to its owner and guest
+pub fn get_receipt(caller: Principal, booking_id: String) -> Result<Receipt, String> {
+    let booking = bookings::get_booking(caller, booking_id)?;
+    receipt_for(&booking).ok_or_else(|| "Booking has no payment".to_string())
+}
+
+fn receipt_for(booking: &Booking) -> Option<Receipt> {
+    let payment = booking.payment.as_ref()?;
+    let (net_amount, tax_amount, tax_rate_bps) = match payment.tax {
+        Some(ref tax) => (tax.net_amount, tax.tax_amount, tax.rate_bps),
+        None => (payment.amount, 0, 0),
+    };
+
+    let availability = availabilities::get_availability(booking.availability_id.clone()).ok();
+    let locale = i18n::resolve(availability.as_ref().and_then(|a| a.locale), booking.owner);
+    let timezone = bookings::guest_timezone(booking);
+
+    Some(Receipt {
+        booking_id: booking.id.clone(),
+        issued_at: payment.paid_at,
+        seller: get_tax_profile(booking.owner),
+        buyer_name: booking.guest_name.clone(),
+        buyer_email: booking.guest_email.clone(),
+        description: format!(
+            "{} {}",
+            i18n::t(locale, Message::BookingOn),
+            i18n::format_datetime(locale, booking.start_time, &timezone, i18n::wants_hijri(booking.owner))
+        ),
+        start_time: booking.start_time,
+        end_time: booking.end_time,
+        ledger_canister: payment.ledger_canister,
+        block_index: payment.block_index,
+        net_amount,
+        tax_amount,
+        tax_rate_bps,
+        total_amount: payment.amount,
+        message: templates::render(booking, TemplateKind::Receipt),
+    })
+}
+
+/// CSV of the owner's paid bookings for bookkeeping
+pub fn export_bookings_csv(owner: Principal) -> String {
+    let mut csv = String::from(
+        "booking_id,paid_at,start_time,end_time,status,guest_name,guest_email,net_amount,tax_rate_bps,tax_amount,total_amount,refunded_amount,vat_id,ledger_canister,block_index\n",
+    );
+
+    for booking in bookings::list_owner_bookings(owner) {
+        let (Some(receipt), Some(payment)) = (receipt_for(&booking), booking.payment.as_ref()) else {
+            continue;
+        };
+        let refunded = booking.refund.as_ref()
+            .filter(|r| r.error.is_none())
+            .map(|r| r.amount)
+            .unwrap_or(0);
+        let vat_id = payment.tax.as_ref().and_then(|t| t.vat_id.clone()).unwrap_or_default();
+
+        let row = [
+            csv_field(&booking.id),
+            receipt.issued_at.to_string(),
+            booking.start_time.to_string(),
+            booking.end_time.to_string(),
+            format!("{:?}", booking.status),
+            csv_field(&booking.guest_name),
+            csv_field(booking.guest_email.as_deref().unwrap_or("")),
+            receipt.net_amount.to_string(),
+            receipt.tax_rate_bps.to_string(),
+            receipt.tax_amount.to_string(),
+            receipt.total_amount.to_string(),
+            refunded.to_string(),
+            csv_field(&vat_id),
+            payment.ledger_canister.to_text(),
+            payment.block_index.map(|b| b.to_string()).unwrap_or_default(),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
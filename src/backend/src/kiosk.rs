@@ -0,0 +1,92 @@
+use candid::Principal;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::clock::time;
+use crate::availabilities::{self, Availability, DurationOption};
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::error_codes::{self, ErrorCode};
+
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+/// How far ahead a walk-up booking may land (seconds)
+const SEARCH_HORIZON_SECS: u64 = 12 * 60 * 60;
+
+/// Walk-up starts are rounded up to this granularity (seconds)
+const START_STEP_SECS: u64 = 5 * 60;
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // (device, availability ID) -> (window start in ns, bookings in window).
+    // Heap-only like the quota windows: an upgrade simply resets the counters.
+    static DEVICE_WINDOWS: RefCell<HashMap<(Principal, String), (u64, u32)>> = RefCell::new(HashMap::new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn consume_device_rate(device: Principal, availability_id: &str, limit: u32) -> Result<(), String> {
+    let now = time();
+    DEVICE_WINDOWS.with(|w| {
+        let mut windows = w.borrow_mut();
+        let entry = windows.entry((device, availability_id.to_string())).or_insert((now, 0));
+        if now - entry.0 >= HOUR_NS {
+            *entry = (now, 0);
+        }
+        if entry.1 >= limit {
+            return Err(error_codes::coded(ErrorCode::QuotaExceeded, format!(
+                "this device may book at most {} walk-up meetings per hour",
+                limit
+            )));
+        }
+        entry.1 += 1;
+        Ok(())
+    })
+}
+
+/// First start at or after `earliest` where the kiosk duration fits a free window
+fn next_start(availability: &Availability, option: &DurationOption, earliest: u64) -> Option<u64> {
+    let length = option.minutes as u64 * 60;
+
+    availabilities::free_windows(availability, earliest, earliest + SEARCH_HORIZON_SECS)
+        .into_iter()
+        .flat_map(|w| {
+            let first = w.start_time.max(earliest).div_ceil(START_STEP_SECS) * START_STEP_SECS;
+            (first..w.end_time.saturating_sub(length).saturating_add(1)).step_by(START_STEP_SECS as usize)
+        })
+        .find(|&start| availabilities::buffers_clear(availability, option, start, start + length))
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// Book the next free slot starting at least `lead_minutes` from now,
+/// on behalf of a walk-up guest at a registered shared device
+pub fn book_next_available(device: Principal, id: String, guest_name: String) -> Result<Booking, String> {
+    let availability = availabilities::get_availability(id)?;
+    let config = availability.kiosk.clone().ok_or("Walk-up booking is not enabled")?;
+    if !config.devices.contains(&device) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not a registered kiosk device"));
+    }
+
+    let option = availabilities::duration_option(&availability, config.duration_minutes)?;
+    consume_device_rate(device, &availability.id, config.max_bookings_per_device_per_hour)?;
+
+    let earliest = time() / 1_000_000_000 + config.lead_minutes as u64 * 60;
+    let start = next_start(&availability, &option, earliest)
+        .ok_or("No free slot in the next 12 hours")?;
+
+    bookings::create_booking(device, CreateBookingRequest {
+        availability_id: availability.id.clone(),
+        start_time: start,
+        end_time: start + config.duration_minutes as u64 * 60,
+        guest_name,
+        guest_email: None,
+        guest_timezone: None,
+        field_answers: None,
+    })
+}
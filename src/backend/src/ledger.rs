@@ -0,0 +1,146 @@
+use candid::{Nat, Principal};
+use ic_cdk::api::call::RejectionCode;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{Memo, TransferArg, TransferError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Why a transfer didn't report a block
+#[derive(Debug, PartialEq)]
+pub enum TransferFailure {
+    Rejected(String), // The ledger refused it; nothing moved
+    Unknown(String),  // It may have executed; repeat it with the same arguments to find out
+}
+
+impl TransferFailure {
+    pub fn message(self) -> String {
+        match self {
+            TransferFailure::Rejected(message) | TransferFailure::Unknown(message) => message,
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Only a refusal for funds or fee proves the transfer didn't happen; a
+/// Duplicate is the ledger recognising an earlier attempt that did
+fn transfer_outcome(
+    result: Result<(Result<Nat, TransferError>,), (RejectionCode, String)>,
+) -> Result<u64, TransferFailure> {
+    match result {
+        Ok((Ok(block),)) | Ok((Err(TransferError::Duplicate { duplicate_of: block }),)) => {
+            Ok(u64::try_from(block.0).unwrap_or(u64::MAX))
+        }
+        Ok((Err(e @ (TransferError::InsufficientFunds { .. } | TransferError::BadFee { .. })),)) => {
+            Err(TransferFailure::Rejected(format!("Transfer failed: {}", e)))
+        }
+        Ok((Err(e),)) => Err(TransferFailure::Unknown(format!("Transfer failed: {}", e))),
+        Err((code, msg)) => Err(TransferFailure::Unknown(format!("Ledger call failed: {:?} - {}", code, msg))),
+    }
+}
+
+// ============================================================================
+// ICRC Ledger Calls
+// ============================================================================
+
+/// Pull `amount` from `from` into the canister's default account using an
+/// ICRC-2 allowance the user granted with icrc2_approve.
+/// Returns the ledger block index on success.
+pub async fn transfer_from(ledger: Principal, from: Principal, amount: u64, memo: u64) -> Result<u64, String> {
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account { owner: from, subaccount: None },
+        to: Account { owner: ic_cdk::id(), subaccount: None },
+        amount: Nat::from(amount),
+        fee: None,
+        memo: Some(Memo::from(memo)),
+        created_at_time: Some(crate::clock::time()),
+    };
+
+    let result: Result<(Result<Nat, TransferFromError>,), _> =
+        ic_cdk::call(ledger, "icrc2_transfer_from", (args,)).await;
+
+    match result {
+        Ok((Ok(block),)) => Ok(u64::try_from(block.0).unwrap_or(u64::MAX)),
+        Ok((Err(e),)) => {
+            ic_cdk::println!("❌ [ledger] transfer_from {} failed: {}", from.to_text(), e);
+            Err(format!("Transfer failed: {}", e))
+        }
+        Err((code, msg)) => {
+            ic_cdk::println!("❌ [ledger] Ledger call failed: {:?} - {}", code, msg);
+            Err(format!("Ledger call failed: {:?} - {}", code, msg))
+        }
+    }
+}
+
+/// Send `amount` from the canister's default account (refunds).
+/// The ledger fee is paid on top of `amount` by the canister.
+pub async fn transfer(ledger: Principal, to: Account, amount: u64, memo: u64) -> Result<u64, String> {
+    transfer_at(ledger, to, amount, memo, crate::clock::time()).await.map_err(TransferFailure::message)
+}
+
+/// Send `amount` as of `created_at_time` (payouts). Repeating the call with
+/// the same arguments inside the ledger's deduplication window (24 hours)
+/// can't pay twice: it returns the block of the transfer that went through.
+pub async fn transfer_at(
+    ledger: Principal,
+    to: Account,
+    amount: u64,
+    memo: u64,
+    created_at_time: u64,
+) -> Result<u64, TransferFailure> {
+    let args = TransferArg {
+        from_subaccount: None,
+        to,
+        amount: Nat::from(amount),
+        fee: None,
+        memo: Some(Memo::from(memo)),
+        created_at_time: Some(created_at_time),
+    };
+
+    let result: Result<(Result<Nat, TransferError>,), _> =
+        ic_cdk::call(ledger, "icrc1_transfer", (args,)).await;
+    let outcome = transfer_outcome(result);
+    if let Err(e) = &outcome {
+        ic_cdk::println!("❌ [ledger] transfer to {} failed: {:?}", to.owner.to_text(), e);
+    }
+    outcome
+}
+
+/// Current transfer fee of an ICRC-1 ledger
+pub async fn fee(ledger: Principal) -> Result<u64, String> {
+    let result: Result<(Nat,), _> = ic_cdk::call(ledger, "icrc1_fee", ()).await;
+    result
+        .map(|(fee,)| u64::try_from(fee.0).unwrap_or(u64::MAX))
+        .map_err(|(code, msg)| format!("Ledger call failed: {:?} - {}", code, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(result: Result<u64, TransferError>) -> Result<(Result<Nat, TransferError>,), (RejectionCode, String)> {
+        Ok((result.map(Nat::from),))
+    }
+
+    #[test]
+    fn only_funds_and_fee_refusals_are_definite() {
+        assert_eq!(transfer_outcome(answer(Ok(7))), Ok(7));
+        assert_eq!(transfer_outcome(answer(Err(TransferError::Duplicate { duplicate_of: Nat::from(7u64) }))), Ok(7));
+        let rejected = |e| matches!(transfer_outcome(answer(Err(e))), Err(TransferFailure::Rejected(_)));
+        assert!(rejected(TransferError::InsufficientFunds { balance: Nat::from(0u64) }));
+        assert!(rejected(TransferError::BadFee { expected_fee: Nat::from(10_000u64) }));
+        assert!(!rejected(TransferError::TemporarilyUnavailable));
+        assert!(!rejected(TransferError::TooOld));
+        assert!(!rejected(TransferError::GenericError { error_code: Nat::from(1u64), message: "busy".to_string() }));
+        assert!(matches!(
+            transfer_outcome(Err((RejectionCode::SysTransient, "timeout".to_string()))),
+            Err(TransferFailure::Unknown(_))
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+//! Legal holds on an org member's bookings. While a hold is in place the
+//! member's bookings can't be deleted and trashed ones are not purged; only
+//! an admin of the member's org can lift it. Holds are never edited or
+//! removed, lifting one just records who lifted it and when.
+//!
+//! Every deletion path checks `require_no_hold` before removing a booking
+//! for good: `bookings::delete_booking` refuses, and the trash purge keeps
+//! held entries past their retention until the hold is lifted.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::tenants;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+const MAX_REASON_LEN: usize = 500;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct LegalHold {
+    pub member: Principal,
+    pub tenant_id: String,
+    pub reason: String,
+    pub placed_by: Principal,
+    pub placed_at: u64,
+    pub lifted_by: Option<Principal>,
+    pub lifted_at: Option<u64>,       // None = still in force
+}
+
+/// Every hold ever placed on a member, oldest first
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct HoldHistory(Vec<LegalHold>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for HoldHistory {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "HoldHistory").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Member -> holds placed on them
+    static LEGAL_HOLDS: RefCell<StableBTreeMap<Principal, HoldHistory, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(65)))
+        )
+    );
+}
+
+pub fn clear() {
+    LEGAL_HOLDS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+pub fn is_held(member: Principal) -> bool {
+    LEGAL_HOLDS.with(|h| h.borrow().get(&member))
+        .is_some_and(|history| history.0.iter().any(|hold| hold.lifted_at.is_none()))
+}
+
+/// Call before deleting any of the member's bookings for good
+pub fn require_no_hold(member: Principal) -> Result<(), String> {
+    if is_held(member) {
+        return Err("These bookings are under a legal hold and can't be deleted until an org admin lifts it".to_string());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Org Admin Endpoints
+// ============================================================================
+
+fn require_member_admin(caller: Principal, tenant_id: &str, member: Principal) -> Result<(), String> {
+    tenants::require_tenant_admin(caller, tenant_id)?;
+    if tenants::tenant_of(member) != tenant_id {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Not a member of this org"));
+    }
+    Ok(())
+}
+
+pub fn place_hold(caller: Principal, tenant_id: String, member: Principal, reason: String) -> Result<LegalHold, String> {
+    require_member_admin(caller, &tenant_id, member)?;
+    if reason.trim().is_empty() || reason.len() > MAX_REASON_LEN {
+        return Err(format!("reason must be 1-{} characters", MAX_REASON_LEN));
+    }
+    if is_held(member) {
+        return Err("Member is already under a legal hold".to_string());
+    }
+
+    let hold = LegalHold {
+        member,
+        tenant_id,
+        reason,
+        placed_by: caller,
+        placed_at: time(),
+        lifted_by: None,
+        lifted_at: None,
+    };
+    LEGAL_HOLDS.with(|h| {
+        let mut map = h.borrow_mut();
+        let mut history = map.get(&member).unwrap_or_default();
+        history.0.push(hold.clone());
+        map.insert(member, history);
+    });
+    ic_cdk::println!("⚖️ Legal hold placed on {} by {}", member, caller);
+    Ok(hold)
+}
+
+pub fn lift_hold(caller: Principal, tenant_id: String, member: Principal) -> Result<LegalHold, String> {
+    require_member_admin(caller, &tenant_id, member)?;
+    LEGAL_HOLDS.with(|h| {
+        let mut map = h.borrow_mut();
+        let mut history = map.get(&member).unwrap_or_default();
+        let hold = history.0.iter_mut()
+            .find(|hold| hold.lifted_at.is_none())
+            .ok_or("Member is not under a legal hold")?;
+        hold.lifted_by = Some(caller);
+        hold.lifted_at = Some(time());
+        let lifted = hold.clone();
+        map.insert(member, history);
+        ic_cdk::println!("⚖️ Legal hold on {} lifted by {}", member, caller);
+        Ok(lifted)
+    })
+}
+
+/// Every hold placed on the org's members, current and lifted
+pub fn list_holds(caller: Principal, tenant_id: String) -> Result<Vec<LegalHold>, String> {
+    tenants::require_tenant_admin(caller, &tenant_id)?;
+    Ok(LEGAL_HOLDS.with(|h| {
+        h.borrow()
+            .iter()
+            .flat_map(|(_, history)| history.0)
+            .filter(|hold| hold.tenant_id == tenant_id)
+            .collect()
+    }))
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Holds ever placed on the member
+pub fn count_owned(member: Principal) -> u32 {
+    LEGAL_HOLDS.with(|h| h.borrow().get(&member)).map_or(0, |history| history.0.len() as u32)
+}
+
+/// Holds follow the member to `to` (the same user under a new principal),
+/// merged with any placed on `to`, so a move can't escape a hold
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    LEGAL_HOLDS.with(|h| {
+        let mut map = h.borrow_mut();
+        let Some(history) = map.remove(&from) else {
+            return 0;
+        };
+        let moved = history.0.len() as u32;
+        let mut merged = map.get(&to).unwrap_or_default();
+        merged.0.extend(history.0.into_iter().map(|hold| LegalHold { member: to, ..hold }));
+        merged.0.sort_by_key(|hold| hold.placed_at);
+        map.insert(to, merged);
+        moved
+    })
+}
@@ -1,20 +1,173 @@
-use ic_cdk_macros::{query, update, init};
-use candid::{CandidType, Principal, Decode, Encode};
+use ic_cdk_macros::{query, update, init, pre_upgrade, post_upgrade};
+use candid::{CandidType, Principal, Encode};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use ic_stable_structures::{
     memory_manager::MemoryId,
     storable::Bound,
-    StableBTreeMap, Storable,
+    Storable,
 };
 use std::borrow::Cow;
 
 mod memory;
 mod availabilities;
+mod quotas;
+mod plans;
+mod ledger;
+mod billing;
+mod promos;
+mod bookings;
+mod booking_events;
+mod session_links;
+mod earnings;
+mod invoicing;
+mod origins;
+mod tenants;
+mod i18n;
+mod scheduling;
+mod datetime;
+mod one_time_links;
+mod link_stats;
+mod qr;
+mod http;
+mod api_keys;
+mod calendly;
+mod ical;
+mod kiosk;
+mod resources;
+mod integrations;
+mod org_busy;
+mod delegations;
+mod org_load;
+mod interviews;
+mod teams;
+mod verified_emails;
+mod snapshots;
+mod booking_schema;
+mod quick;
+mod discord;
+mod matrix;
+mod outbox;
+mod circuit_breaker;
+mod outcall_cache;
+mod storage;
+mod quarantine;
+mod upgrade_check;
+mod api_v2;
+mod migration;
+mod maintenance;
+mod tokens;
+mod api_usage;
+mod sync_scheduler;
+mod webhook_auth;
+mod stripe;
+mod templates;
+mod delivery_log;
+mod experiments;
+mod response_size;
+mod cursor;
+mod search;
+mod trash;
+mod legal_hold;
+mod terms;
+mod data_export;
+mod regions;
+mod analytics;
+mod counters;
+mod index_rebuild;
+mod calendar_access;
+mod calendar_conflicts;
+mod check_ins;
+mod followups;
+mod rebook;
+mod availability_shares;
+mod org_defaults;
+mod service_status;
+mod self_test;
+mod trace;
+mod error_codes;
+mod session_expiry;
+mod principal_moves;
+mod read_cache;
+mod clock;
+mod entropy;
+mod watched_map;
+#[cfg(feature = "dev-fixtures")]
+mod fixtures;
 use availabilities::*;
 pub use availabilities::BusyTimeBlock;
-use memory::{Memory, MEMORY_MANAGER};
+use quotas::{QuotaKind, QuotaLimits, QuotaStatus};
+use plans::{PlanInfo, PlanSource, PlanTier};
+use billing::{BillingConfig, Subscription};
+use discord::DiscordConfig;
+use stripe::{ParkedStripeEvent, StripeConfig};
+use promos::{BookingDiscount, PromoCode, PromoReward, ReferralStats};
+use bookings::{Attendee, Booking, BookingPage, CancellationQuote, CreateBookingRequest};
+use booking_events::BookingEventRecord;
+use session_links::{CreateSessionLinkRequest, DurationPrice, RedeemSessionLinkRequest, SessionLink, StripePrice};
+use earnings::{EarningsBalance, JournalEntry};
+use invoicing::{Receipt, SetTaxProfileRequest, TaxProfile};
+use origins::{OriginConfig, SetOriginConfigRequest};
+use tenants::{Tenant, TenantMember};
+use i18n::Locale;
+use link_stats::LinkStats;
+use integrations::{CreateIntegrationHookRequest, IntegrationHook};
+use matrix::{MatrixNotifier, SetMatrixNotifierRequest};
+use outbox::{FailedJobsPage, OutboxJob};
+use circuit_breaker::{BreakerStatus, Provider};
+use outcall_cache::CachedEndpoint;
+use storage::StorageReport;
+use quarantine::{CorruptRecordsPage, SweepReport};
+use upgrade_check::UpgradeVerification;
+use watched_map::WatchedMap;
+use api_v2::{ApiError, ApiVersionInfo};
+use migration::MigrationStatus;
+use maintenance::{MaintenanceJob, MaintenanceTask};
+use tokens::OrphanedTokenReport;
+use api_usage::{AccountUsage, ApiUsageDay};
+use sync_scheduler::SyncSchedulerStatus;
+use templates::{NotificationTemplate, TemplateKind, TemplateScope};
+use delivery_log::{DeliveryRecord, NotificationChannel};
+use experiments::{SlotExperiment, StartExperimentRequest};
+use search::SearchResult;
+use trash::TrashEntry;
+use legal_hold::LegalHold;
+use terms::{TermsAcceptance, TermsConfig, TermsStatus};
+use data_export::MyDataExport;
+use regions::{RegionDeclaration, RegionPolicy, SetRegionPolicyRequest};
+use analytics::{AnalyticsConfig, EventCount, EventProp};
+use counters::{CounterScope, CounterSet, PlatformStats};
+use index_rebuild::RebuildStatus;
+use calendar_access::CalendarConnection;
+use calendar_conflicts::{CalendarConflict, ConflictResolution};
+use check_ins::{AttendanceEntry, NoShowStats};
+use followups::FollowUp;
+use rebook::RebookInfo;
+use availability_shares::AvailabilityShare;
+use org_defaults::{OrgDefaults, SetOrgDefaultsRequest};
+use service_status::ServiceStatus;
+use self_test::SelfTestReport;
+use error_codes::{ErrorCatalogEntry, ErrorCode};
+use principal_moves::PrincipalMove;
+use read_cache::ReadCacheStatus;
+#[cfg(feature = "dev-fixtures")]
+use fixtures::SeedReport;
+use resources::{GroupBookingRequest, Resource, ResourceRequest};
+use org_busy::{OrgBusyBlock, OrgBusyBlockRequest};
+use org_load::OrgLoadReport;
+use snapshots::FreeSlotSnapshot;
+use booking_schema::BookingSchema;
+use teams::{Team, TeamBookingRequest, TeamRequest};
+use interviews::{BookRoundRequest, Candidate, CreatePipelineRequest, InterviewPipeline, RoundSlot};
+use delegations::{DelegateAction, DelegateAuditPage, DelegationScope, ManagementDelegation};
+use http::{HttpRequest, HttpResponse};
+use calendly::CalendlyImportReport;
+use api_keys::{ApiKey, CreateApiKeyRequest, CreatedApiKey};
+use one_time_links::{BookWithLinkRequest, OneTimeLink};
+use scheduling::{CommonFreeSlot, MeetingSuggestion, SuggestionConstraints};
+use icrc_ledger_types::icrc1::account::Account;
+use memory::MEMORY_MANAGER;
 
 // ============================================================================
 // Constants
@@ -46,7 +199,7 @@ pub struct OAuthProvider {
     pub response_type: String,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Default)]
 struct SessionData {
     user_id: String,
     email: Option<String>,
@@ -110,7 +263,7 @@ pub struct ExchangeCodeRequest {
     pub redirect_uri: String,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Default)]
 pub struct TokenResponse {
     pub access_token: String,
     pub refresh_token: Option<String>,
@@ -128,12 +281,24 @@ pub struct RefreshTokenRequest {
 // ============================================================================
 
 impl Storable for TokenResponse {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TokenResponse").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SessionData {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
 
-    fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "SessionData").unwrap_or_default()
     }
 
     const BOUND: Bound = Bound::Unbounded;
@@ -147,10 +312,11 @@ thread_local! {
     static PROVIDERS: RefCell<HashMap<String, OAuthProvider>> = RefCell::new(HashMap::new());
     static SESSIONS: RefCell<HashMap<Vec<u8>, SessionData>> = RefCell::new(HashMap::new());
     
-    // Store encrypted tokens per user (user_id -> TokenResponse) - STABLE STORAGE
-    pub static USER_TOKENS: RefCell<StableBTreeMap<String, TokenResponse, Memory>> = RefCell::new(
-        StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2)))
+    // Legacy tokens keyed by principal text, drained into tokens.rs by TokenRekey
+    pub static USER_TOKENS: RefCell<WatchedMap<String, TokenResponse>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))),
+            |key, old, new| upgrade_check::track("USER_TOKENS", key, old, new),
         )
     );
 }
@@ -173,6 +339,33 @@ fn init() {
             response_type: "code id_token".to_string(),
         });
     });
+    
+    start_timers();
+}
+
+#[pre_upgrade]
+fn pre_upgrade() {
+    upgrade_check::take_snapshot();
+}
+
+#[post_upgrade]
+fn post_upgrade() {
+    counters::seed();
+    start_timers();
+}
+
+/// Timers are not persisted across upgrades, so register them from init and post_upgrade
+fn start_timers() {
+    billing::start_timer();
+    outbox::start_timer();
+    maintenance::start_timer();
+    sync_scheduler::start_timer();
+    cursor::start_timer();
+    trash::start_timer();
+    followups::start_timer();
+    read_cache::start_timer();
+    entropy::start_timer();
+    upgrade_check::start_timer();
 }
 
 // ============================================================================
@@ -253,27 +446,38 @@ fn get_providers() -> Vec<OAuthProvider> {
 
 #[update]
 async fn prepare_delegation(req: PrepareDelegationRequest) -> Result<PrepareDelegationResponse, String> {
-    // 1. Verify provider exists
-    let _provider = PROVIDERS.with(|p| {
-        p.borrow().get(&req.provider).cloned()
-    }).ok_or("Provider not found")?;
-    
-    // 2. Verify JWT token and extract user ID, email, and name
-    let (user_id, email, name) = verify_jwt_token(&req.id_token)?;
-    
-    ic_cdk::println!("📧 [prepare_delegation] JWT extracted - user_id={}, email={:?}, name={:?}", user_id, email, name);
-    
-    // 3. Calculate expiration
-    let now = ic_cdk::api::time();
-    let expire_at = now + req.max_time_to_live;
-    
-    // Derive the principal for this user
-    let user_principal = derive_user_principal(&user_id, &req.origin);
-    ic_cdk::println!("🔑 [prepare_delegation] Derived principal: {:?}", user_principal);
+    trace::run_async(async move {
+        // 1. Verify provider exists
+        let _provider = PROVIDERS.with(|p| {
+            p.borrow().get(&req.provider).cloned()
+        }).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Provider not found"))?;
+    
+        // 2. Verify JWT token and extract user ID, email, and name
+        let (user_id, email, name) = verify_jwt_token(&req.id_token)?;
+    
+        ic_cdk::println!("📧 [prepare_delegation] JWT extracted - user_id={}, email={:?}, name={:?}", user_id, email, name);
+    
+        // 3. Calculate expiration (capped and on a whole second, see session_expiry.rs)
+        let now = clock::time();
+        let expire_at = session_expiry::canonical_expiry(now, req.max_time_to_live);
+    
+        // Derive the principal for this user (white-label origins may alias another origin)
+        let derivation = origins::derivation_origin(&req.origin);
+        let user_principal = derive_user_principal(&user_id, &derivation);
+        ic_cdk::println!("🔑 [prepare_delegation] Derived principal: {:?}", user_principal);
+        // Records left under the principals this user had on retired origins come along
+        let previous = origins::retired_origins_of(&derivation)
+            .iter()
+            .map(|retired| derive_user_principal(&user_id, retired))
+            .collect();
+        principal_moves::on_sign_in(user_principal, previous);
+        tenants::assign(user_principal, &origins::tenant_for(&req.origin));
+        if let Some(ref email) = email {
+            verified_emails::record(user_principal, email);
+        }
     
-    // 4. Store session
-    SESSIONS.with(|s| {
-        s.borrow_mut().insert(req.session_public_key.clone(), SessionData {
+        // 4. Store session
+        migration::insert_session(req.session_public_key.clone(), SessionData {
             user_id: user_id.clone(),
             email: email.clone(),
             name: name.clone(),
@@ -281,49 +485,47 @@ async fn prepare_delegation(req: PrepareDelegationRequest) -> Result<PrepareDele
             expires_at: expire_at,
             targets: req.targets.clone(),
         });
-    });
     
-    ic_cdk::println!("✅ [prepare_delegation] Session stored for principal {:?}", user_principal);
+        ic_cdk::println!("✅ [prepare_delegation] Session stored for principal {:?}", user_principal);
     
-    Ok(PrepareDelegationResponse { expire_at })
+        Ok(PrepareDelegationResponse { expire_at })
+    }).await
 }
 
 #[query]
 fn get_delegation(req: GetDelegationRequest) -> Result<GetDelegationResponse, String> {
-    // 1. Retrieve session
-    let session = SESSIONS.with(|s| {
-        s.borrow().get(&req.session_public_key).cloned()
-    }).ok_or("Session not found")?;
-    
-    // 2. Verify expiration matches
-    if session.expires_at != req.expire_at {
-        return Err("Invalid expiration time".to_string());
-    }
+    error_codes::run(move || {
+        // 1. Retrieve session
+        let session = migration::get_session(&req.session_public_key).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Session not found"))?;
     
-    // 3. Verify origin matches
-    if session.origin != req.origin {
-        return Err("Invalid origin".to_string());
-    }
+        // 2. Verify expiration matches (clients may echo it back slightly off) and hasn't passed
+        session_expiry::check(session.expires_at, req.expire_at)?;
     
-    // 4. Create delegation
-    let delegation = Delegation {
-        pubkey: req.session_public_key.clone(),
-        expiration: req.expire_at,
-        targets: req.targets,
-    };
+        // 3. Verify origin matches
+        if session.origin != req.origin {
+            return Err("Invalid origin".to_string());
+        }
+    
+        // 4. Create delegation
+        let delegation = Delegation {
+            pubkey: req.session_public_key.clone(),
+            expiration: session.expires_at,
+            targets: req.targets,
+        };
     
-    // 5. Sign delegation
-    let signature = sign_delegation(&delegation)?;
+        // 5. Sign delegation
+        let signature = sign_delegation(&delegation)?;
     
-    // 6. Derive user principal from user_id + origin
-    let user_pubkey = derive_user_pubkey(&session.user_id, &session.origin);
+        // 6. Derive user principal from user_id + origin
+        let user_pubkey = derive_user_pubkey(&session.user_id, &origins::derivation_origin(&session.origin));
     
-    Ok(GetDelegationResponse {
-        signed_delegation: SignedDelegation {
-            delegation,
-            signature,
-        },
-        user_canister_pubkey: user_pubkey,
+        Ok(GetDelegationResponse {
+            signed_delegation: SignedDelegation {
+                delegation,
+                signature,
+            },
+            user_canister_pubkey: user_pubkey,
+        })
     })
 }
 
@@ -335,7 +537,7 @@ fn get_delegation(req: GetDelegationRequest) -> Result<GetDelegationResponse, St
 /// Should be called periodically to prevent memory leaks
 #[update]
 fn cleanup_expired_sessions() -> u64 {
-    let now = ic_cdk::api::time();
+    let now = clock::time();
     let mut removed_count = 0;
     
     SESSIONS.with(|s| {
@@ -348,6 +550,7 @@ fn cleanup_expired_sessions() -> u64 {
             is_valid
         });
     });
+    migration::remove_expired_sessions(now);
     
     ic_cdk::println!("🧹 Cleaned up {} expired sessions", removed_count);
     removed_count
@@ -363,13 +566,12 @@ fn get_session_count() -> u64 {
 /// Note: This requires the session public key to identify the session
 #[update]
 fn logout(session_public_key: Vec<u8>) -> Result<(), String> {
-    SESSIONS.with(|s| {
-        let mut sessions = s.borrow_mut();
-        if sessions.remove(&session_public_key).is_some() {
+    trace::run(move || {
+        if migration::remove_session(&session_public_key) {
             ic_cdk::println!("👋 User logged out successfully");
             Ok(())
         } else {
-            Err("Session not found".to_string())
+            Err(error_codes::coded(ErrorCode::NotFound, "Session not found"))
         }
     })
 }
@@ -382,163 +584,193 @@ fn logout(session_public_key: Vec<u8>) -> Result<(), String> {
 /// This is done securely on the backend to protect the client secret
 #[update]
 async fn exchange_oauth_code(req: ExchangeCodeRequest) -> Result<TokenResponse, String> {
-    ic_cdk::println!("🔄 [Backend] Exchanging OAuth code for tokens...");
-    
-    // Build request body
-    let mut params = vec![
-        ("code", req.code.as_str()),
-        ("client_id", GOOGLE_CLIENT_ID),
-        ("client_secret", GOOGLE_CLIENT_SECRET),
-        ("redirect_uri", req.redirect_uri.as_str()),
-        ("grant_type", "authorization_code"),
-    ];
-    
-    // Add PKCE verifier if provided
-    if !req.code_verifier.is_empty() {
-        params.push(("code_verifier", req.code_verifier.as_str()));
-    }
-    
-    // Make HTTP outcall to Google's token endpoint
-    let body = params.iter()
-        .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
-        .collect::<Vec<_>>()
-        .join("&");
-    
-    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
-        url: "https://oauth2.googleapis.com/token".to_string(),
-        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
-        body: Some(body.into_bytes()),
-        max_response_bytes: Some(4096),
-        transform: None,
-        headers: vec![
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Content-Type".to_string(),
-                value: "application/x-www-form-urlencoded".to_string(),
-            },
-        ],
-    };
+    trace::run_async(async move {
+        ic_cdk::println!("🔄 [Backend] Exchanging OAuth code for tokens...");
+        quotas::consume_rate(ic_cdk::caller(), QuotaKind::OutcallsPerHour)?;
+    
+        // Build request body
+        let mut params = vec![
+            ("code", req.code.as_str()),
+            ("client_id", GOOGLE_CLIENT_ID),
+            ("client_secret", GOOGLE_CLIENT_SECRET),
+            ("redirect_uri", req.redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+        ];
+    
+        // Add PKCE verifier if provided
+        if !req.code_verifier.is_empty() {
+            params.push(("code_verifier", req.code_verifier.as_str()));
+        }
     
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
-        Ok((response,)) => {
-            if response.status != candid::Nat::from(200u8) {
-                let error_body = String::from_utf8_lossy(&response.body);
-                ic_cdk::println!("❌ [Backend] Token exchange failed: {}", error_body);
-                return Err(format!("Token exchange failed: {}", error_body));
-            }
+        // Make HTTP outcall to Google's token endpoint
+        let body = params.iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+    
+        let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+            url: "https://oauth2.googleapis.com/token".to_string(),
+            method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+            body: Some(body.into_bytes()),
+            max_response_bytes: Some(4096),
+            transform: None,
+            headers: vec![
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: "application/x-www-form-urlencoded".to_string(),
+                },
+            ],
+        };
+    
+        circuit_breaker::check(Provider::Google)?;
+        match api_usage::google_request(ic_cdk::caller(), request, 25_000_000_000).await {
+            Ok((response,)) => {
+                if response.status != candid::Nat::from(200u8) {
+                    let error_body = String::from_utf8_lossy(&response.body);
+                    ic_cdk::println!("❌ [Backend] Token exchange failed: {}", error_body);
+                    return Err(format!("Token exchange failed: {}", error_body));
+                }
             
-            let token_response: TokenResponse = serde_json::from_slice(&response.body)
-                .map_err(|e| format!("Failed to parse token response: {}", e))?;
+                let token_response: TokenResponse = serde_json::from_slice(&response.body)
+                    .map_err(|e| format!("Failed to parse token response: {}", e))?;
             
-            ic_cdk::println!("✅ [Backend] Token exchange successful!");
+                ic_cdk::println!("✅ [Backend] Token exchange successful!");
             
-            // Store tokens for this user
-            // We need to extract the user_id from the id_token to get the OAuth principal
-            // For now, store using caller (frontend principal)
-            let caller = ic_cdk::caller().to_text();
+                // Store tokens for this user
+                // We need to extract the user_id from the id_token to get the OAuth principal
+                // For now, store using caller (frontend principal)
+                let caller = ic_cdk::caller().to_text();
             
-            // Also try to find the OAuth principal from active sessions
-            // by matching the user who just authenticated
-            let oauth_principals: Vec<String> = SESSIONS.with(|s| {
-                s.borrow()
-                    .iter()
-                    .map(|(_, session)| {
-                        derive_user_principal(&session.user_id, &session.origin).to_text()
-                    })
-                    .collect()
-            });
+                // Also try to find the OAuth principal from active sessions
+                // by matching the user who just authenticated
+                let oauth_principals: Vec<Principal> = SESSIONS.with(|s| {
+                    s.borrow()
+                        .iter()
+                        .map(|(_, session)| {
+                            derive_user_principal(&session.user_id, &session.origin)
+                        })
+                        .collect()
+                });
             
-            ic_cdk::println!("💾 [Backend] Storing tokens for caller: {}", caller);
-            ic_cdk::println!("💾 [Backend] Also storing for {} OAuth principals", oauth_principals.len());
+                ic_cdk::println!("💾 [Backend] Storing tokens for caller: {}", caller);
+                ic_cdk::println!("💾 [Backend] Also linking {} OAuth principals", oauth_principals.len());
             
-            USER_TOKENS.with(|t| {
-                let mut tokens = t.borrow_mut();
-                // Store for caller
-                tokens.insert(caller.clone(), token_response.clone());
-                // Store for all OAuth principals (in case one of them is the owner)
+                // Store once for the caller's account
+                tokens::store(ic_cdk::caller(), token_response.clone());
+                calendar_access::clear_revocation(ic_cdk::caller());
+                // Link all OAuth principals to it (in case one of them is the owner)
                 for principal in oauth_principals {
-                    ic_cdk::println!("💾 [Backend] Storing token for OAuth principal: {}", principal);
-                    tokens.insert(principal, token_response.clone());
+                    ic_cdk::println!("💾 [Backend] Linking OAuth principal: {}", principal);
+                    tokens::link(principal, ic_cdk::caller());
                 }
-            });
             
-            Ok(token_response)
-        }
-        Err((code, msg)) => {
-            ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
-            Err(format!("HTTP request failed: {:?} - {}", code, msg))
+                Ok(token_response)
+            }
+            Err((code, msg)) => {
+                ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
+                Err(format!("HTTP request failed: {:?} - {}", code, msg))
+            }
         }
-    }
+    }).await
 }
 
 /// Refresh access token using refresh token
 #[update]
 async fn refresh_google_token(req: RefreshTokenRequest) -> Result<TokenResponse, String> {
-    ic_cdk::println!("🔄 [Backend] Refreshing access token...");
-    
-    let params = vec![
-        ("refresh_token", req.refresh_token.as_str()),
-        ("client_id", GOOGLE_CLIENT_ID),
-        ("client_secret", GOOGLE_CLIENT_SECRET),
-        ("grant_type", "refresh_token"),
-    ];
-    
-    let body = params.iter()
-        .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
-        .collect::<Vec<_>>()
-        .join("&");
-    
-    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
-        url: "https://oauth2.googleapis.com/token".to_string(),
-        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
-        body: Some(body.into_bytes()),
-        max_response_bytes: Some(4096),
-        transform: None,
-        headers: vec![
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Content-Type".to_string(),
-                value: "application/x-www-form-urlencoded".to_string(),
-            },
-        ],
-    };
-    
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
-        Ok((response,)) => {
-            if response.status != candid::Nat::from(200u8) {
-                let error_body = String::from_utf8_lossy(&response.body);
-                return Err(format!("Token refresh failed: {}", error_body));
-            }
+    trace::run_async(async move {
+        ic_cdk::println!("🔄 [Backend] Refreshing access token...");
+        quotas::consume_rate(ic_cdk::caller(), QuotaKind::OutcallsPerHour)?;
+    
+        let params = vec![
+            ("refresh_token", req.refresh_token.as_str()),
+            ("client_id", GOOGLE_CLIENT_ID),
+            ("client_secret", GOOGLE_CLIENT_SECRET),
+            ("grant_type", "refresh_token"),
+        ];
+    
+        let body = params.iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+    
+        let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+            url: "https://oauth2.googleapis.com/token".to_string(),
+            method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+            body: Some(body.into_bytes()),
+            max_response_bytes: Some(4096),
+            transform: None,
+            headers: vec![
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: "application/x-www-form-urlencoded".to_string(),
+                },
+            ],
+        };
+    
+        circuit_breaker::check(Provider::Google)?;
+        match api_usage::google_request(ic_cdk::caller(), request, 25_000_000_000).await {
+            Ok((response,)) => {
+                if response.status != candid::Nat::from(200u8) {
+                    // The user removed our access in their Google account; retrying won't help
+                    if calendar_access::is_invalid_grant(&response.body) {
+                        calendar_access::mark_revoked(ic_cdk::caller());
+                        return Err(error_codes::coded(ErrorCode::GoogleAccessRevoked, "Google access was revoked, reconnect your Google account"));
+                    }
+                    let error_body = String::from_utf8_lossy(&response.body);
+                    return Err(format!("Token refresh failed: {}", error_body));
+                }
             
-            let token_response: TokenResponse = serde_json::from_slice(&response.body)
-                .map_err(|e| format!("Failed to parse token response: {}", e))?;
+                let token_response: TokenResponse = serde_json::from_slice(&response.body)
+                    .map_err(|e| format!("Failed to parse token response: {}", e))?;
             
-            ic_cdk::println!("✅ [Backend] Token refresh successful!");
+                ic_cdk::println!("✅ [Backend] Token refresh successful!");
             
-            // Update stored tokens for all principals
-            let caller = ic_cdk::caller().to_text();
+                // Update stored tokens for all principals
+                let caller = ic_cdk::caller();
             
-            let oauth_principals: Vec<String> = SESSIONS.with(|s| {
-                s.borrow()
-                    .iter()
-                    .map(|(_, session)| {
-                        derive_user_principal(&session.user_id, &session.origin).to_text()
-                    })
-                    .collect()
-            });
+                let oauth_principals: Vec<Principal> = SESSIONS.with(|s| {
+                    s.borrow()
+                        .iter()
+                        .map(|(_, session)| {
+                            derive_user_principal(&session.user_id, &session.origin)
+                        })
+                        .collect()
+                });
             
-            USER_TOKENS.with(|t| {
-                let mut tokens = t.borrow_mut();
-                tokens.insert(caller, token_response.clone());
+                tokens::store(caller, token_response.clone());
                 for principal in oauth_principals {
-                    tokens.insert(principal, token_response.clone());
+                    tokens::link(principal, caller);
                 }
-            });
+                calendar_access::clear_revocation(caller);
             
-            Ok(token_response)
-        }
-        Err((code, msg)) => {
-            Err(format!("HTTP request failed: {:?} - {}", code, msg))
+                Ok(token_response)
+            }
+            Err((code, msg)) => {
+                Err(format!("HTTP request failed: {:?} - {}", code, msg))
+            }
         }
-    }
+    }).await
+}
+
+/// Whether the caller's Google account is connected, and when access was
+/// revoked if it was (their availabilities are paused until they reconnect)
+#[query]
+fn get_calendar_connection() -> CalendarConnection {
+    calendar_access::get_connection(ic_cdk::caller())
+}
+
+/// Bookings whose Google event was moved in Google, newest first (resolved ones included)
+#[query]
+fn list_calendar_conflicts() -> Vec<CalendarConflict> {
+    calendar_conflicts::list_conflicts(ic_cdk::caller())
+}
+
+/// Settle an open conflict by moving the booking to Google's time or the event back to the booking's
+#[update(guard = "terms_accepted")]
+async fn resolve_calendar_conflict(booking_id: String, resolution: ConflictResolution) -> Result<CalendarConflict, String> {
+    trace::run_async(async move {
+        calendar_conflicts::resolve(ic_cdk::caller(), booking_id, resolution).await
+    }).await
 }
 
 // ============================================================================
@@ -555,6 +787,7 @@ pub struct CreateEventRequest {
     pub location: Option<String>,
     pub attendees: Option<Vec<String>>, // Email addresses
     pub conference_data: Option<bool>,  // Add Google Meet
+    pub booking_id: Option<String>,     // Booking the event is for; moves in Google are then journaled
 }
 
 #[derive(CandidType, Deserialize)]
@@ -582,262 +815,287 @@ pub struct CalendarEvent {
 }
 
 /// Create a new calendar event
-#[update]
+#[update(guard = "terms_accepted")]
 async fn create_calendar_event(req: CreateEventRequest) -> Result<String, String> {
-    ic_cdk::println!("📅 [Backend] Creating calendar event: {}", req.summary);
-    
-    // Get user's access token
-    let caller = ic_cdk::caller().to_text();
-    let token = USER_TOKENS.with(|t| {
-        t.borrow().get(&caller).map(|tr| tr.access_token.clone())
-    }).ok_or("No access token found. Please login first.")?;
-    
-    // Build event JSON
-    let mut event_json = serde_json::json!({
-        "summary": req.summary,
-        "start": {
-            "dateTime": req.start_time,
-            "timeZone": req.timezone
-        },
-        "end": {
-            "dateTime": req.end_time,
-            "timeZone": req.timezone
-        }
-    });
+    trace::run_async(async move {
+        ic_cdk::println!("📅 [Backend] Creating calendar event: {}", req.summary);
+        quotas::consume_rate(ic_cdk::caller(), QuotaKind::OutcallsPerHour)?;
+    
+        // Get user's access token
+        let token = tokens::access_token(ic_cdk::caller())
+            .ok_or("No access token found. Please login first.")?;
+        api_usage::check_google(ic_cdk::caller())?;
+    
+        // Build event JSON
+        let mut event_json = serde_json::json!({
+            "summary": req.summary,
+            "start": {
+                "dateTime": req.start_time,
+                "timeZone": req.timezone
+            },
+            "end": {
+                "dateTime": req.end_time,
+                "timeZone": req.timezone
+            }
+        });
     
-    if let Some(desc) = req.description {
-        event_json["description"] = serde_json::json!(desc);
-    }
+        if let Some(desc) = req.description {
+            event_json["description"] = serde_json::json!(desc);
+        }
     
-    if let Some(loc) = req.location {
-        event_json["location"] = serde_json::json!(loc);
-    }
+        if let Some(loc) = req.location {
+            event_json["location"] = serde_json::json!(loc);
+        }
     
-    if let Some(attendees) = req.attendees {
-        let attendee_list: Vec<serde_json::Value> = attendees.iter()
-            .map(|email| serde_json::json!({"email": email}))
-            .collect();
-        event_json["attendees"] = serde_json::json!(attendee_list);
-    }
+        if let Some(attendees) = req.attendees {
+            let attendee_list: Vec<serde_json::Value> = attendees.iter()
+                .map(|email| serde_json::json!({"email": email}))
+                .collect();
+            event_json["attendees"] = serde_json::json!(attendee_list);
+        }
     
-    if req.conference_data.unwrap_or(false) {
-        event_json["conferenceData"] = serde_json::json!({
-            "createRequest": {
-                "requestId": format!("meet-{}", ic_cdk::api::time()),
-                "conferenceSolutionKey": {"type": "hangoutsMeet"}
+        if let Some(booking_id) = req.booking_id {
+            let booking = bookings::find(&booking_id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "Booking not found"))?;
+            if !bookings::manages(ic_cdk::caller(), &booking) {
+                return Err(error_codes::coded(ErrorCode::Unauthorized, "not your booking"));
             }
-        });
-    }
-    
-    let body = serde_json::to_string(&event_json)
-        .map_err(|e| format!("Failed to serialize event: {}", e))?;
+            // The availability's event fields ride along for the owner's Workspace automations
+            let mut private = serde_json::Map::new();
+            if let Ok(availability) = availabilities::get_availability(booking.availability_id.clone()) {
+                for (key, value) in availabilities::event_properties(&availability, booking.field_answers.as_deref().unwrap_or_default()) {
+                    private.insert(key, serde_json::json!(value));
+                }
+            }
+            private.insert(calendar_conflicts::BOOKING_ID_PROPERTY.to_string(), serde_json::json!(booking_id));
+            event_json["extendedProperties"] = serde_json::json!({ "private": private });
+        }
     
-    // Determine URL based on conference data
-    let url = if req.conference_data.unwrap_or(false) {
-        "https://www.googleapis.com/calendar/v3/calendars/primary/events?conferenceDataVersion=1"
-    } else {
-        "https://www.googleapis.com/calendar/v3/calendars/primary/events"
-    };
+        if req.conference_data.unwrap_or(false) {
+            event_json["conferenceData"] = serde_json::json!({
+                "createRequest": {
+                    "requestId": format!("meet-{}", clock::time()),
+                    "conferenceSolutionKey": {"type": "hangoutsMeet"}
+                }
+            });
+        }
     
-    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
-        url: url.to_string(),
-        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
-        body: Some(body.into_bytes()),
-        max_response_bytes: Some(8192),
-        transform: None,
-        headers: vec![
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Authorization".to_string(),
-                value: format!("Bearer {}", token),
-            },
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Content-Type".to_string(),
-                value: "application/json".to_string(),
-            },
-        ],
-    };
+        let body = serde_json::to_string(&event_json)
+            .map_err(|e| format!("Failed to serialize event: {}", e))?;
     
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
-        Ok((response,)) => {
-            if response.status != candid::Nat::from(200u8) {
-                let error_body = String::from_utf8_lossy(&response.body);
-                ic_cdk::println!("❌ [Backend] Create event failed: {}", error_body);
-                return Err(format!("Failed to create event: {}", error_body));
-            }
+        // Determine URL based on conference data
+        let url = if req.conference_data.unwrap_or(false) {
+            "https://www.googleapis.com/calendar/v3/calendars/primary/events?conferenceDataVersion=1"
+        } else {
+            "https://www.googleapis.com/calendar/v3/calendars/primary/events"
+        };
+    
+        let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+            url: url.to_string(),
+            method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+            body: Some(body.into_bytes()),
+            max_response_bytes: Some(8192),
+            transform: None,
+            headers: vec![
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Authorization".to_string(),
+                    value: format!("Bearer {}", token),
+                },
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: "application/json".to_string(),
+                },
+            ],
+        };
+    
+        circuit_breaker::check(Provider::Google)?;
+        match api_usage::google_request(ic_cdk::caller(), request, 25_000_000_000).await {
+            Ok((response,)) => {
+                if response.status != candid::Nat::from(200u8) {
+                    let error_body = String::from_utf8_lossy(&response.body);
+                    ic_cdk::println!("❌ [Backend] Create event failed: {}", error_body);
+                    return Err(format!("Failed to create event: {}", error_body));
+                }
             
-            let response_json: serde_json::Value = serde_json::from_slice(&response.body)
-                .map_err(|e| format!("Failed to parse response: {}", e))?;
+                let response_json: serde_json::Value = serde_json::from_slice(&response.body)
+                    .map_err(|e| format!("Failed to parse response: {}", e))?;
             
-            let event_id = response_json["id"].as_str()
-                .ok_or("No event ID in response")?
-                .to_string();
+                let event_id = response_json["id"].as_str()
+                    .ok_or("No event ID in response")?
+                    .to_string();
             
-            ic_cdk::println!("✅ [Backend] Event created: {}", event_id);
-            Ok(event_id)
-        }
-        Err((code, msg)) => {
-            ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
-            Err(format!("HTTP request failed: {:?} - {}", code, msg))
+                ic_cdk::println!("✅ [Backend] Event created: {}", event_id);
+                Ok(event_id)
+            }
+            Err((code, msg)) => {
+                ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
+                Err(format!("HTTP request failed: {:?} - {}", code, msg))
+            }
         }
-    }
+    }).await
 }
 
 /// Update an existing calendar event
-#[update]
+#[update(guard = "terms_accepted")]
 async fn update_calendar_event(req: UpdateEventRequest) -> Result<String, String> {
-    ic_cdk::println!("📝 [Backend] Updating calendar event: {}", req.event_id);
-    
-    // Get user's access token
-    let caller = ic_cdk::caller().to_text();
-    let token = USER_TOKENS.with(|t| {
-        t.borrow().get(&caller).map(|tr| tr.access_token.clone())
-    }).ok_or("No access token found. Please login first.")?;
-    
-    // Build update JSON (only include fields that are being updated)
-    let mut update_json = serde_json::json!({});
-    
-    if let Some(summary) = req.summary {
-        update_json["summary"] = serde_json::json!(summary);
-    }
+    trace::run_async(async move {
+        ic_cdk::println!("📝 [Backend] Updating calendar event: {}", req.event_id);
+        quotas::consume_rate(ic_cdk::caller(), QuotaKind::OutcallsPerHour)?;
     
-    if let Some(desc) = req.description {
-        update_json["description"] = serde_json::json!(desc);
-    }
+        // Get user's access token
+        let token = tokens::access_token(ic_cdk::caller())
+            .ok_or("No access token found. Please login first.")?;
+        api_usage::check_google(ic_cdk::caller())?;
     
-    if let Some(loc) = req.location {
-        update_json["location"] = serde_json::json!(loc);
-    }
+        // Build update JSON (only include fields that are being updated)
+        let mut update_json = serde_json::json!({});
     
-    if let Some(status) = req.status {
-        update_json["status"] = serde_json::json!(status);
-    }
+        if let Some(summary) = req.summary {
+            update_json["summary"] = serde_json::json!(summary);
+        }
     
-    if let Some(start_time) = req.start_time {
-        let timezone = req.timezone.clone().unwrap_or_else(|| "UTC".to_string());
-        update_json["start"] = serde_json::json!({
-            "dateTime": start_time,
-            "timeZone": timezone
-        });
-    }
+        if let Some(desc) = req.description {
+            update_json["description"] = serde_json::json!(desc);
+        }
     
-    if let Some(end_time) = req.end_time {
-        let timezone = req.timezone.unwrap_or_else(|| "UTC".to_string());
-        update_json["end"] = serde_json::json!({
-            "dateTime": end_time,
-            "timeZone": timezone
-        });
-    }
+        if let Some(loc) = req.location {
+            update_json["location"] = serde_json::json!(loc);
+        }
     
-    if let Some(attendees) = req.attendees {
-        let attendee_list: Vec<serde_json::Value> = attendees.iter()
-            .map(|email| serde_json::json!({"email": email}))
-            .collect();
-        update_json["attendees"] = serde_json::json!(attendee_list);
-    }
+        if let Some(status) = req.status {
+            update_json["status"] = serde_json::json!(status);
+        }
     
-    let body = serde_json::to_string(&update_json)
-        .map_err(|e| format!("Failed to serialize update: {}", e))?;
+        if let Some(start_time) = req.start_time {
+            let timezone = req.timezone.clone().unwrap_or_else(|| "UTC".to_string());
+            update_json["start"] = serde_json::json!({
+                "dateTime": start_time,
+                "timeZone": timezone
+            });
+        }
     
-    let url = format!(
-        "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
-        urlencoding::encode(&req.event_id)
-    );
+        if let Some(end_time) = req.end_time {
+            let timezone = req.timezone.unwrap_or_else(|| "UTC".to_string());
+            update_json["end"] = serde_json::json!({
+                "dateTime": end_time,
+                "timeZone": timezone
+            });
+        }
     
-    // IC HTTP outcall only supports GET, POST, HEAD
-    // Use POST with X-HTTP-Method-Override header for PATCH
-    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
-        url,
-        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
-        body: Some(body.into_bytes()),
-        max_response_bytes: Some(8192),
-        transform: None,
-        headers: vec![
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Authorization".to_string(),
-                value: format!("Bearer {}", token),
-            },
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Content-Type".to_string(),
-                value: "application/json".to_string(),
-            },
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "X-HTTP-Method-Override".to_string(),
-                value: "PATCH".to_string(),
-            },
-        ],
-    };
+        if let Some(attendees) = req.attendees {
+            let attendee_list: Vec<serde_json::Value> = attendees.iter()
+                .map(|email| serde_json::json!({"email": email}))
+                .collect();
+            update_json["attendees"] = serde_json::json!(attendee_list);
+        }
     
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
-        Ok((response,)) => {
-            if response.status != candid::Nat::from(200u8) {
-                let error_body = String::from_utf8_lossy(&response.body);
-                ic_cdk::println!("❌ [Backend] Update event failed: {}", error_body);
-                return Err(format!("Failed to update event: {}", error_body));
-            }
+        let body = serde_json::to_string(&update_json)
+            .map_err(|e| format!("Failed to serialize update: {}", e))?;
+    
+        let url = format!(
+            "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
+            urlencoding::encode(&req.event_id)
+        );
+    
+        // IC HTTP outcall only supports GET, POST, HEAD
+        // Use POST with X-HTTP-Method-Override header for PATCH
+        let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+            url,
+            method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+            body: Some(body.into_bytes()),
+            max_response_bytes: Some(8192),
+            transform: None,
+            headers: vec![
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Authorization".to_string(),
+                    value: format!("Bearer {}", token),
+                },
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: "application/json".to_string(),
+                },
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "X-HTTP-Method-Override".to_string(),
+                    value: "PATCH".to_string(),
+                },
+            ],
+        };
+    
+        circuit_breaker::check(Provider::Google)?;
+        match api_usage::google_request(ic_cdk::caller(), request, 25_000_000_000).await {
+            Ok((response,)) => {
+                if response.status != candid::Nat::from(200u8) {
+                    let error_body = String::from_utf8_lossy(&response.body);
+                    ic_cdk::println!("❌ [Backend] Update event failed: {}", error_body);
+                    return Err(format!("Failed to update event: {}", error_body));
+                }
             
-            ic_cdk::println!("✅ [Backend] Event updated: {}", req.event_id);
-            Ok(req.event_id)
-        }
-        Err((code, msg)) => {
-            ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
-            Err(format!("HTTP request failed: {:?} - {}", code, msg))
+                ic_cdk::println!("✅ [Backend] Event updated: {}", req.event_id);
+                Ok(req.event_id)
+            }
+            Err((code, msg)) => {
+                ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
+                Err(format!("HTTP request failed: {:?} - {}", code, msg))
+            }
         }
-    }
+    }).await
 }
 
 /// Delete a calendar event
-#[update]
+#[update(guard = "terms_accepted")]
 async fn delete_calendar_event(event_id: String) -> Result<(), String> {
-    ic_cdk::println!("🗑️ [Backend] Deleting calendar event: {}", event_id);
-    
-    // Get user's access token
-    let caller = ic_cdk::caller().to_text();
-    let token = USER_TOKENS.with(|t| {
-        t.borrow().get(&caller).map(|tr| tr.access_token.clone())
-    }).ok_or("No access token found. Please login first.")?;
-    
-    let url = format!(
-        "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
-        urlencoding::encode(&event_id)
-    );
-    
-    // IC HTTP outcall only supports GET, POST, HEAD
-    // Use POST with X-HTTP-Method-Override header for DELETE
-    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
-        url,
-        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
-        body: None,
-        max_response_bytes: Some(1024),
-        transform: None,
-        headers: vec![
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "Authorization".to_string(),
-                value: format!("Bearer {}", token),
-            },
-            ic_cdk::api::management_canister::http_request::HttpHeader {
-                name: "X-HTTP-Method-Override".to_string(),
-                value: "DELETE".to_string(),
-            },
-        ],
-    };
-    
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
-        Ok((response,)) => {
-            // DELETE returns 204 No Content on success
-            if response.status != candid::Nat::from(204u8) && response.status != candid::Nat::from(200u8) {
-                let error_body = String::from_utf8_lossy(&response.body);
-                ic_cdk::println!("❌ [Backend] Delete event failed: {}", error_body);
-                return Err(format!("Failed to delete event: {}", error_body));
-            }
+    trace::run_async(async move {
+        ic_cdk::println!("🗑️ [Backend] Deleting calendar event: {}", event_id);
+        quotas::consume_rate(ic_cdk::caller(), QuotaKind::OutcallsPerHour)?;
+    
+        // Get user's access token
+        let token = tokens::access_token(ic_cdk::caller())
+            .ok_or("No access token found. Please login first.")?;
+        api_usage::check_google(ic_cdk::caller())?;
+    
+        let url = format!(
+            "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
+            urlencoding::encode(&event_id)
+        );
+    
+        // IC HTTP outcall only supports GET, POST, HEAD
+        // Use POST with X-HTTP-Method-Override header for DELETE
+        let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+            url,
+            method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+            body: None,
+            max_response_bytes: Some(1024),
+            transform: None,
+            headers: vec![
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "Authorization".to_string(),
+                    value: format!("Bearer {}", token),
+                },
+                ic_cdk::api::management_canister::http_request::HttpHeader {
+                    name: "X-HTTP-Method-Override".to_string(),
+                    value: "DELETE".to_string(),
+                },
+            ],
+        };
+    
+        circuit_breaker::check(Provider::Google)?;
+        match api_usage::google_request(ic_cdk::caller(), request, 25_000_000_000).await {
+            Ok((response,)) => {
+                // DELETE returns 204 No Content on success
+                if response.status != candid::Nat::from(204u8) && response.status != candid::Nat::from(200u8) {
+                    let error_body = String::from_utf8_lossy(&response.body);
+                    ic_cdk::println!("❌ [Backend] Delete event failed: {}", error_body);
+                    return Err(format!("Failed to delete event: {}", error_body));
+                }
             
-            ic_cdk::println!("✅ [Backend] Event deleted: {}", event_id);
-            Ok(())
-        }
-        Err((code, msg)) => {
-            ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
-            Err(format!("HTTP request failed: {:?} - {}", code, msg))
+                ic_cdk::println!("✅ [Backend] Event deleted: {}", event_id);
+                Ok(())
+            }
+            Err((code, msg)) => {
+                ic_cdk::println!("❌ [Backend] HTTP request failed: {:?} - {}", code, msg);
+                Err(format!("HTTP request failed: {:?} - {}", code, msg))
+            }
         }
-    }
+    }).await
 }
 
 // ============================================================================
@@ -913,6 +1171,21 @@ fn sign_delegation(delegation: &Delegation) -> Result<Vec<u8>, String> {
     Ok(hash.to_vec())
 }
 
+/// Admin endpoints are restricted to canister controllers
+fn require_controller() -> Result<Principal, String> {
+    let caller = ic_cdk::caller();
+    if !ic_cdk::api::is_controller(&caller) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only canister controllers can call this"));
+    }
+    Ok(caller)
+}
+
+/// Guard for state-changing endpoints: signed-in users must have accepted
+/// the current terms (see terms.rs)
+fn terms_accepted() -> Result<(), String> {
+    terms::require_accepted(ic_cdk::caller())
+}
+
 /// Derive deterministic user public key from user ID and origin
 /// 
 /// This ensures the same user gets the same principal for the same origin,
@@ -946,30 +1219,31 @@ fn derive_user_principal(user_id: &str, origin: &str) -> Principal {
 // ============================================================================
 
 /// Helper function to fetch busy times for an availability
-async fn fetch_busy_times_for_availability(availability: &Availability) -> Result<Vec<BusyTimeBlock>, String> {
+pub(crate) async fn fetch_busy_times_for_availability(availability: &Availability) -> Result<Vec<BusyTimeBlock>, String> {
     ic_cdk::println!("🔍 [fetch_busy_times] Starting for owner: {}", availability.owner.to_text());
     
     // 1. Get owner's access token
     let owner_id = availability.owner.to_text();
-    let token = USER_TOKENS.with(|t| {
-        t.borrow().get(&owner_id).map(|tr| tr.access_token.clone())
-    }).ok_or_else(|| {
+    let token = tokens::access_token(availability.owner).ok_or_else(|| {
         ic_cdk::println!("❌ [fetch_busy_times] Owner not authenticated: {}", owner_id);
-        "Owner not authenticated".to_string()
+        error_codes::coded(ErrorCode::CalendarDisconnected, "Owner not authenticated")
     })?;
+    api_usage::check_google(availability.owner)?;
     
     ic_cdk::println!("✅ [fetch_busy_times] Found access token for owner");
     
     // 2. Calculate time range (next 90 days)
-    let now = ic_cdk::api::time() / 1_000_000_000; // Convert to seconds
+    // Minute-aligned so repeated lookups hit the outcall cache
+    let now = clock::time() / 1_000_000_000 / 60 * 60;
     let end_time = now + (90 * 24 * 60 * 60); // 90 days from now
     
     ic_cdk::println!("📅 [fetch_busy_times] Time range: {} to {}", now, end_time);
     
     // 3. Fetch events from Google Calendar
-    let events = fetch_calendar_events(&token, now, end_time).await?;
+    let events = fetch_calendar_events(availability.owner, &token, now, end_time).await?;
     
     ic_cdk::println!("📋 [fetch_busy_times] Fetched {} events from Google Calendar", events.len());
+    calendar_conflicts::detect(availability.owner, &events);
     
     // 4. Extract only start/end times
     let busy_times: Vec<BusyTimeBlock> = events
@@ -978,8 +1252,8 @@ async fn fetch_busy_times_for_availability(availability: &Availability) -> Resul
             let start_str = event.get("start")?.get("dateTime")?.as_str()?;
             let end_str = event.get("end")?.get("dateTime")?.as_str()?;
             
-            let start_time = parse_iso8601_to_timestamp(start_str)?;
-            let end_time = parse_iso8601_to_timestamp(end_str)?;
+            let start_time = datetime::parse_iso8601(start_str).ok()?;
+            let end_time = datetime::parse_iso8601(end_str).ok()?;
             
             Some(BusyTimeBlock {
                 start_time,
@@ -995,13 +1269,14 @@ async fn fetch_busy_times_for_availability(availability: &Availability) -> Resul
 
 /// Fetch calendar events from Google Calendar API
 async fn fetch_calendar_events(
+    owner: Principal,
     access_token: &str,
     time_min: u64,
     time_max: u64,
 ) -> Result<Vec<serde_json::Value>, String> {
     // Convert timestamps to ISO 8601 format
-    let time_min_iso = format_timestamp_to_iso8601(time_min);
-    let time_max_iso = format_timestamp_to_iso8601(time_max);
+    let time_min_iso = datetime::format_iso8601(time_min);
+    let time_max_iso = datetime::format_iso8601(time_max);
     
     let url = format!(
         "https://www.googleapis.com/calendar/v3/calendars/primary/events?timeMin={}&timeMax={}&singleEvents=true&orderBy=startTime&maxResults=250",
@@ -1023,7 +1298,8 @@ async fn fetch_calendar_events(
         ],
     };
     
-    match ic_cdk::api::management_canister::http_request::http_request(request, 25_000_000_000).await {
+    circuit_breaker::check(Provider::Google)?;
+    match outcall_cache::http_request(CachedEndpoint::GoogleCalendarEvents, owner, request, 25_000_000_000).await {
         Ok((response,)) => {
             if response.status != candid::Nat::from(200u8) {
                 let error_body = String::from_utf8_lossy(&response.body);
@@ -1046,194 +1322,2258 @@ async fn fetch_calendar_events(
     }
 }
 
-/// Parse ISO 8601 timestamp to Unix timestamp (seconds)
-fn parse_iso8601_to_timestamp(iso_str: &str) -> Option<u64> {
-    // Simple parser for ISO 8601 format: 2024-01-15T10:00:00Z or 2024-01-15T10:00:00-08:00
-    // Extract year, month, day, hour, minute, second
-    let parts: Vec<&str> = iso_str.split('T').collect();
-    if parts.len() != 2 {
-        return None;
-    }
-    
-    let date_parts: Vec<&str> = parts[0].split('-').collect();
-    if date_parts.len() != 3 {
-        return None;
-    }
-    
-    let year: i32 = date_parts[0].parse().ok()?;
-    let month: u32 = date_parts[1].parse().ok()?;
-    let day: u32 = date_parts[2].parse().ok()?;
+/// Move a Google event to the given times (Unix seconds) with the owner's token
+pub(crate) async fn patch_calendar_event_times(owner: Principal, event_id: &str, start: u64, end: u64) -> Result<(), String> {
+    quotas::consume_rate(owner, QuotaKind::OutcallsPerHour)?;
+    let token = tokens::access_token(owner).ok_or_else(|| error_codes::coded(ErrorCode::CalendarDisconnected, "Owner not authenticated"))?;
+    api_usage::check_google(owner)?;
     
-    // Parse time part (remove timezone info for simplicity)
-    let time_part = parts[1].split('+').next()?.split('-').next()?.split('Z').next()?;
-    let time_parts: Vec<&str> = time_part.split(':').collect();
-    if time_parts.len() < 2 {
-        return None;
-    }
-    
-    let hour: u32 = time_parts[0].parse().ok()?;
-    let minute: u32 = time_parts[1].parse().ok()?;
-    let second: u32 = if time_parts.len() > 2 {
-        time_parts[2].split('.').next()?.parse().ok()?
-    } else {
-        0
-    };
-    
-    // Calculate Unix timestamp (simplified - doesn't account for all edge cases)
-    // Days since Unix epoch (1970-01-01)
-    let days_since_epoch = days_from_civil(year, month, day);
-    let seconds_from_days = days_since_epoch * 86400;
-    let seconds_from_time = (hour * 3600 + minute * 60 + second) as i64;
+    let body = serde_json::json!({
+        "start": { "dateTime": datetime::format_iso8601(start), "timeZone": "UTC" },
+        "end": { "dateTime": datetime::format_iso8601(end), "timeZone": "UTC" }
+    }).to_string();
     
-    Some((seconds_from_days + seconds_from_time) as u64)
-}
-
-/// Calculate days since Unix epoch (1970-01-01)
-fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
-    let y = year as i64 - (if month <= 2 { 1 } else { 0 });
-    let era = (if y >= 0 { y } else { y - 399 }) / 400;
-    let yoe = (y - era * 400) as u32;
-    let month_adjusted = if month > 2 { month - 3 } else { month + 9 };
-    let doy = (153 * month_adjusted + 2) / 5 + day - 1;
-    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
-    era * 146097 + doe as i64 - 719468
-}
-
-/// Format Unix timestamp (seconds) to ISO 8601
-fn format_timestamp_to_iso8601(timestamp: u64) -> String {
-    // Convert to days and seconds
-    let days = (timestamp / 86400) as i64;
-    let seconds_in_day = timestamp % 86400;
-    
-    // Calculate date from days since epoch
-    let (year, month, day) = civil_from_days(days);
-    
-    // Calculate time
-    let hour = seconds_in_day / 3600;
-    let minute = (seconds_in_day % 3600) / 60;
-    let second = seconds_in_day % 60;
+    let request = ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument {
+        url: format!(
+            "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
+            urlencoding::encode(event_id)
+        ),
+        method: ic_cdk::api::management_canister::http_request::HttpMethod::POST,
+        body: Some(body.into_bytes()),
+        max_response_bytes: Some(8192),
+        transform: None,
+        headers: vec![
+            ic_cdk::api::management_canister::http_request::HttpHeader {
+                name: "Authorization".to_string(),
+                value: format!("Bearer {}", token),
+            },
+            ic_cdk::api::management_canister::http_request::HttpHeader {
+                name: "Content-Type".to_string(),
+                value: "application/json".to_string(),
+            },
+            ic_cdk::api::management_canister::http_request::HttpHeader {
+                name: "X-HTTP-Method-Override".to_string(),
+                value: "PATCH".to_string(),
+            },
+        ],
+    };
     
-    format!(
-        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
-        year, month, day, hour, minute, second
-    )
-}
-
-/// Calculate civil date from days since Unix epoch
-fn civil_from_days(days: i64) -> (i32, u32, u32) {
-    let z = days + 719468;
-    let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
-    let doe = (z - era * 146097) as u32;
-    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
-    let y = yoe as i64 + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let d = doy - (153 * mp + 2) / 5 + 1;
-    let m = if mp < 10 { mp + 3 } else { mp - 9 };
-    let year = (y + if m <= 2 { 1 } else { 0 }) as i32;
-    (year, m, d)
+    circuit_breaker::check(Provider::Google)?;
+    match api_usage::google_request(owner, request, 25_000_000_000).await {
+        Ok((response,)) if response.status == candid::Nat::from(200u8) => Ok(()),
+        Ok((response,)) => Err(format!("Failed to update event: {}", String::from_utf8_lossy(&response.body))),
+        Err((code, msg)) => Err(format!("HTTP request failed: {:?} - {}", code, msg)),
+    }
 }
 
-#[update]
+#[update(guard = "terms_accepted")]
 fn create_availability(req: CreateAvailabilityRequest) -> Result<Availability, String> {
-    let caller = ic_cdk::caller();
-    let result = availabilities::create_availability(caller, req)?;
-    
-    // Copy token from caller to the availability owner (they're the same user)
-    // This ensures the owner principal has the token for fetching busy times
-    let caller_str = caller.to_text();
-    let owner_str = result.owner.to_text();
-    
-    if caller_str != owner_str {
-        ic_cdk::println!("🔑 [create_availability] Copying token from {} to {}", caller_str, owner_str);
-        USER_TOKENS.with(|t| {
-            let tokens = t.borrow();
-            if let Some(token) = tokens.get(&caller_str) {
-                drop(tokens); // Release borrow before mutable borrow
-                t.borrow_mut().insert(owner_str, token);
-                ic_cdk::println!("✅ [create_availability] Token copied successfully");
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::check_count(caller, QuotaKind::Availabilities, availabilities::count_user_availabilities(caller))?;
+        let result = availabilities::create_availability(caller, req)?;
+    
+        // Link the availability owner to the caller's account (they're the same user)
+        // This ensures the owner principal has the token for fetching busy times
+        if caller != result.owner {
+            if tokens::get(caller).is_some() {
+                ic_cdk::println!("🔑 [create_availability] Linking {} to {}", result.owner, caller);
+                tokens::link(result.owner, caller);
             } else {
                 ic_cdk::println!("⚠️ [create_availability] No token found for caller");
             }
-        });
-    }
+        }
     
-    Ok(result)
+        Ok(result)
+    })
 }
 
 #[query]
-fn get_availability(id: String) -> Result<Availability, String> {
-    ic_cdk::println!("🔍 [get_availability] Called for ID: {}", id);
-    let availability = availabilities::get_availability(id)?;
+fn get_availability(id: String, origin: Option<String>) -> Result<Availability, String> {
+    error_codes::run(move || {
+        ic_cdk::println!("🔍 [get_availability] Called for ID: {}", id);
+        let caller = ic_cdk::caller();
+        let availability = availabilities::public_view(availabilities::get_visible_availability(caller, id, origin.as_deref())?, caller);
     
-    ic_cdk::println!("📋 [get_availability] Found availability, owner: {}", availability.owner.to_text());
-    ic_cdk::println!("🎯 [get_availability] Returning availability with busy_times: {:?}", 
-        availability.busy_times.as_ref().map(|bt| bt.len()));
+        ic_cdk::println!("📋 [get_availability] Found availability, owner: {}", availability.owner.to_text());
+        ic_cdk::println!("🎯 [get_availability] Returning availability with busy_times: {:?}", 
+            availability.busy_times.as_ref().map(|bt| bt.len()));
     
-    Ok(availability)
+        Ok(availability)
+    })
 }
 
-#[update]
+/// Read-cache canister that answers `get_availability` for signed-out
+/// viewers on `origin` with the same reply (None = read from the backend)
+#[query]
+fn get_read_cache(origin: Option<String>) -> Option<Principal> {
+    read_cache::get_canister(origin.as_deref())
+}
+
+#[update(guard = "terms_accepted")]
+fn set_read_cache(canister: Option<Principal>) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        read_cache::set_canister(canister)
+    })
+}
+
+#[query]
+fn get_read_cache_status() -> Result<ReadCacheStatus, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(read_cache::get_status())
+    })
+}
+
+#[update(guard = "terms_accepted")]
 fn update_availability(req: UpdateAvailabilityRequest) -> Result<Availability, String> {
-    let caller = ic_cdk::caller();
-    availabilities::update_availability(caller, req)
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::update_availability(caller, req)
+    })
 }
 
-#[update]
+#[update(guard = "terms_accepted")]
 fn update_availability_busy_times(id: String, busy_times: Vec<BusyTimeBlock>) -> Result<(), String> {
-    let caller = ic_cdk::caller();
-    availabilities::update_availability_busy_times(caller, id, busy_times)
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::update_availability_busy_times(caller, id, busy_times)
+    })
 }
 
-#[update]
+#[update(guard = "terms_accepted")]
+fn set_availability_busy_privacy(id: String, privacy: BusyPrivacy) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_availability_busy_privacy(caller, id, privacy)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_availability_cancellation_policy(id: String, policy: Option<CancellationPolicy>) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_availability_cancellation_policy(caller, id, policy)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_availability_durations(id: String, durations: Vec<DurationOption>) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_availability_durations(caller, id, durations)
+    })
+}
+
+/// Portable JSON and VAVAILABILITY copies of an availability's setup
+#[query]
+fn export_availability_definition(id: String) -> Result<AvailabilityExport, String> {
+    error_codes::run(move || {
+        availabilities::export_availability_definition(ic_cdk::caller(), id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_availability_kiosk(id: String, kiosk: Option<KioskConfig>) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_availability_kiosk(caller, id, kiosk)
+    })
+}
+
+/// Snap offered slots to clean boundaries and require minimum notice
+#[update(guard = "terms_accepted")]
+fn set_availability_alignment(id: String, alignment: Option<SlotAlignment>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_alignment(ic_cdk::caller(), id, alignment)
+    })
+}
+
+/// Meet/Zoom link put on booked guests' calendar invites (None removes it)
+#[update(guard = "terms_accepted")]
+fn set_availability_meeting_url(id: String, meeting_url: Option<String>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_meeting_url(ic_cdk::caller(), id, meeting_url)
+    })
+}
+
+/// Extra private properties (fixed or asked of the guest) written on each
+/// booking's Google event (None removes them)
+#[update(guard = "terms_accepted")]
+fn set_availability_event_fields(id: String, fields: Option<Vec<EventField>>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_event_fields(ic_cdk::caller(), id, fields)
+    })
+}
+
+/// Emails to the guest or webhook calls some hours after each booking ends
+/// (None removes them)
+#[update(guard = "terms_accepted")]
+fn set_availability_follow_ups(id: String, follow_ups: Option<Vec<FollowUp>>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_follow_ups(ic_cdk::caller(), id, follow_ups)
+    })
+}
+
+/// Let several guests book the same slot (None = one guest per slot)
+#[update(guard = "terms_accepted")]
+fn set_availability_seats(id: String, seats: Option<u32>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_seats(ic_cdk::caller(), id, seats)
+    })
+}
+
+/// Hold back near-term slots for VIP guests (None removes the reserve window)
+#[update(guard = "terms_accepted")]
+fn set_availability_vip(id: String, vip: Option<VipConfig>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_vip(ic_cdk::caller(), id, vip)
+    })
+}
+
+/// Give other people Viewer, Editor or Approver access to one availability
+#[update(guard = "terms_accepted")]
+fn set_availability_collaborators(id: String, collaborators: Vec<Collaborator>) -> Result<(), String> {
+    trace::run(move || {
+        availabilities::set_availability_collaborators(ic_cdk::caller(), id, collaborators)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_availability_locale(id: String, locale: Option<Locale>) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_availability_locale(caller, id, locale)
+    })
+}
+
+/// Send a copy of an availability's slots and settings (not its bookings)
+/// to a principal or email address, to accept into their own account
+#[update(guard = "terms_accepted")]
+fn share_availability_template(id: String, to: String) -> Result<AvailabilityShare, String> {
+    trace::run(move || {
+        availability_shares::share_template(ic_cdk::caller(), id, to)
+    })
+}
+
+/// Templates shared with the caller's principal or verified email
+#[query]
+fn list_incoming_availability_shares() -> Vec<AvailabilityShare> {
+    availability_shares::list_incoming(ic_cdk::caller())
+}
+
+#[query]
+fn list_outgoing_availability_shares() -> Vec<AvailabilityShare> {
+    availability_shares::list_outgoing(ic_cdk::caller())
+}
+
+/// Create a new availability of the caller's own from a shared template
+#[update(guard = "terms_accepted")]
+fn accept_availability_share(share_id: String) -> Result<Availability, String> {
+    trace::run(move || {
+        availability_shares::accept(ic_cdk::caller(), share_id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn decline_availability_share(share_id: String) -> Result<(), String> {
+    trace::run(move || {
+        availability_shares::decline(ic_cdk::caller(), share_id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn revoke_availability_share(share_id: String) -> Result<(), String> {
+    trace::run(move || {
+        availability_shares::revoke(ic_cdk::caller(), share_id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
 fn delete_availability(id: String) -> Result<(), String> {
-    let caller = ic_cdk::caller();
-    availabilities::delete_availability(caller, id)
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::delete_availability(caller, id)
+    })
+}
+
+/// Availabilities and bookings the caller deleted in the last 30 days
+#[query]
+fn list_trash() -> Vec<TrashEntry> {
+    trash::list_trash(ic_cdk::caller())
+}
+
+/// Undo a deletion still in the trash
+#[update(guard = "terms_accepted")]
+fn restore_from_trash(id: String) -> Result<TrashEntry, String> {
+    trace::run(move || {
+        trash::restore_from_trash(ic_cdk::caller(), id)
+    })
 }
 
+/// Cut short if it would exceed the reply limit; use
+/// `list_user_availabilities_page` to get everything
 #[query]
 fn list_user_availabilities() -> Vec<Availability> {
     let caller = ic_cdk::caller();
-    availabilities::list_user_availabilities(caller)
+    response_size::clamp(availabilities::list_user_availabilities(caller), "list_user_availabilities")
 }
 
-#[update]
+/// The caller's availabilities, as many as fit in one reply; pass
+/// `next_cursor` back while `truncated` is set
+#[query]
+fn list_user_availabilities_page(cursor: Option<String>) -> Result<AvailabilityPage, String> {
+    error_codes::run(move || {
+        availabilities::list_user_availabilities_page(ic_cdk::caller(), cursor)
+    })
+}
+
+#[update(guard = "terms_accepted")]
 fn regenerate_availability_id(old_id: String) -> Result<String, String> {
-    let caller = ic_cdk::caller();
-    availabilities::regenerate_availability_id(caller, old_id)
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::regenerate_availability_id(caller, old_id)
+    })
+}
+
+/// Free windows (Unix seconds) for an availability, never revealing busy data.
+/// VIPs (by verified email, domain or `access_code`) also see the reserve window.
+#[query]
+fn get_free_slots(
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+    origin: Option<String>,
+) -> Result<Vec<FreeSlot>, String> {
+    error_codes::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::get_visible_availability(caller, id.clone(), origin.as_deref())?;
+        availabilities::get_free_slots_as(caller, id, range_start, range_end, duration_minutes, access_code)
+    })
+}
+
+/// Slots guests would see if `settings` were saved (owners only; nothing is stored)
+#[query]
+fn preview_free_slots(
+    id: String,
+    settings: AvailabilitySettingsPreview,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+) -> Result<Vec<FreeSlot>, String> {
+    error_codes::run(move || {
+        availabilities::preview_free_slots(ic_cdk::caller(), id, settings, range_start, range_end, duration_minutes)
+    })
+}
+
+/// Free slots frozen for 15 minutes so a booking submitted from this page
+/// isn't rejected by a calendar sync in between. The page passes the guest's
+/// IANA timezone, which the booking keeps for guest-facing times.
+#[update(guard = "terms_accepted")]
+fn snapshot_free_slots(
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+    guest_timezone: Option<String>,
+) -> Result<FreeSlotSnapshot, String> {
+    trace::run(move || {
+        let snapshot = snapshots::snapshot_free_slots(
+            ic_cdk::caller(), id, range_start, range_end, duration_minutes, access_code, guest_timezone,
+        )?;
+        sync_scheduler::record_view(&snapshot.availability_id);
+        Ok(snapshot)
+    })
+}
+
+/// Windows where every participant is free; `fairness_mode` ranks them by
+/// reasonable local hours and flags anyone asked to meet outside 8:00-20:00
+#[query]
+fn get_common_free_slots(
+    participants: Vec<String>,
+    range_start: u64,
+    range_end: u64,
+    fairness_mode: bool,
+) -> Result<Vec<CommonFreeSlot>, String> {
+    error_codes::run(move || {
+        availabilities::get_common_free_slots(participants, range_start, range_end, fairness_mode)
+    })
+}
+
+/// Ranked meeting times that suit every participant's availability
+#[query]
+fn suggest_meeting_times(
+    participants: Vec<String>,
+    duration_minutes: u32,
+    constraints: SuggestionConstraints,
+) -> Result<Vec<MeetingSuggestion>, String> {
+    error_codes::run(move || {
+        availabilities::suggest_meeting_times(participants, duration_minutes, constraints)
+    })
 }
 
+/// Search bar: the caller's availabilities, bookings and contacts matching
+/// `query`, best matches first
 #[query]
-fn search_availabilities_by_email(email: String) -> Vec<Availability> {
-    availabilities::search_availabilities_by_email(email)
+fn search_my_data(query: String) -> Result<Vec<SearchResult>, String> {
+    error_codes::run(move || {
+        search::search_my_data(ic_cdk::caller(), query)
+    })
 }
 
 #[query]
-fn search_availabilities_by_username(username: String) -> Vec<Availability> {
-    availabilities::search_availabilities_by_username(username)
+fn search_availabilities_by_email(email: String, origin: Option<String>) -> Vec<Availability> {
+    availabilities::search_availabilities_by_email(ic_cdk::caller(), email, origin.as_deref())
 }
 
 #[query]
-fn search_availabilities_by_principal(principal: Principal) -> Vec<Availability> {
-    availabilities::search_availabilities_by_principal(principal)
+fn search_availabilities_by_username(username: String, origin: Option<String>) -> Vec<Availability> {
+    availabilities::search_availabilities_by_username(ic_cdk::caller(), username, origin.as_deref())
 }
 
 #[query]
-fn search_by_emails(emails: Vec<String>) -> Vec<Vec<Availability>> {
-    availabilities::search_by_emails(emails)
+fn search_availabilities_by_principal(principal: Principal, origin: Option<String>) -> Vec<Availability> {
+    availabilities::search_availabilities_by_principal(ic_cdk::caller(), principal, origin.as_deref())
 }
 
 #[query]
-fn search_by_usernames(usernames: Vec<String>) -> Vec<Vec<Availability>> {
-    availabilities::search_by_usernames(usernames)
+fn search_by_emails(emails: Vec<String>, origin: Option<String>) -> Vec<Vec<Availability>> {
+    response_size::clamp(availabilities::search_by_emails(ic_cdk::caller(), emails, origin.as_deref()), "search_by_emails")
 }
 
-#[update]
+#[query]
+fn search_by_usernames(usernames: Vec<String>, origin: Option<String>) -> Vec<Vec<Availability>> {
+    response_size::clamp(availabilities::search_by_usernames(ic_cdk::caller(), usernames, origin.as_deref()), "search_by_usernames")
+}
+
+#[update(guard = "terms_accepted")]
 fn set_favorite_availability(id: String) -> Result<(), String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        availabilities::set_favorite_availability(caller, id)
+    })
+}
+
+// ============================================================================
+// Quota API Endpoints
+// ============================================================================
+
+#[query]
+fn get_my_quotas() -> QuotaStatus {
     let caller = ic_cdk::caller();
-    availabilities::set_favorite_availability(caller, id)
+    quotas::get_quota_status(
+        caller,
+        availabilities::count_user_availabilities(caller),
+        api_usage::google_calls_today(caller),
+    )
+}
+
+/// Google API calls made for the caller's connected account, per day for the last 30 days
+#[query]
+fn get_my_api_usage() -> Vec<ApiUsageDay> {
+    api_usage::get_usage(ic_cdk::caller())
+}
+
+/// Admin: accounts with the most Google API calls on `day` (days since epoch, today if null)
+#[query]
+fn get_api_usage_report(day: Option<u64>) -> Result<Vec<AccountUsage>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(api_usage::usage_report(day))
+    })
 }
 
+#[query]
+fn get_default_quotas() -> QuotaLimits {
+    quotas::get_default_limits()
+}
 
+/// Admin: change the limits applied to principals without an override
+#[update(guard = "terms_accepted")]
+fn set_default_quotas(limits: QuotaLimits) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        quotas::set_default_limits(limits)
+    })
+}
 
-ic_cdk_macros::export_candid!();
+/// Admin: give a principal custom limits, or pass null to clear the override
+#[update(guard = "terms_accepted")]
+fn set_quota_override(principal: Principal, limits: Option<QuotaLimits>) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        quotas::set_limit_override(principal, limits);
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Plan API Endpoints
+// ============================================================================
+
+#[query]
+fn get_my_plan() -> PlanInfo {
+    plans::get_plan_info(ic_cdk::caller())
+}
+
+/// Admin: assign a tier manually (e.g. comped accounts)
+#[update(guard = "terms_accepted")]
+fn set_user_plan(principal: Principal, tier: PlanTier, expires_at: Option<u64>) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        plans::set_plan(principal, tier, PlanSource::Admin, expires_at);
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Discord API Endpoints
+// ============================================================================
+
+#[query]
+fn get_discord_config() -> DiscordConfig {
+    discord::get_config()
+}
+
+/// Admin: set the Discord application's public key used to verify interactions
+#[update(guard = "terms_accepted")]
+fn set_discord_config(config: DiscordConfig) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        discord::set_config(config)
+    })
+}
+
+/// One-time code for `/link` in Discord, valid for ten minutes
+#[update(guard = "terms_accepted")]
+fn create_discord_link_code() -> Result<String, String> {
+    trace::run(move || {
+        discord::create_discord_link_code(ic_cdk::caller())
+    })
+}
+
+/// Disconnect every Discord account linked to the caller; returns how many were removed
+#[update(guard = "terms_accepted")]
+fn unlink_discord() -> u32 {
+    discord::unlink_discord(ic_cdk::caller())
+}
+
+// ============================================================================
+// Stripe API Endpoints
+// ============================================================================
+
+/// Admin: set the signing secret of the /hooks/stripe webhook endpoint
+#[update(guard = "terms_accepted")]
+fn set_stripe_config(config: StripeConfig) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        stripe::set_config(config)
+    })
+}
+
+/// Admin: verified Stripe events that couldn't be applied
+#[query]
+fn list_stripe_dead_letters() -> Result<Vec<ParkedStripeEvent>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(stripe::list_dead_letters())
+    })
+}
+
+/// Admin: apply a parked Stripe event again
+#[update(guard = "terms_accepted")]
+fn retry_stripe_event(event_id: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        stripe::retry_dead_letter(event_id)
+    })
+}
+
+/// Admin: drop a parked Stripe event
+#[update(guard = "terms_accepted")]
+fn discard_stripe_event(event_id: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        stripe::discard_dead_letter(event_id)
+    })
+}
+
+// ============================================================================
+// Billing API Endpoints (ICRC-2)
+// ============================================================================
+
+/// Ledger, price, and grace period the frontend needs to build the icrc2_approve call
+#[query]
+fn get_billing_config() -> BillingConfig {
+    billing::get_config()
+}
+
+/// Admin: configure the ledger and monthly price for on-chain Pro plans
+#[update(guard = "terms_accepted")]
+fn set_billing_config(config: BillingConfig) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        billing::set_config(config)
+    })
+}
+
+/// Charge the first month via icrc2_transfer_from and upgrade to Pro
+#[update(guard = "terms_accepted")]
+async fn subscribe_pro() -> Result<Subscription, String> {
+    trace::run_async(async move {
+        billing::subscribe(ic_cdk::caller()).await
+    }).await
+}
+
+#[update(guard = "terms_accepted")]
+fn cancel_subscription() -> Result<(), String> {
+    trace::run(move || {
+        billing::cancel(ic_cdk::caller())
+    })
+}
+
+#[query]
+fn get_my_subscription() -> Option<Subscription> {
+    billing::get_subscription(ic_cdk::caller())
+}
+
+// ============================================================================
+// Promo & Referral API Endpoints
+// ============================================================================
+
+/// Admin: create a promo code with a usage limit and optional expiry
+#[update(guard = "terms_accepted")]
+fn create_promo_code(code: String, reward: PromoReward, max_uses: u32, expires_at: Option<u64>) -> Result<PromoCode, String> {
+    trace::run(move || {
+        let caller = require_controller()?;
+        promos::create_promo_code(caller, code, reward, max_uses, expires_at)
+    })
+}
+
+#[query]
+fn list_promo_codes() -> Result<Vec<PromoCode>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(promos::list_promo_codes())
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_promo_code(code: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        promos::delete_promo_code(code)
+    })
+}
+
+/// Redeem a promo code or another user's referral code
+#[update(guard = "terms_accepted")]
+fn redeem_code(code: String) -> Result<PromoReward, String> {
+    trace::run(move || {
+        promos::redeem_code(ic_cdk::caller(), code)
+    })
+}
+
+/// Update because the caller's referral code is registered on first request
+#[update(guard = "terms_accepted")]
+fn get_my_referral_code() -> ReferralStats {
+    promos::get_referral_stats(ic_cdk::caller())
+}
+
+#[query]
+fn get_my_booking_discount() -> Option<BookingDiscount> {
+    promos::get_booking_discount(ic_cdk::caller())
+}
+
+// ============================================================================
+// Booking API Endpoints
+// ============================================================================
+
+#[update(guard = "terms_accepted")]
+fn create_booking(req: CreateBookingRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        bookings::create_booking(caller, req)
+    })
+}
+
+/// Guest details, answers and availability behind a book-again link
+#[query]
+fn get_rebook_info(token: String) -> Result<RebookInfo, String> {
+    error_codes::run(move || {
+        rebook::get_rebook_info(token)
+    })
+}
+
+/// create_booking through a book-again link, counted as a repeat booking
+#[update(guard = "terms_accepted")]
+fn book_again(token: String, req: CreateBookingRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        rebook::book_again(caller, token, req)
+    })
+}
+
+/// Dry run of create_booking: Ok if the slot could be booked right now, else
+/// the error booking would fail with. Nothing is held or counted.
+#[query]
+fn check_booking_conflict(availability_id: String, start_time: u64, end_time: u64) -> Result<(), String> {
+    error_codes::run(move || {
+        bookings::check_booking_conflict(ic_cdk::caller(), availability_id, start_time, end_time)
+    })
+}
+
+/// Scripting shortcut: book `slug` (an availability ID) at an RFC 3339 time; returns the booking ID
+#[update(guard = "terms_accepted")]
+fn quick_book(slug: String, iso_start: String, email: String) -> Result<String, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        quick::quick_book(caller, slug, iso_start, email)
+    })
+}
+
+/// Scripting shortcut: free windows over the next `days` as "start/end" ISO 8601 strings
+#[query]
+fn quick_free(slug: String, days: u32) -> Result<Vec<String>, String> {
+    error_codes::run(move || {
+        quick::quick_free(slug, days)
+    })
+}
+
+/// Fields, durations, price and policies of the booking form, as the backend enforces them
+#[query]
+fn get_booking_schema(id: String, session_link: Option<String>) -> Result<BookingSchema, String> {
+    error_codes::run(move || {
+        booking_schema::get_booking_schema(id, session_link)
+    })
+}
+
+/// Book a slot picked from `snapshot_free_slots`
+#[update(guard = "terms_accepted")]
+fn book_with_snapshot(token: String, req: CreateBookingRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        snapshots::book_with_snapshot(caller, token, req)
+    })
+}
+
+#[query]
+fn get_booking(id: String) -> Result<Booking, String> {
+    error_codes::run(move || {
+        bookings::get_booking(ic_cdk::caller(), id)
+    })
+}
+
+/// The booking as an .ics calendar invite (text/calendar) for the guest's calendar app
+#[query]
+fn get_booking_ics(id: String) -> Result<String, String> {
+    error_codes::run(move || {
+        bookings::get_booking_ics(ic_cdk::caller(), id)
+    })
+}
+
+/// Everything that happened to a booking, oldest first
+#[query]
+fn get_booking_history(id: String) -> Result<Vec<BookingEventRecord>, String> {
+    error_codes::run(move || {
+        bookings::get_booking_history(ic_cdk::caller(), id)
+    })
+}
+
+/// Guests in the booking's session; other guests' emails are left out
+/// unless the caller is on the owner's side
+#[query]
+fn get_booking_attendees(id: String) -> Result<Vec<Attendee>, String> {
+    error_codes::run(move || {
+        bookings::get_attendees(ic_cdk::caller(), id)
+    })
+}
+
+/// The guest's check-in token for a booking, to show at the door
+#[update(guard = "terms_accepted")]
+fn get_check_in_token(booking_id: String) -> Result<String, String> {
+    trace::run(move || {
+        check_ins::get_token(ic_cdk::caller(), booking_id)
+    })
+}
+
+/// Check a guest in with their token (owner's side, from 30 minutes before the start)
+#[update(guard = "terms_accepted")]
+fn check_in(booking_id: String, guest_token: String) -> Result<AttendanceEntry, String> {
+    trace::run(move || {
+        check_ins::check_in(ic_cdk::caller(), booking_id, guest_token)
+    })
+}
+
+/// Who is booked into a session and who has checked in, for the owner and Approvers
+#[query]
+fn get_session_attendance(availability_id: String, start_time: u64) -> Result<Vec<AttendanceEntry>, String> {
+    error_codes::run(move || {
+        check_ins::attendance(ic_cdk::caller(), availability_id, start_time)
+    })
+}
+
+#[query]
+fn get_no_show_stats(availability_id: String) -> Result<NoShowStats, String> {
+    error_codes::run(move || {
+        check_ins::no_show_stats(ic_cdk::caller(), availability_id)
+    })
+}
+
+/// Admin: rebuild the booking map from the event log; returns bookings rewritten
+#[update(guard = "terms_accepted")]
+fn replay_booking_events() -> Result<u32, String> {
+    trace::run(move || {
+        require_controller()?;
+        Ok(bookings::replay_booking_events())
+    })
+}
+
+/// Refund the caller would get by cancelling now
+#[query]
+fn get_cancellation_quote(id: String) -> Result<CancellationQuote, String> {
+    error_codes::run(move || {
+        bookings::get_cancellation_quote(ic_cdk::caller(), id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+async fn cancel_booking(id: String) -> Result<Booking, String> {
+    trace::run_async(async move {
+        bookings::cancel_booking(ic_cdk::caller(), id).await
+    }).await
+}
+
+/// Move a cancelled or past booking to the trash (kept 30 days)
+#[update(guard = "terms_accepted")]
+fn delete_booking(id: String) -> Result<(), String> {
+    trace::run(move || {
+        bookings::delete_booking(ic_cdk::caller(), id)
+    })
+}
+
+/// Bookings on one availability, for its owner and Approvers. Cut short if
+/// it would exceed the reply limit; the `_page` variant flags that.
+#[query]
+fn list_availability_bookings(availability_id: String) -> Result<Vec<Booking>, String> {
+    error_codes::run(move || {
+        bookings::list_availability_bookings(ic_cdk::caller(), availability_id)
+            .map(|list| response_size::clamp(list, "list_availability_bookings"))
+    })
+}
+
+#[query]
+fn list_availability_bookings_page(availability_id: String, cursor: Option<String>) -> Result<BookingPage, String> {
+    error_codes::run(move || {
+        let filters = format!("availability|{}", availability_id);
+        bookings::page(bookings::list_availability_bookings(ic_cdk::caller(), availability_id)?, cursor, &filters)
+    })
+}
+
+/// Bookings made on the caller's availabilities. Cut short if it would
+/// exceed the reply limit; the `_page` variant flags that.
+#[query]
+fn list_my_bookings() -> Vec<Booking> {
+    response_size::clamp(bookings::list_owner_bookings(ic_cdk::caller()), "list_my_bookings")
+}
+
+#[query]
+fn list_my_bookings_page(cursor: Option<String>) -> Result<BookingPage, String> {
+    error_codes::run(move || {
+        let caller = ic_cdk::caller();
+        bookings::page(bookings::list_owner_bookings(caller), cursor, &format!("owner|{}", caller))
+    })
+}
+
+// ============================================================================
+// Paid Session Link API Endpoints
+// ============================================================================
+
+/// Mint a single-use link for one paid session at a custom price (Pro)
+#[update(guard = "terms_accepted")]
+fn create_session_link(req: CreateSessionLinkRequest) -> Result<SessionLink, String> {
+    trace::run(move || {
+        session_links::create_session_link(ic_cdk::caller(), req)
+    })
+}
+
+#[query]
+fn list_my_session_links() -> Vec<SessionLink> {
+    session_links::list_session_links(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn set_session_link_prices(token: String, prices: Vec<DurationPrice>) -> Result<SessionLink, String> {
+    trace::run(move || {
+        session_links::set_session_link_prices(ic_cdk::caller(), token, prices)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_session_link_stripe_price(token: String, price: Option<StripePrice>) -> Result<SessionLink, String> {
+    trace::run(move || {
+        session_links::set_session_link_stripe_price(ic_cdk::caller(), token, price)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn revoke_session_link(token: String) -> Result<(), String> {
+    trace::run(move || {
+        session_links::revoke_session_link(ic_cdk::caller(), token)
+    })
+}
+
+#[query]
+fn get_session_link(token: String) -> Result<SessionLink, String> {
+    error_codes::run(move || {
+        session_links::get_session_link(token)
+    })
+}
+
+/// Pay for and book the link's session (requires an ICRC-2 approval first)
+#[update(guard = "terms_accepted")]
+async fn redeem_session_link(req: RedeemSessionLinkRequest) -> Result<Booking, String> {
+    trace::run_async(async move {
+        session_links::redeem_session_link(ic_cdk::caller(), req).await
+    }).await
+}
+
+/// Hold the link's session for a card payment; the Stripe webhook settles it
+#[update(guard = "terms_accepted")]
+fn hold_session_link_for_stripe(req: RedeemSessionLinkRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        session_links::hold_session_link_for_stripe(ic_cdk::caller(), req)
+    })
+}
+
+// ============================================================================
+// One-Time Link API Endpoints
+// ============================================================================
+
+/// Mint a secret link that allows exactly one booking on the availability
+#[update(guard = "terms_accepted")]
+fn create_one_time_link(availability_id: String) -> Result<OneTimeLink, String> {
+    trace::run(move || {
+        one_time_links::create_one_time_link(ic_cdk::caller(), availability_id)
+    })
+}
+
+#[query]
+fn list_my_one_time_links() -> Vec<OneTimeLink> {
+    one_time_links::list_one_time_links(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn revoke_one_time_link(token: String) -> Result<(), String> {
+    trace::run(move || {
+        one_time_links::revoke_one_time_link(ic_cdk::caller(), token)
+    })
+}
+
+#[query]
+fn get_one_time_link(token: String) -> Result<OneTimeLink, String> {
+    error_codes::run(move || {
+        one_time_links::get_one_time_link(token)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn book_with_one_time_link(req: BookWithLinkRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        one_time_links::book_with_one_time_link(caller, req)
+    })
+}
+
+// ============================================================================
+// Resource API Endpoints
+// ============================================================================
+
+/// Add a room or piece of equipment to an org (tenant admins)
+#[update(guard = "terms_accepted")]
+fn create_resource(tenant_id: String, req: ResourceRequest) -> Result<Resource, String> {
+    trace::run(move || {
+        resources::create_resource(ic_cdk::caller(), tenant_id, req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn update_resource(id: String, req: ResourceRequest) -> Result<Resource, String> {
+    trace::run(move || {
+        resources::update_resource(ic_cdk::caller(), id, req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_resource(id: String) -> Result<(), String> {
+    trace::run(move || {
+        resources::delete_resource(ic_cdk::caller(), id)
+    })
+}
+
+/// Resources of the caller's org
+#[query]
+fn list_resources() -> Vec<Resource> {
+    resources::list_resources(ic_cdk::caller())
+}
+
+#[query]
+fn get_resource_free_slots(id: String, range_start: u64, range_end: u64) -> Result<Vec<FreeSlot>, String> {
+    error_codes::run(move || {
+        resources::get_resource_free_slots(ic_cdk::caller(), id, range_start, range_end)
+    })
+}
+
+/// Windows where both the availability's owner and the resource are free
+#[query]
+fn get_free_slots_with_resource(
+    availability_id: String,
+    resource_id: String,
+    range_start: u64,
+    range_end: u64,
+) -> Result<Vec<FreeSlot>, String> {
+    error_codes::run(move || {
+        resources::get_free_slots_with_resource(availability_id, resource_id, range_start, range_end)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn book_with_resource(req: CreateBookingRequest, resource_id: String) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        resources::book_with_resource(caller, req, resource_id)
+    })
+}
+
+/// Book several people and resources for one slot, all or nothing
+#[update(guard = "terms_accepted")]
+fn book_together(req: GroupBookingRequest) -> Result<Vec<Booking>, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        resources::book_together(caller, req)
+    })
+}
+
+// ============================================================================
+// Org Admin API Endpoints
+// ============================================================================
+
+/// Add a company holiday or all-hands that blocks every member (tenant admins)
+#[update(guard = "terms_accepted")]
+fn add_org_busy_block(tenant_id: String, req: OrgBusyBlockRequest) -> Result<OrgBusyBlock, String> {
+    trace::run(move || {
+        org_busy::add_org_busy_block(ic_cdk::caller(), tenant_id, req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_org_busy_block(tenant_id: String, id: String) -> Result<(), String> {
+    trace::run(move || {
+        org_busy::delete_org_busy_block(ic_cdk::caller(), tenant_id, id)
+    })
+}
+
+/// Upcoming shared busy blocks of the caller's org
+#[query]
+fn list_org_busy_blocks() -> Vec<OrgBusyBlock> {
+    org_busy::list_org_busy_blocks(ic_cdk::caller())
+}
+
+/// Booked vs offered time per member over a range (tenant admins, paginated)
+#[query]
+fn get_org_load_report(
+    tenant_id: String,
+    range_start: u64,
+    range_end: u64,
+    offset: u32,
+    limit: u32,
+    cursor: Option<String>,
+) -> Result<OrgLoadReport, String> {
+    error_codes::run(move || {
+        org_load::get_org_load_report(ic_cdk::caller(), tenant_id, range_start, range_end, offset, limit, cursor)
+    })
+}
+
+// ============================================================================
+// Management Delegation API Endpoints
+// ============================================================================
+
+/// Let another principal (e.g. an assistant) manage the caller's availabilities
+/// and/or bookings. An empty scope list revokes the delegation.
+#[update(guard = "terms_accepted")]
+fn grant_delegation(delegate: Principal, scopes: Vec<DelegationScope>) -> Result<Option<ManagementDelegation>, String> {
+    trace::run(move || {
+        delegations::grant_delegation(ic_cdk::caller(), delegate, scopes)
+    })
+}
+
+#[query]
+fn list_my_delegates() -> Vec<ManagementDelegation> {
+    delegations::list_delegates(ic_cdk::caller())
+}
+
+/// Owners the caller can act for
+#[query]
+fn list_delegated_to_me() -> Vec<ManagementDelegation> {
+    delegations::list_delegated_to_me(ic_cdk::caller())
+}
+
+/// Actions delegates took on the caller's behalf, newest first
+#[query]
+fn get_delegate_audit_log() -> Vec<DelegateAction> {
+    delegations::get_delegate_audit_log(ic_cdk::caller())
+}
+
+/// The audit log one page at a time, newest first
+#[query]
+fn get_delegate_audit_log_page(cursor: Option<String>) -> Result<DelegateAuditPage, String> {
+    error_codes::run(move || {
+        delegations::get_delegate_audit_log_page(ic_cdk::caller(), cursor)
+    })
+}
+
+/// An owner's availabilities, for their delegates
+#[query]
+fn list_availabilities_for(owner: Principal) -> Result<Vec<Availability>, String> {
+    error_codes::run(move || {
+        delegations::require(ic_cdk::caller(), owner, DelegationScope::ManageAvailabilities)?;
+        Ok(response_size::clamp(availabilities::list_user_availabilities(owner), "list_availabilities_for"))
+    })
+}
+
+/// Bookings on an owner's availabilities, for their delegates
+#[query]
+fn list_bookings_for(owner: Principal) -> Result<Vec<Booking>, String> {
+    error_codes::run(move || {
+        delegations::require(ic_cdk::caller(), owner, DelegationScope::ManageBookings)?;
+        Ok(response_size::clamp(bookings::list_owner_bookings(owner), "list_bookings_for"))
+    })
+}
+
+// ============================================================================
+// Interview Pipeline API Endpoints
+// ============================================================================
+
+/// Multi-round interview loop; each round is booked from its own interviewer pool
+#[update(guard = "terms_accepted")]
+fn create_interview_pipeline(req: CreatePipelineRequest) -> Result<InterviewPipeline, String> {
+    trace::run(move || {
+        interviews::create_interview_pipeline(ic_cdk::caller(), req)
+    })
+}
+
+#[query]
+fn list_interview_pipelines() -> Vec<InterviewPipeline> {
+    interviews::list_interview_pipelines(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn add_interview_candidate(pipeline_id: String, name: String, email: Option<String>) -> Result<Candidate, String> {
+    trace::run(move || {
+        interviews::add_candidate(ic_cdk::caller(), pipeline_id, name, email)
+    })
+}
+
+#[query]
+fn list_interview_candidates(pipeline_id: String) -> Result<Vec<Candidate>, String> {
+    error_codes::run(move || {
+        interviews::list_candidates(ic_cdk::caller(), pipeline_id)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn withdraw_interview_candidate(token: String) -> Result<Candidate, String> {
+    trace::run(move || {
+        interviews::withdraw_candidate(ic_cdk::caller(), token)
+    })
+}
+
+/// The candidate's current round and stage, via their private token
+#[query]
+fn get_candidate_status(token: String) -> Result<Candidate, String> {
+    error_codes::run(move || {
+        interviews::get_candidate_status(token)
+    })
+}
+
+#[query]
+fn get_interview_round_slots(token: String, range_start: u64, range_end: u64) -> Result<Vec<RoundSlot>, String> {
+    error_codes::run(move || {
+        interviews::get_round_slots(token, range_start, range_end)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn book_interview_round(req: BookRoundRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        interviews::book_interview_round(caller, req)
+    })
+}
+
+// ============================================================================
+// Team Round-Robin API Endpoints
+// ============================================================================
+
+/// Weighted round-robin over members' availabilities, with optional weekly caps
+#[update(guard = "terms_accepted")]
+fn create_team(req: TeamRequest) -> Result<Team, String> {
+    trace::run(move || {
+        teams::create_team(ic_cdk::caller(), req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn update_team(id: String, req: TeamRequest) -> Result<Team, String> {
+    trace::run(move || {
+        teams::update_team(ic_cdk::caller(), id, req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_team(id: String) -> Result<(), String> {
+    trace::run(move || {
+        teams::delete_team(ic_cdk::caller(), id)
+    })
+}
+
+#[query]
+fn list_teams() -> Vec<Team> {
+    teams::list_teams(ic_cdk::caller())
+}
+
+#[query]
+fn get_team(id: String) -> Result<Team, String> {
+    error_codes::run(move || {
+        teams::get_team(&id)
+    })
+}
+
+#[query]
+fn get_team_free_slots(id: String, range_start: u64, range_end: u64) -> Result<Vec<FreeSlot>, String> {
+    error_codes::run(move || {
+        teams::get_team_free_slots(id, range_start, range_end)
+    })
+}
+
+/// Book the team; the least-loaded free member gets the meeting
+#[update(guard = "terms_accepted")]
+fn book_with_team(req: TeamBookingRequest) -> Result<Booking, String> {
+    trace::run(move || {
+        let caller = ic_cdk::caller();
+        quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+        teams::book_with_team(caller, req)
+    })
+}
+
+// ============================================================================
+// Integration Hook API Endpoints
+// ============================================================================
+
+/// Post flat booking rows to a Zapier/Make/Sheets catch hook (Pro)
+#[update(guard = "terms_accepted")]
+fn create_integration_hook(req: CreateIntegrationHookRequest) -> Result<IntegrationHook, String> {
+    trace::run(move || {
+        integrations::create_hook(ic_cdk::caller(), req)
+    })
+}
+
+#[query]
+fn list_my_integration_hooks() -> Vec<IntegrationHook> {
+    integrations::list_hooks(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_integration_hook(id: String) -> Result<(), String> {
+    trace::run(move || {
+        integrations::delete_hook(ic_cdk::caller(), id)
+    })
+}
+
+/// The JSON the hook would receive for one of your bookings
+#[query]
+fn preview_integration_payload(id: String, booking_id: String) -> Result<String, String> {
+    error_codes::run(move || {
+        integrations::preview_payload(ic_cdk::caller(), id, booking_id)
+    })
+}
+
+/// Post booking notifications to a Matrix room with a bot access token (Pro)
+#[update(guard = "terms_accepted")]
+fn set_matrix_notifier(req: SetMatrixNotifierRequest) -> Result<MatrixNotifier, String> {
+    trace::run(move || {
+        matrix::set_matrix_notifier(ic_cdk::caller(), req)
+    })
+}
+
+/// The caller's Matrix settings, without the access token
+#[query]
+fn get_matrix_notifier() -> Option<MatrixNotifier> {
+    matrix::get_matrix_notifier(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_matrix_notifier() -> Result<(), String> {
+    trace::run(move || {
+        matrix::delete_matrix_notifier(ic_cdk::caller())
+    })
+}
+
+#[query]
+fn transform_hook_response(
+    args: ic_cdk::api::management_canister::http_request::TransformArgs,
+) -> ic_cdk::api::management_canister::http_request::HttpResponse {
+    integrations::transform_response(args)
+}
+
+// ============================================================================
+// Notification Template API Endpoints
+// ============================================================================
+
+/// Custom subject and body for booking notifications and receipts, with
+/// `{{variable}}` placeholders named like integration hook fields
+#[update(guard = "terms_accepted")]
+fn set_notification_template(
+    scope: TemplateScope,
+    kind: TemplateKind,
+    subject: String,
+    body: String,
+) -> Result<NotificationTemplate, String> {
+    trace::run(move || {
+        templates::set_template(ic_cdk::caller(), scope, kind, subject, body)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_notification_template(scope: TemplateScope, kind: TemplateKind) -> Result<(), String> {
+    trace::run(move || {
+        templates::delete_template(ic_cdk::caller(), scope, kind)
+    })
+}
+
+#[query]
+fn list_notification_templates(scope: TemplateScope) -> Result<Vec<NotificationTemplate>, String> {
+    error_codes::run(move || {
+        templates::list_templates(ic_cdk::caller(), scope)
+    })
+}
+
+/// Every notification delivery attempt for one of your bookings, oldest first
+#[query]
+fn get_notification_deliveries(booking_id: String) -> Result<Vec<DeliveryRecord>, String> {
+    error_codes::run(move || {
+        delivery_log::get_log(ic_cdk::caller(), booking_id)
+    })
+}
+
+/// Send a booking's notification on a channel again; returns how many deliveries were queued
+#[update(guard = "terms_accepted")]
+fn resend_notification(booking_id: String, channel: NotificationChannel) -> Result<u32, String> {
+    trace::run(move || {
+        delivery_log::resend(ic_cdk::caller(), booking_id, channel)
+    })
+}
+
+// ============================================================================
+// Slot Experiment API Endpoints
+// ============================================================================
+
+/// Offer alternative weekly hours to a share of guests until `ends_at`,
+/// counting views and bookings per variant
+#[update(guard = "terms_accepted")]
+fn start_slot_experiment(req: StartExperimentRequest) -> Result<SlotExperiment, String> {
+    trace::run(move || {
+        experiments::start_experiment(ic_cdk::caller(), req)
+    })
+}
+
+/// End the running experiment early; its stats stay readable
+#[update(guard = "terms_accepted")]
+fn stop_slot_experiment(availability_id: String) -> Result<SlotExperiment, String> {
+    trace::run(move || {
+        experiments::stop_experiment(ic_cdk::caller(), availability_id)
+    })
+}
+
+/// The availability's latest experiment with its per-variant stats
+#[query]
+fn get_slot_experiment(availability_id: String) -> Result<Option<SlotExperiment>, String> {
+    error_codes::run(move || {
+        experiments::get_experiment(ic_cdk::caller(), availability_id)
+    })
+}
+
+// ============================================================================
+// Storage Introspection Endpoints
+// ============================================================================
+
+/// Admin: stable memory per structure, entry counts and sizes of the unbounded
+/// maps, their largest records, and growth since the last snapshot
+#[query]
+fn get_storage_report() -> Result<StorageReport, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(storage::get_storage_report())
+    })
+}
+
+/// Admin: save the current report as the baseline for growth figures
+#[update(guard = "terms_accepted")]
+fn snapshot_storage_report() -> Result<StorageReport, String> {
+    trace::run(move || {
+        require_controller()?;
+        storage::snapshot_storage_report()
+    })
+}
+
+// ============================================================================
+// Corrupt Record Endpoints
+// ============================================================================
+
+/// Admin: stored records that failed to decode, 50 per page
+#[query]
+fn list_corrupt_records(cursor: Option<String>) -> Result<CorruptRecordsPage, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(quarantine::list_corrupt_records(cursor))
+    })
+}
+
+/// Admin: remove the placeholders standing in for undecodable availabilities and bookings
+#[update(guard = "terms_accepted")]
+fn sweep_corrupt_records() -> Result<SweepReport, String> {
+    trace::run(move || {
+        require_controller()?;
+        Ok(quarantine::sweep_corrupt_records())
+    })
+}
+
+/// Admin: forget a quarantined record
+#[update(guard = "terms_accepted")]
+fn purge_corrupt_record(id: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        quarantine::purge_corrupt_record(id)
+    })
+}
+
+// ============================================================================
+// Upgrade Verification Endpoints
+// ============================================================================
+
+/// Admin: availabilities, bookings and tokens scanned and compared with the
+/// digests kept since before the last upgrade, to confirm it lost nothing
+#[query]
+fn verify_upgrade() -> Result<UpgradeVerification, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(upgrade_check::verify_upgrade())
+    })
+}
+
+// ============================================================================
+// Index Migration Endpoints
+// ============================================================================
+
+/// Admin: copy the next chunk of search indices and sessions into stable
+/// memory; call repeatedly until the phase is Done
+#[update(guard = "terms_accepted")]
+fn run_index_migration(batch_size: Option<u32>) -> Result<MigrationStatus, String> {
+    trace::run(move || {
+        require_controller()?;
+        migration::run_migration_step(batch_size)
+    })
+}
+
+/// Admin: progress of the heap-to-stable index migration
+#[query]
+fn get_index_migration_status() -> Result<MigrationStatus, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(migration::migration_status())
+    })
+}
+
+// ============================================================================
+// Maintenance Endpoints
+// ============================================================================
+
+/// Admin: run a long maintenance task in the background, chunked across timer
+/// messages; returns the already active job if the task is running
+#[update(guard = "terms_accepted")]
+fn start_maintenance(task: MaintenanceTask) -> Result<MaintenanceJob, String> {
+    trace::run(move || {
+        require_controller()?;
+        Ok(match task {
+            MaintenanceTask::IndexRebuild => index_rebuild::start(),
+            _ => maintenance::start(task),
+        })
+    })
+}
+
+/// Admin: recent maintenance jobs with their progress, newest first
+#[query]
+fn list_maintenance_jobs() -> Result<Vec<MaintenanceJob>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(maintenance::list_jobs())
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn cancel_maintenance_job(id: u64) -> Result<MaintenanceJob, String> {
+    trace::run(move || {
+        require_controller()?;
+        maintenance::cancel(id)
+    })
+}
+
+/// Admin: rebuild owner lists, search indices and counters from the
+/// availability and booking maps, chunked across timer messages
+#[update(guard = "terms_accepted")]
+fn rebuild_indices() -> Result<MaintenanceJob, String> {
+    trace::run(move || {
+        require_controller()?;
+        Ok(index_rebuild::start())
+    })
+}
+
+#[query]
+fn get_rebuild_progress() -> Result<RebuildStatus, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(index_rebuild::status())
+    })
+}
+
+/// Admin: background busy-time sync progress and any Google quota backoff
+#[query]
+fn get_sync_scheduler_status() -> Result<SyncSchedulerStatus, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(sync_scheduler::status())
+    })
+}
+
+/// Admin: token entries no lookup can reach (shadowed tokens, dangling links,
+/// stale fingerprints) and legacy entries still waiting to be re-keyed
+#[query]
+fn get_orphaned_tokens() -> Result<OrphanedTokenReport, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(tokens::orphaned_tokens())
+    })
+}
+
+/// Admin: remove the unreachable entries reported by get_orphaned_tokens
+#[update(guard = "terms_accepted")]
+fn cleanup_orphaned_tokens() -> Result<OrphanedTokenReport, String> {
+    trace::run(move || {
+        require_controller()?;
+        Ok(tokens::cleanup_orphaned_tokens())
+    })
+}
+
+// ============================================================================
+// Dev Fixture Endpoints (only built with the dev-fixtures feature)
+// ============================================================================
+
+/// Admin: generate deterministic demo users, availabilities and bookings
+#[cfg(feature = "dev-fixtures")]
+#[update(guard = "terms_accepted")]
+fn seed_demo_data(users: u32, availabilities: u32, bookings: u32) -> Result<SeedReport, String> {
+    trace::run(move || {
+        require_controller()?;
+        fixtures::seed_demo_data(users, availabilities, bookings)
+    })
+}
+
+/// Admin: wipe availabilities, bookings, tokens and pending side effects
+#[cfg(feature = "dev-fixtures")]
+#[update(guard = "terms_accepted")]
+fn reset_all_data() -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        fixtures::reset_all_data();
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Upstream Health Endpoints
+// ============================================================================
+
+/// Admin: circuit breaker state of each upstream provider
+#[query]
+fn get_upstream_status() -> Result<Vec<BreakerStatus>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(circuit_breaker::get_breaker_status())
+    })
+}
+
+/// Health of Google connectivity, timers and maintenance for the status
+/// banner; open to anyone, signed in or not
+#[query]
+fn get_service_status() -> ServiceStatus {
+    service_status::get_service_status()
+}
+
+/// Every stable error code clients can receive, for localizing messages
+#[query]
+fn get_error_catalog() -> Vec<ErrorCatalogEntry> {
+    error_codes::catalog()
+}
+
+/// Admin: exercise stable memory, timers, an outcall and the clock, for
+/// uptime monitors to poll
+#[update(guard = "terms_accepted")]
+async fn run_self_test() -> Result<SelfTestReport, String> {
+    trace::run_async(async move {
+        require_controller()?;
+        Ok(self_test::run().await)
+    }).await
+}
+
+/// Forget the caller's cached Google/Calendly responses so the next reads are fresh
+#[update(guard = "terms_accepted")]
+fn refresh_outcall_cache() -> u32 {
+    outcall_cache::clear_for(ic_cdk::caller())
+}
+
+// ============================================================================
+// Outbox Admin Endpoints
+// ============================================================================
+
+/// Admin: side effects that exhausted their retries, 50 per page
+#[query]
+fn list_failed_jobs(cursor: Option<u64>) -> Result<FailedJobsPage, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(outbox::list_failed_jobs(cursor))
+    })
+}
+
+/// Admin: re-queue a failed job with a fresh set of attempts
+#[update(guard = "terms_accepted")]
+fn retry_job(id: u64) -> Result<OutboxJob, String> {
+    trace::run(move || {
+        require_controller()?;
+        outbox::retry_job(id)
+    })
+}
+
+/// Admin: drop a failed job without running it
+#[update(guard = "terms_accepted")]
+fn discard_job(id: u64) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        outbox::discard_job(id)
+    })
+}
+
+// ============================================================================
+// Import API Endpoints
+// ============================================================================
+
+/// Turn Calendly event types into availabilities and upcoming events into
+/// bookings; `dry_run` previews the result without creating anything
+#[update(guard = "terms_accepted")]
+async fn import_from_calendly(api_token: String, dry_run: bool) -> Result<CalendlyImportReport, String> {
+    trace::run_async(async move {
+        calendly::import_from_calendly(ic_cdk::caller(), api_token, dry_run).await
+    }).await
+}
+
+#[query]
+fn transform_import_response(
+    args: ic_cdk::api::management_canister::http_request::TransformArgs,
+) -> ic_cdk::api::management_canister::http_request::HttpResponse {
+    calendly::transform_response(args)
+}
+
+// ============================================================================
+// Kiosk API Endpoints
+// ============================================================================
+
+/// Walk-up booking from a registered shared device: takes the next free slot
+/// starting at least the kiosk's lead time from now
+#[update(guard = "terms_accepted")]
+fn book_next_available(id: String, guest_name: String) -> Result<Booking, String> {
+    trace::run(move || {
+        kiosk::book_next_available(ic_cdk::caller(), id, guest_name)
+    })
+}
+
+// ============================================================================
+// Link Tracking API Endpoints
+// ============================================================================
+
+/// Called by the booking page when a one-time or session link is opened
+#[update]
+fn record_link_open(token: String) -> Result<(), String> {
+    trace::run(move || {
+        link_stats::record_open(ic_cdk::caller(), token)
+    })
+}
+
+/// How often one of your links was opened, to the nearest hour
+#[query]
+fn get_link_stats(token: String) -> Result<LinkStats, String> {
+    error_codes::run(move || {
+        link_stats::get_link_stats(ic_cdk::caller(), token)
+    })
+}
+
+// ============================================================================
+// Analytics API Endpoints
+// ============================================================================
+
+/// Report a product event; returns false when the caller isn't sampled
+#[update]
+fn track_event(name: String, props: Vec<EventProp>) -> Result<bool, String> {
+    trace::run(move || {
+        analytics::track_event(ic_cdk::caller(), name, props)
+    })
+}
+
+/// Admin: daily event counts between `from` and `to` (nanoseconds)
+#[query]
+fn get_event_counts(name: Option<String>, from: u64, to: u64) -> Result<Vec<EventCount>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(analytics::event_counts(name, from, to))
+    })
+}
+
+#[query]
+fn get_analytics_config() -> Result<AnalyticsConfig, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(analytics::get_config())
+    })
+}
+
+/// Admin: sampling rate and retention of tracked events
+#[update(guard = "terms_accepted")]
+fn set_analytics_config(config: AnalyticsConfig) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        analytics::set_config(config)
+    })
+}
+
+// ============================================================================
+// Platform Stats API Endpoints
+// ============================================================================
+
+/// Aggregate numbers for the public stats page, read from counters
+#[query]
+fn get_platform_stats() -> PlatformStats {
+    counters::platform_stats()
+}
+
+/// How many availabilities and bookings the caller has
+#[query]
+fn get_my_counters() -> CounterSet {
+    counters::counts(&CounterScope::User(ic_cdk::caller()))
+}
+
+#[query]
+fn get_org_counters(tenant_id: String) -> Result<CounterSet, String> {
+    error_codes::run(move || {
+        tenants::require_tenant_admin(ic_cdk::caller(), &tenant_id)?;
+        Ok(counters::counts(&CounterScope::Org(tenant_id)))
+    })
+}
+
+// ============================================================================
+// QR Code API Endpoints
+// ============================================================================
+
+/// SVG QR code of an availability's share link on `origin` (default weeekaly.com)
+#[query]
+fn get_availability_qr_svg(id: String, origin: Option<String>) -> Result<String, String> {
+    error_codes::run(move || {
+        qr::get_availability_qr_svg(id, origin)
+    })
+}
+
+// ============================================================================
+// HTTP Gateway & API Key Endpoints
+// ============================================================================
+
+/// HTTP gateway entry point: QR codes at /qr/<availability_id>.svg; /api/
+/// routes are upgraded to http_request_update
+#[query]
+fn http_request(req: HttpRequest) -> HttpResponse {
+    http::http_request(req)
+}
+
+/// JSON API authenticated with API keys
+#[update]
+fn http_request_update(req: HttpRequest) -> HttpResponse {
+    http::http_request_update(req)
+}
+
+/// Create a key for the HTTP API; the returned secret is shown only once
+#[update(guard = "terms_accepted")]
+fn create_api_key(req: CreateApiKeyRequest) -> Result<CreatedApiKey, String> {
+    trace::run(move || {
+        api_keys::create_api_key(ic_cdk::caller(), req)
+    })
+}
+
+#[query]
+fn list_my_api_keys() -> Vec<ApiKey> {
+    api_keys::list_api_keys(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn revoke_api_key(id: String) -> Result<(), String> {
+    trace::run(move || {
+        api_keys::revoke_api_key(ic_cdk::caller(), id)
+    })
+}
+
+// ============================================================================
+// Earnings & Payout API Endpoints
+// ============================================================================
+
+/// Booking payments held by the canister on the caller's behalf, per ledger
+#[query]
+fn get_earnings_balance() -> Vec<EarningsBalance> {
+    earnings::get_balances(ic_cdk::caller())
+}
+
+#[query]
+fn list_my_earnings_entries() -> Vec<JournalEntry> {
+    earnings::list_entries(ic_cdk::caller())
+}
+
+/// Pay out earnings to an ICRC-1 account; returns the ledger block index
+#[update(guard = "terms_accepted")]
+async fn withdraw(ledger_canister: Principal, amount: u64, to_account: Account) -> Result<u64, String> {
+    trace::run_async(async move {
+        earnings::withdraw(ic_cdk::caller(), ledger_canister, amount, to_account).await
+    }).await
+}
+
+// ============================================================================
+// Tax & Receipt API Endpoints
+// ============================================================================
+
+#[query]
+fn get_my_tax_profile() -> Option<TaxProfile> {
+    invoicing::get_tax_profile(ic_cdk::caller())
+}
+
+/// Business details and tax rate applied to the caller's future paid bookings
+#[update(guard = "terms_accepted")]
+fn set_my_tax_profile(req: SetTaxProfileRequest) -> Result<TaxProfile, String> {
+    trace::run(move || {
+        invoicing::set_tax_profile(ic_cdk::caller(), req)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn clear_my_tax_profile() {
+    invoicing::clear_tax_profile(ic_cdk::caller())
+}
+
+#[query]
+fn get_booking_receipt(booking_id: String) -> Result<Receipt, String> {
+    error_codes::run(move || {
+        invoicing::get_receipt(ic_cdk::caller(), booking_id)
+    })
+}
+
+/// Everything stored about the caller, including their terms acceptances
+#[query]
+fn export_my_data() -> MyDataExport {
+    data_export::export_my_data(ic_cdk::caller())
+}
+
+/// Paid bookings on the caller's availabilities as CSV
+#[query]
+fn export_my_bookings_csv() -> String {
+    invoicing::export_bookings_csv(ic_cdk::caller())
+}
+
+// ============================================================================
+// Terms & Consent API Endpoints
+// ============================================================================
+
+/// Current terms and whether the caller still has to accept them
+#[query]
+fn get_terms_status() -> TermsStatus {
+    terms::get_status(ic_cdk::caller())
+}
+
+#[update]
+fn accept_terms(version: u32) -> Result<TermsAcceptance, String> {
+    trace::run(move || {
+        terms::accept(ic_cdk::caller(), version)
+    })
+}
+
+/// Admin: publish a new terms version; users must accept it before their
+/// next state-changing call
+#[update]
+fn publish_terms(version: u32, terms_url: String, privacy_url: String) -> Result<TermsConfig, String> {
+    trace::run(move || {
+        require_controller()?;
+        terms::publish(version, terms_url, privacy_url)
+    })
+}
+
+// ============================================================================
+// Region Gating API Endpoints
+// ============================================================================
+
+/// Age and region rules the caller's org applies to gated features
+#[query]
+fn get_my_region_policy() -> Option<RegionPolicy> {
+    regions::get_policy(tenants::tenant_of(ic_cdk::caller()))
+}
+
+#[query]
+fn get_my_region() -> Option<RegionDeclaration> {
+    regions::get_declaration(ic_cdk::caller())
+}
+
+/// Self-declare the caller's region (ISO 3166-1 alpha-2) and whether they are of age
+#[update(guard = "terms_accepted")]
+fn set_my_region(region: String, is_adult: bool) -> Result<RegionDeclaration, String> {
+    trace::run(move || {
+        regions::declare(ic_cdk::caller(), region, is_adult)
+    })
+}
+
+/// Org admin: gate features such as payments by declared region and age
+#[update(guard = "terms_accepted")]
+fn set_region_policy(req: SetRegionPolicyRequest) -> Result<RegionPolicy, String> {
+    trace::run(move || {
+        regions::set_policy(ic_cdk::caller(), req)
+    })
+}
+
+// ============================================================================
+// White-label Origin API Endpoints
+// ============================================================================
+
+/// Branding and feature settings for the frontend served at `origin`
+#[query]
+fn get_origin_config(origin: String) -> OriginConfig {
+    origins::get_origin_config(&origin)
+}
+
+#[query]
+fn list_origin_configs() -> Result<Vec<OriginConfig>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(origins::list_origin_configs())
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_origin_config(req: SetOriginConfigRequest) -> Result<OriginConfig, String> {
+    trace::run(move || {
+        require_controller()?;
+        let providers: Vec<String> = PROVIDERS.with(|p| p.borrow().keys().cloned().collect());
+        origins::set_origin_config(req, &providers)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_origin_config(origin: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        origins::delete_origin_config(&origin)
+    })
+}
+
+/// Make users signing in on a retired origin keep the principals they get on
+/// `canonical` (after a domain move). Records under their old principals move
+/// over at their next sign-in, or via migrate_principal.
+#[update(guard = "terms_accepted")]
+fn set_origin_alias(retired: String, canonical: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        origins::set_origin_alias(&retired, &canonical)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_origin_alias(retired: String) -> Result<(), String> {
+    trace::run(move || {
+        require_controller()?;
+        origins::delete_origin_alias(&retired)
+    })
+}
+
+/// (retired origin, canonical origin) pairs
+#[query]
+fn list_origin_aliases() -> Result<Vec<(String, String)>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(origins::list_origin_aliases())
+    })
+}
+
+/// Move every record of `from` to `to` once (users who haven't signed in
+/// since their origin was aliased)
+#[update(guard = "terms_accepted")]
+fn migrate_principal(from: Principal, to: Principal) -> Result<PrincipalMove, String> {
+    trace::run(move || {
+        require_controller()?;
+        principal_moves::migrate(from, to)
+    })
+}
+
+#[query]
+fn list_principal_moves() -> Result<Vec<PrincipalMove>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(principal_moves::list_moves())
+    })
+}
+
+// ============================================================================
+// Tenant API Endpoints
+// ============================================================================
+
+/// Tenant the caller belongs to ("default" unless they signed in on a tenant origin)
+#[query]
+fn get_my_tenant() -> String {
+    tenants::tenant_of(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn create_tenant(id: String, name: String, admins: Vec<Principal>) -> Result<Tenant, String> {
+    trace::run(move || {
+        require_controller()?;
+        tenants::create_tenant(id, name, admins)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn set_tenant_admins(id: String, admins: Vec<Principal>) -> Result<Tenant, String> {
+    trace::run(move || {
+        require_controller()?;
+        tenants::set_tenant_admins(id, admins)
+    })
+}
+
+#[query]
+fn list_tenants() -> Result<Vec<Tenant>, String> {
+    error_codes::run(move || {
+        require_controller()?;
+        Ok(tenants::list_tenants())
+    })
+}
+
+#[query]
+fn list_tenant_users(tenant_id: String) -> Result<Vec<TenantMember>, String> {
+    error_codes::run(move || {
+        tenants::require_tenant_admin(ic_cdk::caller(), &tenant_id)?;
+        Ok(tenants::list_members(&tenant_id))
+    })
+}
+
+#[query]
+fn list_tenant_availabilities(tenant_id: String) -> Result<Vec<Availability>, String> {
+    error_codes::run(move || {
+        tenants::require_tenant_admin(ic_cdk::caller(), &tenant_id)?;
+        Ok(response_size::clamp(availabilities::list_tenant_availabilities(&tenant_id), "list_tenant_availabilities"))
+    })
+}
+
+/// Defaults new member availabilities start from; enforced settings are
+/// applied to every member availability and cannot be overridden
+#[update(guard = "terms_accepted")]
+fn set_org_defaults(tenant_id: String, req: SetOrgDefaultsRequest) -> Result<OrgDefaults, String> {
+    trace::run(move || {
+        org_defaults::set_defaults(ic_cdk::caller(), tenant_id, req)
+    })
+}
+
+/// Defaults of the caller's org (None = none set)
+#[query]
+fn get_org_defaults() -> Option<OrgDefaults> {
+    org_defaults::get_defaults(ic_cdk::caller())
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_org_defaults(tenant_id: String) -> Result<(), String> {
+    trace::run(move || {
+        org_defaults::delete_defaults(ic_cdk::caller(), tenant_id)
+    })
+}
+
+/// Tenant admins: stop a member's bookings from being deleted until lifted
+#[update(guard = "terms_accepted")]
+fn place_legal_hold(tenant_id: String, member: Principal, reason: String) -> Result<LegalHold, String> {
+    trace::run(move || {
+        legal_hold::place_hold(ic_cdk::caller(), tenant_id, member, reason)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn lift_legal_hold(tenant_id: String, member: Principal) -> Result<LegalHold, String> {
+    trace::run(move || {
+        legal_hold::lift_hold(ic_cdk::caller(), tenant_id, member)
+    })
+}
+
+/// Tenant admins: current and lifted holds on the org's members
+#[query]
+fn list_legal_holds(tenant_id: String) -> Result<Vec<LegalHold>, String> {
+    error_codes::run(move || {
+        legal_hold::list_holds(ic_cdk::caller(), tenant_id)
+    })
+}
+
+/// An org's availabilities one page at a time (tenant admins)
+#[query]
+fn list_tenant_availabilities_page(tenant_id: String, cursor: Option<String>) -> Result<AvailabilityPage, String> {
+    error_codes::run(move || {
+        tenants::require_tenant_admin(ic_cdk::caller(), &tenant_id)?;
+        availabilities::list_tenant_availabilities_page(&tenant_id, cursor)
+    })
+}
+
+#[update(guard = "terms_accepted")]
+fn delete_tenant_availability(tenant_id: String, id: String) -> Result<(), String> {
+    trace::run(move || {
+        tenants::require_tenant_admin(ic_cdk::caller(), &tenant_id)?;
+        availabilities::delete_tenant_availability(&tenant_id, id)
+    })
+}
+
+// ============================================================================
+// Localization API Endpoints
+// ============================================================================
+
+#[query]
+fn get_my_locale() -> Option<Locale> {
+    i18n::get_user_locale(ic_cdk::caller())
+}
+
+/// Default language for text generated about the caller's availabilities
+#[update(guard = "terms_accepted")]
+fn set_my_locale(locale: Locale) {
+    i18n::set_user_locale(ic_cdk::caller(), locale)
+}
+
+#[query]
+fn get_my_hijri_annotation() -> bool {
+    i18n::wants_hijri(ic_cdk::caller())
+}
+
+/// Annotate dates in text generated for the caller with the Hijri date
+#[update(guard = "terms_accepted")]
+fn set_my_hijri_annotation(enabled: bool) {
+    i18n::set_hijri_annotation(ic_cdk::caller(), enabled)
+}
+
+// ============================================================================
+// API v2 Endpoints
+// ============================================================================
+
+/// Newest API version served and the oldest still supported, so a frontend
+/// can tell whether its `v2_*` calls will be answered
+#[query]
+fn api_version() -> ApiVersionInfo {
+    api_v2::api_version()
+}
+
+#[query]
+fn v2_get_availability(id: String, origin: Option<String>) -> Result<Availability, ApiError> {
+    api_v2::get_availability(ic_cdk::caller(), id, origin)
+}
+
+#[query]
+fn v2_get_free_slots(
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+    origin: Option<String>,
+) -> Result<Vec<FreeSlot>, ApiError> {
+    api_v2::get_free_slots(ic_cdk::caller(), id, range_start, range_end, duration_minutes, access_code, origin)
+}
+
+#[update(guard = "terms_accepted")]
+fn v2_create_booking(req: CreateBookingRequest) -> Result<Booking, ApiError> {
+    api_v2::create_booking(ic_cdk::caller(), req)
+}
+
+#[query]
+fn v2_get_booking(id: String) -> Result<Booking, ApiError> {
+    api_v2::get_booking(ic_cdk::caller(), id)
+}
+
+ic_cdk_macros::export_candid!();
+
+#[cfg(test)]
+mod tests {
+    use candid_parser::utils::{service_compatible, service_equal, CandidSource};
+
+    /// Interface of the last mainnet release, refreshed by `make deploy-ic`
+    const RELEASED_DID: &str = include_str!("../candid/released.did");
+    /// Checked-in interface the frontend declarations are generated from
+    const SNAPSHOT_DID: &str = include_str!("../backend.did");
+    /// Read-cache canister, which replays this canister's get_availability replies
+    const READ_CACHE_DID: &str = include_str!("../../read_cache/read_cache.did");
+
+    /// The types of `did` under a service offering only its get_availability
+    fn get_availability_only(did: &str) -> String {
+        let (types, service) = did.split_once("\nservice").unwrap();
+        let method = service.lines().find(|line| line.trim_start().starts_with("get_availability :")).unwrap();
+        format!("{}\nservice : {{\n{}\n}}\n", types, method)
+    }
+
+    #[test]
+    fn interface_is_backward_compatible_with_last_release() {
+        let current = super::__export_service();
+        if let Err(e) = service_compatible(CandidSource::Text(&current), CandidSource::Text(RELEASED_DID)) {
+            panic!(
+                "The Candid interface changed incompatibly since the last release, which breaks \
+                 deployed frontends and integrators. Add a new method or optional field instead.\n{}",
+                e
+            );
+        }
+    }
+
+    #[test]
+    fn checked_in_did_matches_the_code() {
+        let current = super::__export_service();
+        if let Err(e) = service_equal(CandidSource::Text(&current), CandidSource::Text(SNAPSHOT_DID)) {
+            panic!("src/backend/backend.did is out of date; run scripts/did.sh and dfx generate backend.\n{}", e);
+        }
+    }
+
+    #[test]
+    fn read_cache_replies_like_get_availability() {
+        let current = super::__export_service();
+        let backend = get_availability_only(&current);
+        let cache = get_availability_only(READ_CACHE_DID);
+        if let Err(e) = service_equal(CandidSource::Text(&cache), CandidSource::Text(&backend)) {
+            panic!("Copy the Availability types from src/backend/backend.did into src/read_cache/read_cache.did.\n{}", e);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::availabilities::{self, CollaboratorRole};
+use crate::{one_time_links, quarantine, session_links};
+use crate::error_codes::{self, ErrorCode};
+
+/// Open times are stored rounded down to the hour so they can't fingerprint a visitor
+const BUCKET_NANOS: u64 = 60 * 60 * 1_000_000_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// How often a shared link was opened. No IPs or visitor identities are kept.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LinkStats {
+    pub open_count: u64,
+    pub first_opened_at: Option<u64>, // Nanoseconds, rounded down to the hour
+    pub last_opened_at: Option<u64>,  // Nanoseconds, rounded down to the hour
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for LinkStats {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "LinkStats").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Link token -> open counters
+    static LINK_STATS: RefCell<StableBTreeMap<String, LinkStats, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    LINK_STATS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Owner of a trackable link (one-time or paid session link)
+fn link_owner(token: &str) -> Option<Principal> {
+    one_time_links::owner_of(token).or_else(|| session_links::owner_of(token))
+}
+
+// ============================================================================
+// Tracking
+// ============================================================================
+
+/// Count an open of the link. Opens by the owner themselves are ignored.
+pub fn record_open(caller: Principal, token: String) -> Result<(), String> {
+    let owner = link_owner(&token).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Link not found"))?;
+    if caller == owner {
+        return Ok(());
+    }
+
+    let bucket = time() / BUCKET_NANOS * BUCKET_NANOS;
+    LINK_STATS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut stats = map.get(&token).unwrap_or_default();
+        stats.open_count += 1;
+        stats.first_opened_at.get_or_insert(bucket);
+        stats.last_opened_at = Some(bucket);
+        map.insert(token, stats);
+    });
+    Ok(())
+}
+
+/// Open counters for a link, for its owner and Viewers of its availability
+pub fn get_link_stats(caller: Principal, token: String) -> Result<LinkStats, String> {
+    let viewer = one_time_links::availability_of(&token)
+        .or_else(|| session_links::availability_of(&token))
+        .and_then(|id| availabilities::get_availability(id).ok())
+        .is_some_and(|a| availabilities::has_role(&a, caller, CollaboratorRole::Viewer));
+    if link_owner(&token) != Some(caller) && !viewer {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Link not found"));
+    }
+    Ok(LINK_STATS.with(|s| s.borrow().get(&token)).unwrap_or_default())
+}
+
+/// Forget a link's counters once the link itself is deleted
+pub fn remove(token: &str) {
+    LINK_STATS.with(|s| s.borrow_mut().remove(&token.to_string()));
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    LINK_STATS.with(|m| storage::map_stats(&m.borrow()))
+}
@@ -0,0 +1,250 @@
+//! Long-running maintenance work split into chunks that each fit one
+//! message's instruction limit. Jobs are queued in stable memory and driven
+//! by timers, so they resume after an upgrade and report their progress.
+
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::migration::{self, MigrationPhase};
+use crate::{index_rebuild, quarantine, tokens};
+use crate::error_codes::{self, ErrorCode};
+
+/// Stop starting new chunks past this many instructions; a timer message may
+/// use 40B, so a chunk that starts just under the budget still fits
+const INSTRUCTION_BUDGET: u64 = 10_000_000_000;
+const MIGRATION_CHUNK: u32 = 500;
+const TOKEN_REKEY_CHUNK: usize = 500;
+const INDEX_REBUILD_CHUNK: usize = 500;
+const JOBS_LISTED: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Work that runs in chunks; each variant knows how to do its next chunk
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MaintenanceTask {
+    IndexMigration,
+    TokenRekey,     // Move text-keyed USER_TOKENS entries to the principal-keyed store
+    IndexRebuild,   // Owner lists, search indices and counters (start via rebuild_indices)
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MaintenanceStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct MaintenanceJob {
+    pub id: u64,
+    pub task: MaintenanceTask,
+    pub status: MaintenanceStatus,
+    pub chunks: u64,      // Chunks run so far
+    pub processed: u64,   // Items handled so far, as counted by the task
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// What one chunk did
+struct ChunkOutcome {
+    processed: u64,
+    done: bool,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl MaintenanceJob {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Failed, so the runner never picks it up.
+    fn unreadable() -> Self {
+        MaintenanceJob {
+            id: 0,
+            task: MaintenanceTask::IndexMigration,
+            status: MaintenanceStatus::Failed,
+            chunks: 0,
+            processed: 0,
+            last_error: Some("Unreadable record".to_string()),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for MaintenanceJob {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "MaintenanceJob").unwrap_or_else(MaintenanceJob::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static MAINTENANCE_JOBS: RefCell<StableBTreeMap<u64, MaintenanceJob, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(50)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// True while the current message has room for another chunk
+pub fn within_budget() -> bool {
+    ic_cdk::api::instruction_counter() < INSTRUCTION_BUDGET
+}
+
+fn is_active(job: &MaintenanceJob) -> bool {
+    matches!(job.status, MaintenanceStatus::Queued | MaintenanceStatus::Running)
+}
+
+fn save(job: &MaintenanceJob) {
+    MAINTENANCE_JOBS.with(|j| j.borrow_mut().insert(job.id, job.clone()));
+}
+
+fn next_active() -> Option<MaintenanceJob> {
+    MAINTENANCE_JOBS.with(|j| j.borrow().iter().map(|(_, job)| job).find(is_active))
+}
+
+/// Whether a job is queued or running
+pub fn is_running() -> bool {
+    next_active().is_some()
+}
+
+fn run_chunk(task: MaintenanceTask) -> Result<ChunkOutcome, String> {
+    match task {
+        MaintenanceTask::IndexMigration => {
+            let before = migration::migration_status();
+            let after = migration::run_migration_step(Some(MIGRATION_CHUNK))?;
+            Ok(ChunkOutcome {
+                processed: (after.indexed_availabilities + after.copied_sessions)
+                    - (before.indexed_availabilities + before.copied_sessions),
+                done: after.phase == MigrationPhase::Done,
+            })
+        }
+        MaintenanceTask::TokenRekey => {
+            let (processed, done) = tokens::rekey_legacy(TOKEN_REKEY_CHUNK);
+            Ok(ChunkOutcome { processed, done })
+        }
+        MaintenanceTask::IndexRebuild => {
+            let (processed, done) = index_rebuild::run_step(INDEX_REBUILD_CHUNK);
+            Ok(ChunkOutcome { processed, done })
+        }
+    }
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// The queued or running job of a task, if any
+pub fn find_active(task: MaintenanceTask) -> Option<MaintenanceJob> {
+    MAINTENANCE_JOBS.with(|j| {
+        j.borrow().iter().map(|(_, job)| job).find(|job| job.task == task && is_active(job))
+    })
+}
+
+/// Queue a task; if the same task is already queued or running, that job is returned
+pub fn start(task: MaintenanceTask) -> MaintenanceJob {
+    if let Some(job) = find_active(task) {
+        return job;
+    }
+
+    let now = time();
+    let id = MAINTENANCE_JOBS.with(|j| j.borrow().last_key_value().map(|(id, _)| id + 1).unwrap_or(0));
+    let job = MaintenanceJob {
+        id,
+        task,
+        status: MaintenanceStatus::Queued,
+        chunks: 0,
+        processed: 0,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    save(&job);
+    ic_cdk::println!("🛠️ [maintenance] Queued {:?} as job {}", task, id);
+    ic_cdk_timers::set_timer(Duration::ZERO, process);
+    job
+}
+
+/// Stop a queued or running job after its current chunk
+pub fn cancel(id: u64) -> Result<MaintenanceJob, String> {
+    let mut job = MAINTENANCE_JOBS.with(|j| j.borrow().get(&id))
+        .filter(is_active)
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Active maintenance job not found"))?;
+    job.status = MaintenanceStatus::Cancelled;
+    job.updated_at = time();
+    save(&job);
+    Ok(job)
+}
+
+/// Most recent jobs first
+pub fn list_jobs() -> Vec<MaintenanceJob> {
+    MAINTENANCE_JOBS.with(|j| j.borrow().iter().rev().take(JOBS_LISTED).map(|(_, job)| job).collect())
+}
+
+/// Resume unfinished jobs (timers don't survive upgrades, so this runs from
+/// both init and post_upgrade)
+pub fn start_timer() {
+    if next_active().is_some() {
+        ic_cdk_timers::set_timer(Duration::ZERO, process);
+    }
+}
+
+/// Run chunks oldest job first until the budget is spent, then hand the rest
+/// to a fresh message
+fn process() {
+    while within_budget() {
+        let Some(mut job) = next_active() else {
+            return;
+        };
+        let outcome = run_chunk(job.task);
+        job.chunks += 1;
+        job.updated_at = time();
+        match outcome {
+            Ok(outcome) => {
+                job.processed += outcome.processed;
+                job.status = if outcome.done { MaintenanceStatus::Completed } else { MaintenanceStatus::Running };
+                if outcome.done {
+                    ic_cdk::println!(
+                        "✅ [maintenance] Job {} ({:?}) completed: {} items in {} chunks",
+                        job.id, job.task, job.processed, job.chunks
+                    );
+                }
+            }
+            Err(e) => {
+                ic_cdk::println!("❌ [maintenance] Job {} ({:?}) failed: {}", job.id, job.task, e);
+                job.status = MaintenanceStatus::Failed;
+                job.last_error = Some(e);
+            }
+        }
+        save(&job);
+    }
+    ic_cdk_timers::set_timer(Duration::ZERO, process);
+}
@@ -0,0 +1,238 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, TransformContext,
+};
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, datetime, delivery_log, principal_moves, quarantine};
+use crate::delivery_log::NotificationChannel;
+use crate::bookings::{self, Booking};
+use crate::integrations::{self, HookEvent};
+use crate::outbox::{self, SideEffect};
+use crate::plans::{self, Feature};
+use crate::templates::{self, TemplateKind};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Posts booking notifications into a Matrix room through the client-server
+/// API, using an access token of a bot account the owner created
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MatrixNotifier {
+    pub homeserver: String,        // e.g. https://matrix.org
+    pub room_id: String,           // e.g. !abc123:matrix.org
+    pub access_token: String,      // Never returned to clients (blank in responses)
+    pub events: Vec<HookEvent>,
+    pub created_at: u64,
+    pub last_delivery_at: Option<u64>,
+    pub last_status: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct SetMatrixNotifierRequest {
+    pub homeserver: String,
+    pub room_id: String,
+    pub access_token: String,
+    pub events: Vec<HookEvent>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for MatrixNotifier {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "MatrixNotifier").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Owner -> their Matrix room
+    static MATRIX_NOTIFIERS: RefCell<StableBTreeMap<Principal, MatrixNotifier, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(39)))
+        )
+    );
+}
+
+pub fn clear() {
+    MATRIX_NOTIFIERS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn validate_request(req: &SetMatrixNotifierRequest) -> Result<(), String> {
+    if !req.homeserver.starts_with("https://") || req.homeserver.len() > 200 {
+        return Err("homeserver must be an https URL up to 200 characters".to_string());
+    }
+    if !req.room_id.starts_with('!') || !req.room_id.contains(':') || req.room_id.len() > 255 {
+        return Err("room_id must look like !room:server (use the internal ID, not an alias)".to_string());
+    }
+    if req.access_token.trim().is_empty() || req.access_token.len() > 500 {
+        return Err("access_token is required".to_string());
+    }
+    if req.events.is_empty() {
+        return Err("at least one event is required".to_string());
+    }
+    Ok(())
+}
+
+fn redacted(mut notifier: MatrixNotifier) -> MatrixNotifier {
+    notifier.access_token = String::new();
+    notifier
+}
+
+fn message(event: HookEvent, booking: &Booking) -> String {
+    let kind = match event {
+        HookEvent::BookingCreated => TemplateKind::BookingCreated,
+        HookEvent::BookingCancelled => TemplateKind::BookingCancelled,
+    };
+    if let Some(rendered) = templates::render(booking, kind) {
+        return format!("{}\n\n{}", rendered.subject, rendered.body);
+    }
+    let title = availabilities::get_availability(booking.availability_id.clone())
+        .map(|a| a.title)
+        .unwrap_or_default();
+    let start = datetime::format_iso8601(booking.start_time);
+    let verb = match event {
+        HookEvent::BookingCreated => "📅 New booking",
+        HookEvent::BookingCancelled => "❌ Booking cancelled",
+    };
+    format!(
+        "{}: {} with {} at {} ({} min)",
+        verb,
+        title,
+        booking.guest_name,
+        start,
+        (booking.end_time - booking.start_time) / 60,
+    )
+}
+
+/// Post one booking event to the owner's room. Errors are retried by the outbox.
+pub async fn deliver(owner: Principal, event: HookEvent, booking_id: String) -> Result<(), String> {
+    let Some(notifier) = MATRIX_NOTIFIERS.with(|m| m.borrow().get(&owner)) else {
+        return Ok(());
+    };
+    let Some(booking) = bookings::find(&booking_id) else {
+        return Ok(());
+    };
+    // Canister outcalls can't PUT, so this uses the POST form of the send
+    // endpoint (no transaction ID), which Synapse and Conduit both accept
+    let url = format!(
+        "{}/_matrix/client/v3/rooms/{}/send/m.room.message",
+        notifier.homeserver.trim_end_matches('/'),
+        urlencoding::encode(&notifier.room_id),
+    );
+    let body = serde_json::json!({ "msgtype": "m.text", "body": message(event, &booking) });
+    let request = CanisterHttpRequestArgument {
+        url,
+        method: HttpMethod::POST,
+        body: Some(body.to_string().into_bytes()),
+        max_response_bytes: Some(1024),
+        transform: Some(TransformContext::from_name("transform_hook_response".to_string(), vec![])),
+        headers: vec![
+            HttpHeader { name: "Content-Type".to_string(), value: "application/json".to_string() },
+            HttpHeader { name: "Authorization".to_string(), value: format!("Bearer {}", notifier.access_token) },
+        ],
+    };
+
+    let result = match http_request(request, 25_000_000_000).await {
+        Ok((response,)) if integrations::is_success(&response.status) => Ok(response.status.to_string()),
+        Ok((response,)) => Err(format!("HTTP {}", response.status)),
+        Err((code, msg)) => Err(format!("{:?}: {}", code, msg)),
+    };
+
+    delivery_log::record(&booking.id, NotificationChannel::Matrix, None, event, &result);
+    MATRIX_NOTIFIERS.with(|m| {
+        let mut map = m.borrow_mut();
+        if let Some(mut stored) = map.get(&owner) {
+            stored.last_delivery_at = Some(time());
+            stored.last_status = Some(result.clone().unwrap_or_else(|e| e));
+            map.insert(owner, stored);
+        }
+    });
+    result.map(|_| ())
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+pub fn is_subscribed(owner: Principal, event: HookEvent) -> bool {
+    MATRIX_NOTIFIERS.with(|m| m.borrow().get(&owner)).is_some_and(|n| n.events.contains(&event))
+}
+
+/// Queue the event for the booking owner's room, if they subscribed to it.
+/// Called from `integrations::notify`.
+pub fn notify(event: HookEvent, booking: &Booking) {
+    if is_subscribed(booking.owner, event) {
+        outbox::enqueue(SideEffect::Matrix { owner: booking.owner, event, booking_id: booking.id.clone() });
+    }
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn set_matrix_notifier(caller: Principal, req: SetMatrixNotifierRequest) -> Result<MatrixNotifier, String> {
+    plans::require_feature(caller, Feature::Webhooks)?;
+    validate_request(&req)?;
+
+    let notifier = MatrixNotifier {
+        homeserver: req.homeserver,
+        room_id: req.room_id,
+        access_token: req.access_token,
+        events: req.events,
+        created_at: time(),
+        last_delivery_at: None,
+        last_status: None,
+    };
+    MATRIX_NOTIFIERS.with(|m| m.borrow_mut().insert(caller, notifier.clone()));
+
+    ic_cdk::println!("💬 Matrix notifications set up for {}", caller.to_text());
+    Ok(redacted(notifier))
+}
+
+pub fn get_matrix_notifier(caller: Principal) -> Option<MatrixNotifier> {
+    MATRIX_NOTIFIERS.with(|m| m.borrow().get(&caller)).map(redacted)
+}
+
+pub fn delete_matrix_notifier(caller: Principal) -> Result<(), String> {
+    MATRIX_NOTIFIERS.with(|m| m.borrow_mut().remove(&caller))
+        .map(|_| ())
+        .ok_or_else(|| "No Matrix notifier configured".to_string())
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    MATRIX_NOTIFIERS.with(|m| principal_moves::count_keyed(&m.borrow(), owner))
+}
+
+/// Carry `from`'s notifier over to `to` unless `to` has one of its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    MATRIX_NOTIFIERS.with(|m| principal_moves::move_keyed(&mut m.borrow_mut(), from, to))
+}
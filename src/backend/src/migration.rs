@@ -0,0 +1,265 @@
+//! Soft migration of the heap-only indices (email/username search and OAuth
+//! sessions) into stable memory. New writes go to both copies right away;
+//! existing entries are copied in admin-triggered chunks small enough to fit
+//! one message's instruction limit. Reads prefer the stable copy and fall back
+//! to the heap, so results are correct at every point of the migration.
+
+use candid::{CandidType, Encode, Principal};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use std::ops::Bound as RangeBound;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, AVAILABILITIES, EMAIL_TO_PRINCIPAL, USERNAME_TO_PRINCIPAL};
+use crate::{SessionData, SESSIONS};
+use crate::{principal_moves, quarantine};
+
+const DEFAULT_BATCH_SIZE: u32 = 500;
+const MAX_BATCH_SIZE: u32 = 5_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum MigrationPhase {
+    #[default]
+    NotStarted,
+    SearchIndices, // Rebuilt from AVAILABILITIES, since the heap copy is empty after every upgrade
+    Sessions,
+    Done,
+}
+
+/// Progress of the index migration, persisted so it resumes across calls and upgrades
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MigrationStatus {
+    pub phase: MigrationPhase,
+    pub availability_cursor: Option<String>,  // Last availability ID indexed
+    pub session_cursor: Option<Vec<u8>>,      // Last session key copied
+    pub indexed_availabilities: u64,
+    pub copied_sessions: u64,
+    pub started_at: Option<u64>,
+    pub completed_at: Option<u64>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for MigrationStatus {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "MigrationStatus").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Same keys as the heap EMAIL_TO_PRINCIPAL / USERNAME_TO_PRINCIPAL ("tenant|value")
+    static EMAIL_INDEX: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(46)))
+        )
+    );
+
+    static USERNAME_INDEX: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(47)))
+        )
+    );
+
+    // Session public key -> session, mirroring the heap SESSIONS
+    static STABLE_SESSIONS: RefCell<StableBTreeMap<Vec<u8>, SessionData, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(48)))
+        )
+    );
+
+    static MIGRATION_STATUS: RefCell<StableCell<MigrationStatus, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(49))),
+            MigrationStatus::default(),
+        ).expect("Failed to init migration status")
+    );
+}
+
+// ============================================================================
+// Dual Writes and Reads
+// ============================================================================
+
+pub fn index_email(key: String, owner: Principal) {
+    EMAIL_TO_PRINCIPAL.with(|e| e.borrow_mut().insert(key.clone(), owner));
+    EMAIL_INDEX.with(|e| e.borrow_mut().insert(key, owner));
+}
+
+pub fn index_username(key: String, owner: Principal) {
+    USERNAME_TO_PRINCIPAL.with(|u| u.borrow_mut().insert(key.clone(), owner));
+    USERNAME_INDEX.with(|u| u.borrow_mut().insert(key, owner));
+}
+
+pub fn lookup_email(key: &str) -> Option<Principal> {
+    EMAIL_INDEX.with(|e| e.borrow().get(&key.to_string()))
+        .or_else(|| EMAIL_TO_PRINCIPAL.with(|e| e.borrow().get(key).cloned()))
+}
+
+pub fn lookup_username(key: &str) -> Option<Principal> {
+    USERNAME_INDEX.with(|u| u.borrow().get(&key.to_string()))
+        .or_else(|| USERNAME_TO_PRINCIPAL.with(|u| u.borrow().get(key).cloned()))
+}
+
+pub fn insert_session(key: Vec<u8>, session: SessionData) {
+    SESSIONS.with(|s| s.borrow_mut().insert(key.clone(), session.clone()));
+    STABLE_SESSIONS.with(|s| s.borrow_mut().insert(key, session));
+}
+
+pub fn get_session(key: &[u8]) -> Option<SessionData> {
+    STABLE_SESSIONS.with(|s| s.borrow().get(&key.to_vec()))
+        .or_else(|| SESSIONS.with(|s| s.borrow().get(key).cloned()))
+}
+
+/// Remove from both copies; true if either had it
+pub fn remove_session(key: &[u8]) -> bool {
+    let heap = SESSIONS.with(|s| s.borrow_mut().remove(key)).is_some();
+    let stable = STABLE_SESSIONS.with(|s| s.borrow_mut().remove(&key.to_vec())).is_some();
+    heap || stable
+}
+
+/// Drop expired sessions from the stable copy (the heap copy is pruned by the caller)
+pub fn remove_expired_sessions(now: u64) {
+    STABLE_SESSIONS.with(|s| {
+        let mut map = s.borrow_mut();
+        let expired: Vec<Vec<u8>> = map.iter()
+            .filter(|(_, session)| session.expires_at <= now)
+            .map(|(key, _)| key)
+            .collect();
+        for key in expired {
+            map.remove(&key);
+        }
+    });
+}
+
+pub fn clear_search_indices() {
+    EMAIL_INDEX.with(|e| e.borrow_mut().clear_new());
+    USERNAME_INDEX.with(|u| u.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Migration
+// ============================================================================
+
+pub fn migration_status() -> MigrationStatus {
+    MIGRATION_STATUS.with(|s| s.borrow().get().clone())
+}
+
+fn save(status: &MigrationStatus) {
+    MIGRATION_STATUS.with(|s| s.borrow_mut().set(status.clone()))
+        .expect("Failed to save migration status");
+}
+
+/// Copy up to `batch_size` entries and return the progress. Call again until
+/// the phase is `Done`; calling after that is a no-op.
+pub fn run_migration_step(batch_size: Option<u32>) -> Result<MigrationStatus, String> {
+    let batch = batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+    if batch == 0 || batch > MAX_BATCH_SIZE {
+        return Err(format!("Batch size must be between 1 and {}", MAX_BATCH_SIZE));
+    }
+    let batch = batch as usize;
+
+    let mut status = migration_status();
+    if status.phase == MigrationPhase::NotStarted {
+        status.phase = MigrationPhase::SearchIndices;
+        status.started_at = Some(time());
+    }
+
+    match status.phase {
+        MigrationPhase::SearchIndices => {
+            let start = match status.availability_cursor.clone() {
+                Some(id) => RangeBound::Excluded(id),
+                None => RangeBound::Unbounded,
+            };
+            let chunk: Vec<_> = AVAILABILITIES.with(|a| {
+                a.borrow()
+                    .range((start, RangeBound::Unbounded))
+                    .take(batch)
+                    .collect()
+            });
+            for (_, availability) in &chunk {
+                availabilities::index_owner(availability);
+            }
+            status.indexed_availabilities += chunk.len() as u64;
+            match chunk.last() {
+                Some((id, _)) if chunk.len() == batch => status.availability_cursor = Some(id.clone()),
+                _ => status.phase = MigrationPhase::Sessions,
+            }
+        }
+        MigrationPhase::Sessions => {
+            let cursor = status.session_cursor.clone();
+            let mut keys: Vec<Vec<u8>> = SESSIONS.with(|s| {
+                s.borrow()
+                    .keys()
+                    .filter(|key| cursor.as_ref().is_none_or(|c| *key > c))
+                    .cloned()
+                    .collect()
+            });
+            keys.sort();
+            keys.truncate(batch);
+            for key in &keys {
+                if let Some(session) = SESSIONS.with(|s| s.borrow().get(key).cloned()) {
+                    STABLE_SESSIONS.with(|s| s.borrow_mut().insert(key.clone(), session));
+                }
+            }
+            status.copied_sessions += keys.len() as u64;
+            if keys.len() == batch {
+                status.session_cursor = keys.pop();
+            } else {
+                status.phase = MigrationPhase::Done;
+                status.completed_at = Some(time());
+                ic_cdk::println!(
+                    "✅ [migration] Indexed {} availabilities and copied {} sessions to stable memory",
+                    status.indexed_availabilities, status.copied_sessions
+                );
+            }
+        }
+        MigrationPhase::NotStarted | MigrationPhase::Done => {}
+    }
+
+    save(&status);
+    Ok(status)
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+/// Legacy email and username index entries pointing at the principal
+pub fn count_owned(principal: Principal) -> u32 {
+    EMAIL_INDEX.with(|e| principal_moves::count_owned_in(&e.borrow(), |owner| owner, principal))
+        + USERNAME_INDEX.with(|u| principal_moves::count_owned_in(&u.borrow(), |owner| owner, principal))
+}
+
+/// Point `from`'s legacy index entries, heap and stable, at `to`
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    for index in [&EMAIL_TO_PRINCIPAL, &USERNAME_TO_PRINCIPAL] {
+        index.with(|i| {
+            for owner in i.borrow_mut().values_mut().filter(|owner| **owner == from) {
+                *owner = to;
+            }
+        });
+    }
+    EMAIL_INDEX.with(|e| principal_moves::move_owned_in(&mut e.borrow_mut(), |owner| owner, from, to))
+        + USERNAME_INDEX.with(|u| principal_moves::move_owned_in(&mut u.borrow_mut(), |owner| owner, from, to))
+}
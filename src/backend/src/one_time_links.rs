@@ -0,0 +1,286 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities;
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::{entropy, link_stats, principal_moves, quarantine};
+use crate::error_codes::{self, ErrorCode};
+
+/// Unused links an owner may hold at once
+const MAX_OPEN_LINKS_PER_OWNER: usize = 100;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A secret link that allows exactly one booking on an availability,
+/// for sending to a specific person instead of the evergreen share link
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct OneTimeLink {
+    pub token: String,
+    pub availability_id: String,
+    pub owner: Principal,
+    pub created_at: u64,
+    pub used_by: Option<Principal>,
+    pub used_at: Option<u64>,
+    pub booking_id: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct BookWithLinkRequest {
+    pub token: String,
+    pub start_time: u64, // Unix seconds
+    pub end_time: u64,   // Unix seconds
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl OneTimeLink {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Already used, so it can't be redeemed.
+    fn unreadable() -> Self {
+        OneTimeLink {
+            token: String::new(),
+            availability_id: String::new(),
+            owner: Principal::anonymous(),
+            created_at: 0,
+            used_by: Some(Principal::anonymous()),
+            used_at: Some(0),
+            booking_id: Some(String::new()),
+        }
+    }
+}
+
+impl Storable for OneTimeLink {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "OneTimeLink").unwrap_or_else(OneTimeLink::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static ONE_TIME_LINKS: RefCell<StableBTreeMap<String, OneTimeLink, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24)))
+        )
+    );
+}
+
+pub fn clear() {
+    ONE_TIME_LINKS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_link(token: &str) -> Result<OneTimeLink, String> {
+    ONE_TIME_LINKS.with(|l| l.borrow().get(&token.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Link not found"))
+}
+
+pub fn owner_of(token: &str) -> Option<Principal> {
+    ONE_TIME_LINKS.with(|l| l.borrow().get(&token.to_string())).map(|link| link.owner)
+}
+
+pub fn availability_of(token: &str) -> Option<String> {
+    ONE_TIME_LINKS.with(|l| l.borrow().get(&token.to_string())).map(|link| link.availability_id)
+}
+
+fn save(link: &OneTimeLink) {
+    ONE_TIME_LINKS.with(|l| {
+        l.borrow_mut().insert(link.token.clone(), link.clone());
+    });
+}
+
+fn ensure_unused(link: &OneTimeLink) -> Result<(), String> {
+    if link.booking_id.is_some() {
+        return Err("This link has already been used".to_string());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Owner Operations
+// ============================================================================
+
+pub fn create_one_time_link(caller: Principal, availability_id: String) -> Result<OneTimeLink, String> {
+    let availability = availabilities::get_availability(availability_id)?;
+    if availability.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    let open = list_one_time_links(caller).iter().filter(|l| l.booking_id.is_none()).count();
+    if open >= MAX_OPEN_LINKS_PER_OWNER {
+        return Err(format!("At most {} unused one-time links", MAX_OPEN_LINKS_PER_OWNER));
+    }
+
+    let link = OneTimeLink {
+        token: entropy::next_token(16)?,
+        availability_id: availability.id,
+        owner: caller,
+        created_at: time(),
+        used_by: None,
+        used_at: None,
+        booking_id: None,
+    };
+    save(&link);
+
+    ic_cdk::println!("🔑 Created one-time link for {}", link.availability_id);
+    Ok(link)
+}
+
+pub fn list_one_time_links(owner: Principal) -> Vec<OneTimeLink> {
+    ONE_TIME_LINKS.with(|l| {
+        l.borrow()
+            .iter()
+            .filter(|(_, link)| link.owner == owner)
+            .map(|(_, link)| link)
+            .collect()
+    })
+}
+
+/// Delete an unused link; used links are kept as the booking's record
+pub fn revoke_one_time_link(caller: Principal, token: String) -> Result<(), String> {
+    let link = get_link(&token)?;
+    if link.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    ensure_unused(&link)?;
+    ONE_TIME_LINKS.with(|l| l.borrow_mut().remove(&token));
+    link_stats::remove(&token);
+    Ok(())
+}
+
+// ============================================================================
+// Guest Operations
+// ============================================================================
+
+/// What a guest sees when opening the link; fails once it has been used
+pub fn get_one_time_link(token: String) -> Result<OneTimeLink, String> {
+    let link = get_link(&token)?;
+    ensure_unused(&link)?;
+    Ok(link)
+}
+
+/// Book through the link, which deactivates it
+pub fn book_with_one_time_link(caller: Principal, req: BookWithLinkRequest) -> Result<Booking, String> {
+    let mut link = get_link(&req.token)?;
+    ensure_unused(&link)?;
+
+    let booking = bookings::create_booking(caller, CreateBookingRequest {
+        availability_id: link.availability_id.clone(),
+        start_time: req.start_time,
+        end_time: req.end_time,
+        guest_name: req.guest_name,
+        guest_email: req.guest_email,
+        guest_timezone: req.guest_timezone,
+        field_answers: None,
+    })?;
+
+    link.used_by = Some(caller);
+    link.used_at = Some(time());
+    link.booking_id = Some(booking.id.clone());
+    save(&link);
+
+    Ok(booking)
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    ONE_TIME_LINKS.with(|l| principal_moves::count_owned_in(&l.borrow(), |link| &mut link.owner, owner))
+}
+
+/// Hand `from`'s links to `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    ONE_TIME_LINKS.with(|l| principal_moves::move_owned_in(&mut l.borrow_mut(), |link| &mut link.owner, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(token: &str, owner: Principal) -> OneTimeLink {
+        let link = OneTimeLink {
+            token: token.to_string(),
+            availability_id: "intro".to_string(),
+            owner,
+            created_at: 0,
+            used_by: None,
+            used_at: None,
+            booking_id: None,
+        };
+        save(&link);
+        link
+    }
+
+    fn request(token: &str) -> BookWithLinkRequest {
+        BookWithLinkRequest {
+            token: token.to_string(),
+            start_time: 0,
+            end_time: 0,
+            guest_name: "Guest".to_string(),
+            guest_email: None,
+            guest_timezone: None,
+        }
+    }
+
+    #[test]
+    fn a_used_link_can_not_be_opened_booked_or_revoked_again() {
+        let owner = Principal::from_slice(&[1; 29]);
+        let guest = Principal::from_slice(&[2; 29]);
+        let mut used = link("used", owner);
+        assert!(get_one_time_link("used".to_string()).is_ok());
+
+        used.used_by = Some(guest);
+        used.used_at = Some(1);
+        used.booking_id = Some("booking".to_string());
+        save(&used);
+
+        assert!(get_one_time_link("used".to_string()).is_err());
+        assert!(book_with_one_time_link(guest, request("used")).is_err());
+        assert!(revoke_one_time_link(owner, "used".to_string()).is_err());
+        // The link stays behind as the booking's record
+        assert_eq!(owner_of("used"), Some(owner));
+    }
+
+    #[test]
+    fn only_the_owner_revokes_an_unused_link() {
+        let owner = Principal::from_slice(&[3; 29]);
+        link("open", owner);
+
+        assert!(revoke_one_time_link(Principal::from_slice(&[4; 29]), "open".to_string()).is_err());
+        assert!(revoke_one_time_link(owner, "open".to_string()).is_ok());
+        assert!(get_one_time_link("open".to_string()).is_err());
+        assert!(book_with_one_time_link(owner, request("open")).is_err());
+    }
+
+    #[test]
+    fn an_unreadable_link_counts_as_used() {
+        assert!(ensure_unused(&OneTimeLink::unreadable()).is_err());
+    }
+}
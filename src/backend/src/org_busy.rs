@@ -0,0 +1,172 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::BusyTimeBlock;
+use crate::{entropy, quarantine, tenants};
+use crate::error_codes::{self, ErrorCode};
+
+/// Upcoming shared blocks an org may hold at once
+const MAX_BLOCKS_PER_ORG: usize = 500;
+/// Longest single shared block (a two-week shutdown)
+const MAX_BLOCK_SECS: u64 = 14 * 24 * 60 * 60;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A busy period that applies to every member of an org (company holiday,
+/// all-hands). Stored once per org and overlaid on members' free time.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct OrgBusyBlock {
+    pub id: String,
+    pub title: String,
+    pub start_time: u64, // Unix seconds
+    pub end_time: u64,   // Unix seconds
+    pub created_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct OrgBusyBlockRequest {
+    pub title: String,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OrgBusyList(pub Vec<OrgBusyBlock>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for OrgBusyList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "OrgBusyList").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Tenant ID -> shared busy blocks
+    static ORG_BUSY: RefCell<StableBTreeMap<String, OrgBusyList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(30)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    ORG_BUSY.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn generate_block_id() -> Result<String, String> {
+    Ok(format!("obb-{}", entropy::next_token(6)?))
+}
+
+fn validate_request(req: &OrgBusyBlockRequest) -> Result<(), String> {
+    if req.title.trim().is_empty() || req.title.len() > 100 {
+        return Err("title must be 1-100 characters".to_string());
+    }
+    if req.start_time >= req.end_time {
+        return Err("start_time must be before end_time".to_string());
+    }
+    if req.end_time - req.start_time > MAX_BLOCK_SECS {
+        return Err("a shared busy block can last at most 14 days".to_string());
+    }
+    Ok(())
+}
+
+/// Blocks of a tenant that haven't ended yet
+fn upcoming(tenant_id: &str) -> Vec<OrgBusyBlock> {
+    let now = time() / 1_000_000_000;
+    ORG_BUSY.with(|o| o.borrow().get(&tenant_id.to_string()))
+        .unwrap_or_default()
+        .0
+        .into_iter()
+        .filter(|b| b.end_time > now)
+        .collect()
+}
+
+/// Shared blocks of the owner's org overlapping the range
+pub fn blocks_for(owner: Principal, range_start: u64, range_end: u64) -> Vec<BusyTimeBlock> {
+    ORG_BUSY.with(|o| o.borrow().get(&tenants::tenant_of(owner)))
+        .map(|list| {
+            list.0
+                .iter()
+                .filter(|b| b.start_time < range_end && range_start < b.end_time)
+                .map(|b| BusyTimeBlock { start_time: b.start_time, end_time: b.end_time })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// Add a shared busy block to an org (tenant admins)
+pub fn add_org_busy_block(caller: Principal, tenant_id: String, req: OrgBusyBlockRequest) -> Result<OrgBusyBlock, String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    validate_request(&req)?;
+
+    // Ended blocks are pruned on every write
+    let mut blocks = upcoming(&tenant_id);
+    if blocks.len() >= MAX_BLOCKS_PER_ORG {
+        return Err(error_codes::coded(ErrorCode::QuotaExceeded, format!("an org can hold at most {} upcoming busy blocks", MAX_BLOCKS_PER_ORG)));
+    }
+
+    let block = OrgBusyBlock {
+        id: generate_block_id()?,
+        title: req.title.trim().to_string(),
+        start_time: req.start_time,
+        end_time: req.end_time,
+        created_by: caller,
+        created_at: time(),
+    };
+    blocks.push(block.clone());
+    blocks.sort_by_key(|b| b.start_time);
+    ORG_BUSY.with(|o| o.borrow_mut().insert(tenant_id.clone(), OrgBusyList(blocks)));
+
+    ic_cdk::println!("🏢 Added org busy block {} for tenant {}", block.id, tenant_id);
+    Ok(block)
+}
+
+pub fn delete_org_busy_block(caller: Principal, tenant_id: String, id: String) -> Result<(), String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    let mut blocks = upcoming(&tenant_id);
+    let before = blocks.len();
+    blocks.retain(|b| b.id != id);
+    if blocks.len() == before {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Busy block not found"));
+    }
+    ORG_BUSY.with(|o| o.borrow_mut().insert(tenant_id, OrgBusyList(blocks)));
+    Ok(())
+}
+
+/// Upcoming shared blocks of the caller's org
+pub fn list_org_busy_blocks(caller: Principal) -> Vec<OrgBusyBlock> {
+    upcoming(&tenants::tenant_of(caller))
+}
@@ -0,0 +1,287 @@
+//! Org-wide defaults for members' availabilities. Org managers set meeting
+//! lengths and buffers, slot steps and minimum notice, busy privacy, the
+//! cancellation policy, the language of guest-facing text and follow-up
+//! notifications; every availability a member creates starts from them and
+//! can then be changed per availability. Settings listed as enforced are
+//! pushed onto every member availability and cannot be overridden.
+//! Brand name, logo and colours come from the tenant's origin configs, and
+//! notification wording from Tenant-scoped templates.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{
+    self, Availability, BusyPrivacy, CancellationPolicy, DurationOption, SlotAlignment, AVAILABILITIES,
+};
+use crate::followups::{self, FollowUp};
+use crate::i18n::Locale;
+use crate::tenants;
+use crate::quarantine;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum OrgSetting {
+    Durations,
+    Alignment,
+    BusyPrivacy,
+    CancellationPolicy,
+    Locale,
+    FollowUps,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct OrgDefaults {
+    pub tenant_id: String,
+    pub durations: Option<Vec<DurationOption>>, // Meeting lengths and the buffers around them
+    pub alignment: Option<SlotAlignment>,       // Slot steps and minimum notice
+    pub busy_privacy: Option<BusyPrivacy>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub locale: Option<Locale>,
+    pub follow_ups: Option<Vec<FollowUp>>,
+    pub enforced: Vec<OrgSetting>, // Members cannot change these on their availabilities
+    pub updated_at: u64,
+    pub updated_by: Principal,
+}
+
+/// None leaves the setting to each member
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SetOrgDefaultsRequest {
+    pub durations: Option<Vec<DurationOption>>,
+    pub alignment: Option<SlotAlignment>,
+    pub busy_privacy: Option<BusyPrivacy>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub locale: Option<Locale>,
+    pub follow_ups: Option<Vec<FollowUp>>,
+    pub enforced: Vec<OrgSetting>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl OrgDefaults {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Sets and enforces nothing.
+    fn unreadable() -> Self {
+        OrgDefaults {
+            tenant_id: String::new(),
+            durations: None,
+            alignment: None,
+            busy_privacy: None,
+            cancellation_policy: None,
+            locale: None,
+            follow_ups: None,
+            enforced: vec![],
+            updated_at: 0,
+            updated_by: Principal::anonymous(),
+        }
+    }
+}
+
+impl Storable for OrgDefaults {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "OrgDefaults").unwrap_or_else(OrgDefaults::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Tenant ID -> defaults for its members' availabilities
+    static ORG_DEFAULTS: RefCell<StableBTreeMap<String, OrgDefaults, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(79)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn validate(req: &SetOrgDefaultsRequest) -> Result<(), String> {
+    if let Some(ref durations) = req.durations {
+        availabilities::validate_durations(durations)?;
+    }
+    if let Some(ref alignment) = req.alignment {
+        availabilities::validate_alignment(alignment)?;
+    }
+    if let Some(ref policy) = req.cancellation_policy {
+        availabilities::validate_cancellation_policy(policy)?;
+    }
+    if let Some(ref follow_ups) = req.follow_ups {
+        followups::validate(follow_ups)?;
+    }
+    for setting in &req.enforced {
+        let has_value = match setting {
+            OrgSetting::Durations => req.durations.is_some(),
+            OrgSetting::Alignment => req.alignment.is_some(),
+            OrgSetting::BusyPrivacy => req.busy_privacy.is_some(),
+            OrgSetting::CancellationPolicy => req.cancellation_policy.is_some(),
+            OrgSetting::Locale => req.locale.is_some(),
+            OrgSetting::FollowUps => req.follow_ups.is_some(),
+        };
+        if !has_value {
+            return Err(format!("Enforced setting {:?} needs a default value", setting));
+        }
+    }
+    Ok(())
+}
+
+fn defaults_for(owner: Principal) -> Option<OrgDefaults> {
+    ORG_DEFAULTS.with(|d| d.borrow().get(&tenants::tenant_of(owner)))
+}
+
+/// Copy the defaults onto an availability (only the enforced ones when
+/// `only_enforced`); true if anything changed
+fn apply(defaults: &OrgDefaults, availability: &mut Availability, only_enforced: bool) -> bool {
+    fn set<T: Clone + PartialEq>(field: &mut Option<T>, value: &Option<T>) -> bool {
+        if value.is_none() || field == value {
+            return false;
+        }
+        field.clone_from(value);
+        true
+    }
+
+    let applies = |setting: OrgSetting| !only_enforced || defaults.enforced.contains(&setting);
+    let mut changed = false;
+    if applies(OrgSetting::Durations) {
+        changed |= set(&mut availability.durations, &defaults.durations);
+    }
+    if applies(OrgSetting::Alignment) {
+        changed |= set(&mut availability.alignment, &defaults.alignment);
+    }
+    if applies(OrgSetting::BusyPrivacy) {
+        changed |= set(&mut availability.busy_privacy, &defaults.busy_privacy);
+    }
+    if applies(OrgSetting::CancellationPolicy) {
+        changed |= set(&mut availability.cancellation_policy, &defaults.cancellation_policy);
+    }
+    if applies(OrgSetting::Locale) {
+        changed |= set(&mut availability.locale, &defaults.locale);
+    }
+    if applies(OrgSetting::FollowUps) {
+        changed |= set(&mut availability.follow_ups, &defaults.follow_ups);
+    }
+    changed
+}
+
+// ============================================================================
+// Inheritance
+// ============================================================================
+
+/// Start a new availability from its owner's org defaults
+pub fn inherit(availability: &mut Availability) {
+    if let Some(defaults) = defaults_for(availability.owner) {
+        apply(&defaults, availability, false);
+    }
+}
+
+/// Put the owner's enforced org settings back on an availability whose
+/// settings were copied from elsewhere (e.g. a shared template)
+pub fn enforce(availability: &mut Availability) {
+    if let Some(defaults) = defaults_for(availability.owner) {
+        apply(&defaults, availability, true);
+    }
+}
+
+/// Refuse a per-availability change to a setting the owner's org enforces
+pub fn require_overridable(owner: Principal, setting: OrgSetting) -> Result<(), String> {
+    if defaults_for(owner).is_some_and(|defaults| defaults.enforced.contains(&setting)) {
+        return Err(format!("{:?} is enforced by your organization", setting));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Administration
+// ============================================================================
+
+/// Replace a tenant's defaults and push the enforced ones onto every
+/// existing member availability
+pub fn set_defaults(caller: Principal, tenant_id: String, req: SetOrgDefaultsRequest) -> Result<OrgDefaults, String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    validate(&req)?;
+
+    let defaults = OrgDefaults {
+        tenant_id: tenant_id.clone(),
+        durations: req.durations,
+        alignment: req.alignment,
+        busy_privacy: req.busy_privacy,
+        cancellation_policy: req.cancellation_policy,
+        locale: req.locale,
+        follow_ups: req.follow_ups,
+        enforced: req.enforced,
+        updated_at: time(),
+        updated_by: caller,
+    };
+    ORG_DEFAULTS.with(|d| d.borrow_mut().insert(tenant_id.clone(), defaults.clone()));
+
+    let mut updated = 0;
+    if !defaults.enforced.is_empty() {
+        for mut availability in availabilities::list_tenant_availabilities(&tenant_id) {
+            if apply(&defaults, &mut availability, true) {
+                availability.updated_at = time();
+                AVAILABILITIES.with(|a| a.borrow_mut().insert(availability.id.clone(), availability));
+                updated += 1;
+            }
+        }
+    }
+    ic_cdk::println!("🏢 Set org defaults of {} ({} availabilities brought in line)", tenant_id, updated);
+    Ok(defaults)
+}
+
+/// Defaults of the caller's org, so the UI can show what is inherited and locked
+pub fn get_defaults(caller: Principal) -> Option<OrgDefaults> {
+    defaults_for(caller)
+}
+
+/// Stop inheriting and enforcing; existing availabilities keep their settings
+pub fn delete_defaults(caller: Principal, tenant_id: String) -> Result<(), String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    ORG_DEFAULTS.with(|d| d.borrow_mut().remove(&tenant_id))
+        .map(|_| ())
+        .ok_or_else(|| "No defaults set for this org".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(enforced: Vec<OrgSetting>) -> SetOrgDefaultsRequest {
+        SetOrgDefaultsRequest {
+            durations: None,
+            alignment: Some(SlotAlignment { step_minutes: 30, lead_minutes: 120 }),
+            busy_privacy: None,
+            cancellation_policy: None,
+            locale: None,
+            follow_ups: None,
+            enforced,
+        }
+    }
+
+    #[test]
+    fn enforced_settings_need_a_default() {
+        assert!(validate(&request(vec![OrgSetting::Alignment])).is_ok());
+        assert!(validate(&request(vec![OrgSetting::BusyPrivacy])).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use crate::availabilities::{self, FreeSlot};
+use crate::{bookings, cursor, scheduling, tenants};
+
+/// Longest range a load report covers
+const MAX_RANGE_SECS: u64 = 90 * 24 * 60 * 60;
+const MAX_PAGE_SIZE: u32 = 100;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One member's booked versus offered time over the report range
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct MemberLoad {
+    pub principal: Principal,
+    pub availability_count: u32,
+    pub booked_minutes: u64,
+    pub offered_minutes: u64,               // Bookable time across all their availabilities, booked or not
+    pub utilization_percent: Option<u32>,   // booked / offered; None when nothing was offered
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct OrgLoadReport {
+    pub tenant_id: String,
+    pub range_start: u64,
+    pub range_end: u64,
+    pub total_members: u32,
+    pub members: Vec<MemberLoad>,
+    pub next_offset: Option<u32>, // Pass back as `offset` for the next page
+    pub next_cursor: Option<String>, // Or pass this back as `cursor`, which is stable while members join
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Total length of the union of the windows, in seconds
+fn covered_secs(windows: Vec<FreeSlot>) -> u64 {
+    scheduling::merge(windows).iter().map(|w| w.end_time - w.start_time).sum()
+}
+
+fn member_load(principal: Principal, range_start: u64, range_end: u64) -> MemberLoad {
+    let owned = availabilities::list_user_availabilities(principal);
+    let offered = covered_secs(
+        owned.iter()
+            .flat_map(|a| availabilities::offered_windows(a, range_start, range_end))
+            .collect(),
+    );
+    let booked = covered_secs(
+        bookings::booked_blocks(principal, range_start, range_end)
+            .into_iter()
+            .map(|b| FreeSlot {
+                start_time: b.start_time.max(range_start),
+                end_time: b.end_time.min(range_end),
+            })
+            .collect(),
+    );
+
+    MemberLoad {
+        principal,
+        availability_count: owned.len() as u32,
+        booked_minutes: booked / 60,
+        offered_minutes: offered / 60,
+        utilization_percent: (offered > 0).then(|| (booked * 100 / offered) as u32),
+    }
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// Booked hours, offered hours and utilization per member of an org (tenant
+/// admins), one page of members at a time. A `cursor` from the previous
+/// page takes precedence over `offset`.
+pub fn get_org_load_report(
+    caller: Principal,
+    tenant_id: String,
+    range_start: u64,
+    range_end: u64,
+    offset: u32,
+    limit: u32,
+    cursor: Option<String>,
+) -> Result<OrgLoadReport, String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    if range_start >= range_end {
+        return Err("range_start must be before range_end".to_string());
+    }
+    if range_end - range_start > MAX_RANGE_SECS {
+        return Err("range must be at most 90 days".to_string());
+    }
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(format!("limit must be 1-{}", MAX_PAGE_SIZE));
+    }
+
+    let members = tenants::list_members(&tenant_id);
+    let total_members = members.len() as u32;
+    let by_offset = cursor.is_none();
+    let offset = if by_offset { offset } else { 0 };
+    let filters = format!("{}|{}|{}", tenant_id, range_start, range_end);
+    let (members, next_cursor) = cursor::page(
+        members.into_iter().skip(offset as usize).collect(),
+        cursor,
+        "org_load",
+        &filters,
+        limit as usize,
+        |m| m.principal.to_text(),
+    )?;
+    let page: Vec<MemberLoad> = members
+        .into_iter()
+        .map(|m| member_load(m.principal, range_start, range_end))
+        .collect();
+    let end = offset.saturating_add(page.len() as u32);
+
+    Ok(OrgLoadReport {
+        tenant_id,
+        range_start,
+        range_end,
+        total_members,
+        members: page,
+        next_offset: (by_offset && next_cursor.is_some()).then_some(end),
+        next_cursor,
+    })
+}
@@ -0,0 +1,287 @@
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::plans::Feature;
+use crate::tenants;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+const DEFAULT_BRAND_NAME: &str = "weeekaly";
+const DEFAULT_PROVIDER: &str = "google";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// White-label settings for one frontend origin (e.g. https://book.agency.com)
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OriginConfig {
+    pub origin: String,
+    pub brand_name: String,
+    pub logo_url: Option<String>,
+    pub primary_color: Option<String>,     // CSS hex color, e.g. "#1a73e8"
+    pub support_email: Option<String>,     // Sender/reply-to shown in generated emails
+    pub default_provider: String,
+    pub allowed_features: Vec<Feature>,    // Features the deployment exposes in its UI
+    pub derivation_origin: Option<String>, // Derive principals as if signing in here (None = own origin)
+    pub updated_at: u64,
+    pub tenant_id: Option<String>,         // Users signing in here join this tenant (None = default)
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct SetOriginConfigRequest {
+    pub origin: String,
+    pub brand_name: String,
+    pub logo_url: Option<String>,
+    pub primary_color: Option<String>,
+    pub support_email: Option<String>,
+    pub default_provider: String,
+    pub allowed_features: Vec<Feature>,
+    pub derivation_origin: Option<String>,
+    pub tenant_id: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for OriginConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "OriginConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static ORIGIN_CONFIGS: RefCell<StableBTreeMap<String, OriginConfig, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19)))
+        )
+    );
+
+    // Retired origin -> origin it now derives principals as (after a domain
+    // move, e.g. staging -> weeekaly.com); both normalized
+    static ORIGIN_ALIASES: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(81)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Origins are compared without trailing slashes and case-insensitively
+pub fn normalize_origin(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_lowercase()
+}
+
+pub fn validate_origin(origin: &str) -> Result<(), String> {
+    let is_local = origin.starts_with("http://localhost") || origin.starts_with("http://127.0.0.1");
+    if !origin.starts_with("https://") && !is_local {
+        return Err(format!("Origin must use https: {}", origin));
+    }
+    if origin.len() > 253 || origin[origin.find("://").unwrap() + 3..].contains('/') {
+        return Err(format!("Origin must be scheme and host only: {}", origin));
+    }
+    Ok(())
+}
+
+fn validate_config(req: &SetOriginConfigRequest) -> Result<(), String> {
+    if req.brand_name.trim().is_empty() || req.brand_name.len() > 100 {
+        return Err("brand_name must be 1-100 characters".to_string());
+    }
+    if let Some(ref url) = req.logo_url {
+        if !url.starts_with("https://") || url.len() > 500 {
+            return Err("logo_url must be an https URL up to 500 characters".to_string());
+        }
+    }
+    if let Some(ref color) = req.primary_color {
+        let hex = color.strip_prefix('#').unwrap_or("");
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("primary_color must look like #RRGGBB".to_string());
+        }
+    }
+    if let Some(ref email) = req.support_email {
+        if email.len() > 254 || !email.contains('@') {
+            return Err("support_email is not a valid email address".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn default_config(origin: String) -> OriginConfig {
+    OriginConfig {
+        origin,
+        brand_name: DEFAULT_BRAND_NAME.to_string(),
+        logo_url: None,
+        primary_color: None,
+        support_email: None,
+        default_provider: DEFAULT_PROVIDER.to_string(),
+        allowed_features: vec![
+            Feature::Payments,
+            Feature::Webhooks,
+            Feature::MultipleCalendars,
+            Feature::Teams,
+        ],
+        derivation_origin: None,
+        updated_at: 0,
+        tenant_id: None,
+    }
+}
+
+// ============================================================================
+// Queries & Admin
+// ============================================================================
+
+/// Settings for an origin, falling back to the stock weeekaly branding
+pub fn get_origin_config(origin: &str) -> OriginConfig {
+    let origin = normalize_origin(origin);
+    ORIGIN_CONFIGS
+        .with(|c| c.borrow().get(&origin))
+        .unwrap_or_else(|| default_config(origin))
+}
+
+/// Origin that principals are derived from when signing in on `origin`:
+/// a retired origin resolves to its canonical one first, then the origin
+/// config's derivation_origin applies. Unaliased origins are returned
+/// verbatim so existing principals never change.
+pub fn derivation_origin(origin: &str) -> String {
+    let origin = ORIGIN_ALIASES
+        .with(|a| a.borrow().get(&normalize_origin(origin)))
+        .unwrap_or_else(|| origin.to_string());
+    ORIGIN_CONFIGS
+        .with(|c| c.borrow().get(&normalize_origin(&origin)))
+        .and_then(|config| config.derivation_origin)
+        .unwrap_or(origin)
+}
+
+/// Tenant that users signing in on `origin` belong to
+pub fn tenant_for(origin: &str) -> String {
+    ORIGIN_CONFIGS
+        .with(|c| c.borrow().get(&normalize_origin(origin)))
+        .and_then(|config| config.tenant_id)
+        .unwrap_or_else(|| tenants::DEFAULT_TENANT.to_string())
+}
+
+pub fn list_origin_configs() -> Vec<OriginConfig> {
+    ORIGIN_CONFIGS.with(|c| c.borrow().iter().map(|(_, config)| config).collect())
+}
+
+/// `providers` are the provider keys configured in the canister
+pub fn set_origin_config(req: SetOriginConfigRequest, providers: &[String]) -> Result<OriginConfig, String> {
+    let origin = normalize_origin(&req.origin);
+    validate_origin(&origin)?;
+    validate_config(&req)?;
+    if !providers.contains(&req.default_provider) {
+        return Err(format!("Unknown provider: {}", req.default_provider));
+    }
+
+    if let Some(ref tenant_id) = req.tenant_id {
+        if !tenants::tenant_exists(tenant_id) {
+            return Err(format!("Unknown tenant: {}", tenant_id));
+        }
+    }
+
+    let derivation_origin = match req.derivation_origin {
+        Some(ref d) => {
+            let d = normalize_origin(d);
+            validate_origin(&d)?;
+            Some(d)
+        }
+        None => None,
+    };
+
+    let config = OriginConfig {
+        origin: origin.clone(),
+        brand_name: req.brand_name.trim().to_string(),
+        logo_url: req.logo_url,
+        primary_color: req.primary_color,
+        support_email: req.support_email,
+        default_provider: req.default_provider,
+        allowed_features: req.allowed_features,
+        derivation_origin,
+        updated_at: time(),
+        tenant_id: req.tenant_id,
+    };
+    ORIGIN_CONFIGS.with(|c| c.borrow_mut().insert(origin.clone(), config.clone()));
+
+    ic_cdk::println!("🎨 Updated origin config for {}", origin);
+    Ok(config)
+}
+
+/// Retired origins whose users now derive the same principals as on `origin`
+/// (the ones to migrate records from when they sign in)
+pub fn retired_origins_of(origin: &str) -> Vec<String> {
+    let target = derivation_origin(origin);
+    ORIGIN_ALIASES.with(|a| {
+        a.borrow()
+            .iter()
+            .map(|(retired, _)| retired)
+            .filter(|retired| derivation_origin(retired) == target)
+            .collect()
+    })
+}
+
+/// Make users of `retired` derive their principals as on `canonical`
+pub fn set_origin_alias(retired: &str, canonical: &str) -> Result<(), String> {
+    validate_origin(retired)?;
+    validate_origin(canonical)?;
+    let (retired, canonical) = (normalize_origin(retired), normalize_origin(canonical));
+    if retired == canonical {
+        return Err("An origin cannot alias itself".to_string());
+    }
+    ORIGIN_ALIASES.with(|a| {
+        let mut map = a.borrow_mut();
+        if map.contains_key(&canonical) {
+            return Err(format!("{} is itself retired; alias to its canonical origin", canonical));
+        }
+        if map.iter().any(|(_, target)| target == retired) {
+            return Err(format!("Other origins alias to {}; retire them first", retired));
+        }
+        map.insert(retired.clone(), canonical.clone());
+        ic_cdk::println!("🔀 Origin {} now derives as {}", retired, canonical);
+        Ok(())
+    })
+}
+
+pub fn delete_origin_alias(retired: &str) -> Result<(), String> {
+    ORIGIN_ALIASES.with(|a| {
+        a.borrow_mut()
+            .remove(&normalize_origin(retired))
+            .map(|_| ())
+            .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Origin alias not found"))
+    })
+}
+
+/// (retired, canonical) pairs
+pub fn list_origin_aliases() -> Vec<(String, String)> {
+    ORIGIN_ALIASES.with(|a| a.borrow().iter().collect())
+}
+
+pub fn delete_origin_config(origin: &str) -> Result<(), String> {
+    ORIGIN_CONFIGS.with(|c| {
+        c.borrow_mut()
+            .remove(&normalize_origin(origin))
+            .map(|_| ())
+            .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Origin config not found"))
+    })
+}
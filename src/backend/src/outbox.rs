@@ -0,0 +1,500 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::integrations::{self, HookEvent};
+use crate::earnings::{self, Payout};
+use crate::{matrix, quarantine, service_status, stripe, trace};
+use crate::error_codes::{self, ErrorCode};
+
+const OUTBOX_TIMER_INTERVAL: Duration = Duration::from_secs(30);
+/// Attempts before a job is dead-lettered
+const MAX_ATTEMPTS: u32 = 8;
+const BASE_BACKOFF_NS: u64 = 30 * 1_000_000_000;
+const MAX_BACKOFF_NS: u64 = 60 * 60 * 1_000_000_000;
+/// A running job is not picked up again for this long, even if its call never returns
+const LEASE_NS: u64 = 5 * 60 * 1_000_000_000;
+const MAX_JOBS_PER_TICK: usize = 20;
+const FAILED_JOBS_PAGE_SIZE: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// An external call to make once the state change that caused it has committed
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum SideEffect {
+    IntegrationHook { hook_id: String, event: HookEvent, booking_id: String },
+    Matrix { owner: Principal, event: HookEvent, booking_id: String },
+}
+
+/// What a stored job does: a SideEffect, an inbound webhook event whose
+/// first application failed, or a payout whose transfer has no known
+/// outcome yet. Kept apart from SideEffect so internal jobs stay out of the
+/// public failed-jobs API; old SideEffect records decode as-is.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+enum Effect {
+    IntegrationHook { hook_id: String, event: HookEvent, booking_id: String },
+    Matrix { owner: Principal, event: HookEvent, booking_id: String },
+    StripeEvent { event_id: String, payload: String },
+    Payout(Payout),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum JobStatus {
+    Pending,
+    DeadLettered, // Gave up after MAX_ATTEMPTS
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct OutboxJob {
+    pub id: u64,
+    pub effect: SideEffect,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub trace_id: Option<String>, // Trace of the call that queued it; current while it runs
+}
+
+/// A job as stored (OutboxJob is its public view)
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+struct Job {
+    id: u64,
+    effect: Effect,
+    status: JobStatus,
+    attempts: u32,
+    next_attempt_at: u64,
+    last_error: Option<String>,
+    created_at: u64,
+    updated_at: u64,
+    trace_id: Option<String>,
+}
+
+/// A dead-lettered Stripe event
+#[derive(Clone, Debug)]
+pub struct FailedStripeEvent {
+    pub event_id: String,
+    pub payload: String,
+    pub error: String,
+    pub attempts: u32,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// One page of dead-lettered jobs; pass `next_cursor` to get the next one
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct FailedJobsPage {
+    pub jobs: Vec<OutboxJob>,
+    pub next_cursor: Option<u64>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Job {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Dead-lettered, so it is never performed.
+    fn unreadable() -> Self {
+        Job {
+            id: 0,
+            effect: Effect::StripeEvent { event_id: String::new(), payload: String::new() },
+            status: JobStatus::DeadLettered,
+            attempts: 0,
+            next_attempt_at: 0,
+            last_error: Some("Unreadable record".to_string()),
+            created_at: 0,
+            updated_at: 0,
+            trace_id: None,
+        }
+    }
+}
+
+impl Storable for Job {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Job").unwrap_or_else(Job::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Job ID -> job. Jobs are removed once they succeed.
+    static OUTBOX: RefCell<StableBTreeMap<u64, Job, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(41)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn backoff(attempts: u32) -> u64 {
+    BASE_BACKOFF_NS
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF_NS)
+}
+
+impl From<SideEffect> for Effect {
+    fn from(effect: SideEffect) -> Self {
+        match effect {
+            SideEffect::IntegrationHook { hook_id, event, booking_id } => Effect::IntegrationHook { hook_id, event, booking_id },
+            SideEffect::Matrix { owner, event, booking_id } => Effect::Matrix { owner, event, booking_id },
+        }
+    }
+}
+
+impl Job {
+    /// Public view; None for inbound events
+    fn public(self) -> Option<OutboxJob> {
+        let effect = match self.effect {
+            Effect::IntegrationHook { hook_id, event, booking_id } => SideEffect::IntegrationHook { hook_id, event, booking_id },
+            Effect::Matrix { owner, event, booking_id } => SideEffect::Matrix { owner, event, booking_id },
+            Effect::StripeEvent { .. } | Effect::Payout(_) => return None,
+        };
+        Some(OutboxJob {
+            id: self.id,
+            effect,
+            status: self.status,
+            attempts: self.attempts,
+            next_attempt_at: self.next_attempt_at,
+            last_error: self.last_error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            trace_id: self.trace_id,
+        })
+    }
+}
+
+fn update_job(id: u64, f: impl FnOnce(&mut Job)) {
+    OUTBOX.with(|o| {
+        let mut map = o.borrow_mut();
+        if let Some(mut job) = map.get(&id) {
+            f(&mut job);
+            job.updated_at = time();
+            map.insert(id, job);
+        }
+    });
+}
+
+async fn perform(effect: Effect) -> Result<(), String> {
+    match effect {
+        Effect::IntegrationHook { hook_id, event, booking_id } => {
+            integrations::deliver(hook_id, event, booking_id).await
+        }
+        Effect::Matrix { owner, event, booking_id } => {
+            matrix::deliver(owner, event, booking_id).await
+        }
+        Effect::StripeEvent { payload, .. } => stripe::apply(&payload),
+        Effect::Payout(payout) => earnings::settle_payout(payout).await,
+    }
+}
+
+async fn run(id: u64, effect: Effect, trace_id: Option<String>) {
+    let result = trace::within(trace_id, perform(effect)).await;
+    if result.is_ok() {
+        OUTBOX.with(|o| o.borrow_mut().remove(&id));
+        return;
+    }
+    update_job(id, |job| {
+        job.attempts += 1;
+        job.last_error = result.err();
+        if job.attempts >= MAX_ATTEMPTS {
+            job.status = JobStatus::DeadLettered;
+            ic_cdk::println!("☠️ [outbox] Job {} (trace {}) dead-lettered: {:?}", job.id, job.trace_id.as_deref().unwrap_or("-"), job.last_error);
+        } else {
+            job.next_attempt_at = time() + backoff(job.attempts);
+        }
+    });
+}
+
+/// Start every due job, leasing each so overlapping ticks don't run it twice
+fn process_due() {
+    let now = time();
+    let due: Vec<Job> = OUTBOX.with(|o| {
+        o.borrow()
+            .iter()
+            .map(|(_, job)| job)
+            .filter(|job| job.status == JobStatus::Pending && job.next_attempt_at <= now)
+            .take(MAX_JOBS_PER_TICK)
+            .collect()
+    });
+    for job in due {
+        update_job(job.id, |j| j.next_attempt_at = now + LEASE_NS);
+        ic_cdk::spawn(run(job.id, job.effect, job.trace_id));
+    }
+}
+
+// ============================================================================
+// Outbox
+// ============================================================================
+
+/// Persist a side effect and attempt it in a later message. If the current
+/// call traps, the job is rolled back together with the change that caused it.
+pub fn enqueue(effect: SideEffect) {
+    insert_job(effect.into(), 0, None);
+    ic_cdk_timers::set_timer(Duration::ZERO, process_due);
+}
+
+/// Queue a verified Stripe event whose first application failed, so it is
+/// retried with backoff and dead-lettered like any other job
+pub fn enqueue_stripe_event(event_id: String, payload: String, error: String) {
+    insert_job(Effect::StripeEvent { event_id, payload }, 1, Some(error));
+}
+
+/// Queue a payout whose transfer may or may not have gone through. It is
+/// repeated with the same arguments, which the ledger deduplicates, until it
+/// settles; MAX_ATTEMPTS with backoff ends well inside the 24-hour window.
+pub fn enqueue_payout(payout: Payout, error: String) {
+    insert_job(Effect::Payout(payout), 1, Some(error));
+}
+
+/// Whether a payout of the owner is still queued, dead-lettered or not
+pub fn has_payout(owner: Principal) -> bool {
+    OUTBOX.with(|o| o.borrow().iter().any(|(_, job)| matches!(&job.effect, Effect::Payout(payout) if payout.owner == owner)))
+}
+
+fn insert_job(effect: Effect, attempts: u32, last_error: Option<String>) {
+    let now = time();
+    OUTBOX.with(|o| {
+        let mut map = o.borrow_mut();
+        let id = map.last_key_value().map(|(id, _)| id + 1).unwrap_or(0);
+        map.insert(id, Job {
+            id,
+            effect,
+            status: JobStatus::Pending,
+            attempts,
+            next_attempt_at: if attempts == 0 { now } else { now + backoff(attempts) },
+            last_error,
+            created_at: now,
+            updated_at: now,
+            trace_id: trace::current(),
+        });
+    });
+}
+
+/// Register the retry timer (timers don't survive upgrades, so this runs
+/// from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("outbox", OUTBOX_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(OUTBOX_TIMER_INTERVAL, || {
+        service_status::heartbeat("outbox", OUTBOX_TIMER_INTERVAL);
+        process_due();
+    });
+}
+
+// ============================================================================
+// Dead Letters
+// ============================================================================
+
+/// Dead-lettered jobs with IDs from `cursor` on, oldest first. Stripe
+/// events are listed by list_failed_stripe_events instead, and payouts
+/// stay in the owner's journal as withdrawals.
+pub fn list_failed_jobs(cursor: Option<u64>) -> FailedJobsPage {
+    let mut jobs: Vec<OutboxJob> = OUTBOX.with(|o| {
+        o.borrow()
+            .range(cursor.unwrap_or(0)..)
+            .map(|(_, job)| job)
+            .filter(|job| job.status == JobStatus::DeadLettered)
+            .filter_map(Job::public)
+            .take(FAILED_JOBS_PAGE_SIZE + 1)
+            .collect()
+    });
+    let next_cursor = if jobs.len() > FAILED_JOBS_PAGE_SIZE {
+        jobs.pop().map(|job| job.id)
+    } else {
+        None
+    };
+    FailedJobsPage { jobs, next_cursor }
+}
+
+/// Dead-lettered Stripe events, oldest first
+pub fn list_failed_stripe_events() -> Vec<FailedStripeEvent> {
+    OUTBOX.with(|o| {
+        o.borrow()
+            .iter()
+            .map(|(_, job)| job)
+            .filter(|job| job.status == JobStatus::DeadLettered)
+            .filter_map(|job| match job.effect {
+                Effect::StripeEvent { event_id, payload } => Some(FailedStripeEvent {
+                    event_id,
+                    payload,
+                    error: job.last_error.unwrap_or_default(),
+                    attempts: job.attempts,
+                    created_at: job.created_at,
+                    updated_at: job.updated_at,
+                }),
+                _ => None,
+            })
+            .collect()
+    })
+}
+
+/// The dead-lettered job holding this Stripe event
+fn failed_stripe_event(event_id: &str) -> Result<Job, String> {
+    OUTBOX.with(|o| {
+        o.borrow()
+            .iter()
+            .map(|(_, job)| job)
+            .find(|job| job.status == JobStatus::DeadLettered
+                && matches!(&job.effect, Effect::StripeEvent { event_id: id, .. } if id == event_id))
+    })
+    .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Failed Stripe event not found"))
+}
+
+/// A dead-lettered job of the public API (a SideEffect)
+fn dead_letter(id: u64) -> Result<Job, String> {
+    OUTBOX.with(|o| o.borrow().get(&id))
+        .filter(|job| job.status == JobStatus::DeadLettered
+            && matches!(job.effect, Effect::IntegrationHook { .. } | Effect::Matrix { .. }))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Failed job not found"))
+}
+
+fn retry(mut job: Job) -> Job {
+    job.status = JobStatus::Pending;
+    job.attempts = 0;
+    job.next_attempt_at = time();
+    job.updated_at = job.next_attempt_at;
+    OUTBOX.with(|o| o.borrow_mut().insert(job.id, job.clone()));
+    ic_cdk_timers::set_timer(Duration::ZERO, process_due);
+    job
+}
+
+fn discard(id: u64) {
+    OUTBOX.with(|o| o.borrow_mut().remove(&id));
+    ic_cdk::println!("🗑️ [outbox] Discarded job {}", id);
+}
+
+/// Give a dead-lettered job a fresh set of attempts, starting now
+pub fn retry_job(id: u64) -> Result<OutboxJob, String> {
+    retry(dead_letter(id)?).public().ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Failed job not found"))
+}
+
+/// Drop a dead-lettered job for good
+pub fn discard_job(id: u64) -> Result<(), String> {
+    discard(dead_letter(id)?.id);
+    Ok(())
+}
+
+/// Give a dead-lettered Stripe event a fresh set of attempts, starting now
+pub fn retry_stripe_event(event_id: &str) -> Result<(), String> {
+    retry(failed_stripe_event(event_id)?);
+    Ok(())
+}
+
+/// Drop a dead-lettered Stripe event for good
+pub fn discard_stripe_event(event_id: &str) -> Result<(), String> {
+    discard(failed_stripe_event(event_id)?.id);
+    Ok(())
+}
+
+/// Drop every record (dev fixtures only)
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    OUTBOX.with(|m| m.borrow_mut().clear_new());
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    OUTBOX.with(|m| storage::map_stats(&m.borrow()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(effect: Effect) -> Job {
+        Job {
+            id: 7,
+            effect,
+            status: JobStatus::DeadLettered,
+            attempts: MAX_ATTEMPTS,
+            next_attempt_at: 0,
+            last_error: Some("timeout".to_string()),
+            created_at: 1,
+            updated_at: 2,
+            trace_id: None,
+        }
+    }
+
+    #[test]
+    fn jobs_stored_before_inbound_events_still_decode() {
+        let stored = OutboxJob {
+            id: 3,
+            effect: SideEffect::Matrix {
+                owner: Principal::from_slice(&[1]),
+                event: HookEvent::BookingCreated,
+                booking_id: "bk-1".to_string(),
+            },
+            status: JobStatus::Pending,
+            attempts: 2,
+            next_attempt_at: 10,
+            last_error: None,
+            created_at: 1,
+            updated_at: 5,
+            trace_id: None,
+        };
+        let job = Job::from_bytes(Cow::Owned(Encode!(&stored).unwrap()));
+        assert!(matches!(&job.effect, Effect::Matrix { booking_id, .. } if booking_id == "bk-1"));
+        assert_eq!(job.attempts, 2);
+        assert_eq!(job.public().map(|j| j.id), Some(3));
+    }
+
+    #[test]
+    fn stripe_events_stay_out_of_the_public_job_api() {
+        let stripe = job(Effect::StripeEvent { event_id: "evt_1".to_string(), payload: "{}".to_string() });
+        assert!(stripe.public().is_none());
+        let hook = job(Effect::IntegrationHook {
+            hook_id: "hook-1".to_string(),
+            event: HookEvent::BookingCancelled,
+            booking_id: "bk-2".to_string(),
+        });
+        assert!(hook.public().is_some());
+    }
+
+    #[test]
+    fn a_queued_payout_is_private_and_holds_the_owner() {
+        crate::clock::install(crate::clock::MockClock::at(1_735_689_600 * 1_000_000_000));
+        let owner = Principal::from_slice(&[4; 29]);
+        let payout = Payout {
+            owner,
+            ledger_canister: Principal::from_slice(&[5; 10]),
+            to: icrc_ledger_types::icrc1::account::Account { owner, subaccount: None },
+            debited: 100_000,
+            amount: 90_000,
+            created_at_time: 1,
+            reference: "withdrawal@1".to_string(),
+        };
+        assert!(!has_payout(owner));
+        enqueue_payout(payout.clone(), "Ledger call failed: SysTransient - timeout".to_string());
+        assert!(has_payout(owner));
+        assert!(job(Effect::Payout(payout)).public().is_none());
+
+        update_job(0, |job| job.status = JobStatus::DeadLettered);
+        assert!(list_failed_jobs(None).jobs.is_empty());
+        assert!(dead_letter(0).is_err());
+        assert!(has_payout(owner));
+    }
+}
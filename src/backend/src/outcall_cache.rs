@@ -0,0 +1,187 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use ic_cdk::api::call::CallResult;
+use ic_cdk::api::management_canister::http_request::{CanisterHttpRequestArgument, HttpResponse};
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::circuit_breaker::{self, Provider};
+use crate::api_usage;
+use crate::quarantine;
+
+const MAX_ENTRIES: u64 = 200;
+/// Larger responses are passed through uncached
+const MAX_CACHED_BODY_BYTES: usize = 256 * 1024;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Cacheable GET endpoints, each with its own freshness window
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CachedEndpoint {
+    GoogleCalendarEvents,
+    CalendlyApi,
+}
+
+impl CachedEndpoint {
+    fn ttl_ns(&self) -> u64 {
+        let secs = match self {
+            CachedEndpoint::GoogleCalendarEvents => 2 * 60,
+            CachedEndpoint::CalendlyApi => 10 * 60, // Dry run then import reuse the same listings
+        };
+        secs * 1_000_000_000
+    }
+
+    fn provider(&self) -> Provider {
+        match self {
+            CachedEndpoint::GoogleCalendarEvents => Provider::Google,
+            CachedEndpoint::CalendlyApi => Provider::Calendly,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CachedResponse {
+    pub owner: Principal,
+    pub endpoint: CachedEndpoint,
+    pub body: Vec<u8>,
+    pub fetched_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl CachedResponse {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Fetched at the epoch, so it is always stale.
+    fn unreadable() -> Self {
+        CachedResponse {
+            owner: Principal::anonymous(),
+            endpoint: CachedEndpoint::GoogleCalendarEvents,
+            body: vec![],
+            fetched_at: 0,
+        }
+    }
+}
+
+impl Storable for CachedResponse {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "CachedResponse").unwrap_or_else(CachedResponse::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Request hash -> last successful (200) response
+    static OUTCALL_CACHE: RefCell<StableBTreeMap<String, CachedResponse, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(42)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    OUTCALL_CACHE.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Hash of everything that shapes the response, headers included so that
+/// different credentials never share an entry
+fn request_key(request: &CanisterHttpRequestArgument) -> String {
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    hasher.update(request.url.as_bytes());
+    for header in &request.headers {
+        hasher.update(b"\n");
+        hasher.update(header.name.as_bytes());
+        hasher.update(b":");
+        hasher.update(header.value.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn store(key: String, entry: CachedResponse) {
+    OUTCALL_CACHE.with(|c| {
+        let mut map = c.borrow_mut();
+        if map.get(&key).is_none() && map.len() >= MAX_ENTRIES {
+            let oldest = map.iter().min_by_key(|(_, e)| e.fetched_at).map(|(k, _)| k);
+            if let Some(oldest) = oldest {
+                map.remove(&oldest);
+            }
+        }
+        map.insert(key, entry);
+    });
+}
+
+// ============================================================================
+// Cache
+// ============================================================================
+
+/// GET through the cache: a fresh entry is served without an outcall,
+/// otherwise the request goes out (through the provider's circuit breaker)
+/// and a 200 response is stored for the endpoint's TTL
+pub async fn http_request(
+    endpoint: CachedEndpoint,
+    owner: Principal,
+    request: CanisterHttpRequestArgument,
+    cycles: u128,
+) -> CallResult<(HttpResponse,)> {
+    let key = request_key(&request);
+    let now = time();
+    let hit = OUTCALL_CACHE.with(|c| c.borrow().get(&key))
+        .filter(|entry| entry.endpoint == endpoint && now < entry.fetched_at + endpoint.ttl_ns());
+    if let Some(entry) = hit {
+        return Ok((HttpResponse { status: 200u16.into(), headers: vec![], body: entry.body },));
+    }
+
+    let result = circuit_breaker::http_request(endpoint.provider(), request, cycles).await;
+    if endpoint.provider() == Provider::Google {
+        api_usage::record_google(owner, &result);
+    }
+    if let Ok((response,)) = &result {
+        if response.status == 200u16 && response.body.len() <= MAX_CACHED_BODY_BYTES {
+            store(key, CachedResponse { owner, endpoint, body: response.body.clone(), fetched_at: time() });
+        }
+    }
+    result
+}
+
+/// Drop the caller's cached responses so the next reads go to the provider.
+/// Returns how many entries were removed.
+pub fn clear_for(owner: Principal) -> u32 {
+    OUTCALL_CACHE.with(|c| {
+        let mut map = c.borrow_mut();
+        let keys: Vec<String> = map.iter().filter(|(_, e)| e.owner == owner).map(|(k, _)| k).collect();
+        for key in &keys {
+            map.remove(key);
+        }
+        keys.len() as u32
+    })
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    OUTCALL_CACHE.with(|m| storage::map_stats(&m.borrow()))
+}
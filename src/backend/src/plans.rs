@@ -0,0 +1,237 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::principal_moves;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PlanTier {
+    Free,
+    Pro,
+}
+
+/// Features that are gated by plan tier
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Feature {
+    Payments,
+    Webhooks,
+    MultipleCalendars,
+    Teams,
+}
+
+/// Where a paid plan came from (payment modules plug in here)
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PlanSource {
+    Default,
+    Admin,
+    Icrc,  // ICRC-2 recurring billing (billing.rs)
+    Promo, // Promo or referral code (promos.rs)
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct UserPlan {
+    pub tier: PlanTier,
+    pub source: PlanSource,
+    pub started_at: u64,
+    pub expires_at: Option<u64>, // None = never expires
+}
+
+/// What get_my_plan returns
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct PlanInfo {
+    pub tier: PlanTier,
+    pub source: PlanSource,
+    pub expires_at: Option<u64>,
+    pub features: Vec<Feature>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl UserPlan {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). The free plan.
+    fn unreadable() -> Self {
+        UserPlan {
+            tier: PlanTier::Free,
+            source: PlanSource::Default,
+            started_at: 0,
+            expires_at: None,
+        }
+    }
+}
+
+impl Storable for UserPlan {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "UserPlan").unwrap_or_else(UserPlan::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Only principals that were ever upgraded have an entry; everyone else is Free
+    static USER_PLANS: RefCell<StableBTreeMap<Principal, UserPlan, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5)))
+        )
+    );
+}
+
+pub fn clear() {
+    USER_PLANS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn free_plan() -> UserPlan {
+    UserPlan {
+        tier: PlanTier::Free,
+        source: PlanSource::Default,
+        started_at: 0,
+        expires_at: None,
+    }
+}
+
+/// Features unlocked by each tier
+pub fn tier_features(tier: PlanTier) -> Vec<Feature> {
+    match tier {
+        PlanTier::Free => vec![],
+        PlanTier::Pro => vec![
+            Feature::Payments,
+            Feature::Webhooks,
+            Feature::MultipleCalendars,
+            Feature::Teams,
+        ],
+    }
+}
+
+/// Effective plan for a principal; expired paid plans fall back to Free
+pub fn get_plan(principal: Principal) -> UserPlan {
+    match USER_PLANS.with(|p| p.borrow().get(&principal)) {
+        Some(plan) if plan.expires_at.is_none_or(|exp| exp > time()) => plan,
+        _ => free_plan(),
+    }
+}
+
+/// Gate a feature by the principal's tier
+pub fn require_feature(principal: Principal, feature: Feature) -> Result<(), String> {
+    let plan = get_plan(principal);
+    if !tier_features(plan.tier).contains(&feature) {
+        return Err(error_codes::coded(ErrorCode::FeatureNotAvailable, format!(
+            "{:?} requires a plan upgrade (current: {:?})",
+            feature, plan.tier
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Queries & Mutations
+// ============================================================================
+
+pub fn get_plan_info(principal: Principal) -> PlanInfo {
+    let plan = get_plan(principal);
+    PlanInfo {
+        tier: plan.tier,
+        source: plan.source,
+        expires_at: plan.expires_at,
+        features: tier_features(plan.tier),
+    }
+}
+
+/// Grant Pro for extra days, stacking on top of any remaining paid time.
+/// Plans that never expire are left untouched.
+pub fn extend_pro(principal: Principal, days: u32, source: PlanSource) {
+    let now = time();
+    let current = get_plan(principal);
+    
+    let base = match (current.tier, current.expires_at) {
+        (PlanTier::Pro, None) => return,
+        (PlanTier::Pro, Some(expires_at)) => expires_at.max(now),
+        (PlanTier::Free, _) => now,
+    };
+    let expires_at = base + days as u64 * 24 * 60 * 60 * 1_000_000_000;
+    
+    set_plan(principal, PlanTier::Pro, source, Some(expires_at));
+}
+
+pub fn count_owned(principal: Principal) -> u32 {
+    USER_PLANS.with(|p| principal_moves::count_keyed(&p.borrow(), principal))
+}
+
+/// Carry `from`'s plan over to `to` (the same user under a new principal)
+/// unless `to` has a plan of its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    USER_PLANS.with(|p| principal_moves::move_keyed(&mut p.borrow_mut(), from, to))
+}
+
+/// Assign a tier to a principal; Free removes the stored record
+pub fn set_plan(principal: Principal, tier: PlanTier, source: PlanSource, expires_at: Option<u64>) {
+    USER_PLANS.with(|p| {
+        let mut plans = p.borrow_mut();
+        match tier {
+            PlanTier::Free => {
+                plans.remove(&principal);
+            }
+            PlanTier::Pro => {
+                plans.insert(principal, UserPlan {
+                    tier,
+                    source,
+                    started_at: time(),
+                    expires_at,
+                });
+            }
+        }
+    });
+    ic_cdk::println!("💳 Plan for {} set to {:?}", principal.to_text(), tier);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    #[test]
+    fn a_plan_falls_back_to_free_once_it_expires() {
+        let clock = MockClock::at(1_735_689_600 * 1_000_000_000);
+        clock::install(clock.clone());
+        let principal = Principal::from_slice(&[7; 29]);
+        let day_ns = 24 * 60 * 60 * 1_000_000_000;
+        USER_PLANS.with(|p| p.borrow_mut().insert(principal, UserPlan {
+            tier: PlanTier::Pro,
+            source: PlanSource::Promo,
+            started_at: time(),
+            expires_at: Some(time() + 30 * day_ns),
+        }));
+
+        clock.advance(30 * day_ns - 1);
+        assert_eq!(get_plan(principal).tier, PlanTier::Pro);
+        clock.advance(1);
+        assert_eq!(get_plan(principal).tier, PlanTier::Free);
+    }
+}
@@ -0,0 +1,353 @@
+//! One-time moves of a user's records from an old principal to a new one.
+//! Principals are derived from `user_id:origin`, so when a retired origin is
+//! aliased to a canonical one (see origins.rs) the same user signs in as a
+//! new principal. Every store holding records that belong to a principal
+//! is listed in STORES, and all of them follow the user, either on their
+//! first sign-in after the alias or when a controller migrates them.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{
+    api_keys, api_usage, availabilities, availability_shares, billing, booking_events, bookings, calendar_access,
+    calendar_conflicts, delegations, discord, earnings, i18n, integrations, interviews, invoicing, legal_hold, matrix, migration, one_time_links,
+    plans, promos, quarantine, quotas, regions, session_links, teams, tenants, terms, tokens, trash, verified_emails,
+};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PrincipalMove {
+    pub from: Principal,
+    pub to: Principal,
+    pub moved_at: u64,
+    pub availabilities: u32, // Records moved
+    pub bookings: u32,
+    pub records: Option<Vec<MovedRecords>>, // Per store, including the two above (None before every store moved)
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct MovedRecords {
+    pub store: String,
+    pub count: u32,
+}
+
+/// A store holding records that belong to a principal
+struct Store {
+    name: &'static str,
+    #[cfg_attr(not(test), allow(dead_code))]
+    structures: &'static [&'static str], // As listed in storage.rs
+    count: fn(Principal) -> u32,         // Records the principal has in it
+    move_owner: fn(Principal, Principal) -> u32,
+    #[cfg_attr(not(feature = "dev-fixtures"), allow(dead_code))]
+    clear: fn(),                         // Drops every principal's records (reset_all_data)
+}
+
+/// Every store with per-principal records. A module that adds one joins
+/// here; the test below fails for a stable structure nobody accounted for.
+const STORES: [Store; 29] = [
+    Store { name: "availabilities", structures: &["AVAILABILITIES", "USER_AVAILABILITIES", "USER_FAVORITES"], count: availabilities::count_owned, move_owner: availabilities::move_owner, clear: availabilities::clear },
+    Store { name: "bookings", structures: &["BOOKINGS", "OWNER_BOOKINGS", "BOOKING_EVENTS"], count: bookings::count_owned, move_owner: bookings::move_owner, clear: clear_bookings },
+    Store { name: "availability shares", structures: &["AVAILABILITY_SHARES"], count: availability_shares::count_owned, move_owner: availability_shares::move_owner, clear: availability_shares::clear },
+    Store { name: "calendar conflicts", structures: &["CALENDAR_CONFLICTS"], count: calendar_conflicts::count_owned, move_owner: calendar_conflicts::move_owner, clear: calendar_conflicts::clear },
+    Store { name: "calendar token", structures: &["USER_TOKENS", "TOKENS", "ACCOUNT_LINKS", "TOKEN_FINGERPRINTS"], count: tokens::count_owned, move_owner: tokens::move_owner, clear: tokens::clear },
+    Store { name: "calendar revocation", structures: &["REVOCATIONS"], count: calendar_access::count_owned, move_owner: calendar_access::move_owner, clear: calendar_access::clear },
+    Store { name: "verified email", structures: &["VERIFIED_EMAILS"], count: verified_emails::count_owned, move_owner: verified_emails::move_owner, clear: verified_emails::clear },
+    Store { name: "terms acceptances", structures: &["TERMS_ACCEPTANCES"], count: terms::count_owned, move_owner: terms::move_owner, clear: terms::clear },
+    Store { name: "plan", structures: &["USER_PLANS"], count: plans::count_owned, move_owner: plans::move_owner, clear: plans::clear },
+    Store { name: "tenant", structures: &["MEMBERSHIPS", "TENANTS"], count: tenants::count_owned, move_owner: tenants::move_owner, clear: tenants::clear },
+    Store { name: "earnings", structures: &["JOURNAL", "BALANCES"], count: earnings::count_owned, move_owner: earnings::move_owner, clear: earnings::clear },
+    Store { name: "subscription", structures: &["SUBSCRIPTIONS"], count: billing::count_owned, move_owner: billing::move_owner, clear: billing::clear },
+    Store { name: "session links", structures: &["SESSION_LINKS"], count: session_links::count_owned, move_owner: session_links::move_owner, clear: session_links::clear },
+    Store { name: "one-time links", structures: &["ONE_TIME_LINKS"], count: one_time_links::count_owned, move_owner: one_time_links::move_owner, clear: one_time_links::clear },
+    Store { name: "tax profile", structures: &["TAX_PROFILES"], count: invoicing::count_owned, move_owner: invoicing::move_owner, clear: invoicing::clear },
+    Store { name: "delegations", structures: &["DELEGATIONS", "DELEGATE_AUDIT"], count: delegations::count_owned, move_owner: delegations::move_owner, clear: delegations::clear },
+    Store { name: "API keys", structures: &["API_KEYS"], count: api_keys::count_owned, move_owner: api_keys::move_owner, clear: api_keys::clear },
+    Store { name: "API usage", structures: &["API_USAGE"], count: api_usage::count_owned, move_owner: api_usage::move_owner, clear: api_usage::clear },
+    Store { name: "integration hooks", structures: &["HOOKS"], count: integrations::count_owned, move_owner: integrations::move_owner, clear: integrations::clear },
+    Store { name: "Matrix notifier", structures: &["MATRIX_NOTIFIERS"], count: matrix::count_owned, move_owner: matrix::move_owner, clear: matrix::clear },
+    Store { name: "Discord links", structures: &["DISCORD_LINKS"], count: discord::count_owned, move_owner: discord::move_owner, clear: discord::clear },
+    Store { name: "locale", structures: &["USER_LOCALES", "HIJRI_ANNOTATION"], count: i18n::count_owned, move_owner: i18n::move_owner, clear: i18n::clear },
+    Store { name: "limit override", structures: &["LIMIT_OVERRIDES"], count: quotas::count_owned, move_owner: quotas::move_owner, clear: quotas::clear },
+    Store { name: "referrals and discounts", structures: &["REDEMPTIONS", "BOOKING_DISCOUNTS", "REFERRAL_CODES", "REFERRALS"], count: promos::count_owned, move_owner: promos::move_owner, clear: promos::clear },
+    Store { name: "region declaration", structures: &["REGION_DECLARATIONS"], count: regions::count_owned, move_owner: regions::move_owner, clear: regions::clear },
+    Store { name: "legal holds", structures: &["LEGAL_HOLDS"], count: legal_hold::count_owned, move_owner: legal_hold::move_owner, clear: legal_hold::clear },
+    Store { name: "teams and interviews", structures: &["TEAMS", "PIPELINES", "CANDIDATES"], count: count_teams_and_interviews, move_owner: move_teams_and_interviews, clear: clear_teams_and_interviews },
+    Store { name: "trash", structures: &["TRASH"], count: trash::count_owned, move_owner: trash::move_owner, clear: trash::clear },
+    Store { name: "legacy sign-in indexes", structures: &["EMAIL_INDEX", "USERNAME_INDEX"], count: migration::count_owned, move_owner: migration::move_owner, clear: migration::clear_search_indices },
+];
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl PrincipalMove {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Between anonymous principals, moving nothing.
+    fn unreadable() -> Self {
+        PrincipalMove {
+            from: Principal::anonymous(),
+            to: Principal::anonymous(),
+            moved_at: 0,
+            availabilities: 0,
+            bookings: 0,
+            records: None,
+        }
+    }
+}
+
+impl Storable for PrincipalMove {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "PrincipalMove").unwrap_or_else(PrincipalMove::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Old principal -> where its records went; a principal is only moved once
+    static PRINCIPAL_MOVES: RefCell<StableBTreeMap<Principal, PrincipalMove, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(82)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear_history() {
+    PRINCIPAL_MOVES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn moved(from: Principal) -> bool {
+    PRINCIPAL_MOVES.with(|m| m.borrow().contains_key(&from))
+}
+
+/// Whether the principal owns anything worth moving
+fn has_records(principal: Principal) -> bool {
+    STORES.iter().any(|store| (store.count)(principal) > 0)
+}
+
+fn count_teams_and_interviews(principal: Principal) -> u32 {
+    teams::count_owned(principal) + interviews::count_owned(principal)
+}
+
+fn move_teams_and_interviews(from: Principal, to: Principal) -> u32 {
+    teams::move_owner(from, to) + interviews::move_owner(from, to)
+}
+
+fn clear_teams_and_interviews() {
+    teams::clear();
+    interviews::clear();
+}
+
+fn clear_bookings() {
+    bookings::clear();
+    booking_events::clear();
+}
+
+/// Drop every principal's records from every store
+#[cfg(feature = "dev-fixtures")]
+pub fn clear_all() {
+    for store in STORES.iter() {
+        (store.clear)();
+    }
+}
+
+/// The stable structures clear_all empties
+#[cfg(all(test, feature = "dev-fixtures"))]
+pub(crate) fn cleared_structures() -> impl Iterator<Item = &'static str> {
+    STORES.iter().flat_map(|store| store.structures.iter().copied())
+}
+
+fn move_all(from: Principal, to: Principal) -> Vec<MovedRecords> {
+    STORES.iter()
+        .map(|store| MovedRecords { store: store.name.to_string(), count: (store.move_owner)(from, to) })
+        .collect()
+}
+
+// ============================================================================
+// Store Helpers
+// ============================================================================
+
+/// 1 if the principal has a record in a one-per-user map
+pub fn count_keyed<V: Storable>(map: &StableBTreeMap<Principal, V, Memory>, principal: Principal) -> u32 {
+    map.contains_key(&principal) as u32
+}
+
+/// Move `from`'s record in a one-per-user map over to `to` unless `to` has
+/// one of its own, which is then kept. Returns the records moved.
+pub fn move_keyed<V: Storable>(map: &mut StableBTreeMap<Principal, V, Memory>, from: Principal, to: Principal) -> u32 {
+    match map.remove(&from) {
+        Some(record) if !map.contains_key(&to) => {
+            map.insert(to, record);
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Records the principal owns in a map keyed by record ID
+pub fn count_owned_in<K: Storable + Ord + Clone, V: Storable>(
+    map: &StableBTreeMap<K, V, Memory>,
+    owner: fn(&mut V) -> &mut Principal,
+    principal: Principal,
+) -> u32 {
+    map.iter().map(|(_, mut record)| *owner(&mut record)).filter(|p| *p == principal).count() as u32
+}
+
+/// Hand every record `from` owns in a map keyed by record ID over to `to`
+pub fn move_owned_in<K: Storable + Ord + Clone, V: Storable>(
+    map: &mut StableBTreeMap<K, V, Memory>,
+    owner: fn(&mut V) -> &mut Principal,
+    from: Principal,
+    to: Principal,
+) -> u32 {
+    let owned: Vec<(K, V)> = map.iter()
+        .filter_map(|(key, mut record)| (*owner(&mut record) == from).then_some((key, record)))
+        .collect();
+    let count = owned.len() as u32;
+    for (key, mut record) in owned {
+        *owner(&mut record) = to;
+        map.insert(key, record);
+    }
+    count
+}
+
+// ============================================================================
+// Moves
+// ============================================================================
+
+/// Move every record of `from` to `to`. Where a user has only one of
+/// something (token, plan, terms acceptances, favorite, tenant, ...), the
+/// one `to` already has is kept.
+pub fn migrate(from: Principal, to: Principal) -> Result<PrincipalMove, String> {
+    if from == to {
+        return Err("Cannot move a principal onto itself".to_string());
+    }
+    if from == Principal::anonymous() || to == Principal::anonymous() {
+        return Err("Cannot move records to or from the anonymous principal".to_string());
+    }
+    if moved(from) {
+        return Err(format!("{} was already moved", from.to_text()));
+    }
+    // A payout or charge awaiting the ledger would settle against `from`
+    // after its balance or subscription had moved on
+    if earnings::withdrawal_in_flight(from) || billing::charge_in_flight(from) {
+        return Err(format!("{} has a payment in flight; try again shortly", from.to_text()));
+    }
+
+    let records = move_all(from, to);
+    let moved = |name: &str| records.iter().find(|r| r.store == name).map_or(0, |r| r.count);
+    let (availabilities, bookings) = (moved("availabilities"), moved("bookings"));
+
+    let record = PrincipalMove { from, to, moved_at: time(), availabilities, bookings, records: Some(records) };
+    PRINCIPAL_MOVES.with(|m| m.borrow_mut().insert(from, record.clone()));
+    ic_cdk::println!(
+        "🔀 Moved {} -> {} ({} availabilities, {} bookings)",
+        from.to_text(), to.to_text(), availabilities, bookings
+    );
+    Ok(record)
+}
+
+/// Pick up the records a user left under the principals they had on retired
+/// origins. Called at sign-in; principals without records are skipped so
+/// they can still be moved later if records turn up.
+pub fn on_sign_in(to: Principal, previous: Vec<Principal>) {
+    for from in previous {
+        if from != to && !moved(from) && has_records(from) {
+            if let Err(e) = migrate(from, to) {
+                ic_cdk::println!("⚠️ Moving {} at sign-in failed: {}", from.to_text(), e);
+            }
+        }
+    }
+}
+
+/// Every move, most recent first
+pub fn list_moves() -> Vec<PrincipalMove> {
+    let mut list: Vec<PrincipalMove> = PRINCIPAL_MOVES.with(|m| m.borrow().iter().map(|(_, record)| record).collect());
+    list.sort_by_key(|record| std::cmp::Reverse(record.moved_at));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+    use crate::i18n::Locale;
+    use crate::storage;
+
+    /// Structures whose records are keyed by something other than a user:
+    /// configuration, tenants, caches, per-booking and per-availability
+    /// bookkeeping, and expiring sign-in sessions
+    const NOT_PER_PRINCIPAL: &[&str] = &[
+        "DEFAULT_LIMITS", "BILLING_CONFIG", "PROMO_CODES", "ORIGIN_CONFIGS", "LINK_STATS", "RESOURCES",
+        "RESERVATIONS", "ORG_BUSY", "DISCORD_CONFIG", "OUTBOX", "OUTCALL_CACHE", "STORAGE_SNAPSHOT",
+        "CORRUPT_RECORDS", "UPGRADE_SNAPSHOT", "STABLE_SESSIONS", "MIGRATION_STATUS", "MAINTENANCE_JOBS",
+        "SYNC_STATE", "SEEN_WEBHOOK_EVENTS", "STRIPE_CONFIG", "STRIPE_DEAD_LETTERS (retired)",
+        "NOTIFICATION_TEMPLATES", "DELIVERY_LOG", "SLOT_EXPERIMENTS", "CURSOR_KEY", "TERMS_CONFIG",
+        "REGION_POLICIES", "ANALYTICS_CONFIG", "ANALYTICS_EVENTS", "COUNTERS", "REBUILD_STATUS", "CHECK_INS",
+        "FOLLOW_UP_QUEUE", "ORG_DEFAULTS", "SELF_TEST_SCRATCH", "ORIGIN_ALIASES", "PRINCIPAL_MOVES",
+        "READ_CACHE_CONFIG", "READ_CACHE_PENDING", "ENTROPY_POOL", "UPGRADE_DIGESTS",
+    ];
+
+    #[test]
+    fn every_stable_structure_is_moved_or_not_per_principal() {
+        let moved: Vec<&str> = STORES.iter().flat_map(|store| store.structures.iter().copied()).collect();
+        for (id, name) in storage::STRUCTURES {
+            assert!(
+                moved.contains(&name) != NOT_PER_PRINCIPAL.contains(&name),
+                "memory {} ({}) must be in exactly one of STORES or NOT_PER_PRINCIPAL",
+                id, name
+            );
+        }
+    }
+
+    #[test]
+    fn a_move_leaves_nothing_behind() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        let from = Principal::from_slice(&[1; 29]);
+        let to = Principal::from_slice(&[2; 29]);
+        verified_emails::record(from, "Ada@example.com");
+        i18n::set_user_locale(from, Locale::De);
+        i18n::set_hijri_annotation(from, true);
+        regions::declare(from, "de".to_string(), true).unwrap();
+        i18n::set_user_locale(to, Locale::Ar);
+
+        let count = |principal| STORES.iter().map(|store| (store.count)(principal)).sum::<u32>();
+        let before = count(from);
+        assert_eq!(before, 4);
+        assert!(has_records(from));
+
+        let records = move_all(from, to);
+
+        assert_eq!(count(from), 0);
+        assert!(!has_records(from));
+        // `to` keeps its own locale; everything else came over
+        assert_eq!(count(to), before);
+        assert_eq!(records.iter().map(|r| r.count).sum::<u32>(), before - 1);
+        assert_eq!(i18n::get_user_locale(to), Some(Locale::Ar));
+        assert_eq!(verified_emails::get(to).as_deref(), Some("ada@example.com"));
+    }
+}
@@ -0,0 +1,426 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::plans::{self, PlanSource};
+use crate::principal_moves;
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Referral codes are derived from the owner's principal and use this prefix
+const REFERRAL_PREFIX: &str = "REF-";
+
+/// Pro days granted to the new user and to the referrer
+const REFEREE_REWARD_DAYS: u32 = 14;
+const REFERRER_REWARD_DAYS: u32 = 30;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PromoReward {
+    PlanUpgrade { days: u32 },       // Pro for N days (stacks on an existing expiry)
+    BookingDiscount { percent: u8 }, // Applied to the caller's next paid booking
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct PromoCode {
+    pub code: String,
+    pub reward: PromoReward,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: Option<u64>,
+    pub created_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BookingDiscount {
+    pub percent: u8,
+    pub code: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Referral {
+    pub referrer: Principal,
+    pub redeemed_at: u64,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ReferralStats {
+    pub code: String,
+    pub referred_count: u32,
+    pub reward_days_earned: u32,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl PromoCode {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Expired and used up, so it can't be redeemed.
+    fn unreadable() -> Self {
+        PromoCode {
+            code: String::new(),
+            reward: PromoReward::BookingDiscount { percent: 0 },
+            max_uses: 0,
+            uses: 0,
+            expires_at: Some(0),
+            created_by: Principal::anonymous(),
+            created_at: 0,
+        }
+    }
+}
+
+impl Storable for PromoCode {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "PromoCode").unwrap_or_else(PromoCode::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for BookingDiscount {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "BookingDiscount").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Referral {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Credited to nobody.
+    fn unreadable() -> Self {
+        Referral {
+            referrer: Principal::anonymous(),
+            redeemed_at: 0,
+        }
+    }
+}
+
+impl Storable for Referral {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Referral").unwrap_or_else(Referral::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static PROMO_CODES: RefCell<StableBTreeMap<String, PromoCode, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8)))
+        )
+    );
+
+    // "CODE|principal" -> redeemed_at, so a user can redeem each code once
+    static REDEMPTIONS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9)))
+        )
+    );
+
+    // Discount waiting to be applied to the principal's next paid booking
+    static BOOKING_DISCOUNTS: RefCell<StableBTreeMap<Principal, BookingDiscount, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10)))
+        )
+    );
+
+    static REFERRAL_CODES: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11)))
+        )
+    );
+
+    // Referee -> who referred them (a user can only ever be referred once)
+    static REFERRALS: RefCell<StableBTreeMap<Principal, Referral, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12)))
+        )
+    );
+}
+
+pub fn clear() {
+    REDEMPTIONS.with(|m| m.borrow_mut().clear_new());
+    BOOKING_DISCOUNTS.with(|m| m.borrow_mut().clear_new());
+    REFERRAL_CODES.with(|m| m.borrow_mut().clear_new());
+    REFERRALS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn validate_code(code: &str) -> Result<(), String> {
+    if code.len() < 3 || code.len() > 32 {
+        return Err("code must be 3-32 characters".to_string());
+    }
+    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err("code may only contain letters, digits and dashes".to_string());
+    }
+    if code.starts_with(REFERRAL_PREFIX) {
+        return Err(format!("codes starting with {} are reserved for referrals", REFERRAL_PREFIX));
+    }
+    Ok(())
+}
+
+/// Deterministic referral code for a principal: REF- + first 8 hex chars of its hash
+fn referral_code_for(principal: Principal) -> String {
+    use sha2::{Sha256, Digest};
+    let hash = Sha256::digest(principal.as_slice());
+    format!("{}{}", REFERRAL_PREFIX, hex::encode(&hash[..4]).to_uppercase())
+}
+
+fn apply_reward(principal: Principal, reward: &PromoReward, code: &str) {
+    match reward {
+        PromoReward::PlanUpgrade { days } => {
+            plans::extend_pro(principal, *days, PlanSource::Promo);
+        }
+        PromoReward::BookingDiscount { percent } => {
+            BOOKING_DISCOUNTS.with(|d| {
+                d.borrow_mut().insert(principal, BookingDiscount {
+                    percent: *percent,
+                    code: code.to_string(),
+                });
+            });
+        }
+    }
+}
+
+// ============================================================================
+// Promo Codes (Admin)
+// ============================================================================
+
+pub fn create_promo_code(
+    caller: Principal,
+    code: String,
+    reward: PromoReward,
+    max_uses: u32,
+    expires_at: Option<u64>,
+) -> Result<PromoCode, String> {
+    let code = normalize_code(&code);
+    validate_code(&code)?;
+
+    if let PromoReward::BookingDiscount { percent } = reward {
+        if percent == 0 || percent > 100 {
+            return Err("discount percent must be 1-100".to_string());
+        }
+    }
+    if max_uses == 0 {
+        return Err("max_uses must be at least 1".to_string());
+    }
+
+    PROMO_CODES.with(|p| {
+        let mut codes = p.borrow_mut();
+        if codes.contains_key(&code) {
+            return Err("Promo code already exists".to_string());
+        }
+
+        let promo = PromoCode {
+            code: code.clone(),
+            reward,
+            max_uses,
+            uses: 0,
+            expires_at,
+            created_by: caller,
+            created_at: time(),
+        };
+        codes.insert(code.clone(), promo.clone());
+
+        ic_cdk::println!("🎟️ Created promo code: {}", code);
+        Ok(promo)
+    })
+}
+
+pub fn list_promo_codes() -> Vec<PromoCode> {
+    PROMO_CODES.with(|p| p.borrow().iter().map(|(_, promo)| promo).collect())
+}
+
+pub fn delete_promo_code(code: String) -> Result<(), String> {
+    PROMO_CODES.with(|p| {
+        p.borrow_mut()
+            .remove(&normalize_code(&code))
+            .map(|_| ())
+            .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Promo code not found"))
+    })
+}
+
+// ============================================================================
+// Redemption
+// ============================================================================
+
+/// Redeem a promo or referral code, returning the reward the caller received
+pub fn redeem_code(caller: Principal, code: String) -> Result<PromoReward, String> {
+    let code = normalize_code(&code);
+    if code.starts_with(REFERRAL_PREFIX) {
+        return redeem_referral(caller, &code);
+    }
+
+    let mut promo = PROMO_CODES.with(|p| p.borrow().get(&code))
+        .ok_or("Invalid promo code")?;
+
+    if promo.expires_at.is_some_and(|exp| exp <= time()) {
+        return Err(error_codes::coded(ErrorCode::TokenExpired, "Promo code has expired"));
+    }
+    if promo.uses >= promo.max_uses {
+        return Err("Promo code has reached its usage limit".to_string());
+    }
+
+    let redemption_key = format!("{}|{}", code, caller.to_text());
+    if REDEMPTIONS.with(|r| r.borrow().contains_key(&redemption_key)) {
+        return Err("You have already redeemed this code".to_string());
+    }
+
+    promo.uses += 1;
+    PROMO_CODES.with(|p| p.borrow_mut().insert(code.clone(), promo.clone()));
+    REDEMPTIONS.with(|r| r.borrow_mut().insert(redemption_key, time()));
+    apply_reward(caller, &promo.reward, &code);
+
+    ic_cdk::println!("🎟️ {} redeemed {}", caller.to_text(), code);
+    Ok(promo.reward)
+}
+
+fn redeem_referral(caller: Principal, code: &str) -> Result<PromoReward, String> {
+    let referrer = REFERRAL_CODES.with(|r| r.borrow().get(&code.to_string()))
+        .ok_or("Invalid referral code")?;
+
+    if referrer == caller {
+        return Err("You cannot redeem your own referral code".to_string());
+    }
+    if REFERRALS.with(|r| r.borrow().contains_key(&caller)) {
+        return Err("You have already used a referral code".to_string());
+    }
+
+    REFERRALS.with(|r| {
+        r.borrow_mut().insert(caller, Referral {
+            referrer,
+            redeemed_at: time(),
+        })
+    });
+
+    let reward = PromoReward::PlanUpgrade { days: REFEREE_REWARD_DAYS };
+    apply_reward(caller, &reward, code);
+    plans::extend_pro(referrer, REFERRER_REWARD_DAYS, PlanSource::Promo);
+
+    ic_cdk::println!("🤝 {} was referred by {}", caller.to_text(), referrer.to_text());
+    Ok(reward)
+}
+
+// ============================================================================
+// Referral Codes & Discounts
+// ============================================================================
+
+/// Get (and register on first use) the caller's referral code with stats
+pub fn get_referral_stats(caller: Principal) -> ReferralStats {
+    let code = referral_code_for(caller);
+    REFERRAL_CODES.with(|r| {
+        let mut codes = r.borrow_mut();
+        if !codes.contains_key(&code) {
+            codes.insert(code.clone(), caller);
+        }
+    });
+
+    let referred_count = REFERRALS.with(|r| {
+        r.borrow()
+            .iter()
+            .filter(|(_, referral)| referral.referrer == caller)
+            .count() as u32
+    });
+
+    ReferralStats {
+        code,
+        referred_count,
+        reward_days_earned: referred_count * REFERRER_REWARD_DAYS,
+    }
+}
+
+pub fn get_booking_discount(principal: Principal) -> Option<BookingDiscount> {
+    BOOKING_DISCOUNTS.with(|d| d.borrow().get(&principal))
+}
+
+/// Consume the pending discount when a paid booking is charged
+pub fn take_booking_discount(principal: Principal) -> Option<BookingDiscount> {
+    BOOKING_DISCOUNTS.with(|d| d.borrow_mut().remove(&principal))
+}
+
+/// Give a taken discount back when the payment it was meant for failed
+pub fn restore_booking_discount(principal: Principal, discount: BookingDiscount) {
+    BOOKING_DISCOUNTS.with(|d| {
+        d.borrow_mut().insert(principal, discount);
+    });
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+fn redemption_keys(principal: Principal) -> Vec<String> {
+    let suffix = format!("|{}", principal.to_text());
+    REDEMPTIONS.with(|r| r.borrow().iter().map(|(key, _)| key).filter(|key| key.ends_with(&suffix)).collect())
+}
+
+/// Redemptions, pending discount, referral codes and referrals of the principal
+pub fn count_owned(principal: Principal) -> u32 {
+    let referrals = REFERRALS.with(|r| {
+        r.borrow().iter().filter(|(referee, referral)| *referee == principal || referral.referrer == principal).count()
+    });
+    redemption_keys(principal).len() as u32
+        + BOOKING_DISCOUNTS.with(|d| principal_moves::count_keyed(&d.borrow(), principal))
+        + REFERRAL_CODES.with(|c| principal_moves::count_owned_in(&c.borrow(), |referrer| referrer, principal))
+        + referrals as u32
+}
+
+/// Carry `from`'s promo history over to `to` (the same user under a new
+/// principal): codes it redeemed stay redeemed, its referral code and the
+/// people it referred count for `to`, and a referral it used still counts
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let redeemed = redemption_keys(from);
+    REDEMPTIONS.with(|r| {
+        let mut map = r.borrow_mut();
+        for key in &redeemed {
+            if let Some(at) = map.remove(key) {
+                let code = &key[..key.len() - from.to_text().len() - 1];
+                map.insert(format!("{}|{}", code, to.to_text()), at);
+            }
+        }
+    });
+    let discount = BOOKING_DISCOUNTS.with(|d| principal_moves::move_keyed(&mut d.borrow_mut(), from, to));
+    let codes = REFERRAL_CODES.with(|c| principal_moves::move_owned_in(&mut c.borrow_mut(), |referrer| referrer, from, to));
+    let referrals = REFERRALS.with(|r| {
+        let mut map = r.borrow_mut();
+        let used = principal_moves::move_keyed(&mut map, from, to);
+        used + principal_moves::move_owned_in(&mut map, |referral| &mut referral.referrer, from, to)
+    });
+    redeemed.len() as u32 + discount + codes + referrals
+}
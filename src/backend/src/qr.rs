@@ -0,0 +1,72 @@
+use qrcodegen::{QrCode, QrCodeEcc};
+use crate::availabilities;
+use crate::error_codes::{self, ErrorCode};
+use crate::http::{self, HttpResponse};
+use crate::origins;
+
+/// Share links point here unless the caller names its own frontend origin
+const DEFAULT_SHARE_ORIGIN: &str = "https://weeekaly.com";
+
+/// Quiet zone around the code, in modules (the spec minimum)
+const BORDER: i32 = 4;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Public booking page of an availability on a frontend origin
+pub fn share_url(origin: Option<&str>, id: &str) -> Result<String, String> {
+    let origin = origins::normalize_origin(origin.unwrap_or(DEFAULT_SHARE_ORIGIN));
+    origins::validate_origin(&origin)?;
+    Ok(format!("{}/availability/{}", origin, id))
+}
+
+/// Render a QR code as a compact SVG with one path for all dark modules
+fn render_svg(text: &str) -> Result<String, String> {
+    let qr = QrCode::encode_text(text, QrCodeEcc::Medium)
+        .map_err(|_| "Link is too long for a QR code".to_string())?;
+
+    let size = qr.size() + BORDER * 2;
+    let mut path = String::new();
+    for y in 0..qr.size() {
+        for x in 0..qr.size() {
+            if qr.get_module(x, y) {
+                path.push_str(&format!("M{},{}h1v1h-1z", x + BORDER, y + BORDER));
+            }
+        }
+    }
+
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" shape-rendering=\"crispEdges\">\
+<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/><path d=\"{path}\" fill=\"#000000\"/></svg>"
+    ))
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// SVG QR code for an availability's share link
+pub fn get_availability_qr_svg(id: String, origin: Option<String>) -> Result<String, String> {
+    let availability = availabilities::get_availability(id)?;
+    render_svg(&share_url(origin.as_deref(), &availability.id)?)
+}
+
+/// Serves `GET /qr/<availability_id>.svg[?origin=https://…]` so printed
+/// material can embed the code with a plain image URL
+pub fn serve(file: &str, query: &str) -> HttpResponse {
+    let Some(id) = file.strip_suffix(".svg") else {
+        return http::text(404, "Not found");
+    };
+    match get_availability_qr_svg(id.to_string(), http::query_param(query, "origin")) {
+        Ok(svg) => {
+            let mut response = http::response(200, "image/svg+xml", svg.into_bytes());
+            response.headers.push(("Cache-Control".to_string(), "public, max-age=3600".to_string()));
+            response
+        }
+        Err(e) => match error_codes::split(&e) {
+            (ErrorCode::AvailNotFound, text) => http::text(404, text),
+            (_, text) => http::text(400, text),
+        },
+    }
+}
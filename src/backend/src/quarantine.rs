@@ -0,0 +1,164 @@
+use candid::{CandidType, Decode, Encode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, bookings, response_size};
+use crate::error_codes::{self, ErrorCode};
+
+const CORRUPT_RECORDS_PAGE_SIZE: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Raw bytes of a stored record that no longer decodes
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CorruptRecord {
+    pub id: String,              // sha256 of the bytes, so re-reads don't add duplicates
+    pub type_name: String,
+    pub bytes: Vec<u8>,
+    pub error: String,
+    pub first_seen_at: u64,
+}
+
+/// One page of quarantined records; pass `next_cursor` to get the next one
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct CorruptRecordsPage {
+    pub records: Vec<CorruptRecord>,
+    pub next_cursor: Option<String>,
+    pub truncated: bool, // Cut short by reply size rather than page size
+}
+
+/// Placeholders removed from the live maps by `sweep_corrupt_records`
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SweepReport {
+    pub availabilities: Vec<String>,
+    pub bookings: Vec<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for CorruptRecord {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    // Can't quarantine itself, so an unreadable one keeps its raw bytes in a
+    // record of its own (listed with an empty ID, purged by sweeps)
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap_or_else(|e| CorruptRecord {
+            id: String::new(),
+            type_name: "CorruptRecord".to_string(),
+            bytes: bytes.to_vec(),
+            error: e.to_string(),
+            first_seen_at: 0,
+        })
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Bytes hash -> record. Only persisted when the failed read happens in an
+    // update call; a query re-quarantines on every read until then.
+    static CORRUPT_RECORDS: RefCell<StableBTreeMap<String, CorruptRecord, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(44)))
+        )
+    );
+}
+
+// ============================================================================
+// Decoding
+// ============================================================================
+
+/// Decode a stored record, or log it and copy its bytes to the corrupt store.
+/// Meant for `Storable::from_bytes`, which then falls back to a placeholder
+/// instead of trapping and taking every read of the map down with it.
+pub fn decode<T: CandidType + DeserializeOwned>(bytes: &[u8], type_name: &str) -> Option<T> {
+    let error = match Decode!(bytes, T) {
+        Ok(value) => return Some(value),
+        Err(e) => e.to_string(),
+    };
+
+    use sha2::{Sha256, Digest};
+    let id = hex::encode(Sha256::digest(bytes));
+    ic_cdk::println!("☣️ [quarantine] Undecodable {} record {}: {}", type_name, id, error);
+    CORRUPT_RECORDS.with(|c| {
+        let mut map = c.borrow_mut();
+        if !map.contains_key(&id) {
+            map.insert(id.clone(), CorruptRecord {
+                id,
+                type_name: type_name.to_string(),
+                bytes: bytes.to_vec(),
+                error,
+                first_seen_at: time(),
+            });
+        }
+    });
+    None
+}
+
+// ============================================================================
+// Admin
+// ============================================================================
+
+/// Quarantined records with IDs from `cursor` on
+pub fn list_corrupt_records(cursor: Option<String>) -> CorruptRecordsPage {
+    let mut records: Vec<CorruptRecord> = CORRUPT_RECORDS.with(|c| {
+        c.borrow()
+            .range(cursor.unwrap_or_default()..)
+            .map(|(_, record)| record)
+            .take(CORRUPT_RECORDS_PAGE_SIZE + 1)
+            .collect()
+    });
+    let mut next_cursor = if records.len() > CORRUPT_RECORDS_PAGE_SIZE {
+        records.pop().map(|record| record.id)
+    } else {
+        None
+    };
+    // Raw bytes can be large, so the page may also be cut by size
+    let kept = response_size::fitting_len(&records, response_size::MAX_RESPONSE_BYTES);
+    let truncated = kept < records.len();
+    if truncated {
+        next_cursor = Some(records[kept].id.clone());
+        records.truncate(kept);
+    }
+    CorruptRecordsPage { records, next_cursor, truncated }
+}
+
+/// Drop the placeholders that stand in for undecodable records, so they stop
+/// showing up in listings. Their bytes stay in the corrupt store.
+pub fn sweep_corrupt_records() -> SweepReport {
+    let report = SweepReport {
+        availabilities: availabilities::remove_unreadable(),
+        bookings: bookings::remove_unreadable(),
+    };
+    ic_cdk::println!(
+        "🧹 [quarantine] Swept {} availabilities and {} bookings",
+        report.availabilities.len(),
+        report.bookings.len()
+    );
+    report
+}
+
+/// Forget a quarantined record once it has been inspected or restored
+pub fn purge_corrupt_record(id: String) -> Result<(), String> {
+    CORRUPT_RECORDS.with(|c| c.borrow_mut().remove(&id))
+        .map(|_| ())
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Corrupt record not found"))
+}
@@ -0,0 +1,65 @@
+use candid::Principal;
+use chrono::{SecondsFormat, TimeZone};
+use chrono_tz::Tz;
+use crate::clock::time;
+use crate::{availabilities, datetime};
+use crate::bookings::{self, CreateBookingRequest};
+
+/// Meeting length when the availability doesn't define duration options
+const DEFAULT_MINUTES: u32 = 30;
+const MAX_DAYS: u32 = 31;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// RFC 3339 timestamp in the availability's timezone
+fn iso(tz: &Tz, unix_secs: u64) -> String {
+    tz.timestamp_opt(unix_secs as i64, 0)
+        .single()
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, false))
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// Free windows over the next `days` as ISO 8601 intervals ("start/end") in
+/// the availability's timezone, one per entry
+pub fn quick_free(slug: String, days: u32) -> Result<Vec<String>, String> {
+    if days == 0 || days > MAX_DAYS {
+        return Err(format!("days must be 1-{}", MAX_DAYS));
+    }
+    let availability = availabilities::get_availability(slug.clone())?;
+    let tz: Tz = availability.timezone.parse().unwrap_or(Tz::UTC);
+    let now = time() / 1_000_000_000;
+
+    let slots = availabilities::get_free_slots(slug, now, now + days as u64 * 24 * 60 * 60, None)?;
+    Ok(slots.iter().map(|s| format!("{}/{}", iso(&tz, s.start_time), iso(&tz, s.end_time))).collect())
+}
+
+/// Book the availability's default length at an RFC 3339 start time and
+/// return the booking ID. The guest name is taken from the email.
+pub fn quick_book(caller: Principal, slug: String, iso_start: String, email: String) -> Result<String, String> {
+    let start = datetime::parse_iso8601(&iso_start).map_err(|e| format!("iso_start: {}", e))?;
+
+    let availability = availabilities::get_availability(slug)?;
+    let minutes = availability.durations
+        .as_ref()
+        .and_then(|options| options.first())
+        .map(|o| o.minutes)
+        .unwrap_or(DEFAULT_MINUTES);
+    let guest_name = email.split('@').next().unwrap_or_default().to_string();
+
+    let booking = bookings::create_booking(caller, CreateBookingRequest {
+        availability_id: availability.id,
+        start_time: start,
+        end_time: start + minutes as u64 * 60,
+        guest_name,
+        guest_email: Some(email.trim().to_string()),
+        guest_timezone: None,
+        field_answers: None,
+    })?;
+    Ok(booking.id)
+}
@@ -0,0 +1,264 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{principal_moves, quarantine};
+use crate::error_codes::{self, ErrorCode};
+
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+const DAY_NS: u64 = 24 * HOUR_NS;
+/// Google calls per account per day when the stored limits predate the setting
+const DEFAULT_GOOGLE_CALLS_PER_DAY: u32 = 1_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Per-principal limits protecting cycles and storage
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuotaLimits {
+    pub max_availabilities: u32,
+    pub max_bookings_per_day: u32,
+    pub max_webhook_subscriptions: u32,
+    pub max_outcalls_per_hour: u32, // Calls that trigger Google HTTP outcalls
+    pub max_google_calls_per_day: Option<u32>, // Actual Google API calls per connected account (None = 1000)
+}
+
+impl Default for QuotaLimits {
+    fn default() -> Self {
+        QuotaLimits {
+            max_availabilities: 20,
+            max_bookings_per_day: 50,
+            max_webhook_subscriptions: 5,
+            max_outcalls_per_hour: 120,
+            max_google_calls_per_day: Some(DEFAULT_GOOGLE_CALLS_PER_DAY),
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QuotaKind {
+    Availabilities,
+    BookingsPerDay,
+    WebhookSubscriptions,
+    OutcallsPerHour,
+    GoogleCallsPerDay,
+}
+
+impl QuotaKind {
+    fn name(&self) -> &'static str {
+        match self {
+            QuotaKind::Availabilities => "max_availabilities",
+            QuotaKind::BookingsPerDay => "max_bookings_per_day",
+            QuotaKind::WebhookSubscriptions => "max_webhook_subscriptions",
+            QuotaKind::OutcallsPerHour => "max_outcalls_per_hour",
+            QuotaKind::GoogleCallsPerDay => "max_google_calls_per_day",
+        }
+    }
+
+    fn limit(&self, limits: &QuotaLimits) -> u32 {
+        match self {
+            QuotaKind::Availabilities => limits.max_availabilities,
+            QuotaKind::BookingsPerDay => limits.max_bookings_per_day,
+            QuotaKind::WebhookSubscriptions => limits.max_webhook_subscriptions,
+            QuotaKind::OutcallsPerHour => limits.max_outcalls_per_hour,
+            QuotaKind::GoogleCallsPerDay => limits.max_google_calls_per_day.unwrap_or(DEFAULT_GOOGLE_CALLS_PER_DAY),
+        }
+    }
+
+    /// Length of the rolling window for rate-style quotas
+    fn window_ns(&self) -> u64 {
+        match self {
+            QuotaKind::BookingsPerDay | QuotaKind::GoogleCallsPerDay => DAY_NS,
+            _ => HOUR_NS,
+        }
+    }
+}
+
+/// What get_my_quotas returns: effective limits plus current usage
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct QuotaStatus {
+    pub limits: QuotaLimits,
+    pub is_override: bool,
+    pub availabilities: u32,
+    pub bookings_today: u32,
+    pub outcalls_this_hour: u32,
+    pub google_calls_today: u32,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for QuotaLimits {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "QuotaLimits").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static DEFAULT_LIMITS: RefCell<StableCell<QuotaLimits, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
+            QuotaLimits::default(),
+        ).expect("Failed to init quota defaults")
+    );
+
+    // Admin overrides replace the defaults entirely for a principal
+    static LIMIT_OVERRIDES: RefCell<StableBTreeMap<Principal, QuotaLimits, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4)))
+        )
+    );
+
+    // Rolling windows: (principal, kind) -> (window_start, count). Heap only, reset on upgrade
+    static RATE_WINDOWS: RefCell<HashMap<(Principal, QuotaKind), (u64, u32)>> = RefCell::new(HashMap::new());
+}
+
+pub fn clear() {
+    LIMIT_OVERRIDES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn quota_exceeded(kind: QuotaKind, limit: u32) -> String {
+    error_codes::coded(ErrorCode::QuotaExceeded, format!("{} limit of {} reached", kind.name(), limit))
+}
+
+/// Effective limits for a principal (override if present, else defaults)
+pub fn limits_for(principal: Principal) -> QuotaLimits {
+    LIMIT_OVERRIDES
+        .with(|o| o.borrow().get(&principal))
+        .unwrap_or_else(|| DEFAULT_LIMITS.with(|d| d.borrow().get().clone()))
+}
+
+/// Current count inside the rate window, or 0 if the window has elapsed
+fn window_usage(principal: Principal, kind: QuotaKind) -> u32 {
+    let now = time();
+    RATE_WINDOWS.with(|w| {
+        match w.borrow().get(&(principal, kind)) {
+            Some((start, count)) if now - start < kind.window_ns() => *count,
+            _ => 0,
+        }
+    })
+}
+
+// ============================================================================
+// Enforcement
+// ============================================================================
+
+/// Check a stock-style quota (things the principal currently owns)
+/// before creating one more
+pub fn check_count(principal: Principal, kind: QuotaKind, current: u32) -> Result<(), String> {
+    let limit = kind.limit(&limits_for(principal));
+    if current >= limit {
+        return Err(quota_exceeded(kind, limit));
+    }
+    Ok(())
+}
+
+/// Fail if a rate-style quota is exhausted, without consuming a unit
+pub fn check_rate(principal: Principal, kind: QuotaKind) -> Result<(), String> {
+    let limit = kind.limit(&limits_for(principal));
+    if window_usage(principal, kind) >= limit {
+        return Err(quota_exceeded(kind, limit));
+    }
+    Ok(())
+}
+
+/// Consume one unit of a rate-style quota, failing without consuming if exhausted
+pub fn consume_rate(principal: Principal, kind: QuotaKind) -> Result<(), String> {
+    let limit = kind.limit(&limits_for(principal));
+    let now = time();
+
+    RATE_WINDOWS.with(|w| {
+        let mut windows = w.borrow_mut();
+        let entry = windows.entry((principal, kind)).or_insert((now, 0));
+
+        // Start a fresh window once the previous one has elapsed
+        if now - entry.0 >= kind.window_ns() {
+            *entry = (now, 0);
+        }
+
+        if entry.1 >= limit {
+            ic_cdk::println!("🚫 Quota exceeded for {}: {}", principal.to_text(), kind.name());
+            return Err(quota_exceeded(kind, limit));
+        }
+
+        entry.1 += 1;
+        Ok(())
+    })
+}
+
+// ============================================================================
+// Queries & Admin
+// ============================================================================
+
+pub fn get_quota_status(principal: Principal, availabilities: u32, google_calls_today: u32) -> QuotaStatus {
+    QuotaStatus {
+        limits: limits_for(principal),
+        is_override: LIMIT_OVERRIDES.with(|o| o.borrow().contains_key(&principal)),
+        availabilities,
+        bookings_today: window_usage(principal, QuotaKind::BookingsPerDay),
+        outcalls_this_hour: window_usage(principal, QuotaKind::OutcallsPerHour),
+        google_calls_today,
+    }
+}
+
+pub fn get_default_limits() -> QuotaLimits {
+    DEFAULT_LIMITS.with(|d| d.borrow().get().clone())
+}
+
+pub fn set_default_limits(limits: QuotaLimits) -> Result<(), String> {
+    DEFAULT_LIMITS.with(|d| {
+        d.borrow_mut()
+            .set(limits)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store quota defaults: {:?}", e))
+    })
+}
+
+/// Set (Some) or clear (None) a principal's override
+pub fn set_limit_override(principal: Principal, limits: Option<QuotaLimits>) {
+    LIMIT_OVERRIDES.with(|o| {
+        let mut overrides = o.borrow_mut();
+        match limits {
+            Some(limits) => {
+                overrides.insert(principal, limits);
+            }
+            None => {
+                overrides.remove(&principal);
+            }
+        }
+    });
+    ic_cdk::println!("⚙️ Updated quota override for {}", principal.to_text());
+}
+
+pub fn count_owned(principal: Principal) -> u32 {
+    LIMIT_OVERRIDES.with(|o| principal_moves::count_keyed(&o.borrow(), principal))
+}
+
+/// Carry `from`'s override over to `to` unless `to` has one of its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    LIMIT_OVERRIDES.with(|o| principal_moves::move_keyed(&mut o.borrow_mut(), from, to))
+}
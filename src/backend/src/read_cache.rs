@@ -0,0 +1,433 @@
+//! Feeds the read-cache canister (src/read_cache) that serves public
+//! availability pages. Changed availabilities are queued here and a timer
+//! pushes their public view, encoded exactly as `get_availability` would
+//! reply to a signed-out viewer, so the cache can replay the bytes. Every
+//! write to AVAILABILITIES queues the availability (see watched_map.rs), and
+//! a rolling sweep queues those whose view changed without a write to the
+//! record (favorite and list order). Unchanged views are not pushed again.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, Availability, AVAILABILITIES};
+use crate::{quarantine, service_status, tenants};
+
+const PUSH_TIMER_INTERVAL: Duration = Duration::from_secs(10);
+/// Availabilities pushed per call to the cache
+const PUSH_BATCH: usize = 100;
+/// Availabilities re-queued by the sweep per tick
+const SWEEP_BATCH: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct ReadCacheConfig {
+    canister: Option<Principal>,
+    updated_at: u64,
+}
+
+/// Entry the cache stores: an availability ID and its encoded reply
+#[derive(CandidType, Serialize, Clone, Debug)]
+struct CacheEntry {
+    id: String,
+    reply: Vec<u8>,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ReadCacheStatus {
+    pub canister: Option<Principal>,
+    pub pending: u64,         // Availabilities waiting to be pushed
+    pub last_pushed_at: u64,  // 0 = nothing pushed since the last upgrade
+    pub last_error: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for ReadCacheConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ReadCacheConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static READ_CACHE_CONFIG: RefCell<StableCell<ReadCacheConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(83))),
+            ReadCacheConfig::default(),
+        ).expect("Failed to init read cache config")
+    );
+
+    // Availability IDs waiting to be pushed; stable so deletions queued
+    // right before an upgrade still reach the cache
+    static READ_CACHE_PENDING: RefCell<StableBTreeMap<String, u8, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(84)))
+        )
+    );
+
+    // Availability ID -> digest of the reply last pushed, to skip unchanged views
+    static PUSHED: RefCell<HashMap<String, [u8; 8]>> = RefCell::new(HashMap::new());
+
+    // Last availability ID the sweep looked at (None = start over)
+    static SWEEP_CURSOR: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    // Last queued ID the push took, so every queued ID gets its turn
+    static PUSH_CURSOR: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    // IDs queued again while a push of them was in flight
+    static TOUCHED_IN_FLIGHT: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+
+    static PUSH_IN_FLIGHT: Cell<bool> = const { Cell::new(false) };
+    static LAST_PUSHED_AT: Cell<u64> = const { Cell::new(0) };
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear_pending() {
+    READ_CACHE_PENDING.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn canister() -> Option<Principal> {
+    READ_CACHE_CONFIG.with(|c| c.borrow().get().canister)
+}
+
+fn digest(reply: &[u8]) -> [u8; 8] {
+    use sha2::{Sha256, Digest};
+    let hash = Sha256::digest(reply);
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash[..8]);
+    out
+}
+
+/// What `get_availability` replies to a signed-out viewer (None = gone).
+/// The cache can't tell tenants apart, so it only holds the default tenant's.
+fn public_reply(id: &str) -> Option<Vec<u8>> {
+    let availability = availabilities::get_availability(id.to_string())
+        .ok()
+        .filter(|availability| tenants::tenant_of(availability.owner) == tenants::DEFAULT_TENANT)?;
+    let reply: Result<Availability, String> = Ok(availabilities::public_view(availability, Principal::anonymous()));
+    Encode!(&reply).ok()
+}
+
+fn queue(ids: impl IntoIterator<Item = String>) {
+    let in_flight = PUSH_IN_FLIGHT.with(|f| f.get());
+    READ_CACHE_PENDING.with(|p| {
+        let mut pending = p.borrow_mut();
+        for id in ids {
+            if in_flight {
+                TOUCHED_IN_FLIGHT.with(|t| t.borrow_mut().insert(id.clone()));
+            }
+            pending.insert(id, 0);
+        }
+    });
+}
+
+/// Drop pushed IDs from the queue, keeping those changed during the push
+fn dequeue(ids: &[String]) {
+    let touched = TOUCHED_IN_FLIGHT.with(|t| std::mem::take(&mut *t.borrow_mut()));
+    READ_CACHE_PENDING.with(|p| {
+        let mut pending = p.borrow_mut();
+        for id in ids.iter().filter(|id| !touched.contains(*id)) {
+            pending.remove(id);
+        }
+    });
+}
+
+/// Whether the cache doesn't hold this view of the availability yet
+fn is_stale(id: &str, reply: &[u8]) -> bool {
+    PUSHED.with(|p| p.borrow().get(id) != Some(&digest(reply)))
+}
+
+/// Queue the next slice of availabilities whose view differs from the one
+/// last pushed, wrapping around at the end
+fn sweep() {
+    let after = SWEEP_CURSOR.with(|c| c.borrow().clone());
+    let ids: Vec<String> = AVAILABILITIES.with(|a| {
+        let map = a.borrow();
+        match after {
+            Some(ref after) => map.range(after.clone()..).map(|(id, _)| id).filter(|id| id != after).take(SWEEP_BATCH).collect(),
+            None => map.iter().map(|(id, _)| id).take(SWEEP_BATCH).collect(),
+        }
+    });
+    let cursor = if ids.len() < SWEEP_BATCH { None } else { ids.last().cloned() };
+    SWEEP_CURSOR.with(|c| *c.borrow_mut() = cursor);
+    let stale: Vec<String> = ids.into_iter()
+        .filter(|id| public_reply(id).is_some_and(|reply| is_stale(id, &reply)))
+        .collect();
+    queue(stale);
+}
+
+/// The next batch of queued IDs after the push cursor, wrapping around
+fn next_batch() -> Vec<String> {
+    let after = PUSH_CURSOR.with(|c| c.borrow().clone());
+    let ids: Vec<String> = READ_CACHE_PENDING.with(|p| {
+        let pending = p.borrow();
+        let mut ids: Vec<String> = match after {
+            Some(ref after) => pending.range(after.clone()..).map(|(id, _)| id).filter(|id| id != after).take(PUSH_BATCH).collect(),
+            None => pending.iter().map(|(id, _)| id).take(PUSH_BATCH).collect(),
+        };
+        if ids.len() < PUSH_BATCH && after.is_some() {
+            let wrapped: Vec<String> = pending.iter().map(|(id, _)| id)
+                .take_while(|id| Some(id) <= after.as_ref())
+                .take(PUSH_BATCH - ids.len())
+                .collect();
+            ids.extend(wrapped);
+        }
+        ids
+    });
+    PUSH_CURSOR.with(|c| *c.borrow_mut() = ids.last().cloned());
+    ids
+}
+
+/// Push the next batch of queued availabilities. IDs stay queued until the
+/// cache has them, so a failed push is retried on the next tick.
+async fn push(canister: Principal) {
+    let ids = next_batch();
+    let mut puts = Vec::new();
+    let mut removes = Vec::new();
+    for id in &ids {
+        match public_reply(id) {
+            Some(reply) if !is_stale(id, &reply) => {}
+            Some(reply) => puts.push(CacheEntry { id: id.clone(), reply }),
+            None => removes.push(id.clone()),
+        }
+    }
+    if puts.is_empty() && removes.is_empty() {
+        dequeue(&ids);
+        return;
+    }
+
+    let digests: Vec<(String, [u8; 8])> = puts.iter().map(|entry| (entry.id.clone(), digest(&entry.reply))).collect();
+    let result: Result<(Result<(), String>,), _> = ic_cdk::call(canister, "apply", (puts, removes.clone())).await;
+    let error = match result {
+        Ok((Ok(()),)) => None,
+        Ok((Err(e),)) => Some(e),
+        Err((code, msg)) => Some(format!("{:?} - {}", code, msg)),
+    };
+    match error {
+        None => {
+            PUSHED.with(|p| {
+                let mut pushed = p.borrow_mut();
+                pushed.extend(digests);
+                for id in &removes {
+                    pushed.remove(id);
+                }
+            });
+            dequeue(&ids);
+            LAST_PUSHED_AT.with(|l| l.set(time()));
+            LAST_ERROR.with(|l| *l.borrow_mut() = None);
+        }
+        Some(e) => {
+            ic_cdk::println!("❌ [read_cache] Push of {} availabilities failed: {}", ids.len(), e);
+            TOUCHED_IN_FLIGHT.with(|t| t.borrow_mut().clear());
+            LAST_ERROR.with(|l| *l.borrow_mut() = Some(e));
+        }
+    }
+}
+
+fn tick() {
+    let Some(canister) = canister() else {
+        return;
+    };
+    sweep();
+    if PUSH_IN_FLIGHT.with(|f| f.replace(true)) {
+        return;
+    }
+    ic_cdk::spawn(async move {
+        push(canister).await;
+        PUSH_IN_FLIGHT.with(|f| f.set(false));
+    });
+}
+
+// ============================================================================
+// Queueing
+// ============================================================================
+
+/// Note that an availability was created, changed or removed
+pub fn touch(id: &str) {
+    if canister().is_some() {
+        queue([id.to_string()]);
+    }
+}
+
+/// Register the push timer (timers don't survive upgrades, so this runs
+/// from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("read_cache", PUSH_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(PUSH_TIMER_INTERVAL, || {
+        service_status::heartbeat("read_cache", PUSH_TIMER_INTERVAL);
+        tick();
+    });
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Point the backend at a read-cache canister (None stops pushing). The
+/// sweep then fills the cache from scratch.
+pub fn set_canister(canister: Option<Principal>) -> Result<(), String> {
+    if canister == Some(Principal::anonymous()) || canister == Some(ic_cdk::id()) {
+        return Err("The read cache must be a separate canister".to_string());
+    }
+    READ_CACHE_CONFIG.with(|c| c.borrow_mut().set(ReadCacheConfig { canister, updated_at: time() }))
+        .map_err(|e| format!("Failed to store read cache config: {:?}", e))?;
+    PUSHED.with(|p| p.borrow_mut().clear());
+    SWEEP_CURSOR.with(|c| *c.borrow_mut() = None);
+    if canister.is_none() {
+        READ_CACHE_PENDING.with(|p| p.borrow_mut().clear_new());
+    }
+    Ok(())
+}
+
+/// Canister the frontend on `origin` may read public availabilities from;
+/// none for a tenant's origin, whose availabilities aren't cached
+pub fn get_canister(origin: Option<&str>) -> Option<Principal> {
+    if tenants::viewer_tenant(Principal::anonymous(), origin) != tenants::DEFAULT_TENANT {
+        return None;
+    }
+    canister()
+}
+
+pub fn get_status() -> ReadCacheStatus {
+    ReadCacheStatus {
+        canister: canister(),
+        pending: READ_CACHE_PENDING.with(|p| p.borrow().len()),
+        last_pushed_at: LAST_PUSHED_AT.with(|l| l.get()),
+        last_error: LAST_ERROR.with(|l| l.borrow().clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Decode;
+    use crate::availabilities::{BusyPrivacy, BusyTimeBlock};
+
+    fn availability(id: &str) -> Availability {
+        Availability {
+            id: id.to_string(),
+            owner: Principal::from_slice(&[1]),
+            owner_email: None,
+            owner_name: None,
+            title: "Intro call".to_string(),
+            description: String::new(),
+            slots: vec![],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            busy_times: Some(vec![BusyTimeBlock { start_time: 1_000, end_time: 2_000 }]),
+            is_favorite: false,
+            display_order: 0,
+            busy_privacy: Some(BusyPrivacy::Exact),
+            cancellation_policy: None,
+            locale: None,
+            durations: None,
+            kiosk: None,
+            collaborators: None,
+            vip: None,
+            alignment: None,
+            meeting_url: None,
+            event_fields: None,
+            seats: None,
+            follow_ups: None,
+        }
+    }
+
+    fn connect() {
+        READ_CACHE_CONFIG.with(|c| c.borrow_mut().set(ReadCacheConfig { canister: Some(Principal::from_slice(&[9])), updated_at: 0 }))
+            .unwrap();
+    }
+
+    fn pending(id: &str) -> bool {
+        READ_CACHE_PENDING.with(|p| p.borrow().contains_key(&id.to_string()))
+    }
+
+    /// What a successful push records
+    fn mark_pushed(id: &str) {
+        let reply = public_reply(id).unwrap();
+        PUSHED.with(|p| p.borrow_mut().insert(id.to_string(), digest(&reply)));
+        dequeue(&[id.to_string()]);
+    }
+
+    #[test]
+    fn hiding_busy_times_invalidates_the_cached_view() {
+        connect();
+        let mut record = availability("priv01");
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(record.id.clone(), record.clone()));
+        assert!(pending("priv01"));
+        mark_pushed("priv01");
+        assert!(!pending("priv01"));
+
+        record.busy_privacy = Some(BusyPrivacy::FreeSlotsOnly);
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(record.id.clone(), record));
+        assert!(pending("priv01"));
+        let reply = public_reply("priv01").unwrap();
+        assert!(is_stale("priv01", &reply));
+        let view = Decode!(&reply, Result<Availability, String>).unwrap().unwrap();
+        assert!(view.busy_times.is_none());
+    }
+
+    #[test]
+    fn removing_an_availability_queues_its_removal() {
+        connect();
+        let record = availability("gone01");
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(record.id.clone(), record));
+        mark_pushed("gone01");
+        AVAILABILITIES.with(|a| a.borrow_mut().remove(&"gone01".to_string()));
+        assert!(pending("gone01"));
+        assert!(public_reply("gone01").is_none());
+    }
+
+    #[test]
+    fn batches_reach_every_queued_id() {
+        let ids: Vec<String> = (0..250).map(|i| format!("q{:03}", i)).collect();
+        queue(ids.clone());
+        let mut seen = HashSet::new();
+        for _ in 0..3 {
+            let batch = next_batch();
+            assert!(batch.len() <= PUSH_BATCH);
+            seen.extend(batch);
+        }
+        assert_eq!(seen.len(), ids.len());
+    }
+
+    #[test]
+    fn changes_during_a_push_stay_queued() {
+        queue(["busy01".to_string(), "calm01".to_string()]);
+        PUSH_IN_FLIGHT.with(|f| f.set(true));
+        queue(["busy01".to_string()]);
+        PUSH_IN_FLIGHT.with(|f| f.set(false));
+        dequeue(&["busy01".to_string(), "calm01".to_string()]);
+        assert!(pending("busy01"));
+        assert!(!pending("calm01"));
+    }
+}
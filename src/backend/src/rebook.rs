@@ -0,0 +1,114 @@
+//! "Book again" links for past guests. The token names the earlier booking
+//! and is signed with the canister's cursor key, so nothing is stored per
+//! link. It opens the same availability with the guest's details and answers
+//! filled in, and bookings made through it are counted as repeat bookings.
+
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use crate::{analytics, cursor, qr, webhook_auth};
+use crate::analytics::{EventProp, PropValue};
+use crate::availabilities::FieldAnswer;
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::error_codes::{self, ErrorCode};
+
+/// Bytes of the HMAC kept in a token
+const TAG_LEN: usize = 16;
+const CONTEXT: &[u8] = b"rebook|";
+const MAX_TOKEN_LEN: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What the booking page pre-fills from a book-again link
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct RebookInfo {
+    pub availability_id: String,
+    pub previous_booking_id: String,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,
+    pub duration_minutes: u32,           // Length of the previous meeting
+    pub field_answers: Vec<FieldAnswer>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn tag(booking_id: &str) -> Result<[u8; 32], String> {
+    cursor::sign(&[CONTEXT, booking_id.as_bytes()].concat())
+}
+
+/// The earlier booking a token was issued for
+fn open(token: &str) -> Result<Booking, String> {
+    const INVALID: &str = "Invalid book-again link";
+    if token.len() > MAX_TOKEN_LEN {
+        return Err(INVALID.to_string());
+    }
+    let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| INVALID)?;
+    if bytes.len() <= TAG_LEN {
+        return Err(INVALID.to_string());
+    }
+    let (id, given) = bytes.split_at(bytes.len() - TAG_LEN);
+    let id = std::str::from_utf8(id).map_err(|_| INVALID)?;
+    if !webhook_auth::constant_time_eq(&tag(id)?[..TAG_LEN], given) {
+        return Err(INVALID.to_string());
+    }
+    bookings::find(id).ok_or_else(|| error_codes::coded(ErrorCode::BookingNotFound, "The earlier booking no longer exists"))
+}
+
+// ============================================================================
+// Links
+// ============================================================================
+
+pub fn token_for(booking_id: &str) -> Result<String, String> {
+    let mut bytes = booking_id.as_bytes().to_vec();
+    bytes.extend_from_slice(&tag(booking_id)?[..TAG_LEN]);
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Booking page of the booking's availability with its book-again token
+/// (empty while the signing key is not set up yet)
+pub fn url_for(booking: &Booking) -> String {
+    match (qr::share_url(None, &booking.availability_id), token_for(&booking.id)) {
+        (Ok(url), Ok(token)) => format!("{}?rebook={}", url, token),
+        _ => String::new(),
+    }
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+pub fn get_rebook_info(token: String) -> Result<RebookInfo, String> {
+    let previous = open(&token)?;
+    Ok(RebookInfo {
+        availability_id: previous.availability_id,
+        duration_minutes: ((previous.end_time - previous.start_time) / 60) as u32,
+        previous_booking_id: previous.id,
+        guest_name: previous.guest_name,
+        guest_email: previous.guest_email,
+        guest_timezone: previous.guest_timezone,
+        field_answers: previous.field_answers.unwrap_or_default(),
+    })
+}
+
+/// Book the availability of the earlier booking again, counted as a repeat booking
+pub fn book_again(caller: Principal, token: String, req: CreateBookingRequest) -> Result<Booking, String> {
+    let previous = open(&token)?;
+    if req.availability_id != previous.availability_id {
+        return Err("Book-again links only book the same availability".to_string());
+    }
+    let booking = bookings::create_booking(caller, req)?;
+    let props = vec![EventProp {
+        key: "availability_id".to_string(),
+        value: PropValue::Text(booking.availability_id.clone()),
+    }];
+    if let Err(e) = analytics::record(caller, "repeat_booking".to_string(), props) {
+        ic_cdk::println!("⚠️ [rebook] Could not record repeat booking: {}", e);
+    }
+    Ok(booking)
+}
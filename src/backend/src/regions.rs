@@ -0,0 +1,273 @@
+//! Age and region gating. Each org can require members to declare their
+//! region (and that they are of age) before using gated features, and turn
+//! those features off for regions it doesn't support. The checks run in the
+//! endpoints themselves, so a frontend that skips the dialog gets an error.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::plans::Feature;
+use crate::{principal_moves, quarantine, tenants};
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_UNSUPPORTED_REGIONS: usize = 250;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct RegionPolicy {
+    pub tenant_id: String,
+    pub require_region: bool,             // Gated features need a declared region
+    pub require_adult: bool,              // ...and a declaration that the member is of age
+    pub unsupported_regions: Vec<String>, // ISO 3166-1 alpha-2, e.g. "US"
+    pub gated_features: Vec<Feature>,     // Features the rules above apply to
+    pub updated_by: Principal,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct SetRegionPolicyRequest {
+    pub tenant_id: String,
+    pub require_region: bool,
+    pub require_adult: bool,
+    pub unsupported_regions: Vec<String>,
+    pub gated_features: Vec<Feature>,
+}
+
+/// What the member told us about themselves; not verified
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RegionDeclaration {
+    pub region: String,
+    pub is_adult: bool,
+    pub declared_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl RegionPolicy {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Gates nothing.
+    fn unreadable() -> Self {
+        RegionPolicy {
+            tenant_id: String::new(),
+            require_region: false,
+            require_adult: false,
+            unsupported_regions: vec![],
+            gated_features: vec![],
+            updated_by: Principal::anonymous(),
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for RegionPolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "RegionPolicy").unwrap_or_else(RegionPolicy::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RegionDeclaration {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "RegionDeclaration").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Tenant ID -> policy (no entry = nothing gated)
+    static REGION_POLICIES: RefCell<StableBTreeMap<String, RegionPolicy, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(68)))
+        )
+    );
+
+    static REGION_DECLARATIONS: RefCell<StableBTreeMap<Principal, RegionDeclaration, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(69)))
+        )
+    );
+}
+
+pub fn clear() {
+    REGION_DECLARATIONS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Upper-case a two-letter region code, rejecting anything else
+fn normalize_region(region: &str) -> Result<String, String> {
+    let region = region.trim().to_ascii_uppercase();
+    if region.len() != 2 || !region.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err("region must be a two-letter ISO 3166-1 code, e.g. \"DE\"".to_string());
+    }
+    Ok(region)
+}
+
+fn check(policy: &RegionPolicy, declaration: Option<&RegionDeclaration>, feature: Feature) -> Result<(), String> {
+    if !policy.gated_features.contains(&feature) {
+        return Ok(());
+    }
+    let Some(declaration) = declaration else {
+        if policy.require_region || policy.require_adult {
+            return Err(error_codes::coded(ErrorCode::RegionRequired, format!("declare your region to use {:?}", feature)));
+        }
+        return Ok(());
+    };
+    if policy.require_adult && !declaration.is_adult {
+        return Err(format!("AgeRequired: {:?} is only available to adults", feature));
+    }
+    if policy.unsupported_regions.contains(&declaration.region) {
+        return Err(error_codes::coded(ErrorCode::RegionNotSupported, format!("{:?} is not available in {}", feature, declaration.region)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Enforcement
+// ============================================================================
+
+/// Reject the call if the caller's org gates `feature` and the caller's
+/// declaration doesn't meet its policy
+pub fn require_available(caller: Principal, feature: Feature) -> Result<(), String> {
+    let Some(policy) = get_policy(tenants::tenant_of(caller)) else {
+        return Ok(());
+    };
+    let declaration = get_declaration(caller);
+    check(&policy, declaration.as_ref(), feature)
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+pub fn get_policy(tenant_id: String) -> Option<RegionPolicy> {
+    REGION_POLICIES.with(|p| p.borrow().get(&tenant_id))
+}
+
+pub fn set_policy(caller: Principal, req: SetRegionPolicyRequest) -> Result<RegionPolicy, String> {
+    tenants::require_org_manager(caller, &req.tenant_id)?;
+    if req.unsupported_regions.len() > MAX_UNSUPPORTED_REGIONS {
+        return Err(format!("At most {} unsupported regions", MAX_UNSUPPORTED_REGIONS));
+    }
+    let mut unsupported_regions = req.unsupported_regions.iter()
+        .map(|region| normalize_region(region))
+        .collect::<Result<Vec<_>, _>>()?;
+    unsupported_regions.sort();
+    unsupported_regions.dedup();
+
+    let policy = RegionPolicy {
+        tenant_id: req.tenant_id.clone(),
+        require_region: req.require_region,
+        require_adult: req.require_adult,
+        unsupported_regions,
+        gated_features: req.gated_features,
+        updated_by: caller,
+        updated_at: time(),
+    };
+    REGION_POLICIES.with(|p| p.borrow_mut().insert(req.tenant_id, policy.clone()));
+    ic_cdk::println!("🌍 Region policy of {} updated by {}", policy.tenant_id, caller);
+    Ok(policy)
+}
+
+pub fn get_declaration(caller: Principal) -> Option<RegionDeclaration> {
+    REGION_DECLARATIONS.with(|d| d.borrow().get(&caller))
+}
+
+pub fn declare(caller: Principal, region: String, is_adult: bool) -> Result<RegionDeclaration, String> {
+    if caller == Principal::anonymous() {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Sign in to declare your region"));
+    }
+    let declaration = RegionDeclaration {
+        region: normalize_region(&region)?,
+        is_adult,
+        declared_at: time(),
+    };
+    REGION_DECLARATIONS.with(|d| d.borrow_mut().insert(caller, declaration.clone()));
+    Ok(declaration)
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(principal: Principal) -> u32 {
+    REGION_DECLARATIONS.with(|d| principal_moves::count_keyed(&d.borrow(), principal))
+}
+
+/// Carry `from`'s declaration over to `to` unless `to` made its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    REGION_DECLARATIONS.with(|d| principal_moves::move_keyed(&mut d.borrow_mut(), from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(require_region: bool, require_adult: bool, unsupported: &[&str]) -> RegionPolicy {
+        RegionPolicy {
+            tenant_id: "acme".to_string(),
+            require_region,
+            require_adult,
+            unsupported_regions: unsupported.iter().map(|r| r.to_string()).collect(),
+            gated_features: vec![Feature::Payments],
+            updated_by: Principal::anonymous(),
+            updated_at: 0,
+        }
+    }
+
+    fn declared(region: &str, is_adult: bool) -> RegionDeclaration {
+        RegionDeclaration { region: region.to_string(), is_adult, declared_at: 0 }
+    }
+
+    #[test]
+    fn region_codes_are_two_letters() {
+        assert_eq!(normalize_region(" de ").unwrap(), "DE");
+        assert!(normalize_region("DEU").is_err());
+        assert!(normalize_region("1A").is_err());
+    }
+
+    #[test]
+    fn missing_declaration_blocks_only_when_required() {
+        assert!(check(&policy(true, false, &[]), None, Feature::Payments).is_err());
+        assert!(check(&policy(false, true, &[]), None, Feature::Payments).is_err());
+        assert!(check(&policy(false, false, &["US"]), None, Feature::Payments).is_ok());
+    }
+
+    #[test]
+    fn unsupported_regions_and_minors_are_blocked_from_gated_features() {
+        let policy = policy(true, true, &["US"]);
+        assert!(check(&policy, Some(&declared("US", true)), Feature::Payments).is_err());
+        assert!(check(&policy, Some(&declared("DE", false)), Feature::Payments).is_err());
+        assert!(check(&policy, Some(&declared("DE", true)), Feature::Payments).is_ok());
+        assert!(check(&policy, Some(&declared("US", false)), Feature::Webhooks).is_ok());
+    }
+}
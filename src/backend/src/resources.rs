@@ -0,0 +1,542 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, Availability, BusyTimeBlock, FieldAnswer, FreeSlot, TimeSlot};
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::{entropy, quarantine, scheduling, tenants};
+use crate::error_codes::{self, ErrorCode};
+
+/// Longest range resource free slots are expanded for
+const MAX_RANGE_SECS: u64 = 90 * 24 * 60 * 60;
+const MAX_CAPACITY: u32 = 1_000;
+const MAX_GROUP_MEMBERS: usize = 10;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ResourceKind {
+    Room,
+    Equipment,
+    Other,
+}
+
+/// A bookable thing without its own calendar account (meeting room, camera kit),
+/// owned by an org (tenant) and managed by its admins
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Resource {
+    pub id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub kind: ResourceKind,
+    pub capacity: u32,           // Bookings it can take at the same time
+    pub open_hours: Vec<TimeSlot>,
+    pub timezone: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct ResourceRequest {
+    pub name: String,
+    pub kind: ResourceKind,
+    pub capacity: u32,
+    pub open_hours: Vec<TimeSlot>,
+    pub timezone: String,
+}
+
+/// One booking's claim on a resource
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ResourceReservation {
+    pub booking_id: String,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// One meeting that needs several people and resources at the same time
+#[derive(CandidType, Deserialize)]
+pub struct GroupBookingRequest {
+    pub availability_ids: Vec<String>, // Every person who must attend
+    pub resource_ids: Vec<String>,     // Rooms/equipment the meeting needs
+    pub start_time: u64,
+    pub end_time: u64,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,
+    pub field_answers: Option<Vec<GroupFieldAnswers>>, // Answers to each availability's event fields
+}
+
+/// The guest's answers for one availability of a group booking
+#[derive(CandidType, Deserialize)]
+pub struct GroupFieldAnswers {
+    pub availability_id: String,
+    pub answers: Vec<FieldAnswer>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReservationList(pub Vec<ResourceReservation>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Resource {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). In no tenant and without capacity, so it is never booked.
+    fn unreadable() -> Self {
+        Resource {
+            id: String::new(),
+            tenant_id: String::new(),
+            name: String::new(),
+            kind: ResourceKind::Other,
+            capacity: 0,
+            open_hours: vec![],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for Resource {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Resource").unwrap_or_else(Resource::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for ReservationList {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "ReservationList").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static RESOURCES: RefCell<StableBTreeMap<String, Resource, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(26)))
+        )
+    );
+
+    // Resource ID -> reservations. Entries whose booking was cancelled or
+    // released are ignored when computing free time and pruned on the next write.
+    static RESERVATIONS: RefCell<StableBTreeMap<String, ReservationList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(27)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear_reservations() {
+    RESERVATIONS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn generate_resource_id() -> Result<String, String> {
+    Ok(format!("res-{}", entropy::next_token(6)?))
+}
+
+fn validate_request(req: &ResourceRequest) -> Result<(), String> {
+    if req.name.trim().is_empty() || req.name.len() > 100 {
+        return Err("name must be 1-100 characters".to_string());
+    }
+    if req.capacity == 0 || req.capacity > MAX_CAPACITY {
+        return Err(format!("capacity must be 1-{}", MAX_CAPACITY));
+    }
+    if req.timezone.parse::<chrono_tz::Tz>().is_err() {
+        return Err(format!("Unknown timezone: {}", req.timezone));
+    }
+    availabilities::validate_weekly_slots(&req.open_hours)
+}
+
+pub fn get_resource(id: &str) -> Result<Resource, String> {
+    RESOURCES.with(|r| r.borrow().get(&id.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Resource not found"))
+}
+
+/// Live reservations overlapping the range
+fn live_reservations(id: &str, range_start: u64, range_end: u64) -> Vec<ResourceReservation> {
+    RESERVATIONS.with(|r| r.borrow().get(&id.to_string()))
+        .unwrap_or_default()
+        .0
+        .into_iter()
+        .filter(|res| res.start_time < range_end && range_start < res.end_time)
+        .filter(|res| bookings::is_live(&res.booking_id))
+        .collect()
+}
+
+/// Intervals where the resource is booked to capacity
+fn full_blocks(reservations: &[ResourceReservation], capacity: u32) -> Vec<BusyTimeBlock> {
+    let mut edges: Vec<(u64, i64)> = reservations.iter()
+        .flat_map(|r| [(r.start_time, 1), (r.end_time, -1)])
+        .collect();
+    // Ends sort before starts at the same instant so back-to-back bookings don't stack
+    edges.sort();
+
+    let mut blocks = Vec::new();
+    let mut active = 0i64;
+    let mut full_since = None;
+    for (at, delta) in edges {
+        active += delta;
+        match full_since {
+            None if active >= capacity as i64 => full_since = Some(at),
+            Some(start) if active < capacity as i64 => {
+                if start < at {
+                    blocks.push(BusyTimeBlock { start_time: start, end_time: at });
+                }
+                full_since = None;
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Open hours minus the times the resource is booked to capacity
+pub fn free_windows(resource: &Resource, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    let open = scheduling::expand_weekly(&resource.open_hours, &resource.timezone, range_start, range_end);
+    let full = full_blocks(&live_reservations(&resource.id, range_start, range_end), resource.capacity);
+    scheduling::subtract_busy_times(open, &full)
+}
+
+fn clamp_range(range_start: u64, range_end: u64) -> Result<(u64, u64), String> {
+    if range_start >= range_end {
+        return Err("range_start must be before range_end".to_string());
+    }
+    if range_end - range_start > MAX_RANGE_SECS {
+        return Err("range must be at most 90 days".to_string());
+    }
+    Ok((range_start.max(time() / 1_000_000_000), range_end))
+}
+
+/// Resources are visible to members of their org only
+fn visible_resource(caller: Principal, id: &str) -> Result<Resource, String> {
+    let resource = get_resource(id)?;
+    if tenants::tenant_of(caller) != resource.tenant_id && !ic_cdk::api::is_controller(&caller) {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Resource not found"));
+    }
+    Ok(resource)
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn create_resource(caller: Principal, tenant_id: String, req: ResourceRequest) -> Result<Resource, String> {
+    tenants::require_org_manager(caller, &tenant_id)?;
+    validate_request(&req)?;
+
+    let now = time();
+    let resource = Resource {
+        id: generate_resource_id()?,
+        tenant_id,
+        name: req.name.trim().to_string(),
+        kind: req.kind,
+        capacity: req.capacity,
+        open_hours: req.open_hours,
+        timezone: req.timezone,
+        created_at: now,
+        updated_at: now,
+    };
+    RESOURCES.with(|r| r.borrow_mut().insert(resource.id.clone(), resource.clone()));
+
+    ic_cdk::println!("🚪 Created resource {} for tenant {}", resource.id, resource.tenant_id);
+    Ok(resource)
+}
+
+pub fn update_resource(caller: Principal, id: String, req: ResourceRequest) -> Result<Resource, String> {
+    let mut resource = get_resource(&id)?;
+    tenants::require_org_manager(caller, &resource.tenant_id)?;
+    validate_request(&req)?;
+
+    resource.name = req.name.trim().to_string();
+    resource.kind = req.kind;
+    resource.capacity = req.capacity;
+    resource.open_hours = req.open_hours;
+    resource.timezone = req.timezone;
+    resource.updated_at = time();
+    RESOURCES.with(|r| r.borrow_mut().insert(id, resource.clone()));
+    Ok(resource)
+}
+
+pub fn delete_resource(caller: Principal, id: String) -> Result<(), String> {
+    let resource = get_resource(&id)?;
+    tenants::require_org_manager(caller, &resource.tenant_id)?;
+    RESOURCES.with(|r| r.borrow_mut().remove(&id));
+    RESERVATIONS.with(|r| r.borrow_mut().remove(&id));
+    Ok(())
+}
+
+/// Resources of the caller's org
+pub fn list_resources(caller: Principal) -> Vec<Resource> {
+    let tenant_id = tenants::tenant_of(caller);
+    RESOURCES.with(|r| {
+        r.borrow()
+            .iter()
+            .filter(|(_, resource)| resource.tenant_id == tenant_id)
+            .map(|(_, resource)| resource)
+            .collect()
+    })
+}
+
+// ============================================================================
+// Availability & Booking
+// ============================================================================
+
+pub fn get_resource_free_slots(caller: Principal, id: String, range_start: u64, range_end: u64) -> Result<Vec<FreeSlot>, String> {
+    let resource = visible_resource(caller, &id)?;
+    let (range_start, range_end) = clamp_range(range_start, range_end)?;
+    Ok(free_windows(&resource, range_start, range_end))
+}
+
+/// Times when both a person's availability and a resource are free
+/// ("Alice + Room 3"). The resource must belong to the availability owner's org.
+pub fn get_free_slots_with_resource(
+    availability_id: String,
+    resource_id: String,
+    range_start: u64,
+    range_end: u64,
+) -> Result<Vec<FreeSlot>, String> {
+    let availability = availabilities::get_availability(availability_id)?;
+    let resource = get_resource(&resource_id)?;
+    if tenants::tenant_of(availability.owner) != resource.tenant_id {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Resource not found"));
+    }
+    let (range_start, range_end) = clamp_range(range_start, range_end)?;
+
+    Ok(scheduling::intersect(
+        &availabilities::free_windows(&availability, range_start, range_end),
+        &free_windows(&resource, range_start, range_end),
+    ))
+}
+
+/// Whether [start, end) fits inside one free window of the resource
+pub fn is_free(resource: &Resource, start: u64, end: u64) -> bool {
+    free_windows(resource, start, end)
+        .iter()
+        .any(|w| w.start_time <= start && w.end_time >= end)
+}
+
+/// Record a booking's claim on a resource (callers check `is_free` first)
+pub fn reserve(resource_id: &str, booking: &Booking) {
+    RESERVATIONS.with(|r| {
+        let mut map = r.borrow_mut();
+        let mut list = map.get(&resource_id.to_string()).unwrap_or_default();
+        list.0.retain(|res| bookings::is_live(&res.booking_id));
+        list.0.push(ResourceReservation {
+            booking_id: booking.id.clone(),
+            start_time: booking.start_time,
+            end_time: booking.end_time,
+        });
+        map.insert(resource_id.to_string(), list);
+    });
+}
+
+/// Book a person's availability together with one of their org's resources
+pub fn book_with_resource(caller: Principal, req: CreateBookingRequest, resource_id: String) -> Result<Booking, String> {
+    let availability = availabilities::get_availability(req.availability_id.clone())?;
+    let resource = get_resource(&resource_id)?;
+    if tenants::tenant_of(availability.owner) != resource.tenant_id {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Resource not found"));
+    }
+    if !is_free(&resource, req.start_time, req.end_time) {
+        return Err(error_codes::coded(ErrorCode::SlotUnavailable, format!("{} is not available at that time", resource.name)));
+    }
+
+    let booking = bookings::create_booking(caller, req)?;
+    reserve(&resource.id, &booking);
+    Ok(booking)
+}
+
+/// A group booking that passed every check, ready to be written
+struct CheckedGroup {
+    bookings: Vec<(Availability, CreateBookingRequest)>,
+    resources: Vec<Resource>,
+}
+
+/// Book every listed availability and resource for the same slot, or none of
+/// them. Every booking and resource is checked before anything is stored, so
+/// a failure leaves no state behind.
+pub fn book_together(caller: Principal, req: GroupBookingRequest) -> Result<Vec<Booking>, String> {
+    let group = check_group(caller, req)?;
+
+    // Only drawing a booking ID can still fail here; that traps, so the
+    // bookings already written are rolled back with it
+    let booked: Vec<Booking> = group.bookings.into_iter()
+        .map(|(availability, booking_req)| {
+            bookings::book_validated(caller, &availability, booking_req)
+                .unwrap_or_else(|e| ic_cdk::trap(&format!("{}: {}", availability.id, e)))
+        })
+        .collect();
+    for resource in &group.resources {
+        reserve(&resource.id, &booked[0]);
+    }
+    Ok(booked)
+}
+
+/// Every check book_together makes, without storing anything
+fn check_group(caller: Principal, req: GroupBookingRequest) -> Result<CheckedGroup, String> {
+    if req.availability_ids.is_empty() {
+        return Err("at least one availability is required".to_string());
+    }
+    if req.availability_ids.len() + req.resource_ids.len() > MAX_GROUP_MEMBERS {
+        return Err(format!("at most {} availabilities and resources", MAX_GROUP_MEMBERS));
+    }
+    let ids: Vec<&String> = req.availability_ids.iter().chain(&req.resource_ids).collect();
+    if ids.iter().enumerate().any(|(i, id)| ids[..i].contains(id)) {
+        return Err("availability and resource IDs must be unique".to_string());
+    }
+    let answers = req.field_answers.unwrap_or_default();
+    if let Some(extra) = answers.iter().find(|a| !req.availability_ids.contains(&a.availability_id)) {
+        return Err(format!("{}: not part of this booking", extra.availability_id));
+    }
+
+    let mut validated: Vec<(Availability, CreateBookingRequest)> = Vec::new();
+    for availability_id in &req.availability_ids {
+        let booking_req = CreateBookingRequest {
+            availability_id: availability_id.clone(),
+            start_time: req.start_time,
+            end_time: req.end_time,
+            guest_name: req.guest_name.clone(),
+            guest_email: req.guest_email.clone(),
+            guest_timezone: req.guest_timezone.clone(),
+            field_answers: answers.iter()
+                .find(|a| &a.availability_id == availability_id)
+                .map(|a| a.answers.clone()),
+        };
+        let availability = bookings::validate_booking(caller, &booking_req)
+            .map_err(|e| format!("{}: {}", availability_id, e))?;
+        // Checks only see stored bookings, so one person can't appear twice
+        if validated.iter().any(|(other, _)| other.owner == availability.owner) {
+            return Err(format!("{}: its owner is already part of this booking", availability_id));
+        }
+        validated.push((availability, booking_req));
+    }
+
+    let owners_tenants: Vec<_> = validated.iter().map(|(a, _)| tenants::tenant_of(a.owner)).collect();
+    let resources = req.resource_ids.iter()
+        .map(|id| get_resource(id))
+        .collect::<Result<Vec<_>, String>>()?;
+    for resource in &resources {
+        if !owners_tenants.contains(&resource.tenant_id) {
+            return Err(error_codes::coded(ErrorCode::NotFound, "Resource not found"));
+        }
+        if !is_free(resource, req.start_time, req.end_time) {
+            return Err(error_codes::coded(ErrorCode::SlotUnavailable, format!("{} is not available at that time", resource.name)));
+        }
+    }
+    Ok(CheckedGroup { bookings: validated, resources })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availabilities::AVAILABILITIES;
+    use crate::clock::{self, MockClock};
+
+    // Wednesday 2025-01-01 00:00 UTC
+    const DAY_START: u64 = 1_735_689_600;
+
+    fn resource(id: &str, day_of_week: u8) -> Resource {
+        let resource = Resource {
+            id: id.to_string(),
+            tenant_id: tenants::DEFAULT_TENANT.to_string(),
+            name: id.to_string(),
+            kind: ResourceKind::Room,
+            capacity: 1,
+            open_hours: vec![TimeSlot { day_of_week, start_time: 9 * 60, end_time: 17 * 60 }],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        RESOURCES.with(|r| r.borrow_mut().insert(resource.id.clone(), resource.clone()));
+        resource
+    }
+
+    fn availability(id: &str) -> Availability {
+        let availability = Availability {
+            id: id.to_string(),
+            owner: Principal::from_slice(&[1; 29]),
+            owner_email: None,
+            owner_name: None,
+            title: "Planning".to_string(),
+            description: String::new(),
+            slots: vec![TimeSlot { day_of_week: 3, start_time: 9 * 60, end_time: 17 * 60 }],
+            timezone: "UTC".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            busy_times: None,
+            is_favorite: false,
+            display_order: 0,
+            busy_privacy: None,
+            cancellation_policy: None,
+            locale: None,
+            durations: None,
+            kiosk: None,
+            collaborators: None,
+            vip: None,
+            alignment: None,
+            meeting_url: None,
+            event_fields: None,
+            seats: None,
+            follow_ups: None,
+        };
+        AVAILABILITIES.with(|a| a.borrow_mut().insert(availability.id.clone(), availability.clone()));
+        availability
+    }
+
+    #[test]
+    fn a_conflict_on_the_second_resource_leaves_the_first_unbooked() {
+        clock::install(MockClock::at(DAY_START * 1_000_000_000));
+        availability("planning");
+        let room = resource("room", 3);
+        // Open Thursdays only, so busy at the requested Wednesday slot
+        let van = resource("van", 4);
+        let start = DAY_START + 10 * 3600;
+        let req = GroupBookingRequest {
+            availability_ids: vec!["planning".to_string()],
+            resource_ids: vec![room.id.clone(), van.id.clone()],
+            start_time: start,
+            end_time: start + 3600,
+            guest_name: "Ada".to_string(),
+            guest_email: None,
+            guest_timezone: None,
+            field_answers: None,
+        };
+
+        let error = book_together(Principal::from_slice(&[2; 29]), req).unwrap_err();
+
+        assert!(error.contains("van is not available"), "{}", error);
+        assert!(RESERVATIONS.with(|r| r.borrow().get(&room.id)).is_none());
+        assert!(is_free(&room, start, start + 3600));
+        assert_eq!(bookings::count_owned(Principal::from_slice(&[1; 29])), 0);
+    }
+}
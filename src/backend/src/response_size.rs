@@ -0,0 +1,86 @@
+//! Replies over the 2MB message limit trap mid-serialization, so the caller
+//! gets nothing. Listings measure their items against a byte budget first
+//! and cut the reply short instead: paged endpoints flag it with
+//! `truncated` and a cursor to continue from, plain lists log it.
+
+use candid::CandidType;
+
+/// Reply budget in bytes, under the 2MiB limit with room for the envelope
+pub const MAX_RESPONSE_BYTES: usize = 1_800_000;
+
+/// Candid size of one value. Each count includes the type table, so sums
+/// overestimate the real reply.
+pub fn encoded_len<T: CandidType>(value: &T) -> usize {
+    candid::encode_one(value).map(|bytes| bytes.len()).unwrap_or(0)
+}
+
+/// How many leading items fit in `budget` bytes. The first is always kept
+/// so paging makes progress.
+pub fn fitting_len<T: CandidType>(items: &[T], budget: usize) -> usize {
+    let mut used = 0usize;
+    for (i, item) in items.iter().enumerate() {
+        used = used.saturating_add(encoded_len(item));
+        if used > budget && i > 0 {
+            return i;
+        }
+    }
+    items.len()
+}
+
+/// One reply's worth of `items` (at most `max_items`), starting at the item
+/// whose `key` is `position`. Returns the items and the key to continue
+/// from when cut short.
+pub fn page_from<T: CandidType>(
+    mut items: Vec<T>,
+    position: Option<String>,
+    max_items: usize,
+    key: impl Fn(&T) -> String,
+) -> Result<(Vec<T>, Option<String>), String> {
+    if let Some(position) = position {
+        let start = items.iter().position(|item| key(item) == position)
+            .ok_or("Cursor no longer matches an item, start from the beginning")?;
+        items.drain(..start);
+    }
+    items.truncate(max_items.saturating_add(1));
+    let kept = fitting_len(&items, MAX_RESPONSE_BYTES).min(max_items);
+    let next_cursor = items.get(kept).map(&key);
+    items.truncate(kept);
+    Ok((items, next_cursor))
+}
+
+/// For replies that can't carry a marker: drop items past the budget and log it
+pub fn clamp<T: CandidType>(mut items: Vec<T>, endpoint: &str) -> Vec<T> {
+    let kept = fitting_len(&items, MAX_RESPONSE_BYTES);
+    if kept < items.len() {
+        ic_cdk::println!("✂️ {} truncated to {} of {} items, use the paged endpoint", endpoint, kept, items.len());
+        items.truncate(kept);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fitting_len_stops_at_the_budget_but_keeps_one() {
+        let items = vec!["x".repeat(100); 10];
+        let each = encoded_len(&items[0]);
+        assert_eq!(fitting_len(&items, each * 3), 3);
+        assert_eq!(fitting_len(&items, each * 3 - 1), 2);
+        assert_eq!(fitting_len(&items, 0), 1);
+        assert_eq!(fitting_len(&items, usize::MAX), 10);
+    }
+
+    #[test]
+    fn pages_resume_at_the_cursor() {
+        let items: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+        let (page, next) = page_from(items.clone(), Some("2".to_string()), 10, |s| s.clone()).unwrap();
+        assert_eq!(page, vec!["2", "3", "4"]);
+        assert_eq!(next, None);
+        let (page, next) = page_from(items.clone(), None, 2, |s| s.clone()).unwrap();
+        assert_eq!(page, vec!["0", "1"]);
+        assert_eq!(next, Some("2".to_string()));
+        assert!(page_from(items, Some("9".to_string()), 10, |s| s.clone()).is_err());
+    }
+}
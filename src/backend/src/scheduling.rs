@@ -0,0 +1,722 @@
+//! Pure scheduling math: no canister state and no ic_cdk calls, so everything
+//! here is unit- and property-tested off-chain.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Offset, TimeZone, Timelike};
+use chrono_tz::Tz;
+use crate::availabilities::{BusyTimeBlock, DurationOption, FreeSlot, TimeSlot};
+
+const DEFAULT_STEP_MINUTES: u32 = 30;
+const DEFAULT_MAX_RESULTS: u32 = 10;
+const DEFAULT_PREFERRED_START_HOUR: u8 = 9;
+const DEFAULT_PREFERRED_END_HOUR: u8 = 17;
+
+/// Local hours considered reasonable for a meeting in fairness mode
+const REASONABLE_START_HOUR: u32 = 8;
+const REASONABLE_END_HOUR: u32 = 20;
+
+/// Score weights (out of 100)
+const WEIGHT_PREFERRED_HOURS: f64 = 40.0;
+const WEIGHT_FAIRNESS: f64 = 30.0;
+const WEIGHT_CLUSTERING: f64 = 20.0;
+const WEIGHT_EARLINESS: f64 = 10.0;
+
+/// Local time everyone's inconvenience is measured from
+const IDEAL_LOCAL_MINUTE: i64 = 13 * 60;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SuggestionConstraints {
+    pub range_start: u64,                  // Unix seconds
+    pub range_end: u64,                    // Unix seconds
+    pub preferred_start_hour: Option<u8>,  // Local hour in each participant's timezone (default 9)
+    pub preferred_end_hour: Option<u8>,    // Default 17
+    pub step_minutes: Option<u32>,         // Candidate granularity (default 30)
+    pub max_results: Option<u32>,          // Default 10
+}
+
+#[derive(CandidType, Serialize, Clone, Debug, PartialEq)]
+pub struct MeetingSuggestion {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub score: u32,           // 0-1000, higher is better
+    pub reasons: Vec<String>,
+}
+
+/// A window where every participant is free. In fairness mode windows are split
+/// wherever someone's reasonable hours begin or end, then scored and ranked.
+#[derive(CandidType, Serialize, Clone, Debug, PartialEq)]
+pub struct CommonFreeSlot {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub score: Option<u32>,                   // 0-1000, fairness mode only
+    pub outside_reasonable_hours: Vec<String>, // Participants for whom this is outside 8:00-20:00 local
+}
+
+/// What the engine knows about one participant: only free windows and a timezone,
+/// so suggestions never depend on raw busy data
+#[derive(Clone, Debug)]
+pub struct ParticipantWindows {
+    pub id: String,
+    pub free: Vec<FreeSlot>,
+    pub timezone: Tz,
+}
+
+// ============================================================================
+// Calendar Math
+// ============================================================================
+
+/// Unknown timezone strings fall back to UTC rather than failing the whole query
+pub fn parse_timezone(timezone: &str) -> Tz {
+    timezone.parse().unwrap_or(Tz::UTC)
+}
+
+/// Expand a weekly schedule in local time into concrete UTC windows clipped to the range
+pub fn expand_weekly(slots: &[TimeSlot], timezone: &str, range_start: u64, range_end: u64) -> Vec<FreeSlot> {
+    let tz = parse_timezone(timezone);
+    
+    let (first_day, last_day) = match (
+        tz.timestamp_opt(range_start as i64, 0).single(),
+        tz.timestamp_opt(range_end as i64, 0).single(),
+    ) {
+        (Some(start), Some(end)) => (start.date_naive(), end.date_naive()),
+        _ => return vec![],
+    };
+    
+    let mut windows = Vec::new();
+    let mut day = first_day;
+    while day <= last_day {
+        let weekday = day.weekday().num_days_from_sunday() as u8;
+        for slot in slots.iter().filter(|s| s.day_of_week == weekday) {
+            let start = local_minutes_to_timestamp(&tz, day, slot.start_time);
+            let end = local_minutes_to_timestamp(&tz, day, slot.end_time);
+            if let (Some(start), Some(end)) = (start, end) {
+                let start = start.max(range_start);
+                let end = end.min(range_end);
+                if start < end {
+                    windows.push(FreeSlot { start_time: start, end_time: end });
+                }
+            }
+        }
+        day = match day.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    
+    windows.sort_by_key(|w| w.start_time);
+    windows
+}
+
+/// Convert minutes-from-midnight on a local date to a Unix timestamp (seconds).
+/// Returns None for local times skipped by a DST transition.
+pub fn local_minutes_to_timestamp(tz: &Tz, day: NaiveDate, minutes: u16) -> Option<u64> {
+    let local = day.and_hms_opt((minutes / 60) as u32, (minutes % 60) as u32, 0)?;
+    let timestamp = tz.from_local_datetime(&local).earliest()?.timestamp();
+    u64::try_from(timestamp).ok()
+}
+
+/// Remove busy blocks from free windows, splitting windows where needed
+pub fn subtract_busy_times(windows: Vec<FreeSlot>, busy: &[BusyTimeBlock]) -> Vec<FreeSlot> {
+    let mut free = windows;
+    for block in busy {
+        free = free.into_iter()
+            .flat_map(|w| {
+                let mut parts = Vec::with_capacity(2);
+                if block.end_time <= w.start_time || block.start_time >= w.end_time {
+                    parts.push(w);
+                    return parts;
+                }
+                if block.start_time > w.start_time {
+                    parts.push(FreeSlot { start_time: w.start_time, end_time: block.start_time });
+                }
+                if block.end_time < w.end_time {
+                    parts.push(FreeSlot { start_time: block.end_time, end_time: w.end_time });
+                }
+                parts
+            })
+            .collect();
+    }
+    free
+}
+
+/// Widen busy blocks to `quantum`-second boundaries and merge blocks that now touch
+pub fn quantize_busy_times(blocks: &[BusyTimeBlock], quantum: u64) -> Vec<BusyTimeBlock> {
+    let mut widened: Vec<BusyTimeBlock> = blocks.iter()
+        .map(|b| BusyTimeBlock {
+            start_time: b.start_time - b.start_time % quantum,
+            end_time: b.end_time.div_ceil(quantum) * quantum,
+        })
+        .collect();
+    widened.sort_by_key(|b| b.start_time);
+    
+    let mut merged: Vec<BusyTimeBlock> = Vec::with_capacity(widened.len());
+    for block in widened {
+        match merged.last_mut() {
+            Some(last) if block.start_time <= last.end_time => {
+                last.end_time = last.end_time.max(block.end_time);
+            }
+            _ => merged.push(block),
+        }
+    }
+    merged
+}
+
+/// The span a meeting [start, end) blocks once its buffers are added
+pub fn with_buffers(option: &DurationOption, start: u64, end: u64) -> (u64, u64) {
+    (
+        start.saturating_sub(option.buffer_before_minutes as u64 * 60),
+        end + option.buffer_after_minutes as u64 * 60,
+    )
+}
+
+/// Round a timestamp up (or down) to the next `step`-second boundary in local
+/// time, so :30 offsets like India's still get clean slots
+pub fn align_local(tz: &Tz, at: u64, step: u64, up: bool) -> u64 {
+    let offset = tz.timestamp_opt(at as i64, 0)
+        .single()
+        .map(|dt| dt.offset().fix().local_minus_utc() as i64)
+        .unwrap_or(0);
+    let local = at as i64 + offset;
+    let step = step as i64;
+    let aligned = if up { (local + step - 1).div_euclid(step) * step } else { local.div_euclid(step) * step };
+    (aligned - offset).max(0) as u64
+}
+
+// ============================================================================
+// Scoring Engine
+// ============================================================================
+
+/// Intersect two sorted lists of windows
+pub fn intersect(a: &[FreeSlot], b: &[FreeSlot]) -> Vec<FreeSlot> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        let start = a[i].start_time.max(b[j].start_time);
+        let end = a[i].end_time.min(b[j].end_time);
+        if start < end {
+            out.push(FreeSlot { start_time: start, end_time: end });
+        }
+        if a[i].end_time < b[j].end_time {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Union of windows: sorted, with overlapping and touching windows joined
+pub fn merge(mut windows: Vec<FreeSlot>) -> Vec<FreeSlot> {
+    windows.sort_by_key(|w| w.start_time);
+    let mut out: Vec<FreeSlot> = Vec::with_capacity(windows.len());
+    for w in windows {
+        match out.last_mut() {
+            Some(last) if w.start_time <= last.end_time => last.end_time = last.end_time.max(w.end_time),
+            _ => out.push(w),
+        }
+    }
+    out
+}
+
+/// Windows where every participant is free
+pub fn mutual_free(participants: &[ParticipantWindows]) -> Vec<FreeSlot> {
+    let mut iter = participants.iter();
+    let Some(first) = iter.next() else {
+        return vec![];
+    };
+    let mut sorted = first.free.clone();
+    sorted.sort_by_key(|w| w.start_time);
+    iter.fold(sorted, |acc, p| {
+        let mut other = p.free.clone();
+        other.sort_by_key(|w| w.start_time);
+        intersect(&acc, &other)
+    })
+}
+
+/// Start times aligned to `step` that fit `duration` inside a window
+pub fn candidate_starts(windows: &[FreeSlot], duration: u64, step: u64) -> Vec<u64> {
+    let mut starts = Vec::new();
+    for w in windows {
+        let mut start = w.start_time.div_ceil(step) * step;
+        while start + duration <= w.end_time {
+            starts.push(start);
+            start += step;
+        }
+    }
+    starts
+}
+
+fn local_minute(tz: &Tz, unix_secs: u64) -> i64 {
+    tz.timestamp_opt(unix_secs as i64, 0)
+        .single()
+        .map(|dt| (dt.hour() * 60 + dt.minute()) as i64)
+        .unwrap_or(IDEAL_LOCAL_MINUTE)
+}
+
+/// Score one candidate against all heuristics, returning (score 0-1000, reasons)
+pub fn score_candidate(
+    participants: &[ParticipantWindows],
+    start: u64,
+    duration: u64,
+    constraints: &SuggestionConstraints,
+) -> (u32, Vec<String>) {
+    let end = start + duration;
+    let pref_start = constraints.preferred_start_hour.unwrap_or(DEFAULT_PREFERRED_START_HOUR) as i64 * 60;
+    let pref_end = constraints.preferred_end_hour.unwrap_or(DEFAULT_PREFERRED_END_HOUR) as i64 * 60;
+    let count = participants.len().max(1) as f64;
+    let mut reasons = Vec::new();
+
+    // Preferred hours: share of participants for whom the whole meeting is in hours
+    let in_hours = participants.iter()
+        .filter(|p| {
+            let local_start = local_minute(&p.timezone, start);
+            local_start >= pref_start && local_start + (duration / 60) as i64 <= pref_end
+        })
+        .count() as f64;
+    if in_hours == count {
+        reasons.push("Within preferred hours for everyone".to_string());
+    }
+
+    // Timezone fairness: the worst-off participant's distance from midday
+    let worst_deviation = participants.iter()
+        .map(|p| {
+            let midpoint = local_minute(&p.timezone, start + duration / 2);
+            (midpoint - IDEAL_LOCAL_MINUTE).abs()
+        })
+        .max()
+        .unwrap_or(0) as f64;
+    let fairness = 1.0 - (worst_deviation / (12.0 * 60.0)).min(1.0);
+    if participants.len() > 1 && worst_deviation <= 3.0 * 60.0 {
+        reasons.push("Reasonable local time for all timezones".to_string());
+    }
+
+    // Clustering: meetings that touch the edge of a free window avoid fragmenting the day
+    let clustered = participants.iter()
+        .filter(|p| p.free.iter().any(|w| w.start_time == start || w.end_time == end))
+        .count() as f64;
+    if clustered > 0.0 {
+        reasons.push("Keeps free time in one block".to_string());
+    }
+
+    // Earliness: small bias towards sooner meetings as a tie-breaker
+    let span = constraints.range_end.saturating_sub(constraints.range_start).max(1) as f64;
+    let earliness = 1.0 - (start.saturating_sub(constraints.range_start) as f64 / span).min(1.0);
+
+    let score = WEIGHT_PREFERRED_HOURS * in_hours / count
+        + WEIGHT_FAIRNESS * fairness
+        + WEIGHT_CLUSTERING * clustered / count
+        + WEIGHT_EARLINESS * earliness;
+
+    ((score * 10.0).round() as u32, reasons)
+}
+
+/// Rank every mutual candidate, best first (ties go to the earlier slot)
+pub fn rank(
+    participants: &[ParticipantWindows],
+    duration: u64,
+    constraints: &SuggestionConstraints,
+) -> Vec<MeetingSuggestion> {
+    let step = constraints.step_minutes.unwrap_or(DEFAULT_STEP_MINUTES).max(5) as u64 * 60;
+    let max_results = constraints.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, 50) as usize;
+
+    let mut suggestions: Vec<MeetingSuggestion> = candidate_starts(&mutual_free(participants), duration, step)
+        .into_iter()
+        .map(|start| {
+            let (score, reasons) = score_candidate(participants, start, duration, constraints);
+            MeetingSuggestion { start_time: start, end_time: start + duration, score, reasons }
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| b.score.cmp(&a.score).then(a.start_time.cmp(&b.start_time)));
+    suggestions.truncate(max_results);
+    suggestions
+}
+
+// ============================================================================
+// Fairness Mode
+// ============================================================================
+
+/// Next instant after `after` where local time in `tz` reaches 8:00 or 20:00
+fn next_reasonable_boundary(tz: &Tz, after: u64) -> Option<u64> {
+    let local = tz.timestamp_opt(after as i64, 0).single()?.date_naive();
+    [local, local + Duration::days(1)]
+        .iter()
+        .flat_map(|day| [REASONABLE_START_HOUR, REASONABLE_END_HOUR].map(|h| (*day, h)))
+        .filter_map(|(day, hour)| {
+            let naive = day.and_time(NaiveTime::from_hms_opt(hour, 0, 0)?);
+            tz.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp() as u64)
+        })
+        .filter(|&t| t > after)
+        .min()
+}
+
+fn is_reasonable(tz: &Tz, start: u64, end: u64) -> bool {
+    let start_minute = local_minute(tz, start);
+    // Measure the end one second early so a slot ending exactly at 20:00 counts
+    let end_minute = local_minute(tz, end.saturating_sub(1).max(start));
+    let (open, close) = ((REASONABLE_START_HOUR * 60) as i64, (REASONABLE_END_HOUR * 60) as i64);
+    start_minute >= open && end_minute < close && end_minute >= start_minute
+}
+
+/// Split windows wherever any participant's reasonable hours begin or end
+fn split_at_reasonable_boundaries(windows: Vec<FreeSlot>, participants: &[ParticipantWindows]) -> Vec<FreeSlot> {
+    let mut pieces = Vec::new();
+    for w in windows {
+        let mut start = w.start_time;
+        while start < w.end_time {
+            let end = participants.iter()
+                .filter_map(|p| next_reasonable_boundary(&p.timezone, start))
+                .min()
+                .unwrap_or(w.end_time)
+                .min(w.end_time);
+            pieces.push(FreeSlot { start_time: start, end_time: end });
+            start = end;
+        }
+    }
+    pieces
+}
+
+/// Mutual free windows, optionally split, scored and ranked for timezone fairness
+pub fn common_slots(participants: &[ParticipantWindows], fairness_mode: bool) -> Vec<CommonFreeSlot> {
+    let mutual = mutual_free(participants);
+    if !fairness_mode {
+        return mutual.into_iter()
+            .map(|w| CommonFreeSlot {
+                start_time: w.start_time,
+                end_time: w.end_time,
+                score: None,
+                outside_reasonable_hours: vec![],
+            })
+            .collect();
+    }
+
+    let count = participants.len().max(1) as f64;
+    let mut slots: Vec<CommonFreeSlot> = split_at_reasonable_boundaries(mutual, participants)
+        .into_iter()
+        .map(|w| {
+            let outside: Vec<String> = participants.iter()
+                .filter(|p| !is_reasonable(&p.timezone, w.start_time, w.end_time))
+                .map(|p| p.id.clone())
+                .collect();
+
+            let midpoint = w.start_time + (w.end_time - w.start_time) / 2;
+            let worst_deviation = participants.iter()
+                .map(|p| (local_minute(&p.timezone, midpoint) - IDEAL_LOCAL_MINUTE).abs())
+                .max()
+                .unwrap_or(0) as f64;
+            let fairness = 1.0 - (worst_deviation / (12.0 * 60.0)).min(1.0);
+            let reasonable_share = (count - outside.len() as f64) / count;
+
+            CommonFreeSlot {
+                start_time: w.start_time,
+                end_time: w.end_time,
+                score: Some(((reasonable_share * 0.6 + fairness * 0.4) * 1000.0).round() as u32),
+                outside_reasonable_hours: outside,
+            }
+        })
+        .collect();
+
+    slots.sort_by(|a, b| b.score.cmp(&a.score).then(a.start_time.cmp(&b.start_time)));
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // 2024-01-15 00:00:00 UTC (a Monday)
+    const DAY: u64 = 1_705_276_800;
+    const HOUR: u64 = 3600;
+
+    fn slot(start: u64, end: u64) -> FreeSlot {
+        FreeSlot { start_time: start, end_time: end }
+    }
+
+    fn participant(free: Vec<FreeSlot>, tz: &str) -> ParticipantWindows {
+        ParticipantWindows { id: tz.to_string(), free, timezone: tz.parse().unwrap() }
+    }
+
+    fn constraints() -> SuggestionConstraints {
+        SuggestionConstraints {
+            range_start: DAY,
+            range_end: DAY + 24 * HOUR,
+            preferred_start_hour: None,
+            preferred_end_hour: None,
+            step_minutes: None,
+            max_results: None,
+        }
+    }
+
+    #[test]
+    fn merges_overlapping_and_touching_windows() {
+        let merged = merge(vec![slot(20, 30), slot(0, 10), slot(5, 12), slot(12, 15)]);
+        assert_eq!(merged, vec![slot(0, 15), slot(20, 30)]);
+    }
+
+    #[test]
+    fn intersects_overlapping_windows() {
+        let a = vec![slot(0, 10), slot(20, 30)];
+        let b = vec![slot(5, 25)];
+        assert_eq!(intersect(&a, &b), vec![slot(5, 10), slot(20, 25)]);
+    }
+
+    #[test]
+    fn mutual_free_is_empty_without_overlap() {
+        let people = vec![
+            participant(vec![slot(DAY, DAY + HOUR)], "UTC"),
+            participant(vec![slot(DAY + 2 * HOUR, DAY + 3 * HOUR)], "UTC"),
+        ];
+        assert!(mutual_free(&people).is_empty());
+    }
+
+    #[test]
+    fn candidates_align_to_step_and_fit_duration() {
+        let windows = vec![slot(DAY + 9 * HOUR + 600, DAY + 11 * HOUR)];
+        let starts = candidate_starts(&windows, HOUR, 30 * 60);
+        assert_eq!(starts, vec![DAY + 9 * HOUR + 1800, DAY + 10 * HOUR]);
+    }
+
+    #[test]
+    fn prefers_working_hours() {
+        let people = vec![participant(vec![slot(DAY + 6 * HOUR, DAY + 12 * HOUR)], "UTC")];
+        let ranked = rank(&people, HOUR, &constraints());
+        let best_hour = (ranked[0].start_time - DAY) / HOUR;
+        assert!((9..=11).contains(&best_hour), "best slot at {}h", best_hour);
+        assert!(ranked.iter().all(|s| s.start_time >= DAY + 6 * HOUR));
+    }
+
+    #[test]
+    fn fairness_favours_slot_reasonable_in_both_timezones() {
+        // Berlin (UTC+1) and New York (UTC-5) are both free all day
+        let all_day = vec![slot(DAY, DAY + 24 * HOUR)];
+        let people = vec![
+            participant(all_day.clone(), "Europe/Berlin"),
+            participant(all_day, "America/New_York"),
+        ];
+        let early = score_candidate(&people, DAY + 8 * HOUR, HOUR, &constraints()).0;
+        let overlap = score_candidate(&people, DAY + 15 * HOUR, HOUR, &constraints()).0;
+        assert!(overlap > early, "overlap {} <= early {}", overlap, early);
+    }
+
+    #[test]
+    fn clustering_rewards_slots_at_window_edges() {
+        let people = vec![participant(vec![slot(DAY + 10 * HOUR, DAY + 14 * HOUR)], "UTC")];
+        let edge = score_candidate(&people, DAY + 10 * HOUR, HOUR, &constraints()).0;
+        let middle = score_candidate(&people, DAY + 11 * HOUR, HOUR, &constraints()).0;
+        assert!(edge > middle);
+    }
+
+    #[test]
+    fn respects_max_results() {
+        let people = vec![participant(vec![slot(DAY, DAY + 24 * HOUR)], "UTC")];
+        let ranked = rank(&people, HOUR, &SuggestionConstraints {
+            max_results: Some(3),
+            ..constraints()
+        });
+        assert_eq!(ranked.len(), 3);
+    }
+
+    #[test]
+    fn common_slots_without_fairness_are_mutual_windows() {
+        let people = vec![
+            participant(vec![slot(DAY + 9 * HOUR, DAY + 12 * HOUR)], "UTC"),
+            participant(vec![slot(DAY + 10 * HOUR, DAY + 14 * HOUR)], "UTC"),
+        ];
+        let slots = common_slots(&people, false);
+        assert_eq!(slots.len(), 1);
+        assert_eq!((slots[0].start_time, slots[0].end_time), (DAY + 10 * HOUR, DAY + 12 * HOUR));
+        assert_eq!(slots[0].score, None);
+    }
+
+    #[test]
+    fn fairness_mode_flags_and_ranks_unreasonable_hours() {
+        // 06:00-22:00 UTC; New York (UTC-5) is only reasonable from 13:00 UTC
+        let window = vec![slot(DAY + 6 * HOUR, DAY + 22 * HOUR)];
+        let people = vec![
+            participant(window.clone(), "UTC"),
+            participant(window, "America/New_York"),
+        ];
+        let slots = common_slots(&people, true);
+
+        let best = &slots[0];
+        assert!(best.outside_reasonable_hours.is_empty());
+        assert_eq!((best.start_time, best.end_time), (DAY + 13 * HOUR, DAY + 20 * HOUR));
+
+        let early = slots.iter().find(|s| s.start_time == DAY + 6 * HOUR).unwrap();
+        assert_eq!(early.end_time, DAY + 8 * HOUR);
+        assert_eq!(early.outside_reasonable_hours, vec!["UTC".to_string(), "America/New_York".to_string()]);
+        assert!(early.score < best.score);
+    }
+
+    fn busy(start: u64, end: u64) -> BusyTimeBlock {
+        BusyTimeBlock { start_time: start, end_time: end }
+    }
+
+    fn weekly(day_of_week: u8, start_time: u16, end_time: u16) -> TimeSlot {
+        TimeSlot { day_of_week, start_time, end_time }
+    }
+
+    #[test]
+    fn expands_local_slots_to_utc() {
+        // Monday 09:00-17:00 in Berlin is 08:00-16:00 UTC in January
+        let windows = expand_weekly(&[weekly(1, 9 * 60, 17 * 60)], "Europe/Berlin", DAY, DAY + 7 * 24 * HOUR);
+        assert_eq!(windows, vec![slot(DAY + 8 * HOUR, DAY + 16 * HOUR)]);
+    }
+
+    #[test]
+    fn expansion_is_clipped_to_the_range() {
+        let windows = expand_weekly(&[weekly(1, 9 * 60, 17 * 60)], "UTC", DAY + 10 * HOUR, DAY + 12 * HOUR);
+        assert_eq!(windows, vec![slot(DAY + 10 * HOUR, DAY + 12 * HOUR)]);
+    }
+
+    #[test]
+    fn expansion_skips_local_times_lost_to_dst() {
+        // 2024-03-10 (a Sunday): New York clocks jump from 02:00 to 03:00
+        let sunday = 1_710_028_800;
+        let windows = expand_weekly(&[weekly(0, 2 * 60 + 30, 2 * 60 + 45)], "America/New_York", sunday, sunday + 2 * 24 * HOUR);
+        assert!(windows.is_empty());
+    }
+
+    #[test]
+    fn unknown_timezone_expands_as_utc() {
+        let windows = expand_weekly(&[weekly(1, 9 * 60, 10 * 60)], "Mars/Olympus", DAY, DAY + 24 * HOUR);
+        assert_eq!(windows, vec![slot(DAY + 9 * HOUR, DAY + 10 * HOUR)]);
+    }
+
+    #[test]
+    fn busy_block_splits_a_window() {
+        let free = subtract_busy_times(vec![slot(0, 100)], &[busy(40, 60), busy(90, 120)]);
+        assert_eq!(free, vec![slot(0, 40), slot(60, 90)]);
+    }
+
+    #[test]
+    fn quantizing_widens_and_merges_blocks() {
+        let blocks = quantize_busy_times(&[busy(1900, 2000), busy(100, 200)], 1800);
+        let spans: Vec<(u64, u64)> = blocks.iter().map(|b| (b.start_time, b.end_time)).collect();
+        assert_eq!(spans, vec![(0, 3600)]);
+    }
+
+    #[test]
+    fn buffers_extend_both_sides() {
+        let option = DurationOption { minutes: 30, buffer_before_minutes: 10, buffer_after_minutes: 5 };
+        assert_eq!(with_buffers(&option, DAY, DAY + 1800), (DAY - 600, DAY + 2100));
+        assert_eq!(with_buffers(&option, 60, 1860).0, 0);
+    }
+
+    #[test]
+    fn alignment_follows_half_hour_offsets() {
+        // DAY 00:10 UTC is 05:40 in Kolkata (UTC+5:30)
+        let tz: Tz = "Asia/Kolkata".parse().unwrap();
+        assert_eq!(align_local(&tz, DAY + 600, 15 * 60, true), DAY + 900);
+        assert_eq!(align_local(&tz, DAY + 600, 15 * 60, false), DAY);
+        assert_eq!(align_local(&tz, DAY + 600, HOUR, true), DAY + 1800);
+    }
+
+    /// Sorted, disjoint windows inside one week
+    fn disjoint_windows() -> impl Strategy<Value = Vec<FreeSlot>> {
+        prop::collection::vec((1u64..4 * HOUR, 0u64..4 * HOUR), 0..12).prop_map(|gaps| {
+            let mut at = DAY;
+            gaps.into_iter()
+                .map(|(len, gap)| {
+                    let w = slot(at + gap, at + gap + len);
+                    at = w.end_time;
+                    w
+                })
+                .collect()
+        })
+    }
+
+    fn busy_blocks() -> impl Strategy<Value = Vec<BusyTimeBlock>> {
+        prop::collection::vec((0u64..7 * 24 * HOUR, 1u64..6 * HOUR), 0..12)
+            .prop_map(|blocks| blocks.into_iter().map(|(start, len)| busy(DAY + start, DAY + start + len)).collect())
+    }
+
+    fn overlap(a: (u64, u64), b: (u64, u64)) -> u64 {
+        a.1.min(b.1).saturating_sub(a.0.max(b.0))
+    }
+
+    proptest! {
+        #[test]
+        fn subtraction_keeps_exactly_the_unbusy_time(windows in disjoint_windows(), blocks in busy_blocks()) {
+            let free = subtract_busy_times(windows.clone(), &blocks);
+
+            for w in &free {
+                prop_assert!(w.start_time < w.end_time);
+                prop_assert!(windows.iter().any(|o| o.start_time <= w.start_time && w.end_time <= o.end_time));
+                prop_assert!(blocks.iter().all(|b| overlap((w.start_time, w.end_time), (b.start_time, b.end_time)) == 0));
+            }
+
+            let merged_busy = merge(blocks.iter().map(|b| slot(b.start_time, b.end_time)).collect());
+            let expected: u64 = windows.iter()
+                .map(|w| {
+                    let len = w.end_time - w.start_time;
+                    len - merged_busy.iter().map(|b| overlap((w.start_time, w.end_time), (b.start_time, b.end_time))).sum::<u64>()
+                })
+                .sum();
+            let actual: u64 = free.iter().map(|w| w.end_time - w.start_time).sum();
+            prop_assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn quantized_blocks_cover_the_originals(blocks in busy_blocks(), quantum in prop::sample::select(vec![900u64, 1800, 3600])) {
+            let quantized = quantize_busy_times(&blocks, quantum);
+            for q in &quantized {
+                prop_assert_eq!(q.start_time % quantum, 0);
+                prop_assert_eq!(q.end_time % quantum, 0);
+            }
+            for pair in quantized.windows(2) {
+                prop_assert!(pair[0].end_time < pair[1].start_time);
+            }
+            for b in &blocks {
+                prop_assert!(quantized.iter().any(|q| q.start_time <= b.start_time && b.end_time <= q.end_time));
+            }
+        }
+
+        #[test]
+        fn expansion_stays_sorted_and_in_range(
+            day in 0u8..7,
+            start in 0u16..1439,
+            len in 1u16..600,
+            offset in 0u64..14 * 24 * HOUR,
+            span in 1u64..14 * 24 * HOUR,
+            tz in prop::sample::select(vec!["UTC", "Europe/Berlin", "America/New_York", "Asia/Kolkata", "Pacific/Chatham"]),
+        ) {
+            let end = (start + len).min(1439);
+            prop_assume!(start < end);
+            let (range_start, range_end) = (DAY + offset, DAY + offset + span);
+            let windows = expand_weekly(&[weekly(day, start, end)], tz, range_start, range_end);
+            for w in &windows {
+                prop_assert!(range_start <= w.start_time && w.start_time < w.end_time && w.end_time <= range_end);
+            }
+            for pair in windows.windows(2) {
+                prop_assert!(pair[0].end_time <= pair[1].start_time);
+            }
+            prop_assert!(windows.len() as u64 <= span / (7 * 24 * HOUR) + 2);
+        }
+
+        #[test]
+        fn alignment_lands_on_a_local_boundary_within_one_step(
+            at in DAY..DAY + 365 * 24 * HOUR,
+            step_minutes in prop::sample::select(vec![5u64, 10, 15, 20, 30, 60]),
+            tz in prop::sample::select(vec!["UTC", "Asia/Kolkata", "Asia/Kathmandu", "America/St_Johns"]),
+            up in any::<bool>(),
+        ) {
+            let tz: Tz = tz.parse().unwrap();
+            let step = step_minutes * 60;
+            let aligned = align_local(&tz, at, step, up);
+            if up {
+                prop_assert!(aligned >= at && aligned - at < step);
+            } else {
+                prop_assert!(aligned <= at && at - aligned < step);
+            }
+            let offset = tz.timestamp_opt(aligned as i64, 0).single().unwrap().offset().fix().local_minus_utc() as i64;
+            prop_assert_eq!((aligned as i64 + offset).rem_euclid(step as i64), 0);
+        }
+    }
+}
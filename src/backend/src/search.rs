@@ -0,0 +1,143 @@
+//! Universal search over the caller's own data: their availabilities, the
+//! bookings made on them, and their contacts (the guests who booked them,
+//! grouped by email, else by name).
+
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use crate::{availabilities, bookings, datetime};
+
+const MIN_QUERY_LEN: usize = 2;
+const MAX_QUERY_LEN: usize = 100;
+const MAX_RESULTS: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum SearchResultKind {
+    Availability,
+    Booking,
+    Contact,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    pub id: String,        // Availability or booking ID; the email or name for contacts
+    pub title: String,
+    pub subtitle: String,
+    pub score: u32,        // Higher is better; results come sorted by it
+}
+
+// ============================================================================
+// Ranking
+// ============================================================================
+
+/// How well one field matches the lowercased query: whole field, then
+/// field prefix, then word prefix, then anywhere
+fn field_score(field: &str, query: &str) -> u32 {
+    let field = field.to_lowercase();
+    if field == query {
+        100
+    } else if field.starts_with(query) {
+        75
+    } else if field.split(|c: char| !c.is_alphanumeric()).any(|word| word.starts_with(query)) {
+        50
+    } else if field.contains(query) {
+        25
+    } else {
+        0
+    }
+}
+
+/// Best match over weighted fields (weight in percent)
+fn score(fields: &[(&str, u32)], query: &str) -> u32 {
+    fields.iter().map(|(field, weight)| field_score(field, query) * weight / 100).max().unwrap_or(0)
+}
+
+// ============================================================================
+// Search
+// ============================================================================
+
+pub fn search_my_data(caller: Principal, query: String) -> Result<Vec<SearchResult>, String> {
+    let query = query.trim().to_lowercase();
+    if query.chars().count() < MIN_QUERY_LEN || query.len() > MAX_QUERY_LEN {
+        return Err(format!("query must be {}-{} characters", MIN_QUERY_LEN, MAX_QUERY_LEN));
+    }
+
+    let mut results = Vec::new();
+    for availability in availabilities::list_user_availabilities(caller) {
+        let score = score(&[(&availability.title, 100), (&availability.description, 60)], &query);
+        if score > 0 {
+            results.push(SearchResult {
+                kind: SearchResultKind::Availability,
+                id: availability.id,
+                title: availability.title,
+                subtitle: availability.description.chars().take(100).collect(),
+                score,
+            });
+        }
+    }
+
+    // Contacts: one per guest, keyed by email when given
+    let mut contacts: BTreeMap<String, (String, Option<String>, u32)> = BTreeMap::new();
+    for booking in bookings::list_owner_bookings(caller) {
+        let email = booking.guest_email.clone().unwrap_or_default();
+        let score = score(&[(&booking.guest_name, 90), (&email, 90)], &query);
+        if score > 0 {
+            results.push(SearchResult {
+                kind: SearchResultKind::Booking,
+                id: booking.id.clone(),
+                title: booking.guest_name.clone(),
+                subtitle: format!("{} · {:?}", datetime::format_iso8601(booking.start_time), booking.status),
+                score,
+            });
+        }
+        let key = booking.guest_email.clone().unwrap_or_else(|| booking.guest_name.clone()).to_lowercase();
+        contacts.entry(key)
+            .or_insert_with(|| (booking.guest_name.clone(), booking.guest_email.clone(), 0))
+            .2 += 1;
+    }
+    for (key, (name, email, count)) in contacts {
+        let score = score(&[(&name, 100), (email.as_deref().unwrap_or(""), 100)], &query);
+        if score > 0 {
+            results.push(SearchResult {
+                kind: SearchResultKind::Contact,
+                id: key,
+                title: name,
+                subtitle: match email {
+                    Some(email) => format!("{} · {} bookings", email, count),
+                    None => format!("{} bookings", count),
+                },
+                score,
+            });
+        }
+    }
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    results.truncate(MAX_RESULTS);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_beats_prefix_beats_word_beats_substring() {
+        assert_eq!(field_score("Coffee chat", "coffee chat"), 100);
+        assert_eq!(field_score("Coffee chat", "coff"), 75);
+        assert_eq!(field_score("Morning coffee", "coff"), 50);
+        assert_eq!(field_score("Decaffeinated", "caff"), 25);
+        assert_eq!(field_score("Tea", "coffee"), 0);
+    }
+
+    #[test]
+    fn weights_scale_the_best_field() {
+        assert_eq!(score(&[("Intro call", 100), ("intro", 60)], "intro"), 75);
+        assert_eq!(score(&[("Call", 100), ("intro", 60)], "intro"), 60);
+        assert_eq!(score(&[], "intro"), 0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Synthetic self-test for external uptime monitors. Each run exercises the
+//! paths everything else depends on: a stable-memory write and read, timer
+//! registration, an HTTPS outcall and the system clock. The report says
+//! which of them passed.
+
+use candid::CandidType;
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpMethod, TransformContext,
+};
+use crate::clock::time;
+use ic_stable_structures::{memory_manager::MemoryId, StableBTreeMap};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::integrations;
+
+/// Returns 204 with no body; HEAD keeps the outcall as cheap as possible
+const HEALTH_URL: &str = "https://www.google.com/generate_204";
+const OUTCALL_CYCLES: u128 = 25_000_000_000;
+/// 2025-01-01T00:00:00Z; a clock before this is certainly wrong
+const CLOCK_FLOOR_NS: u64 = 1_735_689_600 * 1_000_000_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SelfTestReport {
+    pub passed: bool, // Every check passed
+    pub checks: Vec<SelfTestCheck>,
+    pub ran_at: u64,
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Scratch map; entries are removed again by the run that wrote them
+    static SELF_TEST_SCRATCH: RefCell<StableBTreeMap<u64, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(80)))
+        )
+    );
+
+    // Clock reading of the previous run, to catch the clock going backwards
+    static LAST_RUN_AT: Cell<u64> = const { Cell::new(0) };
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+fn check(name: &str, result: Result<String, String>) -> SelfTestCheck {
+    let (passed, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    SelfTestCheck { name: name.to_string(), passed, detail }
+}
+
+fn stable_memory() -> Result<String, String> {
+    let key = time();
+    let value = key.rotate_left(17);
+    SELF_TEST_SCRATCH.with(|s| {
+        let mut map = s.borrow_mut();
+        map.insert(key, value);
+        let read = map.get(&key);
+        map.remove(&key);
+        match read {
+            Some(read) if read == value => Ok("Wrote, read back and removed a scratch entry".to_string()),
+            Some(_) => Err("Read back a different value than was written".to_string()),
+            None => Err("Scratch entry missing right after the write".to_string()),
+        }
+    })
+}
+
+fn timer_registration() -> Result<String, String> {
+    let id = ic_cdk_timers::set_timer(Duration::from_secs(24 * 60 * 60), || {});
+    ic_cdk_timers::clear_timer(id);
+    Ok("Registered and cleared a timer".to_string())
+}
+
+async fn outcall() -> Result<String, String> {
+    let request = CanisterHttpRequestArgument {
+        url: HEALTH_URL.to_string(),
+        method: HttpMethod::HEAD,
+        body: None,
+        max_response_bytes: Some(1024),
+        transform: Some(TransformContext::from_name("transform_hook_response".to_string(), vec![])),
+        headers: vec![],
+    };
+    match http_request(request, OUTCALL_CYCLES).await {
+        Ok((response,)) if integrations::is_success(&response.status) => Ok(format!("HEAD {} returned {}", HEALTH_URL, response.status)),
+        Ok((response,)) => Err(format!("HEAD {} returned {}", HEALTH_URL, response.status)),
+        Err((code, msg)) => Err(format!("Outcall rejected: {:?} {}", code, msg)),
+    }
+}
+
+fn clock_sane(now: u64, previous: u64) -> Result<String, String> {
+    if now < CLOCK_FLOOR_NS {
+        return Err(format!("Clock reads {}, before 2025", now));
+    }
+    if now < previous {
+        return Err(format!("Clock went backwards by {} ns since the last run", previous - now));
+    }
+    Ok(format!("Clock reads {}", now))
+}
+
+// ============================================================================
+// Run
+// ============================================================================
+
+pub async fn run() -> SelfTestReport {
+    let ran_at = time();
+    let mut checks = vec![
+        check("clock", clock_sane(ran_at, LAST_RUN_AT.with(|l| l.get()))),
+        check("stable_memory", stable_memory()),
+        check("timer_registration", timer_registration()),
+    ];
+    LAST_RUN_AT.with(|l| l.set(ran_at));
+    checks.push(check("outcall", outcall().await));
+
+    let passed = checks.iter().all(|c| c.passed);
+    if !passed {
+        ic_cdk::println!("❌ [self-test] Failed: {:?}", checks.iter().filter(|c| !c.passed).map(|c| &c.name).collect::<Vec<_>>());
+    }
+    SelfTestReport { passed, checks, ran_at }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_must_be_recent_and_not_go_backwards() {
+        assert!(clock_sane(CLOCK_FLOOR_NS + 10, CLOCK_FLOOR_NS).is_ok());
+        assert!(clock_sane(CLOCK_FLOOR_NS - 1, 0).is_err());
+        assert!(clock_sane(CLOCK_FLOOR_NS + 10, CLOCK_FLOOR_NS + 20).is_err());
+    }
+}
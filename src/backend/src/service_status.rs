@@ -0,0 +1,212 @@
+//! Public service health for the frontend's status banner. Upstream health
+//! comes from the outcome of recent outcalls and the circuit breakers,
+//! timer health from the last tick of each periodic timer, and maintenance
+//! from running maintenance jobs. Everything here lives on the heap and
+//! starts over after an upgrade.
+
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use crate::clock::time;
+use crate::circuit_breaker::{self, BreakerState, Provider};
+use crate::maintenance;
+
+/// Outcalls older than this don't count towards the success rate
+const OUTCALL_WINDOW_NS: u64 = 15 * 60 * 1_000_000_000;
+/// Outcome of the most recent outcalls kept per provider
+const MAX_OUTCALL_SAMPLES: usize = 200;
+/// Below this many recent outcalls the rate says too little to judge
+const MIN_OUTCALL_SAMPLES: usize = 5;
+const OPERATIONAL_PERCENT: u64 = 90;
+const DEGRADED_PERCENT: u64 = 50;
+/// A timer is considered stuck after missing this many ticks
+const MISSED_TICKS: u64 = 3;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Ordered from best to worst, so the overall status is the maximum
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ComponentHealth {
+    Operational,
+    Maintenance, // Working, with background maintenance running
+    Degraded,
+    Down,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ComponentStatus {
+    pub component: String, // e.g. "google", "timer:outbox", "maintenance"
+    pub health: ComponentHealth,
+    pub detail: String,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct ServiceStatus {
+    pub overall: ComponentHealth,
+    pub components: Vec<ComponentStatus>,
+    pub checked_at: u64,
+}
+
+struct TimerBeat {
+    interval: Duration,
+    last_tick: u64,
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Provider -> (time, succeeded) of its most recent outcalls, oldest first
+    static OUTCALLS: RefCell<HashMap<Provider, VecDeque<(u64, bool)>>> = RefCell::new(HashMap::new());
+
+    // Timer name -> interval and last tick (registration counts as a tick)
+    static TIMERS: RefCell<HashMap<&'static str, TimerBeat>> = RefCell::new(HashMap::new());
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Note the outcome of one outcall to an upstream provider
+pub fn record_outcall(provider: Provider, success: bool) {
+    OUTCALLS.with(|o| {
+        let mut map = o.borrow_mut();
+        let samples = map.entry(provider).or_default();
+        if samples.len() == MAX_OUTCALL_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((time(), success));
+    });
+}
+
+/// Note that a periodic timer was registered or fired
+pub fn heartbeat(name: &'static str, interval: Duration) {
+    TIMERS.with(|t| t.borrow_mut().insert(name, TimerBeat { interval, last_tick: time() }));
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn rate_health(succeeded: usize, total: usize) -> ComponentHealth {
+    if total < MIN_OUTCALL_SAMPLES {
+        return ComponentHealth::Operational;
+    }
+    let percent = succeeded as u64 * 100 / total as u64;
+    if percent >= OPERATIONAL_PERCENT {
+        ComponentHealth::Operational
+    } else if percent >= DEGRADED_PERCENT {
+        ComponentHealth::Degraded
+    } else {
+        ComponentHealth::Down
+    }
+}
+
+fn timer_alive(beat: &TimerBeat, now: u64) -> bool {
+    now.saturating_sub(beat.last_tick) <= beat.interval.as_nanos() as u64 * MISSED_TICKS
+}
+
+fn provider_status(provider: Provider, now: u64) -> ComponentStatus {
+    let (succeeded, total) = OUTCALLS.with(|o| {
+        o.borrow().get(&provider).map_or((0, 0), |samples| {
+            let recent = samples.iter().filter(|(at, _)| now.saturating_sub(*at) <= OUTCALL_WINDOW_NS);
+            recent.fold((0, 0), |(ok, all), (_, success)| (ok + usize::from(*success), all + 1))
+        })
+    });
+    let breaker_open = circuit_breaker::get_breaker_status()
+        .iter()
+        .any(|status| status.provider == provider && status.state != BreakerState::Closed);
+
+    let health = if breaker_open { ComponentHealth::Down } else { rate_health(succeeded, total) };
+    let detail = match (breaker_open, total) {
+        (true, _) => "Circuit breaker open after repeated failures".to_string(),
+        (false, 0) => "No calls in the last 15 minutes".to_string(),
+        (false, _) => format!("{} of {} calls succeeded in the last 15 minutes", succeeded, total),
+    };
+    ComponentStatus { component: format!("{:?}", provider).to_lowercase(), health, detail }
+}
+
+// ============================================================================
+// Status
+// ============================================================================
+
+pub fn get_service_status() -> ServiceStatus {
+    let now = time();
+    let mut components = vec![provider_status(Provider::Google, now)];
+
+    let mut timers: Vec<ComponentStatus> = TIMERS.with(|t| {
+        t.borrow()
+            .iter()
+            .map(|(name, beat)| {
+                let alive = timer_alive(beat, now);
+                ComponentStatus {
+                    component: format!("timer:{}", name),
+                    health: if alive { ComponentHealth::Operational } else { ComponentHealth::Down },
+                    detail: format!("Last ran {}s ago", now.saturating_sub(beat.last_tick) / 1_000_000_000),
+                }
+            })
+            .collect()
+    });
+    if timers.is_empty() {
+        timers.push(ComponentStatus {
+            component: "timers".to_string(),
+            health: ComponentHealth::Down,
+            detail: "No timers registered".to_string(),
+        });
+    }
+    timers.sort_by(|a, b| a.component.cmp(&b.component));
+    components.extend(timers);
+
+    let maintenance = maintenance::is_running();
+    components.push(ComponentStatus {
+        component: "maintenance".to_string(),
+        health: if maintenance { ComponentHealth::Maintenance } else { ComponentHealth::Operational },
+        detail: if maintenance { "Maintenance job running" } else { "Idle" }.to_string(),
+    });
+
+    ServiceStatus {
+        overall: components.iter().map(|c| c.health).max().unwrap_or(ComponentHealth::Operational),
+        components,
+        checked_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    #[test]
+    fn success_rate_maps_to_health_once_there_are_enough_calls() {
+        assert_eq!(rate_health(0, 4), ComponentHealth::Operational);
+        assert_eq!(rate_health(9, 10), ComponentHealth::Operational);
+        assert_eq!(rate_health(5, 10), ComponentHealth::Degraded);
+        assert_eq!(rate_health(4, 10), ComponentHealth::Down);
+    }
+
+    #[test]
+    fn timers_are_stuck_after_missing_three_ticks() {
+        let beat = TimerBeat { interval: Duration::from_secs(60), last_tick: 0 };
+        assert!(timer_alive(&beat, 180 * 1_000_000_000));
+        assert!(!timer_alive(&beat, 181 * 1_000_000_000));
+    }
+
+    #[test]
+    fn a_timer_that_stops_ticking_shows_as_down() {
+        let clock = MockClock::at(1_735_689_600 * 1_000_000_000);
+        clock::install(clock.clone());
+        heartbeat("outbox", Duration::from_secs(30));
+        let timer_health = || {
+            get_service_status().components.into_iter().find(|c| c.component == "timer:outbox").map(|c| c.health)
+        };
+
+        assert_eq!(timer_health(), Some(ComponentHealth::Operational));
+        clock.advance(91 * 1_000_000_000);
+        assert_eq!(timer_health(), Some(ComponentHealth::Down));
+    }
+}
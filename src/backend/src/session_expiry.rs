@@ -0,0 +1,78 @@
+//! Expiry of sign-in sessions and the delegations issued for them. Clients
+//! echo the expiry back in nanoseconds, and JavaScript numbers can't hold a
+//! nanosecond timestamp exactly, so a client may send back a value a few
+//! hundred nanoseconds off. The server therefore derives a canonical expiry
+//! on whole seconds and compares what clients send within a tolerance,
+//! always signing its own stored value. Time comes from clock.rs, so it is
+//! tested off-chain.
+
+use crate::clock::time;
+use crate::error_codes::{self, ErrorCode};
+
+const SECOND_NS: u64 = 1_000_000_000;
+/// Longest session a client may ask for (30 days, as Internet Identity allows)
+pub const MAX_TIME_TO_LIVE_NS: u64 = 30 * 24 * 60 * 60 * SECOND_NS;
+/// How far an echoed expiry may be from the stored one
+pub const EXPIRY_TOLERANCE_NS: u64 = SECOND_NS;
+
+/// Expiry of a session created at `now`: the requested lifetime, capped at
+/// MAX_TIME_TO_LIVE_NS and rounded down to a whole second (exactly
+/// representable as a JavaScript number)
+pub fn canonical_expiry(now: u64, max_time_to_live: u64) -> u64 {
+    let expire_at = now.saturating_add(max_time_to_live.min(MAX_TIME_TO_LIVE_NS));
+    expire_at - expire_at % SECOND_NS
+}
+
+/// Whether the expiry a client sent back names the stored one
+pub fn matches(stored: u64, given: u64) -> bool {
+    stored.abs_diff(given) <= EXPIRY_TOLERANCE_NS
+}
+
+/// Check a delegation request against its session: the echoed expiry must
+/// name the stored one, and the session must not have expired yet
+pub fn check(stored: u64, given: u64) -> Result<(), String> {
+    if !matches(stored, given) {
+        return Err("Invalid expiration time".to_string());
+    }
+    if stored <= time() {
+        return Err(error_codes::coded(ErrorCode::TokenExpired, "Session has expired"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    #[test]
+    fn expiry_is_capped_rounded_and_survives_a_float_round_trip() {
+        let now = 1_760_000_000_123_456_789;
+        let expire_at = canonical_expiry(now, 8 * 60 * 60 * SECOND_NS);
+        assert_eq!(expire_at % SECOND_NS, 0);
+        assert_eq!(expire_at as f64 as u64, expire_at);
+        assert_eq!(canonical_expiry(now, u64::MAX), canonical_expiry(now, MAX_TIME_TO_LIVE_NS));
+    }
+
+    #[test]
+    fn echoed_expiry_matches_within_the_tolerance() {
+        let stored = 1_760_000_000_000_000_000;
+        assert!(matches(stored, stored + 256));
+        assert!(matches(stored, stored - EXPIRY_TOLERANCE_NS));
+        assert!(!matches(stored, stored + EXPIRY_TOLERANCE_NS + 1));
+    }
+
+    #[test]
+    fn a_session_stops_issuing_delegations_once_it_expires() {
+        let clock = MockClock::at(1_735_689_600 * SECOND_NS);
+        clock::install(clock.clone());
+        let expire_at = canonical_expiry(time(), 60 * 60 * SECOND_NS);
+        assert_eq!(check(expire_at, expire_at + 256), Ok(()));
+        assert_eq!(check(expire_at, expire_at + 2 * SECOND_NS), Err("Invalid expiration time".to_string()));
+
+        clock.advance(60 * 60 * SECOND_NS - 1);
+        assert_eq!(check(expire_at, expire_at), Ok(()));
+        clock.advance(1);
+        assert_eq!(check(expire_at, expire_at), Err(error_codes::coded(ErrorCode::TokenExpired, "Session has expired")));
+    }
+}
@@ -0,0 +1,585 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::storage::{self, EntryStats};
+use crate::availabilities::{self, FieldAnswer};
+use crate::bookings::{self, Booking, BookingPayment, BookingStatus, CreateBookingRequest, PaymentHold, PaymentMethod};
+use crate::plans::{self, Feature};
+use crate::quotas::{self, QuotaKind};
+use crate::{billing, earnings, entropy, invoicing, ledger, link_stats, principal_moves, promos, quarantine, regions};
+use crate::i18n::{self, Locale, Message};
+use crate::error_codes::{self, ErrorCode};
+
+/// Allowed session lengths
+const MIN_DURATION_MINUTES: u32 = 5;
+const MAX_DURATION_MINUTES: u32 = 8 * 60;
+const MAX_DURATION_PRICES: usize = 10;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A single-use link to book one paid session at a custom price,
+/// independent of the availability's own settings
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SessionLink {
+    pub token: String,
+    pub availability_id: String,
+    pub owner: Principal,
+    pub title: String,
+    pub price: u64,                    // In the ledger's base units (e.g. e8s)
+    pub ledger_canister: Principal,
+    pub duration_minutes: u32,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,       // Nanoseconds; None = valid until redeemed
+    pub redeemed_by: Option<Principal>,
+    pub redeemed_at: Option<u64>,
+    pub booking_id: Option<String>,
+    pub duration_prices: Option<Vec<DurationPrice>>, // Other lengths the guest may pick (None = duration_minutes only)
+    pub stripe_price: Option<StripePrice>,           // Card price for the default length (None = ledger only)
+}
+
+/// What a guest pays by card through Stripe Checkout
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StripePrice {
+    pub amount: u64,      // In the currency's smallest unit (e.g. cents)
+    pub currency: String, // Lowercase ISO 4217 code, as Stripe reports it
+}
+
+/// Price of one alternative session length
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DurationPrice {
+    pub duration_minutes: u32,
+    pub price: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateSessionLinkRequest {
+    pub availability_id: String,
+    pub title: String,
+    pub price: u64,
+    pub duration_minutes: u32,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct RedeemSessionLinkRequest {
+    pub token: String,
+    pub start_time: u64, // Unix seconds
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,
+    pub duration_minutes: Option<u32>, // One of the link's priced lengths (None = the link's default)
+    pub field_answers: Option<Vec<FieldAnswer>>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl SessionLink {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Already redeemed and owned by nobody, so it can't be paid.
+    fn unreadable() -> Self {
+        SessionLink {
+            token: String::new(),
+            availability_id: String::new(),
+            owner: Principal::anonymous(),
+            title: String::new(),
+            price: 0,
+            ledger_canister: Principal::anonymous(),
+            duration_minutes: 0,
+            created_at: 0,
+            expires_at: Some(0),
+            redeemed_by: Some(Principal::anonymous()),
+            redeemed_at: Some(0),
+            booking_id: None,
+            duration_prices: None,
+            stripe_price: None,
+        }
+    }
+}
+
+impl Storable for SessionLink {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "SessionLink").unwrap_or_else(SessionLink::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static SESSION_LINKS: RefCell<StableBTreeMap<String, SessionLink, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15)))
+        )
+    );
+
+    // Tokens with a payment in flight, so a link can't be redeemed twice concurrently
+    static REDEMPTIONS_IN_FLIGHT: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+pub fn clear() {
+    SESSION_LINKS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_link(token: &str) -> Result<SessionLink, String> {
+    SESSION_LINKS.with(|l| l.borrow().get(&token.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Session link not found"))
+}
+
+pub fn owner_of(token: &str) -> Option<Principal> {
+    SESSION_LINKS.with(|l| l.borrow().get(&token.to_string())).map(|link| link.owner)
+}
+
+pub fn availability_of(token: &str) -> Option<String> {
+    SESSION_LINKS.with(|l| l.borrow().get(&token.to_string())).map(|link| link.availability_id)
+}
+
+fn save(link: &SessionLink) {
+    SESSION_LINKS.with(|l| {
+        l.borrow_mut().insert(link.token.clone(), link.clone());
+    });
+}
+
+/// Guest-facing check, worded in the availability's language
+fn ensure_redeemable(link: &SessionLink, locale: Locale) -> Result<(), String> {
+    if link.redeemed_by.is_some() {
+        return Err(i18n::t(locale, Message::LinkAlreadyUsed).to_string());
+    }
+    if link.expires_at.is_some_and(|exp| exp <= time()) {
+        return Err(i18n::t(locale, Message::LinkExpired).to_string());
+    }
+    Ok(())
+}
+
+/// Length and price of the session the guest picked
+fn priced_duration(link: &SessionLink, duration_minutes: Option<u32>) -> Result<(u32, u64), String> {
+    let Some(minutes) = duration_minutes else {
+        return Ok((link.duration_minutes, link.price));
+    };
+    if let Some(entry) = link.duration_prices.iter().flatten().find(|p| p.duration_minutes == minutes) {
+        return Ok((minutes, entry.price));
+    }
+    if minutes == link.duration_minutes {
+        return Ok((minutes, link.price));
+    }
+    Err(format!("{}-minute sessions are not offered by this link", minutes))
+}
+
+fn link_locale(link: &SessionLink) -> Locale {
+    let availability_locale = availabilities::get_availability(link.availability_id.clone())
+        .ok()
+        .and_then(|a| a.locale);
+    i18n::resolve(availability_locale, link.owner)
+}
+
+/// Refuse a second payment while a card checkout for the link is still open
+fn ensure_not_held(link: &SessionLink) -> Result<(), String> {
+    let held = link.booking_id.as_deref()
+        .and_then(bookings::find)
+        .is_some_and(|booking| booking.status == BookingStatus::Pending);
+    if held {
+        return Err("A card payment for this link is still in progress".to_string());
+    }
+    Ok(())
+}
+
+fn begin_redemption(token: &str) -> Result<(), String> {
+    REDEMPTIONS_IN_FLIGHT.with(|r| {
+        if !r.borrow_mut().insert(token.to_string()) {
+            return Err("This link is already being redeemed".to_string());
+        }
+        Ok(())
+    })
+}
+
+fn end_redemption(token: &str) {
+    REDEMPTIONS_IN_FLIGHT.with(|r| {
+        r.borrow_mut().remove(token);
+    });
+}
+
+// ============================================================================
+// Owner Operations
+// ============================================================================
+
+pub fn create_session_link(caller: Principal, req: CreateSessionLinkRequest) -> Result<SessionLink, String> {
+    plans::require_feature(caller, Feature::Payments)?;
+    regions::require_available(caller, Feature::Payments)?;
+
+    let availability = availabilities::get_availability(req.availability_id.clone())?;
+    if availability.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    if req.title.trim().is_empty() || req.title.len() > 100 {
+        return Err("title must be 1-100 characters".to_string());
+    }
+    if req.price == 0 {
+        return Err("price must be greater than 0".to_string());
+    }
+    if req.duration_minutes < MIN_DURATION_MINUTES || req.duration_minutes > MAX_DURATION_MINUTES {
+        return Err(format!(
+            "duration_minutes must be {}-{}",
+            MIN_DURATION_MINUTES, MAX_DURATION_MINUTES
+        ));
+    }
+    if availability.durations.is_some() {
+        availabilities::duration_option(&availability, req.duration_minutes)?;
+    }
+    if req.expires_at.is_some_and(|exp| exp <= time()) {
+        return Err("expires_at must be in the future".to_string());
+    }
+
+    let ledger_canister = billing::get_config().ledger_canister
+        .ok_or("On-chain payments are not configured")?;
+
+    let link = SessionLink {
+        token: entropy::next_token(16)?,
+        availability_id: req.availability_id,
+        owner: caller,
+        title: req.title.trim().to_string(),
+        price: req.price,
+        ledger_canister,
+        duration_minutes: req.duration_minutes,
+        created_at: time(),
+        expires_at: req.expires_at,
+        redeemed_by: None,
+        redeemed_at: None,
+        booking_id: None,
+        duration_prices: None,
+        stripe_price: None,
+    };
+    save(&link);
+
+    ic_cdk::println!("🔗 Created session link for {} at {}", link.availability_id, link.price);
+    Ok(link)
+}
+
+pub fn list_session_links(owner: Principal) -> Vec<SessionLink> {
+    SESSION_LINKS.with(|l| {
+        l.borrow()
+            .iter()
+            .filter(|(_, link)| link.owner == owner)
+            .map(|(_, link)| link)
+            .collect()
+    })
+}
+
+/// Offer extra session lengths on an unused link, each at its own price
+pub fn set_session_link_prices(caller: Principal, token: String, prices: Vec<DurationPrice>) -> Result<SessionLink, String> {
+    let mut link = get_link(&token)?;
+    if link.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    if link.redeemed_by.is_some() {
+        return Err("Session link has already been used".to_string());
+    }
+    if prices.len() > MAX_DURATION_PRICES {
+        return Err(format!("At most {} duration prices", MAX_DURATION_PRICES));
+    }
+
+    let availability = availabilities::get_availability(link.availability_id.clone())?;
+    for (i, entry) in prices.iter().enumerate() {
+        if entry.duration_minutes < MIN_DURATION_MINUTES || entry.duration_minutes > MAX_DURATION_MINUTES {
+            return Err(format!(
+                "duration_minutes must be {}-{}",
+                MIN_DURATION_MINUTES, MAX_DURATION_MINUTES
+            ));
+        }
+        if entry.price == 0 {
+            return Err("price must be greater than 0".to_string());
+        }
+        if prices[..i].iter().any(|p| p.duration_minutes == entry.duration_minutes) {
+            return Err(format!("Duplicate price for {} minutes", entry.duration_minutes));
+        }
+        if availability.durations.is_some() {
+            availabilities::duration_option(&availability, entry.duration_minutes)?;
+        }
+    }
+
+    link.duration_prices = if prices.is_empty() { None } else { Some(prices) };
+    save(&link);
+    Ok(link)
+}
+
+/// Let guests pay for the link's default length by card (None = ledger only)
+pub fn set_session_link_stripe_price(caller: Principal, token: String, price: Option<StripePrice>) -> Result<SessionLink, String> {
+    let mut link = get_link(&token)?;
+    if link.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    if link.redeemed_by.is_some() {
+        return Err("Session link has already been used".to_string());
+    }
+    if let Some(price) = &price {
+        if price.amount == 0 {
+            return Err("amount must be greater than 0".to_string());
+        }
+        if price.currency.len() != 3 || !price.currency.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err("currency must be a lowercase ISO 4217 code".to_string());
+        }
+    }
+
+    link.stripe_price = price;
+    save(&link);
+    Ok(link)
+}
+
+/// Delete an unused link; redeemed links are kept as the booking's record
+pub fn revoke_session_link(caller: Principal, token: String) -> Result<(), String> {
+    let link = get_link(&token)?;
+    if link.owner != caller {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "not the owner"));
+    }
+    if link.redeemed_by.is_some() {
+        return Err("Session link has already been used".to_string());
+    }
+    SESSION_LINKS.with(|l| l.borrow_mut().remove(&token));
+    link_stats::remove(&token);
+    Ok(())
+}
+
+// ============================================================================
+// Guest Operations
+// ============================================================================
+
+/// What a guest sees when opening the link
+pub fn get_session_link(token: String) -> Result<SessionLink, String> {
+    let link = get_link(&token)?;
+    ensure_redeemable(&link, link_locale(&link))?;
+    Ok(link)
+}
+
+/// Book the link's session and pay for it. The guest must have called
+/// icrc2_approve on the link's ledger for at least the price of the chosen
+/// duration (plus fee).
+pub async fn redeem_session_link(caller: Principal, req: RedeemSessionLinkRequest) -> Result<Booking, String> {
+    let link = get_link(&req.token)?;
+    let availability = availabilities::get_availability(link.availability_id.clone())?;
+    let locale = i18n::resolve(availability.locale, link.owner);
+
+    if caller == Principal::anonymous() {
+        return Err(i18n::t(locale, Message::SignInToPay).to_string());
+    }
+    regions::require_available(caller, Feature::Payments)?;
+    ensure_redeemable(&link, locale)?;
+    let (duration_minutes, price) = priced_duration(&link, req.duration_minutes)?;
+
+    ensure_not_held(&link)?;
+    begin_redemption(&link.token)?;
+
+    let discount = promos::take_booking_discount(caller);
+    let percent = discount.as_ref().map(|d| d.percent.min(100) as u64).unwrap_or(0);
+    let (amount, tax) = invoicing::apply_tax(link.owner, price - price * percent / 100);
+
+    // Hold the slot before the ledger call so nobody else can take it meanwhile
+    let held = quotas::consume_rate(caller, QuotaKind::BookingsPerDay)
+        .and_then(|_| bookings::hold_slot(
+            &availability,
+            caller,
+            CreateBookingRequest {
+                availability_id: link.availability_id.clone(),
+                start_time: req.start_time,
+                end_time: req.start_time + duration_minutes as u64 * 60,
+                guest_name: req.guest_name,
+                guest_email: req.guest_email,
+                guest_timezone: req.guest_timezone,
+                field_answers: req.field_answers,
+            },
+            link.token.clone(),
+            PaymentHold {
+                method: PaymentMethod::Ledger,
+                amount,
+                currency: link.ledger_canister.to_text(),
+            },
+        ));
+    let booking = match held {
+        Ok(booking) => booking,
+        Err(e) => {
+            end_redemption(&link.token);
+            if let Some(discount) = discount {
+                promos::restore_booking_discount(caller, discount);
+            }
+            return Err(e);
+        }
+    };
+
+    let paid = if amount == 0 {
+        Ok(None)
+    } else {
+        ledger::transfer_from(link.ledger_canister, caller, amount, time()).await.map(Some)
+    };
+    end_redemption(&link.token);
+
+    let block_index = match paid {
+        Ok(block_index) => block_index,
+        Err(e) => {
+            bookings::release_hold(&booking.id);
+            if let Some(discount) = discount {
+                promos::restore_booking_discount(caller, discount);
+            }
+            return Err(e);
+        }
+    };
+
+    let now = time();
+    let booking = bookings::confirm_payment(&booking.id, BookingPayment {
+        ledger_canister: link.ledger_canister,
+        amount,
+        discount_percent: discount.map(|d| d.percent),
+        block_index,
+        paid_at: now,
+        tax,
+        stripe_payment_id: None,
+    })?;
+
+    if amount > 0 {
+        earnings::credit_payment(link.owner, link.ledger_canister, amount, &booking.id);
+    }
+
+    let mut link = link;
+    link.redeemed_by = Some(caller);
+    link.redeemed_at = Some(now);
+    link.booking_id = Some(booking.id.clone());
+    save(&link);
+
+    ic_cdk::println!("💰 Session link redeemed by {} (booking {})", caller.to_text(), booking.id);
+    Ok(booking)
+}
+
+/// Hold the link's session while the guest pays by card. The returned
+/// booking's ID goes into the Checkout session's `metadata.booking_id`; the
+/// Stripe webhook confirms or releases the hold.
+pub fn hold_session_link_for_stripe(caller: Principal, req: RedeemSessionLinkRequest) -> Result<Booking, String> {
+    let mut link = get_link(&req.token)?;
+    let availability = availabilities::get_availability(link.availability_id.clone())?;
+    let locale = i18n::resolve(availability.locale, link.owner);
+
+    if caller == Principal::anonymous() {
+        return Err(i18n::t(locale, Message::SignInToPay).to_string());
+    }
+    regions::require_available(caller, Feature::Payments)?;
+    ensure_redeemable(&link, locale)?;
+    let price = link.stripe_price.clone().ok_or("This link doesn't take card payments")?;
+    if req.duration_minutes.is_some_and(|minutes| minutes != link.duration_minutes) {
+        return Err("Card payments are only offered for the link's default length".to_string());
+    }
+    ensure_not_held(&link)?;
+    if REDEMPTIONS_IN_FLIGHT.with(|r| r.borrow().contains(&link.token)) {
+        return Err("This link is already being redeemed".to_string());
+    }
+
+    quotas::consume_rate(caller, QuotaKind::BookingsPerDay)?;
+    let booking = bookings::hold_slot(
+        &availability,
+        caller,
+        CreateBookingRequest {
+            availability_id: link.availability_id.clone(),
+            start_time: req.start_time,
+            end_time: req.start_time + link.duration_minutes as u64 * 60,
+            guest_name: req.guest_name,
+            guest_email: req.guest_email,
+            guest_timezone: req.guest_timezone,
+            field_answers: req.field_answers,
+        },
+        link.token.clone(),
+        PaymentHold {
+            method: PaymentMethod::Stripe,
+            amount: price.amount,
+            currency: price.currency,
+        },
+    )?;
+
+    link.booking_id = Some(booking.id.clone());
+    save(&link);
+    Ok(booking)
+}
+
+/// Mark the link used once the card payment for its hold has settled
+pub fn complete_stripe_hold(booking: &Booking) -> Result<(), String> {
+    let token = booking.session_link.as_deref().ok_or("Booking was not made through a session link")?;
+    let mut link = get_link(token)?;
+    if link.booking_id.as_deref() != Some(booking.id.as_str()) || link.redeemed_by.is_some() {
+        return Err("Session link is held by a different booking".to_string());
+    }
+    link.redeemed_by = Some(booking.guest);
+    link.redeemed_at = Some(time());
+    save(&link);
+    Ok(())
+}
+
+pub fn count_owned(owner: Principal) -> u32 {
+    SESSION_LINKS.with(|l| principal_moves::count_owned_in(&l.borrow(), |link| &mut link.owner, owner))
+}
+
+/// Hand `from`'s links to `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    SESSION_LINKS.with(|l| principal_moves::move_owned_in(&mut l.borrow_mut(), |link| &mut link.owner, from, to))
+}
+
+/// Entry count and sizes for get_storage_report
+pub fn storage_stats() -> EntryStats {
+    SESSION_LINKS.with(|m| storage::map_stats(&m.borrow()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+
+    #[test]
+    fn a_link_stops_being_redeemable_once_it_expires() {
+        let clock = MockClock::at(1_735_689_600 * 1_000_000_000);
+        clock::install(clock.clone());
+        let hour_ns = 60 * 60 * 1_000_000_000;
+        let mut link = SessionLink {
+            token: "token".to_string(),
+            availability_id: "availability".to_string(),
+            owner: Principal::from_slice(&[1; 29]),
+            title: "Consultation".to_string(),
+            price: 100_000_000,
+            ledger_canister: Principal::from_slice(&[2; 29]),
+            duration_minutes: 60,
+            created_at: time(),
+            expires_at: Some(time() + hour_ns),
+            redeemed_by: None,
+            redeemed_at: None,
+            booking_id: None,
+            duration_prices: None,
+            stripe_price: None,
+        };
+
+        clock.advance(hour_ns - 1);
+        assert_eq!(ensure_redeemable(&link, Locale::En), Ok(()));
+        clock.advance(1);
+        assert_eq!(ensure_redeemable(&link, Locale::En), Err(i18n::t(Locale::En, Message::LinkExpired).to_string()));
+
+        // Without an expiry a link waits until it is redeemed
+        link.expires_at = None;
+        assert_eq!(ensure_redeemable(&link, Locale::En), Ok(()));
+        link.redeemed_by = Some(Principal::from_slice(&[3; 29]));
+        assert_eq!(ensure_redeemable(&link, Locale::En), Err(i18n::t(Locale::En, Message::LinkAlreadyUsed).to_string()));
+    }
+}
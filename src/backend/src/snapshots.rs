@@ -0,0 +1,95 @@
+use candid::{CandidType, Principal};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::clock::time;
+use crate::availabilities::{self, FreeSlot};
+use crate::bookings::{self, Booking, CreateBookingRequest};
+use crate::{entropy, experiments};
+use crate::error_codes::{self, ErrorCode};
+
+/// How long a guest can take between loading slots and submitting (nanoseconds)
+const SNAPSHOT_TTL_NS: u64 = 15 * 60 * 1_000_000_000;
+const MAX_SNAPSHOTS: usize = 10_000;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Free slots frozen at page load; pass the token back when booking
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct FreeSlotSnapshot {
+    pub token: String,
+    pub availability_id: String,
+    pub slots: Vec<FreeSlot>,
+    pub expires_at: u64, // Nanoseconds
+    pub guest_timezone: Option<String>, // Applied to the booking unless it names its own
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Token -> snapshot. Heap-only: snapshots live for minutes, and after an
+    // upgrade guests simply fall back to the live check.
+    static SNAPSHOTS: RefCell<HashMap<String, FreeSlotSnapshot>> = RefCell::new(HashMap::new());
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+pub fn snapshot_free_slots(
+    caller: Principal,
+    id: String,
+    range_start: u64,
+    range_end: u64,
+    duration_minutes: Option<u32>,
+    access_code: Option<String>,
+    guest_timezone: Option<String>,
+) -> Result<FreeSlotSnapshot, String> {
+    if let Some(timezone) = &guest_timezone {
+        bookings::validate_timezone(timezone)?;
+    }
+    let slots = availabilities::get_free_slots_as(caller, id.clone(), range_start, range_end, duration_minutes, access_code)?;
+    experiments::record_view(&id, caller);
+    let snapshot = FreeSlotSnapshot {
+        token: entropy::next_token(16)?,
+        availability_id: id,
+        slots,
+        expires_at: time() + SNAPSHOT_TTL_NS,
+        guest_timezone,
+    };
+
+    SNAPSHOTS.with(|s| {
+        let mut map = s.borrow_mut();
+        let now = time();
+        map.retain(|_, snap| snap.expires_at > now);
+        if map.len() >= MAX_SNAPSHOTS {
+            return Err("Too many open slot snapshots, try again shortly".to_string());
+        }
+        map.insert(snapshot.token.clone(), snapshot.clone());
+        Ok(())
+    })?;
+    Ok(snapshot)
+}
+
+/// Book a slot from the snapshot. Calendar changes synced after the snapshot
+/// are tolerated; overlapping bookings are still rejected. The snapshot is
+/// used up by a successful booking.
+pub fn book_with_snapshot(caller: Principal, token: String, mut req: CreateBookingRequest) -> Result<Booking, String> {
+    let snapshot = SNAPSHOTS.with(|s| s.borrow().get(&token).cloned())
+        .filter(|snap| snap.expires_at > time())
+        .ok_or_else(|| error_codes::coded(ErrorCode::TokenExpired, "Snapshot expired, reload the free slots"))?;
+    if snapshot.availability_id != req.availability_id {
+        return Err("Snapshot belongs to a different availability".to_string());
+    }
+
+    if req.guest_timezone.is_none() {
+        req.guest_timezone = snapshot.guest_timezone;
+    }
+    let booking = bookings::create_booking_from_snapshot(caller, req, &snapshot.slots)?;
+    SNAPSHOTS.with(|s| s.borrow_mut().remove(&token));
+    Ok(booking)
+}
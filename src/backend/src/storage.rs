@@ -0,0 +1,239 @@
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt::Display;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    Memory as _, StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, booking_events, bookings, delegations, earnings, link_stats, outbox, outcall_cache, quarantine, session_links};
+
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+/// Largest records listed per inspected structure
+const LARGEST_RECORDS: usize = 5;
+
+/// Every stable structure by memory ID (mirrors stable-struct-guide.md)
+pub(crate) const STRUCTURES: [(u8, &str); 87] = [
+    (0, "AVAILABILITIES"), (1, "USER_AVAILABILITIES"), (2, "USER_TOKENS"), (3, "DEFAULT_LIMITS"),
+    (4, "LIMIT_OVERRIDES"), (5, "USER_PLANS"), (6, "BILLING_CONFIG"), (7, "SUBSCRIPTIONS"),
+    (8, "PROMO_CODES"), (9, "REDEMPTIONS"), (10, "BOOKING_DISCOUNTS"), (11, "REFERRAL_CODES"),
+    (12, "REFERRALS"), (13, "BOOKINGS"), (14, "OWNER_BOOKINGS"), (15, "SESSION_LINKS"),
+    (16, "JOURNAL"), (17, "BALANCES"), (18, "TAX_PROFILES"), (19, "ORIGIN_CONFIGS"),
+    (20, "TENANTS"), (21, "MEMBERSHIPS"), (22, "USER_LOCALES"), (23, "HIJRI_ANNOTATION"),
+    (24, "ONE_TIME_LINKS"), (25, "LINK_STATS"), (26, "RESOURCES"), (27, "RESERVATIONS"),
+    (28, "HOOKS"), (29, "API_KEYS"), (30, "ORG_BUSY"), (31, "DELEGATIONS"),
+    (32, "DELEGATE_AUDIT"), (33, "PIPELINES"), (34, "CANDIDATES"), (35, "TEAMS"),
+    (36, "VERIFIED_EMAILS"), (37, "DISCORD_CONFIG"), (38, "DISCORD_LINKS"), (39, "MATRIX_NOTIFIERS"),
+    (40, "BOOKING_EVENTS"), (41, "OUTBOX"), (42, "OUTCALL_CACHE"), (43, "STORAGE_SNAPSHOT"),
+    (44, "CORRUPT_RECORDS"), (45, "UPGRADE_SNAPSHOT"), (46, "EMAIL_INDEX"), (47, "USERNAME_INDEX"),
+    (48, "STABLE_SESSIONS"), (49, "MIGRATION_STATUS"), (50, "MAINTENANCE_JOBS"), (51, "USER_FAVORITES"),
+    (52, "TOKENS"), (53, "ACCOUNT_LINKS"), (54, "TOKEN_FINGERPRINTS"), (55, "API_USAGE"),
+    (56, "SYNC_STATE"),
+    (57, "SEEN_WEBHOOK_EVENTS"),
+    (58, "STRIPE_CONFIG"),
+    (59, "STRIPE_DEAD_LETTERS (retired)"),
+    (60, "NOTIFICATION_TEMPLATES"),
+    (61, "DELIVERY_LOG"),
+    (62, "SLOT_EXPERIMENTS"),
+    (63, "CURSOR_KEY"),
+    (64, "TRASH"),
+    (65, "LEGAL_HOLDS"),
+    (66, "TERMS_CONFIG"),
+    (67, "TERMS_ACCEPTANCES"),
+    (68, "REGION_POLICIES"),
+    (69, "REGION_DECLARATIONS"),
+    (70, "ANALYTICS_CONFIG"),
+    (71, "ANALYTICS_EVENTS"),
+    (72, "COUNTERS"),
+    (73, "REBUILD_STATUS"),
+    (74, "REVOCATIONS"),
+    (75, "CALENDAR_CONFLICTS"),
+    (76, "CHECK_INS"),
+    (77, "FOLLOW_UP_QUEUE"),
+    (78, "AVAILABILITY_SHARES"),
+    (79, "ORG_DEFAULTS"),
+    (80, "SELF_TEST_SCRATCH"),
+    (81, "ORIGIN_ALIASES"),
+    (82, "PRINCIPAL_MOVES"),
+    (83, "READ_CACHE_CONFIG"),
+    (84, "READ_CACHE_PENDING"),
+    (85, "ENTROPY_POOL"),
+    (86, "UPGRADE_DIGESTS"),
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct LargeRecord {
+    pub key: String,
+    pub bytes: u64,
+}
+
+/// Entry-level figures for a map that was walked record by record
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct EntryStats {
+    pub entries: u64,
+    pub encoded_bytes: u64,          // Sum of encoded values, keys excluded
+    pub largest: Vec<LargeRecord>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct StructureUsage {
+    pub memory_id: u8,
+    pub name: String,
+    pub allocated_bytes: u64,        // Pages the memory manager has handed this structure
+    pub stats: Option<EntryStats>,   // None for structures that aren't walked (small or fixed-size)
+    pub allocated_growth: Option<i64>, // Since the last snapshot; None without one
+    pub entries_growth: Option<i64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct StorageReport {
+    pub heap_bytes: u64,
+    pub stable_bytes: u64,
+    pub structures: Vec<StructureUsage>,
+    pub generated_at: u64,
+    pub snapshot_at: Option<u64>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StorageSnapshot(pub Option<StorageReport>);
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for StorageSnapshot {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "StorageSnapshot").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Report saved by the last `snapshot_storage_report`, the baseline for growth figures
+    static STORAGE_SNAPSHOT: RefCell<StableCell<StorageSnapshot, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(43))),
+            StorageSnapshot::default(),
+        ).expect("Failed to init storage snapshot")
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Walk a map, summing encoded value sizes and keeping the largest records
+pub fn map_stats<K, V>(map: &StableBTreeMap<K, V, Memory>) -> EntryStats
+where
+    K: Storable + Ord + Clone + Display,
+    V: Storable,
+{
+    let mut encoded_bytes = 0;
+    let mut largest: Vec<LargeRecord> = Vec::new();
+    for (key, value) in map.iter() {
+        let bytes = value.to_bytes().len() as u64;
+        encoded_bytes += bytes;
+        if largest.len() < LARGEST_RECORDS || largest.last().is_some_and(|r| bytes > r.bytes) {
+            largest.push(LargeRecord { key: key.to_string(), bytes });
+            largest.sort_by_key(|r| std::cmp::Reverse(r.bytes));
+            largest.truncate(LARGEST_RECORDS);
+        }
+    }
+    EntryStats { entries: map.len(), encoded_bytes, largest }
+}
+
+/// Structures that can grow without bound are walked; the rest report pages only
+fn entry_stats(memory_id: u8) -> Option<EntryStats> {
+    match memory_id {
+        0 => Some(availabilities::storage_stats()),
+        2 => Some(crate::USER_TOKENS.with(|t| map_stats(t.borrow().inner()))),
+        13 => Some(bookings::storage_stats()),
+        15 => Some(session_links::storage_stats()),
+        16 => Some(earnings::storage_stats()),
+        25 => Some(link_stats::storage_stats()),
+        32 => Some(delegations::storage_stats()),
+        40 => Some(booking_events::storage_stats()),
+        41 => Some(outbox::storage_stats()),
+        42 => Some(outcall_cache::storage_stats()),
+        _ => None,
+    }
+}
+
+fn heap_bytes() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+fn build_report() -> StorageReport {
+    let previous = STORAGE_SNAPSHOT.with(|s| s.borrow().get().0.clone());
+    let structures = STRUCTURES
+        .iter()
+        .map(|(id, name)| {
+            let allocated_bytes = MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(*id)).size()) * WASM_PAGE_SIZE;
+            let stats = entry_stats(*id);
+            let before = previous.as_ref().and_then(|p| p.structures.iter().find(|s| s.memory_id == *id));
+            StructureUsage {
+                memory_id: *id,
+                name: name.to_string(),
+                allocated_bytes,
+                allocated_growth: before.map(|b| allocated_bytes as i64 - b.allocated_bytes as i64),
+                entries_growth: match (&stats, before.and_then(|b| b.stats.as_ref())) {
+                    (Some(now), Some(then)) => Some(now.entries as i64 - then.entries as i64),
+                    _ => None,
+                },
+                stats,
+            }
+        })
+        .collect();
+
+    StorageReport {
+        heap_bytes: heap_bytes(),
+        stable_bytes: ic_cdk::api::stable::stable_size() * WASM_PAGE_SIZE,
+        structures,
+        generated_at: time(),
+        snapshot_at: previous.map(|p| p.generated_at),
+    }
+}
+
+// ============================================================================
+// Reports
+// ============================================================================
+
+/// Current usage per structure, with growth since the last saved snapshot
+pub fn get_storage_report() -> StorageReport {
+    build_report()
+}
+
+/// Save the current report as the baseline for future growth figures
+pub fn snapshot_storage_report() -> Result<StorageReport, String> {
+    let report = build_report();
+    STORAGE_SNAPSHOT.with(|s| {
+        s.borrow_mut()
+            .set(StorageSnapshot(Some(report.clone())))
+            .map(|_| report)
+            .map_err(|e| format!("Failed to store snapshot: {:?}", e))
+    })
+}
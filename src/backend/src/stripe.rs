@@ -0,0 +1,242 @@
+//! Stripe webhook receiver. Checkouts carry the ID of a booking held by
+//! `hold_session_link_for_stripe` in `metadata.booking_id` (or
+//! `client_reference_id`); a payment of the held amount confirms the hold and
+//! an expired or failed checkout releases it. Events that
+//! can't be applied are retried by the outbox and end up in its dead-letter
+//! queue for an admin to retry.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableCell, Storable,
+};
+use std::borrow::Cow;
+use serde_json::{json, Value};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::bookings::{self, BookingPayment, BookingStatus, PaymentMethod};
+use crate::http::{self, HttpRequest, HttpResponse};
+use crate::{outbox, quarantine, session_links, webhook_auth};
+use crate::error_codes::{self, ErrorCode};
+
+/// Endpoint URL path to register in the Stripe dashboard
+pub const WEBHOOK_PATH: &str = "/hooks/stripe";
+const DEAD_LETTERS_LISTED: usize = 100;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StripeConfig {
+    pub webhook_secret: Option<String>, // Endpoint signing secret (whsec_...); None = receiver off
+}
+
+/// A verified event the outbox gave up on, e.g. a payment for a hold that
+/// was already released
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ParkedStripeEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub error: String,
+    pub attempts: u32,
+    pub parked_at: u64,
+    pub updated_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for StripeConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "StripeConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static STRIPE_CONFIG: RefCell<StableCell<StripeConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(58))),
+            StripeConfig::default(),
+        ).expect("Failed to init Stripe config")
+    );
+}
+
+// ============================================================================
+// Event Handling
+// ============================================================================
+
+fn booking_id(object: &Value) -> Option<&str> {
+    object["metadata"]["booking_id"].as_str()
+        .or(object["client_reference_id"].as_str())
+        .filter(|id| !id.is_empty())
+}
+
+/// Confirm a booking held for a Stripe payment of exactly this amount and
+/// currency; a repeat of the same payment is a no-op
+fn capture(object: &Value, amount: Option<u64>) -> Result<(), String> {
+    let id = booking_id(object).ok_or("Event has no booking_id")?;
+    let payment_id = object["payment_intent"].as_str().or(object["id"].as_str()).unwrap_or_default();
+    let amount = amount.ok_or("Event has no amount")?;
+    let currency = object["currency"].as_str().ok_or("Event has no currency")?;
+    let booking = bookings::find(id).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, format!("Booking {} not found", id)))?;
+    match booking.status {
+        BookingStatus::Pending => {}
+        BookingStatus::Confirmed if booking.payment.as_ref()
+            .is_some_and(|p| p.stripe_payment_id.as_deref() == Some(payment_id)) => return Ok(()),
+        status => return Err(format!("Booking {} is {:?}, not awaiting payment", id, status)),
+    }
+    let hold = booking.payment_hold.as_ref()
+        .filter(|hold| hold.method == PaymentMethod::Stripe)
+        .ok_or_else(|| format!("Booking {} is not awaiting a Stripe payment", id))?;
+    if hold.amount != amount || !hold.currency.eq_ignore_ascii_case(currency) {
+        return Err(format!(
+            "Booking {} expects {} {}, got {} {}",
+            id, hold.amount, hold.currency, amount, currency
+        ));
+    }
+    session_links::complete_stripe_hold(&booking)?;
+    bookings::confirm_payment(id, BookingPayment {
+        ledger_canister: Principal::management_canister(),
+        amount,
+        discount_percent: None,
+        block_index: None,
+        paid_at: time(),
+        tax: None,
+        stripe_payment_id: Some(payment_id.to_string()),
+    })?;
+    ic_cdk::println!("💳 [stripe] Booking {} paid ({})", id, payment_id);
+    Ok(())
+}
+
+/// Free the slot of a Stripe hold whose checkout failed; already released is
+/// fine. Holds waiting on a ledger transfer aren't Stripe's to release.
+fn release(object: &Value) -> Result<(), String> {
+    let id = booking_id(object).ok_or("Event has no booking_id")?;
+    let Some(booking) = bookings::find(id).filter(|b| b.status == BookingStatus::Pending) else {
+        return Ok(());
+    };
+    if !booking.payment_hold.is_some_and(|hold| hold.method == PaymentMethod::Stripe) {
+        return Err(format!("Booking {} is not awaiting a Stripe payment", id));
+    }
+    bookings::release_hold(id);
+    Ok(())
+}
+
+/// Apply a verified event. Unrelated event types are accepted and ignored.
+pub fn apply(payload: &str) -> Result<(), String> {
+    let event: Value = serde_json::from_str(payload).map_err(|e| format!("Invalid event: {}", e))?;
+    let object = &event["data"]["object"];
+    match event["type"].as_str().unwrap_or_default() {
+        "checkout.session.completed" | "checkout.session.async_payment_succeeded" => {
+            // Delayed methods complete unpaid and follow up with async_payment_*
+            if object["payment_status"] != "paid" {
+                return Ok(());
+            }
+            capture(object, object["amount_total"].as_u64())
+        }
+        "payment_intent.succeeded" => capture(object, object["amount_received"].as_u64()),
+        "checkout.session.expired"
+        | "checkout.session.async_payment_failed"
+        | "payment_intent.payment_failed"
+        | "payment_intent.canceled" => release(object),
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// Entry Points
+// ============================================================================
+
+/// Handle an event POSTed by Stripe. Anything verified gets a 200 so Stripe
+/// stops retrying; what couldn't be applied is retried through the outbox.
+pub fn handle(req: &HttpRequest) -> HttpResponse {
+    if req.method != "POST" {
+        return http::text(405, "Method not allowed");
+    }
+    let Some(secret) = STRIPE_CONFIG.with(|c| c.borrow().get().webhook_secret.clone()) else {
+        return http::text(401, "Stripe integration is not configured");
+    };
+    let signature = http::header(req, "Stripe-Signature").unwrap_or_default();
+    if let Err(e) = webhook_auth::verify_stripe_signature(&secret, signature, &req.body, time() / 1_000_000_000) {
+        return http::text(401, &e);
+    }
+    let Ok(payload) = String::from_utf8(req.body.to_vec()) else {
+        return http::text(400, "Invalid body");
+    };
+    let Some(event_id) = serde_json::from_str::<Value>(&payload).ok()
+        .and_then(|event| event["id"].as_str().map(str::to_string)) else {
+        return http::text(400, "Invalid JSON");
+    };
+
+    if !webhook_auth::claim_event("stripe", &event_id) {
+        return http::json(200, &json!({ "received": true, "duplicate": true }));
+    }
+    if let Err(e) = apply(&payload) {
+        ic_cdk::println!("⚠️ [stripe] Queueing event {} for retry: {}", event_id, e);
+        outbox::enqueue_stripe_event(event_id, payload, e);
+    }
+    http::json(200, &json!({ "received": true }))
+}
+
+// ============================================================================
+// Dead Letters
+// ============================================================================
+
+/// Events the outbox gave up on, most recently failed first
+pub fn list_dead_letters() -> Vec<ParkedStripeEvent> {
+    let mut events: Vec<ParkedStripeEvent> = outbox::list_failed_stripe_events()
+        .into_iter()
+        .map(|failed| ParkedStripeEvent {
+            event_type: serde_json::from_str::<Value>(&failed.payload).ok()
+                .and_then(|event| event["type"].as_str().map(str::to_string))
+                .unwrap_or_default(),
+            event_id: failed.event_id,
+            payload: failed.payload,
+            error: failed.error,
+            attempts: failed.attempts,
+            parked_at: failed.created_at,
+            updated_at: failed.updated_at,
+        })
+        .collect();
+    events.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+    events.truncate(DEAD_LETTERS_LISTED);
+    events
+}
+
+/// Give a dead-lettered event a fresh set of outbox attempts, starting now
+pub fn retry_dead_letter(event_id: String) -> Result<(), String> {
+    outbox::retry_stripe_event(&event_id)
+}
+
+/// Drop a dead-lettered event for good
+pub fn discard_dead_letter(event_id: String) -> Result<(), String> {
+    outbox::discard_stripe_event(&event_id)
+}
+
+pub fn set_config(config: StripeConfig) -> Result<(), String> {
+    if config.webhook_secret.as_ref().is_some_and(|s| !s.starts_with("whsec_")) {
+        return Err("webhook_secret must be a Stripe signing secret (whsec_...)".to_string());
+    }
+    STRIPE_CONFIG.with(|c| {
+        c.borrow_mut()
+            .set(config)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store Stripe config: {:?}", e))
+    })
+}
@@ -0,0 +1,229 @@
+//! Background refresh of busy times from Google Calendar. A few availabilities
+//! are synced per tick so thousands of owners are spread over time; recently
+//! viewed ones are refreshed more often, and a quota error from Google pauses
+//! every sync with exponential backoff.
+
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, AVAILABILITIES};
+use crate::{quarantine, service_status, tokens};
+use crate::error_codes::{self, ErrorCode};
+
+const SYNC_TIMER_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_SYNCS_PER_TICK: usize = 5;
+const MINUTE_NS: u64 = 60 * 1_000_000_000;
+const HOUR_NS: u64 = 60 * MINUTE_NS;
+const DAY_NS: u64 = 24 * HOUR_NS;
+const MAX_BACKOFF_NS: u64 = HOUR_NS;
+/// A tick still marked running after this long is assumed to have trapped
+const TICK_LEASE_NS: u64 = 10 * MINUTE_NS;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SyncState {
+    pub last_viewed_at: u64,        // Last guest page load (snapshot_free_slots), 0 if never
+    pub last_synced_at: u64,        // Last attempt, successful or not
+    pub consecutive_failures: u32,  // Each one doubles the refresh interval
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct SyncSchedulerStatus {
+    pub tracked: u64,
+    pub backoff_until: Option<u64>,  // Set while Google reports quota errors
+    pub backoff_level: u32,
+    pub last_tick_at: Option<u64>,
+    pub last_tick_synced: u32,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for SyncState {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "SyncState").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Availability ID -> sync bookkeeping
+    static SYNC_STATE: RefCell<StableBTreeMap<String, SyncState, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(56)))
+        )
+    );
+
+    // Heap-only, like the circuit breakers: an upgrade lifts the backoff
+    static BACKOFF_UNTIL: Cell<u64> = const { Cell::new(0) };
+    static BACKOFF_LEVEL: Cell<u32> = const { Cell::new(0) };
+    static TICK_STARTED_AT: Cell<u64> = const { Cell::new(0) };
+    static LAST_TICK: Cell<(u64, u32)> = const { Cell::new((0, 0)) };
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    SYNC_STATE.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// How stale busy times may get, by how recently a guest looked at the page
+fn refresh_interval(state: &SyncState, now: u64) -> u64 {
+    let since_view = now.saturating_sub(state.last_viewed_at);
+    let base = if state.last_viewed_at == 0 {
+        DAY_NS
+    } else if since_view < HOUR_NS {
+        10 * MINUTE_NS
+    } else if since_view < DAY_NS {
+        HOUR_NS
+    } else if since_view < 7 * DAY_NS {
+        6 * HOUR_NS
+    } else {
+        DAY_NS
+    };
+    base.saturating_mul(1 << state.consecutive_failures.min(3)).min(DAY_NS)
+}
+
+/// Google's project and per-user quota errors (403 usageLimits and 429)
+fn is_google_quota_error(error: &str) -> bool {
+    ["rateLimitExceeded", "userRateLimitExceeded", "quotaExceeded", "RESOURCE_EXHAUSTED"]
+        .iter()
+        .any(|reason| error.contains(reason))
+}
+
+fn back_off() {
+    let level = BACKOFF_LEVEL.with(|l| l.get()) + 1;
+    let delay = (MINUTE_NS << level.min(6)).min(MAX_BACKOFF_NS);
+    BACKOFF_LEVEL.with(|l| l.set(level));
+    BACKOFF_UNTIL.with(|b| b.set(time() + delay));
+    ic_cdk::println!("⏸️ [sync] Google quota error, pausing syncs for {}s", delay / 1_000_000_000);
+}
+
+/// Availabilities due for a refresh, most recently viewed first
+fn due_availabilities(now: u64) -> Vec<String> {
+    let mut due: Vec<(String, SyncState)> = AVAILABILITIES.with(|a| {
+        a.borrow()
+            .iter()
+            .filter(|(_, availability)| !availability.is_unreadable() && tokens::get(availability.owner).is_some())
+            .map(|(id, _)| {
+                let state = SYNC_STATE.with(|s| s.borrow().get(&id)).unwrap_or_default();
+                (id, state)
+            })
+            .filter(|(_, state)| now.saturating_sub(state.last_synced_at) >= refresh_interval(state, now))
+            .collect()
+    });
+    due.sort_by(|(_, a), (_, b)| {
+        b.last_viewed_at.cmp(&a.last_viewed_at).then(a.last_synced_at.cmp(&b.last_synced_at))
+    });
+    due.into_iter().take(MAX_SYNCS_PER_TICK).map(|(id, _)| id).collect()
+}
+
+fn update_state(id: &str, f: impl FnOnce(&mut SyncState)) {
+    SYNC_STATE.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut state = map.get(&id.to_string()).unwrap_or_default();
+        f(&mut state);
+        map.insert(id.to_string(), state);
+    });
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Note a guest page load so the availability is refreshed sooner. Only update
+/// calls persist this; views through queries are not counted.
+pub fn record_view(id: &str) {
+    let now = time();
+    update_state(id, |state| state.last_viewed_at = now);
+}
+
+/// Register the sync timer (timers don't survive upgrades, so this runs
+/// from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("calendar_sync", SYNC_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(SYNC_TIMER_INTERVAL, || {
+        service_status::heartbeat("calendar_sync", SYNC_TIMER_INTERVAL);
+        ic_cdk::spawn(tick());
+    });
+}
+
+async fn tick() {
+    let now = time();
+    let running = now.saturating_sub(TICK_STARTED_AT.with(|t| t.get())) < TICK_LEASE_NS;
+    if running || now < BACKOFF_UNTIL.with(|b| b.get()) {
+        return;
+    }
+    TICK_STARTED_AT.with(|t| t.set(now));
+
+    let mut synced = 0;
+    for id in due_availabilities(now) {
+        let Ok(availability) = availabilities::get_availability(id.clone()) else {
+            SYNC_STATE.with(|s| s.borrow_mut().remove(&id));
+            continue;
+        };
+        let result = crate::fetch_busy_times_for_availability(&availability).await;
+        let failed = result.is_err();
+        match result {
+            Ok(busy_times) => {
+                let _ = availabilities::update_availability_busy_times(availability.owner, id.clone(), busy_times);
+                BACKOFF_LEVEL.with(|l| l.set(0));
+                synced += 1;
+            }
+            Err(e) if is_google_quota_error(&e) => {
+                update_state(&id, |state| state.last_synced_at = time());
+                back_off();
+                break;
+            }
+            // Google is failing as a whole; the breaker decides when to try again
+            Err(e) if error_codes::classify(&e) == ErrorCode::UpstreamUnavailable => break,
+            Err(e) => {
+                ic_cdk::println!("⚠️ [sync] {} failed: {}", id, e);
+            }
+        }
+        update_state(&id, |state| {
+            state.last_synced_at = time();
+            state.consecutive_failures = if failed { state.consecutive_failures + 1 } else { 0 };
+        });
+    }
+
+    LAST_TICK.with(|t| t.set((now, synced)));
+    TICK_STARTED_AT.with(|t| t.set(0));
+}
+
+pub fn status() -> SyncSchedulerStatus {
+    let backoff_until = BACKOFF_UNTIL.with(|b| b.get());
+    let (last_tick_at, last_tick_synced) = LAST_TICK.with(|t| t.get());
+    SyncSchedulerStatus {
+        tracked: SYNC_STATE.with(|s| s.borrow().len()),
+        backoff_until: (backoff_until > time()).then_some(backoff_until),
+        backoff_level: BACKOFF_LEVEL.with(|l| l.get()),
+        last_tick_at: (last_tick_at > 0).then_some(last_tick_at),
+        last_tick_synced,
+    }
+}
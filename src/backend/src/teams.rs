@@ -0,0 +1,283 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, CollaboratorRole, FreeSlot};
+use crate::bookings::{self, Booking, BookingStatus, CreateBookingRequest};
+use crate::{entropy, principal_moves, quarantine, scheduling};
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_TEAM_MEMBERS: usize = 20;
+const MAX_WEIGHT: u32 = 10;
+const WEEK_SECS: u64 = 7 * 24 * 60 * 60;
+/// The Unix epoch fell on a Thursday; weeks are counted from Monday 00:00 UTC
+const EPOCH_TO_MONDAY_SECS: u64 = 3 * 24 * 60 * 60;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A member of a round-robin team, identified by their availability
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TeamMember {
+    pub availability_id: String,
+    pub weight: u32,                // 1-10; a weight of 2 takes twice the bookings of a weight of 1
+    pub max_per_week: Option<u32>,  // None = no weekly cap
+}
+
+/// Guests book the team; each booking goes to one eligible member
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Team {
+    pub id: String,
+    pub owner: Principal,
+    pub name: String,
+    pub members: Vec<TeamMember>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct TeamRequest {
+    pub name: String,
+    pub members: Vec<TeamMember>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct TeamBookingRequest {
+    pub team_id: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub guest_name: String,
+    pub guest_email: Option<String>,
+    pub guest_timezone: Option<String>,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Team {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and without members.
+    fn unreadable() -> Self {
+        Team {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            name: String::new(),
+            members: vec![],
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for Team {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Team").unwrap_or_else(Team::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static TEAMS: RefCell<StableBTreeMap<String, Team, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(35)))
+        )
+    );
+}
+
+pub fn clear() {
+    TEAMS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn generate_team_id() -> Result<String, String> {
+    Ok(format!("team-{}", entropy::next_token(6)?))
+}
+
+pub fn get_team(id: &str) -> Result<Team, String> {
+    TEAMS.with(|t| t.borrow().get(&id.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Team not found"))
+}
+
+fn owned_team(caller: Principal, id: &str) -> Result<Team, String> {
+    let team = get_team(id)?;
+    if team.owner != caller {
+        return Err(error_codes::coded(ErrorCode::NotFound, "Team not found"));
+    }
+    Ok(team)
+}
+
+/// Members must be availabilities the team owner owns or approves bookings for
+fn validate_request(caller: Principal, req: &TeamRequest) -> Result<(), String> {
+    if req.name.trim().is_empty() || req.name.len() > 100 {
+        return Err("name must be 1-100 characters".to_string());
+    }
+    if req.members.is_empty() || req.members.len() > MAX_TEAM_MEMBERS {
+        return Err(format!("a team needs 1-{} members", MAX_TEAM_MEMBERS));
+    }
+    for (i, member) in req.members.iter().enumerate() {
+        if member.weight == 0 || member.weight > MAX_WEIGHT {
+            return Err(format!("weight must be 1-{}", MAX_WEIGHT));
+        }
+        if member.max_per_week == Some(0) {
+            return Err("max_per_week must be at least 1 (remove the member instead)".to_string());
+        }
+        if req.members[..i].iter().any(|m| m.availability_id == member.availability_id) {
+            return Err(format!("Duplicate member {}", member.availability_id));
+        }
+        let availability = availabilities::get_availability(member.availability_id.clone())?;
+        if !availabilities::has_role(&availability, caller, CollaboratorRole::Approver) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, format!("Not an owner or approver of availability {}", member.availability_id)));
+        }
+    }
+    Ok(())
+}
+
+/// Live bookings on the availability in the Monday-to-Monday UTC week containing `at`
+fn bookings_in_week(owner: Principal, availability_id: &str, at: u64) -> u32 {
+    let week_start = ((at + EPOCH_TO_MONDAY_SECS) / WEEK_SECS * WEEK_SECS).saturating_sub(EPOCH_TO_MONDAY_SECS);
+    let week_end = week_start + WEEK_SECS;
+    bookings::list_owner_bookings(owner)
+        .iter()
+        .filter(|bk| bk.availability_id == availability_id && bk.status != BookingStatus::Cancelled)
+        .filter(|bk| bk.start_time >= week_start && bk.start_time < week_end)
+        .count() as u32
+}
+
+/// The least-loaded member (bookings this week divided by weight) who is
+/// under their weekly cap and free for the whole slot. Ties go to the member
+/// listed first.
+fn pick_member(team: &Team, start: u64, end: u64) -> Option<String> {
+    team.members
+        .iter()
+        .filter_map(|member| {
+            let availability = availabilities::get_availability(member.availability_id.clone()).ok()?;
+            let count = bookings_in_week(availability.owner, &availability.id, start);
+            if member.max_per_week.is_some_and(|cap| count >= cap) {
+                return None;
+            }
+            if !availabilities::is_free(&availability, start, end)
+                || !bookings::booked_blocks(availability.owner, start, end).is_empty()
+            {
+                return None;
+            }
+            Some((count as u64, member.weight as u64, availability.id))
+        })
+        // count_a / weight_a vs count_b / weight_b, cross-multiplied to stay exact
+        .min_by(|a, b| (a.0 * b.1).cmp(&(b.0 * a.1)))
+        .map(|(_, _, id)| id)
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn create_team(caller: Principal, req: TeamRequest) -> Result<Team, String> {
+    validate_request(caller, &req)?;
+    let now = time();
+    let team = Team {
+        id: generate_team_id()?,
+        owner: caller,
+        name: req.name.trim().to_string(),
+        members: req.members,
+        created_at: now,
+        updated_at: now,
+    };
+    TEAMS.with(|t| t.borrow_mut().insert(team.id.clone(), team.clone()));
+    Ok(team)
+}
+
+pub fn update_team(caller: Principal, id: String, req: TeamRequest) -> Result<Team, String> {
+    let mut team = owned_team(caller, &id)?;
+    validate_request(caller, &req)?;
+    team.name = req.name.trim().to_string();
+    team.members = req.members;
+    team.updated_at = time();
+    TEAMS.with(|t| t.borrow_mut().insert(id, team.clone()));
+    Ok(team)
+}
+
+pub fn delete_team(caller: Principal, id: String) -> Result<(), String> {
+    owned_team(caller, &id)?;
+    TEAMS.with(|t| t.borrow_mut().remove(&id));
+    Ok(())
+}
+
+pub fn list_teams(caller: Principal) -> Vec<Team> {
+    TEAMS.with(|t| {
+        t.borrow()
+            .iter()
+            .filter(|(_, team)| team.owner == caller)
+            .map(|(_, team)| team)
+            .collect()
+    })
+}
+
+// ============================================================================
+// Guest Operations
+// ============================================================================
+
+/// Windows where at least one member is free (weekly caps are applied at booking time)
+pub fn get_team_free_slots(id: String, range_start: u64, range_end: u64) -> Result<Vec<FreeSlot>, String> {
+    let team = get_team(&id)?;
+    let mut windows = Vec::new();
+    for member in &team.members {
+        windows.extend(availabilities::get_free_slots(member.availability_id.clone(), range_start, range_end, None)?);
+    }
+    Ok(scheduling::merge(windows))
+}
+
+/// Book the team; the slot goes to the least-loaded eligible member
+pub fn book_with_team(caller: Principal, req: TeamBookingRequest) -> Result<Booking, String> {
+    let team = get_team(&req.team_id)?;
+    if req.start_time >= req.end_time {
+        return Err("start_time must be less than end_time".to_string());
+    }
+    let availability_id = pick_member(&team, req.start_time, req.end_time)
+        .ok_or_else(|| error_codes::coded(ErrorCode::SlotUnavailable, "No team member is available for this slot"))?;
+
+    let booking = bookings::create_booking(caller, CreateBookingRequest {
+        availability_id,
+        start_time: req.start_time,
+        end_time: req.end_time,
+        guest_name: req.guest_name,
+        guest_email: req.guest_email,
+        guest_timezone: req.guest_timezone,
+        field_answers: None,
+    })?;
+    ic_cdk::println!("🔁 Team {} assigned booking {} to {}", team.id, booking.id, booking.availability_id);
+    Ok(booking)
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    TEAMS.with(|t| principal_moves::count_owned_in(&t.borrow(), |team| &mut team.owner, owner))
+}
+
+/// Hand `from`'s teams to `to` (the same user under a new principal)
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    TEAMS.with(|t| principal_moves::move_owned_in(&mut t.borrow_mut(), |team| &mut team.owner, from, to))
+}
@@ -0,0 +1,294 @@
+//! Owner-written notification and receipt text. A template has a subject and
+//! a body with `{{variable}}` placeholders (the integration hook field names),
+//! set per availability or org-wide per tenant; the availability's wins.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, integrations, quarantine, tenants};
+use crate::bookings::Booking;
+use crate::integrations::HookEvent;
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_SUBJECT_LEN: usize = 200;
+const MAX_BODY_LEN: usize = 5_000;
+const MAX_PLACEHOLDERS: usize = 50;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TemplateKind {
+    BookingCreated,
+    BookingCancelled,
+    Receipt,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TemplateScope {
+    Availability(String),
+    Tenant(String),        // Applies to every availability owned by a member
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct NotificationTemplate {
+    pub scope: TemplateScope,
+    pub kind: TemplateKind,
+    pub subject: String,
+    pub body: String,
+    pub updated_by: Principal,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct RenderedTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl NotificationTemplate {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Blank, so rendering falls back to the built-in text.
+    fn unreadable() -> Self {
+        NotificationTemplate {
+            scope: TemplateScope::Availability(String::new()),
+            kind: TemplateKind::BookingCreated,
+            subject: String::new(),
+            body: String::new(),
+            updated_by: Principal::anonymous(),
+            updated_at: 0,
+        }
+    }
+}
+
+impl Storable for NotificationTemplate {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "NotificationTemplate").unwrap_or_else(NotificationTemplate::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "a|<availability id>|<kind>" or "t|<tenant id>|<kind>" -> template
+    static NOTIFICATION_TEMPLATES: RefCell<StableBTreeMap<String, NotificationTemplate, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(60)))
+        )
+    );
+}
+
+#[cfg(feature = "dev-fixtures")]
+pub fn clear() {
+    NOTIFICATION_TEMPLATES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn scope_prefix(scope: &TemplateScope) -> String {
+    match scope {
+        TemplateScope::Availability(id) => format!("a|{}|", id),
+        TemplateScope::Tenant(id) => format!("t|{}|", id),
+    }
+}
+
+fn key(scope: &TemplateScope, kind: TemplateKind) -> String {
+    format!("{}{:?}", scope_prefix(scope), kind)
+}
+
+fn require_manager(caller: Principal, scope: &TemplateScope) -> Result<(), String> {
+    match scope {
+        TemplateScope::Availability(id) => {
+            let availability = availabilities::get_availability(id.clone())?;
+            if !availabilities::can_edit(caller, &availability) {
+                return Err(error_codes::coded(ErrorCode::Unauthorized, "Only the owner can change notification templates"));
+            }
+            Ok(())
+        }
+        TemplateScope::Tenant(id) => tenants::require_org_manager(caller, id),
+    }
+}
+
+/// Names inside `{{...}}`, in order of appearance
+fn placeholders(text: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or("Unclosed {{ in template")?;
+        names.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+/// Check a subject and body the way saved templates are checked
+pub fn validate(subject: &str, body: &str) -> Result<(), String> {
+    if subject.trim().is_empty() || subject.len() > MAX_SUBJECT_LEN {
+        return Err(format!("subject must be 1-{} characters", MAX_SUBJECT_LEN));
+    }
+    if subject.contains(['\r', '\n']) {
+        return Err("subject must be a single line".to_string());
+    }
+    if body.trim().is_empty() || body.len() > MAX_BODY_LEN {
+        return Err(format!("body must be 1-{} characters", MAX_BODY_LEN));
+    }
+    let mut names = placeholders(subject)?;
+    names.extend(placeholders(body)?);
+    if names.len() > MAX_PLACEHOLDERS {
+        return Err(format!("At most {} variables per template", MAX_PLACEHOLDERS));
+    }
+    if let Some(unknown) = names.iter().find(|name| !integrations::FIELDS.contains(name)) {
+        return Err(format!("Unknown variable: {} (expected one of {})", unknown, integrations::FIELDS.join(", ")));
+    }
+    Ok(())
+}
+
+/// Replace each placeholder with `value(name)`; the text was validated on save
+fn fill(text: &str, value: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&value(rest[start + 2..start + end].trim()));
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// The template that applies to a booking: its availability's, else its owner's tenant's
+fn resolve(booking: &Booking, kind: TemplateKind) -> Option<NotificationTemplate> {
+    NOTIFICATION_TEMPLATES.with(|t| {
+        let map = t.borrow();
+        // A saved template always has a subject; a blank one is unreadable
+        let get = |scope: TemplateScope| map.get(&key(&scope, kind)).filter(|template| !template.subject.is_empty());
+        get(TemplateScope::Availability(booking.availability_id.clone()))
+            .or_else(|| get(TemplateScope::Tenant(tenants::tenant_of(booking.owner))))
+    })
+}
+
+/// Render the owner's template for the booking, if they set one
+pub fn render(booking: &Booking, kind: TemplateKind) -> Option<RenderedTemplate> {
+    let template = resolve(booking, kind)?;
+    let event = match kind {
+        TemplateKind::BookingCancelled => HookEvent::BookingCancelled,
+        TemplateKind::BookingCreated | TemplateKind::Receipt => HookEvent::BookingCreated,
+    };
+    Some(render_text(booking, event, &template.subject, &template.body))
+}
+
+/// Fill a validated subject and body (e.g. a follow-up email) for the booking
+pub fn render_text(booking: &Booking, event: HookEvent, subject: &str, body: &str) -> RenderedTemplate {
+    let title = availabilities::get_availability(booking.availability_id.clone())
+        .map(|a| a.title)
+        .unwrap_or_default();
+    let value = |name: &str| integrations::field_value(name, event, booking, &title);
+    RenderedTemplate {
+        subject: fill(subject, value),
+        body: fill(body, value),
+    }
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+pub fn set_template(
+    caller: Principal,
+    scope: TemplateScope,
+    kind: TemplateKind,
+    subject: String,
+    body: String,
+) -> Result<NotificationTemplate, String> {
+    require_manager(caller, &scope)?;
+    validate(&subject, &body)?;
+
+    let template = NotificationTemplate {
+        scope,
+        kind,
+        subject,
+        body,
+        updated_by: caller,
+        updated_at: time(),
+    };
+    NOTIFICATION_TEMPLATES.with(|t| t.borrow_mut().insert(key(&template.scope, kind), template.clone()));
+    Ok(template)
+}
+
+pub fn delete_template(caller: Principal, scope: TemplateScope, kind: TemplateKind) -> Result<(), String> {
+    require_manager(caller, &scope)?;
+    NOTIFICATION_TEMPLATES.with(|t| t.borrow_mut().remove(&key(&scope, kind)))
+        .map(|_| ())
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Template not found"))
+}
+
+pub fn list_templates(caller: Principal, scope: TemplateScope) -> Result<Vec<NotificationTemplate>, String> {
+    require_manager(caller, &scope)?;
+    let prefix = scope_prefix(&scope);
+    Ok(NOTIFICATION_TEMPLATES.with(|t| {
+        t.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, template)| template)
+            .collect()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_found_and_trimmed() {
+        assert_eq!(placeholders("Hi {{guest_name}}, see you {{ start_time }}"), Ok(vec!["guest_name", "start_time"]));
+        assert_eq!(placeholders("no variables"), Ok(vec![]));
+        assert!(placeholders("Hi {{guest_name").is_err());
+    }
+
+    #[test]
+    fn validation_rejects_unknown_variables_and_bad_sizes() {
+        assert!(validate("Booked: {{availability_title}}", "See you at {{start_time}}").is_ok());
+        assert!(validate("Booked", "{{password}}").unwrap_err().starts_with("Unknown variable: password"));
+        assert!(validate("", "body").is_err());
+        assert!(validate("two\nlines", "body").is_err());
+        assert!(validate("subject", &"x".repeat(MAX_BODY_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn fill_substitutes_every_placeholder() {
+        let value = |name: &str| name.to_uppercase();
+        assert_eq!(fill("{{a}} and {{ b }}!", value), "A and B!");
+        assert_eq!(fill("plain", value), "plain");
+    }
+}
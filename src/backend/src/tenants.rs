@@ -0,0 +1,287 @@
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{origins, principal_moves};
+use crate::error_codes::{self, ErrorCode};
+use crate::quarantine;
+
+/// Tenant of every principal that never signed in through a tenant-scoped origin
+pub const DEFAULT_TENANT: &str = "default";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// An isolated white-label deployment sharing this canister
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,
+    pub admins: Vec<Principal>,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TenantMembership {
+    pub tenant_id: String,
+    pub joined_at: u64,
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct TenantMember {
+    pub principal: Principal,
+    pub joined_at: u64,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for Tenant {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "Tenant").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TenantMembership {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TenantMembership").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static TENANTS: RefCell<StableBTreeMap<String, Tenant, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20)))
+        )
+    );
+
+    // Principal -> tenant, fixed the first time the principal signs in
+    static MEMBERSHIPS: RefCell<StableBTreeMap<Principal, TenantMembership, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21)))
+        )
+    );
+}
+
+pub fn clear() {
+    TENANTS.with(|m| m.borrow_mut().clear_new());
+    MEMBERSHIPS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Membership
+// ============================================================================
+
+pub fn tenant_of(principal: Principal) -> String {
+    MEMBERSHIPS
+        .with(|m| m.borrow().get(&principal))
+        .map(|membership| membership.tenant_id)
+        .unwrap_or_else(|| DEFAULT_TENANT.to_string())
+}
+
+pub fn same_tenant(a: Principal, b: Principal) -> bool {
+    a == b || tenant_of(a) == tenant_of(b)
+}
+
+/// Tenant a viewer browses as: their own, or for a signed-out viewer the
+/// tenant of the origin the page was loaded from
+pub fn viewer_tenant(viewer: Principal, origin: Option<&str>) -> String {
+    match origin {
+        Some(origin) if viewer == Principal::anonymous() => origins::tenant_for(origin),
+        _ => tenant_of(viewer),
+    }
+}
+
+/// Whether the owner's records are visible to the viewer (see viewer_tenant)
+pub fn visible_to(viewer: Principal, origin: Option<&str>, owner: Principal) -> bool {
+    viewer == owner || viewer_tenant(viewer, origin) == tenant_of(owner)
+}
+
+/// Record which tenant a principal belongs to; the first assignment sticks
+pub fn assign(principal: Principal, tenant_id: &str) {
+    if tenant_id == DEFAULT_TENANT {
+        return;
+    }
+    MEMBERSHIPS.with(|m| {
+        let mut memberships = m.borrow_mut();
+        if !memberships.contains_key(&principal) {
+            memberships.insert(principal, TenantMembership {
+                tenant_id: tenant_id.to_string(),
+                joined_at: time(),
+            });
+            ic_cdk::println!("🏢 {} joined tenant {}", principal.to_text(), tenant_id);
+        }
+    });
+}
+
+/// Membership plus the tenants the principal administers
+pub fn count_owned(principal: Principal) -> u32 {
+    let admin_of = TENANTS.with(|t| t.borrow().iter().filter(|(_, tenant)| tenant.admins.contains(&principal)).count());
+    MEMBERSHIPS.with(|m| principal_moves::count_keyed(&m.borrow(), principal)) + admin_of as u32
+}
+
+/// Carry `from`'s membership over to `to` unless `to` already belongs to a
+/// tenant, and make `to` an admin wherever `from` was one
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let membership = MEMBERSHIPS.with(|m| principal_moves::move_keyed(&mut m.borrow_mut(), from, to));
+    let administered: Vec<Tenant> = TENANTS.with(|t| {
+        t.borrow().iter().map(|(_, tenant)| tenant).filter(|tenant| tenant.admins.contains(&from)).collect()
+    });
+    for mut tenant in administered.iter().cloned() {
+        tenant.admins.retain(|admin| *admin != from);
+        if !tenant.admins.contains(&to) {
+            tenant.admins.push(to);
+        }
+        TENANTS.with(|t| t.borrow_mut().insert(tenant.id.clone(), tenant));
+    }
+    membership + administered.len() as u32
+}
+
+pub fn tenant_exists(tenant_id: &str) -> bool {
+    tenant_id == DEFAULT_TENANT || TENANTS.with(|t| t.borrow().contains_key(&tenant_id.to_string()))
+}
+
+// ============================================================================
+// Tenant Administration
+// ============================================================================
+
+pub fn require_tenant_admin(caller: Principal, tenant_id: &str) -> Result<(), String> {
+    let tenant = TENANTS.with(|t| t.borrow().get(&tenant_id.to_string()))
+        .ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Tenant not found"))?;
+    if !tenant.admins.contains(&caller) && !ic_cdk::api::is_controller(&caller) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Only tenant admins can call this"));
+    }
+    Ok(())
+}
+
+/// Admins of a tenant manage its org-wide settings (resources, shared busy
+/// blocks); the default tenant's are managed by controllers
+pub fn require_org_manager(caller: Principal, tenant_id: &str) -> Result<(), String> {
+    if tenant_id == DEFAULT_TENANT {
+        if !ic_cdk::api::is_controller(&caller) {
+            return Err(error_codes::coded(ErrorCode::Unauthorized, "Only controllers can manage the default tenant"));
+        }
+        return Ok(());
+    }
+    require_tenant_admin(caller, tenant_id)
+}
+
+pub fn create_tenant(id: String, name: String, admins: Vec<Principal>) -> Result<Tenant, String> {
+    if id.len() < 3 || id.len() > 32 || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err("tenant id must be 3-32 lowercase letters, digits or dashes".to_string());
+    }
+    if id == DEFAULT_TENANT {
+        return Err(format!("{} is reserved", DEFAULT_TENANT));
+    }
+    if name.trim().is_empty() || name.len() > 100 {
+        return Err("name must be 1-100 characters".to_string());
+    }
+
+    TENANTS.with(|t| {
+        let mut tenants = t.borrow_mut();
+        if tenants.contains_key(&id) {
+            return Err("Tenant already exists".to_string());
+        }
+        let tenant = Tenant {
+            id: id.clone(),
+            name: name.trim().to_string(),
+            admins,
+            created_at: time(),
+        };
+        tenants.insert(id, tenant.clone());
+        Ok(tenant)
+    })
+}
+
+pub fn set_tenant_admins(id: String, admins: Vec<Principal>) -> Result<Tenant, String> {
+    TENANTS.with(|t| {
+        let mut tenants = t.borrow_mut();
+        let mut tenant = tenants.get(&id).ok_or_else(|| error_codes::coded(ErrorCode::NotFound, "Tenant not found"))?;
+        tenant.admins = admins;
+        tenants.insert(id, tenant.clone());
+        Ok(tenant)
+    })
+}
+
+pub fn list_tenants() -> Vec<Tenant> {
+    TENANTS.with(|t| t.borrow().iter().map(|(_, tenant)| tenant).collect())
+}
+
+pub fn list_members(tenant_id: &str) -> Vec<TenantMember> {
+    MEMBERSHIPS.with(|m| {
+        m.borrow()
+            .iter()
+            .filter(|(_, membership)| membership.tenant_id == tenant_id)
+            .map(|(principal, membership)| TenantMember {
+                principal,
+                joined_at: membership.joined_at,
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(principal: Principal, tenant_id: &str) {
+        MEMBERSHIPS.with(|m| m.borrow_mut().insert(principal, TenantMembership {
+            tenant_id: tenant_id.to_string(),
+            joined_at: 0,
+        }));
+    }
+
+    #[test]
+    fn records_of_other_tenants_are_invisible() {
+        let agency_owner = Principal::from_slice(&[1; 29]);
+        let agency_guest = Principal::from_slice(&[2; 29]);
+        let default_owner = Principal::from_slice(&[3; 29]);
+        join(agency_owner, "agency");
+        join(agency_guest, "agency");
+
+        assert!(visible_to(agency_guest, None, agency_owner));
+        assert!(!visible_to(agency_guest, None, default_owner));
+        assert!(!visible_to(default_owner, None, agency_owner));
+        assert!(visible_to(agency_owner, None, agency_owner));
+    }
+
+    #[test]
+    fn a_signed_out_viewer_browses_as_the_tenant_of_their_origin() {
+        let agency_owner = Principal::from_slice(&[4; 29]);
+        let default_owner = Principal::from_slice(&[5; 29]);
+        join(agency_owner, "agency");
+        let anonymous = Principal::anonymous();
+
+        // An origin without a tenant of its own is the default deployment
+        assert_eq!(viewer_tenant(anonymous, Some("https://weeekaly.com")), DEFAULT_TENANT);
+        assert!(visible_to(anonymous, Some("https://weeekaly.com"), default_owner));
+        assert!(!visible_to(anonymous, Some("https://weeekaly.com"), agency_owner));
+        // The origin only speaks for signed-out viewers
+        assert_eq!(viewer_tenant(agency_owner, Some("https://weeekaly.com")), "agency");
+    }
+}
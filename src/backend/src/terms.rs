@@ -0,0 +1,198 @@
+//! Terms of service and privacy policy acceptance. Controllers publish a
+//! version; every signed-in principal has to accept the current version
+//! before any state-changing call goes through, and each acceptance is kept
+//! (with its time) for the user's data export.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{principal_moves, quarantine};
+use crate::error_codes::{self, ErrorCode};
+
+const MAX_URL_LEN: usize = 500;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TermsConfig {
+    pub version: u32,          // 0 = no terms published, nothing is required
+    pub terms_url: String,
+    pub privacy_url: String,
+    pub published_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TermsAcceptance {
+    pub version: u32,
+    pub accepted_at: u64,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+struct AcceptanceLog(Vec<TermsAcceptance>);
+
+/// What the frontend needs to decide whether to show the terms dialog
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct TermsStatus {
+    pub current: TermsConfig,
+    pub accepted_version: Option<u32>,
+    pub needs_acceptance: bool,
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for TermsConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TermsConfig").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for AcceptanceLog {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "AcceptanceLog").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static TERMS_CONFIG: RefCell<StableCell<TermsConfig, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(66))),
+            TermsConfig::default(),
+        ).expect("Failed to init terms config")
+    );
+
+    // Principal -> every version they accepted, oldest first
+    static TERMS_ACCEPTANCES: RefCell<StableBTreeMap<Principal, AcceptanceLog, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(67)))
+        )
+    );
+}
+
+pub fn clear() {
+    TERMS_ACCEPTANCES.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn current_version() -> u32 {
+    TERMS_CONFIG.with(|c| c.borrow().get().version)
+}
+
+fn accepted_version(principal: Principal) -> Option<u32> {
+    TERMS_ACCEPTANCES.with(|a| a.borrow().get(&principal))
+        .and_then(|log| log.0.last().map(|acceptance| acceptance.version))
+}
+
+// ============================================================================
+// Enforcement
+// ============================================================================
+
+/// Reject state-changing calls until the caller accepts the current terms.
+/// Anonymous guests and controllers are not asked.
+pub fn require_accepted(caller: Principal) -> Result<(), String> {
+    let version = current_version();
+    if version == 0 || caller == Principal::anonymous() || ic_cdk::api::is_controller(&caller) {
+        return Ok(());
+    }
+    if accepted_version(caller) != Some(version) {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, format!("Please accept the terms of service (version {}) to continue", version)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+pub fn get_status(caller: Principal) -> TermsStatus {
+    let current = TERMS_CONFIG.with(|c| c.borrow().get().clone());
+    let accepted_version = accepted_version(caller);
+    TermsStatus {
+        needs_acceptance: current.version > 0 && accepted_version != Some(current.version),
+        current,
+        accepted_version,
+    }
+}
+
+/// Accept the published terms; `version` must be the one the user was shown
+pub fn accept(caller: Principal, version: u32) -> Result<TermsAcceptance, String> {
+    if caller == Principal::anonymous() {
+        return Err(error_codes::coded(ErrorCode::Unauthorized, "Sign in to accept the terms"));
+    }
+    let current = current_version();
+    if current == 0 || version != current {
+        return Err(format!("The current terms are version {}, reload and review them", current));
+    }
+    let acceptance = TermsAcceptance { version, accepted_at: time() };
+    TERMS_ACCEPTANCES.with(|a| {
+        let mut map = a.borrow_mut();
+        let mut log = map.get(&caller).unwrap_or_default();
+        if log.0.last().is_none_or(|last| last.version != version) {
+            log.0.push(acceptance.clone());
+            map.insert(caller, log);
+        }
+    });
+    Ok(acceptance)
+}
+
+/// Every acceptance by the principal, oldest first
+pub fn acceptances(principal: Principal) -> Vec<TermsAcceptance> {
+    TERMS_ACCEPTANCES.with(|a| a.borrow().get(&principal)).map(|log| log.0).unwrap_or_default()
+}
+
+pub fn count_owned(principal: Principal) -> u32 {
+    TERMS_ACCEPTANCES.with(|a| principal_moves::count_keyed(&a.borrow(), principal))
+}
+
+/// Carry `from`'s acceptances over to `to` (the same user under a new
+/// principal) unless `to` has accepted something already
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    TERMS_ACCEPTANCES.with(|a| principal_moves::move_keyed(&mut a.borrow_mut(), from, to))
+}
+
+/// Publish new terms; a higher version makes everyone accept again
+pub fn publish(version: u32, terms_url: String, privacy_url: String) -> Result<TermsConfig, String> {
+    if version <= current_version() {
+        return Err(format!("version must be above the current {}", current_version()));
+    }
+    for url in [&terms_url, &privacy_url] {
+        if !url.starts_with("https://") || url.len() > MAX_URL_LEN {
+            return Err(format!("URLs must be https and at most {} characters", MAX_URL_LEN));
+        }
+    }
+    let config = TermsConfig { version, terms_url, privacy_url, published_at: time() };
+    TERMS_CONFIG.with(|c| c.borrow_mut().set(config.clone()))
+        .map_err(|e| format!("Failed to store terms: {:?}", e))?;
+    ic_cdk::println!("📜 Published terms version {}", version);
+    Ok(config)
+}
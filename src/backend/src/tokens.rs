@@ -0,0 +1,253 @@
+//! OAuth tokens keyed by `Principal`, one entry per account. Principals that
+//! share an account (a frontend principal and its OAuth-derived ones) are
+//! linked to a canonical principal instead of each holding a copy.
+//!
+//! Tokens in the legacy text-keyed `USER_TOKENS` map are still read as a
+//! fallback until the `TokenRekey` maintenance task has moved them over.
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    StableBTreeMap,
+};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::upgrade_check::{self, RunningDigest};
+use crate::watched_map::WatchedMap;
+use crate::{TokenResponse, USER_TOKENS};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Entries no lookup can reach any more
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OrphanedTokenReport {
+    pub shadowed_tokens: Vec<Principal>,  // Own token on a principal that is linked elsewhere
+    pub dangling_links: Vec<Principal>,   // Linked to a canonical principal without a token
+    pub stale_fingerprints: u64,          // Point at a principal whose token has since changed
+    pub legacy_entries: u64,              // Still in USER_TOKENS, waiting for TokenRekey
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Canonical principal -> token
+    static TOKENS: RefCell<WatchedMap<Principal, TokenResponse>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(52))),
+            |principal, old, new| upgrade_check::track("TOKENS", principal, old, new),
+        )
+    );
+
+    // Canonical-account table: alias principal -> canonical principal
+    static ACCOUNT_LINKS: RefCell<WatchedMap<Principal, Principal>> = RefCell::new(
+        WatchedMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(53))),
+            |principal, old, new| upgrade_check::track("ACCOUNT_LINKS", principal, old, new),
+        )
+    );
+
+    // sha256 of the refresh (or access) token -> canonical principal, so the
+    // same token arriving under another principal becomes a link, not a copy
+    static TOKEN_FINGERPRINTS: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(54)))
+        )
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn fingerprint(token: &TokenResponse) -> String {
+    use sha2::{Sha256, Digest};
+    let secret = token.refresh_token.as_deref().unwrap_or(&token.access_token);
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Follows links, since relinking an account leaves its old aliases pointing
+/// at the principal it was merged into. `link` never creates a cycle.
+pub fn canonical(principal: Principal) -> Principal {
+    let mut current = principal;
+    while let Some(next) = ACCOUNT_LINKS.with(|l| l.borrow().get(&current)) {
+        current = next;
+    }
+    current
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+pub fn get(principal: Principal) -> Option<TokenResponse> {
+    TOKENS.with(|t| t.borrow().get(&canonical(principal)))
+        .or_else(|| USER_TOKENS.with(|t| t.borrow().get(&principal.to_text())))
+}
+
+pub fn access_token(principal: Principal) -> Option<String> {
+    get(principal)
+        .map(|token| token.access_token)
+        .filter(|token| !token.is_empty())
+}
+
+/// Store a token for the principal's account
+pub fn store(principal: Principal, token: TokenResponse) {
+    let owner = canonical(principal);
+    TOKEN_FINGERPRINTS.with(|f| f.borrow_mut().insert(fingerprint(&token), owner));
+    TOKENS.with(|t| t.borrow_mut().insert(owner, token));
+    USER_TOKENS.with(|t| t.borrow_mut().remove(&principal.to_text()));
+}
+
+/// Drop the account's token (the user revoked access). Links stay, so
+/// reconnecting from any alias lands on the same account.
+pub fn remove(principal: Principal) {
+    let owner = canonical(principal);
+    if let Some(token) = TOKENS.with(|t| t.borrow_mut().remove(&owner)) {
+        TOKEN_FINGERPRINTS.with(|f| f.borrow_mut().remove(&fingerprint(&token)));
+    }
+    USER_TOKENS.with(|t| {
+        let mut map = t.borrow_mut();
+        map.remove(&principal.to_text());
+        map.remove(&owner.to_text());
+    });
+}
+
+/// Make `alias` share `principal`'s account and token
+pub fn link(alias: Principal, principal: Principal) {
+    let owner = canonical(principal);
+    if alias == owner {
+        return;
+    }
+    ACCOUNT_LINKS.with(|l| l.borrow_mut().insert(alias, owner));
+    TOKENS.with(|t| t.borrow_mut().remove(&alias));
+    USER_TOKENS.with(|t| t.borrow_mut().remove(&alias.to_text()));
+}
+
+/// The principal's own token, if it isn't linked to another account
+pub fn count_owned(principal: Principal) -> u32 {
+    let own = TOKENS.with(|t| t.borrow().contains_key(&principal))
+        || USER_TOKENS.with(|t| t.borrow().contains_key(&principal.to_text()));
+    own as u32
+}
+
+/// Link `from`'s account to `to` (the same user under a new principal),
+/// handing its token over unless `to` has one of its own
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let Some(token) = get(from) else {
+        return 0;
+    };
+    let moved = count_owned(from);
+    if get(to).is_none() {
+        store(to, token);
+    }
+    link(from, to);
+    moved
+}
+
+/// Move up to `batch` legacy text-keyed entries over, linking duplicates of a
+/// token already moved. Returns (entries handled, legacy map now empty).
+pub fn rekey_legacy(batch: usize) -> (u64, bool) {
+    let chunk: Vec<(String, TokenResponse)> = USER_TOKENS.with(|t| t.borrow().iter().take(batch).collect());
+    for (key, token) in &chunk {
+        USER_TOKENS.with(|t| t.borrow_mut().remove(key));
+        let Ok(principal) = Principal::from_text(key) else {
+            ic_cdk::println!("⚠️ [tokens] Dropped token under non-principal key {}", key);
+            continue;
+        };
+        let existing = TOKEN_FINGERPRINTS.with(|f| f.borrow().get(&fingerprint(token)));
+        match existing {
+            Some(owner) if owner != principal => link(principal, owner),
+            _ => store(principal, token.clone()),
+        }
+    }
+    let done = USER_TOKENS.with(|t| t.borrow().is_empty());
+    (chunk.len() as u64, done)
+}
+
+// ============================================================================
+// Orphans
+// ============================================================================
+
+fn find_orphans() -> (OrphanedTokenReport, Vec<String>) {
+    let shadowed_tokens: Vec<Principal> = TOKENS.with(|t| {
+        t.borrow().iter().map(|(p, _)| p).filter(|p| canonical(*p) != *p).collect()
+    });
+    let dangling_links: Vec<Principal> = ACCOUNT_LINKS.with(|l| {
+        l.borrow()
+            .iter()
+            .filter(|(alias, _)| !TOKENS.with(|t| t.borrow().contains_key(&canonical(*alias))))
+            .map(|(alias, _)| alias)
+            .collect()
+    });
+    let stale: Vec<String> = TOKEN_FINGERPRINTS.with(|f| {
+        f.borrow()
+            .iter()
+            .filter(|(hash, owner)| {
+                TOKENS.with(|t| t.borrow().get(owner)).is_none_or(|token| fingerprint(&token) != *hash)
+            })
+            .map(|(hash, _)| hash)
+            .collect()
+    });
+    let report = OrphanedTokenReport {
+        shadowed_tokens,
+        dangling_links,
+        stale_fingerprints: stale.len() as u64,
+        legacy_entries: USER_TOKENS.with(|t| t.borrow().len()),
+    };
+    (report, stale)
+}
+
+pub fn orphaned_tokens() -> OrphanedTokenReport {
+    find_orphans().0
+}
+
+/// Remove everything `orphaned_tokens` reports, except legacy entries (those
+/// are moved by TokenRekey). Returns what was removed.
+pub fn cleanup_orphaned_tokens() -> OrphanedTokenReport {
+    let (report, stale) = find_orphans();
+    TOKENS.with(|t| {
+        let mut map = t.borrow_mut();
+        for principal in &report.shadowed_tokens {
+            map.remove(principal);
+        }
+    });
+    ACCOUNT_LINKS.with(|l| {
+        let mut map = l.borrow_mut();
+        for alias in &report.dangling_links {
+            map.remove(alias);
+        }
+    });
+    TOKEN_FINGERPRINTS.with(|f| {
+        let mut map = f.borrow_mut();
+        for hash in &stale {
+            map.remove(hash);
+        }
+    });
+    ic_cdk::println!(
+        "🧹 [tokens] Removed {} shadowed tokens, {} dangling links, {} stale fingerprints",
+        report.shadowed_tokens.len(), report.dangling_links.len(), report.stale_fingerprints
+    );
+    report
+}
+
+/// Fingerprints of the token store and account links, for upgrade checks
+pub fn upgrade_digests() -> Vec<(&'static str, RunningDigest)> {
+    vec![
+        ("USER_TOKENS", USER_TOKENS.with(|t| upgrade_check::digest(t.borrow().inner()))),
+        ("TOKENS", TOKENS.with(|t| upgrade_check::digest(t.borrow().inner()))),
+        ("ACCOUNT_LINKS", ACCOUNT_LINKS.with(|l| upgrade_check::digest(l.borrow().inner()))),
+    ]
+}
+
+/// Drop every record (dev fixtures only)
+pub fn clear() {
+    USER_TOKENS.with(|t| t.borrow_mut().clear_new());
+    TOKENS.with(|t| t.borrow_mut().clear_new());
+    ACCOUNT_LINKS.with(|l| l.borrow_mut().clear_new());
+    TOKEN_FINGERPRINTS.with(|f| f.borrow_mut().clear_new());
+}
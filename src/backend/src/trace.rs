@@ -0,0 +1,104 @@
+//! Trace IDs tying one call's log lines, audit entries, outbox jobs and
+//! webhooks together. Every update endpoint runs inside `run`/`run_async`,
+//! which picks a fresh ID, makes it current while the call executes and
+//! appends it to any error returned to the client, so "error (E_..., trace
+//! 3f9a01c2)" in a support ticket can be found in the canister logs.
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use crate::clock::time;
+use crate::error_codes::{self, ErrorCode};
+
+thread_local! {
+    // Trace of the call executing right now; set around every poll so
+    // interleaved async calls each see their own
+    static CURRENT: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    // Tells apart IDs minted in the same round
+    static TRACE_SEQ: Cell<u64> = const { Cell::new(0) };
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn new_id() -> String {
+    use sha2::{Sha256, Digest};
+    let seq = TRACE_SEQ.with(|s| {
+        s.set(s.get().wrapping_add(1));
+        s.get()
+    });
+    let hash = Sha256::digest(format!("{}|{}|{}", time(), seq, ic_cdk::caller()));
+    hex::encode(&hash[..4])
+}
+
+fn set_current(id: Option<String>) {
+    CURRENT.with(|c| *c.borrow_mut() = id);
+}
+
+/// Log the error under its trace and point the client at it
+fn tag(id: &str, error: String) -> (ErrorCode, String) {
+    let (code, text) = error_codes::split(&error);
+    ic_cdk::println!("❌ [trace {}] {}: {}", id, code.as_str(), text);
+    (code, format!("{} ({}, trace {})", text, code.as_str(), id))
+}
+
+// ============================================================================
+// Tracing
+// ============================================================================
+
+/// Trace of the call in progress (None outside traced calls)
+pub fn current() -> Option<String> {
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+/// Run a synchronous endpoint body under a new trace
+pub fn run<T>(body: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    run_into(body, |_, text| text)
+}
+
+/// Run a synchronous endpoint body under a new trace and turn its tagged
+/// error into another type by its code (the v2 API's variants)
+pub fn run_into<T, E>(
+    body: impl FnOnce() -> Result<T, String>,
+    into: impl FnOnce(ErrorCode, String) -> E,
+) -> Result<T, E> {
+    let id = new_id();
+    set_current(Some(id.clone()));
+    let result = body();
+    set_current(None);
+    result.map_err(|e| {
+        let (code, text) = tag(&id, e);
+        into(code, text)
+    })
+}
+
+/// Run an async endpoint body under a new trace
+pub async fn run_async<T>(body: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+    let id = new_id();
+    within(Some(id.clone()), body).await.map_err(|e| tag(&id, e).1)
+}
+
+/// Future that makes `id` the current trace whenever it is polled, so work
+/// resumed later (e.g. an outbox job) keeps the trace of the call that queued it
+pub struct Traced<F> {
+    id: Option<String>,
+    inner: Pin<Box<F>>,
+}
+
+pub fn within<F: Future>(id: Option<String>, inner: F) -> Traced<F> {
+    Traced { id, inner: Box::pin(inner) }
+}
+
+impl<F: Future> Future for Traced<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        set_current(self.id.clone());
+        let poll = self.inner.as_mut().poll(cx);
+        set_current(None);
+        poll
+    }
+}
@@ -0,0 +1,247 @@
+//! Deleted availabilities and bookings wait here for 30 days before a timer
+//! removes them for good, so an accidental delete can be undone. Bookings of
+//! a member under a legal hold stay until the hold is lifted.
+
+use candid::{CandidType, Principal, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::Duration;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, Storable,
+};
+use std::borrow::Cow;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::availabilities::{self, Availability};
+use crate::bookings::{self, Booking};
+use crate::{booking_events, legal_hold, quarantine, service_status};
+use crate::quotas::{self, QuotaKind};
+
+const RETENTION_NS: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+const PURGE_TIMER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Entries purged per timer tick; the rest wait for the next one
+const PURGE_BATCH: usize = 500;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum TrashedItem {
+    Availability(Box<Availability>),
+    Booking(Box<Booking>),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct TrashEntry {
+    pub id: String,          // ID of the availability or booking
+    pub owner: Principal,
+    pub item: TrashedItem,
+    pub deleted_by: Principal,
+    pub deleted_at: u64,
+    pub purge_at: u64,       // Nanoseconds; removed for good after this
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl TrashEntry {
+    /// Stand-in for a record that failed to decode (its bytes are in the
+    /// quarantine store). Owned by nobody and due for purging.
+    fn unreadable() -> Self {
+        TrashEntry {
+            id: String::new(),
+            owner: Principal::anonymous(),
+            item: TrashedItem::Booking(Box::new(Booking::unreadable())),
+            deleted_by: Principal::anonymous(),
+            deleted_at: 0,
+            purge_at: 0,
+        }
+    }
+}
+
+impl Storable for TrashEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "TrashEntry").unwrap_or_else(TrashEntry::unreadable)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "<owner>|<item id>" -> entry
+    static TRASH: RefCell<StableBTreeMap<String, TrashEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(64)))
+        )
+    );
+}
+
+pub fn clear() {
+    TRASH.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn prefix(owner: Principal) -> String {
+    format!("{}|", owner)
+}
+
+fn key(owner: Principal, id: &str) -> String {
+    format!("{}{}", prefix(owner), id)
+}
+
+fn put(owner: Principal, id: String, deleted_by: Principal, item: TrashedItem) {
+    let now = time();
+    let entry = TrashEntry {
+        id,
+        owner,
+        item,
+        deleted_by,
+        deleted_at: now,
+        purge_at: now + RETENTION_NS,
+    };
+    ic_cdk::println!("🗑️ Moved {} to the trash of {}", entry.id, owner);
+    TRASH.with(|t| t.borrow_mut().insert(key(owner, &entry.id), entry));
+}
+
+/// Whether the owner's trash holds this ID (replays must not bring it back)
+pub fn holds(owner: Principal, id: &str) -> bool {
+    TRASH.with(|t| t.borrow().contains_key(&key(owner, id)))
+}
+
+// ============================================================================
+// Trashing
+// ============================================================================
+
+pub fn put_availability(deleted_by: Principal, availability: Availability) {
+    put(availability.owner, availability.id.clone(), deleted_by, TrashedItem::Availability(Box::new(availability)));
+}
+
+pub fn put_booking(deleted_by: Principal, booking: Booking) {
+    put(booking.owner, booking.id.clone(), deleted_by, TrashedItem::Booking(Box::new(booking)));
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+/// The caller's trash, most recently deleted first
+pub fn list_trash(caller: Principal) -> Vec<TrashEntry> {
+    let prefix = prefix(caller);
+    let mut entries: Vec<TrashEntry> = TRASH.with(|t| {
+        t.borrow()
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, entry)| entry)
+            .collect()
+    });
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    entries
+}
+
+/// Put a deleted availability or booking back where it was
+pub fn restore_from_trash(caller: Principal, id: String) -> Result<TrashEntry, String> {
+    let entry = TRASH.with(|t| t.borrow().get(&key(caller, &id))).ok_or("Not in your trash")?;
+    match &entry.item {
+        TrashedItem::Availability(availability) => {
+            quotas::check_count(caller, QuotaKind::Availabilities, availabilities::count_user_availabilities(caller))?;
+            availabilities::restore(availability.as_ref().clone())?;
+        }
+        TrashedItem::Booking(booking) => bookings::restore(booking.as_ref().clone())?,
+    }
+    TRASH.with(|t| t.borrow_mut().remove(&key(caller, &id)));
+    ic_cdk::println!("♻️ Restored {} from the trash of {}", id, caller);
+    Ok(entry)
+}
+
+// ============================================================================
+// Purge Timer
+// ============================================================================
+
+/// Register the purge timer (timers don't survive upgrades, so this runs
+/// from both init and post_upgrade)
+pub fn start_timer() {
+    service_status::heartbeat("trash_purge", PURGE_TIMER_INTERVAL);
+    ic_cdk_timers::set_timer_interval(PURGE_TIMER_INTERVAL, || {
+        service_status::heartbeat("trash_purge", PURGE_TIMER_INTERVAL);
+        purge_expired();
+    });
+}
+
+/// Remove entries past their retention for good. Purged bookings also leave
+/// the event log, so a replay can't bring them back.
+fn purge_expired() {
+    let now = time();
+    let expired: Vec<(String, TrashEntry)> = TRASH.with(|t| {
+        t.borrow()
+            .iter()
+            .filter(|(_, entry)| entry.purge_at <= now)
+            .filter(|(_, entry)| {
+                !matches!(entry.item, TrashedItem::Booking(_)) || !legal_hold::is_held(entry.owner)
+            })
+            .take(PURGE_BATCH)
+            .collect()
+    });
+    if expired.is_empty() {
+        return;
+    }
+
+    let booking_ids: HashSet<String> = expired.iter()
+        .filter(|(_, entry)| matches!(entry.item, TrashedItem::Booking(_)))
+        .map(|(_, entry)| entry.id.clone())
+        .collect();
+    booking_events::forget(&booking_ids);
+    TRASH.with(|t| {
+        let mut map = t.borrow_mut();
+        for (key, _) in &expired {
+            map.remove(key);
+        }
+    });
+    ic_cdk::println!("🧹 [trash] Purged {} entries ({} bookings)", expired.len(), booking_ids.len());
+}
+
+// ============================================================================
+// Principal Moves
+// ============================================================================
+
+pub fn count_owned(owner: Principal) -> u32 {
+    let prefix = prefix(owner);
+    TRASH.with(|t| t.borrow().range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)).count() as u32)
+}
+
+/// Move `from`'s trash to `to` (the same user under a new principal), so
+/// restoring an item hands it to `to`
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    let prefix = prefix(from);
+    let entries: Vec<(String, TrashEntry)> = TRASH.with(|t| {
+        t.borrow().range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)).collect()
+    });
+    TRASH.with(|t| {
+        let mut map = t.borrow_mut();
+        for (old_key, mut entry) in entries.iter().cloned() {
+            map.remove(&old_key);
+            entry.owner = to;
+            match &mut entry.item {
+                TrashedItem::Availability(availability) => availability.owner = to,
+                TrashedItem::Booking(booking) => booking.owner = to,
+            }
+            map.insert(key(to, &entry.id), entry);
+        }
+    });
+    entries.len() as u32
+}
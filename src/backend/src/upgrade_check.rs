@@ -0,0 +1,372 @@
+use candid::{CandidType, Encode};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    storable::Bound,
+    StableBTreeMap, StableCell, Storable,
+};
+use std::borrow::Cow;
+use std::time::Duration;
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::{availabilities, bookings, quarantine, tokens};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Fingerprint of one stable map
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct StructureDigest {
+    pub name: String,
+    pub entries: u64,
+    pub keys_hash: String,
+    pub values_hash: String,  // Changes legitimately when the new code re-encodes records differently
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct UpgradeSnapshot {
+    pub taken_at: u64,
+    pub digests: Vec<StructureDigest>,
+    pub running: Option<bool>, // Some(true) = copied from the running digests; older snapshots hashed keys in order and can't be compared
+}
+
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct StructureCheck {
+    pub name: String,
+    pub entries_before: u64,  // Per the running digest: the pre-upgrade count plus writes since
+    pub entries_after: u64,   // Found by scanning the map
+    pub keys_match: bool,
+    pub values_match: bool,
+}
+
+/// Critical maps scanned now, compared with their running digests, which
+/// carried over from before the last upgrade and followed every write since
+#[derive(CandidType, Serialize, Clone, Debug)]
+pub struct UpgradeVerification {
+    pub snapshot_at: Option<u64>,  // None if no upgrade has run since this check was added
+    pub ok: bool,                  // Every structure kept its entry count and keys
+    pub structures: Vec<StructureCheck>,
+}
+
+/// Order-independent fingerprint of a map: the entry count and lane-wise
+/// sums of per-entry hashes, so a write adjusts it without a scan
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RunningDigest {
+    entries: u64,
+    keys: [u64; 4],
+    values: [u64; 4],
+}
+
+impl RunningDigest {
+    fn add(&mut self, key: &[u8], value: &[u8]) {
+        let (keys, values) = entry_hashes(key, value);
+        self.entries += 1;
+        for lane in 0..4 {
+            self.keys[lane] = self.keys[lane].wrapping_add(keys[lane]);
+            self.values[lane] = self.values[lane].wrapping_add(values[lane]);
+        }
+    }
+
+    fn remove(&mut self, key: &[u8], value: &[u8]) {
+        let (keys, values) = entry_hashes(key, value);
+        self.entries = self.entries.saturating_sub(1);
+        for lane in 0..4 {
+            self.keys[lane] = self.keys[lane].wrapping_sub(keys[lane]);
+            self.values[lane] = self.values[lane].wrapping_sub(values[lane]);
+        }
+    }
+
+    fn public(&self, name: &str) -> StructureDigest {
+        let hex_of = |lanes: &[u64; 4]| hex::encode(lanes.iter().flat_map(|lane| lane.to_le_bytes()).collect::<Vec<u8>>());
+        StructureDigest {
+            name: name.to_string(),
+            entries: self.entries,
+            keys_hash: hex_of(&self.keys),
+            values_hash: hex_of(&self.values),
+        }
+    }
+}
+
+// ============================================================================
+// Storable Implementations
+// ============================================================================
+
+impl Storable for UpgradeSnapshot {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        quarantine::decode(bytes.as_ref(), "UpgradeSnapshot").unwrap_or_default()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RunningDigest {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let lanes = std::iter::once(self.entries).chain(self.keys).chain(self.values);
+        Cow::Owned(lanes.flat_map(u64::to_le_bytes).collect())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        let lane = |i: usize| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        RunningDigest {
+            entries: lane(0),
+            keys: [lane(1), lane(2), lane(3), lane(4)],
+            values: [lane(5), lane(6), lane(7), lane(8)],
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded { max_size: 72, is_fixed_size: true };
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Written by pre_upgrade, read back by the new code after the upgrade
+    static UPGRADE_SNAPSHOT: RefCell<StableCell<UpgradeSnapshot, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(45))),
+            UpgradeSnapshot::default(),
+        ).expect("Failed to init upgrade snapshot")
+    );
+
+    // Structure name -> running digest, updated by the maps' write hooks.
+    // Missing until the first verification pass seeded it with a scan.
+    static RUNNING_DIGESTS: RefCell<StableBTreeMap<String, RunningDigest, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(86)))
+        )
+    );
+
+    // Checks gathered by the verification pass in progress
+    static PASS: RefCell<Vec<StructureCheck>> = const { RefCell::new(Vec::new()) };
+}
+
+/// One module's critical maps, each scanned into a running digest
+type Scan = fn() -> Vec<(&'static str, RunningDigest)>;
+
+/// Availabilities, bookings and OAuth tokens, with their indexes, one module
+/// per verification step
+const SCANS: [Scan; 3] = [
+    availabilities::upgrade_digests,
+    bookings::upgrade_digests,
+    tokens::upgrade_digests,
+];
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// sha256 of the key, and of the key with its value (so swapping two
+/// values changes the sum), as little-endian lanes
+fn entry_hashes(key: &[u8], value: &[u8]) -> ([u64; 4], [u64; 4]) {
+    use sha2::{Sha256, Digest};
+    let lanes = |hash: &[u8]| -> [u64; 4] {
+        std::array::from_fn(|lane| u64::from_le_bytes(hash[lane * 8..lane * 8 + 8].try_into().unwrap()))
+    };
+    let keys = Sha256::digest(key);
+    let mut values = Sha256::new();
+    values.update((key.len() as u64).to_le_bytes());
+    values.update(key);
+    values.update(value);
+    (lanes(&keys), lanes(&values.finalize()))
+}
+
+/// Running digest of a map computed from scratch
+pub fn digest<K, V>(map: &StableBTreeMap<K, V, Memory>) -> RunningDigest
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+{
+    let mut digest = RunningDigest::default();
+    for (key, value) in map.iter() {
+        digest.add(&key.to_bytes(), &value.to_bytes());
+    }
+    digest
+}
+
+/// Write hook of a critical map: fold the write into its running digest.
+/// Nothing to do until the first verification pass has seeded it.
+pub fn track<K: Storable, V: Storable>(name: &str, key: &K, old: Option<&V>, new: Option<&V>) {
+    RUNNING_DIGESTS.with(|d| {
+        let mut digests = d.borrow_mut();
+        let Some(mut running) = digests.get(&name.to_string()) else {
+            return;
+        };
+        let key = key.to_bytes();
+        if let Some(old) = old {
+            running.remove(&key, &old.to_bytes());
+        }
+        if let Some(new) = new {
+            running.add(&key, &new.to_bytes());
+        }
+        digests.insert(name.to_string(), running);
+    });
+}
+
+/// Scan one module's maps and compare them with their running digests,
+/// which then restart from the scan so a difference is reported once (new
+/// code re-encoding old records would otherwise skew values_hash for good).
+/// A structure without a running digest yet is only seeded.
+fn check(scan: Scan) -> Vec<StructureCheck> {
+    scan()
+        .into_iter()
+        .filter_map(|(name, now)| {
+            let expected = RUNNING_DIGESTS.with(|d| d.borrow_mut().insert(name.to_string(), now))?;
+            Some(StructureCheck {
+                name: name.to_string(),
+                entries_before: expected.entries,
+                entries_after: now.entries,
+                keys_match: expected.keys == now.keys,
+                values_match: expected.values == now.values,
+            })
+        })
+        .collect()
+}
+
+fn verification(structures: Vec<StructureCheck>) -> UpgradeVerification {
+    let snapshot = UPGRADE_SNAPSHOT.with(|s| s.borrow().get().clone());
+    let snapshot_at = (snapshot.taken_at > 0 && snapshot.running == Some(true)).then_some(snapshot.taken_at);
+    let ok = snapshot_at.is_some()
+        && structures.iter().all(|s| s.keys_match && s.entries_before == s.entries_after);
+    UpgradeVerification { snapshot_at, ok, structures }
+}
+
+// ============================================================================
+// Upgrade Hooks
+// ============================================================================
+
+/// Record the critical maps before the old code is replaced. Must not trap:
+/// a failing pre_upgrade blocks the upgrade. Copies the running digests, so
+/// it costs the same however large the maps are.
+pub fn take_snapshot() {
+    let digests = RUNNING_DIGESTS.with(|d| d.borrow().iter().map(|(name, running)| running.public(&name)).collect());
+    let snapshot = UpgradeSnapshot { taken_at: time(), digests, running: Some(true) };
+    let saved = UPGRADE_SNAPSHOT.with(|s| s.borrow_mut().set(snapshot));
+    if let Err(e) = saved {
+        ic_cdk::println!("⚠️ [upgrade] Failed to store pre-upgrade snapshot: {:?}", e);
+    }
+}
+
+/// Scan every critical map and compare it with its running digest
+pub fn verify_upgrade() -> UpgradeVerification {
+    verification(SCANS.iter().flat_map(|scan| check(*scan)).collect())
+}
+
+/// Verify the critical maps after install or upgrade, one module per timer
+/// tick so the scans stay out of post_upgrade and each fits in a message.
+/// The first pass after install seeds the running digests.
+pub fn start_timer() {
+    PASS.with(|p| p.borrow_mut().clear());
+    ic_cdk_timers::set_timer(Duration::ZERO, || verify_step(0));
+}
+
+fn verify_step(step: usize) {
+    let checks = check(SCANS[step]);
+    PASS.with(|p| p.borrow_mut().extend(checks));
+    if step + 1 < SCANS.len() {
+        ic_cdk_timers::set_timer(Duration::ZERO, move || verify_step(step + 1));
+    } else {
+        log_verification(verification(PASS.with(|p| p.take())));
+    }
+}
+
+/// Log the result of the verification pass
+fn log_verification(verification: UpgradeVerification) {
+    if verification.snapshot_at.is_none() {
+        ic_cdk::println!("ℹ️ [upgrade] No pre-upgrade snapshot to verify against");
+    } else if verification.ok {
+        ic_cdk::println!("✅ [upgrade] Critical structures intact");
+    } else {
+        for s in verification.structures.iter().filter(|s| !s.keys_match || s.entries_before != s.entries_after) {
+            ic_cdk::println!(
+                "🚨 [upgrade] {} changed: {} -> {} entries, keys match: {}",
+                s.name, s.entries_before, s.entries_after, s.keys_match
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+    use crate::TokenResponse;
+    use candid::Principal;
+
+    fn token(access_token: &str) -> TokenResponse {
+        TokenResponse {
+            access_token: access_token.to_string(),
+            refresh_token: None,
+            expires_in: 3600,
+            token_type: "Bearer".to_string(),
+        }
+    }
+
+    #[test]
+    fn the_digest_does_not_depend_on_write_order() {
+        let mut forward = RunningDigest::default();
+        let mut backward = RunningDigest::default();
+        let entries: [(&[u8], &[u8]); 3] = [(b"a", b"1"), (b"b", b"2"), (b"c", b"3")];
+        for (key, value) in entries {
+            forward.add(key, value);
+        }
+        for (key, value) in entries.iter().rev() {
+            backward.add(key, value);
+        }
+        assert_eq!(forward, backward);
+
+        // Swapping two values changes the digest even though the multiset of values is the same
+        let mut swapped = RunningDigest::default();
+        swapped.add(b"a", b"2");
+        swapped.add(b"b", b"1");
+        swapped.add(b"c", b"3");
+        assert_eq!(swapped.keys, forward.keys);
+        assert_ne!(swapped.values, forward.values);
+    }
+
+    #[test]
+    fn running_digests_follow_writes_to_the_critical_maps() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        let alice = Principal::from_slice(&[1; 29]);
+        let bob = Principal::from_slice(&[2; 29]);
+        tokens::store(alice, token("a-1"));
+
+        // The first pass only seeds
+        assert!(verify_upgrade().structures.is_empty());
+
+        tokens::store(alice, token("a-2"));
+        tokens::store(bob, token("b-1"));
+        tokens::link(bob, alice);
+        take_snapshot();
+        tokens::remove(alice);
+
+        let verification = verify_upgrade();
+        assert!(verification.ok, "{:?}", verification);
+        assert_eq!(verification.structures.len(), 7);
+        assert!(verification.structures.iter().all(|s| s.values_match));
+        let links = verification.structures.iter().find(|s| s.name == "ACCOUNT_LINKS").unwrap();
+        assert_eq!((links.entries_before, links.entries_after), (1, 1));
+    }
+
+    #[test]
+    fn a_record_lost_behind_the_hooks_fails_verification() {
+        clock::install(MockClock::at(1_735_689_600 * 1_000_000_000));
+        verify_upgrade();
+        take_snapshot();
+        // What a broken layout change would look like: a digest that no
+        // longer agrees with the map
+        track("TOKENS", &Principal::from_slice(&[3; 29]), None, Some(&token("lost")));
+
+        let verification = verify_upgrade();
+        assert!(!verification.ok);
+        let tokens = verification.structures.iter().find(|s| s.name == "TOKENS").unwrap();
+        assert_eq!((tokens.entries_before, tokens.entries_after, tokens.keys_match), (1, 0, false));
+    }
+}
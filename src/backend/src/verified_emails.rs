@@ -0,0 +1,45 @@
+use candid::Principal;
+use std::cell::RefCell;
+use ic_stable_structures::{memory_manager::MemoryId, StableBTreeMap};
+use crate::memory::{Memory, MEMORY_MANAGER};
+use crate::principal_moves;
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // Principal -> email from the identity provider's ID token at the last sign-in
+    static VERIFIED_EMAILS: RefCell<StableBTreeMap<Principal, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(36)))
+        )
+    );
+}
+
+pub fn clear() {
+    VERIFIED_EMAILS.with(|m| m.borrow_mut().clear_new());
+}
+
+// ============================================================================
+// Lookups
+// ============================================================================
+
+/// Remember the email the identity provider vouched for at sign-in
+pub fn record(principal: Principal, email: &str) {
+    VERIFIED_EMAILS.with(|v| v.borrow_mut().insert(principal, email.trim().to_lowercase()));
+}
+
+/// Lowercased email of a signed-in principal, if their provider shared one
+pub fn get(principal: Principal) -> Option<String> {
+    VERIFIED_EMAILS.with(|v| v.borrow().get(&principal))
+}
+
+pub fn count_owned(principal: Principal) -> u32 {
+    VERIFIED_EMAILS.with(|v| principal_moves::count_keyed(&v.borrow(), principal))
+}
+
+/// Carry `from`'s email over to `to` unless `to` signed in with one already
+pub fn move_owner(from: Principal, to: Principal) -> u32 {
+    VERIFIED_EMAILS.with(|v| principal_moves::move_keyed(&mut v.borrow_mut(), from, to))
+}
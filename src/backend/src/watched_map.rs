@@ -0,0 +1,83 @@
+//! A StableBTreeMap that reports every write to a hook. State derived from
+//! a map (the read cache of public availabilities) stays correct only if no
+//! write is missed, and a hook on the map itself can't be forgotten the way
+//! a call at each write site can.
+
+use ic_stable_structures::btreemap::Iter;
+use ic_stable_structures::{StableBTreeMap, Storable};
+use std::ops::RangeBounds;
+use crate::memory::Memory;
+
+/// Called after a write with the key, the previous value and the new one
+/// (None = removed)
+pub type WriteHook<K, V> = fn(&K, Option<&V>, Option<&V>);
+
+pub struct WatchedMap<K: Storable + Ord + Clone, V: Storable> {
+    map: StableBTreeMap<K, V, Memory>,
+    on_write: WriteHook<K, V>,
+}
+
+impl<K: Storable + Ord + Clone, V: Storable + Clone> WatchedMap<K, V> {
+    pub fn init(memory: Memory, on_write: WriteHook<K, V>) -> Self {
+        WatchedMap { map: StableBTreeMap::init(memory), on_write }
+    }
+
+    // ------------------------------------------------------------------------
+    // Reads
+    // ------------------------------------------------------------------------
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.map.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, Memory> {
+        self.map.iter()
+    }
+
+    pub fn range(&self, key_range: impl RangeBounds<K>) -> Iter<'_, K, V, Memory> {
+        self.map.range(key_range)
+    }
+
+    /// The underlying map, read-only
+    pub fn inner(&self) -> &StableBTreeMap<K, V, Memory> {
+        &self.map
+    }
+
+    // ------------------------------------------------------------------------
+    // Writes
+    // ------------------------------------------------------------------------
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.map.insert(key.clone(), value.clone());
+        (self.on_write)(&key, old.as_ref(), Some(&value));
+        old
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let old = self.map.remove(key);
+        if old.is_some() {
+            (self.on_write)(key, old.as_ref(), None);
+        }
+        old
+    }
+
+    pub fn clear_new(&mut self) {
+        let entries: Vec<(K, V)> = self.map.iter().collect();
+        self.map.clear_new();
+        for (key, value) in entries {
+            (self.on_write)(&key, Some(&value), None);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Shared checks for inbound webhooks: HMAC-SHA256 and Ed25519 signatures,
+//! timestamp replay windows and once-only event IDs. The signature checks are
+//! pure (the caller passes the current time), so they are unit-tested off-chain.
+
+use std::cell::RefCell;
+use crate::clock::time;
+use ic_stable_structures::{
+    memory_manager::MemoryId,
+    StableBTreeMap,
+};
+use crate::memory::{Memory, MEMORY_MANAGER};
+
+/// Default tolerance between the sender's timestamp and ours (seconds)
+pub const DEFAULT_REPLAY_WINDOW_SECS: u64 = 5 * 60;
+/// Event IDs are remembered this long, well past any provider's retry schedule
+const SEEN_EVENT_TTL_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+/// Expired IDs are pruned once the store grows past this
+const MAX_SEEN_EVENTS: u64 = 50_000;
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    // "source|event_id" -> first seen (ns)
+    static SEEN_WEBHOOK_EVENTS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(57)))
+        )
+    );
+}
+
+// ============================================================================
+// Signatures
+// ============================================================================
+
+/// Compare without an early exit, so timing doesn't reveal the matching prefix
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn hmac_sha256(secret: &[u8], message: &[u8]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Check a hex-encoded HMAC-SHA256 of `message`
+pub fn verify_hmac_sha256(secret: &[u8], message: &[u8], signature_hex: &str) -> Result<(), String> {
+    let signature = hex::decode(signature_hex.trim()).map_err(|_| "Invalid signature encoding")?;
+    if constant_time_eq(&hmac_sha256(secret, message), &signature) {
+        Ok(())
+    } else {
+        Err("Invalid signature".to_string())
+    }
+}
+
+/// Check a hex-encoded Ed25519 signature against a hex-encoded public key
+pub fn verify_ed25519(public_key_hex: &str, message: &[u8], signature_hex: &str) -> Result<(), String> {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let key_bytes: [u8; 32] = hex::decode(public_key_hex).ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Invalid public key")?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|_| "Invalid public key")?;
+    let signature: [u8; 64] = hex::decode(signature_hex).ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Invalid signature encoding")?;
+    key.verify(message, &Signature::from_bytes(&signature))
+        .map_err(|_| "Invalid signature".to_string())
+}
+
+// ============================================================================
+// Replay Protection
+// ============================================================================
+
+/// Reject timestamps (Unix seconds) further than `window_secs` from now, in
+/// either direction
+pub fn check_timestamp(signed_at: u64, now_secs: u64, window_secs: u64) -> Result<(), String> {
+    if now_secs.abs_diff(signed_at) > window_secs {
+        return Err("Stale signature".to_string());
+    }
+    Ok(())
+}
+
+/// Verify a `Stripe-Signature` header (`t=<secs>,v1=<hex>[,v1=<hex>...]`):
+/// any v1 signature over `<t>.<body>` must match, within the replay window
+pub fn verify_stripe_signature(secret: &str, header: &str, body: &[u8], now_secs: u64) -> Result<u64, String> {
+    let mut signed_at = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => signed_at = value.parse::<u64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+    let signed_at = signed_at.ok_or("Missing timestamp")?;
+    if signatures.is_empty() {
+        return Err("Missing signature".to_string());
+    }
+    check_timestamp(signed_at, now_secs, DEFAULT_REPLAY_WINDOW_SECS)?;
+
+    let mut payload = format!("{}.", signed_at).into_bytes();
+    payload.extend_from_slice(body);
+    if signatures.iter().any(|s| verify_hmac_sha256(secret.as_bytes(), &payload, s).is_ok()) {
+        Ok(signed_at)
+    } else {
+        Err("Invalid signature".to_string())
+    }
+}
+
+/// Record an event ID from `source`; false if it was already processed, so
+/// retried deliveries are acknowledged without being applied twice
+pub fn claim_event(source: &str, event_id: &str) -> bool {
+    let key = format!("{}|{}", source, event_id);
+    let now = time();
+    SEEN_WEBHOOK_EVENTS.with(|s| {
+        let mut map = s.borrow_mut();
+        if map.contains_key(&key) {
+            return false;
+        }
+        if map.len() >= MAX_SEEN_EVENTS {
+            let expired: Vec<String> = map.iter()
+                .filter(|(_, seen_at)| now.saturating_sub(*seen_at) > SEEN_EVENT_TTL_NS)
+                .map(|(key, _)| key)
+                .collect();
+            for key in expired {
+                map.remove(&key);
+            }
+        }
+        map.insert(key, now);
+        true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4231 test case 2
+    const KEY: &[u8] = b"Jefe";
+    const DATA: &[u8] = b"what do ya want for nothing?";
+    const MAC: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn stripe_header(secret: &str, t: u64, body: &[u8]) -> String {
+        let mut payload = format!("{}.", t).into_bytes();
+        payload.extend_from_slice(body);
+        format!("t={},v1={}", t, hex::encode(hmac_sha256(secret.as_bytes(), &payload)))
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        assert_eq!(hex::encode(hmac_sha256(KEY, DATA)), MAC);
+        assert!(verify_hmac_sha256(KEY, DATA, MAC).is_ok());
+        assert!(verify_hmac_sha256(KEY, b"what do ya want for something?", MAC).is_err());
+        assert!(verify_hmac_sha256(KEY, DATA, "not hex").is_err());
+    }
+
+    #[test]
+    fn constant_time_eq_compares_length_and_bytes() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn ed25519_accepts_only_the_signed_message() {
+        use ed25519_dalek::{Signer, SigningKey};
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let public = hex::encode(signing.verifying_key().to_bytes());
+        let signature = hex::encode(signing.sign(b"1700000000{}").to_bytes());
+        assert!(verify_ed25519(&public, b"1700000000{}", &signature).is_ok());
+        assert!(verify_ed25519(&public, b"1700000001{}", &signature).is_err());
+        assert!(verify_ed25519("00", b"1700000000{}", &signature).is_err());
+    }
+
+    #[test]
+    fn timestamps_outside_the_window_are_stale() {
+        assert!(check_timestamp(NOW - 300, NOW, 300).is_ok());
+        assert!(check_timestamp(NOW + 300, NOW, 300).is_ok());
+        assert!(check_timestamp(NOW - 301, NOW, 300).is_err());
+        assert!(check_timestamp(NOW + 301, NOW, 300).is_err());
+    }
+
+    #[test]
+    fn stripe_signatures_verify_with_any_v1() {
+        let body = br#"{"id":"evt_1"}"#;
+        let header = stripe_header("whsec_test", NOW, body);
+        assert_eq!(verify_stripe_signature("whsec_test", &header, body, NOW), Ok(NOW));
+
+        // During secret rotation Stripe sends one v1 per active secret
+        let rotated = format!("{},v1={}", header.replace("v1=", "v1=00"), &header[header.find("v1=").unwrap() + 3..]);
+        assert!(verify_stripe_signature("whsec_test", &rotated, body, NOW).is_ok());
+
+        assert!(verify_stripe_signature("whsec_other", &header, body, NOW).is_err());
+        assert!(verify_stripe_signature("whsec_test", &header, b"{}", NOW).is_err());
+        assert_eq!(verify_stripe_signature("whsec_test", &header, body, NOW + 301), Err("Stale signature".to_string()));
+        assert!(verify_stripe_signature("whsec_test", "v1=abcd", body, NOW).is_err());
+    }
+}
@@ -2,22 +2,314 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface Account {
+  'owner' : Principal,
+  'subaccount' : [] | [Uint8Array | number[]],
+}
+export interface AccountUsage {
+  'google_errors' : number,
+  'google_calls' : number,
+  'account' : Principal,
+}
+export interface AnalyticsConfig {
+  'sample_percent' : number,
+  'retention_days' : number,
+}
+export type ApiError = { 'InvalidInput' : string } |
+  { 'UpstreamUnavailable' : string } |
+  { 'NotFound' : string } |
+  { 'Unauthorized' : string } |
+  { 'FeatureNotAvailable' : string } |
+  { 'QuotaExceeded' : string };
+export interface ApiKey {
+  'id' : string,
+  'last_used_at' : [] | [bigint],
+  'owner' : Principal,
+  'scopes' : Array<ApiScope>,
+  'name' : string,
+  'created_at' : bigint,
+  'requests_per_hour' : number,
+}
+export type ApiScope = { 'WriteBookings' : null } |
+  { 'ReadAvailabilities' : null } |
+  { 'WriteBusyTimes' : null };
+export interface ApiUsageDay {
+  'day' : bigint,
+  'google_errors' : number,
+  'google_calls' : number,
+}
+export interface ApiVersionInfo { 'min_supported' : number, 'current' : number }
+export interface AttendanceEntry {
+  'guest_name' : string,
+  'checked_in_at' : [] | [bigint],
+  'booking_id' : string,
+  'guest_email' : [] | [string],
+}
+export interface Attendee {
+  'is_caller' : boolean,
+  'guest_name' : string,
+  'booking_id' : [] | [string],
+  'guest_email' : [] | [string],
+}
 export interface Availability {
   'id' : string,
+  'vip' : [] | [VipConfig],
   'timezone' : string,
   'title' : string,
   'updated_at' : bigint,
+  'cancellation_policy' : [] | [CancellationPolicy],
   'owner' : Principal,
+  'locale' : [] | [Locale],
   'description' : string,
   'owner_email' : [] | [string],
   'created_at' : bigint,
-  'busy_times' : [] | [Array<BusyTimeBlock>],
+  'busy_times' : [] | [Array<FreeSlot>],
   'is_favorite' : boolean,
+  'meeting_url' : [] | [string],
+  'seats' : [] | [number],
   'slots' : Array<TimeSlot>,
+  'collaborators' : [] | [Array<Collaborator>],
+  'event_fields' : [] | [Array<EventField>],
   'display_order' : number,
+  'kiosk' : [] | [KioskConfig],
+  'follow_ups' : [] | [Array<FollowUp>],
+  'durations' : [] | [Array<DurationOption>],
+  'busy_privacy' : [] | [BusyPrivacy],
+  'alignment' : [] | [SlotAlignment],
   'owner_name' : [] | [string],
 }
+export interface AvailabilityExport {
+  'json' : string,
+  'vavailability' : string,
+}
+export interface AvailabilityPage {
+  'truncated' : boolean,
+  'availabilities' : Array<Availability>,
+  'next_cursor' : [] | [string],
+}
+export interface AvailabilitySettingsPreview {
+  'timezone' : [] | [string],
+  'slots' : [] | [Array<TimeSlot>],
+  'durations' : [] | [Array<DurationOption>],
+  'alignment' : [] | [SlotAlignment],
+}
+export interface AvailabilityShare {
+  'id' : string,
+  'to' : ShareRecipient,
+  'from' : Principal,
+  'created_at' : bigint,
+  'source_availability_id' : string,
+  'template' : AvailabilityTemplate,
+}
+export interface AvailabilityTemplate {
+  'timezone' : string,
+  'title' : string,
+  'cancellation_policy' : [] | [CancellationPolicy],
+  'locale' : [] | [Locale],
+  'description' : string,
+  'seats' : [] | [number],
+  'slots' : Array<TimeSlot>,
+  'event_fields' : [] | [Array<EventField>],
+  'follow_ups' : [] | [Array<FollowUp>],
+  'durations' : [] | [Array<DurationOption>],
+  'busy_privacy' : [] | [BusyPrivacy],
+  'alignment' : [] | [SlotAlignment],
+}
+export interface BillingConfig {
+  'grace_period_days' : number,
+  'ledger_canister' : [] | [Principal],
+  'monthly_price' : bigint,
+}
+export type BookAccount = { 'Custody' : null } |
+  { 'Owner' : Principal };
+export interface BookRoundRequest {
+  'token' : string,
+  'start_time' : bigint,
+  'availability_id' : string,
+}
+export interface BookWithLinkRequest {
+  'token' : string,
+  'guest_name' : string,
+  'end_time' : bigint,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'guest_email' : [] | [string],
+}
+export interface Booking {
+  'id' : string,
+  'status' : BookingStatus,
+  'updated_at' : bigint,
+  'payment_hold' : [] | [PaymentHold],
+  'cancelled_at' : [] | [bigint],
+  'cancelled_by' : [] | [Principal],
+  'owner' : Principal,
+  'guest_name' : string,
+  'created_at' : bigint,
+  'end_time' : bigint,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'guest' : Principal,
+  'field_answers' : [] | [Array<FieldAnswer>],
+  'availability_id' : string,
+  'guest_email' : [] | [string],
+  'payment' : [] | [BookingPayment],
+  'session_link' : [] | [string],
+  'refund' : [] | [BookingRefund],
+}
+export interface BookingDiscount { 'code' : string, 'percent' : number }
+export type BookingEvent = { 'Refunded' : BookingRefund } |
+  { 'HoldReleased' : null } |
+  { 'Cancelled' : { 'by' : Principal } } |
+  { 'Created' : Booking } |
+  { 'PaymentCaptured' : BookingPayment };
+export interface BookingEventRecord {
+  'at' : bigint,
+  'seq' : bigint,
+  'event' : BookingEvent,
+  'booking_id' : string,
+}
+export interface BookingPage {
+  'bookings' : Array<Booking>,
+  'truncated' : boolean,
+  'next_cursor' : [] | [string],
+}
+export interface BookingPayment {
+  'tax' : [] | [TaxBreakdown],
+  'block_index' : [] | [bigint],
+  'stripe_payment_id' : [] | [string],
+  'discount_percent' : [] | [number],
+  'paid_at' : bigint,
+  'ledger_canister' : Principal,
+  'amount' : bigint,
+}
+export interface BookingRefund {
+  'block_index' : [] | [bigint],
+  'error' : [] | [string],
+  'refunded_at' : bigint,
+  'amount' : bigint,
+}
+export interface BookingSchema {
+  'timezone' : string,
+  'title' : string,
+  'start_step_minutes' : number,
+  'cancellation_policy' : [] | [CancellationPolicy],
+  'locale' : Locale,
+  'lead_minutes' : number,
+  'min_duration_minutes' : number,
+  'fields' : Array<SchemaField>,
+  'availability_id' : string,
+  'prices' : Array<SchemaPrice>,
+  'durations' : Array<DurationOption>,
+  'max_duration_minutes' : number,
+}
+export type BookingStatus = { 'Confirmed' : null } |
+  { 'Cancelled' : null } |
+  { 'Pending' : null };
+export type BreakerState = { 'Open' : null } |
+  { 'Closed' : null } |
+  { 'HalfOpen' : null };
+export interface BreakerStatus {
+  'provider' : Provider,
+  'opened_at' : [] | [bigint],
+  'state' : BreakerState,
+  'consecutive_failures' : number,
+}
+export type BusyPrivacy = { 'Exact' : null } |
+  { 'FreeSlotsOnly' : null } |
+  { 'Quantized' : null };
 export interface BusyTimeBlock { 'end_time' : bigint, 'start_time' : bigint }
+export interface CalendarConflict {
+  'detected_at' : bigint,
+  'resolution' : [] | [ConflictResolution],
+  'google_start' : bigint,
+  'booking_end' : bigint,
+  'event_id' : string,
+  'booking_start' : bigint,
+  'booking_id' : string,
+  'google_end' : bigint,
+  'resolved_at' : [] | [bigint],
+}
+export interface CalendarConnection {
+  'connected' : boolean,
+  'revocation' : [] | [Revocation],
+}
+export interface CalendlyImportReport {
+  'bookings' : Array<ImportedBooking>,
+  'availabilities' : Array<ImportedAvailability>,
+  'dry_run' : boolean,
+}
+export interface CancellationPolicy {
+  'partial_refund_percent' : number,
+  'free_until_hours' : number,
+  'partial_until_hours' : number,
+}
+export interface CancellationQuote {
+  'refund_percent' : number,
+  'refund_amount' : bigint,
+  'policy' : [] | [CancellationPolicy],
+}
+export interface Candidate {
+  'updated_at' : bigint,
+  'token' : string,
+  'owner' : Principal,
+  'pipeline_id' : string,
+  'name' : string,
+  'history' : Array<RoundBooking>,
+  'created_at' : bigint,
+  'email' : [] | [string],
+  'stage' : CandidateStage,
+  'round' : number,
+}
+export type CandidateStage = { 'Withdrawn' : null } |
+  { 'Booked' : null } |
+  { 'AwaitingBooking' : null } |
+  { 'Completed' : null };
+export interface Collaborator {
+  'principal' : Principal,
+  'role' : CollaboratorRole,
+}
+export type CollaboratorRole = { 'Viewer' : null } |
+  { 'Approver' : null } |
+  { 'Editor' : null };
+export interface CommonFreeSlot {
+  'outside_reasonable_hours' : Array<string>,
+  'end_time' : bigint,
+  'score' : [] | [number],
+  'start_time' : bigint,
+}
+export type ComponentHealth = { 'Down' : null } |
+  { 'Operational' : null } |
+  { 'Maintenance' : null } |
+  { 'Degraded' : null };
+export interface ComponentStatus {
+  'component' : string,
+  'detail' : string,
+  'health' : ComponentHealth,
+}
+export type ConflictResolution = { 'RestoreWeeekaly' : null } |
+  { 'AcceptGoogle' : null };
+export interface CorruptRecord {
+  'id' : string,
+  'type_name' : string,
+  'error' : string,
+  'bytes' : Uint8Array | number[],
+  'first_seen_at' : bigint,
+}
+export interface CorruptRecordsPage {
+  'records' : Array<CorruptRecord>,
+  'truncated' : boolean,
+  'next_cursor' : [] | [string],
+}
+export interface CounterSet {
+  'bookings' : bigint,
+  'cancelled_bookings' : bigint,
+  'availabilities' : bigint,
+}
+export interface CreateApiKeyRequest {
+  'scopes' : Array<ApiScope>,
+  'name' : string,
+  'requests_per_hour' : [] | [number],
+}
 export interface CreateAvailabilityRequest {
   'timezone' : string,
   'title' : string,
@@ -27,6 +319,15 @@ export interface CreateAvailabilityRequest {
   'slots' : Array<TimeSlot>,
   'owner_name' : [] | [string],
 }
+export interface CreateBookingRequest {
+  'guest_name' : string,
+  'end_time' : bigint,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'field_answers' : [] | [Array<FieldAnswer>],
+  'availability_id' : string,
+  'guest_email' : [] | [string],
+}
 export interface CreateEventRequest {
   'timezone' : string,
   'description' : [] | [string],
@@ -34,19 +335,122 @@ export interface CreateEventRequest {
   'summary' : string,
   'start_time' : string,
   'attendees' : [] | [Array<string>],
+  'booking_id' : [] | [string],
   'conference_data' : [] | [boolean],
   'location' : [] | [string],
 }
+export interface CreateIntegrationHookRequest {
+  'url' : string,
+  'field_mappings' : Array<FieldMapping>,
+  'events' : Array<HookEvent>,
+}
+export interface CreatePipelineRequest {
+  'title' : string,
+  'rounds' : Array<InterviewRound>,
+}
+export interface CreateSessionLinkRequest {
+  'title' : string,
+  'duration_minutes' : number,
+  'availability_id' : string,
+  'price' : bigint,
+  'expires_at' : [] | [bigint],
+}
+export interface CreatedApiKey { 'key' : ApiKey, 'secret' : string }
+export interface DelegateAction {
+  'at' : bigint,
+  'action' : string,
+  'actor' : Principal,
+  'trace_id' : [] | [string],
+}
+export interface DelegateAuditPage {
+  'actions' : Array<DelegateAction>,
+  'next_cursor' : [] | [string],
+}
 export interface Delegation {
   'pubkey' : Uint8Array | number[],
   'targets' : [] | [Array<Principal>],
   'expiration' : bigint,
 }
+export type DelegationScope = { 'ManageAvailabilities' : null } |
+  { 'ManageBookings' : null };
+export interface DeliveryRecord {
+  'attempted_at' : bigint,
+  'status' : DeliveryStatus,
+  'detail' : string,
+  'event' : HookEvent,
+  'target' : [] | [string],
+  'provider_message_id' : [] | [string],
+  'channel' : NotificationChannel,
+}
+export type DeliveryStatus = { 'Failed' : null } |
+  { 'Delivered' : null };
+export interface DiscordConfig { 'public_key' : [] | [string] }
+export interface DurationOption {
+  'buffer_before_minutes' : number,
+  'minutes' : number,
+  'buffer_after_minutes' : number,
+}
+export interface DurationPrice { 'duration_minutes' : number, 'price' : bigint }
+export interface EarningsBalance {
+  'balance' : bigint,
+  'ledger_canister' : Principal,
+}
+export type EntryKind = { 'BookingPayment' : null } |
+  { 'Reversal' : null } |
+  { 'Withdrawal' : null } |
+  { 'BookingRefund' : null };
+export interface EntryStats {
+  'largest' : Array<LargeRecord>,
+  'entries' : bigint,
+  'encoded_bytes' : bigint,
+}
+export interface ErrorCatalogEntry {
+  'code' : string,
+  'description' : string,
+  'number' : number,
+}
+export interface EventCount {
+  'day' : bigint,
+  'name' : string,
+  'count' : bigint,
+  'estimated_total' : bigint,
+}
+export interface EventField {
+  'key' : string,
+  'value' : [] | [string],
+  'label' : string,
+}
+export interface EventProp { 'key' : string, 'value' : PropValue }
 export interface ExchangeCodeRequest {
   'code_verifier' : string,
   'redirect_uri' : string,
   'code' : string,
 }
+export interface FailedJobsPage {
+  'jobs' : Array<OutboxJob>,
+  'next_cursor' : [] | [bigint],
+}
+export type Feature = { 'Payments' : null } |
+  { 'Webhooks' : null } |
+  { 'MultipleCalendars' : null } |
+  { 'Teams' : null };
+export interface FieldAnswer { 'key' : string, 'value' : string }
+export interface FieldMapping { 'key' : string, 'field' : string }
+export type FieldType = { 'Email' : null } |
+  { 'Minutes' : null } |
+  { 'Text' : null } |
+  { 'UnixSeconds' : null };
+export interface FollowUp { 'action' : FollowUpAction, 'delay_hours' : number }
+export type FollowUpAction = { 'Email' : RenderedTemplate } |
+  { 'Webhook' : { 'url' : string } };
+export interface FreeSlot { 'end_time' : bigint, 'start_time' : bigint }
+export interface FreeSlotSnapshot {
+  'token' : string,
+  'guest_timezone' : [] | [string],
+  'slots' : Array<FreeSlot>,
+  'availability_id' : string,
+  'expires_at' : bigint,
+}
 export interface GetDelegationRequest {
   'expire_at' : bigint,
   'provider' : string,
@@ -58,6 +462,201 @@ export interface GetDelegationResponse {
   'signed_delegation' : SignedDelegation,
   'user_canister_pubkey' : Uint8Array | number[],
 }
+export interface GroupBookingRequest {
+  'guest_name' : string,
+  'end_time' : bigint,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'resource_ids' : Array<string>,
+  'field_answers' : [] | [Array<GroupFieldAnswers>],
+  'availability_ids' : Array<string>,
+  'guest_email' : [] | [string],
+}
+export interface GroupFieldAnswers {
+  'answers' : Array<FieldAnswer>,
+  'availability_id' : string,
+}
+export type HookEvent = { 'BookingCreated' : null } |
+  { 'BookingCancelled' : null };
+export interface HttpHeader { 'value' : string, 'name' : string }
+export interface HttpRequest {
+  'url' : string,
+  'method' : string,
+  'body' : Uint8Array | number[],
+  'headers' : Array<[string, string]>,
+}
+export interface HttpResponse {
+  'body' : Uint8Array | number[],
+  'headers' : Array<[string, string]>,
+  'upgrade' : [] | [boolean],
+  'status_code' : number,
+}
+export interface HttpResponse_1 {
+  'status' : bigint,
+  'body' : Uint8Array | number[],
+  'headers' : Array<HttpHeader>,
+}
+export interface ImportedAvailability {
+  'timezone' : string,
+  'title' : string,
+  'duration_minutes' : number,
+  'slots' : Array<TimeSlot>,
+  'availability_id' : [] | [string],
+}
+export interface ImportedBooking {
+  'title' : string,
+  'skipped_reason' : [] | [string],
+  'end_time' : bigint,
+  'start_time' : bigint,
+  'availability_title' : [] | [string],
+  'booking_id' : [] | [string],
+}
+export interface IntegrationHook {
+  'id' : string,
+  'url' : string,
+  'last_delivery_at' : [] | [bigint],
+  'owner' : Principal,
+  'last_status' : [] | [string],
+  'created_at' : bigint,
+  'field_mappings' : Array<FieldMapping>,
+  'events' : Array<HookEvent>,
+}
+export interface InterviewPipeline {
+  'id' : string,
+  'title' : string,
+  'owner' : Principal,
+  'created_at' : bigint,
+  'rounds' : Array<InterviewRound>,
+}
+export interface InterviewRound {
+  'duration_minutes' : number,
+  'name' : string,
+  'pool' : Array<string>,
+}
+export type JobStatus = { 'DeadLettered' : null } |
+  { 'Pending' : null };
+export interface JournalEntry {
+  'id' : bigint,
+  'kind' : EntryKind,
+  'reference' : string,
+  'created_at' : bigint,
+  'credit' : BookAccount,
+  'ledger_canister' : Principal,
+  'amount' : bigint,
+  'debit' : BookAccount,
+}
+export interface KioskConfig {
+  'duration_minutes' : number,
+  'lead_minutes' : number,
+  'max_bookings_per_device_per_hour' : number,
+  'devices' : Array<Principal>,
+}
+export interface LargeRecord { 'key' : string, 'bytes' : bigint }
+export interface LegalHold {
+  'member' : Principal,
+  'placed_at' : bigint,
+  'placed_by' : Principal,
+  'tenant_id' : string,
+  'lifted_at' : [] | [bigint],
+  'lifted_by' : [] | [Principal],
+  'reason' : string,
+}
+export interface LinkStats {
+  'last_opened_at' : [] | [bigint],
+  'open_count' : bigint,
+  'first_opened_at' : [] | [bigint],
+}
+export type Locale = { 'Ar' : null } |
+  { 'De' : null } |
+  { 'En' : null } |
+  { 'Es' : null } |
+  { 'He' : null };
+export interface MaintenanceJob {
+  'id' : bigint,
+  'last_error' : [] | [string],
+  'status' : MaintenanceStatus,
+  'updated_at' : bigint,
+  'task' : MaintenanceTask,
+  'created_at' : bigint,
+  'chunks' : bigint,
+  'processed' : bigint,
+}
+export type MaintenanceStatus = { 'Queued' : null } |
+  { 'Failed' : null } |
+  { 'Running' : null } |
+  { 'Cancelled' : null } |
+  { 'Completed' : null };
+export type MaintenanceTask = { 'TokenRekey' : null } |
+  { 'IndexMigration' : null } |
+  { 'IndexRebuild' : null };
+export interface ManagementDelegation {
+  'owner' : Principal,
+  'scopes' : Array<DelegationScope>,
+  'delegate' : Principal,
+  'granted_at' : bigint,
+}
+export interface MatrixNotifier {
+  'room_id' : string,
+  'last_delivery_at' : [] | [bigint],
+  'last_status' : [] | [string],
+  'created_at' : bigint,
+  'events' : Array<HookEvent>,
+  'access_token' : string,
+  'homeserver' : string,
+}
+export interface MeetingSuggestion {
+  'reasons' : Array<string>,
+  'end_time' : bigint,
+  'score' : number,
+  'start_time' : bigint,
+}
+export interface MemberLoad {
+  'utilization_percent' : [] | [number],
+  'principal' : Principal,
+  'offered_minutes' : bigint,
+  'booked_minutes' : bigint,
+  'availability_count' : number,
+}
+export type MigrationPhase = { 'Done' : null } |
+  { 'Sessions' : null } |
+  { 'SearchIndices' : null } |
+  { 'NotStarted' : null };
+export interface MigrationStatus {
+  'copied_sessions' : bigint,
+  'indexed_availabilities' : bigint,
+  'availability_cursor' : [] | [string],
+  'phase' : MigrationPhase,
+  'completed_at' : [] | [bigint],
+  'started_at' : [] | [bigint],
+  'session_cursor' : [] | [Uint8Array | number[]],
+}
+export interface MovedRecords { 'count' : number, 'store' : string }
+export interface MyDataExport {
+  'region' : [] | [RegionDeclaration],
+  'principal' : Principal,
+  'bookings' : Array<Booking>,
+  'truncated' : boolean,
+  'exported_at' : bigint,
+  'availabilities' : Array<Availability>,
+  'terms_acceptances' : Array<TermsAcceptance>,
+}
+export interface NoShowStats {
+  'no_shows' : bigint,
+  'attended' : bigint,
+  'availability_id' : string,
+  'no_show_rate_percent' : number,
+  'past_bookings' : bigint,
+}
+export type NotificationChannel = { 'Matrix' : null } |
+  { 'IntegrationHook' : null };
+export interface NotificationTemplate {
+  'updated_at' : bigint,
+  'updated_by' : Principal,
+  'subject' : string,
+  'body' : string,
+  'kind' : TemplateKind,
+  'scope' : TemplateScope,
+}
 export interface OAuthProvider {
   'response_type' : string,
   'authorization_url' : string,
@@ -66,6 +665,120 @@ export interface OAuthProvider {
   'token_url' : string,
   'client_id' : string,
 }
+export interface OneTimeLink {
+  'token' : string,
+  'owner' : Principal,
+  'used_at' : [] | [bigint],
+  'used_by' : [] | [Principal],
+  'created_at' : bigint,
+  'availability_id' : string,
+  'booking_id' : [] | [string],
+}
+export interface OrgBusyBlock {
+  'id' : string,
+  'title' : string,
+  'created_at' : bigint,
+  'created_by' : Principal,
+  'end_time' : bigint,
+  'start_time' : bigint,
+}
+export interface OrgBusyBlockRequest {
+  'title' : string,
+  'end_time' : bigint,
+  'start_time' : bigint,
+}
+export interface OrgDefaults {
+  'updated_at' : bigint,
+  'updated_by' : Principal,
+  'cancellation_policy' : [] | [CancellationPolicy],
+  'locale' : [] | [Locale],
+  'enforced' : Array<OrgSetting>,
+  'tenant_id' : string,
+  'follow_ups' : [] | [Array<FollowUp>],
+  'durations' : [] | [Array<DurationOption>],
+  'busy_privacy' : [] | [BusyPrivacy],
+  'alignment' : [] | [SlotAlignment],
+}
+export interface OrgLoadReport {
+  'range_end' : bigint,
+  'members' : Array<MemberLoad>,
+  'tenant_id' : string,
+  'total_members' : number,
+  'next_cursor' : [] | [string],
+  'range_start' : bigint,
+  'next_offset' : [] | [number],
+}
+export type OrgSetting = { 'BusyPrivacy' : null } |
+  { 'FollowUps' : null } |
+  { 'Durations' : null } |
+  { 'Alignment' : null } |
+  { 'Locale' : null } |
+  { 'CancellationPolicy' : null };
+export interface OriginConfig {
+  'derivation_origin' : [] | [string],
+  'brand_name' : string,
+  'updated_at' : bigint,
+  'support_email' : [] | [string],
+  'origin' : string,
+  'tenant_id' : [] | [string],
+  'logo_url' : [] | [string],
+  'default_provider' : string,
+  'allowed_features' : Array<Feature>,
+  'primary_color' : [] | [string],
+}
+export interface OrphanedTokenReport {
+  'legacy_entries' : bigint,
+  'stale_fingerprints' : bigint,
+  'shadowed_tokens' : Array<Principal>,
+  'dangling_links' : Array<Principal>,
+}
+export interface OutboxJob {
+  'id' : bigint,
+  'last_error' : [] | [string],
+  'status' : JobStatus,
+  'updated_at' : bigint,
+  'next_attempt_at' : bigint,
+  'attempts' : number,
+  'effect' : SideEffect,
+  'created_at' : bigint,
+  'trace_id' : [] | [string],
+}
+export interface ParkedStripeEvent {
+  'updated_at' : bigint,
+  'attempts' : number,
+  'error' : string,
+  'parked_at' : bigint,
+  'event_id' : string,
+  'event_type' : string,
+  'payload' : string,
+}
+export interface PaymentHold {
+  'method' : PaymentMethod,
+  'currency' : string,
+  'amount' : bigint,
+}
+export type PaymentMethod = { 'Stripe' : null } |
+  { 'Ledger' : null };
+export interface PlanInfo {
+  'features' : Array<Feature>,
+  'source' : PlanSource,
+  'tier' : PlanTier,
+  'expires_at' : [] | [bigint],
+}
+export type PlanSource = { 'Icrc' : null } |
+  { 'Promo' : null } |
+  { 'Default' : null } |
+  { 'Admin' : null };
+export type PlanTier = { 'Pro' : null } |
+  { 'Free' : null };
+export interface PlatformStats {
+  'week_start' : bigint,
+  'generated_at' : bigint,
+  'total_availabilities' : bigint,
+  'total_bookings' : bigint,
+  'active_orgs' : bigint,
+  'bookings_this_week' : bigint,
+}
 export interface PrepareDelegationRequest {
   'provider' : string,
   'origin' : string,
@@ -75,23 +788,614 @@ export interface PrepareDelegationRequest {
   'id_token' : string,
 }
 export interface PrepareDelegationResponse { 'expire_at' : bigint }
+export interface PrincipalMove {
+  'to' : Principal,
+  'bookings' : number,
+  'records' : [] | [Array<MovedRecords>],
+  'from' : Principal,
+  'availabilities' : number,
+  'moved_at' : bigint,
+}
+export interface PromoCode {
+  'reward' : PromoReward,
+  'max_uses' : number,
+  'code' : string,
+  'uses' : number,
+  'created_at' : bigint,
+  'created_by' : Principal,
+  'expires_at' : [] | [bigint],
+}
+export type PromoReward = { 'PlanUpgrade' : { 'days' : number } } |
+  { 'BookingDiscount' : { 'percent' : number } };
+export type PropValue = { 'Bool' : boolean } |
+  { 'Text' : string } |
+  { 'Number' : bigint };
+export type Provider = { 'Calendly' : null } |
+  { 'Google' : null };
+export interface QuotaLimits {
+  'max_webhook_subscriptions' : number,
+  'max_google_calls_per_day' : [] | [number],
+  'max_bookings_per_day' : number,
+  'max_availabilities' : number,
+  'max_outcalls_per_hour' : number,
+}
+export interface QuotaStatus {
+  'google_calls_today' : number,
+  'bookings_today' : number,
+  'availabilities' : number,
+  'outcalls_this_hour' : number,
+  'is_override' : boolean,
+  'limits' : QuotaLimits,
+}
+export interface ReadCacheStatus {
+  'last_error' : [] | [string],
+  'pending' : bigint,
+  'canister' : [] | [Principal],
+  'last_pushed_at' : bigint,
+}
+export interface RebookInfo {
+  'duration_minutes' : number,
+  'guest_name' : string,
+  'previous_booking_id' : string,
+  'guest_timezone' : [] | [string],
+  'field_answers' : Array<FieldAnswer>,
+  'availability_id' : string,
+  'guest_email' : [] | [string],
+}
+export type RebuildPhase = { 'ResetCounters' : null } |
+  { 'Bookings' : null } |
+  { 'Done' : null } |
+  { 'Availabilities' : null } |
+  { 'AvailabilityLists' : null } |
+  { 'BookingLists' : null } |
+  { 'NotStarted' : null };
+export interface RebuildStatus {
+  'availabilities_scanned' : bigint,
+  'cursor' : [] | [string],
+  'bookings_total' : bigint,
+  'bookings_scanned' : bigint,
+  'entries_fixed' : bigint,
+  'phase' : RebuildPhase,
+  'completed_at' : [] | [bigint],
+  'started_at' : [] | [bigint],
+  'availabilities_total' : bigint,
+}
+export interface Receipt {
+  'buyer_name' : string,
+  'net_amount' : bigint,
+  'tax_rate_bps' : number,
+  'issued_at' : bigint,
+  'total_amount' : bigint,
+  'tax_amount' : bigint,
+  'block_index' : [] | [bigint],
+  'description' : string,
+  'seller' : [] | [TaxProfile],
+  'end_time' : bigint,
+  'buyer_email' : [] | [string],
+  'start_time' : bigint,
+  'message' : [] | [RenderedTemplate],
+  'ledger_canister' : Principal,
+  'booking_id' : string,
+}
+export interface RedeemSessionLinkRequest {
+  'token' : string,
+  'duration_minutes' : [] | [number],
+  'guest_name' : string,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'field_answers' : [] | [Array<FieldAnswer>],
+  'guest_email' : [] | [string],
+}
+export interface ReferralStats {
+  'code' : string,
+  'reward_days_earned' : number,
+  'referred_count' : number,
+}
 export interface RefreshTokenRequest { 'refresh_token' : string }
+export interface RegionDeclaration {
+  'region' : string,
+  'is_adult' : boolean,
+  'declared_at' : bigint,
+}
+export interface RegionPolicy {
+  'updated_at' : bigint,
+  'updated_by' : Principal,
+  'require_adult' : boolean,
+  'unsupported_regions' : Array<string>,
+  'tenant_id' : string,
+  'gated_features' : Array<Feature>,
+  'require_region' : boolean,
+}
+export interface RenderedTemplate { 'subject' : string, 'body' : string }
+export interface Resource {
+  'id' : string,
+  'timezone' : string,
+  'updated_at' : bigint,
+  'open_hours' : Array<TimeSlot>,
+  'kind' : ResourceKind,
+  'name' : string,
+  'created_at' : bigint,
+  'tenant_id' : string,
+  'capacity' : number,
+}
+export type ResourceKind = { 'Room' : null } |
+  { 'Other' : null } |
+  { 'Equipment' : null };
+export interface ResourceRequest {
+  'timezone' : string,
+  'open_hours' : Array<TimeSlot>,
+  'kind' : ResourceKind,
+  'name' : string,
+  'capacity' : number,
+}
 export type Result = { 'Ok' : Availability } |
   { 'Err' : string };
-export type Result_1 = { 'Ok' : string } |
+export type Result_1 = { 'Ok' : TermsAcceptance } |
+  { 'Err' : string };
+export type Result_10 = { 'Ok' : CreatedApiKey } |
+  { 'Err' : string };
+export type Result_11 = { 'Ok' : string } |
+  { 'Err' : string };
+export type Result_12 = { 'Ok' : IntegrationHook } |
+  { 'Err' : string };
+export type Result_13 = { 'Ok' : InterviewPipeline } |
+  { 'Err' : string };
+export type Result_14 = { 'Ok' : OneTimeLink } |
+  { 'Err' : string };
+export type Result_15 = { 'Ok' : PromoCode } |
+  { 'Err' : string };
+export type Result_16 = { 'Ok' : Resource } |
   { 'Err' : string };
-export type Result_2 = { 'Ok' : null } |
+export type Result_17 = { 'Ok' : SessionLink } |
   { 'Err' : string };
-export type Result_3 = { 'Ok' : TokenResponse } |
+export type Result_18 = { 'Ok' : Team } |
   { 'Err' : string };
-export type Result_4 = { 'Ok' : GetDelegationResponse } |
+export type Result_19 = { 'Ok' : Tenant } |
   { 'Err' : string };
-export type Result_5 = { 'Ok' : PrepareDelegationResponse } |
+export type Result_2 = { 'Ok' : Candidate } |
   { 'Err' : string };
+export type Result_20 = { 'Ok' : TokenResponse } |
+  { 'Err' : string };
+export type Result_21 = { 'Ok' : AvailabilityExport } |
+  { 'Err' : string };
+export type Result_22 = { 'Ok' : AnalyticsConfig } |
+  { 'Err' : string };
+export type Result_23 = { 'Ok' : Array<AccountUsage> } |
+  { 'Err' : string };
+export type Result_24 = { 'Ok' : Array<Attendee> } |
+  { 'Err' : string };
+export type Result_25 = { 'Ok' : Array<BookingEventRecord> } |
+  { 'Err' : string };
+export type Result_26 = { 'Ok' : Receipt } |
+  { 'Err' : string };
+export type Result_27 = { 'Ok' : BookingSchema } |
+  { 'Err' : string };
+export type Result_28 = { 'Ok' : CancellationQuote } |
+  { 'Err' : string };
+export type Result_29 = { 'Ok' : Array<CommonFreeSlot> } |
+  { 'Err' : string };
+export type Result_3 = { 'Ok' : OrgBusyBlock } |
+  { 'Err' : string };
+export type Result_30 = { 'Ok' : DelegateAuditPage } |
+  { 'Err' : string };
+export type Result_31 = { 'Ok' : GetDelegationResponse } |
+  { 'Err' : string };
+export type Result_32 = { 'Ok' : Array<EventCount> } |
+  { 'Err' : string };
+export type Result_33 = { 'Ok' : Array<FreeSlot> } |
+  { 'Err' : string };
+export type Result_34 = { 'Ok' : MigrationStatus } |
+  { 'Err' : string };
+export type Result_35 = { 'Ok' : Array<RoundSlot> } |
+  { 'Err' : string };
+export type Result_36 = { 'Ok' : LinkStats } |
+  { 'Err' : string };
+export type Result_37 = { 'Ok' : NoShowStats } |
+  { 'Err' : string };
+export type Result_38 = { 'Ok' : Array<DeliveryRecord> } |
+  { 'Err' : string };
+export type Result_39 = { 'Ok' : CounterSet } |
+  { 'Err' : string };
+export type Result_4 = { 'Ok' : Booking } |
+  { 'Err' : string };
+export type Result_40 = { 'Ok' : OrgLoadReport } |
+  { 'Err' : string };
+export type Result_41 = { 'Ok' : ReadCacheStatus } |
+  { 'Err' : string };
+export type Result_42 = { 'Ok' : RebookInfo } |
+  { 'Err' : string };
+export type Result_43 = { 'Ok' : RebuildStatus } |
+  { 'Err' : string };
+export type Result_44 = { 'Ok' : Array<AttendanceEntry> } |
+  { 'Err' : string };
+export type Result_45 = { 'Ok' : [] | [SlotExperiment] } |
+  { 'Err' : string };
+export type Result_46 = { 'Ok' : StorageReport } |
+  { 'Err' : string };
+export type Result_47 = { 'Ok' : SyncSchedulerStatus } |
+  { 'Err' : string };
+export type Result_48 = { 'Ok' : Array<BreakerStatus> } |
+  { 'Err' : string };
+export type Result_49 = { 'Ok' : [] | [ManagementDelegation] } |
+  { 'Err' : string };
+export type Result_5 = { 'Ok' : Array<Booking> } |
+  { 'Err' : string };
+export type Result_50 = { 'Ok' : CalendlyImportReport } |
+  { 'Err' : string };
+export type Result_51 = { 'Ok' : LegalHold } |
+  { 'Err' : string };
+export type Result_52 = { 'Ok' : Array<Availability> } |
+  { 'Err' : string };
+export type Result_53 = { 'Ok' : BookingPage } |
+  { 'Err' : string };
+export type Result_54 = { 'Ok' : CorruptRecordsPage } |
+  { 'Err' : string };
+export type Result_55 = { 'Ok' : FailedJobsPage } |
+  { 'Err' : string };
+export type Result_56 = { 'Ok' : Array<Candidate> } |
+  { 'Err' : string };
+export type Result_57 = { 'Ok' : Array<LegalHold> } |
+  { 'Err' : string };
+export type Result_58 = { 'Ok' : Array<MaintenanceJob> } |
+  { 'Err' : string };
+export type Result_59 = { 'Ok' : Array<NotificationTemplate> } |
+  { 'Err' : string };
+export type Result_6 = { 'Ok' : MaintenanceJob } |
+  { 'Err' : string };
+export type Result_60 = { 'Ok' : Array<[string, string]> } |
+  { 'Err' : string };
+export type Result_61 = { 'Ok' : Array<OriginConfig> } |
+  { 'Err' : string };
+export type Result_62 = { 'Ok' : Array<PrincipalMove> } |
+  { 'Err' : string };
+export type Result_63 = { 'Ok' : Array<PromoCode> } |
+  { 'Err' : string };
+export type Result_64 = { 'Ok' : Array<ParkedStripeEvent> } |
+  { 'Err' : string };
+export type Result_65 = { 'Ok' : AvailabilityPage } |
+  { 'Err' : string };
+export type Result_66 = { 'Ok' : Array<TenantMember> } |
+  { 'Err' : string };
+export type Result_67 = { 'Ok' : Array<Tenant> } |
+  { 'Err' : string };
+export type Result_68 = { 'Ok' : PrincipalMove } |
+  { 'Err' : string };
+export type Result_69 = { 'Ok' : PrepareDelegationResponse } |
+  { 'Err' : string };
+export type Result_7 = { 'Ok' : null } |
+  { 'Err' : string };
+export type Result_70 = { 'Ok' : TermsConfig } |
+  { 'Err' : string };
+export type Result_71 = { 'Ok' : Array<string> } |
+  { 'Err' : string };
+export type Result_72 = { 'Ok' : PromoReward } |
+  { 'Err' : string };
+export type Result_73 = { 'Ok' : number } |
+  { 'Err' : string };
+export type Result_74 = { 'Ok' : CalendarConflict } |
+  { 'Err' : string };
+export type Result_75 = { 'Ok' : TrashEntry } |
+  { 'Err' : string };
+export type Result_76 = { 'Ok' : OutboxJob } |
+  { 'Err' : string };
+export type Result_77 = { 'Ok' : SelfTestReport } |
+  { 'Err' : string };
+export type Result_78 = { 'Ok' : Array<SearchResult> } |
+  { 'Err' : string };
+export type Result_79 = { 'Ok' : MatrixNotifier } |
+  { 'Err' : string };
+export type Result_8 = { 'Ok' : AttendanceEntry } |
+  { 'Err' : string };
+export type Result_80 = { 'Ok' : RegionDeclaration } |
+  { 'Err' : string };
+export type Result_81 = { 'Ok' : TaxProfile } |
+  { 'Err' : string };
+export type Result_82 = { 'Ok' : NotificationTemplate } |
+  { 'Err' : string };
+export type Result_83 = { 'Ok' : OrgDefaults } |
+  { 'Err' : string };
+export type Result_84 = { 'Ok' : OriginConfig } |
+  { 'Err' : string };
+export type Result_85 = { 'Ok' : RegionPolicy } |
+  { 'Err' : string };
+export type Result_86 = { 'Ok' : AvailabilityShare } |
+  { 'Err' : string };
+export type Result_87 = { 'Ok' : FreeSlotSnapshot } |
+  { 'Err' : string };
+export type Result_88 = { 'Ok' : SlotExperiment } |
+  { 'Err' : string };
+export type Result_89 = { 'Ok' : Subscription } |
+  { 'Err' : string };
+export type Result_9 = { 'Ok' : OrphanedTokenReport } |
+  { 'Err' : string };
+export type Result_90 = { 'Ok' : Array<MeetingSuggestion> } |
+  { 'Err' : string };
+export type Result_91 = { 'Ok' : SweepReport } |
+  { 'Err' : string };
+export type Result_92 = { 'Ok' : boolean } |
+  { 'Err' : string };
+export type Result_93 = { 'Ok' : Booking } |
+  { 'Err' : ApiError };
+export type Result_94 = { 'Ok' : Availability } |
+  { 'Err' : ApiError };
+export type Result_95 = { 'Ok' : Array<FreeSlot> } |
+  { 'Err' : ApiError };
+export type Result_96 = { 'Ok' : UpgradeVerification } |
+  { 'Err' : string };
+export type Result_97 = { 'Ok' : bigint } |
+  { 'Err' : string };
+export interface Revocation {
+  'revoked_at' : bigint,
+  'paused_availabilities' : Array<string>,
+}
+export interface RoundBooking {
+  'end_time' : bigint,
+  'start_time' : bigint,
+  'availability_id' : string,
+  'booking_id' : string,
+  'round' : number,
+}
+export interface RoundSlot {
+  'end_time' : bigint,
+  'start_time' : bigint,
+  'availability_id' : string,
+}
+export interface SchemaField {
+  'field_type' : FieldType,
+  'name' : string,
+  'description' : string,
+  'max_length' : [] | [number],
+  'required' : boolean,
+}
+export interface SchemaPrice {
+  'duration_minutes' : number,
+  'ledger_canister' : Principal,
+  'amount' : bigint,
+}
+export interface SearchResult {
+  'id' : string,
+  'title' : string,
+  'kind' : SearchResultKind,
+  'score' : number,
+  'subtitle' : string,
+}
+export type SearchResultKind = { 'Availability' : null } |
+  { 'Booking' : null } |
+  { 'Contact' : null };
+export interface SelfTestCheck {
+  'name' : string,
+  'detail' : string,
+  'passed' : boolean,
+}
+export interface SelfTestReport {
+  'ran_at' : bigint,
+  'checks' : Array<SelfTestCheck>,
+  'passed' : boolean,
+}
+export interface ServiceStatus {
+  'components' : Array<ComponentStatus>,
+  'overall' : ComponentHealth,
+  'checked_at' : bigint,
+}
+export interface SessionLink {
+  'title' : string,
+  'token' : string,
+  'owner' : Principal,
+  'duration_minutes' : number,
+  'stripe_price' : [] | [StripePrice],
+  'created_at' : bigint,
+  'duration_prices' : [] | [Array<DurationPrice>],
+  'redeemed_at' : [] | [bigint],
+  'redeemed_by' : [] | [Principal],
+  'availability_id' : string,
+  'ledger_canister' : Principal,
+  'price' : bigint,
+  'booking_id' : [] | [string],
+  'expires_at' : [] | [bigint],
+}
+export interface SetMatrixNotifierRequest {
+  'room_id' : string,
+  'events' : Array<HookEvent>,
+  'access_token' : string,
+  'homeserver' : string,
+}
+export interface SetOrgDefaultsRequest {
+  'cancellation_policy' : [] | [CancellationPolicy],
+  'locale' : [] | [Locale],
+  'enforced' : Array<OrgSetting>,
+  'follow_ups' : [] | [Array<FollowUp>],
+  'durations' : [] | [Array<DurationOption>],
+  'busy_privacy' : [] | [BusyPrivacy],
+  'alignment' : [] | [SlotAlignment],
+}
+export interface SetOriginConfigRequest {
+  'derivation_origin' : [] | [string],
+  'brand_name' : string,
+  'support_email' : [] | [string],
+  'origin' : string,
+  'tenant_id' : [] | [string],
+  'logo_url' : [] | [string],
+  'default_provider' : string,
+  'allowed_features' : Array<Feature>,
+  'primary_color' : [] | [string],
+}
+export interface SetRegionPolicyRequest {
+  'require_adult' : boolean,
+  'unsupported_regions' : Array<string>,
+  'tenant_id' : string,
+  'gated_features' : Array<Feature>,
+  'require_region' : boolean,
+}
+export interface SetTaxProfileRequest {
+  'tax_rate_bps' : number,
+  'vat_id' : [] | [string],
+  'business_name' : string,
+  'business_address' : string,
+  'country_code' : string,
+  'prices_include_tax' : boolean,
+}
+export type ShareRecipient = { 'Email' : string } |
+  { 'Principal' : Principal };
+export type SideEffect = {
+    'Matrix' : {
+      'owner' : Principal,
+      'event' : HookEvent,
+      'booking_id' : string,
+    }
+  } |
+  {
+    'IntegrationHook' : {
+      'event' : HookEvent,
+      'hook_id' : string,
+      'booking_id' : string,
+    }
+  };
 export interface SignedDelegation {
   'signature' : Uint8Array | number[],
   'delegation' : Delegation,
 }
+export interface SlotAlignment {
+  'lead_minutes' : number,
+  'step_minutes' : number,
+}
+export interface SlotExperiment {
+  'a' : VariantStats,
+  'b' : VariantStats,
+  'starts_at' : bigint,
+  'variant_b_slots' : Array<TimeSlot>,
+  'ends_at' : bigint,
+  'created_at' : bigint,
+  'created_by' : Principal,
+  'b_percent' : number,
+  'availability_id' : string,
+}
+export interface StartExperimentRequest {
+  'starts_at' : [] | [bigint],
+  'variant_b_slots' : Array<TimeSlot>,
+  'ends_at' : bigint,
+  'b_percent' : number,
+  'availability_id' : string,
+}
+export interface StorageReport {
+  'structures' : Array<StructureUsage>,
+  'generated_at' : bigint,
+  'stable_bytes' : bigint,
+  'heap_bytes' : bigint,
+  'snapshot_at' : [] | [bigint],
+}
+export interface StripeConfig { 'webhook_secret' : [] | [string] }
+export interface StripePrice { 'currency' : string, 'amount' : bigint }
+export interface StructureCheck {
+  'name' : string,
+  'keys_match' : boolean,
+  'entries_after' : bigint,
+  'entries_before' : bigint,
+  'values_match' : boolean,
+}
+export interface StructureUsage {
+  'allocated_growth' : [] | [bigint],
+  'entries_growth' : [] | [bigint],
+  'name' : string,
+  'allocated_bytes' : bigint,
+  'memory_id' : number,
+  'stats' : [] | [EntryStats],
+}
+export interface Subscription {
+  'last_error' : [] | [string],
+  'status' : SubscriptionStatus,
+  'failed_attempts' : number,
+  'last_block_index' : [] | [bigint],
+  'owner' : Principal,
+  'created_at' : bigint,
+  'next_charge_at' : bigint,
+  'current_period_end' : bigint,
+  'grace_until' : [] | [bigint],
+  'ledger_canister' : Principal,
+  'amount' : bigint,
+}
+export type SubscriptionStatus = { 'Active' : null } |
+  { 'PastDue' : null } |
+  { 'Cancelled' : null };
+export interface SuggestionConstraints {
+  'range_end' : bigint,
+  'preferred_end_hour' : [] | [number],
+  'max_results' : [] | [number],
+  'preferred_start_hour' : [] | [number],
+  'step_minutes' : [] | [number],
+  'range_start' : bigint,
+}
+export interface SweepReport {
+  'bookings' : Array<string>,
+  'availabilities' : Array<string>,
+}
+export interface SyncSchedulerStatus {
+  'last_tick_synced' : number,
+  'backoff_level' : number,
+  'backoff_until' : [] | [bigint],
+  'tracked' : bigint,
+  'last_tick_at' : [] | [bigint],
+}
+export interface TaxBreakdown {
+  'rate_bps' : number,
+  'net_amount' : bigint,
+  'tax_amount' : bigint,
+  'vat_id' : [] | [string],
+}
+export interface TaxProfile {
+  'tax_rate_bps' : number,
+  'updated_at' : bigint,
+  'vat_id' : [] | [string],
+  'business_name' : string,
+  'business_address' : string,
+  'country_code' : string,
+  'prices_include_tax' : boolean,
+}
+export interface Team {
+  'id' : string,
+  'updated_at' : bigint,
+  'members' : Array<TeamMember>,
+  'owner' : Principal,
+  'name' : string,
+  'created_at' : bigint,
+}
+export interface TeamBookingRequest {
+  'team_id' : string,
+  'guest_name' : string,
+  'end_time' : bigint,
+  'guest_timezone' : [] | [string],
+  'start_time' : bigint,
+  'guest_email' : [] | [string],
+}
+export interface TeamMember {
+  'weight' : number,
+  'max_per_week' : [] | [number],
+  'availability_id' : string,
+}
+export interface TeamRequest { 'members' : Array<TeamMember>, 'name' : string }
+export type TemplateKind = { 'BookingCreated' : null } |
+  { 'Receipt' : null } |
+  { 'BookingCancelled' : null };
+export type TemplateScope = { 'Tenant' : string } |
+  { 'Availability' : string };
+export interface Tenant {
+  'id' : string,
+  'name' : string,
+  'created_at' : bigint,
+  'admins' : Array<Principal>,
+}
+export interface TenantMember { 'principal' : Principal, 'joined_at' : bigint }
+export interface TermsAcceptance { 'accepted_at' : bigint, 'version' : number }
+export interface TermsConfig {
+  'privacy_url' : string,
+  'published_at' : bigint,
+  'version' : number,
+  'terms_url' : string,
+}
+export interface TermsStatus {
+  'accepted_version' : [] | [number],
+  'needs_acceptance' : boolean,
+  'current' : TermsConfig,
+}
 export interface TimeSlot {
   'end_time' : number,
   'start_time' : number,
@@ -103,6 +1407,20 @@ export interface TokenResponse {
   'expires_in' : bigint,
   'token_type' : string,
 }
+export interface TransformArgs {
+  'context' : Uint8Array | number[],
+  'response' : HttpResponse_1,
+}
+export interface TrashEntry {
+  'id' : string,
+  'purge_at' : bigint,
+  'owner' : Principal,
+  'item' : TrashedItem,
+  'deleted_at' : bigint,
+  'deleted_by' : Principal,
+}
+export type TrashedItem = { 'Availability' : Availability } |
+  { 'Booking' : Booking };
 export interface UpdateAvailabilityRequest {
   'id' : string,
   'timezone' : [] | [string],
@@ -121,54 +1439,406 @@ export interface UpdateEventRequest {
   'event_id' : string,
   'location' : [] | [string],
 }
+export interface UpgradeVerification {
+  'ok' : boolean,
+  'structures' : Array<StructureCheck>,
+  'snapshot_at' : [] | [bigint],
+}
 export interface UserInfo {
   'principal' : string,
   'name' : [] | [string],
   'user_id' : [] | [string],
   'email' : [] | [string],
 }
+export interface VariantStats { 'bookings' : bigint, 'views' : bigint }
+export interface VipConfig {
+  'emails' : Array<string>,
+  'reserve_hours' : number,
+  'domains' : Array<string>,
+  'access_codes' : Array<string>,
+}
 export interface _SERVICE {
+  'accept_availability_share' : ActorMethod<[string], Result>,
+  'accept_terms' : ActorMethod<[number], Result_1>,
+  'add_interview_candidate' : ActorMethod<
+    [string, string, [] | [string]],
+    Result_2
+  >,
+  'add_org_busy_block' : ActorMethod<[string, OrgBusyBlockRequest], Result_3>,
+  'api_version' : ActorMethod<[], ApiVersionInfo>,
+  'book_again' : ActorMethod<[string, CreateBookingRequest], Result_4>,
+  'book_interview_round' : ActorMethod<[BookRoundRequest], Result_4>,
+  'book_next_available' : ActorMethod<[string, string], Result_4>,
+  'book_together' : ActorMethod<[GroupBookingRequest], Result_5>,
+  'book_with_one_time_link' : ActorMethod<[BookWithLinkRequest], Result_4>,
+  'book_with_resource' : ActorMethod<[CreateBookingRequest, string], Result_4>,
+  'book_with_snapshot' : ActorMethod<[string, CreateBookingRequest], Result_4>,
+  'book_with_team' : ActorMethod<[TeamBookingRequest], Result_4>,
+  'cancel_booking' : ActorMethod<[string], Result_4>,
+  'cancel_maintenance_job' : ActorMethod<[bigint], Result_6>,
+  'cancel_subscription' : ActorMethod<[], Result_7>,
+  'check_booking_conflict' : ActorMethod<[string, bigint, bigint], Result_7>,
+  'check_in' : ActorMethod<[string, string], Result_8>,
   'cleanup_expired_sessions' : ActorMethod<[], bigint>,
+  'cleanup_orphaned_tokens' : ActorMethod<[], Result_9>,
+  'clear_my_tax_profile' : ActorMethod<[], undefined>,
+  'create_api_key' : ActorMethod<[CreateApiKeyRequest], Result_10>,
   'create_availability' : ActorMethod<[CreateAvailabilityRequest], Result>,
-  'create_calendar_event' : ActorMethod<[CreateEventRequest], Result_1>,
-  'delete_availability' : ActorMethod<[string], Result_2>,
-  'delete_calendar_event' : ActorMethod<[string], Result_2>,
-  'exchange_oauth_code' : ActorMethod<[ExchangeCodeRequest], Result_3>,
-  'get_availability' : ActorMethod<[string], Result>,
+  'create_booking' : ActorMethod<[CreateBookingRequest], Result_4>,
+  'create_calendar_event' : ActorMethod<[CreateEventRequest], Result_11>,
+  'create_discord_link_code' : ActorMethod<[], Result_11>,
+  'create_integration_hook' : ActorMethod<
+    [CreateIntegrationHookRequest],
+    Result_12
+  >,
+  'create_interview_pipeline' : ActorMethod<[CreatePipelineRequest], Result_13>,
+  'create_one_time_link' : ActorMethod<[string], Result_14>,
+  'create_promo_code' : ActorMethod<
+    [string, PromoReward, number, [] | [bigint]],
+    Result_15
+  >,
+  'create_resource' : ActorMethod<[string, ResourceRequest], Result_16>,
+  'create_session_link' : ActorMethod<[CreateSessionLinkRequest], Result_17>,
+  'create_team' : ActorMethod<[TeamRequest], Result_18>,
+  'create_tenant' : ActorMethod<[string, string, Array<Principal>], Result_19>,
+  'decline_availability_share' : ActorMethod<[string], Result_7>,
+  'delete_availability' : ActorMethod<[string], Result_7>,
+  'delete_booking' : ActorMethod<[string], Result_7>,
+  'delete_calendar_event' : ActorMethod<[string], Result_7>,
+  'delete_integration_hook' : ActorMethod<[string], Result_7>,
+  'delete_matrix_notifier' : ActorMethod<[], Result_7>,
+  'delete_notification_template' : ActorMethod<
+    [TemplateScope, TemplateKind],
+    Result_7
+  >,
+  'delete_org_busy_block' : ActorMethod<[string, string], Result_7>,
+  'delete_org_defaults' : ActorMethod<[string], Result_7>,
+  'delete_origin_alias' : ActorMethod<[string], Result_7>,
+  'delete_origin_config' : ActorMethod<[string], Result_7>,
+  'delete_promo_code' : ActorMethod<[string], Result_7>,
+  'delete_resource' : ActorMethod<[string], Result_7>,
+  'delete_team' : ActorMethod<[string], Result_7>,
+  'delete_tenant_availability' : ActorMethod<[string, string], Result_7>,
+  'discard_job' : ActorMethod<[bigint], Result_7>,
+  'discard_stripe_event' : ActorMethod<[string], Result_7>,
+  'exchange_oauth_code' : ActorMethod<[ExchangeCodeRequest], Result_20>,
+  'export_availability_definition' : ActorMethod<[string], Result_21>,
+  'export_my_bookings_csv' : ActorMethod<[], string>,
+  'export_my_data' : ActorMethod<[], MyDataExport>,
+  'get_analytics_config' : ActorMethod<[], Result_22>,
+  'get_api_usage_report' : ActorMethod<[[] | [bigint]], Result_23>,
+  'get_availability' : ActorMethod<[string, [] | [string]], Result>,
+  'get_availability_qr_svg' : ActorMethod<[string, [] | [string]], Result_11>,
+  'get_billing_config' : ActorMethod<[], BillingConfig>,
+  'get_booking' : ActorMethod<[string], Result_4>,
+  'get_booking_attendees' : ActorMethod<[string], Result_24>,
+  'get_booking_history' : ActorMethod<[string], Result_25>,
+  'get_booking_ics' : ActorMethod<[string], Result_11>,
+  'get_booking_receipt' : ActorMethod<[string], Result_26>,
+  'get_booking_schema' : ActorMethod<[string, [] | [string]], Result_27>,
+  'get_calendar_connection' : ActorMethod<[], CalendarConnection>,
   'get_caller' : ActorMethod<[], string>,
-  'get_delegation' : ActorMethod<[GetDelegationRequest], Result_4>,
+  'get_cancellation_quote' : ActorMethod<[string], Result_28>,
+  'get_candidate_status' : ActorMethod<[string], Result_2>,
+  'get_check_in_token' : ActorMethod<[string], Result_11>,
+  'get_common_free_slots' : ActorMethod<
+    [Array<string>, bigint, bigint, boolean],
+    Result_29
+  >,
+  'get_default_quotas' : ActorMethod<[], QuotaLimits>,
+  'get_delegate_audit_log' : ActorMethod<[], Array<DelegateAction>>,
+  'get_delegate_audit_log_page' : ActorMethod<[[] | [string]], Result_30>,
+  'get_delegation' : ActorMethod<[GetDelegationRequest], Result_31>,
+  'get_discord_config' : ActorMethod<[], DiscordConfig>,
+  'get_earnings_balance' : ActorMethod<[], Array<EarningsBalance>>,
+  'get_error_catalog' : ActorMethod<[], Array<ErrorCatalogEntry>>,
+  'get_event_counts' : ActorMethod<[[] | [string], bigint, bigint], Result_32>,
+  'get_free_slots' : ActorMethod<
+    [string, bigint, bigint, [] | [number], [] | [string], [] | [string]],
+    Result_33
+  >,
+  'get_free_slots_with_resource' : ActorMethod<
+    [string, string, bigint, bigint],
+    Result_33
+  >,
+  'get_index_migration_status' : ActorMethod<[], Result_34>,
+  'get_interview_round_slots' : ActorMethod<
+    [string, bigint, bigint],
+    Result_35
+  >,
+  'get_link_stats' : ActorMethod<[string], Result_36>,
+  'get_matrix_notifier' : ActorMethod<[], [] | [MatrixNotifier]>,
+  'get_my_api_usage' : ActorMethod<[], Array<ApiUsageDay>>,
+  'get_my_booking_discount' : ActorMethod<[], [] | [BookingDiscount]>,
+  'get_my_counters' : ActorMethod<[], CounterSet>,
+  'get_my_hijri_annotation' : ActorMethod<[], boolean>,
+  'get_my_locale' : ActorMethod<[], [] | [Locale]>,
+  'get_my_plan' : ActorMethod<[], PlanInfo>,
+  'get_my_quotas' : ActorMethod<[], QuotaStatus>,
+  'get_my_referral_code' : ActorMethod<[], ReferralStats>,
+  'get_my_region' : ActorMethod<[], [] | [RegionDeclaration]>,
+  'get_my_region_policy' : ActorMethod<[], [] | [RegionPolicy]>,
+  'get_my_subscription' : ActorMethod<[], [] | [Subscription]>,
+  'get_my_tax_profile' : ActorMethod<[], [] | [TaxProfile]>,
+  'get_my_tenant' : ActorMethod<[], string>,
+  'get_no_show_stats' : ActorMethod<[string], Result_37>,
+  'get_notification_deliveries' : ActorMethod<[string], Result_38>,
+  'get_one_time_link' : ActorMethod<[string], Result_14>,
+  'get_org_counters' : ActorMethod<[string], Result_39>,
+  'get_org_defaults' : ActorMethod<[], [] | [OrgDefaults]>,
+  'get_org_load_report' : ActorMethod<
+    [string, bigint, bigint, number, number, [] | [string]],
+    Result_40
+  >,
+  'get_origin_config' : ActorMethod<[string], OriginConfig>,
+  'get_orphaned_tokens' : ActorMethod<[], Result_9>,
+  'get_platform_stats' : ActorMethod<[], PlatformStats>,
   'get_providers' : ActorMethod<[], Array<OAuthProvider>>,
+  'get_read_cache' : ActorMethod<[[] | [string]], [] | [Principal]>,
+  'get_read_cache_status' : ActorMethod<[], Result_41>,
+  'get_rebook_info' : ActorMethod<[string], Result_42>,
+  'get_rebuild_progress' : ActorMethod<[], Result_43>,
+  'get_resource_free_slots' : ActorMethod<[string, bigint, bigint], Result_33>,
+  'get_service_status' : ActorMethod<[], ServiceStatus>,
+  'get_session_attendance' : ActorMethod<[string, bigint], Result_44>,
   'get_session_count' : ActorMethod<[], bigint>,
+  'get_session_link' : ActorMethod<[string], Result_17>,
+  'get_slot_experiment' : ActorMethod<[string], Result_45>,
+  'get_storage_report' : ActorMethod<[], Result_46>,
+  'get_sync_scheduler_status' : ActorMethod<[], Result_47>,
+  'get_team' : ActorMethod<[string], Result_18>,
+  'get_team_free_slots' : ActorMethod<[string, bigint, bigint], Result_33>,
+  'get_terms_status' : ActorMethod<[], TermsStatus>,
+  'get_upstream_status' : ActorMethod<[], Result_48>,
   'get_user_info' : ActorMethod<[], UserInfo>,
+  'grant_delegation' : ActorMethod<
+    [Principal, Array<DelegationScope>],
+    Result_49
+  >,
   'greet' : ActorMethod<[string], string>,
   'hello_world' : ActorMethod<[], string>,
+  'hold_session_link_for_stripe' : ActorMethod<
+    [RedeemSessionLinkRequest],
+    Result_4
+  >,
+  'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
+  'http_request_update' : ActorMethod<[HttpRequest], HttpResponse>,
+  'import_from_calendly' : ActorMethod<[string, boolean], Result_50>,
   'is_authenticated' : ActorMethod<[], boolean>,
+  'lift_legal_hold' : ActorMethod<[string, Principal], Result_51>,
+  'list_availabilities_for' : ActorMethod<[Principal], Result_52>,
+  'list_availability_bookings' : ActorMethod<[string], Result_5>,
+  'list_availability_bookings_page' : ActorMethod<
+    [string, [] | [string]],
+    Result_53
+  >,
+  'list_bookings_for' : ActorMethod<[Principal], Result_5>,
+  'list_calendar_conflicts' : ActorMethod<[], Array<CalendarConflict>>,
+  'list_corrupt_records' : ActorMethod<[[] | [string]], Result_54>,
+  'list_delegated_to_me' : ActorMethod<[], Array<ManagementDelegation>>,
+  'list_failed_jobs' : ActorMethod<[[] | [bigint]], Result_55>,
+  'list_incoming_availability_shares' : ActorMethod<
+    [],
+    Array<AvailabilityShare>
+  >,
+  'list_interview_candidates' : ActorMethod<[string], Result_56>,
+  'list_interview_pipelines' : ActorMethod<[], Array<InterviewPipeline>>,
+  'list_legal_holds' : ActorMethod<[string], Result_57>,
+  'list_maintenance_jobs' : ActorMethod<[], Result_58>,
+  'list_my_api_keys' : ActorMethod<[], Array<ApiKey>>,
+  'list_my_bookings' : ActorMethod<[], Array<Booking>>,
+  'list_my_bookings_page' : ActorMethod<[[] | [string]], Result_53>,
+  'list_my_delegates' : ActorMethod<[], Array<ManagementDelegation>>,
+  'list_my_earnings_entries' : ActorMethod<[], Array<JournalEntry>>,
+  'list_my_integration_hooks' : ActorMethod<[], Array<IntegrationHook>>,
+  'list_my_one_time_links' : ActorMethod<[], Array<OneTimeLink>>,
+  'list_my_session_links' : ActorMethod<[], Array<SessionLink>>,
+  'list_notification_templates' : ActorMethod<[TemplateScope], Result_59>,
+  'list_org_busy_blocks' : ActorMethod<[], Array<OrgBusyBlock>>,
+  'list_origin_aliases' : ActorMethod<[], Result_60>,
+  'list_origin_configs' : ActorMethod<[], Result_61>,
+  'list_outgoing_availability_shares' : ActorMethod<
+    [],
+    Array<AvailabilityShare>
+  >,
+  'list_principal_moves' : ActorMethod<[], Result_62>,
+  'list_promo_codes' : ActorMethod<[], Result_63>,
+  'list_resources' : ActorMethod<[], Array<Resource>>,
+  'list_stripe_dead_letters' : ActorMethod<[], Result_64>,
+  'list_teams' : ActorMethod<[], Array<Team>>,
+  'list_tenant_availabilities' : ActorMethod<[string], Result_52>,
+  'list_tenant_availabilities_page' : ActorMethod<
+    [string, [] | [string]],
+    Result_65
+  >,
+  'list_tenant_users' : ActorMethod<[string], Result_66>,
+  'list_tenants' : ActorMethod<[], Result_67>,
+  'list_trash' : ActorMethod<[], Array<TrashEntry>>,
   'list_user_availabilities' : ActorMethod<[], Array<Availability>>,
-  'logout' : ActorMethod<[Uint8Array | number[]], Result_2>,
-  'prepare_delegation' : ActorMethod<[PrepareDelegationRequest], Result_5>,
-  'refresh_google_token' : ActorMethod<[RefreshTokenRequest], Result_3>,
-  'regenerate_availability_id' : ActorMethod<[string], Result_1>,
-  'search_availabilities_by_email' : ActorMethod<[string], Array<Availability>>,
+  'list_user_availabilities_page' : ActorMethod<[[] | [string]], Result_65>,
+  'logout' : ActorMethod<[Uint8Array | number[]], Result_7>,
+  'migrate_principal' : ActorMethod<[Principal, Principal], Result_68>,
+  'place_legal_hold' : ActorMethod<[string, Principal, string], Result_51>,
+  'prepare_delegation' : ActorMethod<[PrepareDelegationRequest], Result_69>,
+  'preview_free_slots' : ActorMethod<
+    [string, AvailabilitySettingsPreview, bigint, bigint, [] | [number]],
+    Result_33
+  >,
+  'preview_integration_payload' : ActorMethod<[string, string], Result_11>,
+  'publish_terms' : ActorMethod<[number, string, string], Result_70>,
+  'purge_corrupt_record' : ActorMethod<[string], Result_7>,
+  'quick_book' : ActorMethod<[string, string, string], Result_11>,
+  'quick_free' : ActorMethod<[string, number], Result_71>,
+  'rebuild_indices' : ActorMethod<[], Result_6>,
+  'record_link_open' : ActorMethod<[string], Result_7>,
+  'redeem_code' : ActorMethod<[string], Result_72>,
+  'redeem_session_link' : ActorMethod<[RedeemSessionLinkRequest], Result_4>,
+  'refresh_google_token' : ActorMethod<[RefreshTokenRequest], Result_20>,
+  'refresh_outcall_cache' : ActorMethod<[], number>,
+  'regenerate_availability_id' : ActorMethod<[string], Result_11>,
+  'replay_booking_events' : ActorMethod<[], Result_73>,
+  'resend_notification' : ActorMethod<[string, NotificationChannel], Result_73>,
+  'resolve_calendar_conflict' : ActorMethod<
+    [string, ConflictResolution],
+    Result_74
+  >,
+  'restore_from_trash' : ActorMethod<[string], Result_75>,
+  'retry_job' : ActorMethod<[bigint], Result_76>,
+  'retry_stripe_event' : ActorMethod<[string], Result_7>,
+  'revoke_api_key' : ActorMethod<[string], Result_7>,
+  'revoke_availability_share' : ActorMethod<[string], Result_7>,
+  'revoke_one_time_link' : ActorMethod<[string], Result_7>,
+  'revoke_session_link' : ActorMethod<[string], Result_7>,
+  'run_index_migration' : ActorMethod<[[] | [number]], Result_34>,
+  'run_self_test' : ActorMethod<[], Result_77>,
+  'search_availabilities_by_email' : ActorMethod<
+    [string, [] | [string]],
+    Array<Availability>
+  >,
   'search_availabilities_by_principal' : ActorMethod<
-    [Principal],
+    [Principal, [] | [string]],
     Array<Availability>
   >,
   'search_availabilities_by_username' : ActorMethod<
-    [string],
+    [string, [] | [string]],
     Array<Availability>
   >,
-  'search_by_emails' : ActorMethod<[Array<string>], Array<Array<Availability>>>,
+  'search_by_emails' : ActorMethod<
+    [Array<string>, [] | [string]],
+    Array<Array<Availability>>
+  >,
   'search_by_usernames' : ActorMethod<
-    [Array<string>],
+    [Array<string>, [] | [string]],
     Array<Array<Availability>>
   >,
-  'set_favorite_availability' : ActorMethod<[string], Result_2>,
+  'search_my_data' : ActorMethod<[string], Result_78>,
+  'set_analytics_config' : ActorMethod<[AnalyticsConfig], Result_7>,
+  'set_availability_alignment' : ActorMethod<
+    [string, [] | [SlotAlignment]],
+    Result_7
+  >,
+  'set_availability_busy_privacy' : ActorMethod<
+    [string, BusyPrivacy],
+    Result_7
+  >,
+  'set_availability_cancellation_policy' : ActorMethod<
+    [string, [] | [CancellationPolicy]],
+    Result_7
+  >,
+  'set_availability_collaborators' : ActorMethod<
+    [string, Array<Collaborator>],
+    Result_7
+  >,
+  'set_availability_durations' : ActorMethod<
+    [string, Array<DurationOption>],
+    Result_7
+  >,
+  'set_availability_event_fields' : ActorMethod<
+    [string, [] | [Array<EventField>]],
+    Result_7
+  >,
+  'set_availability_follow_ups' : ActorMethod<
+    [string, [] | [Array<FollowUp>]],
+    Result_7
+  >,
+  'set_availability_kiosk' : ActorMethod<
+    [string, [] | [KioskConfig]],
+    Result_7
+  >,
+  'set_availability_locale' : ActorMethod<[string, [] | [Locale]], Result_7>,
+  'set_availability_meeting_url' : ActorMethod<
+    [string, [] | [string]],
+    Result_7
+  >,
+  'set_availability_seats' : ActorMethod<[string, [] | [number]], Result_7>,
+  'set_availability_vip' : ActorMethod<[string, [] | [VipConfig]], Result_7>,
+  'set_billing_config' : ActorMethod<[BillingConfig], Result_7>,
+  'set_default_quotas' : ActorMethod<[QuotaLimits], Result_7>,
+  'set_discord_config' : ActorMethod<[DiscordConfig], Result_7>,
+  'set_favorite_availability' : ActorMethod<[string], Result_7>,
+  'set_matrix_notifier' : ActorMethod<[SetMatrixNotifierRequest], Result_79>,
+  'set_my_hijri_annotation' : ActorMethod<[boolean], undefined>,
+  'set_my_locale' : ActorMethod<[Locale], undefined>,
+  'set_my_region' : ActorMethod<[string, boolean], Result_80>,
+  'set_my_tax_profile' : ActorMethod<[SetTaxProfileRequest], Result_81>,
+  'set_notification_template' : ActorMethod<
+    [TemplateScope, TemplateKind, string, string],
+    Result_82
+  >,
+  'set_org_defaults' : ActorMethod<[string, SetOrgDefaultsRequest], Result_83>,
+  'set_origin_alias' : ActorMethod<[string, string], Result_7>,
+  'set_origin_config' : ActorMethod<[SetOriginConfigRequest], Result_84>,
+  'set_quota_override' : ActorMethod<[Principal, [] | [QuotaLimits]], Result_7>,
+  'set_read_cache' : ActorMethod<[[] | [Principal]], Result_7>,
+  'set_region_policy' : ActorMethod<[SetRegionPolicyRequest], Result_85>,
+  'set_session_link_prices' : ActorMethod<
+    [string, Array<DurationPrice>],
+    Result_17
+  >,
+  'set_session_link_stripe_price' : ActorMethod<
+    [string, [] | [StripePrice]],
+    Result_17
+  >,
+  'set_stripe_config' : ActorMethod<[StripeConfig], Result_7>,
+  'set_tenant_admins' : ActorMethod<[string, Array<Principal>], Result_19>,
+  'set_user_plan' : ActorMethod<[Principal, PlanTier, [] | [bigint]], Result_7>,
+  'share_availability_template' : ActorMethod<[string, string], Result_86>,
+  'snapshot_free_slots' : ActorMethod<
+    [string, bigint, bigint, [] | [number], [] | [string], [] | [string]],
+    Result_87
+  >,
+  'snapshot_storage_report' : ActorMethod<[], Result_46>,
+  'start_maintenance' : ActorMethod<[MaintenanceTask], Result_6>,
+  'start_slot_experiment' : ActorMethod<[StartExperimentRequest], Result_88>,
+  'stop_slot_experiment' : ActorMethod<[string], Result_88>,
+  'subscribe_pro' : ActorMethod<[], Result_89>,
+  'suggest_meeting_times' : ActorMethod<
+    [Array<string>, number, SuggestionConstraints],
+    Result_90
+  >,
+  'sweep_corrupt_records' : ActorMethod<[], Result_91>,
+  'track_event' : ActorMethod<[string, Array<EventProp>], Result_92>,
+  'transform_hook_response' : ActorMethod<[TransformArgs], HttpResponse_1>,
+  'transform_import_response' : ActorMethod<[TransformArgs], HttpResponse_1>,
+  'unlink_discord' : ActorMethod<[], number>,
   'update_availability' : ActorMethod<[UpdateAvailabilityRequest], Result>,
   'update_availability_busy_times' : ActorMethod<
-    [string, Array<BusyTimeBlock>],
-    Result_2
+    [string, Array<FreeSlot>],
+    Result_7
+  >,
+  'update_calendar_event' : ActorMethod<[UpdateEventRequest], Result_11>,
+  'update_resource' : ActorMethod<[string, ResourceRequest], Result_16>,
+  'update_team' : ActorMethod<[string, TeamRequest], Result_18>,
+  'v2_create_booking' : ActorMethod<[CreateBookingRequest], Result_93>,
+  'v2_get_availability' : ActorMethod<[string, [] | [string]], Result_94>,
+  'v2_get_booking' : ActorMethod<[string], Result_93>,
+  'v2_get_free_slots' : ActorMethod<
+    [string, bigint, bigint, [] | [number], [] | [string], [] | [string]],
+    Result_95
   >,
-  'update_calendar_event' : ActorMethod<[UpdateEventRequest], Result_1>,
+  'verify_upgrade' : ActorMethod<[], Result_96>,
+  'withdraw' : ActorMethod<[Principal, bigint, Account], Result_97>,
+  'withdraw_interview_candidate' : ActorMethod<[string], Result_2>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
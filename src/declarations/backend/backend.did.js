@@ -1,5 +1,23 @@
 export const idlFactory = ({ IDL }) => {
-  const BusyTimeBlock = IDL.Record({
+  const VipConfig = IDL.Record({
+    'emails' : IDL.Vec(IDL.Text),
+    'reserve_hours' : IDL.Nat32,
+    'domains' : IDL.Vec(IDL.Text),
+    'access_codes' : IDL.Vec(IDL.Text),
+  });
+  const CancellationPolicy = IDL.Record({
+    'partial_refund_percent' : IDL.Nat8,
+    'free_until_hours' : IDL.Nat32,
+    'partial_until_hours' : IDL.Nat32,
+  });
+  const Locale = IDL.Variant({
+    'Ar' : IDL.Null,
+    'De' : IDL.Null,
+    'En' : IDL.Null,
+    'Es' : IDL.Null,
+    'He' : IDL.Null,
+  });
+  const FreeSlot = IDL.Record({
     'end_time' : IDL.Nat64,
     'start_time' : IDL.Nat64,
   });
@@ -8,31 +26,307 @@ export const idlFactory = ({ IDL }) => {
     'start_time' : IDL.Nat16,
     'day_of_week' : IDL.Nat8,
   });
-  const CreateAvailabilityRequest = IDL.Record({
+  const CollaboratorRole = IDL.Variant({
+    'Viewer' : IDL.Null,
+    'Approver' : IDL.Null,
+    'Editor' : IDL.Null,
+  });
+  const Collaborator = IDL.Record({
+    'principal' : IDL.Principal,
+    'role' : CollaboratorRole,
+  });
+  const EventField = IDL.Record({
+    'key' : IDL.Text,
+    'value' : IDL.Opt(IDL.Text),
+    'label' : IDL.Text,
+  });
+  const KioskConfig = IDL.Record({
+    'duration_minutes' : IDL.Nat32,
+    'lead_minutes' : IDL.Nat32,
+    'max_bookings_per_device_per_hour' : IDL.Nat32,
+    'devices' : IDL.Vec(IDL.Principal),
+  });
+  const RenderedTemplate = IDL.Record({
+    'subject' : IDL.Text,
+    'body' : IDL.Text,
+  });
+  const FollowUpAction = IDL.Variant({
+    'Email' : RenderedTemplate,
+    'Webhook' : IDL.Record({ 'url' : IDL.Text }),
+  });
+  const FollowUp = IDL.Record({
+    'action' : FollowUpAction,
+    'delay_hours' : IDL.Nat32,
+  });
+  const DurationOption = IDL.Record({
+    'buffer_before_minutes' : IDL.Nat32,
+    'minutes' : IDL.Nat32,
+    'buffer_after_minutes' : IDL.Nat32,
+  });
+  const BusyPrivacy = IDL.Variant({
+    'Exact' : IDL.Null,
+    'FreeSlotsOnly' : IDL.Null,
+    'Quantized' : IDL.Null,
+  });
+  const SlotAlignment = IDL.Record({
+    'lead_minutes' : IDL.Nat32,
+    'step_minutes' : IDL.Nat32,
+  });
+  const Availability = IDL.Record({
+    'id' : IDL.Text,
+    'vip' : IDL.Opt(VipConfig),
     'timezone' : IDL.Text,
     'title' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'cancellation_policy' : IDL.Opt(CancellationPolicy),
+    'owner' : IDL.Principal,
+    'locale' : IDL.Opt(Locale),
     'description' : IDL.Text,
     'owner_email' : IDL.Opt(IDL.Text),
-    'busy_times' : IDL.Opt(IDL.Vec(BusyTimeBlock)),
+    'created_at' : IDL.Nat64,
+    'busy_times' : IDL.Opt(IDL.Vec(FreeSlot)),
+    'is_favorite' : IDL.Bool,
+    'meeting_url' : IDL.Opt(IDL.Text),
+    'seats' : IDL.Opt(IDL.Nat32),
     'slots' : IDL.Vec(TimeSlot),
+    'collaborators' : IDL.Opt(IDL.Vec(Collaborator)),
+    'event_fields' : IDL.Opt(IDL.Vec(EventField)),
+    'display_order' : IDL.Nat32,
+    'kiosk' : IDL.Opt(KioskConfig),
+    'follow_ups' : IDL.Opt(IDL.Vec(FollowUp)),
+    'durations' : IDL.Opt(IDL.Vec(DurationOption)),
+    'busy_privacy' : IDL.Opt(BusyPrivacy),
+    'alignment' : IDL.Opt(SlotAlignment),
     'owner_name' : IDL.Opt(IDL.Text),
   });
-  const Availability = IDL.Record({
+  const Result = IDL.Variant({ 'Ok' : Availability, 'Err' : IDL.Text });
+  const TermsAcceptance = IDL.Record({
+    'accepted_at' : IDL.Nat64,
+    'version' : IDL.Nat32,
+  });
+  const Result_1 = IDL.Variant({ 'Ok' : TermsAcceptance, 'Err' : IDL.Text });
+  const RoundBooking = IDL.Record({
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+    'availability_id' : IDL.Text,
+    'booking_id' : IDL.Text,
+    'round' : IDL.Nat32,
+  });
+  const CandidateStage = IDL.Variant({
+    'Withdrawn' : IDL.Null,
+    'Booked' : IDL.Null,
+    'AwaitingBooking' : IDL.Null,
+    'Completed' : IDL.Null,
+  });
+  const Candidate = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'token' : IDL.Text,
+    'owner' : IDL.Principal,
+    'pipeline_id' : IDL.Text,
+    'name' : IDL.Text,
+    'history' : IDL.Vec(RoundBooking),
+    'created_at' : IDL.Nat64,
+    'email' : IDL.Opt(IDL.Text),
+    'stage' : CandidateStage,
+    'round' : IDL.Nat32,
+  });
+  const Result_2 = IDL.Variant({ 'Ok' : Candidate, 'Err' : IDL.Text });
+  const OrgBusyBlockRequest = IDL.Record({
+    'title' : IDL.Text,
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+  });
+  const OrgBusyBlock = IDL.Record({
     'id' : IDL.Text,
-    'timezone' : IDL.Text,
     'title' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'created_by' : IDL.Principal,
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+  });
+  const Result_3 = IDL.Variant({ 'Ok' : OrgBusyBlock, 'Err' : IDL.Text });
+  const ApiVersionInfo = IDL.Record({
+    'min_supported' : IDL.Nat32,
+    'current' : IDL.Nat32,
+  });
+  const FieldAnswer = IDL.Record({ 'key' : IDL.Text, 'value' : IDL.Text });
+  const CreateBookingRequest = IDL.Record({
+    'guest_name' : IDL.Text,
+    'end_time' : IDL.Nat64,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'field_answers' : IDL.Opt(IDL.Vec(FieldAnswer)),
+    'availability_id' : IDL.Text,
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const BookingStatus = IDL.Variant({
+    'Confirmed' : IDL.Null,
+    'Cancelled' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const PaymentMethod = IDL.Variant({
+    'Stripe' : IDL.Null,
+    'Ledger' : IDL.Null,
+  });
+  const PaymentHold = IDL.Record({
+    'method' : PaymentMethod,
+    'currency' : IDL.Text,
+    'amount' : IDL.Nat64,
+  });
+  const TaxBreakdown = IDL.Record({
+    'rate_bps' : IDL.Nat32,
+    'net_amount' : IDL.Nat64,
+    'tax_amount' : IDL.Nat64,
+    'vat_id' : IDL.Opt(IDL.Text),
+  });
+  const BookingPayment = IDL.Record({
+    'tax' : IDL.Opt(TaxBreakdown),
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'stripe_payment_id' : IDL.Opt(IDL.Text),
+    'discount_percent' : IDL.Opt(IDL.Nat8),
+    'paid_at' : IDL.Nat64,
+    'ledger_canister' : IDL.Principal,
+    'amount' : IDL.Nat64,
+  });
+  const BookingRefund = IDL.Record({
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'error' : IDL.Opt(IDL.Text),
+    'refunded_at' : IDL.Nat64,
+    'amount' : IDL.Nat64,
+  });
+  const Booking = IDL.Record({
+    'id' : IDL.Text,
+    'status' : BookingStatus,
+    'updated_at' : IDL.Nat64,
+    'payment_hold' : IDL.Opt(PaymentHold),
+    'cancelled_at' : IDL.Opt(IDL.Nat64),
+    'cancelled_by' : IDL.Opt(IDL.Principal),
+    'owner' : IDL.Principal,
+    'guest_name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'end_time' : IDL.Nat64,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'guest' : IDL.Principal,
+    'field_answers' : IDL.Opt(IDL.Vec(FieldAnswer)),
+    'availability_id' : IDL.Text,
+    'guest_email' : IDL.Opt(IDL.Text),
+    'payment' : IDL.Opt(BookingPayment),
+    'session_link' : IDL.Opt(IDL.Text),
+    'refund' : IDL.Opt(BookingRefund),
+  });
+  const Result_4 = IDL.Variant({ 'Ok' : Booking, 'Err' : IDL.Text });
+  const BookRoundRequest = IDL.Record({
+    'token' : IDL.Text,
+    'start_time' : IDL.Nat64,
+    'availability_id' : IDL.Text,
+  });
+  const GroupFieldAnswers = IDL.Record({
+    'answers' : IDL.Vec(FieldAnswer),
+    'availability_id' : IDL.Text,
+  });
+  const GroupBookingRequest = IDL.Record({
+    'guest_name' : IDL.Text,
+    'end_time' : IDL.Nat64,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'resource_ids' : IDL.Vec(IDL.Text),
+    'field_answers' : IDL.Opt(IDL.Vec(GroupFieldAnswers)),
+    'availability_ids' : IDL.Vec(IDL.Text),
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const Result_5 = IDL.Variant({ 'Ok' : IDL.Vec(Booking), 'Err' : IDL.Text });
+  const BookWithLinkRequest = IDL.Record({
+    'token' : IDL.Text,
+    'guest_name' : IDL.Text,
+    'end_time' : IDL.Nat64,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const TeamBookingRequest = IDL.Record({
+    'team_id' : IDL.Text,
+    'guest_name' : IDL.Text,
+    'end_time' : IDL.Nat64,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const MaintenanceStatus = IDL.Variant({
+    'Queued' : IDL.Null,
+    'Failed' : IDL.Null,
+    'Running' : IDL.Null,
+    'Cancelled' : IDL.Null,
+    'Completed' : IDL.Null,
+  });
+  const MaintenanceTask = IDL.Variant({
+    'TokenRekey' : IDL.Null,
+    'IndexMigration' : IDL.Null,
+    'IndexRebuild' : IDL.Null,
+  });
+  const MaintenanceJob = IDL.Record({
+    'id' : IDL.Nat64,
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : MaintenanceStatus,
     'updated_at' : IDL.Nat64,
+    'task' : MaintenanceTask,
+    'created_at' : IDL.Nat64,
+    'chunks' : IDL.Nat64,
+    'processed' : IDL.Nat64,
+  });
+  const Result_6 = IDL.Variant({ 'Ok' : MaintenanceJob, 'Err' : IDL.Text });
+  const Result_7 = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const AttendanceEntry = IDL.Record({
+    'guest_name' : IDL.Text,
+    'checked_in_at' : IDL.Opt(IDL.Nat64),
+    'booking_id' : IDL.Text,
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const Result_8 = IDL.Variant({ 'Ok' : AttendanceEntry, 'Err' : IDL.Text });
+  const OrphanedTokenReport = IDL.Record({
+    'legacy_entries' : IDL.Nat64,
+    'stale_fingerprints' : IDL.Nat64,
+    'shadowed_tokens' : IDL.Vec(IDL.Principal),
+    'dangling_links' : IDL.Vec(IDL.Principal),
+  });
+  const Result_9 = IDL.Variant({
+    'Ok' : OrphanedTokenReport,
+    'Err' : IDL.Text,
+  });
+  const ApiScope = IDL.Variant({
+    'WriteBookings' : IDL.Null,
+    'ReadAvailabilities' : IDL.Null,
+    'WriteBusyTimes' : IDL.Null,
+  });
+  const CreateApiKeyRequest = IDL.Record({
+    'scopes' : IDL.Vec(ApiScope),
+    'name' : IDL.Text,
+    'requests_per_hour' : IDL.Opt(IDL.Nat32),
+  });
+  const ApiKey = IDL.Record({
+    'id' : IDL.Text,
+    'last_used_at' : IDL.Opt(IDL.Nat64),
     'owner' : IDL.Principal,
+    'scopes' : IDL.Vec(ApiScope),
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'requests_per_hour' : IDL.Nat32,
+  });
+  const CreatedApiKey = IDL.Record({ 'key' : ApiKey, 'secret' : IDL.Text });
+  const Result_10 = IDL.Variant({ 'Ok' : CreatedApiKey, 'Err' : IDL.Text });
+  const BusyTimeBlock = IDL.Record({
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+  });
+  const CreateAvailabilityRequest = IDL.Record({
+    'timezone' : IDL.Text,
+    'title' : IDL.Text,
     'description' : IDL.Text,
     'owner_email' : IDL.Opt(IDL.Text),
-    'created_at' : IDL.Nat64,
     'busy_times' : IDL.Opt(IDL.Vec(BusyTimeBlock)),
-    'is_favorite' : IDL.Bool,
     'slots' : IDL.Vec(TimeSlot),
-    'display_order' : IDL.Nat32,
     'owner_name' : IDL.Opt(IDL.Text),
   });
-  const Result = IDL.Variant({ 'Ok' : Availability, 'Err' : IDL.Text });
   const CreateEventRequest = IDL.Record({
     'timezone' : IDL.Text,
     'description' : IDL.Opt(IDL.Text),
@@ -40,11 +334,163 @@ export const idlFactory = ({ IDL }) => {
     'summary' : IDL.Text,
     'start_time' : IDL.Text,
     'attendees' : IDL.Opt(IDL.Vec(IDL.Text)),
+    'booking_id' : IDL.Opt(IDL.Text),
     'conference_data' : IDL.Opt(IDL.Bool),
     'location' : IDL.Opt(IDL.Text),
   });
-  const Result_1 = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
-  const Result_2 = IDL.Variant({ 'Ok' : IDL.Null, 'Err' : IDL.Text });
+  const Result_11 = IDL.Variant({ 'Ok' : IDL.Text, 'Err' : IDL.Text });
+  const FieldMapping = IDL.Record({ 'key' : IDL.Text, 'field' : IDL.Text });
+  const HookEvent = IDL.Variant({
+    'BookingCreated' : IDL.Null,
+    'BookingCancelled' : IDL.Null,
+  });
+  const CreateIntegrationHookRequest = IDL.Record({
+    'url' : IDL.Text,
+    'field_mappings' : IDL.Vec(FieldMapping),
+    'events' : IDL.Vec(HookEvent),
+  });
+  const IntegrationHook = IDL.Record({
+    'id' : IDL.Text,
+    'url' : IDL.Text,
+    'last_delivery_at' : IDL.Opt(IDL.Nat64),
+    'owner' : IDL.Principal,
+    'last_status' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'field_mappings' : IDL.Vec(FieldMapping),
+    'events' : IDL.Vec(HookEvent),
+  });
+  const Result_12 = IDL.Variant({ 'Ok' : IntegrationHook, 'Err' : IDL.Text });
+  const InterviewRound = IDL.Record({
+    'duration_minutes' : IDL.Nat32,
+    'name' : IDL.Text,
+    'pool' : IDL.Vec(IDL.Text),
+  });
+  const CreatePipelineRequest = IDL.Record({
+    'title' : IDL.Text,
+    'rounds' : IDL.Vec(InterviewRound),
+  });
+  const InterviewPipeline = IDL.Record({
+    'id' : IDL.Text,
+    'title' : IDL.Text,
+    'owner' : IDL.Principal,
+    'created_at' : IDL.Nat64,
+    'rounds' : IDL.Vec(InterviewRound),
+  });
+  const Result_13 = IDL.Variant({ 'Ok' : InterviewPipeline, 'Err' : IDL.Text });
+  const OneTimeLink = IDL.Record({
+    'token' : IDL.Text,
+    'owner' : IDL.Principal,
+    'used_at' : IDL.Opt(IDL.Nat64),
+    'used_by' : IDL.Opt(IDL.Principal),
+    'created_at' : IDL.Nat64,
+    'availability_id' : IDL.Text,
+    'booking_id' : IDL.Opt(IDL.Text),
+  });
+  const Result_14 = IDL.Variant({ 'Ok' : OneTimeLink, 'Err' : IDL.Text });
+  const PromoReward = IDL.Variant({
+    'PlanUpgrade' : IDL.Record({ 'days' : IDL.Nat32 }),
+    'BookingDiscount' : IDL.Record({ 'percent' : IDL.Nat8 }),
+  });
+  const PromoCode = IDL.Record({
+    'reward' : PromoReward,
+    'max_uses' : IDL.Nat32,
+    'code' : IDL.Text,
+    'uses' : IDL.Nat32,
+    'created_at' : IDL.Nat64,
+    'created_by' : IDL.Principal,
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_15 = IDL.Variant({ 'Ok' : PromoCode, 'Err' : IDL.Text });
+  const ResourceKind = IDL.Variant({
+    'Room' : IDL.Null,
+    'Other' : IDL.Null,
+    'Equipment' : IDL.Null,
+  });
+  const ResourceRequest = IDL.Record({
+    'timezone' : IDL.Text,
+    'open_hours' : IDL.Vec(TimeSlot),
+    'kind' : ResourceKind,
+    'name' : IDL.Text,
+    'capacity' : IDL.Nat32,
+  });
+  const Resource = IDL.Record({
+    'id' : IDL.Text,
+    'timezone' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'open_hours' : IDL.Vec(TimeSlot),
+    'kind' : ResourceKind,
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'tenant_id' : IDL.Text,
+    'capacity' : IDL.Nat32,
+  });
+  const Result_16 = IDL.Variant({ 'Ok' : Resource, 'Err' : IDL.Text });
+  const CreateSessionLinkRequest = IDL.Record({
+    'title' : IDL.Text,
+    'duration_minutes' : IDL.Nat32,
+    'availability_id' : IDL.Text,
+    'price' : IDL.Nat64,
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const StripePrice = IDL.Record({
+    'currency' : IDL.Text,
+    'amount' : IDL.Nat64,
+  });
+  const DurationPrice = IDL.Record({
+    'duration_minutes' : IDL.Nat32,
+    'price' : IDL.Nat64,
+  });
+  const SessionLink = IDL.Record({
+    'title' : IDL.Text,
+    'token' : IDL.Text,
+    'owner' : IDL.Principal,
+    'duration_minutes' : IDL.Nat32,
+    'stripe_price' : IDL.Opt(StripePrice),
+    'created_at' : IDL.Nat64,
+    'duration_prices' : IDL.Opt(IDL.Vec(DurationPrice)),
+    'redeemed_at' : IDL.Opt(IDL.Nat64),
+    'redeemed_by' : IDL.Opt(IDL.Principal),
+    'availability_id' : IDL.Text,
+    'ledger_canister' : IDL.Principal,
+    'price' : IDL.Nat64,
+    'booking_id' : IDL.Opt(IDL.Text),
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_17 = IDL.Variant({ 'Ok' : SessionLink, 'Err' : IDL.Text });
+  const TeamMember = IDL.Record({
+    'weight' : IDL.Nat32,
+    'max_per_week' : IDL.Opt(IDL.Nat32),
+    'availability_id' : IDL.Text,
+  });
+  const TeamRequest = IDL.Record({
+    'members' : IDL.Vec(TeamMember),
+    'name' : IDL.Text,
+  });
+  const Team = IDL.Record({
+    'id' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'members' : IDL.Vec(TeamMember),
+    'owner' : IDL.Principal,
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+  });
+  const Result_18 = IDL.Variant({ 'Ok' : Team, 'Err' : IDL.Text });
+  const Tenant = IDL.Record({
+    'id' : IDL.Text,
+    'name' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'admins' : IDL.Vec(IDL.Principal),
+  });
+  const Result_19 = IDL.Variant({ 'Ok' : Tenant, 'Err' : IDL.Text });
+  const TemplateScope = IDL.Variant({
+    'Tenant' : IDL.Text,
+    'Availability' : IDL.Text,
+  });
+  const TemplateKind = IDL.Variant({
+    'BookingCreated' : IDL.Null,
+    'Receipt' : IDL.Null,
+    'BookingCancelled' : IDL.Null,
+  });
   const ExchangeCodeRequest = IDL.Record({
     'code_verifier' : IDL.Text,
     'redirect_uri' : IDL.Text,
@@ -56,7 +502,174 @@ export const idlFactory = ({ IDL }) => {
     'expires_in' : IDL.Nat64,
     'token_type' : IDL.Text,
   });
-  const Result_3 = IDL.Variant({ 'Ok' : TokenResponse, 'Err' : IDL.Text });
+  const Result_20 = IDL.Variant({ 'Ok' : TokenResponse, 'Err' : IDL.Text });
+  const AvailabilityExport = IDL.Record({
+    'json' : IDL.Text,
+    'vavailability' : IDL.Text,
+  });
+  const Result_21 = IDL.Variant({
+    'Ok' : AvailabilityExport,
+    'Err' : IDL.Text,
+  });
+  const RegionDeclaration = IDL.Record({
+    'region' : IDL.Text,
+    'is_adult' : IDL.Bool,
+    'declared_at' : IDL.Nat64,
+  });
+  const MyDataExport = IDL.Record({
+    'region' : IDL.Opt(RegionDeclaration),
+    'principal' : IDL.Principal,
+    'bookings' : IDL.Vec(Booking),
+    'truncated' : IDL.Bool,
+    'exported_at' : IDL.Nat64,
+    'availabilities' : IDL.Vec(Availability),
+    'terms_acceptances' : IDL.Vec(TermsAcceptance),
+  });
+  const AnalyticsConfig = IDL.Record({
+    'sample_percent' : IDL.Nat8,
+    'retention_days' : IDL.Nat32,
+  });
+  const Result_22 = IDL.Variant({ 'Ok' : AnalyticsConfig, 'Err' : IDL.Text });
+  const AccountUsage = IDL.Record({
+    'google_errors' : IDL.Nat32,
+    'google_calls' : IDL.Nat32,
+    'account' : IDL.Principal,
+  });
+  const Result_23 = IDL.Variant({
+    'Ok' : IDL.Vec(AccountUsage),
+    'Err' : IDL.Text,
+  });
+  const BillingConfig = IDL.Record({
+    'grace_period_days' : IDL.Nat32,
+    'ledger_canister' : IDL.Opt(IDL.Principal),
+    'monthly_price' : IDL.Nat64,
+  });
+  const Attendee = IDL.Record({
+    'is_caller' : IDL.Bool,
+    'guest_name' : IDL.Text,
+    'booking_id' : IDL.Opt(IDL.Text),
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const Result_24 = IDL.Variant({ 'Ok' : IDL.Vec(Attendee), 'Err' : IDL.Text });
+  const BookingEvent = IDL.Variant({
+    'Refunded' : BookingRefund,
+    'HoldReleased' : IDL.Null,
+    'Cancelled' : IDL.Record({ 'by' : IDL.Principal }),
+    'Created' : Booking,
+    'PaymentCaptured' : BookingPayment,
+  });
+  const BookingEventRecord = IDL.Record({
+    'at' : IDL.Nat64,
+    'seq' : IDL.Nat64,
+    'event' : BookingEvent,
+    'booking_id' : IDL.Text,
+  });
+  const Result_25 = IDL.Variant({
+    'Ok' : IDL.Vec(BookingEventRecord),
+    'Err' : IDL.Text,
+  });
+  const TaxProfile = IDL.Record({
+    'tax_rate_bps' : IDL.Nat32,
+    'updated_at' : IDL.Nat64,
+    'vat_id' : IDL.Opt(IDL.Text),
+    'business_name' : IDL.Text,
+    'business_address' : IDL.Text,
+    'country_code' : IDL.Text,
+    'prices_include_tax' : IDL.Bool,
+  });
+  const Receipt = IDL.Record({
+    'buyer_name' : IDL.Text,
+    'net_amount' : IDL.Nat64,
+    'tax_rate_bps' : IDL.Nat32,
+    'issued_at' : IDL.Nat64,
+    'total_amount' : IDL.Nat64,
+    'tax_amount' : IDL.Nat64,
+    'block_index' : IDL.Opt(IDL.Nat64),
+    'description' : IDL.Text,
+    'seller' : IDL.Opt(TaxProfile),
+    'end_time' : IDL.Nat64,
+    'buyer_email' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'message' : IDL.Opt(RenderedTemplate),
+    'ledger_canister' : IDL.Principal,
+    'booking_id' : IDL.Text,
+  });
+  const Result_26 = IDL.Variant({ 'Ok' : Receipt, 'Err' : IDL.Text });
+  const FieldType = IDL.Variant({
+    'Email' : IDL.Null,
+    'Minutes' : IDL.Null,
+    'Text' : IDL.Null,
+    'UnixSeconds' : IDL.Null,
+  });
+  const SchemaField = IDL.Record({
+    'field_type' : FieldType,
+    'name' : IDL.Text,
+    'description' : IDL.Text,
+    'max_length' : IDL.Opt(IDL.Nat32),
+    'required' : IDL.Bool,
+  });
+  const SchemaPrice = IDL.Record({
+    'duration_minutes' : IDL.Nat32,
+    'ledger_canister' : IDL.Principal,
+    'amount' : IDL.Nat64,
+  });
+  const BookingSchema = IDL.Record({
+    'timezone' : IDL.Text,
+    'title' : IDL.Text,
+    'start_step_minutes' : IDL.Nat32,
+    'cancellation_policy' : IDL.Opt(CancellationPolicy),
+    'locale' : Locale,
+    'lead_minutes' : IDL.Nat32,
+    'min_duration_minutes' : IDL.Nat32,
+    'fields' : IDL.Vec(SchemaField),
+    'availability_id' : IDL.Text,
+    'prices' : IDL.Vec(SchemaPrice),
+    'durations' : IDL.Vec(DurationOption),
+    'max_duration_minutes' : IDL.Nat32,
+  });
+  const Result_27 = IDL.Variant({ 'Ok' : BookingSchema, 'Err' : IDL.Text });
+  const Revocation = IDL.Record({
+    'revoked_at' : IDL.Nat64,
+    'paused_availabilities' : IDL.Vec(IDL.Text),
+  });
+  const CalendarConnection = IDL.Record({
+    'connected' : IDL.Bool,
+    'revocation' : IDL.Opt(Revocation),
+  });
+  const CancellationQuote = IDL.Record({
+    'refund_percent' : IDL.Nat8,
+    'refund_amount' : IDL.Nat64,
+    'policy' : IDL.Opt(CancellationPolicy),
+  });
+  const Result_28 = IDL.Variant({ 'Ok' : CancellationQuote, 'Err' : IDL.Text });
+  const CommonFreeSlot = IDL.Record({
+    'outside_reasonable_hours' : IDL.Vec(IDL.Text),
+    'end_time' : IDL.Nat64,
+    'score' : IDL.Opt(IDL.Nat32),
+    'start_time' : IDL.Nat64,
+  });
+  const Result_29 = IDL.Variant({
+    'Ok' : IDL.Vec(CommonFreeSlot),
+    'Err' : IDL.Text,
+  });
+  const QuotaLimits = IDL.Record({
+    'max_webhook_subscriptions' : IDL.Nat32,
+    'max_google_calls_per_day' : IDL.Opt(IDL.Nat32),
+    'max_bookings_per_day' : IDL.Nat32,
+    'max_availabilities' : IDL.Nat32,
+    'max_outcalls_per_hour' : IDL.Nat32,
+  });
+  const DelegateAction = IDL.Record({
+    'at' : IDL.Nat64,
+    'action' : IDL.Text,
+    'actor' : IDL.Principal,
+    'trace_id' : IDL.Opt(IDL.Text),
+  });
+  const DelegateAuditPage = IDL.Record({
+    'actions' : IDL.Vec(DelegateAction),
+    'next_cursor' : IDL.Opt(IDL.Text),
+  });
+  const Result_30 = IDL.Variant({ 'Ok' : DelegateAuditPage, 'Err' : IDL.Text });
   const GetDelegationRequest = IDL.Record({
     'expire_at' : IDL.Nat64,
     'provider' : IDL.Text,
@@ -77,10 +690,231 @@ export const idlFactory = ({ IDL }) => {
     'signed_delegation' : SignedDelegation,
     'user_canister_pubkey' : IDL.Vec(IDL.Nat8),
   });
-  const Result_4 = IDL.Variant({
+  const Result_31 = IDL.Variant({
     'Ok' : GetDelegationResponse,
     'Err' : IDL.Text,
   });
+  const DiscordConfig = IDL.Record({ 'public_key' : IDL.Opt(IDL.Text) });
+  const EarningsBalance = IDL.Record({
+    'balance' : IDL.Nat64,
+    'ledger_canister' : IDL.Principal,
+  });
+  const ErrorCatalogEntry = IDL.Record({
+    'code' : IDL.Text,
+    'description' : IDL.Text,
+    'number' : IDL.Nat16,
+  });
+  const EventCount = IDL.Record({
+    'day' : IDL.Nat64,
+    'name' : IDL.Text,
+    'count' : IDL.Nat64,
+    'estimated_total' : IDL.Nat64,
+  });
+  const Result_32 = IDL.Variant({
+    'Ok' : IDL.Vec(EventCount),
+    'Err' : IDL.Text,
+  });
+  const Result_33 = IDL.Variant({ 'Ok' : IDL.Vec(FreeSlot), 'Err' : IDL.Text });
+  const MigrationPhase = IDL.Variant({
+    'Done' : IDL.Null,
+    'Sessions' : IDL.Null,
+    'SearchIndices' : IDL.Null,
+    'NotStarted' : IDL.Null,
+  });
+  const MigrationStatus = IDL.Record({
+    'copied_sessions' : IDL.Nat64,
+    'indexed_availabilities' : IDL.Nat64,
+    'availability_cursor' : IDL.Opt(IDL.Text),
+    'phase' : MigrationPhase,
+    'completed_at' : IDL.Opt(IDL.Nat64),
+    'started_at' : IDL.Opt(IDL.Nat64),
+    'session_cursor' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+  const Result_34 = IDL.Variant({ 'Ok' : MigrationStatus, 'Err' : IDL.Text });
+  const RoundSlot = IDL.Record({
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+    'availability_id' : IDL.Text,
+  });
+  const Result_35 = IDL.Variant({
+    'Ok' : IDL.Vec(RoundSlot),
+    'Err' : IDL.Text,
+  });
+  const LinkStats = IDL.Record({
+    'last_opened_at' : IDL.Opt(IDL.Nat64),
+    'open_count' : IDL.Nat64,
+    'first_opened_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_36 = IDL.Variant({ 'Ok' : LinkStats, 'Err' : IDL.Text });
+  const MatrixNotifier = IDL.Record({
+    'room_id' : IDL.Text,
+    'last_delivery_at' : IDL.Opt(IDL.Nat64),
+    'last_status' : IDL.Opt(IDL.Text),
+    'created_at' : IDL.Nat64,
+    'events' : IDL.Vec(HookEvent),
+    'access_token' : IDL.Text,
+    'homeserver' : IDL.Text,
+  });
+  const ApiUsageDay = IDL.Record({
+    'day' : IDL.Nat64,
+    'google_errors' : IDL.Nat32,
+    'google_calls' : IDL.Nat32,
+  });
+  const BookingDiscount = IDL.Record({
+    'code' : IDL.Text,
+    'percent' : IDL.Nat8,
+  });
+  const CounterSet = IDL.Record({
+    'bookings' : IDL.Nat64,
+    'cancelled_bookings' : IDL.Nat64,
+    'availabilities' : IDL.Nat64,
+  });
+  const Feature = IDL.Variant({
+    'Payments' : IDL.Null,
+    'Webhooks' : IDL.Null,
+    'MultipleCalendars' : IDL.Null,
+    'Teams' : IDL.Null,
+  });
+  const PlanSource = IDL.Variant({
+    'Icrc' : IDL.Null,
+    'Promo' : IDL.Null,
+    'Default' : IDL.Null,
+    'Admin' : IDL.Null,
+  });
+  const PlanTier = IDL.Variant({ 'Pro' : IDL.Null, 'Free' : IDL.Null });
+  const PlanInfo = IDL.Record({
+    'features' : IDL.Vec(Feature),
+    'source' : PlanSource,
+    'tier' : PlanTier,
+    'expires_at' : IDL.Opt(IDL.Nat64),
+  });
+  const QuotaStatus = IDL.Record({
+    'google_calls_today' : IDL.Nat32,
+    'bookings_today' : IDL.Nat32,
+    'availabilities' : IDL.Nat32,
+    'outcalls_this_hour' : IDL.Nat32,
+    'is_override' : IDL.Bool,
+    'limits' : QuotaLimits,
+  });
+  const ReferralStats = IDL.Record({
+    'code' : IDL.Text,
+    'reward_days_earned' : IDL.Nat32,
+    'referred_count' : IDL.Nat32,
+  });
+  const RegionPolicy = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'updated_by' : IDL.Principal,
+    'require_adult' : IDL.Bool,
+    'unsupported_regions' : IDL.Vec(IDL.Text),
+    'tenant_id' : IDL.Text,
+    'gated_features' : IDL.Vec(Feature),
+    'require_region' : IDL.Bool,
+  });
+  const SubscriptionStatus = IDL.Variant({
+    'Active' : IDL.Null,
+    'PastDue' : IDL.Null,
+    'Cancelled' : IDL.Null,
+  });
+  const Subscription = IDL.Record({
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : SubscriptionStatus,
+    'failed_attempts' : IDL.Nat32,
+    'last_block_index' : IDL.Opt(IDL.Nat64),
+    'owner' : IDL.Principal,
+    'created_at' : IDL.Nat64,
+    'next_charge_at' : IDL.Nat64,
+    'current_period_end' : IDL.Nat64,
+    'grace_until' : IDL.Opt(IDL.Nat64),
+    'ledger_canister' : IDL.Principal,
+    'amount' : IDL.Nat64,
+  });
+  const NoShowStats = IDL.Record({
+    'no_shows' : IDL.Nat64,
+    'attended' : IDL.Nat64,
+    'availability_id' : IDL.Text,
+    'no_show_rate_percent' : IDL.Nat8,
+    'past_bookings' : IDL.Nat64,
+  });
+  const Result_37 = IDL.Variant({ 'Ok' : NoShowStats, 'Err' : IDL.Text });
+  const DeliveryStatus = IDL.Variant({
+    'Failed' : IDL.Null,
+    'Delivered' : IDL.Null,
+  });
+  const NotificationChannel = IDL.Variant({
+    'Matrix' : IDL.Null,
+    'IntegrationHook' : IDL.Null,
+  });
+  const DeliveryRecord = IDL.Record({
+    'attempted_at' : IDL.Nat64,
+    'status' : DeliveryStatus,
+    'detail' : IDL.Text,
+    'event' : HookEvent,
+    'target' : IDL.Opt(IDL.Text),
+    'provider_message_id' : IDL.Opt(IDL.Text),
+    'channel' : NotificationChannel,
+  });
+  const Result_38 = IDL.Variant({
+    'Ok' : IDL.Vec(DeliveryRecord),
+    'Err' : IDL.Text,
+  });
+  const Result_39 = IDL.Variant({ 'Ok' : CounterSet, 'Err' : IDL.Text });
+  const OrgSetting = IDL.Variant({
+    'BusyPrivacy' : IDL.Null,
+    'FollowUps' : IDL.Null,
+    'Durations' : IDL.Null,
+    'Alignment' : IDL.Null,
+    'Locale' : IDL.Null,
+    'CancellationPolicy' : IDL.Null,
+  });
+  const OrgDefaults = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'updated_by' : IDL.Principal,
+    'cancellation_policy' : IDL.Opt(CancellationPolicy),
+    'locale' : IDL.Opt(Locale),
+    'enforced' : IDL.Vec(OrgSetting),
+    'tenant_id' : IDL.Text,
+    'follow_ups' : IDL.Opt(IDL.Vec(FollowUp)),
+    'durations' : IDL.Opt(IDL.Vec(DurationOption)),
+    'busy_privacy' : IDL.Opt(BusyPrivacy),
+    'alignment' : IDL.Opt(SlotAlignment),
+  });
+  const MemberLoad = IDL.Record({
+    'utilization_percent' : IDL.Opt(IDL.Nat32),
+    'principal' : IDL.Principal,
+    'offered_minutes' : IDL.Nat64,
+    'booked_minutes' : IDL.Nat64,
+    'availability_count' : IDL.Nat32,
+  });
+  const OrgLoadReport = IDL.Record({
+    'range_end' : IDL.Nat64,
+    'members' : IDL.Vec(MemberLoad),
+    'tenant_id' : IDL.Text,
+    'total_members' : IDL.Nat32,
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'range_start' : IDL.Nat64,
+    'next_offset' : IDL.Opt(IDL.Nat32),
+  });
+  const Result_40 = IDL.Variant({ 'Ok' : OrgLoadReport, 'Err' : IDL.Text });
+  const OriginConfig = IDL.Record({
+    'derivation_origin' : IDL.Opt(IDL.Text),
+    'brand_name' : IDL.Text,
+    'updated_at' : IDL.Nat64,
+    'support_email' : IDL.Opt(IDL.Text),
+    'origin' : IDL.Text,
+    'tenant_id' : IDL.Opt(IDL.Text),
+    'logo_url' : IDL.Opt(IDL.Text),
+    'default_provider' : IDL.Text,
+    'allowed_features' : IDL.Vec(Feature),
+    'primary_color' : IDL.Opt(IDL.Text),
+  });
+  const PlatformStats = IDL.Record({
+    'week_start' : IDL.Nat64,
+    'generated_at' : IDL.Nat64,
+    'total_availabilities' : IDL.Nat64,
+    'total_bookings' : IDL.Nat64,
+    'active_orgs' : IDL.Nat64,
+    'bookings_this_week' : IDL.Nat64,
+  });
   const OAuthProvider = IDL.Record({
     'response_type' : IDL.Text,
     'authorization_url' : IDL.Text,
@@ -89,26 +923,591 @@ export const idlFactory = ({ IDL }) => {
     'token_url' : IDL.Text,
     'client_id' : IDL.Text,
   });
-  const UserInfo = IDL.Record({
-    'principal' : IDL.Text,
-    'name' : IDL.Opt(IDL.Text),
-    'user_id' : IDL.Opt(IDL.Text),
-    'email' : IDL.Opt(IDL.Text),
+  const ReadCacheStatus = IDL.Record({
+    'last_error' : IDL.Opt(IDL.Text),
+    'pending' : IDL.Nat64,
+    'canister' : IDL.Opt(IDL.Principal),
+    'last_pushed_at' : IDL.Nat64,
   });
-  const PrepareDelegationRequest = IDL.Record({
-    'provider' : IDL.Text,
-    'origin' : IDL.Text,
-    'targets' : IDL.Opt(IDL.Vec(IDL.Principal)),
-    'max_time_to_live' : IDL.Nat64,
-    'session_public_key' : IDL.Vec(IDL.Nat8),
+  const Result_41 = IDL.Variant({ 'Ok' : ReadCacheStatus, 'Err' : IDL.Text });
+  const RebookInfo = IDL.Record({
+    'duration_minutes' : IDL.Nat32,
+    'guest_name' : IDL.Text,
+    'previous_booking_id' : IDL.Text,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'field_answers' : IDL.Vec(FieldAnswer),
+    'availability_id' : IDL.Text,
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const Result_42 = IDL.Variant({ 'Ok' : RebookInfo, 'Err' : IDL.Text });
+  const RebuildPhase = IDL.Variant({
+    'ResetCounters' : IDL.Null,
+    'Bookings' : IDL.Null,
+    'Done' : IDL.Null,
+    'Availabilities' : IDL.Null,
+    'AvailabilityLists' : IDL.Null,
+    'BookingLists' : IDL.Null,
+    'NotStarted' : IDL.Null,
+  });
+  const RebuildStatus = IDL.Record({
+    'availabilities_scanned' : IDL.Nat64,
+    'cursor' : IDL.Opt(IDL.Text),
+    'bookings_total' : IDL.Nat64,
+    'bookings_scanned' : IDL.Nat64,
+    'entries_fixed' : IDL.Nat64,
+    'phase' : RebuildPhase,
+    'completed_at' : IDL.Opt(IDL.Nat64),
+    'started_at' : IDL.Opt(IDL.Nat64),
+    'availabilities_total' : IDL.Nat64,
+  });
+  const Result_43 = IDL.Variant({ 'Ok' : RebuildStatus, 'Err' : IDL.Text });
+  const ComponentHealth = IDL.Variant({
+    'Down' : IDL.Null,
+    'Operational' : IDL.Null,
+    'Maintenance' : IDL.Null,
+    'Degraded' : IDL.Null,
+  });
+  const ComponentStatus = IDL.Record({
+    'component' : IDL.Text,
+    'detail' : IDL.Text,
+    'health' : ComponentHealth,
+  });
+  const ServiceStatus = IDL.Record({
+    'components' : IDL.Vec(ComponentStatus),
+    'overall' : ComponentHealth,
+    'checked_at' : IDL.Nat64,
+  });
+  const Result_44 = IDL.Variant({
+    'Ok' : IDL.Vec(AttendanceEntry),
+    'Err' : IDL.Text,
+  });
+  const VariantStats = IDL.Record({
+    'bookings' : IDL.Nat64,
+    'views' : IDL.Nat64,
+  });
+  const SlotExperiment = IDL.Record({
+    'a' : VariantStats,
+    'b' : VariantStats,
+    'starts_at' : IDL.Nat64,
+    'variant_b_slots' : IDL.Vec(TimeSlot),
+    'ends_at' : IDL.Nat64,
+    'created_at' : IDL.Nat64,
+    'created_by' : IDL.Principal,
+    'b_percent' : IDL.Nat8,
+    'availability_id' : IDL.Text,
+  });
+  const Result_45 = IDL.Variant({
+    'Ok' : IDL.Opt(SlotExperiment),
+    'Err' : IDL.Text,
+  });
+  const LargeRecord = IDL.Record({ 'key' : IDL.Text, 'bytes' : IDL.Nat64 });
+  const EntryStats = IDL.Record({
+    'largest' : IDL.Vec(LargeRecord),
+    'entries' : IDL.Nat64,
+    'encoded_bytes' : IDL.Nat64,
+  });
+  const StructureUsage = IDL.Record({
+    'allocated_growth' : IDL.Opt(IDL.Int64),
+    'entries_growth' : IDL.Opt(IDL.Int64),
+    'name' : IDL.Text,
+    'allocated_bytes' : IDL.Nat64,
+    'memory_id' : IDL.Nat8,
+    'stats' : IDL.Opt(EntryStats),
+  });
+  const StorageReport = IDL.Record({
+    'structures' : IDL.Vec(StructureUsage),
+    'generated_at' : IDL.Nat64,
+    'stable_bytes' : IDL.Nat64,
+    'heap_bytes' : IDL.Nat64,
+    'snapshot_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_46 = IDL.Variant({ 'Ok' : StorageReport, 'Err' : IDL.Text });
+  const SyncSchedulerStatus = IDL.Record({
+    'last_tick_synced' : IDL.Nat32,
+    'backoff_level' : IDL.Nat32,
+    'backoff_until' : IDL.Opt(IDL.Nat64),
+    'tracked' : IDL.Nat64,
+    'last_tick_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_47 = IDL.Variant({
+    'Ok' : SyncSchedulerStatus,
+    'Err' : IDL.Text,
+  });
+  const TermsConfig = IDL.Record({
+    'privacy_url' : IDL.Text,
+    'published_at' : IDL.Nat64,
+    'version' : IDL.Nat32,
+    'terms_url' : IDL.Text,
+  });
+  const TermsStatus = IDL.Record({
+    'accepted_version' : IDL.Opt(IDL.Nat32),
+    'needs_acceptance' : IDL.Bool,
+    'current' : TermsConfig,
+  });
+  const Provider = IDL.Variant({ 'Calendly' : IDL.Null, 'Google' : IDL.Null });
+  const BreakerState = IDL.Variant({
+    'Open' : IDL.Null,
+    'Closed' : IDL.Null,
+    'HalfOpen' : IDL.Null,
+  });
+  const BreakerStatus = IDL.Record({
+    'provider' : Provider,
+    'opened_at' : IDL.Opt(IDL.Nat64),
+    'state' : BreakerState,
+    'consecutive_failures' : IDL.Nat32,
+  });
+  const Result_48 = IDL.Variant({
+    'Ok' : IDL.Vec(BreakerStatus),
+    'Err' : IDL.Text,
+  });
+  const UserInfo = IDL.Record({
+    'principal' : IDL.Text,
+    'name' : IDL.Opt(IDL.Text),
+    'user_id' : IDL.Opt(IDL.Text),
+    'email' : IDL.Opt(IDL.Text),
+  });
+  const DelegationScope = IDL.Variant({
+    'ManageAvailabilities' : IDL.Null,
+    'ManageBookings' : IDL.Null,
+  });
+  const ManagementDelegation = IDL.Record({
+    'owner' : IDL.Principal,
+    'scopes' : IDL.Vec(DelegationScope),
+    'delegate' : IDL.Principal,
+    'granted_at' : IDL.Nat64,
+  });
+  const Result_49 = IDL.Variant({
+    'Ok' : IDL.Opt(ManagementDelegation),
+    'Err' : IDL.Text,
+  });
+  const RedeemSessionLinkRequest = IDL.Record({
+    'token' : IDL.Text,
+    'duration_minutes' : IDL.Opt(IDL.Nat32),
+    'guest_name' : IDL.Text,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'start_time' : IDL.Nat64,
+    'field_answers' : IDL.Opt(IDL.Vec(FieldAnswer)),
+    'guest_email' : IDL.Opt(IDL.Text),
+  });
+  const HttpRequest = IDL.Record({
+    'url' : IDL.Text,
+    'method' : IDL.Text,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+  });
+  const HttpResponse = IDL.Record({
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+    'upgrade' : IDL.Opt(IDL.Bool),
+    'status_code' : IDL.Nat16,
+  });
+  const ImportedBooking = IDL.Record({
+    'title' : IDL.Text,
+    'skipped_reason' : IDL.Opt(IDL.Text),
+    'end_time' : IDL.Nat64,
+    'start_time' : IDL.Nat64,
+    'availability_title' : IDL.Opt(IDL.Text),
+    'booking_id' : IDL.Opt(IDL.Text),
+  });
+  const ImportedAvailability = IDL.Record({
+    'timezone' : IDL.Text,
+    'title' : IDL.Text,
+    'duration_minutes' : IDL.Nat32,
+    'slots' : IDL.Vec(TimeSlot),
+    'availability_id' : IDL.Opt(IDL.Text),
+  });
+  const CalendlyImportReport = IDL.Record({
+    'bookings' : IDL.Vec(ImportedBooking),
+    'availabilities' : IDL.Vec(ImportedAvailability),
+    'dry_run' : IDL.Bool,
+  });
+  const Result_50 = IDL.Variant({
+    'Ok' : CalendlyImportReport,
+    'Err' : IDL.Text,
+  });
+  const LegalHold = IDL.Record({
+    'member' : IDL.Principal,
+    'placed_at' : IDL.Nat64,
+    'placed_by' : IDL.Principal,
+    'tenant_id' : IDL.Text,
+    'lifted_at' : IDL.Opt(IDL.Nat64),
+    'lifted_by' : IDL.Opt(IDL.Principal),
+    'reason' : IDL.Text,
+  });
+  const Result_51 = IDL.Variant({ 'Ok' : LegalHold, 'Err' : IDL.Text });
+  const Result_52 = IDL.Variant({
+    'Ok' : IDL.Vec(Availability),
+    'Err' : IDL.Text,
+  });
+  const BookingPage = IDL.Record({
+    'bookings' : IDL.Vec(Booking),
+    'truncated' : IDL.Bool,
+    'next_cursor' : IDL.Opt(IDL.Text),
+  });
+  const Result_53 = IDL.Variant({ 'Ok' : BookingPage, 'Err' : IDL.Text });
+  const ConflictResolution = IDL.Variant({
+    'RestoreWeeekaly' : IDL.Null,
+    'AcceptGoogle' : IDL.Null,
+  });
+  const CalendarConflict = IDL.Record({
+    'detected_at' : IDL.Nat64,
+    'resolution' : IDL.Opt(ConflictResolution),
+    'google_start' : IDL.Nat64,
+    'booking_end' : IDL.Nat64,
+    'event_id' : IDL.Text,
+    'booking_start' : IDL.Nat64,
+    'booking_id' : IDL.Text,
+    'google_end' : IDL.Nat64,
+    'resolved_at' : IDL.Opt(IDL.Nat64),
+  });
+  const CorruptRecord = IDL.Record({
+    'id' : IDL.Text,
+    'type_name' : IDL.Text,
+    'error' : IDL.Text,
+    'bytes' : IDL.Vec(IDL.Nat8),
+    'first_seen_at' : IDL.Nat64,
+  });
+  const CorruptRecordsPage = IDL.Record({
+    'records' : IDL.Vec(CorruptRecord),
+    'truncated' : IDL.Bool,
+    'next_cursor' : IDL.Opt(IDL.Text),
+  });
+  const Result_54 = IDL.Variant({
+    'Ok' : CorruptRecordsPage,
+    'Err' : IDL.Text,
+  });
+  const JobStatus = IDL.Variant({
+    'DeadLettered' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const SideEffect = IDL.Variant({
+    'Matrix' : IDL.Record({
+      'owner' : IDL.Principal,
+      'event' : HookEvent,
+      'booking_id' : IDL.Text,
+    }),
+    'IntegrationHook' : IDL.Record({
+      'event' : HookEvent,
+      'hook_id' : IDL.Text,
+      'booking_id' : IDL.Text,
+    }),
+  });
+  const OutboxJob = IDL.Record({
+    'id' : IDL.Nat64,
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : JobStatus,
+    'updated_at' : IDL.Nat64,
+    'next_attempt_at' : IDL.Nat64,
+    'attempts' : IDL.Nat32,
+    'effect' : SideEffect,
+    'created_at' : IDL.Nat64,
+    'trace_id' : IDL.Opt(IDL.Text),
+  });
+  const FailedJobsPage = IDL.Record({
+    'jobs' : IDL.Vec(OutboxJob),
+    'next_cursor' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_55 = IDL.Variant({ 'Ok' : FailedJobsPage, 'Err' : IDL.Text });
+  const ShareRecipient = IDL.Variant({
+    'Email' : IDL.Text,
+    'Principal' : IDL.Principal,
+  });
+  const AvailabilityTemplate = IDL.Record({
+    'timezone' : IDL.Text,
+    'title' : IDL.Text,
+    'cancellation_policy' : IDL.Opt(CancellationPolicy),
+    'locale' : IDL.Opt(Locale),
+    'description' : IDL.Text,
+    'seats' : IDL.Opt(IDL.Nat32),
+    'slots' : IDL.Vec(TimeSlot),
+    'event_fields' : IDL.Opt(IDL.Vec(EventField)),
+    'follow_ups' : IDL.Opt(IDL.Vec(FollowUp)),
+    'durations' : IDL.Opt(IDL.Vec(DurationOption)),
+    'busy_privacy' : IDL.Opt(BusyPrivacy),
+    'alignment' : IDL.Opt(SlotAlignment),
+  });
+  const AvailabilityShare = IDL.Record({
+    'id' : IDL.Text,
+    'to' : ShareRecipient,
+    'from' : IDL.Principal,
+    'created_at' : IDL.Nat64,
+    'source_availability_id' : IDL.Text,
+    'template' : AvailabilityTemplate,
+  });
+  const Result_56 = IDL.Variant({
+    'Ok' : IDL.Vec(Candidate),
+    'Err' : IDL.Text,
+  });
+  const Result_57 = IDL.Variant({
+    'Ok' : IDL.Vec(LegalHold),
+    'Err' : IDL.Text,
+  });
+  const Result_58 = IDL.Variant({
+    'Ok' : IDL.Vec(MaintenanceJob),
+    'Err' : IDL.Text,
+  });
+  const EntryKind = IDL.Variant({
+    'BookingPayment' : IDL.Null,
+    'Reversal' : IDL.Null,
+    'Withdrawal' : IDL.Null,
+    'BookingRefund' : IDL.Null,
+  });
+  const BookAccount = IDL.Variant({
+    'Custody' : IDL.Null,
+    'Owner' : IDL.Principal,
+  });
+  const JournalEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'kind' : EntryKind,
+    'reference' : IDL.Text,
+    'created_at' : IDL.Nat64,
+    'credit' : BookAccount,
+    'ledger_canister' : IDL.Principal,
+    'amount' : IDL.Nat64,
+    'debit' : BookAccount,
+  });
+  const NotificationTemplate = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'updated_by' : IDL.Principal,
+    'subject' : IDL.Text,
+    'body' : IDL.Text,
+    'kind' : TemplateKind,
+    'scope' : TemplateScope,
+  });
+  const Result_59 = IDL.Variant({
+    'Ok' : IDL.Vec(NotificationTemplate),
+    'Err' : IDL.Text,
+  });
+  const Result_60 = IDL.Variant({
+    'Ok' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Text)),
+    'Err' : IDL.Text,
+  });
+  const Result_61 = IDL.Variant({
+    'Ok' : IDL.Vec(OriginConfig),
+    'Err' : IDL.Text,
+  });
+  const MovedRecords = IDL.Record({ 'count' : IDL.Nat32, 'store' : IDL.Text });
+  const PrincipalMove = IDL.Record({
+    'to' : IDL.Principal,
+    'bookings' : IDL.Nat32,
+    'records' : IDL.Opt(IDL.Vec(MovedRecords)),
+    'from' : IDL.Principal,
+    'availabilities' : IDL.Nat32,
+    'moved_at' : IDL.Nat64,
+  });
+  const Result_62 = IDL.Variant({
+    'Ok' : IDL.Vec(PrincipalMove),
+    'Err' : IDL.Text,
+  });
+  const Result_63 = IDL.Variant({
+    'Ok' : IDL.Vec(PromoCode),
+    'Err' : IDL.Text,
+  });
+  const ParkedStripeEvent = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'attempts' : IDL.Nat32,
+    'error' : IDL.Text,
+    'parked_at' : IDL.Nat64,
+    'event_id' : IDL.Text,
+    'event_type' : IDL.Text,
+    'payload' : IDL.Text,
+  });
+  const Result_64 = IDL.Variant({
+    'Ok' : IDL.Vec(ParkedStripeEvent),
+    'Err' : IDL.Text,
+  });
+  const AvailabilityPage = IDL.Record({
+    'truncated' : IDL.Bool,
+    'availabilities' : IDL.Vec(Availability),
+    'next_cursor' : IDL.Opt(IDL.Text),
+  });
+  const Result_65 = IDL.Variant({ 'Ok' : AvailabilityPage, 'Err' : IDL.Text });
+  const TenantMember = IDL.Record({
+    'principal' : IDL.Principal,
+    'joined_at' : IDL.Nat64,
+  });
+  const Result_66 = IDL.Variant({
+    'Ok' : IDL.Vec(TenantMember),
+    'Err' : IDL.Text,
+  });
+  const Result_67 = IDL.Variant({ 'Ok' : IDL.Vec(Tenant), 'Err' : IDL.Text });
+  const TrashedItem = IDL.Variant({
+    'Availability' : Availability,
+    'Booking' : Booking,
+  });
+  const TrashEntry = IDL.Record({
+    'id' : IDL.Text,
+    'purge_at' : IDL.Nat64,
+    'owner' : IDL.Principal,
+    'item' : TrashedItem,
+    'deleted_at' : IDL.Nat64,
+    'deleted_by' : IDL.Principal,
+  });
+  const Result_68 = IDL.Variant({ 'Ok' : PrincipalMove, 'Err' : IDL.Text });
+  const PrepareDelegationRequest = IDL.Record({
+    'provider' : IDL.Text,
+    'origin' : IDL.Text,
+    'targets' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'max_time_to_live' : IDL.Nat64,
+    'session_public_key' : IDL.Vec(IDL.Nat8),
     'id_token' : IDL.Text,
   });
   const PrepareDelegationResponse = IDL.Record({ 'expire_at' : IDL.Nat64 });
-  const Result_5 = IDL.Variant({
+  const Result_69 = IDL.Variant({
     'Ok' : PrepareDelegationResponse,
     'Err' : IDL.Text,
   });
+  const AvailabilitySettingsPreview = IDL.Record({
+    'timezone' : IDL.Opt(IDL.Text),
+    'slots' : IDL.Opt(IDL.Vec(TimeSlot)),
+    'durations' : IDL.Opt(IDL.Vec(DurationOption)),
+    'alignment' : IDL.Opt(SlotAlignment),
+  });
+  const Result_70 = IDL.Variant({ 'Ok' : TermsConfig, 'Err' : IDL.Text });
+  const Result_71 = IDL.Variant({ 'Ok' : IDL.Vec(IDL.Text), 'Err' : IDL.Text });
+  const Result_72 = IDL.Variant({ 'Ok' : PromoReward, 'Err' : IDL.Text });
   const RefreshTokenRequest = IDL.Record({ 'refresh_token' : IDL.Text });
+  const Result_73 = IDL.Variant({ 'Ok' : IDL.Nat32, 'Err' : IDL.Text });
+  const Result_74 = IDL.Variant({ 'Ok' : CalendarConflict, 'Err' : IDL.Text });
+  const Result_75 = IDL.Variant({ 'Ok' : TrashEntry, 'Err' : IDL.Text });
+  const Result_76 = IDL.Variant({ 'Ok' : OutboxJob, 'Err' : IDL.Text });
+  const SelfTestCheck = IDL.Record({
+    'name' : IDL.Text,
+    'detail' : IDL.Text,
+    'passed' : IDL.Bool,
+  });
+  const SelfTestReport = IDL.Record({
+    'ran_at' : IDL.Nat64,
+    'checks' : IDL.Vec(SelfTestCheck),
+    'passed' : IDL.Bool,
+  });
+  const Result_77 = IDL.Variant({ 'Ok' : SelfTestReport, 'Err' : IDL.Text });
+  const SearchResultKind = IDL.Variant({
+    'Availability' : IDL.Null,
+    'Booking' : IDL.Null,
+    'Contact' : IDL.Null,
+  });
+  const SearchResult = IDL.Record({
+    'id' : IDL.Text,
+    'title' : IDL.Text,
+    'kind' : SearchResultKind,
+    'score' : IDL.Nat32,
+    'subtitle' : IDL.Text,
+  });
+  const Result_78 = IDL.Variant({
+    'Ok' : IDL.Vec(SearchResult),
+    'Err' : IDL.Text,
+  });
+  const SetMatrixNotifierRequest = IDL.Record({
+    'room_id' : IDL.Text,
+    'events' : IDL.Vec(HookEvent),
+    'access_token' : IDL.Text,
+    'homeserver' : IDL.Text,
+  });
+  const Result_79 = IDL.Variant({ 'Ok' : MatrixNotifier, 'Err' : IDL.Text });
+  const Result_80 = IDL.Variant({ 'Ok' : RegionDeclaration, 'Err' : IDL.Text });
+  const SetTaxProfileRequest = IDL.Record({
+    'tax_rate_bps' : IDL.Nat32,
+    'vat_id' : IDL.Opt(IDL.Text),
+    'business_name' : IDL.Text,
+    'business_address' : IDL.Text,
+    'country_code' : IDL.Text,
+    'prices_include_tax' : IDL.Bool,
+  });
+  const Result_81 = IDL.Variant({ 'Ok' : TaxProfile, 'Err' : IDL.Text });
+  const Result_82 = IDL.Variant({
+    'Ok' : NotificationTemplate,
+    'Err' : IDL.Text,
+  });
+  const SetOrgDefaultsRequest = IDL.Record({
+    'cancellation_policy' : IDL.Opt(CancellationPolicy),
+    'locale' : IDL.Opt(Locale),
+    'enforced' : IDL.Vec(OrgSetting),
+    'follow_ups' : IDL.Opt(IDL.Vec(FollowUp)),
+    'durations' : IDL.Opt(IDL.Vec(DurationOption)),
+    'busy_privacy' : IDL.Opt(BusyPrivacy),
+    'alignment' : IDL.Opt(SlotAlignment),
+  });
+  const Result_83 = IDL.Variant({ 'Ok' : OrgDefaults, 'Err' : IDL.Text });
+  const SetOriginConfigRequest = IDL.Record({
+    'derivation_origin' : IDL.Opt(IDL.Text),
+    'brand_name' : IDL.Text,
+    'support_email' : IDL.Opt(IDL.Text),
+    'origin' : IDL.Text,
+    'tenant_id' : IDL.Opt(IDL.Text),
+    'logo_url' : IDL.Opt(IDL.Text),
+    'default_provider' : IDL.Text,
+    'allowed_features' : IDL.Vec(Feature),
+    'primary_color' : IDL.Opt(IDL.Text),
+  });
+  const Result_84 = IDL.Variant({ 'Ok' : OriginConfig, 'Err' : IDL.Text });
+  const SetRegionPolicyRequest = IDL.Record({
+    'require_adult' : IDL.Bool,
+    'unsupported_regions' : IDL.Vec(IDL.Text),
+    'tenant_id' : IDL.Text,
+    'gated_features' : IDL.Vec(Feature),
+    'require_region' : IDL.Bool,
+  });
+  const Result_85 = IDL.Variant({ 'Ok' : RegionPolicy, 'Err' : IDL.Text });
+  const StripeConfig = IDL.Record({ 'webhook_secret' : IDL.Opt(IDL.Text) });
+  const Result_86 = IDL.Variant({ 'Ok' : AvailabilityShare, 'Err' : IDL.Text });
+  const FreeSlotSnapshot = IDL.Record({
+    'token' : IDL.Text,
+    'guest_timezone' : IDL.Opt(IDL.Text),
+    'slots' : IDL.Vec(FreeSlot),
+    'availability_id' : IDL.Text,
+    'expires_at' : IDL.Nat64,
+  });
+  const Result_87 = IDL.Variant({ 'Ok' : FreeSlotSnapshot, 'Err' : IDL.Text });
+  const StartExperimentRequest = IDL.Record({
+    'starts_at' : IDL.Opt(IDL.Nat64),
+    'variant_b_slots' : IDL.Vec(TimeSlot),
+    'ends_at' : IDL.Nat64,
+    'b_percent' : IDL.Nat8,
+    'availability_id' : IDL.Text,
+  });
+  const Result_88 = IDL.Variant({ 'Ok' : SlotExperiment, 'Err' : IDL.Text });
+  const Result_89 = IDL.Variant({ 'Ok' : Subscription, 'Err' : IDL.Text });
+  const SuggestionConstraints = IDL.Record({
+    'range_end' : IDL.Nat64,
+    'preferred_end_hour' : IDL.Opt(IDL.Nat8),
+    'max_results' : IDL.Opt(IDL.Nat32),
+    'preferred_start_hour' : IDL.Opt(IDL.Nat8),
+    'step_minutes' : IDL.Opt(IDL.Nat32),
+    'range_start' : IDL.Nat64,
+  });
+  const MeetingSuggestion = IDL.Record({
+    'reasons' : IDL.Vec(IDL.Text),
+    'end_time' : IDL.Nat64,
+    'score' : IDL.Nat32,
+    'start_time' : IDL.Nat64,
+  });
+  const Result_90 = IDL.Variant({
+    'Ok' : IDL.Vec(MeetingSuggestion),
+    'Err' : IDL.Text,
+  });
+  const SweepReport = IDL.Record({
+    'bookings' : IDL.Vec(IDL.Text),
+    'availabilities' : IDL.Vec(IDL.Text),
+  });
+  const Result_91 = IDL.Variant({ 'Ok' : SweepReport, 'Err' : IDL.Text });
+  const PropValue = IDL.Variant({
+    'Bool' : IDL.Bool,
+    'Text' : IDL.Text,
+    'Number' : IDL.Int64,
+  });
+  const EventProp = IDL.Record({ 'key' : IDL.Text, 'value' : PropValue });
+  const Result_92 = IDL.Variant({ 'Ok' : IDL.Bool, 'Err' : IDL.Text });
+  const HttpHeader = IDL.Record({ 'value' : IDL.Text, 'name' : IDL.Text });
+  const HttpResponse_1 = IDL.Record({
+    'status' : IDL.Nat,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HttpHeader),
+  });
+  const TransformArgs = IDL.Record({
+    'context' : IDL.Vec(IDL.Nat8),
+    'response' : HttpResponse_1,
+  });
   const UpdateAvailabilityRequest = IDL.Record({
     'id' : IDL.Text,
     'timezone' : IDL.Opt(IDL.Text),
@@ -127,64 +1526,712 @@ export const idlFactory = ({ IDL }) => {
     'event_id' : IDL.Text,
     'location' : IDL.Opt(IDL.Text),
   });
+  const ApiError = IDL.Variant({
+    'InvalidInput' : IDL.Text,
+    'UpstreamUnavailable' : IDL.Text,
+    'NotFound' : IDL.Text,
+    'Unauthorized' : IDL.Text,
+    'FeatureNotAvailable' : IDL.Text,
+    'QuotaExceeded' : IDL.Text,
+  });
+  const Result_93 = IDL.Variant({ 'Ok' : Booking, 'Err' : ApiError });
+  const Result_94 = IDL.Variant({ 'Ok' : Availability, 'Err' : ApiError });
+  const Result_95 = IDL.Variant({ 'Ok' : IDL.Vec(FreeSlot), 'Err' : ApiError });
+  const StructureCheck = IDL.Record({
+    'name' : IDL.Text,
+    'keys_match' : IDL.Bool,
+    'entries_after' : IDL.Nat64,
+    'entries_before' : IDL.Nat64,
+    'values_match' : IDL.Bool,
+  });
+  const UpgradeVerification = IDL.Record({
+    'ok' : IDL.Bool,
+    'structures' : IDL.Vec(StructureCheck),
+    'snapshot_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_96 = IDL.Variant({
+    'Ok' : UpgradeVerification,
+    'Err' : IDL.Text,
+  });
+  const Account = IDL.Record({
+    'owner' : IDL.Principal,
+    'subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+  const Result_97 = IDL.Variant({ 'Ok' : IDL.Nat64, 'Err' : IDL.Text });
   return IDL.Service({
+    'accept_availability_share' : IDL.Func([IDL.Text], [Result], []),
+    'accept_terms' : IDL.Func([IDL.Nat32], [Result_1], []),
+    'add_interview_candidate' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_2],
+        [],
+      ),
+    'add_org_busy_block' : IDL.Func(
+        [IDL.Text, OrgBusyBlockRequest],
+        [Result_3],
+        [],
+      ),
+    'api_version' : IDL.Func([], [ApiVersionInfo], ['query']),
+    'book_again' : IDL.Func([IDL.Text, CreateBookingRequest], [Result_4], []),
+    'book_interview_round' : IDL.Func([BookRoundRequest], [Result_4], []),
+    'book_next_available' : IDL.Func([IDL.Text, IDL.Text], [Result_4], []),
+    'book_together' : IDL.Func([GroupBookingRequest], [Result_5], []),
+    'book_with_one_time_link' : IDL.Func([BookWithLinkRequest], [Result_4], []),
+    'book_with_resource' : IDL.Func(
+        [CreateBookingRequest, IDL.Text],
+        [Result_4],
+        [],
+      ),
+    'book_with_snapshot' : IDL.Func(
+        [IDL.Text, CreateBookingRequest],
+        [Result_4],
+        [],
+      ),
+    'book_with_team' : IDL.Func([TeamBookingRequest], [Result_4], []),
+    'cancel_booking' : IDL.Func([IDL.Text], [Result_4], []),
+    'cancel_maintenance_job' : IDL.Func([IDL.Nat64], [Result_6], []),
+    'cancel_subscription' : IDL.Func([], [Result_7], []),
+    'check_booking_conflict' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_7],
+        ['query'],
+      ),
+    'check_in' : IDL.Func([IDL.Text, IDL.Text], [Result_8], []),
     'cleanup_expired_sessions' : IDL.Func([], [IDL.Nat64], []),
+    'cleanup_orphaned_tokens' : IDL.Func([], [Result_9], []),
+    'clear_my_tax_profile' : IDL.Func([], [], []),
+    'create_api_key' : IDL.Func([CreateApiKeyRequest], [Result_10], []),
     'create_availability' : IDL.Func([CreateAvailabilityRequest], [Result], []),
-    'create_calendar_event' : IDL.Func([CreateEventRequest], [Result_1], []),
-    'delete_availability' : IDL.Func([IDL.Text], [Result_2], []),
-    'delete_calendar_event' : IDL.Func([IDL.Text], [Result_2], []),
-    'exchange_oauth_code' : IDL.Func([ExchangeCodeRequest], [Result_3], []),
-    'get_availability' : IDL.Func([IDL.Text], [Result], ['query']),
+    'create_booking' : IDL.Func([CreateBookingRequest], [Result_4], []),
+    'create_calendar_event' : IDL.Func([CreateEventRequest], [Result_11], []),
+    'create_discord_link_code' : IDL.Func([], [Result_11], []),
+    'create_integration_hook' : IDL.Func(
+        [CreateIntegrationHookRequest],
+        [Result_12],
+        [],
+      ),
+    'create_interview_pipeline' : IDL.Func(
+        [CreatePipelineRequest],
+        [Result_13],
+        [],
+      ),
+    'create_one_time_link' : IDL.Func([IDL.Text], [Result_14], []),
+    'create_promo_code' : IDL.Func(
+        [IDL.Text, PromoReward, IDL.Nat32, IDL.Opt(IDL.Nat64)],
+        [Result_15],
+        [],
+      ),
+    'create_resource' : IDL.Func([IDL.Text, ResourceRequest], [Result_16], []),
+    'create_session_link' : IDL.Func(
+        [CreateSessionLinkRequest],
+        [Result_17],
+        [],
+      ),
+    'create_team' : IDL.Func([TeamRequest], [Result_18], []),
+    'create_tenant' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Vec(IDL.Principal)],
+        [Result_19],
+        [],
+      ),
+    'decline_availability_share' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_availability' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_booking' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_calendar_event' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_integration_hook' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_matrix_notifier' : IDL.Func([], [Result_7], []),
+    'delete_notification_template' : IDL.Func(
+        [TemplateScope, TemplateKind],
+        [Result_7],
+        [],
+      ),
+    'delete_org_busy_block' : IDL.Func([IDL.Text, IDL.Text], [Result_7], []),
+    'delete_org_defaults' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_origin_alias' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_origin_config' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_promo_code' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_resource' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_team' : IDL.Func([IDL.Text], [Result_7], []),
+    'delete_tenant_availability' : IDL.Func(
+        [IDL.Text, IDL.Text],
+        [Result_7],
+        [],
+      ),
+    'discard_job' : IDL.Func([IDL.Nat64], [Result_7], []),
+    'discard_stripe_event' : IDL.Func([IDL.Text], [Result_7], []),
+    'exchange_oauth_code' : IDL.Func([ExchangeCodeRequest], [Result_20], []),
+    'export_availability_definition' : IDL.Func(
+        [IDL.Text],
+        [Result_21],
+        ['query'],
+      ),
+    'export_my_bookings_csv' : IDL.Func([], [IDL.Text], ['query']),
+    'export_my_data' : IDL.Func([], [MyDataExport], ['query']),
+    'get_analytics_config' : IDL.Func([], [Result_22], ['query']),
+    'get_api_usage_report' : IDL.Func(
+        [IDL.Opt(IDL.Nat64)],
+        [Result_23],
+        ['query'],
+      ),
+    'get_availability' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result],
+        ['query'],
+      ),
+    'get_availability_qr_svg' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_11],
+        ['query'],
+      ),
+    'get_billing_config' : IDL.Func([], [BillingConfig], ['query']),
+    'get_booking' : IDL.Func([IDL.Text], [Result_4], ['query']),
+    'get_booking_attendees' : IDL.Func([IDL.Text], [Result_24], ['query']),
+    'get_booking_history' : IDL.Func([IDL.Text], [Result_25], ['query']),
+    'get_booking_ics' : IDL.Func([IDL.Text], [Result_11], ['query']),
+    'get_booking_receipt' : IDL.Func([IDL.Text], [Result_26], ['query']),
+    'get_booking_schema' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_27],
+        ['query'],
+      ),
+    'get_calendar_connection' : IDL.Func([], [CalendarConnection], ['query']),
     'get_caller' : IDL.Func([], [IDL.Text], ['query']),
-    'get_delegation' : IDL.Func([GetDelegationRequest], [Result_4], ['query']),
+    'get_cancellation_quote' : IDL.Func([IDL.Text], [Result_28], ['query']),
+    'get_candidate_status' : IDL.Func([IDL.Text], [Result_2], ['query']),
+    'get_check_in_token' : IDL.Func([IDL.Text], [Result_11], []),
+    'get_common_free_slots' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Nat64, IDL.Nat64, IDL.Bool],
+        [Result_29],
+        ['query'],
+      ),
+    'get_default_quotas' : IDL.Func([], [QuotaLimits], ['query']),
+    'get_delegate_audit_log' : IDL.Func(
+        [],
+        [IDL.Vec(DelegateAction)],
+        ['query'],
+      ),
+    'get_delegate_audit_log_page' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [Result_30],
+        ['query'],
+      ),
+    'get_delegation' : IDL.Func([GetDelegationRequest], [Result_31], ['query']),
+    'get_discord_config' : IDL.Func([], [DiscordConfig], ['query']),
+    'get_earnings_balance' : IDL.Func(
+        [],
+        [IDL.Vec(EarningsBalance)],
+        ['query'],
+      ),
+    'get_error_catalog' : IDL.Func([], [IDL.Vec(ErrorCatalogEntry)], ['query']),
+    'get_event_counts' : IDL.Func(
+        [IDL.Opt(IDL.Text), IDL.Nat64, IDL.Nat64],
+        [Result_32],
+        ['query'],
+      ),
+    'get_free_slots' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Nat64,
+          IDL.Nat64,
+          IDL.Opt(IDL.Nat32),
+          IDL.Opt(IDL.Text),
+          IDL.Opt(IDL.Text),
+        ],
+        [Result_33],
+        ['query'],
+      ),
+    'get_free_slots_with_resource' : IDL.Func(
+        [IDL.Text, IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_33],
+        ['query'],
+      ),
+    'get_index_migration_status' : IDL.Func([], [Result_34], ['query']),
+    'get_interview_round_slots' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_35],
+        ['query'],
+      ),
+    'get_link_stats' : IDL.Func([IDL.Text], [Result_36], ['query']),
+    'get_matrix_notifier' : IDL.Func([], [IDL.Opt(MatrixNotifier)], ['query']),
+    'get_my_api_usage' : IDL.Func([], [IDL.Vec(ApiUsageDay)], ['query']),
+    'get_my_booking_discount' : IDL.Func(
+        [],
+        [IDL.Opt(BookingDiscount)],
+        ['query'],
+      ),
+    'get_my_counters' : IDL.Func([], [CounterSet], ['query']),
+    'get_my_hijri_annotation' : IDL.Func([], [IDL.Bool], ['query']),
+    'get_my_locale' : IDL.Func([], [IDL.Opt(Locale)], ['query']),
+    'get_my_plan' : IDL.Func([], [PlanInfo], ['query']),
+    'get_my_quotas' : IDL.Func([], [QuotaStatus], ['query']),
+    'get_my_referral_code' : IDL.Func([], [ReferralStats], []),
+    'get_my_region' : IDL.Func([], [IDL.Opt(RegionDeclaration)], ['query']),
+    'get_my_region_policy' : IDL.Func([], [IDL.Opt(RegionPolicy)], ['query']),
+    'get_my_subscription' : IDL.Func([], [IDL.Opt(Subscription)], ['query']),
+    'get_my_tax_profile' : IDL.Func([], [IDL.Opt(TaxProfile)], ['query']),
+    'get_my_tenant' : IDL.Func([], [IDL.Text], ['query']),
+    'get_no_show_stats' : IDL.Func([IDL.Text], [Result_37], ['query']),
+    'get_notification_deliveries' : IDL.Func(
+        [IDL.Text],
+        [Result_38],
+        ['query'],
+      ),
+    'get_one_time_link' : IDL.Func([IDL.Text], [Result_14], ['query']),
+    'get_org_counters' : IDL.Func([IDL.Text], [Result_39], ['query']),
+    'get_org_defaults' : IDL.Func([], [IDL.Opt(OrgDefaults)], ['query']),
+    'get_org_load_report' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Nat64,
+          IDL.Nat64,
+          IDL.Nat32,
+          IDL.Nat32,
+          IDL.Opt(IDL.Text),
+        ],
+        [Result_40],
+        ['query'],
+      ),
+    'get_origin_config' : IDL.Func([IDL.Text], [OriginConfig], ['query']),
+    'get_orphaned_tokens' : IDL.Func([], [Result_9], ['query']),
+    'get_platform_stats' : IDL.Func([], [PlatformStats], ['query']),
     'get_providers' : IDL.Func([], [IDL.Vec(OAuthProvider)], ['query']),
+    'get_read_cache' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [IDL.Opt(IDL.Principal)],
+        ['query'],
+      ),
+    'get_read_cache_status' : IDL.Func([], [Result_41], ['query']),
+    'get_rebook_info' : IDL.Func([IDL.Text], [Result_42], ['query']),
+    'get_rebuild_progress' : IDL.Func([], [Result_43], ['query']),
+    'get_resource_free_slots' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_33],
+        ['query'],
+      ),
+    'get_service_status' : IDL.Func([], [ServiceStatus], ['query']),
+    'get_session_attendance' : IDL.Func(
+        [IDL.Text, IDL.Nat64],
+        [Result_44],
+        ['query'],
+      ),
     'get_session_count' : IDL.Func([], [IDL.Nat64], ['query']),
+    'get_session_link' : IDL.Func([IDL.Text], [Result_17], ['query']),
+    'get_slot_experiment' : IDL.Func([IDL.Text], [Result_45], ['query']),
+    'get_storage_report' : IDL.Func([], [Result_46], ['query']),
+    'get_sync_scheduler_status' : IDL.Func([], [Result_47], ['query']),
+    'get_team' : IDL.Func([IDL.Text], [Result_18], ['query']),
+    'get_team_free_slots' : IDL.Func(
+        [IDL.Text, IDL.Nat64, IDL.Nat64],
+        [Result_33],
+        ['query'],
+      ),
+    'get_terms_status' : IDL.Func([], [TermsStatus], ['query']),
+    'get_upstream_status' : IDL.Func([], [Result_48], ['query']),
     'get_user_info' : IDL.Func([], [UserInfo], ['query']),
+    'grant_delegation' : IDL.Func(
+        [IDL.Principal, IDL.Vec(DelegationScope)],
+        [Result_49],
+        [],
+      ),
     'greet' : IDL.Func([IDL.Text], [IDL.Text], ['query']),
     'hello_world' : IDL.Func([], [IDL.Text], ['query']),
+    'hold_session_link_for_stripe' : IDL.Func(
+        [RedeemSessionLinkRequest],
+        [Result_4],
+        [],
+      ),
+    'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
+    'http_request_update' : IDL.Func([HttpRequest], [HttpResponse], []),
+    'import_from_calendly' : IDL.Func([IDL.Text, IDL.Bool], [Result_50], []),
     'is_authenticated' : IDL.Func([], [IDL.Bool], ['query']),
+    'lift_legal_hold' : IDL.Func([IDL.Text, IDL.Principal], [Result_51], []),
+    'list_availabilities_for' : IDL.Func(
+        [IDL.Principal],
+        [Result_52],
+        ['query'],
+      ),
+    'list_availability_bookings' : IDL.Func([IDL.Text], [Result_5], ['query']),
+    'list_availability_bookings_page' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_53],
+        ['query'],
+      ),
+    'list_bookings_for' : IDL.Func([IDL.Principal], [Result_5], ['query']),
+    'list_calendar_conflicts' : IDL.Func(
+        [],
+        [IDL.Vec(CalendarConflict)],
+        ['query'],
+      ),
+    'list_corrupt_records' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [Result_54],
+        ['query'],
+      ),
+    'list_delegated_to_me' : IDL.Func(
+        [],
+        [IDL.Vec(ManagementDelegation)],
+        ['query'],
+      ),
+    'list_failed_jobs' : IDL.Func([IDL.Opt(IDL.Nat64)], [Result_55], ['query']),
+    'list_incoming_availability_shares' : IDL.Func(
+        [],
+        [IDL.Vec(AvailabilityShare)],
+        ['query'],
+      ),
+    'list_interview_candidates' : IDL.Func([IDL.Text], [Result_56], ['query']),
+    'list_interview_pipelines' : IDL.Func(
+        [],
+        [IDL.Vec(InterviewPipeline)],
+        ['query'],
+      ),
+    'list_legal_holds' : IDL.Func([IDL.Text], [Result_57], ['query']),
+    'list_maintenance_jobs' : IDL.Func([], [Result_58], ['query']),
+    'list_my_api_keys' : IDL.Func([], [IDL.Vec(ApiKey)], ['query']),
+    'list_my_bookings' : IDL.Func([], [IDL.Vec(Booking)], ['query']),
+    'list_my_bookings_page' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [Result_53],
+        ['query'],
+      ),
+    'list_my_delegates' : IDL.Func(
+        [],
+        [IDL.Vec(ManagementDelegation)],
+        ['query'],
+      ),
+    'list_my_earnings_entries' : IDL.Func(
+        [],
+        [IDL.Vec(JournalEntry)],
+        ['query'],
+      ),
+    'list_my_integration_hooks' : IDL.Func(
+        [],
+        [IDL.Vec(IntegrationHook)],
+        ['query'],
+      ),
+    'list_my_one_time_links' : IDL.Func([], [IDL.Vec(OneTimeLink)], ['query']),
+    'list_my_session_links' : IDL.Func([], [IDL.Vec(SessionLink)], ['query']),
+    'list_notification_templates' : IDL.Func(
+        [TemplateScope],
+        [Result_59],
+        ['query'],
+      ),
+    'list_org_busy_blocks' : IDL.Func([], [IDL.Vec(OrgBusyBlock)], ['query']),
+    'list_origin_aliases' : IDL.Func([], [Result_60], ['query']),
+    'list_origin_configs' : IDL.Func([], [Result_61], ['query']),
+    'list_outgoing_availability_shares' : IDL.Func(
+        [],
+        [IDL.Vec(AvailabilityShare)],
+        ['query'],
+      ),
+    'list_principal_moves' : IDL.Func([], [Result_62], ['query']),
+    'list_promo_codes' : IDL.Func([], [Result_63], ['query']),
+    'list_resources' : IDL.Func([], [IDL.Vec(Resource)], ['query']),
+    'list_stripe_dead_letters' : IDL.Func([], [Result_64], ['query']),
+    'list_teams' : IDL.Func([], [IDL.Vec(Team)], ['query']),
+    'list_tenant_availabilities' : IDL.Func([IDL.Text], [Result_52], ['query']),
+    'list_tenant_availabilities_page' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_65],
+        ['query'],
+      ),
+    'list_tenant_users' : IDL.Func([IDL.Text], [Result_66], ['query']),
+    'list_tenants' : IDL.Func([], [Result_67], ['query']),
+    'list_trash' : IDL.Func([], [IDL.Vec(TrashEntry)], ['query']),
     'list_user_availabilities' : IDL.Func(
         [],
         [IDL.Vec(Availability)],
         ['query'],
       ),
-    'logout' : IDL.Func([IDL.Vec(IDL.Nat8)], [Result_2], []),
-    'prepare_delegation' : IDL.Func([PrepareDelegationRequest], [Result_5], []),
-    'refresh_google_token' : IDL.Func([RefreshTokenRequest], [Result_3], []),
-    'regenerate_availability_id' : IDL.Func([IDL.Text], [Result_1], []),
+    'list_user_availabilities_page' : IDL.Func(
+        [IDL.Opt(IDL.Text)],
+        [Result_65],
+        ['query'],
+      ),
+    'logout' : IDL.Func([IDL.Vec(IDL.Nat8)], [Result_7], []),
+    'migrate_principal' : IDL.Func(
+        [IDL.Principal, IDL.Principal],
+        [Result_68],
+        [],
+      ),
+    'place_legal_hold' : IDL.Func(
+        [IDL.Text, IDL.Principal, IDL.Text],
+        [Result_51],
+        [],
+      ),
+    'prepare_delegation' : IDL.Func(
+        [PrepareDelegationRequest],
+        [Result_69],
+        [],
+      ),
+    'preview_free_slots' : IDL.Func(
+        [
+          IDL.Text,
+          AvailabilitySettingsPreview,
+          IDL.Nat64,
+          IDL.Nat64,
+          IDL.Opt(IDL.Nat32),
+        ],
+        [Result_33],
+        ['query'],
+      ),
+    'preview_integration_payload' : IDL.Func(
+        [IDL.Text, IDL.Text],
+        [Result_11],
+        ['query'],
+      ),
+    'publish_terms' : IDL.Func(
+        [IDL.Nat32, IDL.Text, IDL.Text],
+        [Result_70],
+        [],
+      ),
+    'purge_corrupt_record' : IDL.Func([IDL.Text], [Result_7], []),
+    'quick_book' : IDL.Func([IDL.Text, IDL.Text, IDL.Text], [Result_11], []),
+    'quick_free' : IDL.Func([IDL.Text, IDL.Nat32], [Result_71], ['query']),
+    'rebuild_indices' : IDL.Func([], [Result_6], []),
+    'record_link_open' : IDL.Func([IDL.Text], [Result_7], []),
+    'redeem_code' : IDL.Func([IDL.Text], [Result_72], []),
+    'redeem_session_link' : IDL.Func(
+        [RedeemSessionLinkRequest],
+        [Result_4],
+        [],
+      ),
+    'refresh_google_token' : IDL.Func([RefreshTokenRequest], [Result_20], []),
+    'refresh_outcall_cache' : IDL.Func([], [IDL.Nat32], []),
+    'regenerate_availability_id' : IDL.Func([IDL.Text], [Result_11], []),
+    'replay_booking_events' : IDL.Func([], [Result_73], []),
+    'resend_notification' : IDL.Func(
+        [IDL.Text, NotificationChannel],
+        [Result_73],
+        [],
+      ),
+    'resolve_calendar_conflict' : IDL.Func(
+        [IDL.Text, ConflictResolution],
+        [Result_74],
+        [],
+      ),
+    'restore_from_trash' : IDL.Func([IDL.Text], [Result_75], []),
+    'retry_job' : IDL.Func([IDL.Nat64], [Result_76], []),
+    'retry_stripe_event' : IDL.Func([IDL.Text], [Result_7], []),
+    'revoke_api_key' : IDL.Func([IDL.Text], [Result_7], []),
+    'revoke_availability_share' : IDL.Func([IDL.Text], [Result_7], []),
+    'revoke_one_time_link' : IDL.Func([IDL.Text], [Result_7], []),
+    'revoke_session_link' : IDL.Func([IDL.Text], [Result_7], []),
+    'run_index_migration' : IDL.Func([IDL.Opt(IDL.Nat32)], [Result_34], []),
+    'run_self_test' : IDL.Func([], [Result_77], []),
     'search_availabilities_by_email' : IDL.Func(
-        [IDL.Text],
+        [IDL.Text, IDL.Opt(IDL.Text)],
         [IDL.Vec(Availability)],
         ['query'],
       ),
     'search_availabilities_by_principal' : IDL.Func(
-        [IDL.Principal],
+        [IDL.Principal, IDL.Opt(IDL.Text)],
         [IDL.Vec(Availability)],
         ['query'],
       ),
     'search_availabilities_by_username' : IDL.Func(
-        [IDL.Text],
+        [IDL.Text, IDL.Opt(IDL.Text)],
         [IDL.Vec(Availability)],
         ['query'],
       ),
     'search_by_emails' : IDL.Func(
-        [IDL.Vec(IDL.Text)],
+        [IDL.Vec(IDL.Text), IDL.Opt(IDL.Text)],
         [IDL.Vec(IDL.Vec(Availability))],
         ['query'],
       ),
     'search_by_usernames' : IDL.Func(
-        [IDL.Vec(IDL.Text)],
+        [IDL.Vec(IDL.Text), IDL.Opt(IDL.Text)],
         [IDL.Vec(IDL.Vec(Availability))],
         ['query'],
       ),
-    'set_favorite_availability' : IDL.Func([IDL.Text], [Result_2], []),
+    'search_my_data' : IDL.Func([IDL.Text], [Result_78], ['query']),
+    'set_analytics_config' : IDL.Func([AnalyticsConfig], [Result_7], []),
+    'set_availability_alignment' : IDL.Func(
+        [IDL.Text, IDL.Opt(SlotAlignment)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_busy_privacy' : IDL.Func(
+        [IDL.Text, BusyPrivacy],
+        [Result_7],
+        [],
+      ),
+    'set_availability_cancellation_policy' : IDL.Func(
+        [IDL.Text, IDL.Opt(CancellationPolicy)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_collaborators' : IDL.Func(
+        [IDL.Text, IDL.Vec(Collaborator)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_durations' : IDL.Func(
+        [IDL.Text, IDL.Vec(DurationOption)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_event_fields' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Vec(EventField))],
+        [Result_7],
+        [],
+      ),
+    'set_availability_follow_ups' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Vec(FollowUp))],
+        [Result_7],
+        [],
+      ),
+    'set_availability_kiosk' : IDL.Func(
+        [IDL.Text, IDL.Opt(KioskConfig)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_locale' : IDL.Func(
+        [IDL.Text, IDL.Opt(Locale)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_meeting_url' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_seats' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat32)],
+        [Result_7],
+        [],
+      ),
+    'set_availability_vip' : IDL.Func(
+        [IDL.Text, IDL.Opt(VipConfig)],
+        [Result_7],
+        [],
+      ),
+    'set_billing_config' : IDL.Func([BillingConfig], [Result_7], []),
+    'set_default_quotas' : IDL.Func([QuotaLimits], [Result_7], []),
+    'set_discord_config' : IDL.Func([DiscordConfig], [Result_7], []),
+    'set_favorite_availability' : IDL.Func([IDL.Text], [Result_7], []),
+    'set_matrix_notifier' : IDL.Func(
+        [SetMatrixNotifierRequest],
+        [Result_79],
+        [],
+      ),
+    'set_my_hijri_annotation' : IDL.Func([IDL.Bool], [], []),
+    'set_my_locale' : IDL.Func([Locale], [], []),
+    'set_my_region' : IDL.Func([IDL.Text, IDL.Bool], [Result_80], []),
+    'set_my_tax_profile' : IDL.Func([SetTaxProfileRequest], [Result_81], []),
+    'set_notification_template' : IDL.Func(
+        [TemplateScope, TemplateKind, IDL.Text, IDL.Text],
+        [Result_82],
+        [],
+      ),
+    'set_org_defaults' : IDL.Func(
+        [IDL.Text, SetOrgDefaultsRequest],
+        [Result_83],
+        [],
+      ),
+    'set_origin_alias' : IDL.Func([IDL.Text, IDL.Text], [Result_7], []),
+    'set_origin_config' : IDL.Func([SetOriginConfigRequest], [Result_84], []),
+    'set_quota_override' : IDL.Func(
+        [IDL.Principal, IDL.Opt(QuotaLimits)],
+        [Result_7],
+        [],
+      ),
+    'set_read_cache' : IDL.Func([IDL.Opt(IDL.Principal)], [Result_7], []),
+    'set_region_policy' : IDL.Func([SetRegionPolicyRequest], [Result_85], []),
+    'set_session_link_prices' : IDL.Func(
+        [IDL.Text, IDL.Vec(DurationPrice)],
+        [Result_17],
+        [],
+      ),
+    'set_session_link_stripe_price' : IDL.Func(
+        [IDL.Text, IDL.Opt(StripePrice)],
+        [Result_17],
+        [],
+      ),
+    'set_stripe_config' : IDL.Func([StripeConfig], [Result_7], []),
+    'set_tenant_admins' : IDL.Func(
+        [IDL.Text, IDL.Vec(IDL.Principal)],
+        [Result_19],
+        [],
+      ),
+    'set_user_plan' : IDL.Func(
+        [IDL.Principal, PlanTier, IDL.Opt(IDL.Nat64)],
+        [Result_7],
+        [],
+      ),
+    'share_availability_template' : IDL.Func(
+        [IDL.Text, IDL.Text],
+        [Result_86],
+        [],
+      ),
+    'snapshot_free_slots' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Nat64,
+          IDL.Nat64,
+          IDL.Opt(IDL.Nat32),
+          IDL.Opt(IDL.Text),
+          IDL.Opt(IDL.Text),
+        ],
+        [Result_87],
+        [],
+      ),
+    'snapshot_storage_report' : IDL.Func([], [Result_46], []),
+    'start_maintenance' : IDL.Func([MaintenanceTask], [Result_6], []),
+    'start_slot_experiment' : IDL.Func(
+        [StartExperimentRequest],
+        [Result_88],
+        [],
+      ),
+    'stop_slot_experiment' : IDL.Func([IDL.Text], [Result_88], []),
+    'subscribe_pro' : IDL.Func([], [Result_89], []),
+    'suggest_meeting_times' : IDL.Func(
+        [IDL.Vec(IDL.Text), IDL.Nat32, SuggestionConstraints],
+        [Result_90],
+        ['query'],
+      ),
+    'sweep_corrupt_records' : IDL.Func([], [Result_91], []),
+    'track_event' : IDL.Func([IDL.Text, IDL.Vec(EventProp)], [Result_92], []),
+    'transform_hook_response' : IDL.Func(
+        [TransformArgs],
+        [HttpResponse_1],
+        ['query'],
+      ),
+    'transform_import_response' : IDL.Func(
+        [TransformArgs],
+        [HttpResponse_1],
+        ['query'],
+      ),
+    'unlink_discord' : IDL.Func([], [IDL.Nat32], []),
     'update_availability' : IDL.Func([UpdateAvailabilityRequest], [Result], []),
     'update_availability_busy_times' : IDL.Func(
-        [IDL.Text, IDL.Vec(BusyTimeBlock)],
-        [Result_2],
+        [IDL.Text, IDL.Vec(FreeSlot)],
+        [Result_7],
         [],
       ),
-    'update_calendar_event' : IDL.Func([UpdateEventRequest], [Result_1], []),
+    'update_calendar_event' : IDL.Func([UpdateEventRequest], [Result_11], []),
+    'update_resource' : IDL.Func([IDL.Text, ResourceRequest], [Result_16], []),
+    'update_team' : IDL.Func([IDL.Text, TeamRequest], [Result_18], []),
+    'v2_create_booking' : IDL.Func([CreateBookingRequest], [Result_93], []),
+    'v2_get_availability' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Text)],
+        [Result_94],
+        ['query'],
+      ),
+    'v2_get_booking' : IDL.Func([IDL.Text], [Result_93], ['query']),
+    'v2_get_free_slots' : IDL.Func(
+        [
+          IDL.Text,
+          IDL.Nat64,
+          IDL.Nat64,
+          IDL.Opt(IDL.Nat32),
+          IDL.Opt(IDL.Text),
+          IDL.Opt(IDL.Text),
+        ],
+        [Result_95],
+        ['query'],
+      ),
+    'verify_upgrade' : IDL.Func([], [Result_96], ['query']),
+    'withdraw' : IDL.Func([IDL.Principal, IDL.Nat64, Account], [Result_97], []),
+    'withdraw_interview_candidate' : IDL.Func([IDL.Text], [Result_2], []),
   });
 };
 export const init = ({ IDL }) => { return []; };
@@ -18,7 +18,11 @@ import {
 import { toast } from "sonner";
 import { Toaster } from "./components/ui/sonner";
 import { useQueryClient } from "@tanstack/react-query";
-import { backendActor, setAuthenticatedActor } from "./utils/actor";
+import {
+  backendActor,
+  resetActor,
+  setAuthenticatedActor,
+} from "./utils/actor";
 import {
   useHelloWorld,
   useCalendarEvents,
@@ -339,6 +343,7 @@ function AppContent() {
 
   const handleLogout = () => {
     logoutOAuth();
+    resetActor();
     queryClient.clear();
     toast.success("Logged out successfully!");
     // Navigate to homepage which will show landing page
@@ -90,8 +90,8 @@ const serializeContext = (
 const fetchAvailabilities = async (emails: string[]) => {
   if (emails.length === 0) return [];
   try {
-    const { backendActor } = await import("../utils/actor");
-    const result = await backendActor.search_by_emails(emails);
+    const { backendActor, pageOrigin } = await import("../utils/actor");
+    const result = await backendActor.search_by_emails(emails, pageOrigin());
     return result.flat();
   } catch (error) {
     console.error("❌ Failed to fetch availabilities:", error);
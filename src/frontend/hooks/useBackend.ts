@@ -1,5 +1,5 @@
 import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
-import { backendActor } from "../utils/actor";
+import { backendActor, readPublicAvailability } from "../utils/actor";
 import { getValidAccessToken, clearTokens } from "../utils/tokenRefresh";
 
 // Define proper types for Google Calendar events
@@ -731,7 +731,7 @@ export function useAvailabilityById(
         throw new Error("Availability ID is required");
       }
 
-      const result = await backendActor.get_availability(id);
+      const result = await readPublicAvailability(id);
 
       if ("Err" in result) {
         throw new Error(result.Err);
@@ -43,6 +43,59 @@ export const backendActor = new Proxy({} as ActorSubclass<_SERVICE>, {
   },
 });
 
+/**
+ * The page's origin, passed with public reads so a signed-out visitor of a
+ * white-label deployment sees that deployment's users
+ */
+export const pageOrigin = (): [string] => [window.location.origin];
+
+// Signed-in users may see more of an availability than the cached public view
+let authenticated = false;
+let readCacheActor: Promise<ActorSubclass<_SERVICE> | null> | null = null;
+
+/**
+ * Actor for the read-cache canister, if the backend has one. The cache
+ * replies to get_availability exactly like the backend does for signed-out
+ * viewers, so it is called with the backend's interface.
+ */
+function getReadCacheActor(): Promise<ActorSubclass<_SERVICE> | null> {
+  if (!readCacheActor) {
+    readCacheActor = backendActor
+      .get_read_cache(pageOrigin())
+      .then((cacheId) =>
+        cacheId.length > 0
+          ? (createBackendCaster(
+              Actor.createActor(idlFactory, {
+                agent,
+                canisterId: cacheId[0],
+              }),
+            ) as ActorSubclass<_SERVICE>)
+          : null,
+      )
+      .catch(() => null);
+  }
+  return readCacheActor;
+}
+
+/**
+ * Fetch an availability for viewing, from the read cache when signed out.
+ * Falls back to the backend when there is no cache or it has no entry yet.
+ */
+export async function readPublicAvailability(id: string) {
+  const cache = authenticated ? null : await getReadCacheActor();
+  if (cache) {
+    try {
+      const result = await cache.get_availability(id, pageOrigin());
+      if ("Ok" in result) {
+        return result;
+      }
+    } catch (err) {
+      console.warn("⚠️ [actor] Read cache unavailable, using backend", err);
+    }
+  }
+  return backendActor.get_availability(id, pageOrigin());
+}
+
 /**
  * Update the backend actor with an authenticated identity
  *
@@ -90,6 +143,38 @@ export async function setAuthenticatedActor(identity: Identity): Promise<void> {
 
   // Update the actor in the holder (this updates all references via the Proxy)
   actorHolder.actor = createBackendCaster(rawActor) as ActorSubclass<_SERVICE>;
+  authenticated = true;
 
   console.log("✅ [actor] Backend actor updated with authenticated identity");
 }
+
+/**
+ * Return the backend actor to the anonymous identity on logout, so public
+ * reads go through the read cache again
+ */
+export function resetActor(): void {
+  agent = HttpAgent.createSync({
+    host:
+      process.env.DFX_NETWORK === "ic"
+        ? "https://icp-api.io"
+        : "http://localhost:4943",
+  });
+  if (process.env.DFX_NETWORK !== "ic") {
+    agent.fetchRootKey().catch((err) => {
+      console.warn(
+        "Unable to fetch root key. Check to ensure that your local replica is running",
+      );
+      console.error(err);
+    });
+  }
+
+  rawActor = Actor.createActor(idlFactory, {
+    agent,
+    canisterId,
+  });
+  actorHolder.actor = createBackendCaster(rawActor) as ActorSubclass<_SERVICE>;
+  authenticated = false;
+  readCacheActor = null;
+
+  console.log("✅ [actor] Backend actor reset to anonymous identity");
+}
@@ -145,8 +145,8 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // get_availability - Deserialize response
       if (prop === "get_availability") {
-        return async (id: string) => {
-          const result = await targetAny.get_availability(id);
+        return async (id: string, origin: [] | [string]) => {
+          const result = await targetAny.get_availability(id, origin);
           console.log({ result });
 
           if ("Err" in result) {
@@ -205,9 +205,11 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // search_availabilities_by_email - Deserialize array
       if (prop === "search_availabilities_by_email") {
-        return async (email: string) => {
-          const availabilities =
-            await targetAny.search_availabilities_by_email(email);
+        return async (email: string, origin: [] | [string]) => {
+          const availabilities = await targetAny.search_availabilities_by_email(
+            email,
+            origin,
+          );
 
           return availabilities.map((avail: any) => {
             const converted = convertBigIntToNumber(avail);
@@ -221,9 +223,9 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // search_availabilities_by_username - Deserialize array
       if (prop === "search_availabilities_by_username") {
-        return async (username: string) => {
+        return async (username: string, origin: [] | [string]) => {
           const availabilities =
-            await targetAny.search_availabilities_by_username(username);
+            await targetAny.search_availabilities_by_username(username, origin);
 
           return availabilities.map((avail: any) => {
             const converted = convertBigIntToNumber(avail);
@@ -237,9 +239,9 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // search_availabilities_by_principal - Deserialize array
       if (prop === "search_availabilities_by_principal") {
-        return async (principal: any) => {
+        return async (principal: any, origin: [] | [string]) => {
           const availabilities =
-            await targetAny.search_availabilities_by_principal(principal);
+            await targetAny.search_availabilities_by_principal(principal, origin);
 
           return availabilities.map((avail: any) => {
             const converted = convertBigIntToNumber(avail);
@@ -253,8 +255,8 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // search_by_emails - Deserialize nested arrays
       if (prop === "search_by_emails") {
-        return async (emails: string[]) => {
-          const results = await targetAny.search_by_emails(emails);
+        return async (emails: string[], origin: [] | [string]) => {
+          const results = await targetAny.search_by_emails(emails, origin);
 
           return results.map((availabilities: any[]) =>
             availabilities.map((avail: any) => {
@@ -274,8 +276,8 @@ export function createBackendCaster(actor: any): ActorSubclass<_SERVICE> {
 
       // search_by_usernames - Deserialize nested arrays
       if (prop === "search_by_usernames") {
-        return async (usernames: string[]) => {
-          const results = await targetAny.search_by_usernames(usernames);
+        return async (usernames: string[], origin: [] | [string]) => {
+          const results = await targetAny.search_by_usernames(usernames, origin);
 
           return results.map((availabilities: any[]) =>
             availabilities.map((avail: any) => {
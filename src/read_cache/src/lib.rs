@@ -0,0 +1,142 @@
+//! Read cache for public availability pages. The backend pushes the public
+//! view of each availability here whenever it changes, already encoded as
+//! the reply of its own `get_availability`, and this canister replays those
+//! bytes verbatim. Busy booking pages then read from here and never queue
+//! behind the backend's update traffic, and the frontend can call it with
+//! the backend's interface. Only the backend (the writer) can change entries.
+
+use candid::{CandidType, Principal};
+use serde::Deserialize;
+use std::cell::RefCell;
+use ic_cdk::api::time;
+use ic_cdk::{init, query, update};
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
+
+type Memory = VirtualMemory<DefaultMemoryImpl>;
+
+/// Largest encoded view accepted per entry
+const MAX_ENTRY_BYTES: usize = 512 * 1024;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(CandidType, Deserialize)]
+pub struct CacheEntry {
+    pub id: String,
+    pub reply: Vec<u8>, // Candid-encoded `Result<Availability, String>`
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CacheStats {
+    pub writer: Option<Principal>,
+    pub entries: u64,
+    pub last_applied_at: u64,
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
+        RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
+
+    // Backend canister allowed to push entries (anonymous = not set yet)
+    static WRITER: RefCell<StableCell<Principal, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))),
+            Principal::anonymous(),
+        ).expect("Failed to init writer")
+    );
+
+    // Availability ID -> encoded reply
+    static ENTRIES: RefCell<StableBTreeMap<String, Vec<u8>, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(1)))
+        )
+    );
+
+    static LAST_APPLIED_AT: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(2))),
+            0,
+        ).expect("Failed to init last applied time")
+    );
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn writer() -> Option<Principal> {
+    let writer = WRITER.with(|w| *w.borrow().get());
+    (writer != Principal::anonymous()).then_some(writer)
+}
+
+fn set_writer_to(writer: Principal) {
+    WRITER.with(|w| w.borrow_mut().set(writer)).expect("Failed to store writer");
+}
+
+// ============================================================================
+// Endpoints
+// ============================================================================
+
+#[init]
+fn init(writer: Option<Principal>) {
+    if let Some(writer) = writer {
+        set_writer_to(writer);
+    }
+}
+
+/// Same reply as the backend's `get_availability` for a signed-out viewer.
+/// The backend only hands this canister out on default-tenant origins, so
+/// `origin` is accepted for the shared interface and not needed.
+#[query(manual_reply = true)]
+fn get_availability(id: String, _origin: Option<String>) {
+    match ENTRIES.with(|e| e.borrow().get(&id)) {
+        Some(reply) => ic_cdk::api::call::reply_raw(&reply),
+        None => ic_cdk::api::call::reply((Err::<candid::Empty, String>("Availability not found".to_string()),)),
+    }
+}
+
+/// Store and drop entries; only the backend calls this
+#[update]
+fn apply(puts: Vec<CacheEntry>, removes: Vec<String>) -> Result<(), String> {
+    if writer() != Some(ic_cdk::caller()) {
+        return Err("Unauthorized: only the backend can write to the cache".to_string());
+    }
+    if let Some(entry) = puts.iter().find(|entry| entry.reply.len() > MAX_ENTRY_BYTES) {
+        return Err(format!("Entry {} exceeds {} bytes", entry.id, MAX_ENTRY_BYTES));
+    }
+    ENTRIES.with(|e| {
+        let mut map = e.borrow_mut();
+        for entry in puts {
+            map.insert(entry.id, entry.reply);
+        }
+        for id in removes {
+            map.remove(&id);
+        }
+    });
+    LAST_APPLIED_AT.with(|l| l.borrow_mut().set(time())).expect("Failed to store last applied time");
+    Ok(())
+}
+
+#[update]
+fn set_writer(writer: Principal) -> Result<(), String> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err("Only controllers can set the writer".to_string());
+    }
+    set_writer_to(writer);
+    Ok(())
+}
+
+#[query]
+fn get_cache_stats() -> CacheStats {
+    CacheStats {
+        writer: writer(),
+        entries: ENTRIES.with(|e| e.borrow().len()),
+        last_applied_at: LAST_APPLIED_AT.with(|l| *l.borrow().get()),
+    }
+}
@@ -0,0 +1,110 @@
+import { describe, test, expect, beforeEach } from "vitest";
+import {
+  createTestUser,
+  createTimeSlot,
+  createAvailabilityRequest,
+} from "../utils";
+
+describe("Availability Busy Privacy", () => {
+  beforeEach(async () => {
+    await globalThis.testPic.resetTime();
+  });
+
+  // 10:07-10:41 UTC on 2024-01-15, deliberately off the 30-minute grid
+  const busyBlock = { start_time: 1705313220n, end_time: 1705315260n };
+
+  async function createWithBusyTimes(ownerName: string) {
+    const { identity } = await createTestUser(ownerName);
+    globalThis.testActor.setIdentity(identity);
+
+    const createResult = await globalThis.testActor.create_availability(
+      createAvailabilityRequest("Private Hours", "Description", [
+        createTimeSlot(1, 540, 1020),
+      ]),
+    );
+    expect("Ok" in createResult).toBe(true);
+    if (!("Ok" in createResult)) throw new Error("create failed");
+
+    const id = createResult.Ok.id;
+    await globalThis.testActor.update_availability_busy_times(id, [busyBlock]);
+    return id;
+  }
+
+  test("should expose exact busy times by default", async () => {
+    const id = await createWithBusyTimes("privacy-default");
+
+    const { identity: guest } = await createTestUser("privacy-guest-1");
+    globalThis.testActor.setIdentity(guest);
+
+    const result = await globalThis.testActor.get_availability(id);
+    expect("Ok" in result).toBe(true);
+    if ("Ok" in result) {
+      expect(result.Ok.busy_times).toEqual([[busyBlock]]);
+    }
+  });
+
+  test("should quantize busy times for non-owners", async () => {
+    const id = await createWithBusyTimes("privacy-quantized");
+    const privacyResult =
+      await globalThis.testActor.set_availability_busy_privacy(id, {
+        Quantized: null,
+      });
+    expect("Ok" in privacyResult).toBe(true);
+
+    // Owner still sees the raw block
+    const ownerView = await globalThis.testActor.get_availability(id);
+    if ("Ok" in ownerView) {
+      expect(ownerView.Ok.busy_times).toEqual([[busyBlock]]);
+    }
+
+    const { identity: guest } = await createTestUser("privacy-guest-2");
+    globalThis.testActor.setIdentity(guest);
+
+    const guestView = await globalThis.testActor.get_availability(id);
+    expect("Ok" in guestView).toBe(true);
+    if ("Ok" in guestView) {
+      // Widened to 10:00-11:00
+      expect(guestView.Ok.busy_times).toEqual([
+        [{ start_time: 1705312800n, end_time: 1705316400n }],
+      ]);
+    }
+  });
+
+  test("should hide busy times entirely in free-slots-only mode", async () => {
+    const id = await createWithBusyTimes("privacy-free-only");
+    await globalThis.testActor.set_availability_busy_privacy(id, {
+      FreeSlotsOnly: null,
+    });
+
+    const { identity: guest } = await createTestUser("privacy-guest-3");
+    globalThis.testActor.setIdentity(guest);
+
+    const guestView = await globalThis.testActor.get_availability(id);
+    expect("Ok" in guestView).toBe(true);
+    if ("Ok" in guestView) {
+      expect(guestView.Ok.busy_times).toEqual([]);
+
+      const searchResult =
+        await globalThis.testActor.search_availabilities_by_principal(
+          guestView.Ok.owner,
+        );
+      expect(searchResult[0].busy_times).toEqual([]);
+    }
+  });
+
+  test("should reject privacy change from non-owner", async () => {
+    const id = await createWithBusyTimes("privacy-owner");
+
+    const { identity: attacker } = await createTestUser("privacy-attacker");
+    globalThis.testActor.setIdentity(attacker);
+
+    const result = await globalThis.testActor.set_availability_busy_privacy(
+      id,
+      { Exact: null },
+    );
+    expect("Err" in result).toBe(true);
+    if ("Err" in result) {
+      expect(result.Err).toContain("Only the owner can change busy privacy");
+    }
+  });
+});
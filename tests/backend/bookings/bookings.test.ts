@@ -0,0 +1,194 @@
+import { describe, test, expect, beforeEach } from "vitest";
+import {
+  createTestUser,
+  createTimeSlot,
+  createAvailabilityRequest,
+} from "../utils";
+
+describe("Bookings", () => {
+  beforeEach(async () => {
+    await globalThis.testPic.resetTime();
+  });
+
+  // Next Monday 10:00 UTC (Unix seconds), inside the 09:00-17:00 slot
+  async function nextMondayAt10() {
+    const now = new Date(await globalThis.testPic.getTime());
+    const daysAhead = ((8 - now.getUTCDay()) % 7) || 7;
+    const monday = Date.UTC(
+      now.getUTCFullYear(),
+      now.getUTCMonth(),
+      now.getUTCDate() + daysAhead,
+      10,
+    );
+    return BigInt(monday / 1000);
+  }
+
+  async function createOwnerAvailability(ownerName: string) {
+    const { identity } = await createTestUser(ownerName);
+    globalThis.testActor.setIdentity(identity);
+
+    const createResult = await globalThis.testActor.create_availability(
+      createAvailabilityRequest("Office Hours", "Description", [
+        createTimeSlot(1, 540, 1020),
+      ]),
+    );
+    if (!("Ok" in createResult)) throw new Error("create failed");
+    return createResult.Ok.id;
+  }
+
+  function bookingRequest(availabilityId: string, start: bigint) {
+    return {
+      availability_id: availabilityId,
+      start_time: start,
+      end_time: start + 1800n,
+      guest_name: "Guest",
+      guest_email: [] as [] | [string],
+    };
+  }
+
+  test("should book a free slot and reject a double booking", async () => {
+    const id = await createOwnerAvailability("booking-owner-1");
+    const start = await nextMondayAt10();
+
+    const { identity: guest } = await createTestUser("booking-guest-1");
+    globalThis.testActor.setIdentity(guest);
+
+    const first = await globalThis.testActor.create_booking(
+      bookingRequest(id, start),
+    );
+    expect("Ok" in first).toBe(true);
+    if ("Ok" in first) {
+      expect(first.Ok.status).toEqual({ Confirmed: null });
+    }
+
+    const second = await globalThis.testActor.create_booking(
+      bookingRequest(id, start + 900n),
+    );
+    expect("Err" in second).toBe(true);
+    if ("Err" in second) {
+      expect(second.Err).toContain("already booked");
+    }
+  });
+
+  test("should reject a slot outside the availability", async () => {
+    const id = await createOwnerAvailability("booking-owner-2");
+    // 20:00 UTC is after the 17:00 end of the slot
+    const start = (await nextMondayAt10()) + 10n * 3600n;
+
+    const { identity: guest } = await createTestUser("booking-guest-2");
+    globalThis.testActor.setIdentity(guest);
+
+    const result = await globalThis.testActor.create_booking(
+      bookingRequest(id, start),
+    );
+    expect("Err" in result).toBe(true);
+    if ("Err" in result) {
+      expect(result.Err).toContain("not available");
+    }
+  });
+
+  test("should only show a booking to its participants", async () => {
+    const id = await createOwnerAvailability("booking-owner-3");
+    const start = await nextMondayAt10();
+
+    const { identity: guest } = await createTestUser("booking-guest-3");
+    globalThis.testActor.setIdentity(guest);
+    const created = await globalThis.testActor.create_booking(
+      bookingRequest(id, start),
+    );
+    if (!("Ok" in created)) throw new Error("booking failed");
+
+    const { identity: stranger } = await createTestUser("booking-stranger");
+    globalThis.testActor.setIdentity(stranger);
+    const result = await globalThis.testActor.get_booking(created.Ok.id);
+    expect("Err" in result).toBe(true);
+  });
+
+  test("should require Pro to mint a paid session link", async () => {
+    const id = await createOwnerAvailability("session-link-owner");
+
+    const result = await globalThis.testActor.create_session_link({
+      availability_id: id,
+      title: "Consultation",
+      price: 100_000_000n,
+      duration_minutes: 60,
+      expires_at: [],
+    });
+    expect("Err" in result).toBe(true);
+    if ("Err" in result) {
+      expect(result.Err).toContain("FeatureNotAvailable");
+    }
+  });
+
+  test("should show the cancellation policy and let the guest cancel", async () => {
+    const id = await createOwnerAvailability("cancel-owner");
+    const policy = {
+      free_until_hours: 24,
+      partial_until_hours: 2,
+      partial_refund_percent: 50,
+    };
+    const policyResult =
+      await globalThis.testActor.set_availability_cancellation_policy(id, [
+        policy,
+      ]);
+    expect("Ok" in policyResult).toBe(true);
+
+    const { identity: guest } = await createTestUser("cancel-guest");
+    globalThis.testActor.setIdentity(guest);
+
+    const availability = await globalThis.testActor.get_availability(id);
+    if ("Ok" in availability) {
+      expect(availability.Ok.cancellation_policy).toEqual([policy]);
+    }
+
+    const created = await globalThis.testActor.create_booking(
+      bookingRequest(id, await nextMondayAt10()),
+    );
+    if (!("Ok" in created)) throw new Error("booking failed");
+
+    const cancelled = await globalThis.testActor.cancel_booking(created.Ok.id);
+    expect("Ok" in cancelled).toBe(true);
+    if ("Ok" in cancelled) {
+      expect(cancelled.Ok.status).toEqual({ Cancelled: null });
+      expect(cancelled.Ok.refund).toEqual([]);
+    }
+
+    const again = await globalThis.testActor.cancel_booking(created.Ok.id);
+    expect("Err" in again).toBe(true);
+  });
+
+  test("should allow exactly one booking through a one-time link", async () => {
+    const id = await createOwnerAvailability("one-time-owner");
+    const created = await globalThis.testActor.create_one_time_link(id);
+    if (!("Ok" in created)) throw new Error("link creation failed");
+    const token = created.Ok.token;
+    const start = await nextMondayAt10();
+
+    const { identity: guest } = await createTestUser("one-time-guest");
+    globalThis.testActor.setIdentity(guest);
+
+    const linkRequest = (slotStart: bigint) => ({
+      token,
+      start_time: slotStart,
+      end_time: slotStart + 1800n,
+      guest_name: "Guest",
+      guest_email: [] as [] | [string],
+    });
+
+    const first = await globalThis.testActor.book_with_one_time_link(
+      linkRequest(start),
+    );
+    expect("Ok" in first).toBe(true);
+
+    const second = await globalThis.testActor.book_with_one_time_link(
+      linkRequest(start + 3600n),
+    );
+    expect("Err" in second).toBe(true);
+    if ("Err" in second) {
+      expect(second.Err).toContain("already been used");
+    }
+
+    const lookup = await globalThis.testActor.get_one_time_link(token);
+    expect("Err" in lookup).toBe(true);
+  });
+});